@@ -0,0 +1,219 @@
+//! Likely breaking changes to a public API
+//!
+//! Release tools such as semantic-release bump the major version for commits
+//! marked `type!:` or carrying a `BREAKING CHANGE:` footer, so a message that
+//! misses a removed function ships it as a minor release. The removed and
+//! added lines of a patch are read for public Rust items (`pub fn`, `pub
+//! struct`, ...) and exported TypeScript and JavaScript declarations (`export
+//! function`, `export class`, ...): one removed without being added back, in
+//! any file, was removed or made private, and a function declared again in
+//! the same file with a different first line changed its signature.
+//!
+//! Rust items count only in library crates: binaries (`main.rs`, `src/bin/`)
+//! have no public API, and neither do private or `#[cfg(test)]` modules, so
+//! declarations indented inside one are skipped.
+//!
+//! Only the declaration line is read, so a change to a parameter on a line of
+//! its own goes unnoticed; test files are skipped.
+
+use crate::git::patch::{parse_patch, FilePatch};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// What happened to a public declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakingKind {
+    /// Removed, or no longer public
+    Removed,
+    /// Declared again with a different first line
+    SignatureChanged { before: String, after: String },
+}
+
+/// A public declaration the patch removes or changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakingChange {
+    pub path: PathBuf,
+    /// The kind of item and its name, e.g. `fn parse` or `class Client`
+    pub item: String,
+    pub kind: BreakingKind,
+}
+
+impl BreakingChange {
+    /// One line for the prompt, e.g. `src/lib.rs: fn parse was removed or made private`
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            BreakingKind::Removed => format!("{}: {} was removed or made private", self.path.display(), self.item),
+            BreakingKind::SignatureChanged { before, after } => {
+                format!("{}: {} changed from `{}` to `{}`", self.path.display(), self.item, before, after)
+            }
+        }
+    }
+}
+
+/// Languages whose public declarations are recognized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    /// TypeScript and JavaScript modules
+    Ecma,
+}
+
+impl Language {
+    fn of(path: &Path) -> Option<Language> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "ts" | "tsx" | "mts" | "cts" | "js" | "jsx" | "mjs" | "cjs" => Some(Language::Ecma),
+            _ => None,
+        }
+    }
+    
+    fn declaration(&self) -> &'static Regex {
+        static RUST: OnceLock<Regex> = OnceLock::new();
+        static ECMA: OnceLock<Regex> = OnceLock::new();
+        match self {
+            Language::Rust => RUST.get_or_init(|| Regex::new(
+                r#"^\s*pub\s+(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*(fn|struct|enum|union|trait|type|const|static|mod)\s+([A-Za-z_][A-Za-z0-9_]*)"#
+            ).unwrap()),
+            Language::Ecma => ECMA.get_or_init(|| Regex::new(
+                r"^\s*export\s+(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?(?:async\s+)?(function\*?|class|interface|type|enum|const|let|var|namespace)\s+([A-Za-z_$][A-Za-z0-9_$]*)"
+            ).unwrap()),
+        }
+    }
+}
+
+/// A public declaration on a removed or added line
+#[derive(Debug, Clone)]
+struct Declaration<'a> {
+    path: &'a Path,
+    language: Language,
+    kind: String,
+    name: String,
+    /// The line with its whitespace collapsed and without an opening brace
+    signature: String,
+}
+
+impl Declaration<'_> {
+    fn is_function(&self) -> bool {
+        self.kind.starts_with("fn") || self.kind.starts_with("function")
+    }
+    
+    fn item(&self) -> String {
+        format!("{} {}", self.kind.trim_end_matches('*'), self.name)
+    }
+    
+    fn same_item(&self, other: &Declaration) -> bool {
+        self.language == other.language && self.name == other.name && self.is_function() == other.is_function()
+    }
+}
+
+/// Public declarations removed or changed by `patch`, a unified diff
+/// 
+/// `is_library` says whether a Rust file belongs to a library crate.
+pub fn detect(patch: &str, is_library: impl Fn(&Path) -> bool) -> Vec<BreakingChange> {
+    let file_patches = parse_patch(patch);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for file_patch in file_patches.iter().filter(|file_patch| !is_test_path(&file_patch.file_path)) {
+        let Some(language) = Language::of(&file_patch.file_path) else {
+            continue;
+        };
+        if language == Language::Rust && (is_binary_path(&file_patch.file_path) || !is_library(&file_patch.file_path)) {
+            continue;
+        }
+        removed.extend(declarations(file_patch, language, '-'));
+        added.extend(declarations(file_patch, language, '+'));
+    }
+    
+    let mut changes = Vec::new();
+    for declaration in &removed {
+        let same_file = |other: &&Declaration| other.path == declaration.path && other.same_item(declaration);
+        if added.iter().filter(same_file).any(|other| other.signature == declaration.signature) {
+            continue;
+        }
+        let kind = match added.iter().find(same_file) {
+            Some(other) if declaration.is_function() => BreakingKind::SignatureChanged {
+                before: declaration.signature.clone(),
+                after: other.signature.clone(),
+            },
+            Some(_) => continue,
+            None if added.iter().any(|other| other.same_item(declaration)) => continue,
+            None => BreakingKind::Removed,
+        };
+        let change = BreakingChange { path: declaration.path.to_path_buf(), item: declaration.item(), kind };
+        if !changes.contains(&change) {
+            changes.push(change);
+        }
+    }
+    changes
+}
+
+/// Declarations on the lines of `file_patch` starting with `sign`
+fn declarations(file_patch: &FilePatch, language: Language, sign: char) -> Vec<Declaration<'_>> {
+    // A renamed file's removed lines are reported under its new path too
+    let path = file_patch.file_path.as_path();
+    // The last unindented line on this side, starting with the hunk header's section
+    let mut enclosing = String::new();
+    let mut declarations = Vec::new();
+    for line in &file_patch.lines {
+        if let Some(header) = line.strip_prefix("@@") {
+            enclosing = header.split_once("@@").map(|(_, section)| section.trim().to_string()).unwrap_or_default();
+            continue;
+        }
+        let mut chars = line.chars();
+        let marker = chars.next();
+        let text = chars.as_str();
+        if marker != Some(sign) && marker != Some(' ') {
+            continue;
+        }
+        
+        let is_top_level = !text.starts_with(char::is_whitespace) && !text.trim().is_empty();
+        let captures = language.declaration().captures(text).filter(|_| marker == Some(sign));
+        if let Some(captures) = captures {
+            let hidden = language == Language::Rust && !is_top_level && is_private_module(&enclosing);
+            if !hidden {
+                declarations.push(Declaration {
+                    path,
+                    language,
+                    kind: captures[1].to_string(),
+                    name: captures[2].to_string(),
+                    signature: signature(text),
+                });
+            }
+        }
+        if is_top_level {
+            enclosing = text.trim().to_string();
+        }
+    }
+    declarations
+}
+
+/// Whether items inside the block opened by `line` are invisible to other crates:
+/// a private, restricted or `#[cfg(test)]` module
+fn is_private_module(line: &str) -> bool {
+    static PRIVATE_MODULE: OnceLock<Regex> = OnceLock::new();
+    PRIVATE_MODULE.get_or_init(|| Regex::new(
+        r"^(?:#\[cfg\(test\)\]|(?:pub\([^)]*\)\s+)?mod\s+[A-Za-z_][A-Za-z0-9_]*\s*\{)"
+    ).unwrap()).is_match(line)
+}
+
+/// Binaries, build scripts and examples have no API for other crates
+fn is_binary_path(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    matches!(name, "main.rs" | "build.rs")
+        || path.components().any(|component| matches!(component.as_os_str().to_str(), Some("bin" | "examples" | "benches")))
+}
+
+fn signature(line: &str) -> String {
+    let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    line.trim_end_matches('{').trim_end().to_string()
+}
+
+/// Tests may remove whatever they like
+fn is_test_path(path: &Path) -> bool {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+    path.components().any(|component| matches!(component.as_os_str().to_str(), Some("tests" | "test" | "__tests__")))
+        || name.ends_with("_test.rs")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+}
@@ -0,0 +1,283 @@
+//! Packages and modules of the repository, for conventional commit scopes
+//!
+//! Left to guess, a model makes up scopes from file names. The manifests git
+//! tracks say what the real units are: Rust crates (`Cargo.toml`), npm
+//! packages (`package.json`) and Python projects (`pyproject.toml`) and
+//! packages (`__init__.py`). A file in a package below the repository root is
+//! scoped by the package's name; in a repository that is one package, by the
+//! module it is in, e.g. `parser` for `src/parser/mod.rs`. Workspaces (see
+//! [`crate::analysis::workspace`]) name the packages a change affects.
+
+use crate::analysis::workspace::{self, Workspace};
+use crate::git::project::parse_manifest;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Manifests and markers that declare packages and workspaces, as git pathspecs
+pub const MANIFEST_PATHSPECS: &[&str] = &[
+    ":(glob)**/Cargo.toml",
+    ":(glob)**/package.json",
+    ":(glob)**/pyproject.toml",
+    ":(glob)**/__init__.py",
+    ":(glob)**/pnpm-workspace.yaml",
+    ":(glob)**/nx.json",
+    ":(glob)**/project.json",
+];
+
+/// File names that are a module's entry point rather than a module of their own
+const ENTRY_FILES: &[&str] = &["lib", "main", "mod", "index", "__init__", "__main__"];
+
+/// The packaging ecosystem a package belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Ecosystem {
+    Rust,
+    Npm,
+    Python,
+}
+
+impl Ecosystem {
+    /// What its packages are called, e.g. `Rust crate`
+    pub fn package_label(&self) -> &'static str {
+        match self {
+            Ecosystem::Rust => "Rust crate",
+            Ecosystem::Npm => "npm package",
+            Ecosystem::Python => "Python package",
+        }
+    }
+    
+    /// The ecosystem whose sources `path` is, if any
+    fn of_source(path: &Path) -> Option<Ecosystem> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Ecosystem::Rust),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => Some(Ecosystem::Npm),
+            "py" | "pyi" => Some(Ecosystem::Python),
+            _ => None,
+        }
+    }
+    
+    /// Directories holding the sources below a package's root
+    fn source_dirs(&self) -> &'static [&'static str] {
+        match self {
+            Ecosystem::Rust => &["src"],
+            Ecosystem::Npm => &["src", "lib"],
+            Ecosystem::Python => &["src"],
+        }
+    }
+}
+
+/// A package declared by a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    /// Directory of the manifest, relative to the repository root
+    pub root: PathBuf,
+    /// A Rust crate with a `src/lib.rs`, which other crates can use
+    pub library: bool,
+}
+
+/// The scope a changed file suggests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileScope {
+    pub ecosystem: Ecosystem,
+    /// Name of the package the file is in
+    pub package: String,
+    pub scope: String,
+}
+
+/// A scope for the prompt and how many staged files suggest it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeSuggestion {
+    pub scope: String,
+    pub ecosystem: Ecosystem,
+    pub package: String,
+    pub files: usize,
+}
+
+impl ScopeSuggestion {
+    /// e.g. `parser (Rust crate acme-parser, 3 files)`
+    pub fn describe(&self) -> String {
+        let files = if self.files == 1 { "1 file".to_string() } else { format!("{} files", self.files) };
+        format!("{} ({} {}, {})", self.scope, self.ecosystem.package_label(), self.package, files)
+    }
+}
+
+/// The packages of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectLayout {
+    packages: Vec<Package>,
+    /// Directories with an `__init__.py`, relative to the repository root
+    python_packages: BTreeSet<PathBuf>,
+    workspaces: Vec<Workspace>,
+}
+
+impl ProjectLayout {
+    /// Read the packages from `manifests`, paths relative to `root` matching
+    /// [`MANIFEST_PATHSPECS`]
+    /// 
+    /// Manifests without a package name, such as a workspace's root
+    /// `Cargo.toml`, and anything under `node_modules` are skipped.
+    pub fn detect(root: &Path, manifests: &[PathBuf]) -> Self {
+        let mut layout = ProjectLayout { workspaces: Workspace::detect(root, manifests), ..ProjectLayout::default() };
+        for manifest in manifests {
+            if workspace::is_vendored(manifest) {
+                continue;
+            }
+            let Some(file_name) = manifest.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let dir = manifest.parent().unwrap_or(Path::new("")).to_path_buf();
+            let ecosystem = match file_name {
+                "__init__.py" => {
+                    layout.python_packages.insert(dir);
+                    continue;
+                }
+                "Cargo.toml" => Ecosystem::Rust,
+                "package.json" => Ecosystem::Npm,
+                "pyproject.toml" => Ecosystem::Python,
+                _ => continue,
+            };
+            let name = fs::read_to_string(root.join(manifest))
+                .ok()
+                .and_then(|content| parse_manifest(file_name, &content))
+                .and_then(|(name, _)| name);
+            if let Some(name) = name {
+                let library = ecosystem == Ecosystem::Rust && root.join(&dir).join("src").join("lib.rs").is_file();
+                layout.packages.push(Package { ecosystem, name, root: dir, library });
+            }
+        }
+        layout
+    }
+    
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+    
+    pub fn workspaces(&self) -> &[Workspace] {
+        &self.workspaces
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.python_packages.is_empty() && self.workspaces.is_empty()
+    }
+    
+    /// Whether `path` (relative to the repository root) is in a Rust library crate
+    pub fn is_rust_library(&self, path: &Path) -> bool {
+        self.packages.iter()
+            .filter(|package| package.ecosystem == Ecosystem::Rust && path.starts_with(&package.root))
+            .max_by_key(|package| package.root.components().count())
+            .is_some_and(|package| package.library)
+    }
+    
+    /// Names of the workspace members `paths` are in, sorted, without an npm `@org/` prefix
+    /// 
+    /// Each path counts for the innermost member of any workspace.
+    pub fn affected_packages<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) -> Vec<String> {
+        let affected: BTreeSet<&str> = paths.into_iter()
+            .filter_map(|path| {
+                self.workspaces.iter()
+                    .filter_map(|workspace| workspace.member_of(path))
+                    .max_by_key(|member| member.root.components().count())
+            })
+            .map(|member| unscoped(&member.name))
+            .collect();
+        affected.into_iter().map(str::to_string).collect()
+    }
+    
+    /// The scope `path` (relative to the repository root) suggests, if it is in a package
+    /// 
+    /// Sources go to the innermost package of their own ecosystem, other files
+    /// to the innermost package of any.
+    pub fn classify(&self, path: &Path) -> Option<FileScope> {
+        let ecosystem = Ecosystem::of_source(path);
+        if ecosystem == Some(Ecosystem::Python) || self.python_package_of(path).is_some() {
+            if let Some(scope) = self.classify_python(path) {
+                return Some(scope);
+            }
+        }
+        
+        let package = self.packages.iter()
+            .filter(|package| ecosystem.is_none_or(|ecosystem| ecosystem == package.ecosystem))
+            .filter(|package| path.starts_with(&package.root))
+            .max_by_key(|package| package.root.components().count())?;
+        let scope = if package.root.as_os_str().is_empty() {
+            module_under(path, package.ecosystem.source_dirs())?
+        } else {
+            unscoped(&package.name).to_string()
+        };
+        Some(FileScope { ecosystem: package.ecosystem, package: package.name.clone(), scope })
+    }
+    
+    /// Scopes suggested by `paths`, the most common first
+    pub fn suggest<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) -> Vec<ScopeSuggestion> {
+        let mut suggestions: Vec<ScopeSuggestion> = Vec::new();
+        for file_scope in paths.into_iter().filter_map(|path| self.classify(path)) {
+            match suggestions.iter_mut().find(|suggestion| suggestion.scope == file_scope.scope) {
+                Some(suggestion) => suggestion.files += 1,
+                None => suggestions.push(ScopeSuggestion {
+                    scope: file_scope.scope,
+                    ecosystem: file_scope.ecosystem,
+                    package: file_scope.package,
+                    files: 1,
+                }),
+            }
+        }
+        suggestions.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.scope.cmp(&b.scope)));
+        suggestions
+    }
+    
+    /// The outermost directory with an `__init__.py` that `path` is in
+    fn python_package_of<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.ancestors()
+            .skip(1)
+            .filter(|dir| self.python_packages.contains(*dir))
+            .last()
+    }
+    
+    /// A Python file is in its top-level import package; in a repository
+    /// that is one Python project, it is scoped by the module within that
+    fn classify_python(&self, path: &Path) -> Option<FileScope> {
+        let top = self.python_package_of(path)?;
+        let top_name = top.file_name()?.to_str()?.to_string();
+        let project = self.packages.iter()
+            .filter(|package| package.ecosystem == Ecosystem::Python && top.starts_with(&package.root))
+            .max_by_key(|package| package.root.components().count());
+        
+        let scope = match project {
+            Some(project) if project.root.as_os_str().is_empty() => {
+                module_under(path.strip_prefix(top).ok()?, &[]).unwrap_or_else(|| top_name.clone())
+            }
+            _ => top_name.clone(),
+        };
+        Some(FileScope {
+            ecosystem: Ecosystem::Python,
+            package: project.map_or(top_name, |project| project.name.clone()),
+            scope,
+        })
+    }
+}
+
+/// The first directory or module of `path` inside one of `source_dirs`
+/// (or at the top, without any), e.g. `parser` for `src/parser/lexer.rs`
+fn module_under(path: &Path, source_dirs: &[&str]) -> Option<String> {
+    let mut components = path.components().filter_map(|component| match component {
+        Component::Normal(name) => name.to_str(),
+        _ => None,
+    });
+    if !source_dirs.is_empty() {
+        let first = components.next()?;
+        if !source_dirs.contains(&first) {
+            return None;
+        }
+    }
+    let module = components.next()?;
+    let is_file = components.next().is_none();
+    let module = if is_file { Path::new(module).file_stem()?.to_str()? } else { module };
+    (!ENTRY_FILES.contains(&module)).then(|| module.to_string())
+}
+
+/// `ui` for the npm package `@acme/ui`
+fn unscoped(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
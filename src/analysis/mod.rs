@@ -0,0 +1,8 @@
+//! Analysis of staged changes
+//!
+//! Lightweight readings of a patch and of the repository's layout that point
+//! out what a model easily misses or makes up.
+
+pub mod breaking;
+pub mod language;
+pub mod workspace;
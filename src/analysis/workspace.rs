@@ -0,0 +1,243 @@
+//! Workspaces of a monorepo and the packages a change affects
+//!
+//! A Cargo workspace (`[workspace] members`), npm and Yarn workspaces
+//! (`"workspaces"` in `package.json`), a pnpm workspace
+//! (`pnpm-workspace.yaml`) and an Nx workspace (`nx.json`, with a
+//! `project.json` per project) each say which directories hold the packages
+//! of one repository. A file belongs to the innermost member it is in.
+
+use crate::git::project::parse_manifest;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The tool a workspace is declared for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceKind {
+    Cargo,
+    /// npm and Yarn, which share the `workspaces` field
+    Npm,
+    Pnpm,
+    Nx,
+}
+
+impl WorkspaceKind {
+    /// The manifest that declares the workspace
+    fn of_manifest(file_name: &str) -> Option<WorkspaceKind> {
+        match file_name {
+            "Cargo.toml" => Some(WorkspaceKind::Cargo),
+            "package.json" => Some(WorkspaceKind::Npm),
+            "pnpm-workspace.yaml" => Some(WorkspaceKind::Pnpm),
+            "nx.json" => Some(WorkspaceKind::Nx),
+            _ => None,
+        }
+    }
+    
+    /// The manifest each member has
+    fn member_manifest(&self) -> &'static str {
+        match self {
+            WorkspaceKind::Cargo => "Cargo.toml",
+            WorkspaceKind::Npm | WorkspaceKind::Pnpm => "package.json",
+            WorkspaceKind::Nx => "project.json",
+        }
+    }
+}
+
+/// A package of a workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Directory of its manifest, relative to the repository root
+    pub root: PathBuf,
+}
+
+/// Packages managed together by one tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub kind: WorkspaceKind,
+    /// Directory of the workspace manifest, relative to the repository root
+    pub root: PathBuf,
+    pub members: Vec<Member>,
+}
+
+impl Workspace {
+    /// Read the workspaces from `manifests`, paths relative to `root`
+    /// 
+    /// Members are the directories of the tracked member manifests matching
+    /// the workspace's globs. Workspaces without any member are left out, as
+    /// is anything under `node_modules`.
+    pub fn detect(root: &Path, manifests: &[PathBuf]) -> Vec<Workspace> {
+        let read = |manifest: &Path| fs::read_to_string(root.join(manifest)).ok();
+        let manifests: Vec<&PathBuf> = manifests.iter().filter(|manifest| !is_vendored(manifest)).collect();
+        
+        let mut workspaces = Vec::new();
+        for manifest in &manifests {
+            let Some(kind) = file_name(manifest).and_then(WorkspaceKind::of_manifest) else {
+                continue;
+            };
+            let globs = match kind {
+                WorkspaceKind::Cargo => read(manifest).and_then(|content| cargo_globs(&content)),
+                WorkspaceKind::Npm => read(manifest).and_then(|content| npm_globs(&content)),
+                WorkspaceKind::Pnpm => read(manifest).map(|content| pnpm_globs(&content)),
+                WorkspaceKind::Nx => Some(MemberGlobs::new(["**"])),
+            };
+            let Some(globs) = globs else {
+                continue;
+            };
+            
+            let dir = parent(manifest);
+            let members: Vec<Member> = manifests.iter()
+                .filter(|member| file_name(member) == Some(kind.member_manifest()))
+                .filter_map(|member| {
+                    let member_dir = parent(member);
+                    let relative = member_dir.strip_prefix(&dir).ok()?;
+                    // A Cargo workspace's root package is one of its members
+                    let is_root_package = kind == WorkspaceKind::Cargo && relative.as_os_str().is_empty();
+                    if !is_root_package && !globs.matches(relative) {
+                        return None;
+                    }
+                    let name = member_name(kind, &read(member)?, &member_dir)?;
+                    Some(Member { name, root: member_dir })
+                })
+                .collect();
+            if !members.is_empty() {
+                workspaces.push(Workspace { kind, root: dir, members });
+            }
+        }
+        workspaces
+    }
+    
+    /// The innermost member `path` (relative to the repository root) is in
+    pub fn member_of(&self, path: &Path) -> Option<&Member> {
+        self.members.iter()
+            .filter(|member| path.starts_with(&member.root))
+            .max_by_key(|member| member.root.components().count())
+    }
+}
+
+/// Included and excluded member globs, relative to the workspace root
+struct MemberGlobs {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl MemberGlobs {
+    /// Globs as npm and pnpm write them, excluding with a leading `!`
+    fn new<'a>(globs: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut member_globs = MemberGlobs { include: Vec::new(), exclude: Vec::new() };
+        for glob in globs {
+            match glob.trim().strip_prefix('!') {
+                Some(excluded) => member_globs.exclude.extend(glob_regex(excluded)),
+                None => member_globs.include.extend(glob_regex(glob)),
+            }
+        }
+        member_globs
+    }
+    
+    fn matches(&self, dir: &Path) -> bool {
+        let dir = dir.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        self.include.iter().any(|glob| glob.is_match(&dir)) && !self.exclude.iter().any(|glob| glob.is_match(&dir))
+    }
+}
+
+/// `[workspace]` `members` and `exclude` of a `Cargo.toml`
+fn cargo_globs(content: &str) -> Option<MemberGlobs> {
+    let toml: toml::Value = toml::from_str(content).ok()?;
+    let workspace = toml.get("workspace")?;
+    let list = |key: &str| -> Vec<String> {
+        workspace.get(key)
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect()
+    };
+    let excluded: Vec<String> = list("exclude").iter().map(|glob| format!("!{}", glob)).collect();
+    let members = list("members");
+    Some(MemberGlobs::new(members.iter().chain(&excluded).map(String::as_str)))
+}
+
+/// `workspaces` of a `package.json`, as a list or Yarn's `{"packages": [...]}`
+fn npm_globs(content: &str) -> Option<MemberGlobs> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    let workspaces = json.get("workspaces")?;
+    let globs = workspaces.as_array().or_else(|| workspaces.get("packages")?.as_array())?;
+    Some(MemberGlobs::new(globs.iter().filter_map(|glob| glob.as_str())))
+}
+
+/// `packages` of a `pnpm-workspace.yaml`, the one key that matters here
+/// 
+/// Both the block form (`- 'packages/*'` lines) and the flow form
+/// (`packages: ['packages/*']`) are read.
+fn pnpm_globs(content: &str) -> MemberGlobs {
+    let unquote = |item: &str| item.trim().trim_matches(|c| c == '\'' || c == '"').to_string();
+    let mut globs = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            let flow = line.strip_prefix("packages:").map(str::trim);
+            in_packages = flow == Some("");
+            if let Some(items) = flow.and_then(|flow| flow.strip_prefix('[')?.strip_suffix(']')) {
+                globs.extend(items.split(',').map(unquote).filter(|glob| !glob.is_empty()));
+            }
+            continue;
+        }
+        if let Some(item) = line.trim_start().strip_prefix('-').filter(|_| in_packages) {
+            globs.push(unquote(item));
+        }
+    }
+    MemberGlobs::new(globs.iter().map(String::as_str))
+}
+
+/// A member's name from its manifest; npm and Nx fall back to the directory name
+fn member_name(kind: WorkspaceKind, content: &str, dir: &Path) -> Option<String> {
+    let name = match kind {
+        WorkspaceKind::Cargo => return parse_manifest("Cargo.toml", content).and_then(|(name, _)| name),
+        WorkspaceKind::Npm | WorkspaceKind::Pnpm => parse_manifest("package.json", content).and_then(|(name, _)| name),
+        WorkspaceKind::Nx => serde_json::from_str::<serde_json::Value>(content)
+            .ok()
+            .and_then(|json| json.get("name")?.as_str().map(str::to_string)),
+    };
+    name.or_else(|| Some(dir.file_name()?.to_str()?.to_string()))
+}
+
+/// `packages/*` as a regex over `/`-separated directories; `**` spans several
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let glob = glob.trim().trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+fn parent(path: &Path) -> PathBuf {
+    path.parent().unwrap_or(Path::new("")).to_path_buf()
+}
+
+/// Installed dependencies bring their own manifests
+pub(crate) fn is_vendored(path: &Path) -> bool {
+    path.components().any(|component| component.as_os_str() == "node_modules")
+}
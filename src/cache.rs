@@ -0,0 +1,163 @@
+//! Model answers kept per staged diff
+//!
+//! Running again after cancelling or a dry run asks the model the same
+//! question. Each answer is kept in the user's cache directory under a hash of
+//! the provider, its endpoint, the model, the staged diff and the prompt
+//! (which holds the template), so the same question is answered from there
+//! instead. Answers older than [`MAX_AGE`] are dropped, as are the least
+//! recently written ones beyond [`MAX_ENTRIES`]. `--no-cache` asks the model
+//! anyway and `cache clear` empties it.
+
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Answers kept at most; writing another drops the oldest
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Answers older than this are neither reused nor kept
+pub const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// A message the model wrote for a staged diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub model: String,
+    pub message: String,
+    /// Unix time it was generated at
+    pub created: u64,
+}
+
+impl CachedResponse {
+    pub fn new(model: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { model: model.into(), message: message.into(), created: now() }
+    }
+    
+    fn is_expired(&self) -> bool {
+        now().saturating_sub(self.created) > MAX_AGE.as_secs()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// One JSON file per answer, named after its key
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    max_entries: usize,
+    refresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), max_entries: MAX_ENTRIES, refresh: false }
+    }
+    
+    /// Keep at most `max_entries` answers instead of [`MAX_ENTRIES`]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+    
+    /// Ignore the kept answers but still write new ones over them
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+    
+    /// Cache in the user's cache directory
+    pub fn open_default() -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| GitAiError::FileSystem("Could not find cache directory".to_string()))?
+            .join("git-ai-commit");
+        Ok(Self::new(cache_dir.join("responses")))
+    }
+    
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    
+    /// Key of the answer `model`, served by `provider` at `endpoint`, gives
+    /// to `prompt` for the staged `diff`
+    pub fn key(provider: &str, endpoint: &str, model: &str, diff: &str, prompt: &str) -> String {
+        let mut hasher = Sha256::new();
+        for part in [provider, endpoint, model, diff, prompt] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+    
+    /// Answer kept under `key`; an unreadable or expired one counts as
+    /// missing, as does every answer while refreshing
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        if self.refresh {
+            return None;
+        }
+        let content = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str::<CachedResponse>(&content).ok().filter(|response| !response.is_expired())
+    }
+    
+    /// Keep `response` under `key`, replacing what was there, then drop
+    /// expired answers and the oldest beyond the limit
+    pub fn put(&self, key: &str, response: &CachedResponse) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to create cache directory: {}", e)))?;
+        let path = self.path(key);
+        fs::write(&path, serde_json::to_string_pretty(response)?)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write {}: {}", path.display(), e)))?;
+        self.prune();
+        Ok(())
+    }
+    
+    /// Remove answers written more than [`MAX_AGE`] ago, then the least
+    /// recently written beyond `max_entries`; files that can't be read or
+    /// removed are left for the next time
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut answers: Vec<(SystemTime, PathBuf)> = entries.filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .filter_map(|path| Some((fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?, path)))
+            .collect();
+        answers.sort_by_key(|(modified, _)| Reverse(*modified));
+        for (index, (modified, path)) in answers.iter().enumerate() {
+            let expired = modified.elapsed().is_ok_and(|age| age > MAX_AGE);
+            if expired || index >= self.max_entries {
+                if let Err(e) = fs::remove_file(path) {
+                    tracing::debug!("Cached message {} not removed: {}", path.display(), e);
+                }
+            }
+        }
+    }
+    
+    /// Remove every answer; returns how many there were
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(GitAiError::FileSystem(format!("Failed to read {}: {}", self.dir.display(), e)).into()),
+        };
+        let mut removed = 0;
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            if path.extension().is_some_and(|extension| extension == "json") {
+                fs::remove_file(&path)
+                    .map_err(|e| GitAiError::FileSystem(format!("Failed to remove {}: {}", path.display(), e)))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+    
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
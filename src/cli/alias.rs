@@ -0,0 +1,75 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Replace a leading alias with the arguments it stands for
+/// 
+/// `args` includes the program name. Like git, aliases may refer to other
+/// aliases but never to themselves, and names in `builtins` (subcommands)
+/// can't be overridden. Arguments after the alias are kept.
+pub fn expand_aliases(
+    args: Vec<String>,
+    aliases: &BTreeMap<String, String>,
+    builtins: &[&str],
+) -> Result<Vec<String>> {
+    let mut args = args;
+    let mut expanded: Vec<String> = Vec::new();
+    
+    while let Some(name) = args.get(1).cloned() {
+        let definition = match aliases.get(&name) {
+            Some(definition) if !name.starts_with('-') && !builtins.contains(&name.as_str()) => definition,
+            _ => break,
+        };
+        if expanded.contains(&name) {
+            return Err(GitAiError::Config(format!(
+                "Alias loop: {} -> {}",
+                expanded.join(" -> "),
+                name
+            )).into());
+        }
+        
+        let words = split_words(definition)
+            .map_err(|e| GitAiError::Config(format!("Invalid alias '{}': {}", name, e)))?;
+        args.splice(1..2, words);
+        expanded.push(name);
+    }
+    
+    Ok(args)
+}
+
+/// Split an alias definition into words, honouring single and double quotes
+fn split_words(definition: &str) -> std::result::Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    
+    for c in definition.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
@@ -1,8 +1,15 @@
-use clap::Parser;
-use std::path::PathBuf;
+use anyhow::Result;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
-use crate::config::Config;
+use crate::cli::alias::expand_aliases;
+use crate::config::{Config, GenerationOptions, MessageStyle, Provider};
+use crate::git::exclude::PathFilter;
+use crate::git::DisplayFormat;
+use crate::utils::error::GitAiError;
+use crate::utils::{logging, WriteMode};
 use crate::ollama::client::OllamaClient;
+use crate::ollama::host::OllamaHost;
 use crate::ollama::OllamaClientTrait;
 use tokio::runtime::Runtime;
 use tokio;
@@ -10,11 +17,11 @@ use std::sync::atomic::{AtomicBool, Ordering};
 
 // Track which fields were explicitly set via command line
 thread_local! {
-    static MODEL_WAS_SET: AtomicBool = AtomicBool::new(false);
-    static MAX_FILES_WAS_SET: AtomicBool = AtomicBool::new(false);
-    static MAX_DIFF_LINES_WAS_SET: AtomicBool = AtomicBool::new(false);
-    static PORT_WAS_SET: AtomicBool = AtomicBool::new(false);
-    static TIMEOUT_WAS_SET: AtomicBool = AtomicBool::new(false);
+    static MODEL_WAS_SET: AtomicBool = const { AtomicBool::new(false) };
+    static MAX_FILES_WAS_SET: AtomicBool = const { AtomicBool::new(false) };
+    static MAX_DIFF_LINES_WAS_SET: AtomicBool = const { AtomicBool::new(false) };
+    static PORT_WAS_SET: AtomicBool = const { AtomicBool::new(false) };
+    static TIMEOUT_WAS_SET: AtomicBool = const { AtomicBool::new(false) };
 }
 
 // Helper function to track when a value is set
@@ -39,7 +46,7 @@ fn get_default_model() -> String {
         let last_model = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
                 if client.is_running().await {
-                    client.get_last_model().await.unwrap_or_else(|_| None)
+                    client.get_last_model().await.unwrap_or(None)
                 } else {
                     None
                 }
@@ -62,7 +69,7 @@ fn get_default_model() -> String {
         // Check if Ollama is running and get the last model
         let last_model = rt.block_on(async {
             if client.is_running().await {
-                client.get_last_model().await.unwrap_or_else(|_| None)
+                client.get_last_model().await.unwrap_or(None)
             } else {
                 None
             }
@@ -78,6 +85,26 @@ fn get_default_model() -> String {
     "gemma3:4b".to_string()
 }
 
+/// URL of the `origin` remote of the repository in `repo_dir`, if it has one
+fn origin_url(repo_dir: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .current_dir(repo_dir)
+        .output()
+        .ok()?;
+    
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !url.is_empty()).then_some(url)
+}
+
+/// The host `source` sets; one that can't be read is ignored with a warning
+fn host_from(source: &str, host: Option<String>) -> Option<OllamaHost> {
+    let host = host.filter(|host| !host.trim().is_empty())?;
+    OllamaHost::parse(&host)
+        .inspect_err(|e| tracing::warn!("Ignoring {}: {}", source, e))
+        .ok()
+}
+
 /// Command-line arguments for git-ai-commit
 /// 
 /// This tool generates AI-powered commit messages by analyzing your git changes.
@@ -97,11 +124,38 @@ USAGE EXAMPLES:\n\n\
   # Use a custom prompt template\n  $ git-ai-commit --template ./my-prompt.txt\n\n\
   # Increase diff context for better messages\n  $ git-ai-commit --max-files 20 --max-diff-lines 100\n\n\
   # Run with custom Ollama port\n  $ git-ai-commit --port 12345\n\n\
+  # Describe an arbitrary patch\n  $ git diff main... | git-ai-commit --stdin-diff\n\n\
+  # Generate and commit without reviewing the message\n  $ git-ai-commit commit\n\n\
+  # List installed models\n  $ git-ai-commit models list\n\n\
+  # Check the setup\n  $ git-ai-commit doctor\n\n\
+  # Summarize commits before pushing\n  $ git-ai-commit push-summary\n\n\
+  # Show Ollama servers kept warm between runs\n  $ git-ai-commit daemon list\n\n\
+  # Collect a redacted bug report\n  $ git-ai-commit report-bug\n\n\
 For more information on each option, use --help.",
     version,
     propagate_version = true
 )]
 pub struct Args {
+    /// Optional subcommand; without one, a commit message is generated for the current changes
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    
+    /// Run as if started in PATH instead of the current directory
+    /// 
+    /// Like `git -C`, this points the tool at another repository, a linked
+    /// worktree or a submodule. Its repository config is read from there too.
+    /// 
+    /// Example:
+    ///   -C ../other-worktree
+    #[arg(
+        global = true,
+        short = 'C',
+        long = "repo",
+        value_name = "PATH",
+        help = "Run in PATH instead of the current directory, like git -C"
+    )]
+    pub repo: Option<PathBuf>,
+    
     /// AI model to use for commit message generation
     /// 
     /// If not specified, the tool will use the value from the config file,
@@ -111,6 +165,7 @@ pub struct Args {
     ///   --model llama3
     ///   -m mistral
     #[arg(
+        global = true,
         short, 
         long, 
         default_value_t = get_default_model(),
@@ -130,6 +185,7 @@ pub struct Args {
     /// Example:
     ///   --max-files 20
     #[arg(
+        global = true,
         short = 'f',
         long, 
         default_value = "10",
@@ -151,6 +207,7 @@ pub struct Args {
     /// Example:
     ///   --max-diff-lines 100
     #[arg(
+        global = true,
         short = 'l',
         long, 
         default_value = "50",
@@ -164,6 +221,22 @@ pub struct Args {
     )]
     pub max_diff_lines: usize,
     
+    /// Only count files matching these globs in the prompt, e.g. generated code
+    /// 
+    /// Added to `exclude_paths` in the config, which lists the lockfiles of
+    /// common package managers unless set. Globs are read like .gitignore.
+    /// 
+    /// Example:
+    ///   --exclude 'vendor/,*.pb.go'
+    #[arg(
+        global = true,
+        long,
+        value_name = "GLOBS",
+        value_delimiter = ',',
+        help_heading = "Diff Options"
+    )]
+    pub exclude: Vec<String>,
+    
     /// Enable interactive confirmation before committing
     /// 
     /// By default, the tool will commit without confirmation. Use this flag to
@@ -172,6 +245,7 @@ pub struct Args {
     /// Example:
     ///   --confirm  # Ask for confirmation before committing
     #[arg(
+        global = true,
         long = "confirm",
         help = "Ask for confirmation before committing (default: false)",
         help_heading = "Commit Options",
@@ -180,20 +254,255 @@ pub struct Args {
     )]
     pub no_confirm: bool,
     
+    /// Open the generated message in your editor before committing
+    /// 
+    /// Like `git commit -e`: the editor is $GIT_EDITOR, core.editor, $VISUAL
+    /// or $EDITOR, lines starting with '#' are dropped, and an empty message
+    /// aborts the commit. Also set with `edit = true` in the config.
+    /// 
+    /// Example:
+    ///   --edit
+    #[arg(global = true, short = 'e', long, help_heading = "Commit Options")]
+    pub edit: bool,
+    
+    /// Finish a merge with the message git prepared, like `git commit --no-edit`
+    /// 
+    /// While a merge is in progress the subject git prepared ("Merge branch
+    /// 'x' into main") is kept and a body describing the merge is generated;
+    /// with this flag nothing is generated.
+    /// 
+    /// Example:
+    ///   --no-edit
+    #[arg(global = true, long, conflicts_with = "edit", help_heading = "Commit Options")]
+    pub no_edit: bool,
+    
+    /// Commit the stopped step of a rebase, cherry-pick or revert, then continue it
+    /// 
+    /// Without this flag, committing while one is in progress is refused, since
+    /// a plain commit would leave the rest of it waiting. With it, the message is
+    /// generated as usual and `git rebase --continue` (or `cherry-pick
+    /// --continue`, `revert --continue`) runs after the commit.
+    /// 
+    /// Example:
+    ///   --continue-sequence
+    #[arg(global = true, long, conflicts_with_all = ["push", "split"], help_heading = "Commit Options")]
+    pub continue_sequence: bool,
+    
+    /// Skip the pre-commit and commit-msg hooks, like `git commit --no-verify`
+    /// 
+    /// Without it, a commit a hook rejects is explained, listing the files the
+    /// hook changed, and in a terminal you can stage them and regenerate the
+    /// message, commit again, or skip the hooks.
+    /// 
+    /// Example:
+    ///   --no-verify
+    #[arg(global = true, short = 'n', long, help_heading = "Commit Options")]
+    pub no_verify: bool,
+    
+    /// Add a Signed-off-by trailer for the committer, like `git commit -s`
+    /// 
+    /// Example:
+    ///   --signoff
+    #[arg(global = true, short = 's', long, help_heading = "Commit Options")]
+    pub signoff: bool,
+    
+    /// Sign the commit, like `git commit -S`, with the default key or KEYID
+    /// 
+    /// The signature is made by gpg, or by ssh-keygen with `gpg.format=ssh`.
+    /// With this or `commit.gpgsign=true`, the confirmation prompt warns that
+    /// signing may wait for a passphrase.
+    /// 
+    /// Examples:
+    ///   --gpg-sign
+    ///   --gpg-sign=0xDEADBEEF
+    #[arg(
+        global = true,
+        short = 'S',
+        long,
+        value_name = "KEYID",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        help_heading = "Commit Options"
+    )]
+    pub gpg_sign: Option<String>,
+    
+    /// Attach the prompt hash, candidates and generation stats to the commit
+    /// as a note in refs/notes/ai-commit, read back with `history show`
+    /// 
+    /// The message itself stays as it is.
+    /// 
+    /// Example:
+    ///   --notes
+    #[arg(global = true, long, help_heading = "Commit Options")]
+    pub notes: bool,
+    
+    /// Let the model group the staged files into several logical commits
+    /// 
+    /// The plan and a message for each commit are shown before anything is
+    /// committed. Each commit gets exactly the staged content of its files.
+    /// 
+    /// Example:
+    ///   --split
+    #[arg(
+        global = true,
+        long,
+        conflicts_with_all = ["amend", "stdin_diff", "prepare_only", "raw", "ensemble", "candidates"],
+        help_heading = "Commit Options"
+    )]
+    pub split: bool,
+    
+    /// Run non-interactively for bot commits in CI pipelines
+    /// 
+    /// Never prompts and prints the result as JSON. Never starts an Ollama
+    /// server or downloads a model: the backend must already be reachable.
+    /// Every model request is limited to --timeout-seconds, and neither the
+    /// config file nor the message history is written. Exits with 0 when a
+    /// message was generated, 1 on errors and 3 when nothing is staged.
+    /// 
+    /// Example:
+    ///   --ci --add-unstaged
+    #[arg(
+        global = true,
+        long,
+        conflicts_with_all = ["raw", "no_confirm", "edit"],
+        help_heading = "Commit Options"
+    )]
+    pub ci: bool,
+    
+    /// Write the generated message to a file instead of committing
+    /// 
+    /// Writes to `.git/COMMIT_EDITMSG` unless a path is given, then exits
+    /// without touching the commit. Finish with `git commit -e -F <file>`.
+    /// 
+    /// Example:
+    ///   --prepare-only
+    ///   --prepare-only ./message.txt
+    #[arg(
+        global = true,
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        help_heading = "Commit Options"
+    )]
+    pub prepare_only: Option<Option<PathBuf>>,
+    
+    /// Copy the generated message to the system clipboard
+    /// 
+    /// The message is still committed unless --dry-run or --prepare-only is
+    /// given, so the commit can be made from a GUI client or web UI instead.
+    /// 
+    /// Example:
+    ///   --copy --dry-run
+    #[arg(global = true, long, help_heading = "Commit Options")]
+    pub copy: bool,
+    
+    /// Write a new message for the last commit and amend it
+    /// 
+    /// The message is generated from the changes in HEAD, e.g. to replace a
+    /// quick "wip". Only the message changes; staged changes stay staged.
+    /// 
+    /// Example:
+    ///   --amend
+    #[arg(
+        global = true,
+        long,
+        conflicts_with_all = ["add_unstaged", "push", "stdin_diff"],
+        help_heading = "Commit Options"
+    )]
+    pub amend: bool,
+    
+    /// Push the new commit after it is created
+    /// 
+    /// Pushes to the branch's upstream when it has one. Otherwise the branch is
+    /// pushed under its own name to the only remote, or `origin`, and set as
+    /// the upstream. With --json the result is reported under `push`.
+    /// 
+    /// Example:
+    ///   --push
+    ///   --push --remote fork
+    #[arg(global = true, long, help_heading = "Commit Options")]
+    pub push: bool,
+    
+    /// Remote to push to instead of the upstream's
+    /// 
+    /// Example:
+    ///   --push --remote upstream
+    #[arg(global = true, long, value_name = "NAME", requires = "push", help_heading = "Commit Options")]
+    pub remote: Option<String>,
+    
+    /// Make the pushed branch the upstream of the current branch
+    /// 
+    /// Example:
+    ///   --push --remote fork --set-upstream
+    #[arg(global = true, long, requires = "push", help_heading = "Commit Options")]
+    pub set_upstream: bool,
+    
     /// Path to a custom prompt template file
     /// 
-    /// The template should be a text file that will be used to generate the
-    /// prompt sent to the AI. Use placeholders like {diff} and {status}.
+    /// The template is the full prompt sent to the AI, with placeholders
+    /// where the changes go: {CONTEXT} (the repository context), {DIFF},
+    /// {BRANCH}, {LAST_COMMITS}, {FILES} and {LANGUAGE}. It needs {CONTEXT},
+    /// {DIFF} or {FILES}. `{{> rules}}` includes a section of the default
+    /// prompt (header, context_block, rules, footer), and a template of only
+    /// `{{#rules}}...{{/rules}}` blocks replaces just those sections.
+    /// Overrides the `template` setting of the config files.
     /// 
     /// Example:
     ///   --template ./my-custom-prompt.txt
     #[arg(
+        global = true,
         long, 
         value_name = "FILE",
         help_heading = "Customization"
     )]
     pub template: Option<PathBuf>,
     
+    /// Write the message in a bundled style: conventional, gitmoji,
+    /// angular, plain or kernel
+    /// 
+    /// Each style has its own prompt and tidies the model's answer into its
+    /// format, e.g. an emoji prefix for gitmoji or a `subsystem:` prefix and
+    /// a Signed-off-by trailer for kernel. `--template` takes precedence
+    /// over the style's prompt. Also set with `style = "gitmoji"` in the
+    /// config.
+    /// 
+    /// Example:
+    ///   --style gitmoji
+    #[arg(
+        global = true,
+        long,
+        value_enum,
+        help_heading = "Customization"
+    )]
+    pub style: Option<MessageStyle>,
+    
+    /// Ask for a body of bullet points under the subject line
+    /// 
+    /// The body lists the key changes, wrapped at 72 characters, and is
+    /// committed as its own paragraph. Also set with `include_body = true`
+    /// in the config.
+    /// 
+    /// Example:
+    ///   --body
+    #[arg(global = true, long, help_heading = "Customization")]
+    pub body: bool,
+    
+    /// Use the named profile from the config file
+    /// 
+    /// Without this flag, the first profile whose `remotes` patterns match
+    /// the repository's `origin` URL is used, if any.
+    /// 
+    /// Example:
+    ///   --profile work
+    #[arg(
+        global = true,
+        long,
+        value_name = "NAME",
+        help_heading = "Customization"
+    )]
+    pub profile: Option<String>,
+    
     /// Show the git analysis and generated commit message without committing
     /// 
     /// This is useful for previewing what the commit would look like.
@@ -201,19 +510,87 @@ pub struct Args {
     /// 
     /// Example:
     ///   --dry-run
-    #[arg(short = 'd', long, help_heading = "Debug Options")]
+    #[arg(global = true, short = 'd', long, help_heading = "Debug Options")]
     pub dry_run: bool,
     
+    /// Format of the repository analysis shown by --dry-run, and of `history export`
+    /// 
+    /// Markdown and table output can be pasted into PR descriptions or chat;
+    /// csv and json are for spreadsheets and scripts.
+    /// 
+    /// Example:
+    ///   --dry-run --format markdown
+    ///   history export --format csv
+    #[arg(
+        global = true,
+        long,
+        value_enum,
+        default_value_t = DisplayFormat::Plain,
+        value_name = "FORMAT",
+        help_heading = "Debug Options"
+    )]
+    pub format: DisplayFormat,
+    
     /// Enable verbose output for debugging
     /// 
     /// Shows additional information about what the tool is doing,
-    /// including the full prompt sent to the AI model.
+    /// including the full prompt sent to the AI model. Repeat it (-vv)
+    /// to also see raw data such as the parsed config.
     /// 
     /// Example:
     ///   --verbose
-    ///   -v
-    #[arg(short, long, help_heading = "Debug Options")]
-    pub verbose: bool,
+    ///   -vv
+    #[arg(global = true, short, long, action = clap::ArgAction::Count, help_heading = "Debug Options")]
+    pub verbose: u8,
+    
+    /// Print only errors and the result
+    /// 
+    /// Progress lines, notes and warnings are left out; the generated
+    /// message, JSON output and errors are still printed.
+    /// 
+    /// Example:
+    ///   --quiet
+    ///   -q
+    #[arg(global = true, short, long, conflicts_with = "verbose", help_heading = "Debug Options")]
+    pub quiet: bool,
+    
+    /// Print the result as JSON on stdout
+    /// 
+    /// Progress messages go to stderr so the output can be piped into
+    /// other tools. Includes the new commit's SHA and stats after committing.
+    /// 
+    /// Example:
+    ///   --json | jq .commit.sha
+    #[arg(global = true, long, help_heading = "Debug Options")]
+    pub json: bool,
+    
+    /// Print only the generated message on stdout, byte for byte
+    /// 
+    /// Implies --dry-run. Progress goes to stderr and the message is printed
+    /// without banners, colors or trailing whitespace, followed by exactly one
+    /// newline, so it can be copied out of tmux or SSH sessions as is.
+    /// 
+    /// Example:
+    ///   --raw --no-wrap
+    #[arg(global = true, long, conflicts_with = "json", help_heading = "Debug Options")]
+    pub raw: bool,
+    
+    /// Join hard-wrapped body paragraphs into single lines in --raw output
+    /// 
+    /// Useful when pasting into web UIs that wrap text themselves.
+    #[arg(global = true, long, requires = "raw", help_heading = "Debug Options")]
+    pub no_wrap: bool,
+    
+    /// Never change anything: no staging, commits, config writes or model downloads
+    /// 
+    /// Overrides every other flag. The message is generated and shown as in
+    /// --dry-run; operations that would write fail with an error instead.
+    /// Useful for CI diagnostics and demos.
+    /// 
+    /// Example:
+    ///   --no-write
+    #[arg(global = true, long, help_heading = "Debug Options")]
+    pub no_write: bool,
     
     /// Custom port for the Ollama server
     /// 
@@ -223,6 +600,7 @@ pub struct Args {
     /// 
     /// Default: 11434
     #[arg(
+        global = true,
         short = 'p',
         long, 
         default_value = "11434", 
@@ -235,10 +613,41 @@ pub struct Args {
     )]
     pub port: u16,
     
+    /// Ollama server to use instead of starting one, e.g. on a GPU box
+    /// 
+    /// Takes what OLLAMA_HOST takes: a host name with an optional port
+    /// (11434 by default), or an http:// or https:// URL. Credentials in the
+    /// URL are sent with basic auth; otherwise a token in $OLLAMA_API_KEY is
+    /// sent as a bearer token. A server on another machine is never started.
+    /// 
+    /// Default: $OLLAMA_HOST, then `host` in the config
+    /// 
+    /// Example:
+    ///   --host https://gpu-box.lan:11434
+    #[arg(
+        global = true,
+        long,
+        value_name = "URL",
+        help_heading = "Advanced",
+        value_parser = |s: &str| OllamaHost::parse(s).map_err(|e| e.to_string())
+    )]
+    pub host: Option<OllamaHost>,
+    
+    /// Accept the Ollama server's TLS certificate without checking it
+    /// 
+    /// For self-signed setups only; prefer trusting their CA with
+    /// `host_ca_cert` in the config.
+    #[arg(global = true, long, help_heading = "Advanced")]
+    pub insecure: bool,
+    
     /// Timeout for AI generation in seconds
     /// 
+    /// A generation that takes longer fails with a timeout error; in a
+    /// terminal you are offered to try again with twice the time.
+    /// 
     /// Default: 60 seconds
     #[arg(
+        global = true,
         short = 't',
         long, 
         default_value = "60", 
@@ -260,39 +669,727 @@ pub struct Args {
     /// Example:
     ///   --add-unstaged
     #[arg(
+        global = true,
         short = 'a',
         long,
         help_heading = "Staging Options"
     )]
     pub add_unstaged: bool,
     
-    /// List all available Ollama models and exit
+    /// Pick the unstaged and untracked files to stage from a list, then
+    /// generate the message from the staged files only
     /// 
-    /// This will connect to the Ollama server and list all locally available models.
-    /// The tool will exit after displaying the list.
+    /// Files already staged stay staged. Files left out aren't mentioned in
+    /// the prompt.
     /// 
     /// Example:
-    ///   --list-models
+    ///   --interactive-stage
     #[arg(
+        global = true,
         long,
+        conflicts_with_all = ["add_unstaged", "amend", "ci", "dry_run", "stdin_diff"],
+        help_heading = "Staging Options"
+    )]
+    pub interactive_stage: bool,
+    
+    /// List all available Ollama models and exit (same as `models list`)
+    #[arg(
+        global = true,
+        long,
+        hide = true,
         help_heading = "Model Options"
     )]
     pub list_models: bool,
+    
+    /// Service that generates the message
+    /// 
+    /// `openai` (or `generic-openai`) uses any OpenAI-compatible API
+    /// (OpenAI, OpenRouter, Groq, LM Studio, vLLM, llama.cpp server,
+    /// llamafile) configured in the `[openai]` config section instead of a
+    /// local Ollama server. `local` runs the GGUF file in `local.model_path`
+    /// in-process, without any server (needs the `local-gguf` build feature).
+    /// 
+    /// Example:
+    ///   --provider openai --model gpt-4o-mini
+    #[arg(
+        global = true,
+        long,
+        value_enum,
+        help_heading = "Model Options"
+    )]
+    pub provider: Option<Provider>,
+    
+    /// Print the message once it's complete instead of token by token
+    /// 
+    /// Generated tokens are streamed to the terminal as the model writes them,
+    /// unless output is piped or --json/--raw is set.
+    /// 
+    /// Example:
+    ///   --no-stream
+    #[arg(
+        global = true,
+        long,
+        help_heading = "Model Options"
+    )]
+    pub no_stream: bool,
+    
+    /// Ask the model even if a message for the same changes is cached
+    /// 
+    /// Messages are kept per staged diff, model and prompt, so running again
+    /// after a dry run or a cancelled commit shows the same message without
+    /// calling the model. The new message replaces the cached one.
+    /// 
+    /// Example:
+    ///   --no-cache
+    #[arg(
+        global = true,
+        long,
+        help_heading = "Model Options"
+    )]
+    pub no_cache: bool,
+    
+    /// Generate even if it goes over the cost caps of a paid API
+    /// 
+    /// With `[cost] max_per_run` or `monthly_budget` set, a call estimated
+    /// to go over either is refused; this makes it anyway. The spending is
+    /// still recorded.
+    /// 
+    /// Example:
+    ///   --provider openai --force
+    #[arg(
+        global = true,
+        long,
+        help_heading = "Model Options"
+    )]
+    pub force: bool,
+    
+    /// Favor speed: smallest installed model, short prompt, short answer
+    /// 
+    /// Skips the project context, validation retries, chunking and history
+    /// to keep generation to a few seconds. Handy for rapid WIP commits.
+    /// 
+    /// Example:
+    ///   --quick --add-unstaged
+    #[arg(
+        global = true,
+        long,
+        conflicts_with_all = ["model", "ensemble", "candidates"],
+        help_heading = "Model Options"
+    )]
+    pub quick: bool,
+    
+    /// Generate candidates with several models in parallel
+    /// 
+    /// All candidates are shown to choose from, or a --judge model picks
+    /// or merges the best one. Slower, but helps on tricky diffs.
+    /// 
+    /// Example:
+    ///   --ensemble llama3,qwen2.5-coder
+    #[arg(
+        global = true,
+        long,
+        value_name = "MODELS",
+        value_delimiter = ',',
+        help_heading = "Model Options"
+    )]
+    pub ensemble: Vec<String>,
+    
+    /// Generate this many alternative messages in parallel and pick one
+    /// 
+    /// Each is a separate generation with the same model. They are offered
+    /// to choose from, or the best ranked one is used without a terminal;
+    /// --verbose prints the ones not chosen.
+    /// 
+    /// Example:
+    ///   --candidates 3
+    #[arg(
+        global = true,
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(2..=10),
+        conflicts_with = "ensemble",
+        help_heading = "Model Options"
+    )]
+    pub candidates: Option<u8>,
+    
+    /// Model that picks or merges the best --ensemble candidate
+    /// 
+    /// Example:
+    ///   --ensemble llama3,qwen2.5-coder --judge gemma3:4b
+    #[arg(
+        global = true,
+        long,
+        value_name = "MODEL",
+        requires = "ensemble",
+        help_heading = "Model Options"
+    )]
+    pub judge: Option<String>,
+    
+    /// Sampling temperature for Ollama, from 0 to 2
+    /// 
+    /// Lower is more predictable, higher more varied.
+    /// 
+    /// Default: `ollama.options.temperature` in the config, else 0.7
+    /// 
+    /// Example:
+    ///   --temperature 0.2
+    #[arg(
+        global = true,
+        long,
+        value_name = "T",
+        help_heading = "Model Options",
+        value_parser = |s: &str| {
+            let temperature = s.parse::<f64>().map_err(|e| e.to_string())?;
+            if (0.0..=2.0).contains(&temperature) {
+                Ok(temperature)
+            } else {
+                Err("must be between 0 and 2".to_string())
+            }
+        }
+    )]
+    pub temperature: Option<f64>,
+    
+    /// Only sample from the likeliest tokens that make up this share of the probability
+    /// 
+    /// Default: `ollama.options.top_p` in the config, else 0.9
+    /// 
+    /// Example:
+    ///   --top-p 0.5
+    #[arg(
+        global = true,
+        long,
+        value_name = "P",
+        help_heading = "Model Options",
+        value_parser = |s: &str| {
+            let top_p = s.parse::<f64>().map_err(|e| e.to_string())?;
+            if top_p > 0.0 && top_p <= 1.0 {
+                Ok(top_p)
+            } else {
+                Err("must be above 0 and at most 1".to_string())
+            }
+        }
+    )]
+    pub top_p: Option<f64>,
+    
+    /// Most tokens Ollama may generate for a message
+    /// 
+    /// Default: `ollama.options.num_predict` in the config, else the model's limit
+    /// 
+    /// Example:
+    ///   --num-predict 120
+    #[arg(
+        global = true,
+        long,
+        value_name = "TOKENS",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help_heading = "Model Options"
+    )]
+    pub num_predict: Option<u32>,
+    
+    /// Seed for Ollama's sampling, so the same changes get the same message
+    /// 
+    /// Handy for reproducible output in tests and CI, best with a low
+    /// --temperature.
+    /// 
+    /// Default: `ollama.options.seed` in the config, else random
+    /// 
+    /// Example:
+    ///   --seed 42 --temperature 0
+    #[arg(global = true, long, value_name = "N", help_heading = "Model Options")]
+    pub seed: Option<u64>,
+    
+    /// Read a unified diff from stdin and describe it instead of the repository
+    /// 
+    /// Works outside of a git repository, so the output of `git diff`, `hg diff`
+    /// or a code review tool can be piped in. Only the generated message is
+    /// printed to stdout; nothing is committed.
+    /// 
+    /// Example:
+    ///   git diff main... | git-ai-commit --stdin-diff
+    #[arg(
+        global = true,
+        long,
+        help_heading = "Input Options"
+    )]
+    pub stdin_diff: bool,
+    
+    /// Run as a prepare-commit-msg hook, writing the message into MSGFILE
+    /// 
+    /// Used by the installed git hook; the message file is updated in place
+    /// instead of committing. Failures never block the commit.
+    #[arg(
+        global = true,
+        long,
+        value_name = "MSGFILE",
+        help_heading = "Hook Options"
+    )]
+    pub hook: Option<PathBuf>,
+    
+    /// Source of the message passed by git to the prepare-commit-msg hook
+    /// 
+    /// One of message, template, merge, squash or commit.
+    #[arg(
+        global = true,
+        long,
+        value_name = "SOURCE",
+        requires = "hook",
+        help_heading = "Hook Options"
+    )]
+    pub hook_source: Option<String>,
+    
+    /// Configuration loaded from the config file
+    #[arg(skip)]
+    pub config: Config,
+}
+
+/// Subcommands for workflows other than generating a commit message
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Generate a commit message for the staged changes (the default)
+    /// 
+    /// Every option of the top-level command applies, before or after the
+    /// subcommand name.
+    /// 
+    /// Example:
+    ///   git-ai-commit generate --dry-run
+    Generate,
+    
+    /// Generate a message and commit with it without asking
+    /// 
+    /// Same as `generate --no-confirm`.
+    /// 
+    /// Example:
+    ///   git-ai-commit commit --add-unstaged
+    Commit,
+    
+    /// List, download, inspect or remove models
+    /// 
+    /// Example:
+    ///   git-ai-commit models list
+    ///   git-ai-commit models pull qwen2.5-coder
+    ///   git-ai-commit models info qwen2.5-coder
+    ///   git-ai-commit models rm qwen2.5-coder
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommand,
+    },
+    
+    /// View and change settings without editing the config file
+    /// 
+    /// Keys are dotted paths into the config file, e.g. `validation.retries`.
+    /// 
+    /// Example:
+    ///   git-ai-commit config set model llama3
+    ///   git-ai-commit config get validation.retries
+    ///   git-ai-commit config list
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    
+    /// Install or remove the prepare-commit-msg hook
+    /// 
+    /// The hook runs git-ai-commit in hook mode (--hook), which writes the
+    /// message into the file git opens in the editor instead of committing.
+    /// A hook that wasn't installed by git-ai-commit is never replaced.
+    /// 
+    /// Example:
+    ///   git-ai-commit hook install
+    ///   git-ai-commit hook uninstall
+    Hook {
+        #[command(subcommand)]
+        action: HookCommand,
+    },
+    
+    /// Check git, the config file and the model provider
+    /// 
+    /// Prints one line per check and exits with an error if any failed.
+    /// 
+    /// Example:
+    ///   git-ai-commit doctor
+    Doctor,
+    
+    /// Run the whole pipeline once in a throwaway repository
+    /// 
+    /// Stages synthetic changes in a temporary repository, then collects
+    /// them, builds the prompt, generates a message with the configured
+    /// backend, validates it and commits. Prints one line per stage and exits
+    /// with an error if any failed, so it also verifies installs in CI images.
+    /// 
+    /// Example:
+    ///   git-ai-commit self-test
+    ///   git-ai-commit self-test --stub
+    SelfTest {
+        /// Answer with a built-in stub instead of a model, to check everything else
+        #[arg(long)]
+        stub: bool,
+    },
+    
+    /// Summarize the commits that are about to be pushed
+    /// 
+    /// Prints a short paragraph describing all commits in the range
+    /// (by default everything not yet on the upstream branch).
+    /// 
+    /// Example:
+    ///   git-ai-commit push-summary
+    ///   git-ai-commit push-summary --range origin/main..HEAD
+    PushSummary {
+        /// Revision range of commits to summarize
+        #[arg(long, default_value = "@{u}..HEAD", value_name = "RANGE")]
+        range: String,
+        
+        /// Install a pre-push hook that prints the summary before every push
+        #[arg(long)]
+        install_hook: bool,
+    },
+    
+    /// Write the squash-merge title and body for the current branch
+    /// 
+    /// Describes everything the branch changes since it left the base as one
+    /// conventional commit, for GitHub's "Squash and merge", instead of a
+    /// list of work-in-progress subjects.
+    /// 
+    /// Example:
+    ///   git-ai-commit squash-title
+    ///   git-ai-commit squash-title --base upstream/main --pr 42 --json
+    SquashTitle {
+        /// Branch the pull request is merged into
+        #[arg(long, default_value = "origin/main", value_name = "REF")]
+        base: String,
+        
+        /// Pull request number, appended to the title as GitHub does, e.g. "(#42)"
+        #[arg(long, value_name = "NUMBER")]
+        pr: Option<u32>,
+    },
+    
+    /// Serve the suggested message for the staged changes to editors
+    /// 
+    /// Watches the index and generates a new suggestion whenever the staged
+    /// changes change. Every connection to the Unix socket receives the
+    /// current suggestion as one line of JSON; its `statusline` field is ready
+    /// to show, e.g. "suggested: feat(cli): add --amend". Nothing is ever
+    /// staged, committed or written besides the socket.
+    /// 
+    /// Example:
+    ///   git-ai-commit serve
+    ///   socat - UNIX-CONNECT:.git/git-ai-commit.sock
+    Serve {
+        /// Socket to listen on (default: git-ai-commit.sock in the git directory)
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+    
+    /// Same as `hook install` (or `hook uninstall` with --uninstall)
+    #[command(hide = true)]
+    InstallHook {
+        /// Remove the hook again
+        #[arg(long)]
+        uninstall: bool,
+    },
+    
+    /// Manage Ollama servers kept warm between runs
+    /// 
+    /// Servers are only kept running when `daemon.keep_warm` is set in the
+    /// config, and are shared by every repository and worktree of the user.
+    /// 
+    /// Example:
+    ///   git-ai-commit daemon list
+    ///   git-ai-commit daemon stop --port 11434
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonCommand,
+    },
+    
+    /// Show how a commit's message was generated, or export the history
+    /// 
+    /// `show` reads the note `--notes` attached to the commit: the model, the
+    /// prompt and template hashes, the generation stats and the candidates
+    /// the message was chosen from. `export` writes every recorded message
+    /// with its model, latency, token counts, whether it was committed and
+    /// how much it was edited.
+    /// 
+    /// Example:
+    ///   git-ai-commit history show HEAD
+    ///   git-ai-commit history show 1a2b3c4 --json
+    ///   git-ai-commit history export --format csv > history.csv
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+    
+    /// Manage the messages kept per staged diff
+    /// 
+    /// A message the model wrote is reused when the same changes are staged
+    /// again with the same model and prompt, e.g. after a dry run.
+    /// 
+    /// Example:
+    ///   git-ai-commit cache clear
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    
+    /// Bundle the details needed to reproduce a problem
+    /// 
+    /// Collects versions, the effective config, the prompt for the current
+    /// changes and recent history, with secrets, email addresses and the home
+    /// directory removed. The contents are shown for review before anything
+    /// is written.
+    /// 
+    /// Example:
+    ///   git-ai-commit report-bug
+    ///   git-ai-commit report-bug --issue > issue.md
+    ReportBug {
+        /// Tarball to write
+        #[arg(long, value_name = "FILE", default_value = "git-ai-commit-report.tar")]
+        output: PathBuf,
+        
+        /// Print a GitHub issue body to stdout instead of writing a tarball
+        #[arg(long)]
+        issue: bool,
+    },
+}
+
+/// Actions of the `models` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum ModelsCommand {
+    /// Show the models the provider offers
+    List,
+    
+    /// Download a model into the Ollama server
+    Pull {
+        /// Model to download, e.g. llama3.2
+        #[arg(value_name = "MODEL")]
+        name: String,
+    },
+    
+    /// Delete a model from the Ollama server
+    #[command(alias = "delete")]
+    Rm {
+        /// Model to delete, e.g. llama3.2
+        #[arg(value_name = "MODEL")]
+        name: String,
+    },
+    
+    /// Show the size, family and quantization of an installed model
+    Info {
+        /// Model to describe, e.g. llama3.2
+        #[arg(value_name = "MODEL")]
+        name: String,
+    },
+}
+
+/// Actions of the `config` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the value of a setting
+    Get {
+        /// Dotted key, e.g. `validation.retries`
+        key: String,
+    },
+    
+    /// Change a setting in the config file
+    /// 
+    /// The value is checked before anything is written; comments in the
+    /// file are not preserved.
+    Set {
+        /// Dotted key, e.g. `model`
+        key: String,
+        
+        /// New value, e.g. `llama3`, `11500` or `true`
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+    },
+    
+    /// Print every setting as `key = value`
+    List,
+    
+    /// Print the effective configuration as TOML
+    Show,
+    
+    /// Print the location of the config file
+    Path,
+    
+    /// Write a config file with the default settings, if there is none yet
+    Init,
+}
+
+/// Actions of the `hook` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum HookCommand {
+    /// Install the prepare-commit-msg hook
+    Install,
+    
+    /// Remove the hook again
+    Uninstall,
+}
+
+/// Actions of the `history` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryCommand {
+    /// Show the generation details attached to a commit
+    Show {
+        /// Commit to show, e.g. HEAD or a SHA
+        #[arg(value_name = "COMMIT", default_value = "HEAD")]
+        commit: String,
+    },
+    
+    /// Write the recorded messages as CSV (the default) or, with --format json, JSON
+    Export,
+}
+
+/// Actions of the `cache` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheCommand {
+    /// Remove every kept message
+    Clear,
+}
+
+/// Actions of the `daemon` subcommand
+#[derive(Subcommand, Debug, Clone)]
+pub enum DaemonCommand {
+    /// Show warm servers with their port, model, memory use and idle time
+    List,
+    
+    /// Shut down warm servers
+    Stop {
+        /// Only stop the server on this port
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    
+    /// Stop a warm server once it has been idle too long (started with the server)
+    #[command(hide = true)]
+    Watch {
+        #[arg(long)]
+        port: u16,
+        
+        /// Process id the server was registered with
+        #[arg(long)]
+        pid: u32,
+    },
 }
 
 impl Args {
+    /// Provider from the command line, or the config
+    pub fn provider(&self) -> Provider {
+        self.provider.unwrap_or(self.config.provider)
+    }
+    
+    /// Models asked for candidates: the `--ensemble` models, or the model `--candidates` times
+    pub fn candidate_models(&self) -> Vec<String> {
+        match self.candidates {
+            Some(count) => vec![self.model.clone(); usize::from(count)],
+            None => self.ensemble.clone(),
+        }
+    }
+    
+    /// Ollama server this run talks to, with the token from `host_token_env`
+    /// and the configured headers and certificates
+    pub fn ollama_host(&self) -> Result<OllamaHost> {
+        let mut host = self.host.clone().unwrap_or_else(|| OllamaHost::local(self.port))
+            .with_token(std::env::var(&self.config.host_token_env).ok())
+            .with_headers(&self.config.host_headers)
+            .with_insecure(self.insecure);
+        if let Some(ref ca_cert) = self.config.host_ca_cert {
+            host = host.with_ca_cert(ca_cert)?;
+        }
+        Ok(host)
+    }
+    
+    /// Files only counted in the prompt: `exclude_paths` and `--exclude`
+    pub fn excluded_paths(&self) -> PathFilter {
+        PathFilter::new(&[self.config.exclude_paths.as_slice(), self.exclude.as_slice()].concat())
+    }
+    
+    /// Ollama's sampling options: the config's, with the command line's on top
+    pub fn generation_options(&self) -> GenerationOptions {
+        let options = self.config.ollama.options;
+        GenerationOptions {
+            temperature: self.temperature.unwrap_or(options.temperature),
+            top_p: self.top_p.unwrap_or(options.top_p),
+            num_predict: self.num_predict.or(options.num_predict),
+            seed: self.seed.or(options.seed),
+        }
+    }
+    
+    /// Whether mutating operations are allowed in this run
+    pub fn write_mode(&self) -> WriteMode {
+        WriteMode::from_no_write(self.no_write)
+    }
+    
+    /// The `--gpg-sign` argument for `git commit`, when signing was asked for
+    pub fn gpg_sign_arg(&self) -> Option<String> {
+        self.gpg_sign.as_deref().map(|key| match key {
+            "" => "--gpg-sign".to_string(),
+            key => format!("--gpg-sign={}", key),
+        })
+    }
+    
+    /// The repository to work in: `--repo`, resolved against the current directory
+    pub fn repo_dir(&self) -> Result<PathBuf> {
+        let current_dir = std::env::current_dir()
+            .map_err(|e| GitAiError::Git(format!("Failed to get current directory: {}", e)))?;
+        let Some(repo) = &self.repo else {
+            return Ok(current_dir);
+        };
+        
+        let repo_dir = current_dir.join(repo);
+        if !repo_dir.is_dir() {
+            return Err(GitAiError::Git(format!("Cannot change to '{}': no such directory", repo.display())).into());
+        }
+        Ok(repo_dir)
+    }
+    
     /// Load configuration from the default location and override with command-line arguments
     pub fn load() -> Self {
-        // First, parse command line arguments to see which ones were explicitly set
-        let mut args = Self::parse();
+        // Diagnostics while reading the config already honor -q and -v, and the
+        // repository config is read from -C; aliases can't set them, as they
+        // aren't expanded yet
+        let early = Self::try_parse().ok();
+        logging::init(early.as_ref().map_or(tracing::Level::INFO, |early| logging::level_for(early.quiet, early.verbose)));
+        let repo_dir = early.as_ref().and_then(|early| early.repo_dir().ok());
+        let config = match repo_dir {
+            Some(repo_dir) => Config::load_for(&repo_dir),
+            None => Config::load(),
+        };
+        
+        // Expand aliases from the config file, then parse command line arguments
+        // to see which ones were explicitly set
+        let argv: Vec<String> = std::env::args().collect();
+        let argv = match &config {
+            Ok(config) => {
+                let command = Self::command();
+                let builtins: Vec<&str> = command.get_subcommands().map(|c| c.get_name()).collect();
+                expand_aliases(argv.clone(), &config.alias, &builtins).unwrap_or_else(|e| {
+                    tracing::warn!("{}", e);
+                    argv
+                })
+            }
+            Err(_) => argv,
+        };
+        let mut args = Self::parse_from(argv);
+        logging::init(logging::level_for(args.quiet, args.verbose));
         
-        // Then load the config file
-        if let Ok(config) = Config::load() {
-            println!("Using model from config: {}", config.model);
+        // Then apply the config file
+        if let Ok(mut config) = config {
+            let profile = args.profile.clone().or_else(|| {
+                let remote_url = origin_url(&args.repo_dir().ok()?)?;
+                config.profile_for_remote(&remote_url).map(str::to_string)
+            });
+            if let Some(profile) = profile {
+                match config.apply_profile(&profile) {
+                    Ok(()) => tracing::info!("Using profile: {}", profile),
+                    Err(e) => tracing::warn!("{}", e),
+                }
+            }
+            
+            let model = config.model_for(args.provider.unwrap_or(config.provider)).to_string();
+            tracing::debug!("Using model from config: {}", model);
             
             // Only override values that weren't explicitly set via command line
             if !MODEL_WAS_SET.with(|f| f.load(Ordering::Relaxed)) {
-                args.model = config.model;
+                args.model = model;
             }
                 
             if !MAX_FILES_WAS_SET.with(|f| f.load(Ordering::Relaxed)) {
@@ -310,8 +1407,44 @@ impl Args {
             if !TIMEOUT_WAS_SET.with(|f| f.load(Ordering::Relaxed)) {
                 args.timeout_seconds = config.timeout_seconds;
             }
+            
+            if args.host.is_none() && !PORT_WAS_SET.with(|f| f.load(Ordering::Relaxed)) {
+                args.host = host_from("OLLAMA_HOST", std::env::var("OLLAMA_HOST").ok())
+                    .or_else(|| host_from("host", config.host.clone()));
+            }
+            
+            if args.template.is_none() {
+                args.template = config.template.clone();
+            }
+            
+            if args.style.is_none() {
+                args.style = config.style;
+            }
+            
+            args.edit |= config.edit;
+            args.body |= config.include_body;
+            
+            args.config = config;
+        }
+        
+        // A server on this machine is the embedded one, on the host's port
+        if let Some(host) = args.host.take() {
+            if host.is_remote() {
+                args.host = Some(host);
+            } else {
+                args.port = host.port();
+            }
+        }
+        if args.insecure {
+            tracing::warn!("--insecure: the Ollama server's TLS certificate isn't checked");
+        }
+        
+        if let Some(style) = args.style {
+            args.config.validation.commit_style = style.commit_style();
+            args.signoff |= style == MessageStyle::Kernel;
         }
         
+        args.apply_ci_preset();
         args
     }
 }
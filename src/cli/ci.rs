@@ -0,0 +1,37 @@
+//! The `--ci` preset for bot commits in pipelines
+//!
+//! CI jobs need the same handful of settings every time: no prompts, machine
+//! readable output, no waiting on a model that isn't there, and no files
+//! written outside the repository. `--ci` sets all of them, and the exit
+//! codes below tell a pipeline what happened without parsing the output.
+
+use crate::cli::Args;
+
+/// A message was generated, and committed unless `--dry-run` was given
+pub const EXIT_OK: i32 = 0;
+
+/// Something failed: git, the backend, validation or the commit itself
+pub const EXIT_ERROR: i32 = 1;
+
+/// The command line could not be parsed (clap's own exit code)
+pub const EXIT_USAGE: i32 = 2;
+
+/// There was nothing to describe: no staged changes
+pub const EXIT_NOTHING_TO_COMMIT: i32 = 3;
+
+impl Args {
+    /// Apply the `--ci` preset on top of the command line and the config
+    /// 
+    /// The commit itself still happens, so `--no-write` is left alone.
+    pub fn apply_ci_preset(&mut self) {
+        if !self.ci {
+            return;
+        }
+        self.json = true;
+        self.no_confirm = true;
+        self.no_stream = true;
+        self.edit = false;
+        self.config.history = false;
+        self.config.daemon.keep_warm = false;
+    }
+}
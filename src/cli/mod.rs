@@ -1,5 +1,9 @@
 //! Command line interface module
 
+pub mod alias;
 pub mod args;
+pub mod ci;
+pub mod quick_action;
 
-pub use args::Args;
+pub use args::{Args, CacheCommand, Command, ConfigCommand, DaemonCommand, HistoryCommand, HookCommand, ModelsCommand};
+pub use quick_action::QuickAction;
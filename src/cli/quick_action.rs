@@ -0,0 +1,49 @@
+use anyhow::Result;
+use console::{Key, Term};
+
+/// Single-key choices offered when confirming a generated message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickAction {
+    Commit,
+    Edit,
+    Regenerate,
+    /// Regenerate after asking for extra instructions, e.g. "mention the migration"
+    RegenerateWithInstructions,
+    Copy,
+    ShowDiff,
+    Quit,
+}
+
+impl QuickAction {
+    /// Key hints shown above the prompt
+    pub const HELP: &'static str = "[y] commit  [e] edit  [r] regenerate  [i] regenerate with instructions  [c] copy  [s] show diff  [q] quit";
+    
+    /// Action bound to a key; Enter commits and Escape quits
+    pub fn from_key(key: &Key) -> Option<Self> {
+        match key {
+            Key::Enter => Some(QuickAction::Commit),
+            Key::Escape => Some(QuickAction::Quit),
+            Key::Char(c) => match c.to_ascii_lowercase() {
+                'y' => Some(QuickAction::Commit),
+                'e' => Some(QuickAction::Edit),
+                'r' => Some(QuickAction::Regenerate),
+                'i' => Some(QuickAction::RegenerateWithInstructions),
+                'c' => Some(QuickAction::Copy),
+                's' => Some(QuickAction::ShowDiff),
+                'q' | 'n' => Some(QuickAction::Quit),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    
+    /// Wait for a key press that maps to an action, ignoring any other key
+    pub fn read(term: &Term) -> Result<Self> {
+        term.write_line(Self::HELP)?;
+        loop {
+            if let Some(action) = Self::from_key(&term.read_key()?) {
+                return Ok(action);
+            }
+        }
+    }
+}
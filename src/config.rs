@@ -1,7 +1,52 @@
+use crate::formatting::{provenance, template, trailers};
+use crate::git::exclude::LOCKFILES;
+use crate::git::ChangeCategory;
+use crate::ollama::host::OllamaHost;
+use crate::utils::error::GitAiError;
 use anyhow::{Context, Result};
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Per-repository overrides, read from the repository root
+pub const REPO_CONFIG_FILE: &str = ".git-ai-commit.toml";
+
+/// Settings a repository's config may override
+/// 
+/// A cloned repository isn't trusted, so anything that decides where diffs
+/// and API keys are sent (provider, endpoints, backends) or what commands run
+/// (aliases) stays in the user's own config.
+pub const REPO_KEYS: &[&str] = &[
+    "model",
+    "template",
+    "max_files",
+    "max_diff_lines",
+    "validation",
+    "templates",
+    "context",
+    "exclude_paths",
+    "postprocess",
+    "trailers",
+    "co_authors",
+    "ticket",
+    "ai_trailer",
+    "provenance",
+    "fast_paths",
+    "summarize_diffs",
+    "chunk_large_commits",
+    "large_context_model",
+    "max_model_calls",
+    "scoring",
+    "project_context",
+    "readme_lines",
+    "hook_mode",
+    "include_body",
+    "mixed_languages",
+    "style",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -21,9 +66,944 @@ pub struct Config {
     #[serde(default = "default_port")]
     pub port: u16,
     
+    /// Ollama server elsewhere to use instead of starting one, e.g. `https://gpu-box:11434` (same as `--host`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    
+    /// Environment variable read for a bearer token sent to `host`
+    #[serde(default = "default_host_token_env")]
+    pub host_token_env: String,
+    
+    /// Extra headers sent to `host`, e.g. the API key a reverse proxy asks for
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub host_headers: BTreeMap<String, String>,
+    
+    /// PEM file of a CA to trust for `host`, e.g. a team's internal one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_ca_cert: Option<PathBuf>,
+    
     /// Timeout for AI generation in seconds
     #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: u64,
+    
+    /// Times a backend call is tried again after a dropped connection, a 429 or a 5xx (0 = never)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    
+    /// Describe trivial single-file changes without contacting the model
+    #[serde(default)]
+    pub fast_paths: bool,
+    
+    /// Keep the most informative lines of long diffs instead of truncating them
+    #[serde(default)]
+    pub summarize_diffs: bool,
+    
+    /// Summarize commits with more than `max_files` staged files in chunks instead of truncating
+    #[serde(default = "default_chunk_large_commits")]
+    pub chunk_large_commits: bool,
+    
+    /// Model used instead when a prompt is longer than the model's context window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large_context_model: Option<String>,
+    
+    /// Most model calls one run may make across retries, regenerations, chunks and ensembles (0 = no limit)
+    #[serde(default = "default_max_model_calls")]
+    pub max_model_calls: usize,
+    
+    /// Record generated messages and token usage in the local history file
+    #[serde(default = "default_history")]
+    pub history: bool,
+    
+    /// Open the generated message in the git editor before committing (same as `--edit`)
+    #[serde(default)]
+    pub edit: bool,
+    
+    /// Ask for a body of bullet points under the subject line (same as `--body`)
+    #[serde(default)]
+    pub include_body: bool,
+    
+    /// What to do when the staged code spans several languages
+    #[serde(default)]
+    pub mixed_languages: MixedLanguages,
+    
+    /// What is removed from prompts before they are sent to the model
+    #[serde(default)]
+    pub redaction: Redaction,
+    
+    /// How the prepare-commit-msg hook treats a message the user already started
+    #[serde(default)]
+    pub hook_mode: HookMode,
+    
+    /// Longest the prepare-commit-msg hook waits for the model, in seconds
+    #[serde(default = "default_hook_timeout_seconds")]
+    pub hook_timeout_seconds: u64,
+    
+    /// What the hook writes when the model doesn't answer in time
+    #[serde(default)]
+    pub hook_timeout_fallback: HookFallback,
+    
+    /// Rules generated messages are checked against before they are shown
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    
+    /// Describe the project (name, package and description) in the prompt
+    #[serde(default = "default_project_context")]
+    pub project_context: bool,
+    
+    /// Lines from the top of the README to include with the project context (0 to skip)
+    #[serde(default)]
+    pub readme_lines: usize,
+    
+    /// Bundled prompt and post-processing for a message convention (same as `--style`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<MessageStyle>,
+    
+    /// Prompt template file used instead of the default prompt; must contain `{CONTEXT}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+    
+    /// Git trailers (e.g. `Signed-off-by: ...`) appended to every message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailers: Vec<String>,
+    
+    /// People credited with a `Co-authored-by:` trailer on every message, as `Name <email>`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_authors: Vec<String>,
+    
+    /// Finding the ticket in the branch name and where the message mentions it
+    #[serde(default)]
+    pub ticket: TicketConfig,
+    
+    /// Name the model in an `AI-Generated-By:` trailer on messages it wrote
+    #[serde(default)]
+    pub ai_trailer: bool,
+    
+    /// Recording the tool version, model and template behind generated messages
+    #[serde(default)]
+    pub provenance: ProvenanceConfig,
+    
+    /// Prompt templates keyed by the dominant change category
+    #[serde(default)]
+    pub templates: CategoryTemplates,
+    
+    /// Shorthands expanded before the arguments are parsed, e.g. `wip = "--quick --add-unstaged"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub alias: BTreeMap<String, String>,
+    
+    /// Named settings picked with `--profile` or by matching the `origin` URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    
+    /// Keeping started Ollama servers warm between runs
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    
+    /// How competing messages are ranked
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    
+    /// Which blocks of repository context go into the prompt
+    #[serde(default)]
+    pub context: ContextBlocks,
+    
+    /// Globs of files only counted in the prompt context, not listed or diffed
+    #[serde(default = "default_exclude_paths")]
+    pub exclude_paths: Vec<String>,
+    
+    /// What is stripped from the model's answers before they are used
+    #[serde(default)]
+    pub postprocess: PostprocessSteps,
+    
+    /// Servers and models to fall back to when the primary one isn't available, in order of preference
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backends: Vec<Backend>,
+    
+    /// Where messages are generated
+    #[serde(default)]
+    pub provider: Provider,
+    
+    /// Settings of the Ollama server's generations
+    #[serde(default)]
+    pub ollama: OllamaConfig,
+    
+    /// Endpoint used when `provider = "openai"`
+    #[serde(default)]
+    pub openai: OpenAiConfig,
+    
+    /// Model file run when `provider = "local"`
+    #[serde(default)]
+    pub local: LocalConfig,
+    
+    /// How the repository is read
+    #[serde(default)]
+    pub git_backend: GitBackendKind,
+    
+    /// Features the backend is assumed to have or lack, whatever it reports
+    #[serde(default)]
+    pub capabilities: CapabilityOverrides,
+    
+    /// Prices and spending caps for paid APIs
+    #[serde(default)]
+    pub cost: CostConfig,
+}
+
+/// Service that generates the messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// The embedded or local Ollama server
+    #[default]
+    Ollama,
+    /// Any OpenAI-compatible chat completions API (OpenAI, OpenRouter, Groq, LM Studio,
+    /// vLLM, llama.cpp server, llamafile); also accepted as `generic-openai`
+    #[value(name = "openai", alias = "generic-openai")]
+    #[serde(alias = "generic-openai")]
+    OpenAi,
+    /// A GGUF model file run in-process, without any server (needs the
+    /// `local-gguf` build feature)
+    Local,
+}
+
+/// Capabilities set in the config, for servers that report them wrongly or not at all
+/// 
+/// Unset entries keep what was detected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_mode: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_messages: Option<bool>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embeddings: Option<bool>,
+    
+    /// Context window in tokens, for every model of the backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context: Option<u64>,
+}
+
+/// What generating on a paid API may cost, in dollars
+/// 
+/// Going over a cap needs `--force`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostConfig {
+    /// Most one run may spend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_run: Option<f64>,
+    
+    /// Most all runs of a calendar month (UTC) may spend together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_budget: Option<f64>,
+    
+    /// Prices by model name, over the built-in ones, e.g. `"gpt-4o-mini" = { input = 0.15, output = 0.6 }`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, ModelPrice>,
+}
+
+/// Dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Prompt tokens
+    pub input: f64,
+    /// Generated tokens
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// Dollars for `prompt_tokens` read and `generated_tokens` written
+    pub fn cost(&self, prompt_tokens: u64, generated_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.input + generated_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// Reader of the repository's status and diffs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitBackendKind {
+    /// Run `git` for every read
+    #[default]
+    Cli,
+    /// Read in-process with libgit2 (needs the `libgit2` build feature)
+    Libgit2,
+}
+
+/// Connection to an OpenAI-compatible API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiConfig {
+    /// API root, including the version, e.g. `http://localhost:1234/v1`
+    #[serde(default = "default_openai_base_url")]
+    pub base_url: String,
+    
+    /// API key; prefer `api_key_env` so the key stays out of the config file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    
+    /// Environment variable read for the API key when `api_key` isn't set
+    #[serde(default = "default_openai_api_key_env")]
+    pub api_key_env: String,
+    
+    /// Model used with this API instead of `model`, which usually names an Ollama model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    
+    /// Extra headers sent with every request, e.g. OpenRouter's `HTTP-Referer` and `X-Title`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_openai_base_url(),
+            api_key: None,
+            api_key_env: default_openai_api_key_env(),
+            model: None,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+impl OpenAiConfig {
+    /// The configured key, or the one in `api_key_env`; local servers usually need none
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key.clone()
+            .or_else(|| std::env::var(&self.api_key_env).ok())
+            .filter(|key| !key.is_empty())
+    }
+}
+
+/// How Ollama generates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    /// Sampling options sent with every generation
+    #[serde(default)]
+    pub options: GenerationOptions,
+}
+
+/// How the model picks the tokens of its answer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    /// Higher is more varied; 0 always picks the likeliest token
+    #[serde(default = "default_temperature")]
+    pub temperature: f64,
+    
+    /// Only sample from the likeliest tokens that make up this share of the probability
+    #[serde(default = "default_top_p")]
+    pub top_p: f64,
+    
+    /// Most tokens to generate; unset leaves it to the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<u32>,
+    
+    /// Seed for sampling, so the same prompt gets the same answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            temperature: default_temperature(),
+            top_p: default_top_p(),
+            num_predict: None,
+            seed: None,
+        }
+    }
+}
+
+/// A GGUF model run in-process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalConfig {
+    /// The `.gguf` file; llama (including Mistral), qwen2, qwen3 and gemma3 models are supported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<PathBuf>,
+    
+    /// The model's `tokenizer.json`; defaults to the one next to `model_path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer_path: Option<PathBuf>,
+    
+    /// The model's chat format around the prompt, which goes in place of `{PROMPT}`
+    #[serde(default = "default_local_template")]
+    pub template: String,
+    
+    /// Sampling temperature; 0 always picks the likeliest token
+    #[serde(default = "default_local_temperature")]
+    pub temperature: f64,
+    
+    /// Seed for sampling, so a run can be repeated
+    #[serde(default = "default_local_seed")]
+    pub seed: u64,
+}
+
+impl Default for LocalConfig {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            tokenizer_path: None,
+            template: default_local_template(),
+            temperature: default_local_temperature(),
+            seed: default_local_seed(),
+        }
+    }
+}
+
+impl LocalConfig {
+    /// The tokenizer to load: `tokenizer_path`, or `tokenizer.json` next to the model
+    pub fn tokenizer(&self) -> Option<PathBuf> {
+        self.tokenizer_path.clone().or_else(|| {
+            self.model_path.as_deref().map(|model| model.with_file_name("tokenizer.json"))
+        })
+    }
+    
+    /// Name the model goes by in messages and history: the file name without `.gguf`
+    pub fn model_name(&self) -> Option<&str> {
+        self.model_path.as_deref().and_then(Path::file_stem).and_then(|stem| stem.to_str())
+    }
+}
+
+/// Blocks of the prompt context, to trade signal against tokens
+/// 
+/// Everything but `stat_graph` is included by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextBlocks {
+    /// Current branch name
+    #[serde(default = "default_context_block")]
+    pub branch: bool,
+    
+    /// Subject of the previous commit
+    #[serde(default = "default_context_block")]
+    pub last_commit: bool,
+    
+    /// Files that will be committed
+    #[serde(default = "default_context_block")]
+    pub staged: bool,
+    
+    /// Modified files that will not be committed
+    #[serde(default = "default_context_block")]
+    pub unstaged: bool,
+    
+    /// New files git doesn't track yet
+    #[serde(default = "default_context_block")]
+    pub untracked: bool,
+    
+    /// Insertion and deletion counts, in total and per file
+    #[serde(default = "default_context_block")]
+    pub stats: bool,
+    
+    /// The patch itself, when one is available (--stdin-diff)
+    #[serde(default = "default_context_block")]
+    pub diff: bool,
+    
+    /// Messages previously committed in this repository, as style examples
+    #[serde(default = "default_context_block")]
+    pub history: bool,
+    
+    /// Public functions and types the patch removes or changes, with a request
+    /// to mark the commit as breaking
+    #[serde(default = "default_context_block")]
+    pub breaking: bool,
+    
+    /// Conventional scopes for the staged files, from the crates, packages and
+    /// modules of the repository
+    #[serde(default = "default_context_block")]
+    pub scopes: bool,
+    
+    /// Workspace packages the staged files are in, in a monorepo
+    #[serde(default = "default_context_block")]
+    pub packages: bool,
+    
+    /// Subjects of this many recent commits (`git log`) among the style examples (0 = none)
+    #[serde(default = "default_recent_commits")]
+    pub recent_commits: usize,
+    
+    /// The `git diff --stat` histogram (`src/main.rs | 12 ++++----`) instead of the
+    /// per-file counts; also shown in --dry-run output
+    #[serde(default)]
+    pub stat_graph: bool,
+}
+
+impl Default for ContextBlocks {
+    fn default() -> Self {
+        Self {
+            branch: true,
+            last_commit: true,
+            staged: true,
+            unstaged: true,
+            untracked: true,
+            stats: true,
+            diff: true,
+            history: true,
+            breaking: true,
+            scopes: true,
+            packages: true,
+            recent_commits: default_recent_commits(),
+            stat_graph: false,
+        }
+    }
+}
+
+/// Steps that clean up a model's answer, each on by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostprocessSteps {
+    /// `<think>...</think>` reasoning of models such as DeepSeek-R1 and Qwen3
+    #[serde(default = "default_postprocess_step")]
+    pub thinking: bool,
+    
+    /// Lead-ins such as `Commit message:` or `Here is the commit message:`
+    #[serde(default = "default_postprocess_step")]
+    pub prefixes: bool,
+    
+    /// Markdown code fences around the message
+    #[serde(default = "default_postprocess_step")]
+    pub code_fences: bool,
+    
+    /// Quotes or backticks around the whole message or its subject
+    #[serde(default = "default_postprocess_step")]
+    pub quotes: bool,
+    
+    /// Paragraphs after the message that explain it, e.g. `This commit message follows...`
+    #[serde(default = "default_postprocess_step")]
+    pub explanations: bool,
+}
+
+impl Default for PostprocessSteps {
+    fn default() -> Self {
+        Self {
+            thinking: true,
+            prefixes: true,
+            code_fences: true,
+            quotes: true,
+            explanations: true,
+        }
+    }
+}
+
+/// An Ollama server and the model to use on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Backend {
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub model: String,
+}
+
+impl Backend {
+    pub fn new(port: u16, model: impl Into<String>) -> Self {
+        Self { port, model: model.into() }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on port {}", self.model, self.port)
+    }
+}
+
+/// Ollama servers left running after a run so the next one skips the startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    /// Leave servers started by git-ai-commit running when it exits
+    #[serde(default)]
+    pub keep_warm: bool,
+    
+    /// Minutes a warm server may sit unused before it is shut down
+    #[serde(default = "default_idle_timeout_minutes")]
+    pub idle_timeout_minutes: u64,
+    
+    /// Seconds a started server has to answer before it is killed and the run fails
+    #[serde(default = "default_start_timeout_seconds")]
+    pub start_timeout_seconds: u64,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            keep_warm: false,
+            idle_timeout_minutes: default_idle_timeout_minutes(),
+            start_timeout_seconds: default_start_timeout_seconds(),
+        }
+    }
+}
+
+impl DaemonConfig {
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_minutes * 60)
+    }
+    
+    pub fn start_timeout(&self) -> Duration {
+        Duration::from_secs(self.start_timeout_seconds)
+    }
+}
+
+/// Weights of the scores that rank ensemble candidates and retries (0 leaves a score out)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    /// Passing the validation rules
+    #[serde(default = "default_validation_weight")]
+    pub validation: f64,
+    
+    /// Naming the changed files and symbols
+    #[serde(default = "default_specificity_weight")]
+    pub specificity: f64,
+    
+    /// Rating by the `--judge` model; above 0 the judge rates candidates instead of picking one
+    #[serde(default)]
+    pub judge: f64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            validation: default_validation_weight(),
+            specificity: default_specificity_weight(),
+            judge: 0.0,
+        }
+    }
+}
+
+/// Settings that override the top-level config for some repositories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    /// Remote URL patterns selecting this profile, e.g. `github.com/acme/*`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_mode: Option<HookMode>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<MessageStyle>,
+    
+    /// Stricter redaction for repositories whose code must not leak, e.g. `strict` for work
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<Redaction>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationConfig>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<CategoryTemplates>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailers: Option<Vec<String>>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    
+    /// Replaces the whole `[openai]` section, so each profile can use its own endpoint and key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai: Option<OpenAiConfig>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local: Option<LocalConfig>,
+    
+    /// Replaces the whole `[capabilities]` section, to go with the profile's endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<CapabilityOverrides>,
+}
+
+impl Profile {
+    /// Whether any of the profile's patterns matches a remote URL
+    pub fn matches_remote(&self, remote_url: &str) -> bool {
+        let remote = normalize_remote(remote_url);
+        self.remotes.iter().any(|pattern| remote_pattern_matches(pattern, &remote))
+    }
+}
+
+/// Reduce a remote URL to `host/path`, e.g. `git@github.com:acme/api.git` to `github.com/acme/api`
+pub fn normalize_remote(url: &str) -> String {
+    let url = url.trim();
+    let (url, has_scheme) = match url.split_once("://") {
+        Some((_, rest)) => (rest, true),
+        None => (url, false),
+    };
+    let url = url.split_once('@').map_or(url, |(_, host_and_path)| host_and_path);
+    
+    // scp-like `host:path` syntax
+    let url = if has_scheme { url.to_string() } else { url.replacen(':', "/", 1) };
+    
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url).to_lowercase()
+}
+
+/// Match a normalized remote against a pattern where `*` matches anything
+fn remote_pattern_matches(pattern: &str, remote: &str) -> bool {
+    let pattern = normalize_remote(pattern);
+    let regex = format!("^{}$", regex::escape(&pattern).replace(r"\*", ".*"));
+    regex::Regex::new(&regex).is_ok_and(|re| re.is_match(remote))
+}
+
+/// Validation of generated messages and what to do when they are rejected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    /// Longest allowed subject line, in characters
+    #[serde(default = "default_max_subject_length")]
+    pub max_subject_length: usize,
+    
+    /// How many times to ask again for a rejected message (0 disables retries)
+    #[serde(default = "default_retries")]
+    pub retries: usize,
+    
+    #[serde(default)]
+    pub retry_strategy: RetryStrategy,
+    
+    /// Words or phrases that must never appear in a commit message (case-insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_words: Vec<String>,
+    
+    #[serde(default)]
+    pub banned_word_action: BannedWordAction,
+    
+    /// Grammar the subject line has to follow
+    #[serde(default)]
+    pub commit_style: CommitStyle,
+    
+    /// When the staged files are all in one workspace package, its name must be the scope
+    #[serde(default)]
+    pub package_scope: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            max_subject_length: default_max_subject_length(),
+            retries: default_retries(),
+            retry_strategy: RetryStrategy::default(),
+            banned_words: Vec::new(),
+            banned_word_action: BannedWordAction::default(),
+            commit_style: CommitStyle::default(),
+            package_scope: false,
+        }
+    }
+}
+
+/// Grammar generated subjects are checked against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitStyle {
+    /// Any subject that passes the other rules
+    #[default]
+    Plain,
+    /// `type(scope)!: description` with a known type; near misses are repaired
+    Conventional,
+}
+
+/// Bundled prompt and post-processing for a common message convention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageStyle {
+    /// `type(scope): description` (Conventional Commits)
+    Conventional,
+    /// An emoji for the kind of change, then the summary (gitmoji.dev)
+    Gitmoji,
+    /// Conventional headers with Angular's types and a lowercase summary
+    Angular,
+    /// A capitalized imperative summary without any prefix
+    Plain,
+    /// `subsystem: summary` and a `Signed-off-by` trailer, as in the Linux kernel
+    Kernel,
+}
+
+impl MessageStyle {
+    /// Grammar generated subjects of this style are validated against
+    pub fn commit_style(&self) -> CommitStyle {
+        match self {
+            MessageStyle::Conventional | MessageStyle::Angular => CommitStyle::Conventional,
+            MessageStyle::Gitmoji | MessageStyle::Plain | MessageStyle::Kernel => CommitStyle::Plain,
+        }
+    }
+}
+
+/// What happens to a message containing a banned word
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BannedWordAction {
+    /// Retry, and refuse to commit if every attempt still contains one
+    #[default]
+    Reject,
+    /// Replace banned words with `***` before the message is shown
+    Mask,
+}
+
+/// How a rejected message is retried
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryStrategy {
+    /// Re-prompt with the rejected message and the rules it broke
+    #[default]
+    Feedback,
+    /// Send the same prompt again
+    Resample,
+}
+
+/// Ticket IDs taken from the branch name, e.g. `JIRA-1234` of `feature/JIRA-1234-add-login`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketConfig {
+    /// Regexes tried on the branch name in order; the first capture group is
+    /// the ticket, or the whole match without one
+    #[serde(default = "default_ticket_patterns")]
+    pub patterns: Vec<String>,
+    
+    /// Where the ticket goes in the message
+    #[serde(default)]
+    pub placement: TicketPlacement,
+    
+    /// Key of the trailer in `footer` placement
+    #[serde(default = "default_ticket_trailer")]
+    pub trailer: String,
+}
+
+impl Default for TicketConfig {
+    fn default() -> Self {
+        Self {
+            patterns: default_ticket_patterns(),
+            placement: TicketPlacement::default(),
+            trailer: default_ticket_trailer(),
+        }
+    }
+}
+
+/// Where the ticket of the branch goes in the message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketPlacement {
+    /// A trailer such as `Refs: JIRA-1234`
+    #[default]
+    Footer,
+    /// Before the description: `feat(auth): JIRA-1234 add login`
+    Subject,
+    /// Leave the message as the model wrote it
+    Off,
+}
+
+/// How hook mode handles messages that already have content (`-m`, templates)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookMode {
+    /// Keep the user's subject and append a generated body
+    #[default]
+    Merge,
+    /// Replace the existing message with a generated one
+    Replace,
+    /// Leave existing messages untouched
+    Keep,
+}
+
+/// Hook behavior when generation exceeds `hook_timeout_seconds`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFallback {
+    /// Write a message built from file names and change types
+    #[default]
+    Heuristic,
+    /// Leave the message file as git prepared it
+    Skip,
+}
+
+/// Handling of changes whose code spans several languages, e.g. a Rust
+/// backend and a TypeScript frontend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MixedLanguages {
+    /// Ask for a subject covering every component and a body paragraph per component
+    #[default]
+    Body,
+    /// Ask for a subject covering every component and suggest `--split`
+    Split,
+    /// Describe the change like any other
+    Off,
+}
+
+/// What is removed from prompts before they are sent to the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Redaction {
+    /// Prompts are sent as they are
+    #[default]
+    Off,
+    /// API keys, tokens, passwords and credentials in URLs are replaced
+    Secrets,
+    /// Secrets, email addresses and the home directory are replaced
+    Strict,
+}
+
+/// Where the record of what generated a message goes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProvenanceMode {
+    /// Don't record it
+    #[default]
+    Off,
+    /// `Generator*:` trailers at the end of the message
+    Footer,
+    /// A git note on the commit, leaving the message as it is
+    Notes,
+}
+
+/// Recording the tool version, model and template behind generated messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceConfig {
+    #[serde(default)]
+    pub mode: ProvenanceMode,
+    
+    /// Notes ref the record is added to in `notes` mode, as in `git notes --ref`
+    #[serde(default = "default_notes_ref")]
+    pub notes_ref: String,
+}
+
+impl Default for ProvenanceConfig {
+    fn default() -> Self {
+        Self {
+            mode: ProvenanceMode::default(),
+            notes_ref: default_notes_ref(),
+        }
+    }
+}
+
+/// Prompt templates used when every change falls into one category
+/// 
+/// Each template is the full prompt text, with the placeholders of
+/// [`crate::formatting::template`]. Categories without a template use the
+/// default prompt.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CategoryTemplates {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deps: Option<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci: Option<String>,
+    
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl CategoryTemplates {
+    /// Get the configured template for a category, if any
+    pub fn get(&self, category: ChangeCategory) -> Option<&str> {
+        let template = match category {
+            ChangeCategory::Docs => &self.docs,
+            ChangeCategory::Tests => &self.tests,
+            ChangeCategory::Deps => &self.deps,
+            ChangeCategory::Ci => &self.ci,
+            ChangeCategory::Code => &self.code,
+        };
+        template.as_deref()
+    }
 }
 
 fn default_model() -> String {
@@ -42,10 +1022,118 @@ fn default_port() -> u16 {
     11434
 }
 
+fn default_host_token_env() -> String {
+    "OLLAMA_API_KEY".to_string()
+}
+
 fn default_timeout_seconds() -> u64 {
     60
 }
 
+fn default_hook_timeout_seconds() -> u64 {
+    8
+}
+
+fn default_max_subject_length() -> usize {
+    72
+}
+
+fn default_retries() -> usize {
+    2
+}
+
+fn default_chunk_large_commits() -> bool {
+    true
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_max_model_calls() -> usize {
+    12
+}
+
+fn default_history() -> bool {
+    true
+}
+
+fn default_project_context() -> bool {
+    true
+}
+
+fn default_context_block() -> bool {
+    true
+}
+
+fn default_exclude_paths() -> Vec<String> {
+    LOCKFILES.iter().map(|lockfile| lockfile.to_string()).collect()
+}
+
+fn default_postprocess_step() -> bool {
+    true
+}
+
+fn default_recent_commits() -> usize {
+    8
+}
+
+fn default_ticket_patterns() -> Vec<String> {
+    vec![r"\b([A-Z]{2,}[A-Z0-9]*-[0-9]+)\b".to_string()]
+}
+
+fn default_ticket_trailer() -> String {
+    "Refs".to_string()
+}
+
+fn default_idle_timeout_minutes() -> u64 {
+    15
+}
+
+fn default_start_timeout_seconds() -> u64 {
+    30
+}
+
+fn default_notes_ref() -> String {
+    provenance::DEFAULT_NOTES_REF.to_string()
+}
+
+fn default_validation_weight() -> f64 {
+    1.0
+}
+
+fn default_specificity_weight() -> f64 {
+    0.5
+}
+
+fn default_openai_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_local_template() -> String {
+    "{PROMPT}".to_string()
+}
+
+fn default_temperature() -> f64 {
+    0.7
+}
+
+fn default_top_p() -> f64 {
+    0.9
+}
+
+fn default_local_temperature() -> f64 {
+    0.2
+}
+
+fn default_local_seed() -> u64 {
+    42
+}
+
+fn default_openai_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -53,61 +1141,208 @@ impl Default for Config {
             max_files: default_max_files(),
             max_diff_lines: default_max_diff_lines(),
             port: default_port(),
+            host: None,
+            host_token_env: default_host_token_env(),
+            host_headers: BTreeMap::new(),
+            host_ca_cert: None,
             timeout_seconds: default_timeout_seconds(),
+            max_retries: default_max_retries(),
+            fast_paths: false,
+            summarize_diffs: false,
+            chunk_large_commits: default_chunk_large_commits(),
+            large_context_model: None,
+            max_model_calls: default_max_model_calls(),
+            history: default_history(),
+            edit: false,
+            include_body: false,
+            mixed_languages: MixedLanguages::default(),
+            redaction: Redaction::default(),
+            hook_mode: HookMode::default(),
+            hook_timeout_seconds: default_hook_timeout_seconds(),
+            hook_timeout_fallback: HookFallback::default(),
+            validation: ValidationConfig::default(),
+            project_context: default_project_context(),
+            readme_lines: 0,
+            style: None,
+            template: None,
+            trailers: Vec::new(),
+            co_authors: Vec::new(),
+            ticket: TicketConfig::default(),
+            ai_trailer: false,
+            provenance: ProvenanceConfig::default(),
+            templates: CategoryTemplates::default(),
+            alias: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            daemon: DaemonConfig::default(),
+            scoring: ScoringConfig::default(),
+            context: ContextBlocks::default(),
+            exclude_paths: default_exclude_paths(),
+            postprocess: PostprocessSteps::default(),
+            backends: Vec::new(),
+            provider: Provider::default(),
+            ollama: OllamaConfig::default(),
+            openai: OpenAiConfig::default(),
+            local: LocalConfig::default(),
+            capabilities: CapabilityOverrides::default(),
+            cost: CostConfig::default(),
+            git_backend: GitBackendKind::default(),
         }
     }
 }
 
 impl Config {
-    /// Load configuration from the default location
-    pub fn load() -> Result<Self> {
+    /// Location of the config file, e.g. `~/.config/git-ai-commit/config.toml`
+    pub fn default_path() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .context("Could not find config directory")?
             .join("git-ai-commit");
+        Ok(config_dir.join("config.toml"))
+    }
+    
+    /// Load configuration from the default location, with the current repository's overrides
+    pub fn load() -> Result<Self> {
+        match std::env::current_dir() {
+            Ok(dir) => Self::load_for(&dir),
+            Err(_) => Self::load_layered(&Self::default_path()?, None),
+        }
+    }
+    
+    /// Load the user config and the config of the repository containing `dir`
+    pub fn load_for(dir: &Path) -> Result<Self> {
+        let config_path = Self::default_path()?;
+        tracing::debug!("Loading config from: {}", config_path.display());
         
-        std::fs::create_dir_all(&config_dir)
-            .context("Failed to create config directory")?;
-            
-        let config_path = config_dir.join("config.toml");
-        println!("Loading config from: {}", config_path.display());
+        let repo_config = repo_config_path(dir);
+        if let Some(ref repo_config) = repo_config {
+            tracing::debug!("Using repository config: {}", repo_config.display());
+        }
         
-        let config = Self::load_from_path(&config_path);
-        println!("Config loaded: {:?}", config);
+        let config = Self::load_layered(&config_path, repo_config.as_deref());
+        tracing::trace!("Config loaded: {:?}", config);
         config
     }
     
+    /// Load the user config at `global` and apply the repository config at `repo` on top
+    /// 
+    /// Tables are merged key by key. Settings outside [`REPO_KEYS`] are ignored
+    /// with a warning, and a relative `template` is resolved against the
+    /// repository root.
+    pub fn load_layered(global: &Path, repo: Option<&Path>) -> Result<Self> {
+        let mut document = read_table(global)?;
+        if let Some(repo) = repo {
+            for (key, value) in read_table(repo)? {
+                if !REPO_KEYS.contains(&key.as_str()) {
+                    tracing::warn!("{}: '{}' can only be set in {}", repo.display(), key, global.display());
+                    continue;
+                }
+                let value = match (key.as_str(), value, repo.parent()) {
+                    ("template", toml::Value::String(path), Some(root)) => {
+                        toml::Value::String(root.join(path).to_string_lossy().into_owned())
+                    }
+                    (_, value, _) => value,
+                };
+                merge_value(&mut document, key, value);
+            }
+        }
+        
+        let config: Self = toml::Value::Table(document)
+            .try_into()
+            .context("Failed to parse config file")?;
+        config.check_host_headers()?;
+        Ok(config)
+    }
+    
     /// Load configuration from a specific path
     pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
         
         // If the config file doesn't exist, return defaults
         if !path.exists() {
-            println!("Config file not found at: {}", path.display());
+            tracing::debug!("Config file not found at: {}", path.display());
             return Ok(Self::default());
         }
         
-        println!("Reading config from: {}", path.display());
+        tracing::debug!("Reading config from: {}", path.display());
         let config_content = fs::read_to_string(path)
             .context(format!("Failed to read config file: {}", path.display()))?;
             
-        println!("Config content: {}", config_content);
+        tracing::trace!("Config content: {}", config_content);
         let config: Self = toml::from_str(&config_content)
             .context("Failed to parse config file")?;
+        config.check_host_headers()?;
             
-        println!("Parsed config: {:?}", config);
+        tracing::trace!("Parsed config: {:?}", config);
         Ok(config)
     }
     
+    /// Name of the first profile whose remote patterns match `remote_url`
+    pub fn profile_for_remote(&self, remote_url: &str) -> Option<&str> {
+        self.profiles.iter()
+            .find(|(_, profile)| profile.matches_remote(remote_url))
+            .map(|(name, _)| name.as_str())
+    }
+    
+    /// Override settings with those the named profile sets
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self.profiles.get(name)
+            .cloned()
+            .ok_or_else(|| GitAiError::Config(format!("Unknown profile '{}'", name)))?;
+        
+        if let Some(model) = profile.model {
+            self.model = model;
+        }
+        if let Some(hook_mode) = profile.hook_mode {
+            self.hook_mode = hook_mode;
+        }
+        if let Some(style) = profile.style {
+            self.style = Some(style);
+        }
+        if let Some(redaction) = profile.redaction {
+            self.redaction = redaction;
+        }
+        if let Some(validation) = profile.validation {
+            self.validation = validation;
+        }
+        if let Some(templates) = profile.templates {
+            self.templates = templates;
+        }
+        if let Some(trailers) = profile.trailers {
+            self.trailers = trailers;
+        }
+        if let Some(provider) = profile.provider {
+            self.provider = provider;
+        }
+        if let Some(openai) = profile.openai {
+            self.openai = openai;
+        }
+        if let Some(local) = profile.local {
+            self.local = local;
+        }
+        if let Some(capabilities) = profile.capabilities {
+            self.capabilities = capabilities;
+        }
+        Ok(())
+    }
+    
+    /// Model to generate with through `provider`: `openai.model` when it is
+    /// set for an OpenAI-compatible API, the file name of `local.model_path`
+    /// for a local model, otherwise `model`
+    pub fn model_for(&self, provider: Provider) -> &str {
+        match provider {
+            Provider::OpenAi => self.openai.model.as_deref().unwrap_or(&self.model),
+            Provider::Local => self.local.model_name().unwrap_or(&self.model),
+            Provider::Ollama => &self.model,
+        }
+    }
+    
     /// Save the current configuration to the default location
     pub fn save(&self) -> Result<()> {
-        let config_dir = dirs::config_dir()
-            .context("Could not find config directory")?
-            .join("git-ai-commit");
-            
-        std::fs::create_dir_all(&config_dir)
-            .context("Failed to create config directory")?;
+        let config_path = Self::default_path()?;
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)
+                .context("Failed to create config directory")?;
+        }
             
-        let config_path = config_dir.join("config.toml");
         let config_content = toml::to_string_pretty(self)
             .context("Failed to serialize config")?;
             
@@ -117,3 +1352,253 @@ impl Config {
         Ok(())
     }
 }
+
+/// Settings left out of the serialized config until they are set
+const OPTIONAL_KEYS: &[&str] = &[
+    "host",
+    "host_ca_cert",
+    "style",
+    "template",
+    "large_context_model",
+    "openai.api_key",
+    "openai.model",
+    "local.model_path",
+    "local.tokenizer_path",
+    "templates.docs",
+    "templates.tests",
+    "templates.deps",
+    "templates.ci",
+    "templates.code",
+];
+
+/// Optional settings that hold whole numbers
+const OPTIONAL_INTEGER_KEYS: &[&str] = &[
+    "ollama.options.num_predict",
+    "ollama.options.seed",
+];
+
+/// Settings addressed by dotted keys, e.g. `validation.retries`, for `git-ai-commit config`
+/// 
+/// Only single values can be read and written this way; lists and tables such
+/// as `backends` and `profiles` are edited in the file.
+impl Config {
+    /// Every single-valued setting with its value in TOML syntax, sorted by key
+    pub fn entries(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.values()?
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect())
+    }
+    
+    /// Value of a dotted key, with strings unquoted
+    pub fn get_key(&self, key: &str) -> Result<String> {
+        match self.values()?.into_iter().find(|(name, _)| name == key) {
+            Some((_, toml::Value::String(value))) => Ok(value),
+            Some((_, value)) => Ok(value.to_string()),
+            None if is_free_form_key(key) => Err(GitAiError::Config(format!("'{}' is not set", key)).into()),
+            None => Err(unknown_key(key)),
+        }
+    }
+    
+    /// Set a dotted key from its command-line spelling, e.g. `("port", "11500")`
+    /// 
+    /// The value is parsed as the type the setting has, and the whole config
+    /// must still be valid afterwards; on error nothing changes.
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<()> {
+        let current = self.values()?;
+        let defaults = Self::default().values()?;
+        let expected = current.iter()
+            .chain(defaults.iter())
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value);
+        
+        let invalid = |expected: &str| GitAiError::Config(format!("Invalid value '{}' for {}: expected {}", value, key, expected));
+        let parsed = match expected {
+            Some(toml::Value::Integer(_)) => toml::Value::Integer(value.parse().map_err(|_| invalid("a whole number"))?),
+            Some(toml::Value::Float(_)) => toml::Value::Float(value.parse().map_err(|_| invalid("a number"))?),
+            Some(toml::Value::Boolean(_)) => toml::Value::Boolean(value.parse().map_err(|_| invalid("true or false"))?),
+            Some(_) => toml::Value::String(value.to_string()),
+            None if OPTIONAL_INTEGER_KEYS.contains(&key) => toml::Value::Integer(value.parse().map_err(|_| invalid("a whole number"))?),
+            None if is_free_form_key(key) => toml::Value::String(value.to_string()),
+            None => return Err(unknown_key(key)),
+        };
+        
+        let mut document = toml::Value::try_from(&*self).context("Failed to serialize config")?;
+        let (parents, name) = key.rsplit_once('.').map_or((None, key), |(parents, name)| (Some(parents), name));
+        let mut table = document.as_table_mut().context("Config is not a table")?;
+        for parent in parents.into_iter().flat_map(|parents| parents.split('.')) {
+            table = table.entry(parent)
+                .or_insert_with(|| toml::Value::Table(toml::value::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| unknown_key(key))?;
+        }
+        table.insert(name.to_string(), parsed);
+        
+        let config: Self = document.try_into()
+            .map_err(|e| GitAiError::Config(format!("Invalid value '{}' for {}: {}", value, key, e.message())))?;
+        config.check()?;
+        *self = config;
+        Ok(())
+    }
+    
+    /// Reject settings that parse but can't work
+    pub fn check(&self) -> Result<()> {
+        let rules = [
+            (self.port == 0, "port must be between 1 and 65535"),
+            (
+                self.host.as_deref().is_some_and(|host| OllamaHost::parse(host).is_err()),
+                "host must be a host name or an http:// or https:// URL",
+            ),
+            (self.timeout_seconds == 0, "timeout_seconds must be positive"),
+            (self.hook_timeout_seconds == 0, "hook_timeout_seconds must be positive"),
+            (self.max_files == 0, "max_files must be positive"),
+            (self.max_diff_lines == 0, "max_diff_lines must be positive"),
+            (self.daemon.idle_timeout_minutes == 0, "daemon.idle_timeout_minutes must be positive"),
+            (self.daemon.start_timeout_seconds == 0, "daemon.start_timeout_seconds must be positive"),
+            (self.validation.max_subject_length == 0, "validation.max_subject_length must be positive"),
+            (
+                [self.scoring.validation, self.scoring.specificity, self.scoring.judge].iter().any(|weight| *weight < 0.0),
+                "scoring weights must not be negative",
+            ),
+            (
+                !self.co_authors.iter().all(|author| trailers::is_identity(author)),
+                "co_authors must be given as \"Name <email>\"",
+            ),
+            (
+                self.ticket.patterns.iter().any(|pattern| regex::Regex::new(pattern).is_err()),
+                "ticket.patterns must be valid regular expressions",
+            ),
+            (
+                !trailers::is_key(&self.ticket.trailer),
+                "ticket.trailer must be a trailer key such as Refs",
+            ),
+            (
+                self.provenance.notes_ref.trim().is_empty(),
+                "provenance.notes_ref must not be empty",
+            ),
+            (
+                !(0.0..=2.0).contains(&self.ollama.options.temperature),
+                "ollama.options.temperature must be between 0 and 2",
+            ),
+            (
+                !(0.0..=1.0).contains(&self.ollama.options.top_p) || self.ollama.options.top_p == 0.0,
+                "ollama.options.top_p must be above 0 and at most 1",
+            ),
+            (self.ollama.options.num_predict == Some(0), "ollama.options.num_predict must be positive"),
+            (
+                !(0.0..=2.0).contains(&self.local.temperature),
+                "local.temperature must be between 0 and 2",
+            ),
+            (
+                !["http://", "https://"].iter().any(|scheme| self.openai.base_url.starts_with(scheme)),
+                "openai.base_url must start with http:// or https://",
+            ),
+            (self.capabilities.max_context == Some(0), "capabilities.max_context must be positive"),
+            (
+                [self.cost.max_per_run, self.cost.monthly_budget].iter().flatten().any(|cap| *cap < 0.0),
+                "cost.max_per_run and cost.monthly_budget must not be negative",
+            ),
+            (
+                self.cost.prices.values().any(|price| price.input < 0.0 || price.output < 0.0),
+                "cost.prices must not be negative",
+            ),
+        ];
+        if let Some((_, problem)) = rules.iter().find(|(broken, _)| *broken) {
+            return Err(GitAiError::Config(problem.to_string()).into());
+        }
+        self.check_host_headers()?;
+        self.check_templates()
+    }
+    
+    /// Reject `host_headers` that can't be sent; values may be keys, so only
+    /// the name is shown
+    pub fn check_host_headers(&self) -> Result<()> {
+        for (name, value) in &self.host_headers {
+            if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                return Err(GitAiError::Config(format!("host_headers: '{}' is not a valid header name", name)).into());
+            }
+            if HeaderValue::from_str(value).is_err() {
+                return Err(GitAiError::Config(format!("host_headers: the value of '{}' is not a valid header value", name)).into());
+            }
+        }
+        Ok(())
+    }
+    
+    /// Reject category templates with unknown placeholders or none showing the changes
+    pub fn check_templates(&self) -> Result<()> {
+        for category in ChangeCategory::ALL {
+            if let Some(text) = self.templates.get(category) {
+                template::check(text, &format!("templates.{}", category.key()))?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Single-valued settings as dotted keys
+    fn values(&self) -> Result<Vec<(String, toml::Value)>> {
+        let document = toml::Value::try_from(self).context("Failed to serialize config")?;
+        let mut values = Vec::new();
+        if let toml::Value::Table(table) = document {
+            flatten("", table, &mut values);
+        }
+        values.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(values)
+    }
+}
+
+/// Collect the leaves of `table` under `prefix`, skipping lists
+fn flatten(prefix: &str, table: toml::value::Table, values: &mut Vec<(String, toml::Value)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, values),
+            toml::Value::Array(_) => {}
+            value => values.push((key, value)),
+        }
+    }
+}
+
+/// Keys that are valid without a default: optional settings and aliases
+fn is_free_form_key(key: &str) -> bool {
+    OPTIONAL_KEYS.contains(&key)
+        || OPTIONAL_INTEGER_KEYS.contains(&key)
+        || key.strip_prefix("alias.").is_some_and(|name| !name.is_empty() && !name.contains('.'))
+}
+
+fn unknown_key(key: &str) -> anyhow::Error {
+    GitAiError::Config(format!("Unknown config key '{}'; see git-ai-commit config list", key)).into()
+}
+
+/// Repository config of the repository containing `start`, if it has one
+/// 
+/// The root is the nearest directory holding `.git`, which is a file rather
+/// than a directory in linked worktrees and submodules; each has its own config.
+pub fn repo_config_path(start: &Path) -> Option<PathBuf> {
+    let root = start.ancestors().find(|dir| dir.join(".git").exists())?;
+    let path = root.join(REPO_CONFIG_FILE);
+    path.is_file().then_some(path)
+}
+
+/// Top-level table of a TOML file; empty when the file doesn't exist
+fn read_table(path: &Path) -> Result<toml::value::Table> {
+    if !path.exists() {
+        return Ok(toml::value::Table::new());
+    }
+    let content = fs::read_to_string(path)
+        .context(format!("Failed to read config file: {}", path.display()))?;
+    toml::from_str(&content).context(format!("Failed to parse config file: {}", path.display()))
+}
+
+/// Set `key` in `table`, merging into an existing table instead of replacing it
+fn merge_value(table: &mut toml::value::Table, key: String, value: toml::Value) {
+    match (table.get_mut(&key), value) {
+        (Some(toml::Value::Table(existing)), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                merge_value(existing, key, value);
+            }
+        }
+        (_, value) => {
+            table.insert(key, value);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Checks behind `git-ai-commit doctor`
+//!
+//! Each check looks at one thing the tool depends on and says what to do
+//! when it is missing. Things set up on demand, like the Ollama server or a
+//! model download, only warn.
+
+use crate::config::{repo_config_path, Config, Provider, REPO_KEYS};
+use crate::git::clone::git_stdout;
+use crate::git::hooks::HOOK_MARKER;
+use crate::git::{lock, CloneInfo, GitCollector};
+use crate::ollama::host::OllamaHost;
+use crate::ollama::preflight::{model_installed, PREFLIGHT_TIMEOUT};
+use crate::ollama::{local, OllamaClient, OllamaClientTrait, OpenAiClient};
+use crate::utils::WriteMode;
+use std::fs;
+use std::path::Path;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One line of the doctor's report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Ok, detail: detail.into() }
+    }
+    
+    pub fn warn(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Warn, detail: detail.into() }
+    }
+    
+    pub fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Fail, detail: detail.into() }
+    }
+    
+    /// Report line, e.g. `[ OK ] git: git version 2.43.0`
+    pub fn render(&self) -> String {
+        let tag = match self.status {
+            CheckStatus::Ok => "[ OK ]",
+            CheckStatus::Warn => "[WARN]",
+            CheckStatus::Fail => "[FAIL]",
+        };
+        format!("{} {}: {}", tag, self.name, self.detail)
+    }
+}
+
+/// Whether git is installed, and which version
+pub async fn check_git(repo_path: &Path) -> Check {
+    match git_stdout(repo_path, &["--version"]).await {
+        Some(version) => Check::ok("git", version.trim()),
+        None => Check::fail("git", "git is not installed or not on PATH"),
+    }
+}
+
+/// The repository at `repo_path`: clone kind, index lock and commit hook
+pub async fn check_repository(repo_path: &Path) -> Vec<Check> {
+    let Some(toplevel) = git_stdout(repo_path, &["rev-parse", "--show-toplevel"]).await else {
+        return vec![Check::warn("repository", "not inside a git repository; run git-ai-commit from one or pass --repo")];
+    };
+    
+    let mut checks = vec![Check::ok("repository", toplevel.trim())];
+    
+    let clone = CloneInfo::detect(repo_path).await;
+    checks.push(match clone.warning() {
+        Some(warning) => Check::warn("clone", warning),
+        None => Check::ok("clone", "complete history"),
+    });
+    
+    if let Ok(index_lock) = lock::index_lock_path(repo_path).await {
+        if index_lock.exists() {
+            checks.push(Check::fail("index", lock::lock_error(&index_lock).to_string()));
+        }
+    }
+    
+    let git_collector = GitCollector::new(repo_path.to_path_buf()).with_write_mode(WriteMode::ReadOnly);
+    if let Ok(hooks_dir) = git_collector.hooks_dir().await {
+        let hook = fs::read_to_string(hooks_dir.join("prepare-commit-msg")).unwrap_or_default();
+        checks.push(if hook.contains(HOOK_MARKER) {
+            Check::ok("hook", "prepare-commit-msg hook installed")
+        } else {
+            Check::ok("hook", "not installed (optional: git-ai-commit hook install)")
+        });
+    }
+    
+    checks
+}
+
+/// Whether the config file at `path` exists and parses
+pub fn check_config(path: &Path) -> Check {
+    if !path.exists() {
+        return Check::ok("config", format!("{} not found, using defaults", path.display()));
+    }
+    match Config::load_from_path(path).and_then(|config| config.check()) {
+        Ok(()) => Check::ok("config", path.display().to_string()),
+        Err(e) => Check::fail("config", format!("{}: {:#}", path.display(), e)),
+    }
+}
+
+/// Whether the repository config, if there is one, parses and only sets what it may
+pub fn check_repo_config(repo_path: &Path) -> Option<Check> {
+    let path = repo_config_path(repo_path)?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return Some(Check::fail("repo config", format!("{}: {}", path.display(), e))),
+    };
+    let table = match toml::from_str::<toml::value::Table>(&content) {
+        Ok(table) => table,
+        Err(e) => return Some(Check::fail("repo config", format!("{}: {}", path.display(), e.message()))),
+    };
+    
+    let ignored: Vec<&str> = table.keys()
+        .map(String::as_str)
+        .filter(|key| !REPO_KEYS.contains(key))
+        .collect();
+    Some(if ignored.is_empty() {
+        Check::ok("repo config", path.display().to_string())
+    } else {
+        Check::warn("repo config", format!("{} sets {}, which only the user config may set", path.display(), ignored.join(", ")))
+    })
+}
+
+/// Whether the provider answers and offers `model`
+pub async fn check_provider(config: &Config, provider: Provider, host: &OllamaHost, model: &str) -> Vec<Check> {
+    let (client, name, location): (Box<dyn OllamaClientTrait>, _, _) = match provider {
+        Provider::Ollama => (
+            Box::new(OllamaClient::for_host(host).with_timeout(PREFLIGHT_TIMEOUT)),
+            "ollama",
+            if host.is_remote() { host.to_string() } else { format!("port {}", host.port()) },
+        ),
+        Provider::OpenAi => (
+            Box::new(OpenAiClient::from_config(&config.openai)),
+            "openai",
+            config.openai.base_url.clone(),
+        ),
+        // Nothing answers; the files are all there is to check before loading
+        Provider::Local => {
+            return match local::open(&config.local, None) {
+                Ok(client) => vec![
+                    Check::ok("local", local::describe(&config.local)),
+                    Check::ok("capabilities", client.capabilities(model).await.with_overrides(&config.capabilities).describe()),
+                ],
+                Err(e) => vec![Check::fail("local", e.to_string())],
+            };
+        }
+    };
+    
+    let models = match tokio::time::timeout(PREFLIGHT_TIMEOUT, client.list_models()).await {
+        Ok(Ok(models)) => models,
+        _ if provider == Provider::Ollama => {
+            return vec![Check::warn(name, format!("not running on {}; it is started when a message is generated", location))];
+        }
+        _ => return vec![Check::fail(name, format!("cannot reach {}; check openai.base_url and the API key", location))],
+    };
+    
+    let mut checks = vec![Check::ok(name, format!("reachable on {}", location))];
+    checks.push(match provider {
+        _ if model_installed(&models, model) => Check::ok("model", model.to_string()),
+        Provider::Ollama => Check::warn("model", format!("'{}' is not installed; it is downloaded on first use", model)),
+        Provider::OpenAi | Provider::Local => Check::fail("model", format!("'{}' is not offered by {}", model, location)),
+    });
+    if model_installed(&models, model) {
+        let capabilities = client.capabilities(model).await.with_overrides(&config.capabilities);
+        checks.push(Check::ok("capabilities", capabilities.describe()));
+    }
+    checks
+}
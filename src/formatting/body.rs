@@ -0,0 +1,81 @@
+//! Commit bodies listing the key changes
+//!
+//! With `--body` the model is asked for a subject line followed by bullet
+//! points. Models rarely keep to a line width, so the body is wrapped here
+//! before it is committed as its own paragraph.
+
+/// Width commit bodies are wrapped at, as `git log` and most tools expect
+pub const BODY_WIDTH: usize = 72;
+
+/// What the prompt asks for in `--body` mode
+pub const BODY_REQUEST: &str = "Message format: a subject line, then a blank line and a body of 2-5 bullet points \
+(\"- \") listing the key changes, each line at most 72 characters.";
+
+/// What the prompt asks for when the staged code spans several components
+pub const COMPONENTS_REQUEST: &str = "Write a subject that covers every component, not only the first one listed.";
+
+/// What the prompt asks for instead of [`BODY_REQUEST`] with `mixed_languages = "body"`
+pub const COMPONENTS_BODY_REQUEST: &str = "Message format: a subject line covering every component, then a blank \
+line and one short paragraph per component, each starting with its name (e.g. \"backend: ...\"), each line at \
+most 72 characters.";
+
+/// The subject line of `message` and its body, if it has one
+pub fn split(message: &str) -> (&str, Option<&str>) {
+    let message = message.trim();
+    match message.split_once('\n') {
+        Some((subject, body)) => {
+            let body = body.trim();
+            (subject.trim(), (!body.is_empty()).then_some(body))
+        }
+        None => (message, None),
+    }
+}
+
+/// `git commit` arguments passing the subject and the body as separate `-m` paragraphs
+pub fn commit_args(message: &str) -> Vec<&str> {
+    match split(message) {
+        (subject, Some(body)) => vec!["-m", subject, "-m", body],
+        (subject, None) => vec!["-m", subject],
+    }
+}
+
+/// Wrap the body of `message` at `width` characters, leaving the subject alone
+/// 
+/// Bullet points keep a hanging indent, so continuation lines line up with
+/// the text of their bullet. Lines that are already short enough, and words
+/// too long to break, such as URLs, are kept as they are.
+pub fn wrap(message: &str, width: usize) -> String {
+    let (subject, body) = split(message);
+    let Some(body) = body else {
+        return subject.to_string();
+    };
+    
+    let wrapped: Vec<String> = body.lines().map(|line| wrap_line(line, width)).collect();
+    format!("{}\n\n{}", subject, wrapped.join("\n"))
+}
+
+fn wrap_line(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    
+    let trimmed = line.trim_start();
+    let bullet = ["- ", "* "].iter().find(|bullet| trimmed.starts_with(**bullet));
+    let indent = line.len() - trimmed.len() + bullet.map_or(0, |bullet| bullet.len());
+    let hanging = " ".repeat(indent);
+    
+    let mut lines = Vec::new();
+    let mut current = line[..indent].to_string();
+    for word in line[indent..].split_whitespace() {
+        let at_start = current.len() == indent;
+        if !at_start && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(current);
+            current = hanging.clone();
+        } else if !at_start {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines.join("\n")
+}
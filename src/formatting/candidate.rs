@@ -0,0 +1,78 @@
+use crate::config::ValidationConfig;
+use crate::formatting::score::{Ranker, Score, ValidationScorer};
+use crate::formatting::validate::{check_package_scope, validate, validate_for_changes, Violation};
+use crate::git::GitInfo;
+use serde::{Serialize, Serializer};
+
+/// A generated commit message that competes with others
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candidate {
+    /// Model that produced the message
+    pub model: String,
+    pub message: String,
+    #[serde(serialize_with = "describe_violations")]
+    pub violations: Vec<Violation>,
+    /// Set once the candidate has been ranked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<Score>,
+}
+
+impl Candidate {
+    pub fn new(model: impl Into<String>, message: impl Into<String>, rules: &ValidationConfig) -> Self {
+        let message = message.into().trim().to_string();
+        let violations = validate(&message, rules);
+        Self {
+            model: model.into(),
+            message,
+            violations,
+            score: None,
+        }
+    }
+    
+    /// Like `new`, but also checks the message against the changes it describes
+    pub fn for_changes(
+        model: impl Into<String>,
+        message: impl Into<String>,
+        rules: &ValidationConfig,
+        git_info: &GitInfo,
+    ) -> Self {
+        let message = message.into().trim().to_string();
+        let violations = validate_for_changes(&message, rules, git_info);
+        Self {
+            model: model.into(),
+            message,
+            violations,
+            score: None,
+        }
+    }
+    
+    /// With `package_scope`, also require the scope to name the only package in `packages`
+    pub fn with_affected_packages(mut self, packages: &[String], rules: &ValidationConfig) -> Self {
+        if rules.package_scope && !self.violations.iter().any(Violation::is_unusable) {
+            self.violations.extend(check_package_scope(&self.message, packages));
+        }
+        self
+    }
+    
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+    
+    /// One-line label used when choosing between candidates
+    pub fn label(&self) -> String {
+        if self.violations.is_empty() {
+            format!("[{}] {}", self.model, self.subject())
+        } else {
+            format!("[{}] {} ({} issues)", self.model, self.subject(), self.violations.len())
+        }
+    }
+}
+
+/// Order candidates best first by validation alone, then original order
+pub fn rank(candidates: Vec<Candidate>) -> Vec<Candidate> {
+    Ranker::new().with_scorer(ValidationScorer, 1.0).rank(candidates)
+}
+
+fn describe_violations<S: Serializer>(violations: &[Violation], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(violations.iter().map(Violation::describe))
+}
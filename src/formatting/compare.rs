@@ -0,0 +1,53 @@
+use crate::formatting::candidate::Candidate;
+use std::collections::HashSet;
+
+/// Stacked view of several candidates with the words they don't share highlighted
+/// 
+/// A word is highlighted when at least one other candidate doesn't use it, so
+/// it's easy to see which candidate mentions which change. `highlight` decides
+/// how a word is marked (e.g. terminal colors).
+pub fn render_comparison(candidates: &[Candidate], highlight: impl Fn(&str) -> String) -> String {
+    let vocabularies: Vec<HashSet<String>> = candidates.iter()
+        .map(|c| c.message.split_whitespace().map(normalize).collect())
+        .collect();
+    let common: HashSet<String> = match vocabularies.split_first() {
+        Some((first, rest)) => first.iter()
+            .filter(|word| rest.iter().all(|other| other.contains(*word)))
+            .cloned()
+            .collect(),
+        None => HashSet::new(),
+    };
+    
+    let mut output = String::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        output.push_str(&format!("  {}. [{}]", i + 1, candidate.model));
+        if !candidate.violations.is_empty() {
+            output.push_str(&format!(" ({} issues)", candidate.violations.len()));
+        }
+        output.push('\n');
+        
+        for line in candidate.message.lines() {
+            let words: Vec<String> = line.split_whitespace()
+                .map(|word| {
+                    let key = normalize(word);
+                    if candidates.len() > 1 && !key.is_empty() && !common.contains(&key) {
+                        highlight(word)
+                    } else {
+                        word.to_string()
+                    }
+                })
+                .collect();
+            if words.is_empty() {
+                output.push('\n');
+            } else {
+                output.push_str(&format!("     {}\n", words.join(" ")));
+            }
+        }
+    }
+    output
+}
+
+/// Lowercase a word and strip surrounding punctuation
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
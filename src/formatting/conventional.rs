@@ -0,0 +1,109 @@
+//! Conventional commit headers: `type(scope)!: description`
+
+use crate::formatting::validate::Violation;
+use regex::Regex;
+
+/// Types accepted in a conventional commit header
+pub const COMMIT_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Header of a conventional commit subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalHeader {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl ConventionalHeader {
+    /// Parse a subject that follows the grammar exactly
+    pub fn parse(subject: &str) -> Option<Self> {
+        let header = Regex::new(r"^([a-z]+)(?:\(([^()\s][^()]*)\))?(!)?: (\S.*)$").unwrap();
+        let captures = header.captures(subject.trim())?;
+        Some(Self {
+            commit_type: captures[1].to_string(),
+            scope: captures.get(2).map(|scope| scope.as_str().to_string()),
+            breaking: captures.get(3).is_some(),
+            description: captures[4].to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for ConventionalHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.commit_type)?;
+        if let Some(ref scope) = self.scope {
+            write!(f, "({})", scope)?;
+        }
+        if self.breaking {
+            write!(f, "!")?;
+        }
+        write!(f, ": {}", self.description)
+    }
+}
+
+/// Why a subject isn't a conventional commit header, if it isn't one
+pub fn check(subject: &str) -> Option<Violation> {
+    match ConventionalHeader::parse(subject) {
+        Some(header) if COMMIT_TYPES.contains(&header.commit_type.as_str()) => None,
+        Some(header) => Some(Violation::UnknownType(header.commit_type)),
+        None => Some(Violation::NotConventional),
+    }
+}
+
+/// Known type for common misspellings and synonyms (`Feature`, `bugfix`, `docs`)
+fn normalize_type(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    let normalized = match word.as_str() {
+        "feature" | "features" => "feat",
+        "bug" | "bugfix" | "hotfix" | "fixes" => "fix",
+        "doc" | "documentation" => "docs",
+        "tests" | "testing" => "test",
+        "refactoring" => "refactor",
+        "performance" => "perf",
+        "chores" => "chore",
+        other => return COMMIT_TYPES.iter().find(|t| **t == other).copied(),
+    };
+    Some(normalized)
+}
+
+/// Rewrite a subject that almost follows the grammar
+/// 
+/// Fixes the type's case and synonyms, the separator (`fix - x`, `fix:x`) and a
+/// trailing period. A subject without any type gets `fallback_type`, when one
+/// is known; otherwise it is left for validation to reject. The body is kept.
+pub fn repair(message: &str, fallback_type: Option<&str>) -> String {
+    let message = message.trim();
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = subject.trim();
+    
+    let loose = Regex::new(r"^([A-Za-z]+)\s*(?:\(\s*([^()]*?)\s*\))?\s*(!)?\s*(?::|\s-)\s*(\S.*)$").unwrap();
+    let repaired = match loose.captures(subject) {
+        Some(captures) if normalize_type(&captures[1]).is_some() => {
+            let header = ConventionalHeader {
+                commit_type: normalize_type(&captures[1]).unwrap_or_default().to_string(),
+                scope: captures.get(2).map(|s| s.as_str().to_string()).filter(|s| !s.is_empty()),
+                breaking: captures.get(3).is_some(),
+                description: captures[4].to_string(),
+            };
+            header.to_string()
+        }
+        _ if ConventionalHeader::parse(subject).is_some() => subject.to_string(),
+        _ => match fallback_type {
+            Some(commit_type) => format!("{}: {}", commit_type, subject),
+            None => subject.to_string(),
+        },
+    };
+    let repaired = match repaired.strip_suffix('.') {
+        Some(stripped) if !repaired.ends_with("...") => stripped.to_string(),
+        _ => repaired,
+    };
+    
+    if body.is_empty() {
+        repaired
+    } else {
+        format!("{}\n{}", repaired, body)
+    }
+}
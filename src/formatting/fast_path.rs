@@ -0,0 +1,273 @@
+use crate::git::GitInfo;
+use crate::history::edit_distance;
+use regex::Regex;
+use std::path::Path;
+
+/// Lines starting with a comment marker that are code: C preprocessor
+/// directives, Rust attributes and shebangs
+const NOT_COMMENTS: &[&str] = &["#include", "#define", "#[", "#!"];
+
+/// Extensions of files where whitespace carries meaning, so re-indenting is never just formatting
+const WHITESPACE_SENSITIVE: &[&str] = &["py", "pyi", "yaml", "yml", "md", "markdown", "mk"];
+
+/// Rule that matched a trivial changeset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastPathRule {
+    VersionBump,
+    CommentTypo,
+    FormattingOnly,
+}
+
+impl FastPathRule {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            FastPathRule::VersionBump => "version bump",
+            FastPathRule::CommentTypo => "comment typo fix",
+            FastPathRule::FormattingOnly => "formatting-only change",
+        }
+    }
+}
+
+/// Deterministic commit message for a trivial changeset
+#[derive(Debug, Clone)]
+pub struct FastPathMessage {
+    pub rule: FastPathRule,
+    pub message: String,
+}
+
+/// Try to describe a trivial single-file change without asking the model
+/// 
+/// `patch` is the staged diff (`git diff --cached`). Returns `None` unless
+/// exactly one file is staged and its changes match one of the fast path rules.
+pub fn trivial_message(git_info: &GitInfo, patch: &str) -> Option<FastPathMessage> {
+    if git_info.status.staged_files.len() != 1 {
+        return None;
+    }
+    let file = git_info.status.staged_files[0].to_string_lossy().to_string();
+    
+    let hunks = hunks(patch)?;
+    let (removed, added) = (changed(&hunks, '-'), changed(&hunks, '+'));
+    if removed.is_empty() && added.is_empty() {
+        return None;
+    }
+    
+    if let Some(version) = version_bump(&file, &hunks) {
+        return Some(FastPathMessage {
+            rule: FastPathRule::VersionBump,
+            message: format!("chore: bump version to {}", version),
+        });
+    }
+    
+    if is_formatting_only(&file, &hunks) {
+        return Some(FastPathMessage {
+            rule: FastPathRule::FormattingOnly,
+            message: format!("style: format {}", file),
+        });
+    }
+    
+    if is_comment_typo(&file, &removed, &added) {
+        return Some(FastPathMessage {
+            rule: FastPathRule::CommentTypo,
+            message: format!("docs: fix typo in {} comment", file),
+        });
+    }
+    
+    None
+}
+
+/// One hunk of a patch
+struct Hunk<'a> {
+    /// First line of the hunk in the old file
+    old_start: usize,
+    /// Context, removed and added lines, each with its ` `, `-` or `+`
+    lines: Vec<&'a str>,
+}
+
+impl Hunk<'_> {
+    /// Context lines before the first change
+    fn leading_context(&self) -> Vec<&str> {
+        self.lines.iter()
+            .take_while(|line| line.starts_with(' ') || line.is_empty())
+            .map(|line| line.get(1..).unwrap_or_default())
+            .collect()
+    }
+    
+    /// Removed and added lines of each run of changes between context lines
+    fn changes(&self) -> Vec<(Vec<&str>, Vec<&str>)> {
+        let mut changes = Vec::new();
+        let mut current: (Vec<&str>, Vec<&str>) = (Vec::new(), Vec::new());
+        for line in &self.lines {
+            if let Some(content) = line.strip_prefix('-') {
+                current.0.push(content);
+            } else if let Some(content) = line.strip_prefix('+') {
+                current.1.push(content);
+            } else if !current.0.is_empty() || !current.1.is_empty() {
+                changes.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.0.is_empty() || !current.1.is_empty() {
+            changes.push(current);
+        }
+        changes
+    }
+}
+
+/// Split a single-file patch into its hunks
+/// 
+/// Returns `None` if the patch touches more than one file.
+fn hunks(patch: &str) -> Option<Vec<Hunk<'_>>> {
+    let mut files = 0;
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut in_hunk = false;
+    
+    for line in patch.lines() {
+        if line.starts_with("diff --git") {
+            files += 1;
+            in_hunk = false;
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@ -") {
+            let old_start = header.split([',', ' ']).next()?.parse().ok()?;
+            hunks.push(Hunk { old_start, lines: Vec::new() });
+            in_hunk = true;
+            continue;
+        }
+        // `\ No newline at end of file` belongs to no side
+        if !in_hunk || line.starts_with('\\') {
+            continue;
+        }
+        hunks.last_mut()?.lines.push(line);
+    }
+    
+    if files > 1 {
+        return None;
+    }
+    Some(hunks)
+}
+
+/// Lines of every hunk starting with `prefix`, without it
+fn changed<'a>(hunks: &[Hunk<'a>], prefix: char) -> Vec<&'a str> {
+    hunks.iter()
+        .flat_map(|hunk| hunk.lines.iter().filter_map(|line| line.strip_prefix(prefix)))
+        .collect()
+}
+
+/// Detect the package's own `version = "x.y.z"` line of a manifest being
+/// replaced, returning the new version
+/// 
+/// The context before the line has to show it is the package's: a
+/// `[package]` or `[project]` table in TOML, the top-level object in
+/// `package.json`. A dependency's version is left to the model.
+fn version_bump(file: &str, hunks: &[Hunk]) -> Option<String> {
+    let (removed, added) = (changed(hunks, '-'), changed(hunks, '+'));
+    if removed.len() != 1 || added.len() != 1 {
+        return None;
+    }
+    
+    let version = Regex::new(r#"^\s*"?version"?\s*[=:]\s*"([^"]+)"\s*,?\s*$"#).unwrap();
+    let old = version.captures(removed[0])?;
+    let new = version.captures(added[0])?;
+    if old[1] == new[1] {
+        return None;
+    }
+    
+    let hunk = hunks.iter().find(|hunk| !hunk.changes().is_empty())?;
+    let context = hunk.leading_context();
+    let is_own = match Path::new(file).file_name()?.to_str()? {
+        "Cargo.toml" => in_table(&context, &["package", "workspace.package"]),
+        "pyproject.toml" => in_table(&context, &["project", "tool.poetry"]),
+        // Only a hunk from the top of the file shows the line isn't nested
+        "package.json" => {
+            hunk.old_start == 1
+                && context.first().is_some_and(|line| line.trim() == "{")
+                && !context[1..].iter().any(|line| line.contains(['{', '}']))
+        }
+        _ => false,
+    };
+    is_own.then(|| new[1].to_string())
+}
+
+/// Whether the last TOML table header in `context` is one of `tables`
+fn in_table(context: &[&str], tables: &[&str]) -> bool {
+    context.iter()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+        .is_some_and(|table| tables.contains(&table.trim()))
+}
+
+/// Check whether the change only moves whitespace around
+/// 
+/// Each run of changes is compared on its own, with runs of whitespace
+/// collapsed to one space, so joining or splitting lines counts but removing
+/// the space between two words or moving a line elsewhere doesn't. Files
+/// where indentation carries meaning never qualify.
+fn is_formatting_only(file: &str, hunks: &[Hunk]) -> bool {
+    if is_whitespace_sensitive(file) {
+        return false;
+    }
+    let collapse = |lines: &[&str]| -> String {
+        lines.iter()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    hunks.iter()
+        .flat_map(|hunk| hunk.changes())
+        .all(|(removed, added)| collapse(&removed) == collapse(&added))
+}
+
+fn is_whitespace_sensitive(file: &str) -> bool {
+    let path = Path::new(file);
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+    WHITESPACE_SENSITIVE.contains(&extension.to_ascii_lowercase().as_str())
+        || matches!(path.file_name().and_then(|name| name.to_str()), Some("Makefile" | "makefile" | "GNUmakefile"))
+}
+
+/// Comment markers of the language of `file`, by its extension or name
+fn comment_prefixes(file: &str) -> &'static [&'static str] {
+    let path = Path::new(file);
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "java" | "kt" | "scala" | "swift" | "go" | "dart"
+        | "js" | "jsx" | "mjs" | "ts" | "tsx" | "php" | "scss" => &["//", "/*", "* "],
+        "css" => &["/*", "* "],
+        "py" | "rb" | "sh" | "bash" | "zsh" | "pl" | "r" | "toml" | "yaml" | "yml" | "cmake" | "ps1" => &["#"],
+        "sql" | "lua" | "hs" => &["--"],
+        "html" | "htm" | "xml" | "svg" | "vue" | "md" => &["<!--"],
+        _ => match path.file_name().and_then(|name| name.to_str()) {
+            Some("Makefile" | "Dockerfile" | "CMakeLists.txt") => &["#"],
+            _ => &[],
+        },
+    }
+}
+
+/// Check whether a single comment line had one word corrected
+fn is_comment_typo(file: &str, removed: &[&str], added: &[&str]) -> bool {
+    if removed.len() != 1 || added.len() != 1 {
+        return false;
+    }
+    
+    let (old, new) = (removed[0].trim(), added[0].trim());
+    let is_comment = |line: &str| {
+        comment_prefixes(file).iter().any(|prefix| line.starts_with(prefix))
+            && !NOT_COMMENTS.iter().any(|code| line.starts_with(code))
+    };
+    if !is_comment(old) || !is_comment(new) {
+        return false;
+    }
+    
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    if old_words.len() != new_words.len() {
+        return false;
+    }
+    
+    let differing: Vec<(&str, &str)> = old_words
+        .into_iter()
+        .zip(new_words)
+        .filter(|(a, b)| a != b)
+        .collect();
+    
+    differing.len() == 1 && edit_distance(differing[0].0, differing[0].1) <= 2
+}
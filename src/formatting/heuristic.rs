@@ -0,0 +1,66 @@
+use crate::git::files::ChangeType;
+use crate::git::{FileChange, GitInfo, LanguageGroup};
+use std::path::Path;
+
+/// Describe the staged changes without a model, from file names and change types
+/// 
+/// Used when the model is too slow or unavailable; the result is always a
+/// valid conventional commit subject, if not a very descriptive one.
+pub fn heuristic_message(git_info: &GitInfo) -> String {
+    let staged: Vec<&FileChange> = git_info.file_changes.iter()
+        .filter(|c| git_info.status.staged_files.contains(&c.file_path))
+        .collect();
+    
+    let verb = match staged.first().map(|c| &c.change_type) {
+        Some(first) if staged.iter().all(|c| std::mem::discriminant(&c.change_type) == std::mem::discriminant(first)) => {
+            match first {
+                ChangeType::Added => "add",
+                ChangeType::Deleted => "remove",
+                ChangeType::Renamed => "rename",
+                _ => "update",
+            }
+        }
+        _ => "update",
+    };
+    
+    let commit_type = git_info.dominant_category()
+        .commit_type()
+        .unwrap_or(if verb == "add" { "feat" } else { "chore" });
+    
+    let groups = git_info.language_groups();
+    let described = describe_components(&groups).unwrap_or_else(|| describe_files(&staged));
+    format!("{}: {} {}", commit_type, verb, described)
+}
+
+/// Name the components of a change spanning several languages, e.g. `backend and web`
+fn describe_components(groups: &[LanguageGroup]) -> Option<String> {
+    let mut components: Vec<&str> = Vec::new();
+    for group in groups {
+        if !components.contains(&group.component.as_str()) {
+            components.push(&group.component);
+        }
+    }
+    match components.split_last() {
+        Some((last, rest)) if !rest.is_empty() => Some(format!("{} and {}", rest.join(", "), last)),
+        _ => None,
+    }
+}
+
+/// Name a single file, or count files and name their common directory
+fn describe_files(changes: &[&FileChange]) -> String {
+    match changes {
+        [] => "files".to_string(),
+        [change] => change.file_path.display().to_string(),
+        _ => {
+            let parents: Vec<Option<&Path>> = changes.iter()
+                .map(|c| c.file_path.parent().filter(|p| !p.as_os_str().is_empty()))
+                .collect();
+            match parents[0] {
+                Some(dir) if parents.iter().all(|p| *p == Some(dir)) => {
+                    format!("{} files in {}", changes.len(), dir.display())
+                }
+                _ => format!("{} files", changes.len()),
+            }
+        }
+    }
+}
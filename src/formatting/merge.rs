@@ -0,0 +1,141 @@
+/// Line `git commit --verbose` puts above the diff, after the comment prefix;
+/// git removes it and everything below
+pub const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// Characters git chooses from for `core.commentChar = auto`, in its order
+const AUTO_COMMENT_CHARS: &[char] = &['#', ';', '@', '!', '$', '%', '^', '&', '|', ':'];
+
+/// Contents of a commit message file prepared by git
+/// 
+/// Git fills the file with the user's `-m`/template text followed by
+/// comment lines (starting with `#`, or `core.commentChar`) that it strips
+/// after editing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExistingMessage {
+    /// Non-comment text written by the user or a template
+    pub content: String,
+    /// Comment lines, kept verbatim so git's status summary survives
+    pub comments: String,
+}
+
+impl ExistingMessage {
+    /// Parse a message file whose comment lines start with `#`
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with(text, "#")
+    }
+    
+    /// Parse a message file whose comment lines start with `comment`
+    /// 
+    /// The scissors line of `git commit --verbose` and the diff below it
+    /// count as comments, whatever their lines start with.
+    pub fn parse_with(text: &str, comment: &str) -> Self {
+        let mut content = Vec::new();
+        let mut comments = Vec::new();
+        
+        let mut lines = text.lines();
+        for line in lines.by_ref() {
+            if is_scissors(line, comment) {
+                comments.push(line);
+                break;
+            }
+            if line.starts_with(comment) {
+                comments.push(line);
+            } else {
+                content.push(line);
+            }
+        }
+        comments.extend(lines);
+        
+        Self {
+            content: content.join("\n").trim().to_string(),
+            comments: comments.join("\n"),
+        }
+    }
+    
+    pub fn has_content(&self) -> bool {
+        !self.content.is_empty()
+    }
+    
+    /// First line of the user's message, if any
+    pub fn subject(&self) -> Option<&str> {
+        self.content.lines().next().map(str::trim).filter(|s| !s.is_empty())
+    }
+    
+    /// Render `message` followed by the preserved comment lines
+    pub fn render_with(&self, message: &str) -> String {
+        let mut output = format!("{}\n", message.trim());
+        if !self.comments.is_empty() {
+            output.push('\n');
+            output.push_str(&self.comments);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+fn is_scissors(line: &str, comment: &str) -> bool {
+    line.strip_prefix(comment).is_some_and(|rest| rest.trim() == SCISSORS)
+}
+
+/// What comment lines of the message file `text` start with, given the
+/// `core.commentString` or `core.commentChar` setting
+/// 
+/// Unset, it is `#`. With `auto` git picked the first of `#;@!$%^&|:` the
+/// message didn't use, so it is read back from the scissors line or the last
+/// comment line of the file.
+pub fn comment_prefix(configured: Option<&str>, text: &str) -> String {
+    match configured.map(str::trim) {
+        None | Some("") => "#".to_string(),
+        Some(setting) if setting.eq_ignore_ascii_case("auto") => {
+            let scissors = text.lines().find_map(|line| {
+                let (comment, rest) = line.split_at(line.find(char::is_whitespace)?);
+                (rest.trim() == SCISSORS).then_some(comment)
+            });
+            let last = text.lines().rev().find(|line| !line.trim().is_empty()).and_then(|line| line.chars().next());
+            let found = scissors
+                .map(str::to_string)
+                .or_else(|| last.filter(|c| AUTO_COMMENT_CHARS.contains(c)).map(String::from));
+            found.unwrap_or_else(|| "#".to_string())
+        }
+        Some(setting) => setting.to_string(),
+    }
+}
+
+/// Keep the user's message and append a generated body below it
+/// 
+/// Any body the user already wrote stays directly under the subject;
+/// generated text is only added, never substituted.
+pub fn merge_with_user_message(user_message: &str, generated_body: &str) -> String {
+    let user_message = user_message.trim();
+    let generated_body = generated_body.trim();
+    
+    if generated_body.is_empty() {
+        return user_message.to_string();
+    }
+    if user_message.is_empty() {
+        return generated_body.to_string();
+    }
+    format!("{}\n\n{}", user_message, generated_body)
+}
+
+/// Append git trailers (`Key: value`) the message doesn't already end with
+/// 
+/// New trailers join an existing trailer block, otherwise they start one
+/// after a blank line, as `git interpret-trailers` would.
+pub fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let message = message.trim();
+    let missing: Vec<&str> = trailers.iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty() && !message.lines().any(|line| line.trim() == *t))
+        .collect();
+    if missing.is_empty() {
+        return message.to_string();
+    }
+    
+    let trailer = regex::Regex::new(r"^[A-Za-z][A-Za-z0-9-]*: ").unwrap();
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or_default();
+    let ends_with_trailers = message.contains("\n\n") && last_paragraph.lines().all(|line| trailer.is_match(line));
+    
+    let separator = if ends_with_trailers { "\n" } else { "\n\n" };
+    format!("{}{}{}", message, separator, missing.join("\n"))
+}
@@ -1,5 +1,25 @@
 //! Prompt formatting and template management
 
 pub mod prompt;
+pub mod template;
+pub mod body;
+pub mod fast_path;
+pub mod heuristic;
+pub mod merge;
+pub mod validate;
+pub mod conventional;
+pub mod candidate;
+pub mod score;
+pub mod compare;
+pub mod summarize;
+pub mod raw;
+pub mod squash;
+pub mod trailers;
+pub mod ticket;
+pub mod provenance;
+pub mod split;
+pub mod style;
+pub mod structured;
+pub mod postprocess;
 
 pub use prompt::PromptBuilder;
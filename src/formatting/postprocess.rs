@@ -0,0 +1,154 @@
+//! Cleaning up a model's answer before it becomes a message
+//!
+//! Models wrap the message in things that don't belong in a commit: the
+//! reasoning of DeepSeek-R1 or Qwen3 in `<think>` tags, a `Commit message:`
+//! lead-in, a Markdown code fence, quotes, or a paragraph explaining the
+//! message. Each is stripped by its own step, in that order, and each can be
+//! turned off in `[postprocess]`. An answer with none of them is only trimmed.
+
+use crate::config::PostprocessSteps;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Openings of paragraphs that talk about the message rather than being part of it
+/// 
+/// Kept to phrases a commit body wouldn't start with; anything looser cut
+/// real bodies.
+const EXPLANATIONS: &[&str] = &[
+    "here is the commit message",
+    "here's the commit message",
+    "explanation:",
+    "i've ",
+];
+
+/// The answer with every enabled step applied
+pub fn clean(answer: &str, steps: &PostprocessSteps) -> String {
+    let mut text = answer.to_string();
+    if steps.thinking {
+        text = strip_thinking(&text);
+    }
+    if steps.prefixes {
+        text = strip_prefix(&text);
+    }
+    if steps.code_fences {
+        text = strip_code_fences(&text);
+    }
+    if steps.quotes {
+        text = strip_quotes(&text);
+    }
+    if steps.explanations {
+        text = strip_explanations(&text);
+    }
+    text.trim().to_string()
+}
+
+fn closing_think_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?i)</think(?:ing)?>").expect("valid closing think pattern"))
+}
+
+fn opening_think_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?i)<think(?:ing)?>").expect("valid opening think pattern"))
+}
+
+fn prefix_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)^[\s*#]*(?:here(?:'s| is| are)\b[^:\n]*|(?:the |a |your |suggested |proposed |generated )*(?:git )?commit message|subject(?: line)?)[\s*]*:[\s*]*",
+        )
+        .expect("valid prefix pattern")
+    })
+}
+
+fn fence_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"(?s)```[^\n`]*\n(.*?)\n?```").expect("valid fence pattern"))
+}
+
+/// The text after the reasoning; a template that opens the tag in the prompt
+/// leaves only its end in the answer, and an answer cut off while thinking
+/// has no message at all
+pub fn strip_thinking(text: &str) -> String {
+    if let Some(close) = closing_think_pattern().find_iter(text).last() {
+        return text[close.end()..].to_string();
+    }
+    match opening_think_pattern().find(text) {
+        Some(open) => text[..open.start()].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// The text without a lead-in such as `Commit message:` on its first line
+pub fn strip_prefix(text: &str) -> String {
+    let text = text.trim_start();
+    match prefix_pattern().find(text) {
+        Some(prefix) => text[prefix.end()..].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// The content of the first code fence, or the text without a fence that was never closed
+pub fn strip_code_fences(text: &str) -> String {
+    if let Some(captures) = fence_pattern().captures(text) {
+        return captures[1].to_string();
+    }
+    let trimmed = text.trim();
+    match trimmed.strip_prefix("```") {
+        Some(rest) => rest.split_once('\n').map_or("", |(_, rest)| rest).to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// The text without quotes around all of it, or else around its subject
+pub fn strip_quotes(text: &str) -> String {
+    let text = text.trim();
+    if let Some(inner) = unquote(text) {
+        return inner.trim().to_string();
+    }
+    let (subject, body) = text.split_once('\n').unwrap_or((text, ""));
+    match unquote(subject.trim()) {
+        Some(inner) if body.is_empty() => inner.to_string(),
+        Some(inner) => format!("{}\n{}", inner, body),
+        None => text.to_string(),
+    }
+}
+
+/// `text` without one pair of surrounding quotes, if it has them and no others
+fn unquote(text: &str) -> Option<&str> {
+    [('"', '"'), ('\'', '\''), ('`', '`'), ('“', '”'), ('‘', '’')]
+        .into_iter()
+        .find_map(|(open, close)| {
+            let inner = text.strip_prefix(open)?.strip_suffix(close)?;
+            // `"a" and "b"` starts and ends with quotes without being quoted
+            (!inner.is_empty() && !inner.contains(close)).then_some(inner)
+        })
+}
+
+fn footer_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"^(?:BREAKING[ -]CHANGE: |[A-Za-z][A-Za-z0-9-]*(?:: | #))").expect("valid footer pattern")
+    })
+}
+
+/// The text without the paragraphs at its end, after the subject, that explain the message
+/// 
+/// Only trailing paragraphs go, so an explanation followed by anything else,
+/// such as trailers or a footer, is kept as it is.
+pub fn strip_explanations(text: &str) -> String {
+    let mut paragraphs: Vec<&str> = text.trim_end().split("\n\n").collect();
+    while paragraphs.len() > 1 && paragraphs.last().is_some_and(|paragraph| is_explanation(paragraph)) {
+        paragraphs.pop();
+    }
+    paragraphs.join("\n\n")
+}
+
+/// Whether a paragraph opens like an explanation and holds no footer lines
+fn is_explanation(paragraph: &str) -> bool {
+    let mut lines = paragraph.trim().lines();
+    let opening = lines.next().unwrap_or_default().to_lowercase();
+    let explains = opening.starts_with("---") || EXPLANATIONS.iter().any(|explanation| opening.starts_with(explanation));
+    explains && !lines.any(|line| footer_pattern().is_match(line))
+}
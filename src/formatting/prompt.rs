@@ -1,10 +1,49 @@
-use crate::git::{GitInfo, FileChange};
+use crate::analysis::breaking;
+use crate::analysis::language::ProjectLayout;
+use crate::config::{CategoryTemplates, CommitStyle, ContextBlocks, MessageStyle, MixedLanguages};
+use crate::git::{ChangeCategory, CommitSummary, GitInfo, FileChange, ProjectInfo};
+use crate::git::diff::{DiffInfo, STAT_WIDTH};
+use crate::git::exclude::{self, PathFilter};
+use crate::git::merge::MergeState;
+use crate::git::files::is_scratch_file;
+use crate::git::patch::parse_patch;
+use crate::formatting::body::{BODY_REQUEST, COMPONENTS_BODY_REQUEST, COMPONENTS_REQUEST};
+use crate::formatting::candidate::Candidate;
+use crate::formatting::summarize::DiffSummarizer;
+use crate::formatting::template::{self, TemplateVars};
+use crate::formatting::validate::Violation;
+use std::path::Path;
+
+/// What the prompt asks for when the patch removes or changes public declarations
+const BREAKING_REQUEST: &str = "If callers have to change, mark the commit as breaking: put ! after the type or \
+scope of a conventional subject (e.g. \"feat(api)!: ...\"), and end the message with a footer \
+\"BREAKING CHANGE: <what callers must change>\".";
 
 /// Builds optimized prompts for AI commit message generation
+#[derive(Clone)]
 pub struct PromptBuilder {
     max_files: usize,
     max_diff_lines: usize,
     template: String,
+    category_templates: CategoryTemplates,
+    summarize_diffs: bool,
+    project: Option<ProjectInfo>,
+    quick: bool,
+    context_blocks: ContextBlocks,
+    examples: Vec<String>,
+    /// Subjects of the latest commits, from `git log`
+    recent_commits: Vec<String>,
+    instructions: Option<String>,
+    body: bool,
+    mixed_languages: MixedLanguages,
+    /// Staged diff filling `{DIFF}` in prompts built without a patch of their own
+    patch: Option<String>,
+    /// Files only counted in the context, not listed or diffed
+    excluded: PathFilter,
+    /// Packages of the repository, suggesting scopes for the staged files
+    layout: ProjectLayout,
+    /// The `--style` the template asks for, if any
+    style: Option<MessageStyle>,
 }
 
 impl PromptBuilder {
@@ -15,49 +54,610 @@ impl PromptBuilder {
             max_files,
             max_diff_lines,
             template,
+            category_templates: CategoryTemplates::default(),
+            summarize_diffs: false,
+            project: None,
+            quick: false,
+            context_blocks: ContextBlocks::default(),
+            examples: Vec::new(),
+            recent_commits: Vec::new(),
+            instructions: None,
+            body: false,
+            mixed_languages: MixedLanguages::default(),
+            patch: None,
+            excluded: PathFilter::default(),
+            layout: ProjectLayout::default(),
+            style: None,
+        }
+    }
+    
+    /// Use `template` instead of the default prompt; see [`template::check`]
+    /// for what it must contain
+    /// 
+    /// Category templates still take precedence for changesets they match.
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        if let Some(template) = template {
+            self.template = template;
         }
+        self
+    }
+    
+    /// Use the given templates for changesets with a single dominant category
+    pub fn with_category_templates(mut self, templates: CategoryTemplates) -> Self {
+        self.category_templates = templates;
+        self
+    }
+    
+    /// Keep the most informative diff lines of long files instead of the first ones
+    /// 
+    /// With a patch set (see `with_patch`), `build` then also shows the
+    /// summarized diff of each file.
+    pub fn with_diff_summary(mut self, enabled: bool) -> Self {
+        self.summarize_diffs = enabled;
+        self
+    }
+    
+    /// Describe the project (name, package, description) at the top of the context
+    pub fn with_project(mut self, project: Option<ProjectInfo>) -> Self {
+        self.project = project;
+        self
+    }
+    
+    /// Use a short prompt listing only the staged files, for fast WIP commits
+    pub fn with_quick(mut self, quick: bool) -> Self {
+        self.quick = quick;
+        self
+    }
+    
+    /// Choose which optional context blocks the prompt includes
+    pub fn with_context_blocks(mut self, blocks: ContextBlocks) -> Self {
+        self.context_blocks = blocks;
+        self
+    }
+    
+    /// Previous commit messages of the repository, shown as style examples
+    pub fn with_examples(mut self, examples: Vec<String>) -> Self {
+        self.examples = examples;
+        self
+    }
+    
+    /// Latest commits of the repository, listed before the examples so the
+    /// model follows the conventions the project uses now
+    pub fn with_recent_commits(mut self, commits: Vec<CommitSummary>) -> Self {
+        self.recent_commits = commits.into_iter().map(|commit| commit.subject).collect();
+        self
+    }
+    
+    /// Extra guidance from the user, added after the repository context
+    pub fn with_instructions(mut self, instructions: Option<String>) -> Self {
+        self.instructions = instructions.filter(|i| !i.trim().is_empty());
+        self
+    }
+    
+    /// Ask for a body of bullet points under the subject line
+    pub fn with_body(mut self, body: bool) -> Self {
+        self.body = body;
+        self
+    }
+    
+    /// How to describe changes whose code spans several languages
+    pub fn with_mixed_languages(mut self, mode: MixedLanguages) -> Self {
+        self.mixed_languages = mode;
+        self
+    }
+    
+    /// Staged diff for templates with a `{DIFF}` placeholder
+    pub fn with_patch(mut self, patch: Option<String>) -> Self {
+        self.patch = patch;
+        self
+    }
+    
+    /// Count files matching `excluded` instead of listing and diffing them
+    pub fn with_excluded_paths(mut self, excluded: PathFilter) -> Self {
+        self.excluded = excluded;
+        self
+    }
+    
+    /// Suggest scopes from the crates, packages and modules in `layout`
+    pub fn with_layout(mut self, layout: ProjectLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+    
+    /// Workspace packages the staged files are in, from the layout given to
+    /// `with_layout`; files matching `exclude_paths` are left out
+    pub fn affected_packages(&self, git_info: &GitInfo) -> Vec<String> {
+        let (_, staged) = self.staged_changes(git_info);
+        self.layout.affected_packages(staged.iter().map(|change| change.file_path.as_path()))
+    }
+    
+    /// The message style of the template, which decides whether the prompt
+    /// talks about conventional types and scopes
+    pub fn with_style(mut self, style: Option<MessageStyle>) -> Self {
+        self.style = style;
+        self
+    }
+    
+    /// Whether messages get a conventional `type(scope):` header; without a
+    /// `--style` the default prompt asks for one
+    pub fn uses_conventional_headers(&self) -> bool {
+        self.style.is_none_or(|style| style.commit_style() == CommitStyle::Conventional)
+    }
+    
+    /// Whether any template in use has a `{DIFF}` placeholder, so `with_patch` is needed
+    pub fn uses_diff(&self) -> bool {
+        ChangeCategory::ALL.iter()
+            .filter_map(|category| self.category_templates.get(*category))
+            .chain([self.template.as_str()])
+            .any(|template| template::uses(template, "DIFF"))
+    }
+    
+    /// Whether the prompt lists the components of a change spanning several languages
+    pub fn spans_components(&self, git_info: &GitInfo) -> bool {
+        self.mixed_languages != MixedLanguages::Off && git_info.language_groups().len() > 1
+    }
+    
+    /// Check whether a category-specific template applies to these changes
+    pub fn uses_category_template(&self, git_info: &GitInfo) -> bool {
+        self.category_templates.get(git_info.dominant_category()).is_some()
+    }
+    
+    /// The template `build` fills for these changes
+    pub fn template_for(&self, git_info: &GitInfo) -> String {
+        if self.quick {
+            return Self::quick_template();
+        }
+        self.category_templates
+            .get(git_info.dominant_category())
+            .unwrap_or(&self.template)
+            .to_string()
     }
     
     /// Build a comprehensive prompt from git information
     pub fn build(&self, git_info: &GitInfo) -> String {
+        if self.quick {
+            return Self::quick_template().replace("{CONTEXT}", &self.build_quick_context(git_info));
+        }
+        
+        // Summarized diffs are short enough for every prompt, not only `{DIFF}` templates
+        let context = match self.patch.as_deref() {
+            Some(patch) if self.summarize_diffs && !template::uses(&self.template_for(git_info), "DIFF") => {
+                self.build_patch_context(git_info, patch)
+            }
+            _ => self.build_context(git_info),
+        };
+        self.apply_template(git_info, &context, self.patch.as_deref())
+    }
+    
+    /// Build a prompt that also includes the content of a unified diff
+    /// 
+    /// At most `max_files` files are included, each truncated to `max_diff_lines` lines.
+    /// With diff summaries enabled, long files keep their `max_diff_lines` most
+    /// informative lines instead of the first ones.
+    pub fn build_with_patch(&self, git_info: &GitInfo, patch: &str) -> String {
+        let context = if template::uses(&self.template_for(git_info), "DIFF") {
+            self.build_context(git_info)
+        } else {
+            self.build_patch_context(git_info, patch)
+        };
+        self.apply_template(git_info, &context, Some(patch))
+    }
+    
+    /// Build a prompt for the squash-merge title and body of a whole branch
+    pub fn build_squash(&self, git_info: &GitInfo, commits: &[CommitSummary], patch: &str) -> String {
+        let mut context = format!("Commits on the branch ({}):\n", commits.len());
+        for commit in commits {
+            context.push_str(&format!("  - {}\n", commit.subject));
+        }
+        context.push('\n');
+        context.push_str(&self.build_patch_context(git_info, patch));
+        
+        Self::squash_template().replace("{CONTEXT}", &context)
+    }
+    
+    /// Build a prompt for the body of the merge commit finishing `merge`
+    /// 
+    /// `commits` are the ones being merged in; the subject stays the one git prepared.
+    pub fn build_merge(&self, git_info: &GitInfo, merge: &MergeState, commits: &[CommitSummary]) -> String {
+        let mut context = format!("Commits being merged ({}):\n", commits.len());
+        for commit in commits.iter().take(self.max_files) {
+            context.push_str(&format!("  - {}\n", commit.subject));
+        }
+        if commits.len() > self.max_files {
+            context.push_str(&format!("  ... and {} more commits\n", commits.len() - self.max_files));
+        }
+        
+        let resolved = merge.resolved(git_info);
+        if !resolved.is_empty() {
+            context.push_str("\nConflicts resolved by hand:\n");
+            for path in resolved {
+                context.push_str(&format!("  - {}\n", path.display()));
+            }
+        }
+        context.push('\n');
+        context.push_str(&self.build_context(git_info));
+        
+        Self::merge_template()
+            .replace("{SUBJECT}", merge.subject())
+            .replace("{CONTEXT}", &context)
+    }
+    
+    /// Context of `git_info` followed by the (truncated) diff of `patch`
+    fn build_patch_context(&self, git_info: &GitInfo, patch: &str) -> String {
+        let mut context = self.build_context(git_info);
+        let diff = self.render_diff(git_info, patch);
+        if self.context_blocks.diff && !diff.is_empty() {
+            context.push_str("\nDiff:\n");
+            context.push_str(&diff);
+        }
+        context
+    }
+    
+    /// The diff of `patch`, at most `max_files` files of `max_diff_lines` lines each
+    /// 
+    /// Binary files are described from `git_info` instead, e.g. `[binary image added, 2.3 MB]`.
+    fn render_diff(&self, git_info: &GitInfo, patch: &str) -> String {
+        let mut diff = String::new();
+        let summarizer = DiffSummarizer::new();
+        
+        let (excluded, file_patches): (Vec<_>, Vec<_>) = parse_patch(patch)
+            .into_iter()
+            .partition(|file_patch| self.excluded.is_excluded(&file_patch.file_path));
+        for file_patch in file_patches.iter().take(self.max_files) {
+            diff.push_str(&format!("--- {}\n", file_patch.file_path.display()));
+            if let Some(note) = file_patch.change_type.prompt_note() {
+                diff.push_str(&format!("{}\n", note));
+            }
+            let change = git_info.file_changes.iter().find(|change| change.file_path == file_patch.file_path);
+            if let Some(note) = change.and_then(FileChange::content_note) {
+                diff.push_str(&format!("[{}]\n", note));
+            } else if file_patch.binary {
+                diff.push_str(&format!("[binary file {}]\n", file_patch.change_type.label()));
+            }
+            
+            if self.summarize_diffs {
+                let (kept, omitted) = summarizer.summarize(&file_patch.lines, self.max_diff_lines);
+                for line in &kept {
+                    diff.push_str(line);
+                    diff.push('\n');
+                }
+                if omitted > 0 {
+                    diff.push_str(&format!("... ({} less informative lines omitted)\n", omitted));
+                }
+                continue;
+            }
+            
+            for line in file_patch.lines.iter().take(self.max_diff_lines) {
+                diff.push_str(line);
+                diff.push('\n');
+            }
+            if file_patch.lines.len() > self.max_diff_lines {
+                diff.push_str(&format!(
+                    "... ({} more lines truncated)\n",
+                    file_patch.lines.len() - self.max_diff_lines
+                ));
+            }
+        }
+        if file_patches.len() > self.max_files {
+            diff.push_str(&format!("... and {} more files\n", file_patches.len() - self.max_files));
+        }
+        if !excluded.is_empty() {
+            let paths: Vec<&Path> = excluded.iter().map(|file_patch| file_patch.file_path.as_path()).collect();
+            diff.push_str(&format!("... diff not shown for {}\n", exclude::summarize(&paths)));
+        }
+        
+        diff
+    }
+    
+    /// Build a prompt asking only for a body to go under the user's own subject
+    pub fn build_body(&self, git_info: &GitInfo, subject: &str) -> String {
+        let context = self.build_context(git_info);
+        Self::body_template()
+            .replace("{SUBJECT}", subject)
+            .replace("{CONTEXT}", &context)
+    }
+    
+    /// Ask again for a message after `rejected` broke the listed rules
+    pub fn build_retry(prompt: &str, rejected: &str, violations: &[Violation]) -> String {
+        let problems: Vec<String> = violations.iter()
+            .map(|v| format!("- {}", v.describe()))
+            .collect();
+        
+        format!(
+            "{}\n\nA previous answer was rejected:\n{}\n\nProblems:\n{}\n\n\
+             Write a corrected commit message that fixes every problem. \
+             Generate only the commit message, no additional explanation:",
+            prompt,
+            rejected.trim(),
+            problems.join("\n")
+        )
+    }
+    
+    /// Ask for a short summary of one chunk of a commit with many files
+    pub fn build_chunk_summary(&self, chunk: &GitInfo, index: usize, total: usize) -> String {
+        let context = self.build_context(chunk);
+        Self::chunk_template()
+            .replace("{INDEX}", &index.to_string())
+            .replace("{TOTAL}", &total.to_string())
+            .replace("{CONTEXT}", &context)
+    }
+    
+    /// Ask how the staged changes divide into logical commits, for `--split`
+    pub fn build_split(&self, git_info: &GitInfo) -> String {
+        Self::split_template().replace("{CONTEXT}", &self.build_context(git_info))
+    }
+    
+    /// Combine the chunk summaries of a large commit into one commit message prompt
+    pub fn build_synthesis(&self, git_info: &GitInfo, partials: &[String]) -> String {
+        let mut context = String::new();
+        if self.context_blocks.branch && !git_info.branch_name.is_empty() {
+            context.push_str(&format!("Current branch: {}\n", git_info.branch_name));
+        }
+        if self.context_blocks.stats {
+            context.push_str(&format!(
+                "\nDiff summary: {} files changed, {} insertions(+), {} deletions(-)\n",
+                git_info.diff_stat.files_changed,
+                git_info.diff_stat.insertions,
+                git_info.diff_stat.deletions
+            ));
+        }
+        
+        context.push_str("\nSummaries of the staged changes, in parts:\n");
+        for (i, partial) in partials.iter().enumerate() {
+            context.push_str(&format!("Part {}: {}\n", i + 1, partial));
+        }
+        
+        self.apply_template(git_info, &context, self.patch.as_deref())
+    }
+    
+    /// Ask a judge model to pick or merge the best of several candidates
+    pub fn build_judge(&self, git_info: &GitInfo, candidates: &[Candidate]) -> String {
+        Self::judge_template()
+            .replace("{CONTEXT}", &self.build_context(git_info))
+            .replace("{CANDIDATES}", &Self::list_candidates(candidates))
+    }
+    
+    /// Ask a judge model to rate every candidate from 0 to 10
+    /// 
+    /// The reply is read with [`crate::formatting::score::parse_judge_ratings`].
+    pub fn build_judge_scores(&self, git_info: &GitInfo, candidates: &[Candidate]) -> String {
+        Self::judge_scores_template()
+            .replace("{CONTEXT}", &self.build_context(git_info))
+            .replace("{CANDIDATES}", &Self::list_candidates(candidates))
+    }
+    
+    fn list_candidates(candidates: &[Candidate]) -> String {
+        let listed: Vec<String> = candidates.iter()
+            .enumerate()
+            .map(|(i, c)| format!("Candidate {}:\n{}", i + 1, c.message))
+            .collect();
+        listed.join("\n\n")
+    }
+    
+    /// Build a prompt summarizing a batch of commits about to be pushed
+    pub fn build_push_summary(&self, commits: &[CommitSummary], shortstat: &str) -> String {
+        let mut context = format!("Commits to be pushed ({}):\n", commits.len());
+        for commit in commits {
+            context.push_str(&format!("  - {}\n", commit.display()));
+        }
+        
+        if !shortstat.is_empty() {
+            context.push_str(&format!("\nOverall: {}\n", shortstat));
+        }
+        
+        Self::push_summary_template().replace("{CONTEXT}", &context)
+    }
+    
+    /// Fill the template for the dominant change category with the context,
+    /// and `{DIFF}` with `patch`
+    fn apply_template(&self, git_info: &GitInfo, context: &str, patch: Option<&str>) -> String {
+        let template = self.category_templates
+            .get(git_info.dominant_category())
+            .unwrap_or(&self.template);
+        let mut context = context.to_string();
+        let components = self.spans_components(git_info);
+        if components && self.mixed_languages == MixedLanguages::Body {
+            context.push_str(&format!("\n{}\n", COMPONENTS_BODY_REQUEST));
+        } else {
+            if self.body {
+                context.push_str(&format!("\n{}\n", BODY_REQUEST));
+            }
+            if components {
+                context.push_str(&format!("\n{}\n", COMPONENTS_REQUEST));
+            }
+        }
+        if let Some(patch) = patch.filter(|_| self.context_blocks.breaking && self.uses_conventional_headers()) {
+            context.push_str(&self.breaking_note(patch));
+        }
+        if let Some(ref instructions) = self.instructions {
+            context.push_str(&format!("\nAdditional instructions from the user:\n{}\n", instructions.trim()));
+        }
+        template::fill(template, &TemplateVars {
+            context,
+            diff: patch.map(|patch| self.render_diff(git_info, patch)).unwrap_or_default(),
+            branch: git_info.branch_name.clone(),
+            last_commits: self.last_commits(git_info),
+            files: self.staged_files(git_info),
+            language: git_info.language_groups().iter().map(|group| group.language).collect::<Vec<_>>().join(", "),
+        })
+    }
+    
+    /// Subjects of the recent commits, or the last one when none were loaded
+    fn last_commits(&self, git_info: &GitInfo) -> String {
+        let mut subjects = self.example_subjects();
+        if subjects.is_empty() {
+            subjects = git_info.last_commit.iter().map(|message| message.lines().next().unwrap_or_default()).collect();
+        }
+        subjects.iter().map(|subject| format!("- {}\n", subject)).collect()
+    }
+    
+    /// Subjects of the recent commits, then of examples not among them
+    fn example_subjects(&self) -> Vec<&str> {
+        let mut subjects: Vec<&str> = self.recent_commits.iter().map(String::as_str).collect();
+        for example in &self.examples {
+            let subject = example.lines().next().unwrap_or_default();
+            if !subjects.contains(&subject) {
+                subjects.push(subject);
+            }
+        }
+        subjects
+    }
+    
+    /// Staged files with how they changed, at most `max_files` of them
+    fn staged_files(&self, git_info: &GitInfo) -> String {
+        let (excluded, staged) = self.staged_changes(git_info);
+        let mut files: String = staged.iter()
+            .take(self.max_files)
+            .map(|change| format!("- {}\n", change.display()))
+            .collect();
+        if staged.len() > self.max_files {
+            files.push_str(&format!("... and {} more files\n", staged.len() - self.max_files));
+        }
+        files.push_str(&self.excluded_note(&excluded));
+        files
+    }
+    
+    /// The staged changes matching `exclude_paths`, and the others
+    fn staged_changes<'a>(&self, git_info: &'a GitInfo) -> (Vec<&'a FileChange>, Vec<&'a FileChange>) {
+        git_info.file_changes.iter()
+            .filter(|c| git_info.status.staged_files.contains(&c.file_path))
+            .partition(|c| self.excluded.is_excluded(&c.file_path))
+    }
+    
+    /// `Also changed (not shown): 1 lockfile updated`, or nothing when no file was excluded
+    fn excluded_note(&self, excluded: &[&FileChange]) -> String {
+        if excluded.is_empty() {
+            return String::new();
+        }
+        let paths: Vec<&Path> = excluded.iter().map(|change| change.file_path.as_path()).collect();
+        format!("Also changed (not shown): {}\n", exclude::summarize(&paths))
+    }
+    
+    /// Public declarations `patch` removes or changes, and how to mark the commit
+    /// as breaking; nothing when there are none
+    fn breaking_note(&self, patch: &str) -> String {
+        let changes: Vec<_> = breaking::detect(patch, |path| self.layout.is_rust_library(path))
+            .into_iter()
+            .filter(|change| !self.excluded.is_excluded(&change.path))
+            .collect();
+        if changes.is_empty() {
+            return String::new();
+        }
+        
+        let mut note = String::from("\nLikely breaking changes to the public API:\n");
+        for change in changes.iter().take(self.max_files) {
+            note.push_str(&format!("  - {}\n", change.describe()));
+        }
+        if changes.len() > self.max_files {
+            note.push_str(&format!("  ... and {} more\n", changes.len() - self.max_files));
+        }
+        note.push_str(BREAKING_REQUEST);
+        note.push('\n');
+        note
+    }
+    
+    /// Staged files and line counts only, keeping the prompt small
+    fn build_quick_context(&self, git_info: &GitInfo) -> String {
+        let (excluded, staged) = self.staged_changes(git_info);
+        
         let mut context = String::new();
+        for change in staged.iter().take(self.max_files) {
+            context.push_str(&format!("{}\n", change.display()));
+        }
+        if staged.len() > self.max_files {
+            context.push_str(&format!("... and {} more files\n", staged.len() - self.max_files));
+        }
+        context.push_str(&self.excluded_note(&excluded));
+        context.push_str(&format!(
+            "+{} -{} lines\n",
+            git_info.diff_stat.insertions, git_info.diff_stat.deletions
+        ));
+        context
+    }
+    
+    /// Describe the repository state for the prompt
+    fn build_context(&self, git_info: &GitInfo) -> String {
+        let mut context = String::new();
+        
+        if let Some(ref project) = self.project {
+            context.push_str(&project.context());
+        }
         
-        // Add branch information
-        context.push_str(&format!("Current branch: {}\n", git_info.branch_name));
+        // Add branch information (empty when describing a standalone patch)
+        if self.context_blocks.branch && !git_info.branch_name.is_empty() {
+            context.push_str(&format!("Current branch: {}\n", git_info.branch_name));
+        }
         
-        if let Some(ref last_commit) = git_info.last_commit {
+        if let Some(last_commit) = git_info.last_commit.as_ref().filter(|_| self.context_blocks.last_commit) {
             context.push_str(&format!("Last commit: {}\n", last_commit));
         }
         
         // Add file changes summary with diff line limits
         if !git_info.file_changes.is_empty() {
             // Group changes by staged/unstaged status
-            let staged_changes: Vec<_> = git_info.file_changes.iter()
-                .filter(|c| git_info.status.staged_files.contains(&c.file_path))
-                .collect();
+            let (excluded, staged_changes) = self.staged_changes(git_info);
+            let (excluded, staged_changes) = if self.context_blocks.staged {
+                (excluded, staged_changes)
+            } else {
+                (Vec::new(), Vec::new())
+            };
                 
             let unstaged_changes: Vec<_> = git_info.file_changes.iter()
-                .filter(|c| !git_info.status.staged_files.contains(&c.file_path))
+                .filter(|c| self.context_blocks.unstaged && !git_info.status.staged_files.contains(&c.file_path))
+                .filter(|c| !self.excluded.is_excluded(&c.file_path))
                 .collect();
             
             // Show staged changes first
-            if !staged_changes.is_empty() {
+            if !staged_changes.is_empty() || !excluded.is_empty() {
                 context.push_str("\nStaged changes (will be committed):\n");
                 self.add_file_changes_to_context(&mut context, &staged_changes);
+                context.push_str(&self.excluded_note(&excluded));
             }
             
             // Then show unstaged changes
             if !unstaged_changes.is_empty() {
                 if !staged_changes.is_empty() {
-                    context.push_str("\n");
+                    context.push('\n');
                 }
                 context.push_str("Unstaged changes (will NOT be committed):\n");
                 self.add_file_changes_to_context(&mut context, &unstaged_changes);
             }
         }
         
+        if git_info.is_mode_only() {
+            context.push_str(
+                "\nOnly file permissions changed. Use a chore or build type and describe the \
+                 permission change; do not describe new or fixed functionality.\n"
+            );
+        }
+        
+        if self.spans_components(git_info) {
+            context.push_str("\nThe staged code spans several components:\n");
+            for group in git_info.language_groups() {
+                context.push_str(&format!("  - {}: {} file(s)\n", group.label(), group.files.len()));
+            }
+        }
+        
+        if self.context_blocks.packages {
+            let packages = self.affected_packages(git_info);
+            if !packages.is_empty() {
+                context.push_str(&format!("\nAffected packages: {}\n", packages.join(", ")));
+            }
+        }
+        
+        if self.context_blocks.scopes && self.uses_conventional_headers() {
+            let (_, staged) = self.staged_changes(git_info);
+            let scopes = self.layout.suggest(staged.iter().map(|change| change.file_path.as_path()));
+            if !scopes.is_empty() {
+                context.push_str("\nScopes from the project layout (use one of these as the conventional scope):\n");
+                for scope in scopes.iter().take(self.max_files) {
+                    context.push_str(&format!("  - {}\n", scope.describe()));
+                }
+            }
+        }
+        
         // Add diff statistics
-        if git_info.diff_stat.files_changed > 0 {
+        if self.context_blocks.stats && git_info.diff_stat.files_changed > 0 {
             // Combined summary
             context.push_str(&format!(
                 "\nDiff summary: {} files changed, {} insertions(+), {} deletions(-)\n",
@@ -66,10 +666,20 @@ impl PromptBuilder {
                 git_info.diff_stat.deletions
             ));
             
-            // Detailed per-file statistics
-            if !git_info.diff_stat.file_stats.is_empty() {
+            // Detailed per-file statistics, without the excluded files
+            let diff_stat = DiffInfo {
+                file_stats: git_info.diff_stat.file_stats.iter()
+                    .filter(|stat| !self.excluded.is_excluded(Path::new(&stat.filename)))
+                    .cloned()
+                    .collect(),
+                ..git_info.diff_stat.clone()
+            };
+            if self.context_blocks.stat_graph {
+                context.push_str("\nDiff stat (git diff --stat):\n");
+                context.push_str(&diff_stat.stat_graph(STAT_WIDTH));
+            } else if !diff_stat.file_stats.is_empty() {
                 context.push_str("\nDetailed changes per file:\n");
-                for stat in &git_info.diff_stat.file_stats {
+                for stat in &diff_stat.file_stats {
                     context.push_str(&format!(
                         "  {}: {} insertions(+), {} deletions(-)\n",
                         stat.filename, stat.insertions, stat.deletions
@@ -78,24 +688,48 @@ impl PromptBuilder {
             }
         }
         
-        // Add untracked files summary (limited)
-        if !git_info.untracked_files.is_empty() {
-            context.push_str(&format!("\nUntracked files ({}): ", git_info.untracked_files.len()));
-            let untracked_display: Vec<_> = git_info.untracked_files
+        // Add untracked files summary (limited), most recent first and without scratch files
+        let (scratch, untracked): (Vec<_>, Vec<_>) = git_info.untracked_files
+            .iter()
+            .partition(|path| is_scratch_file(path));
+        if self.context_blocks.untracked && !untracked.is_empty() {
+            let summary = git_info.untracked_summary();
+            if summary.directories > 0 {
+                context.push_str(&format!(
+                    "\nUntracked files ({} files; {} new directories): ",
+                    summary.files.saturating_sub(scratch.len()), summary.directories
+                ));
+            } else {
+                context.push_str(&format!("\nUntracked files ({}): ", untracked.len()));
+            }
+            let untracked_display: Vec<_> = untracked
                 .iter()
                 .take(5)
                 .map(|p| p.to_string_lossy())
                 .collect();
             context.push_str(&untracked_display.join(", "));
             
-            if git_info.untracked_files.len() > 5 {
-                context.push_str(&format!(" and {} more", git_info.untracked_files.len() - 5));
+            if untracked.len() > 5 {
+                context.push_str(&format!(" and {} more", untracked.len() - 5));
+            }
+            if summary.unexpanded > 0 {
+                context.push_str(&format!(" ({} directories too large to list)", summary.unexpanded));
+            }
+            if !scratch.is_empty() {
+                context.push_str(&format!(" ({} editor/OS scratch files omitted)", scratch.len()));
             }
             context.push('\n');
         }
         
-        // Build final prompt
-        self.template.replace("{CONTEXT}", &context)
+        let subjects = self.example_subjects();
+        if self.context_blocks.history && !subjects.is_empty() {
+            context.push_str("\nRecent commit messages in this repository (match their style):\n");
+            for subject in subjects {
+                context.push_str(&format!("  - {}\n", subject));
+            }
+        }
+        
+        context
     }
     
     /// Helper method to add file changes to the context with proper formatting
@@ -117,6 +751,9 @@ impl PromptBuilder {
             total_diff_lines += estimated_lines;
             
             context.push_str(&format!("  - {}\n", change.display()));
+            if let Some(note) = change.change_type.prompt_note() {
+                context.push_str(&format!("    {}\n", note));
+            }
             
             // Add priority indicators
             if change.is_config_file() {
@@ -127,26 +764,137 @@ impl PromptBuilder {
         }
     }
     
-    fn default_template() -> String {
-        r#"You are an expert software developer creating a git commit message. 
+    fn body_template() -> String {
+        r#"You are an expert software developer completing a git commit message.
 
-Based on the following git repository changes, generate a concise, descriptive commit message that follows conventional commit format.
+The author already wrote this subject line:
+{SUBJECT}
 
 Repository Context:
 {CONTEXT}
 
+Guidelines for the commit body:
+1. Explain what changed and why in 1-3 short lines
+2. Wrap lines at 72 characters
+3. Do not repeat or rephrase the subject line
+4. Only describe changes that are staged for commit
+
+Generate only the commit body, no additional explanation:"#.to_string()
+    }
+    
+    fn chunk_template() -> String {
+        r#"You are an expert software developer reviewing part {INDEX} of {TOTAL} of a large commit.
+
+Repository Context:
+{CONTEXT}
+
+Summarize what changed in these files in one or two short sentences. This summary
+will be combined with the other parts into a single commit message.
+
+Generate only the summary, no additional explanation:"#.to_string()
+    }
+    
+    fn split_template() -> String {
+        r#"You are an expert software developer splitting staged changes into logical commits.
+
+Repository Context:
+{CONTEXT}
+
+Group the staged files into 1-5 commits that each make one logical change,
+e.g. a refactor, a feature, its tests, documentation. Keep files that only
+make sense together in the same commit, and order the commits so each one
+builds on the ones before it.
+
+Reply with one line per commit, the label, a colon and its files separated by commas:
+refactor: src/parser.rs, src/lexer.rs
+tests: tests/parser_test.rs
+
+Use every staged file exactly once. Generate only these lines, no additional explanation:"#.to_string()
+    }
+    
+    fn judge_template() -> String {
+        r#"You are an expert software developer reviewing candidate git commit messages.
+
+Repository Context:
+{CONTEXT}
+
+{CANDIDATES}
+
+Pick the candidate that describes the staged changes most accurately and follows
+conventional commit format, or merge the best parts of several candidates into one.
+
+Generate only the final commit message, no additional explanation:"#.to_string()
+    }
+    
+    fn judge_scores_template() -> String {
+        r#"You are an expert software developer reviewing candidate git commit messages.
+
+Repository Context:
+{CONTEXT}
+
+{CANDIDATES}
+
+Rate how accurately and specifically each candidate describes the staged changes,
+from 0 (wrong or meaningless) to 10 (could not be better).
+
+Answer with one line per candidate and nothing else, for example:
+Candidate 1: 7
+Candidate 2: 4"#.to_string()
+    }
+    
+    fn push_summary_template() -> String {
+        r#"You are an expert software developer reviewing a batch of commits before they are pushed.
+
+Summarize the following commits in one short paragraph (2-4 sentences) for the person pushing and their reviewers.
+
+{CONTEXT}
+
+Guidelines for the summary:
+1. Describe what the batch accomplishes as a whole, not each commit in turn
+2. Mention anything risky or surprising, such as reverts or work-in-progress commits
+3. Use plain prose without bullet points or headings
+
+Generate only the summary, no additional explanation:"#.to_string()
+    }
+    
+    fn squash_template() -> String {
+        r#"You are an expert software developer writing the squash-merge commit for a pull request.
+
+All commits of the branch below become a single commit. Describe the branch as a whole.
+
+{CONTEXT}
+
 Guidelines for the commit message:
-1. Use conventional commit format: type(scope): description
-2. Types: feat, fix, docs, style, refactor, test, chore
-3. Keep the first line under 50 characters
-4. Be specific about what changed and why
-5. Use imperative mood (e.g., "add" not "added")
-6. Focus on the most significant changes
-7. If there are breaking changes, mention them
-8. For config file changes, use "chore" type
-9. For test changes, use "test" type
-10. Only include changes that are staged for commit in the commit message
+1. First line: a conventional commit title (type(scope): description), imperative mood, under 72 characters, no period
+2. Then a blank line and a body of 2-5 sentences or short bullet points explaining what changed and why
+3. Describe the final result, not the history: leave out work-in-progress, fixup and review-feedback commits
+4. Don't list the commits one by one
 
 Generate only the commit message, no additional explanation:"#.to_string()
     }
+    
+    fn merge_template() -> String {
+        r#"You are an expert software developer writing the body of a merge commit.
+
+The subject line is already written: {SUBJECT}
+
+{CONTEXT}
+
+Guidelines for the body:
+1. Summarize what the merge brings in as a whole, in 2-4 sentences or short bullet points
+2. If conflicts were resolved by hand, name the files and say briefly how they were resolved
+3. Don't repeat the subject line and don't list the commits one by one
+
+Generate only the body, no subject line and no additional explanation:"#.to_string()
+    }
+    
+    fn quick_template() -> String {
+        r#"Write a one-line conventional commit message (type: description, under 50 characters) for these staged changes:
+{CONTEXT}
+Reply with the commit message only:"#.to_string()
+    }
+    
+    fn default_template() -> String {
+        template::default_template()
+    }
 }
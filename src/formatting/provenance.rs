@@ -0,0 +1,51 @@
+//! Recording what generated a commit message
+//!
+//! To reproduce a message later it helps to know the version of the tool,
+//! the model and the prompt template that produced it. They are recorded as
+//! `Generator*:` lines, either in a footer of the message or, leaving the
+//! message alone, in a git note on the commit.
+
+use sha2::{Digest, Sha256};
+
+/// Notes ref the record is written to unless `provenance.notes_ref` is set
+pub const DEFAULT_NOTES_REF: &str = "git-ai-commit";
+
+/// The tool, model and template behind a generated message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub version: String,
+    pub model: String,
+    /// First 12 hex digits of the SHA-256 of the prompt template
+    pub template_hash: String,
+}
+
+impl Provenance {
+    /// Record for a message `model` generated from `template` with this build of the tool
+    pub fn new(model: &str, template: &str) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            model: model.to_string(),
+            template_hash: short_hash(template),
+        }
+    }
+    
+    /// The record as trailer lines, e.g. `Generator: git-ai-commit/0.1.0`
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Generator: git-ai-commit/{}", self.version),
+            format!("Generator-Model: {}", self.model),
+            format!("Generator-Template: {}", self.template_hash),
+        ]
+    }
+    
+    /// The record as the text of a git note
+    pub fn note(&self) -> String {
+        self.lines().join("\n")
+    }
+}
+
+/// Short hash identifying a prompt or template; the same text always gives the same hash
+pub fn short_hash(text: &str) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect()
+}
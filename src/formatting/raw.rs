@@ -0,0 +1,45 @@
+use regex::Regex;
+
+/// Exact text printed for `--raw`: the message and a single trailing newline
+/// 
+/// Carriage returns, terminal escape codes, trailing whitespace and blank lines
+/// around the message are removed so nothing but the message is copied out of a
+/// terminal. With `no_wrap`, hard-wrapped body paragraphs are joined into single
+/// lines; list items and the subject stay on their own lines.
+pub fn raw_message(message: &str, no_wrap: bool) -> String {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap();
+    let message = ansi.replace_all(message, "");
+    
+    let lines: Vec<&str> = message
+        .lines()
+        .map(|line| line.trim_end_matches(['\r', ' ', '\t']))
+        .collect();
+    let text = lines.join("\n");
+    let text = text.trim_matches('\n');
+    
+    let text = if no_wrap { unwrap_paragraphs(text) } else { text.to_string() };
+    format!("{}\n", text)
+}
+
+/// Join the lines of each body paragraph, keeping the subject and list items separate
+fn unwrap_paragraphs(text: &str) -> String {
+    let list_item = Regex::new(r"^\s*([-*+]|\d+[.)])\s").unwrap();
+    let mut output: Vec<String> = Vec::new();
+    
+    for (i, line) in text.lines().enumerate() {
+        let continues_previous = i > 1
+            && !line.trim().is_empty()
+            && !list_item.is_match(line)
+            && output.last().is_some_and(|previous| !previous.trim().is_empty());
+        
+        match output.last_mut() {
+            Some(previous) if continues_previous => {
+                previous.push(' ');
+                previous.push_str(line.trim());
+            }
+            _ => output.push(line.to_string()),
+        }
+    }
+    
+    output.join("\n")
+}
@@ -0,0 +1,206 @@
+//! Scoring of competing commit messages
+//!
+//! Ensembles, `--candidates` and validation retries all leave several
+//! messages to choose from. Each [`Scorer`] rates one aspect of a message
+//! between 0 and 1; a [`Ranker`] adds up the weighted points and orders the
+//! candidates best first. A message that is empty or a refusal always scores 0.
+
+use crate::config::ScoringConfig;
+use crate::formatting::candidate::Candidate;
+use crate::formatting::validate::Violation;
+use crate::git::GitInfo;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Mentions of changed files or symbols that earn full specificity points
+const SPECIFIC_MENTIONS: usize = 3;
+
+/// One aspect of a message worth points
+pub trait Scorer: Send + Sync {
+    /// Name shown next to the points, e.g. in `--verbose` output
+    fn name(&self) -> &'static str;
+    
+    /// Points for `candidate`, from 0 to 1
+    fn score(&self, candidate: &Candidate) -> f64;
+}
+
+/// Points for each scorer and their weighted total
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Score {
+    pub total: f64,
+    pub parts: BTreeMap<&'static str, f64>,
+}
+
+impl Score {
+    /// Scores for `--verbose`, e.g. "1.33 (specificity 0.67, validation 1.00)"
+    pub fn display(&self) -> String {
+        let parts: Vec<String> = self.parts.iter()
+            .map(|(name, points)| format!("{} {:.2}", name, points))
+            .collect();
+        format!("{:.2} ({})", self.total, parts.join(", "))
+    }
+}
+
+/// Fewer validation problems score higher
+pub struct ValidationScorer;
+
+impl Scorer for ValidationScorer {
+    fn name(&self) -> &'static str {
+        "validation"
+    }
+    
+    fn score(&self, candidate: &Candidate) -> f64 {
+        (1.0 - 0.25 * candidate.violations.len() as f64).max(0.0)
+    }
+}
+
+/// Naming the changed files and symbols scores higher than vague wording
+pub struct SpecificityScorer {
+    terms: Vec<String>,
+}
+
+impl SpecificityScorer {
+    /// Terms a specific message would mention, all lowercase
+    pub fn new(terms: Vec<String>) -> Self {
+        Self { terms }
+    }
+    
+    /// File names of the changes, and the functions and types the patch adds or removes
+    pub fn for_changes(git_info: &GitInfo, patch: Option<&str>) -> Self {
+        let mut terms: Vec<String> = git_info.file_changes.iter()
+            .filter_map(|change| change.file_path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .filter(|stem| stem.len() > 2 && stem != "mod" && stem != "index")
+            .collect();
+        terms.extend(patch.map(changed_symbols).unwrap_or_default());
+        terms.sort();
+        terms.dedup();
+        Self::new(terms)
+    }
+}
+
+impl Scorer for SpecificityScorer {
+    fn name(&self) -> &'static str {
+        "specificity"
+    }
+    
+    fn score(&self, candidate: &Candidate) -> f64 {
+        let message = candidate.message.to_lowercase();
+        let mentions = self.terms.iter().filter(|term| message.contains(term.as_str())).count();
+        mentions.min(SPECIFIC_MENTIONS) as f64 / SPECIFIC_MENTIONS as f64
+    }
+}
+
+/// Ratings a judge model gave, by message
+pub struct JudgeScorer {
+    ratings: HashMap<String, f64>,
+}
+
+impl JudgeScorer {
+    /// Ratings from 0 to 1 for the messages of `candidates`, in the same order
+    pub fn new(candidates: &[Candidate], ratings: &[Option<f64>]) -> Self {
+        let ratings = candidates.iter()
+            .zip(ratings)
+            .filter_map(|(candidate, rating)| Some((candidate.message.clone(), (*rating)?)))
+            .collect();
+        Self { ratings }
+    }
+}
+
+impl Scorer for JudgeScorer {
+    fn name(&self) -> &'static str {
+        "judge"
+    }
+    
+    fn score(&self, candidate: &Candidate) -> f64 {
+        self.ratings.get(&candidate.message).copied().unwrap_or(0.0)
+    }
+}
+
+/// Weighted scorers that order candidates
+#[derive(Clone, Default)]
+pub struct Ranker {
+    scorers: Vec<(Arc<dyn Scorer>, f64)>,
+}
+
+impl Ranker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Validation and specificity, weighted as configured
+    pub fn from_config(config: &ScoringConfig, git_info: &GitInfo, patch: Option<&str>) -> Self {
+        Self::new()
+            .with_scorer(ValidationScorer, config.validation)
+            .with_scorer(SpecificityScorer::for_changes(git_info, patch), config.specificity)
+    }
+    
+    /// Add `scorer`, whose points are multiplied by `weight`; a weight of 0 leaves it out
+    pub fn with_scorer(mut self, scorer: impl Scorer + 'static, weight: f64) -> Self {
+        if weight > 0.0 {
+            self.scorers.push((Arc::new(scorer), weight));
+        }
+        self
+    }
+    
+    pub fn score(&self, candidate: &Candidate) -> Score {
+        if candidate.violations.iter().any(Violation::is_unusable) {
+            return Score::default();
+        }
+        let parts: BTreeMap<&'static str, f64> = self.scorers.iter()
+            .map(|(scorer, _)| (scorer.name(), scorer.score(candidate)))
+            .collect();
+        let total = self.scorers.iter()
+            .map(|(scorer, weight)| weight * parts[scorer.name()])
+            .sum();
+        Score { total, parts }
+    }
+    
+    /// Score every candidate and order them best first, keeping the original order on ties
+    pub fn rank(&self, candidates: Vec<Candidate>) -> Vec<Candidate> {
+        let mut candidates: Vec<Candidate> = candidates.into_iter()
+            .map(|mut candidate| {
+                candidate.score = Some(self.score(&candidate));
+                candidate
+            })
+            .collect();
+        candidates.sort_by(|a, b| total(b).total_cmp(&total(a)));
+        candidates
+    }
+}
+
+fn total(candidate: &Candidate) -> f64 {
+    candidate.score.as_ref().map_or(0.0, |score| score.total)
+}
+
+/// Names of the functions, types and classes declared on added or removed lines
+pub fn changed_symbols(patch: &str) -> Vec<String> {
+    let declaration = Regex::new(
+        r"\b(fn|def|class|struct|enum|trait|interface|type|function|func)\s+([A-Za-z_][A-Za-z0-9_]*)"
+    ).unwrap();
+    patch.lines()
+        .filter(|line| (line.starts_with('+') && !line.starts_with("+++")) || (line.starts_with('-') && !line.starts_with("---")))
+        .filter_map(|line| declaration.captures(line))
+        .map(|captures| captures[2].to_lowercase())
+        .filter(|name| name.len() > 2)
+        .collect()
+}
+
+/// Ratings from a judge's "Candidate N: score" lines, scaled from 0-10 to 0-1
+/// 
+/// Candidates the judge didn't rate get `None`.
+pub fn parse_judge_ratings(reply: &str, count: usize) -> Vec<Option<f64>> {
+    let line = Regex::new(r"(?i)candidate\s*#?(\d+)\s*[:=-]\s*(\d+(?:\.\d+)?)").unwrap();
+    let mut ratings = vec![None; count];
+    for captures in reply.lines().filter_map(|l| line.captures(l)) {
+        let (Ok(index), Ok(rating)) = (captures[1].parse::<usize>(), captures[2].parse::<f64>()) else {
+            continue;
+        };
+        if (1..=count).contains(&index) {
+            ratings[index - 1] = Some((rating / 10.0).clamp(0.0, 1.0));
+        }
+    }
+    ratings
+}
@@ -0,0 +1,31 @@
+//! Reading the commit plan the model proposes for `--split`
+//!
+//! The model answers with one line per commit, e.g.
+//! `tests: tests/parser_test.rs, tests/lexer_test.rs`. Models decorate their
+//! answers with bullets, numbers and backticks, which are ignored here; the
+//! plan itself is checked against the staged files by
+//! [`crate::git::plan::CommitPlan`].
+
+use std::path::PathBuf;
+
+/// The `(label, files)` groups of the model's answer, in order
+pub fn parse_groups(answer: &str) -> Vec<(String, Vec<PathBuf>)> {
+    answer.lines()
+        .filter_map(|line| {
+            let line = line.trim()
+                .trim_start_matches(|c: char| c.is_ascii_digit() || matches!(c, '-' | '*' | '.' | ')' | ' '));
+            let (label, files) = line.split_once(':')?;
+            let label = clean(label);
+            let files: Vec<PathBuf> = files.split(',')
+                .map(clean)
+                .filter(|file| !file.is_empty())
+                .map(PathBuf::from)
+                .collect();
+            (!label.is_empty() && !files.is_empty()).then_some((label, files))
+        })
+        .collect()
+}
+
+fn clean(text: &str) -> String {
+    text.trim().trim_matches(|c| matches!(c, '`' | '*' | '"' | '\'')).trim().to_string()
+}
@@ -0,0 +1,75 @@
+//! Squash-merge messages for a whole branch
+//!
+//! GitHub's squash merge turns a pull request into one commit whose title is
+//! the PR title followed by ` (#123)` and whose body, by default, lists every
+//! commit subject. Here the title and body are generated from the branch as a
+//! whole instead, and the `Co-authored-by:` trailers GitHub would add for the
+//! other authors are kept.
+
+use crate::formatting::{merge, trailers};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Title and body for the squashed commit of a branch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SquashMessage {
+    pub title: String,
+    pub body: String,
+}
+
+impl SquashMessage {
+    /// Split a generated message into title and body
+    /// 
+    /// `pr` appends GitHub's ` (#123)` reference to the title, and every
+    /// co-author is credited with a `Co-authored-by:` trailer.
+    pub fn new(generated: &str, pr: Option<u32>, co_authors: &[String]) -> Self {
+        let generated = generated.trim();
+        let (title, body) = generated.split_once('\n').unwrap_or((generated, ""));
+        
+        let mut title = title.trim().to_string();
+        if let Some(pr) = pr {
+            let reference = format!("(#{})", pr);
+            if !title.ends_with(&reference) {
+                title = format!("{} {}", title, reference);
+            }
+        }
+        
+        let trailers: Vec<String> = co_authors.iter().map(|author| trailers::co_authored_by(author)).collect();
+        let body = match body.trim() {
+            "" if trailers.is_empty() => String::new(),
+            "" => trailers.join("\n"),
+            body => merge::append_trailers(body, &trailers),
+        };
+        
+        Self { title, body }
+    }
+    
+    /// The full commit message, title and body separated by a blank line
+    pub fn render(&self) -> String {
+        if self.body.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{}\n\n{}", self.title, self.body)
+        }
+    }
+}
+
+/// Authors of the branch's commits other than `committer` (an email), as `Name <email>`
+/// 
+/// `authors` are `Name <email>` lines from `git log`; duplicates are dropped
+/// and the first spelling of each email is kept.
+pub fn co_authors(authors: &[String], committer: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    seen.insert(committer.trim().to_lowercase());
+    
+    authors.iter()
+        .map(|author| author.trim())
+        .filter(|author| {
+            let email = author.rsplit_once('<')
+                .map_or(*author, |(_, email)| email.trim_end_matches('>'))
+                .to_lowercase();
+            !author.is_empty() && seen.insert(email)
+        })
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,81 @@
+//! Messages answered as JSON
+//!
+//! Small models often wrap a message in chatter ("Here is your commit
+//! message:") that would be committed as written. Backends with a JSON mode
+//! are asked for the parts of a conventional commit instead, `{type, scope,
+//! subject, body, breaking}`, and the message is put together from them here,
+//! so nothing else ends up in it.
+
+use crate::formatting::conventional::{ConventionalHeader, COMMIT_TYPES};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Asks for the fields, after the rest of the prompt
+const JSON_REQUEST: &str = "Answer with a JSON object with these fields: \"type\" (one of feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert), \"scope\" (the area changed, or null), \"subject\" (the description after the type, in imperative mood), \"body\" (further details, or null) and \"breaking\" (true for breaking changes).";
+
+/// A commit message as the model answered it in JSON
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct StructuredMessage {
+    #[serde(rename = "type", default)]
+    pub commit_type: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub subject: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub breaking: bool,
+}
+
+/// JSON schema of the answer, for backends that constrain answers to one
+pub fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "type": { "type": "string", "enum": COMMIT_TYPES },
+            "scope": { "type": ["string", "null"] },
+            "subject": { "type": "string" },
+            "body": { "type": ["string", "null"] },
+            "breaking": { "type": "boolean" },
+        },
+        "required": ["type", "scope", "subject", "body", "breaking"],
+    })
+}
+
+/// `prompt`, asking for the answer as JSON
+pub fn request(prompt: &str) -> String {
+    format!("{}\n\n{}", prompt.trim_end(), JSON_REQUEST)
+}
+
+impl StructuredMessage {
+    /// Read the JSON object in `answer`, even inside a code fence or text
+    /// 
+    /// Answers without a subject aren't a message.
+    pub fn parse(answer: &str) -> Option<Self> {
+        let start = answer.find('{')?;
+        let end = answer.rfind('}')?;
+        let message: Self = serde_json::from_str(answer.get(start..=end)?).ok()?;
+        (!message.subject.trim().is_empty()).then_some(message)
+    }
+    
+    /// The message: a `type(scope)!: subject` header, then the body
+    pub fn render(&self) -> String {
+        let commit_type = self.commit_type.trim().to_lowercase();
+        let subject = self.subject.trim();
+        let header = if commit_type.is_empty() {
+            subject.to_string()
+        } else {
+            ConventionalHeader {
+                commit_type,
+                scope: self.scope.as_deref().map(str::trim).filter(|scope| !scope.is_empty()).map(str::to_string),
+                breaking: self.breaking,
+                description: subject.to_string(),
+            }
+            .to_string()
+        };
+        match self.body.as_deref().map(str::trim).filter(|body| !body.is_empty()) {
+            Some(body) => format!("{}\n\n{}", header, body),
+            None => header,
+        }
+    }
+}
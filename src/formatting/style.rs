@@ -0,0 +1,250 @@
+//! Bundled prompts for common message conventions (`--style`)
+//!
+//! Each style asks the model for its format and then tidies the answer,
+//! since small models often mix conventions: a `feat:` header becomes an
+//! emoji for gitmoji, a subsystem prefix for the kernel, and so on. The body
+//! is left as written.
+
+use crate::config::MessageStyle;
+use crate::formatting::conventional::{self, ConventionalHeader, COMMIT_TYPES};
+use crate::git::{ChangeCategory, GitInfo};
+use std::path::Component;
+
+/// Emoji and shortcode for each conventional type, from gitmoji.dev
+const GITMOJI: &[(&str, &str, &str)] = &[
+    ("feat", ":sparkles:", "✨"),
+    ("fix", ":bug:", "🐛"),
+    ("docs", ":memo:", "📝"),
+    ("style", ":art:", "🎨"),
+    ("refactor", ":recycle:", "♻️"),
+    ("perf", ":zap:", "⚡️"),
+    ("test", ":white_check_mark:", "✅"),
+    ("build", ":package:", "📦️"),
+    ("ci", ":construction_worker:", "👷"),
+    ("chore", ":wrench:", "🔧"),
+    ("revert", ":rewind:", "⏪️"),
+    ("deps", ":arrow_up:", "⬆️"),
+];
+
+/// Types Angular's guidelines allow, and what the others become
+const ANGULAR_TYPES: &[(&str, &str)] = &[("chore", "build"), ("style", "refactor")];
+
+/// The prompt of `style`, with a `{CONTEXT}` placeholder
+pub fn template(style: MessageStyle) -> &'static str {
+    match style {
+        MessageStyle::Conventional => CONVENTIONAL_TEMPLATE,
+        MessageStyle::Gitmoji => GITMOJI_TEMPLATE,
+        MessageStyle::Angular => ANGULAR_TEMPLATE,
+        MessageStyle::Plain => PLAIN_TEMPLATE,
+        MessageStyle::Kernel => KERNEL_TEMPLATE,
+    }
+}
+
+/// Bring the subject of a generated message into `style`
+pub fn finish(style: MessageStyle, message: &str, git_info: &GitInfo) -> String {
+    let category = git_info.dominant_category();
+    let message = message.trim();
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = match style {
+        MessageStyle::Conventional => return conventional::repair(message, category.commit_type()),
+        MessageStyle::Angular => angular(subject, category),
+        MessageStyle::Gitmoji => gitmoji(subject, category),
+        MessageStyle::Plain => plain(subject),
+        MessageStyle::Kernel => kernel(subject, git_info),
+    };
+    if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n{}", subject, body)
+    }
+}
+
+/// A conventional header with Angular's types and a lowercase summary
+fn angular(subject: &str, category: ChangeCategory) -> String {
+    let repaired = conventional::repair(subject, category.commit_type());
+    let Some(mut header) = ConventionalHeader::parse(&repaired) else {
+        return repaired;
+    };
+    if let Some((_, angular)) = ANGULAR_TYPES.iter().find(|(other, _)| *other == header.commit_type) {
+        header.commit_type = angular.to_string();
+    }
+    header.description = lowercase_first(&header.description);
+    header.to_string()
+}
+
+/// An emoji, then the summary; shortcodes and `type:` headers are converted
+fn gitmoji(subject: &str, category: ChangeCategory) -> String {
+    let subject = subject.trim();
+    if let Some((_, code, emoji)) = GITMOJI.iter().find(|(_, code, _)| subject.starts_with(code)) {
+        return format!("{} {}", emoji, capitalize(subject[code.len()..].trim()));
+    }
+    if subject.chars().next().is_some_and(|c| !c.is_ascii() && !c.is_alphanumeric()) {
+        return subject.to_string();
+    }
+    
+    let (commit_type, summary) = match summary_of(subject) {
+        Some(header) if header.commit_type == "chore" && header.scope.as_deref() == Some("deps") => {
+            ("deps".to_string(), header.description)
+        }
+        Some(header) => (header.commit_type, header.description),
+        None => (category_type(category).to_string(), subject.to_string()),
+    };
+    let emoji = GITMOJI.iter()
+        .find(|(name, _, _)| *name == commit_type)
+        .map_or("✨", |(_, _, emoji)| emoji);
+    format!("{} {}", emoji, capitalize(strip_period(&summary)))
+}
+
+/// A capitalized summary without any prefix
+fn plain(subject: &str) -> String {
+    let summary = summary_of(subject).map_or_else(|| subject.trim().to_string(), |header| header.description);
+    capitalize(strip_period(&summary))
+}
+
+/// `subsystem: summary`, the subsystem being the conventional scope or the
+/// directory every staged file is in
+fn kernel(subject: &str, git_info: &GitInfo) -> String {
+    let (scope, summary) = match summary_of(subject) {
+        Some(header) => (header.scope, header.description),
+        None => (None, subject.trim().to_string()),
+    };
+    // A summary that already names its subsystem, e.g. `net: fix leak`
+    if scope.is_none() && summary.split_once(": ").is_some_and(|(prefix, _)| !prefix.contains(' ')) {
+        return strip_period(&summary).to_string();
+    }
+    match scope.or_else(|| subsystem(git_info)) {
+        Some(subsystem) => format!("{}: {}", subsystem, lowercase_first(strip_period(&summary))),
+        None => capitalize(strip_period(&summary)),
+    }
+}
+
+/// The header of a subject with a known conventional type, after repairs
+fn summary_of(subject: &str) -> Option<ConventionalHeader> {
+    ConventionalHeader::parse(&conventional::repair(subject, None))
+        .filter(|header| COMMIT_TYPES.contains(&header.commit_type.as_str()))
+}
+
+/// Directory every staged file is in, e.g. `parser` for `src/parser/*`
+fn subsystem(git_info: &GitInfo) -> Option<String> {
+    let staged = &git_info.status.staged_files;
+    let first = staged.first()?;
+    let shared = first.parent()?
+        .components()
+        .enumerate()
+        .take_while(|(index, component)| staged.iter().all(|path| {
+            path.parent().and_then(|dir| dir.components().nth(*index)).as_ref() == Some(component)
+        }))
+        .filter_map(|(_, component)| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .last()?
+        .to_string();
+    Some(shared)
+}
+
+/// Conventional type implied by the category, without a scope
+fn category_type(category: ChangeCategory) -> &'static str {
+    match category {
+        ChangeCategory::Docs => "docs",
+        ChangeCategory::Tests => "test",
+        ChangeCategory::Deps => "deps",
+        ChangeCategory::Ci => "ci",
+        ChangeCategory::Code => "feat",
+    }
+}
+
+fn strip_period(text: &str) -> &str {
+    match text.strip_suffix('.') {
+        Some(stripped) if !text.ends_with("...") => stripped,
+        _ => text,
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Lowercase the first word unless it looks like a name (`README`, `JSON`)
+fn lowercase_first(text: &str) -> String {
+    let first_word = text.split_whitespace().next().unwrap_or_default();
+    if first_word.chars().skip(1).any(char::is_uppercase) {
+        return text.to_string();
+    }
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| first.to_lowercase().chain(chars).collect())
+}
+
+const CONVENTIONAL_TEMPLATE: &str = r#"You are an expert software developer writing a git commit message in the Conventional Commits format.
+
+Repository Context:
+{CONTEXT}
+
+Rules:
+1. Format the subject as type(scope): description
+2. Types: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert
+3. The scope is optional and names the part of the code that changed
+4. Add ! after the type or scope for breaking changes
+5. Keep the subject under 50 characters, in the imperative mood, without a final period
+6. Only describe changes that are staged for commit
+
+Generate only the commit message, no additional explanation:"#;
+
+const GITMOJI_TEMPLATE: &str = r#"You are an expert software developer writing a git commit message in the gitmoji format.
+
+Repository Context:
+{CONTEXT}
+
+Rules:
+1. Start the subject with one emoji for the kind of change:
+   ✨ new feature, 🐛 bug fix, 📝 documentation, 🎨 code structure or formatting,
+   ♻️ refactoring, ⚡️ performance, ✅ tests, 📦️ build or packages, 👷 CI,
+   🔧 configuration, ⬆️ dependency upgrade, ⏪️ revert
+2. Follow it with a space and a capitalized summary in the imperative mood
+3. Keep the subject under 50 characters, without a final period
+4. Only describe changes that are staged for commit
+
+Generate only the commit message, no additional explanation:"#;
+
+const ANGULAR_TEMPLATE: &str = r#"You are an expert software developer writing a git commit message following Angular's commit message guidelines.
+
+Repository Context:
+{CONTEXT}
+
+Rules:
+1. Format the subject as type(scope): summary
+2. Types: build, ci, docs, feat, fix, perf, refactor, test
+3. The scope names the affected package or area and is optional
+4. Write the summary in the present tense and imperative mood, starting with a lowercase letter
+5. Keep the subject under 50 characters, without a final period
+6. Only describe changes that are staged for commit
+
+Generate only the commit message, no additional explanation:"#;
+
+const PLAIN_TEMPLATE: &str = r#"You are an expert software developer writing a git commit message.
+
+Repository Context:
+{CONTEXT}
+
+Rules:
+1. Write one subject line summarizing the change, starting with a capitalized verb in the imperative mood (e.g. "Add", "Fix", "Remove")
+2. Do not prefix the subject with a type, scope, tag or emoji
+3. Keep the subject under 50 characters, without a final period
+4. Only describe changes that are staged for commit
+
+Generate only the commit message, no additional explanation:"#;
+
+const KERNEL_TEMPLATE: &str = r#"You are an expert software developer writing a git commit message in the style of the Linux kernel.
+
+Repository Context:
+{CONTEXT}
+
+Rules:
+1. Format the subject as subsystem: summary, where the subsystem is the component or directory that changed (e.g. "net: ", "docs: ", "parser: ")
+2. Write the summary in the imperative mood, starting with a lowercase letter, without a final period
+3. Keep the subject under 60 characters
+4. Do not use conventional commit types such as feat or fix
+5. Only describe changes that are staged for commit
+
+Generate only the commit message, no additional explanation:"#;
@@ -0,0 +1,96 @@
+use regex::Regex;
+
+/// Scores diff lines by how much they say about a change, without a model
+pub struct DiffSummarizer {
+    signature: Regex,
+    logging: Regex,
+    identifier: Regex,
+}
+
+impl Default for DiffSummarizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffSummarizer {
+    pub fn new() -> Self {
+        Self {
+            signature: Regex::new(
+                r"^\s*(pub(\([^)]*\))?\s+|export\s+|async\s+|public\s+|private\s+|protected\s+|static\s+)*(fn|def|class|struct|enum|trait|impl|interface|type|function|func|mod|const)\b",
+            ).unwrap(),
+            logging: Regex::new(
+                r#"(println!|eprintln!|(info|warn|error|debug|trace)!|console\.\w+|log(ger)?\.\w+|print\(|"[^"]*")"#,
+            ).unwrap(),
+            identifier: Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap(),
+        }
+    }
+    
+    /// Keep at most `top_k` of the most informative lines of one file's hunks
+    /// 
+    /// Lines keep their original order. Returns the kept lines and how many
+    /// were left out.
+    pub fn summarize(&self, lines: &[String], top_k: usize) -> (Vec<String>, usize) {
+        if lines.len() <= top_k {
+            return (lines.to_vec(), 0);
+        }
+        
+        let scores = self.score(lines);
+        let mut ranked: Vec<usize> = (0..lines.len()).filter(|&i| scores[i] > 0).collect();
+        ranked.sort_by(|&a, &b| scores[b].cmp(&scores[a]).then(a.cmp(&b)));
+        ranked.truncate(top_k);
+        ranked.sort_unstable();
+        
+        let kept: Vec<String> = ranked.iter().map(|&i| lines[i].clone()).collect();
+        let omitted = lines.len() - kept.len();
+        (kept, omitted)
+    }
+    
+    /// Score every line; zero means the line is never worth keeping
+    pub fn score(&self, lines: &[String]) -> Vec<u32> {
+        let mut scores: Vec<u32> = lines.iter().map(|line| self.score_line(line)).collect();
+        
+        // A removed line followed by an added line of the same shape is a rename
+        for i in 1..lines.len() {
+            let (prev, line) = (&lines[i - 1], &lines[i]);
+            if let (Some(old), Some(new)) = (prev.strip_prefix('-'), line.strip_prefix('+')) {
+                if old != new && self.shape(old) == self.shape(new) {
+                    scores[i - 1] += 4;
+                    scores[i] += 4;
+                }
+            }
+        }
+        
+        scores
+    }
+    
+    fn score_line(&self, line: &str) -> u32 {
+        if line.starts_with("@@") {
+            // Hunk headers carry the enclosing function name
+            return 2;
+        }
+        
+        let content = match line.strip_prefix('+').or_else(|| line.strip_prefix('-')) {
+            Some(content) => content,
+            None => return 0,
+        };
+        
+        if content.trim().chars().all(|c| "{}()[];,".contains(c) || c.is_whitespace()) {
+            return 0;
+        }
+        
+        let mut score = 1;
+        if self.signature.is_match(content) {
+            score += 5;
+        }
+        if self.logging.is_match(content) {
+            score += 3;
+        }
+        score
+    }
+    
+    /// The line with every identifier replaced, to spot renames
+    fn shape(&self, content: &str) -> String {
+        self.identifier.replace_all(content.trim(), "x").into_owned()
+    }
+}
@@ -0,0 +1,259 @@
+//! Placeholders and partials of custom prompt templates
+//!
+//! A template (`--template`, the `template` setting or `[templates]`) is the
+//! whole prompt, with `{NAME}` placeholders where the changes go. Only names
+//! in capitals count as placeholders, so braces in the rest of the prompt,
+//! e.g. a JSON example or `type(scope)`, are sent as written.
+//!
+//! `{{> name}}` includes a section of the default prompt, so a template can
+//! reuse its context formatting. A template made only of `{{#name}}...{{/name}}`
+//! blocks is the default prompt with those sections replaced.
+
+use crate::history::edit_distance;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+/// Placeholders a template may use, with what each is replaced by
+pub const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("CONTEXT", "the repository context of the default prompt"),
+    ("DIFF", "the staged diff, limited by --max-files and --max-diff-lines"),
+    ("BRANCH", "the current branch"),
+    ("LAST_COMMITS", "subjects of recent commits, one per line"),
+    ("FILES", "the staged files with how they changed, one per line"),
+    ("LANGUAGE", "languages of the staged code, largest share first"),
+];
+
+/// Sections of the default prompt, which templates include with `{{> name}}`
+/// or replace with `{{#name}}...{{/name}}`
+pub const PARTIALS: &[(&str, &str)] = &[
+    ("header", "You are an expert software developer creating a git commit message. \n\n\
+        Based on the following git repository changes, generate a concise, descriptive commit message that follows conventional commit format."),
+    ("context_block", "Repository Context:\n{CONTEXT}"),
+    ("rules", r#"Guidelines for the commit message:
+1. Use conventional commit format: type(scope): description
+2. Types: feat, fix, docs, style, refactor, test, chore
+3. Keep the first line under 50 characters
+4. Be specific about what changed and why
+5. Use imperative mood (e.g., "add" not "added")
+6. Focus on the most significant changes
+7. If there are breaking changes, mention them
+8. For config file changes, use "chore" type
+9. For test changes, use "test" type
+10. Only include changes that are staged for commit in the commit message"#),
+    ("footer", "Generate only the commit message, no additional explanation:"),
+];
+
+/// How the sections make up the default prompt
+const LAYOUT: &str = "{{> header}}\n\n{{> context_block}}\n\n{{> rules}}\n\n{{> footer}}";
+
+/// Placeholders that show the model the changes; a template needs one of them
+const CHANGE_PLACEHOLDERS: &[&str] = &["CONTEXT", "DIFF", "FILES"];
+
+/// Values of the placeholders for one prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateVars {
+    pub context: String,
+    pub diff: String,
+    pub branch: String,
+    pub last_commits: String,
+    pub files: String,
+    pub language: String,
+}
+
+impl TemplateVars {
+    fn get(&self, name: &str) -> Option<&str> {
+        let value = match name {
+            "CONTEXT" => &self.context,
+            "DIFF" => &self.diff,
+            "BRANCH" => &self.branch,
+            "LAST_COMMITS" => &self.last_commits,
+            "FILES" => &self.files,
+            "LANGUAGE" => &self.language,
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+fn partial_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{>\s*([A-Za-z0-9_]+)\s*\}\}").expect("valid partial pattern"))
+}
+
+fn section_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{([#/])\s*([A-Za-z0-9_]+)\s*\}\}").expect("valid section pattern"))
+}
+
+/// The default prompt, before its placeholders are filled
+pub fn default_template() -> String {
+    include_partials(LAYOUT, &[], "The default prompt").expect("the default prompt includes known partials")
+}
+
+/// Resolve the partials and section overrides of `template`
+/// 
+/// `source` names the template in errors, as for [`check`].
+pub fn expand(template: &str, source: &str) -> Result<String> {
+    let (overrides, outside) = sections(template, source)?;
+    if overrides.is_empty() {
+        return include_partials(template, &[], source);
+    }
+    if !outside.trim().is_empty() {
+        return Err(GitAiError::Config(format!(
+            "{} replaces sections of the default prompt, so everything in it must be inside {{{{#section}}}}...{{{{/section}}}} blocks; found \"{}\"",
+            source, outside.trim().lines().next().unwrap_or_default()
+        )).into());
+    }
+    include_partials(LAYOUT, &overrides, source)
+}
+
+/// `{{#name}}...{{/name}}` blocks of `template`, and the text outside them
+fn sections<'a>(template: &'a str, source: &str) -> Result<(Vec<(&'a str, &'a str)>, String)> {
+    let mut overrides = Vec::new();
+    let mut outside = String::new();
+    let mut open: Option<(&str, usize)> = None;
+    let mut rest_start = 0;
+    for captures in section_pattern().captures_iter(template) {
+        let tag = captures.get(0).expect("whole match");
+        let name = captures.get(2).map_or("", |name| name.as_str());
+        match (&captures[1], open) {
+            ("#", None) => {
+                known_partial(name, source)?;
+                outside.push_str(&template[rest_start..tag.start()]);
+                open = Some((name, tag.end()));
+            }
+            ("/", Some((opened, body_start))) if opened == name => {
+                if overrides.iter().any(|(overridden, _)| *overridden == name) {
+                    return Err(GitAiError::Config(format!("{} replaces the section {} twice", source, name)).into());
+                }
+                overrides.push((name, template[body_start..tag.start()].trim_matches('\n')));
+                open = None;
+                rest_start = tag.end();
+            }
+            (_, Some((opened, _))) => {
+                return Err(GitAiError::Config(format!(
+                    "{} has {} inside the section {}, which isn't closed with {{{{/{}}}}}",
+                    source, tag.as_str(), opened, opened
+                )).into());
+            }
+            (_, None) => {
+                return Err(GitAiError::Config(format!("{} closes the section {} it never opened", source, name)).into());
+            }
+        }
+    }
+    if let Some((opened, _)) = open {
+        return Err(GitAiError::Config(format!("{} doesn't close the section {} with {{{{/{}}}}}", source, opened, opened)).into());
+    }
+    outside.push_str(&template[rest_start..]);
+    Ok((overrides, outside))
+}
+
+/// Replace each `{{> name}}` of `text` with the section's replacement in
+/// `overrides`, or the default section
+/// 
+/// A replacement that includes its own section gets the default one.
+fn include_partials(text: &str, overrides: &[(&str, &str)], source: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut last = 0;
+    for captures in partial_pattern().captures_iter(text) {
+        let tag = captures.get(0).expect("whole match");
+        let name = &captures[1];
+        expanded.push_str(&text[last..tag.start()]);
+        match overrides.iter().find(|(overridden, _)| *overridden == name) {
+            Some((_, replacement)) => expanded.push_str(&include_partials(replacement, &[], source)?),
+            None => expanded.push_str(known_partial(name, source)?),
+        }
+        last = tag.end();
+    }
+    expanded.push_str(&text[last..]);
+    Ok(expanded)
+}
+
+/// Text of the default section `name`
+fn known_partial(name: &str, source: &str) -> Result<&'static str> {
+    match PARTIALS.iter().find(|(known, _)| *known == name) {
+        Some((_, text)) => Ok(text),
+        None => {
+            let available: Vec<&str> = PARTIALS.iter().map(|(known, _)| *known).collect();
+            Err(GitAiError::Config(format!(
+                "{} uses the unknown section {}. Available: {}",
+                source, name, available.join(", ")
+            )).into())
+        }
+    }
+}
+
+fn placeholder_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{([A-Z][A-Z0-9_]*)\}").expect("valid placeholder pattern"))
+}
+
+/// Placeholder names in `template`, in order of first appearance
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    for captures in placeholder_pattern().captures_iter(template) {
+        let name = captures.get(1).map_or("", |name| name.as_str());
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Whether `template` uses the placeholder `name`, e.g. `"DIFF"`
+pub fn uses(template: &str, name: &str) -> bool {
+    placeholders(template).contains(&name)
+}
+
+/// Replace every placeholder of `template` in one pass, so text in the
+/// values that looks like a placeholder stays as it is
+/// 
+/// Partials are resolved first; a template [`check`] rejects is filled as written.
+pub fn fill(template: &str, vars: &TemplateVars) -> String {
+    let expanded = expand(template, "Template");
+    let template = expanded.as_deref().unwrap_or(template);
+    placeholder_pattern()
+        .replace_all(template, |captures: &Captures| {
+            let placeholder = &captures[0];
+            vars.get(&captures[1]).unwrap_or(placeholder).to_string()
+        })
+        .into_owned()
+}
+
+/// Reject a template with unknown placeholders or sections, or no placeholder
+/// that shows the changes
+/// 
+/// `source` names the template in the error, e.g. `Template ./prompt.txt`
+/// or `templates.docs`.
+pub fn check(template: &str, source: &str) -> Result<()> {
+    let template = expand(template, source)?;
+    let names = placeholders(&template);
+    if let Some(unknown) = names.iter().find(|name| !PLACEHOLDERS.iter().any(|(known, _)| known == *name)) {
+        let hint = closest(unknown)
+            .map(|known| format!(" (did you mean {{{}}}?)", known))
+            .unwrap_or_default();
+        let available: Vec<String> = PLACEHOLDERS.iter().map(|(name, _)| format!("{{{}}}", name)).collect();
+        return Err(GitAiError::Config(format!(
+            "{} uses the unknown placeholder {{{}}}{}. Available: {}",
+            source, unknown, hint, available.join(", ")
+        )).into());
+    }
+    if !names.iter().any(|name| CHANGE_PLACEHOLDERS.contains(name)) {
+        return Err(GitAiError::Config(format!(
+            "{} has no {{CONTEXT}}, {{DIFF}} or {{FILES}} placeholder, so the model wouldn't see the changes",
+            source
+        )).into());
+    }
+    Ok(())
+}
+
+/// The known placeholder `name` is most likely a typo of
+fn closest(name: &str) -> Option<&'static str> {
+    PLACEHOLDERS.iter()
+        .map(|(known, _)| (*known, edit_distance(name, known)))
+        .filter(|(known, distance)| *distance <= 2 || known.starts_with(name) || name.starts_with(known))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| known)
+}
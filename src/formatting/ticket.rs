@@ -0,0 +1,66 @@
+//! Ticket IDs taken from the branch name
+//!
+//! Teams that name branches after their tracker (`feature/JIRA-1234-add-login`)
+//! want every commit to point back to the ticket. The `[ticket]` patterns find
+//! it in the branch name, and the message gets it as a trailer (`Refs:
+//! JIRA-1234`) or before the subject's description, unless it already
+//! mentions it.
+
+use crate::config::{TicketConfig, TicketPlacement};
+use crate::formatting::conventional::ConventionalHeader;
+use crate::formatting::merge;
+use regex::Regex;
+
+/// Standards and encodings named like ticket keys (`UTF-8`, `ISO-8601`, `SHA-256`)
+const NOT_TICKETS: &[&str] = &["UTF", "ISO", "SHA"];
+
+/// Ticket of `branch`: the first capture group of the first pattern that
+/// matches, or the whole match of a pattern without groups
+/// 
+/// Invalid patterns are skipped; the config check reports them.
+pub fn find(branch: &str, patterns: &[String]) -> Option<String> {
+    patterns.iter()
+        .filter_map(|pattern| Regex::new(pattern).ok())
+        .find_map(|pattern| {
+            pattern.captures_iter(branch)
+                .filter_map(|captures| captures.get(1).or_else(|| captures.get(0)))
+                .map(|ticket| ticket.as_str())
+                .find(|ticket| !ticket.is_empty() && !is_standard(ticket))
+                .map(str::to_string)
+        })
+}
+
+fn is_standard(ticket: &str) -> bool {
+    ticket.split_once('-').is_some_and(|(key, _)| NOT_TICKETS.contains(&key))
+}
+
+/// `message` mentioning `ticket` where `config` places it
+pub fn inject(message: &str, ticket: &str, config: &TicketConfig) -> String {
+    if message.contains(ticket) {
+        return message.to_string();
+    }
+    match config.placement {
+        TicketPlacement::Footer => merge::append_trailers(message, &[format!("{}: {}", config.trailer, ticket)]),
+        TicketPlacement::Subject => prefix_subject(message, ticket),
+        TicketPlacement::Off => message.to_string(),
+    }
+}
+
+/// Put `ticket` before the description of the subject, after any
+/// conventional `type(scope):` header
+fn prefix_subject(message: &str, ticket: &str) -> String {
+    let message = message.trim();
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = match ConventionalHeader::parse(subject) {
+        Some(mut header) => {
+            header.description = format!("{} {}", ticket, header.description);
+            header.to_string()
+        }
+        None => format!("{} {}", ticket, subject),
+    };
+    if body.is_empty() {
+        subject
+    } else {
+        format!("{}\n{}", subject, body)
+    }
+}
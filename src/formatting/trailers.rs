@@ -0,0 +1,41 @@
+//! Trailers crediting the people and tools behind a commit
+//!
+//! Besides the fixed `trailers` of the config, a run can add
+//! `Co-authored-by:` lines for the configured `co_authors`, an
+//! `AI-Generated-By:` line naming the model that wrote the message, and a
+//! `Signed-off-by:` line for `--signoff`. They are appended with
+//! [`crate::formatting::merge::append_trailers`].
+
+/// Key of the trailer naming the model that wrote a message
+pub const AI_GENERATED_BY: &str = "AI-Generated-By";
+
+/// `Co-authored-by:` trailer for `author`, given as `Name <email>`
+pub fn co_authored_by(author: &str) -> String {
+    format!("Co-authored-by: {}", author.trim())
+}
+
+/// `Signed-off-by:` trailer for `identity`, given as `Name <email>`
+pub fn signed_off_by(identity: &str) -> String {
+    format!("Signed-off-by: {}", identity.trim())
+}
+
+/// Trailer naming this tool and the `model` it generated the message with
+pub fn ai_generated_by(model: &str) -> String {
+    format!("{}: git-ai-commit/{}", AI_GENERATED_BY, model)
+}
+
+/// Whether `author` looks like `Name <email>`, the form git expects in trailers
+pub fn is_identity(author: &str) -> bool {
+    let author = author.trim();
+    match author.split_once('<') {
+        Some((name, email)) => !name.trim().is_empty() && email.ends_with('>') && email.contains('@'),
+        None => false,
+    }
+}
+
+/// Whether `key` can start a trailer line, e.g. `Refs` or `Reviewed-by`
+pub fn is_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
@@ -0,0 +1,244 @@
+use crate::config::{BannedWordAction, CommitStyle, ValidationConfig};
+use crate::formatting::conventional::{self, ConventionalHeader};
+use crate::git::GitInfo;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use regex::Regex;
+
+/// A rule a generated commit message breaks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Empty,
+    /// The model declined or apologized instead of describing the changes
+    Refusal,
+    SubjectTooLong { length: usize, max: usize },
+    PastTense(String),
+    TrailingPeriod,
+    Preamble,
+    BannedWord(String),
+    /// Only file modes changed but the type claims more (`feat`, `fix`, ...)
+    ModeChangeType(String),
+    /// Only file modes changed but the message describes new or fixed behavior
+    InventedFunctionality(String),
+    /// `commit_style = "conventional"` but the subject has no `type: ` header
+    NotConventional,
+    /// `commit_style = "conventional"` but the type isn't a conventional one
+    UnknownType(String),
+    /// `package_scope` is set and the scope isn't the one package the change affects
+    PackageScope { expected: String, found: Option<String> },
+}
+
+impl Violation {
+    /// Describe the problem in a form that can be fed back to the model
+    pub fn describe(&self) -> String {
+        match self {
+            Violation::Empty => "the message was empty".to_string(),
+            Violation::Refusal => {
+                "the answer declined the task; describe the staged changes as a commit message".to_string()
+            }
+            Violation::SubjectTooLong { length, max } => {
+                format!("subject was {} chars (max {})", length, max)
+            }
+            Violation::PastTense(word) => {
+                format!("'{}' is past tense; use the imperative mood", word)
+            }
+            Violation::TrailingPeriod => "subject ends with a period".to_string(),
+            Violation::Preamble => {
+                "message starts with an introduction instead of the commit message".to_string()
+            }
+            Violation::BannedWord(word) => format!("contains the banned word '{}'", word),
+            Violation::ModeChangeType(commit_type) => format!(
+                "only file permissions changed; use a chore or build type instead of '{}'",
+                commit_type
+            ),
+            Violation::InventedFunctionality(word) => format!(
+                "only file permissions changed; '{}' describes behavior that did not change",
+                word
+            ),
+            Violation::NotConventional => {
+                "subject is not in conventional commit form 'type(scope): description'".to_string()
+            }
+            Violation::UnknownType(commit_type) => format!(
+                "'{}' is not a conventional commit type; use one of {}",
+                commit_type,
+                conventional::COMMIT_TYPES.join(", ")
+            ),
+            Violation::PackageScope { expected, found: None } => format!(
+                "only package '{}' changed; use it as the scope, as in 'feat({}): ...'",
+                expected, expected
+            ),
+            Violation::PackageScope { expected, found: Some(found) } => format!(
+                "only package '{}' changed; use it as the scope instead of '{}'",
+                expected, found
+            ),
+        }
+    }
+    
+    /// Whether the response can't be used as a commit message at all
+    pub fn is_unusable(&self) -> bool {
+        matches!(self, Violation::Empty | Violation::Refusal)
+    }
+}
+
+/// Words ending in "ed" that are not past tense
+const NOT_PAST_TENSE: &[&str] = &[
+    "need", "speed", "embed", "feed", "seed", "shed", "bleed", "breed", "proceed", "exceed",
+    "succeed", "bed", "red", "shred",
+];
+
+/// Commit types that fit a change to file permissions
+const MODE_CHANGE_TYPES: &[&str] = &["chore", "build"];
+
+/// Words that describe behavior, which a permission change can't have altered
+const FUNCTIONAL_WORDS: &[&str] = &[
+    "implement", "implements", "refactor", "optimize", "improve", "feature", "bug", "logic",
+    "behavior", "behaviour", "functionality",
+];
+
+/// Check a commit message against the configured rules
+pub fn validate(message: &str, config: &ValidationConfig) -> Vec<Violation> {
+    let message = message.trim();
+    let subject = match message.lines().next() {
+        Some(subject) if subject.chars().any(char::is_alphanumeric) => subject.trim(),
+        _ => return vec![Violation::Empty],
+    };
+    
+    let refusal = Regex::new(
+        r"(?i)^(i(['’]m| am) (sorry|unable|not able)|sorry\b|i (cannot|can['’]?t|can not|won['’]t|do not|don['’]t)\b|as an ai\b|unfortunately\b)"
+    ).unwrap();
+    if refusal.is_match(subject) {
+        return vec![Violation::Refusal];
+    }
+    
+    let mut violations = Vec::new();
+    
+    let preamble = Regex::new(r"(?i)^(here('s| is| are)|sure\b|certainly\b|commit message:)").unwrap();
+    if preamble.is_match(subject) {
+        violations.push(Violation::Preamble);
+    }
+    
+    let length = subject.chars().count();
+    if length > config.max_subject_length {
+        violations.push(Violation::SubjectTooLong { length, max: config.max_subject_length });
+    }
+    
+    if let Some(word) = past_tense_verb(subject) {
+        violations.push(Violation::PastTense(word));
+    }
+    
+    if subject.ends_with('.') && !subject.ends_with("...") {
+        violations.push(Violation::TrailingPeriod);
+    }
+    
+    if config.commit_style == CommitStyle::Conventional {
+        violations.extend(conventional::check(subject));
+    }
+    
+    if config.banned_word_action == BannedWordAction::Reject {
+        violations.extend(find_banned_words(message, &config.banned_words).into_iter().map(Violation::BannedWord));
+    }
+    
+    violations
+}
+
+/// Check a commit message against the configured rules and the changes it describes
+/// 
+/// When every staged change is an exec-bit or other mode flip, the message
+/// must use a `chore` or `build` type and may not describe functional changes.
+pub fn validate_for_changes(message: &str, config: &ValidationConfig, git_info: &GitInfo) -> Vec<Violation> {
+    let mut violations = validate(message, config);
+    if violations.iter().any(Violation::is_unusable) || !git_info.is_mode_only() {
+        return violations;
+    }
+    
+    let message = message.trim();
+    let type_prefix = Regex::new(r"^([a-zA-Z]+)(\([^)]*\))?!?:").unwrap();
+    match type_prefix.captures(message) {
+        Some(captures) if MODE_CHANGE_TYPES.contains(&captures[1].to_lowercase().as_str()) => {}
+        Some(captures) => violations.push(Violation::ModeChangeType(captures[1].to_string())),
+        None => violations.push(Violation::ModeChangeType("none".to_string())),
+    }
+    
+    let words = Regex::new(r"[A-Za-z]+").unwrap();
+    if let Some(word) = words.find_iter(message)
+        .map(|m| m.as_str().to_lowercase())
+        .find(|word| FUNCTIONAL_WORDS.contains(&word.as_str()))
+    {
+        violations.push(Violation::InventedFunctionality(word));
+    }
+    
+    violations
+}
+
+/// Whether the subject is scoped by the affected package, when there is exactly one
+/// 
+/// Changes spanning several packages, or none, may use any scope.
+pub fn check_package_scope(message: &str, packages: &[String]) -> Option<Violation> {
+    let [expected] = packages else {
+        return None;
+    };
+    let subject = message.trim().lines().next().unwrap_or_default();
+    let found = ConventionalHeader::parse(subject).and_then(|header| header.scope);
+    (found.as_deref() != Some(expected.as_str()))
+        .then(|| Violation::PackageScope { expected: expected.clone(), found })
+}
+
+/// Banned words or phrases that occur in the message as whole words
+pub fn find_banned_words(message: &str, banned_words: &[String]) -> Vec<String> {
+    banned_words
+        .iter()
+        .filter(|word| banned_word_regex(word).is_some_and(|re| re.is_match(message)))
+        .cloned()
+        .collect()
+}
+
+/// Apply the banned word policy to a message about to be shown or committed
+/// 
+/// With `mask` the words are replaced; with `reject` a message that still
+/// contains one is an error, so it can never reach a commit.
+pub fn enforce_banned_words(message: &str, config: &ValidationConfig) -> Result<String> {
+    match config.banned_word_action {
+        BannedWordAction::Mask => Ok(mask_banned_words(message, &config.banned_words)),
+        BannedWordAction::Reject => {
+            let found = find_banned_words(message, &config.banned_words);
+            if found.is_empty() {
+                return Ok(message.to_string());
+            }
+            Err(GitAiError::Validation(format!(
+                "message contains banned words: {}",
+                found.join(", ")
+            )).into())
+        }
+    }
+}
+
+/// Replace every banned word or phrase with `***`
+pub fn mask_banned_words(message: &str, banned_words: &[String]) -> String {
+    banned_words
+        .iter()
+        .filter_map(|word| banned_word_regex(word))
+        .fold(message.to_string(), |masked, re| re.replace_all(&masked, "***").into_owned())
+}
+
+fn banned_word_regex(word: &str) -> Option<Regex> {
+    let word = word.trim();
+    if word.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(r"(?i)\b{}\b", regex::escape(word))).ok()
+}
+
+/// First word of the description, if it looks like a past tense verb
+fn past_tense_verb(subject: &str) -> Option<String> {
+    let prefix = Regex::new(r"^[a-zA-Z]+(\([^)]*\))?!?:\s*").unwrap();
+    let description = prefix.find(subject).map_or(subject, |m| &subject[m.end()..]);
+    // Skip a leading emoji, as in gitmoji subjects
+    let word = description
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .find(|word| !word.is_empty())?
+        .to_lowercase();
+    
+    let is_past = word.len() > 3 && word.ends_with("ed") && !NOT_PAST_TENSE.contains(&word.as_str());
+    is_past.then_some(word)
+}
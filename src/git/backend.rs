@@ -0,0 +1,280 @@
+//! Where the repository is read from
+//!
+//! Every run reads the status, diff stats, file changes, branch and staged
+//! patch before generating. By default each read spawns `git`; built with the
+//! `libgit2` feature, `git_backend = "libgit2"` reads the repository in-process
+//! instead, which is much faster where process startup is slow (Windows) and
+//! works without git on the PATH. Staging, committing and pushing always run
+//! `git`, so hooks, signing and credential helpers behave as configured.
+
+use crate::config::GitBackendKind;
+use crate::git::command::git_read_command;
+use crate::git::{DiffInfo, FileChange, GitStatus};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Reads of the repository a run depends on
+#[async_trait]
+pub trait GitBackend: Send + Sync {
+    /// Name of the backend, as set in the config
+    fn name(&self) -> &'static str;
+    
+    /// Staged, modified, deleted and untracked paths; untracked directories end in `/`
+    async fn status(&self) -> Result<GitStatus>;
+    
+    /// Line counts of the staged changes, followed by those of the unstaged ones
+    async fn diff_stat(&self) -> Result<DiffInfo>;
+    
+    /// Staged changes, then unstaged changes to files that have no staged ones
+    async fn file_changes(&self) -> Result<Vec<FileChange>>;
+    
+    /// Untracked, non-ignored files under `directory`
+    async fn untracked_in(&self, directory: &Path) -> Result<Vec<PathBuf>>;
+    
+    /// Current branch, empty when HEAD is detached
+    async fn branch_name(&self) -> Result<String>;
+    
+    /// Message of the commit at HEAD, `None` before the first commit
+    async fn last_commit(&self) -> Result<Option<String>>;
+    
+    /// Full patch of the staged changes
+    async fn staged_diff(&self) -> Result<String>;
+    
+    /// The repository's git directory
+    async fn git_dir(&self) -> Result<PathBuf>;
+}
+
+/// The backend selected in the config, reading the repository at `repo_path`
+pub fn open(kind: GitBackendKind, repo_path: &Path) -> Result<Arc<dyn GitBackend>> {
+    match kind {
+        GitBackendKind::Cli => Ok(Arc::new(CliBackend::new(repo_path.to_path_buf()))),
+        #[cfg(feature = "libgit2")]
+        GitBackendKind::Libgit2 => Ok(Arc::new(crate::git::libgit2::Libgit2Backend::open(repo_path)?)),
+        #[cfg(not(feature = "libgit2"))]
+        GitBackendKind::Libgit2 => Err(GitAiError::Config(
+            "git_backend = \"libgit2\" needs git-ai-commit built with --features libgit2".to_string()
+        ).into()),
+    }
+}
+
+/// Reads the repository by running `git`
+pub struct CliBackend {
+    repo_path: PathBuf,
+}
+
+impl CliBackend {
+    pub fn new(repo_path: PathBuf) -> Self {
+        Self { repo_path }
+    }
+}
+
+#[async_trait]
+impl GitBackend for CliBackend {
+    fn name(&self) -> &'static str {
+        "cli"
+    }
+    
+    async fn status(&self) -> Result<GitStatus> {
+        let output = git_read_command(&self.repo_path)
+            .args(["status", "--porcelain=v1"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get git status: {}", error)).into());
+        }
+        
+        let status_text = String::from_utf8_lossy(&output.stdout);
+        GitStatus::parse(&status_text)
+    }
+    
+    async fn diff_stat(&self) -> Result<DiffInfo> {
+        // Get staged changes
+        let staged_output = git_read_command(&self.repo_path)
+            .args(["diff", "--cached", "--numstat", "-M", "-C"])
+            .output()
+            .await?;
+        
+        // Get unstaged changes
+        let unstaged_output = git_read_command(&self.repo_path)
+            .args(["diff", "--numstat", "-M", "-C"])
+            .output()
+            .await?;
+        
+        if !staged_output.status.success() {
+            let error = String::from_utf8_lossy(&staged_output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get staged diff stats: {}", error)).into());
+        }
+        
+        if !unstaged_output.status.success() {
+            let error = String::from_utf8_lossy(&unstaged_output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get unstaged diff stats: {}", error)).into());
+        }
+        
+        // Parse both sets of changes
+        let staged_diff = String::from_utf8_lossy(&staged_output.stdout);
+        let unstaged_diff = String::from_utf8_lossy(&unstaged_output.stdout);
+        
+        // Combine the diffs
+        let mut combined_diff = staged_diff.to_string();
+        if !unstaged_diff.trim().is_empty() {
+            combined_diff.push('\n');
+            combined_diff.push_str(&unstaged_diff);
+        }
+        
+        DiffInfo::parse(&combined_diff)
+    }
+    
+    async fn file_changes(&self) -> Result<Vec<FileChange>> {
+        // Get staged changes
+        let staged_output = git_read_command(&self.repo_path)
+            .args(["diff", "--cached", "--raw", "-M", "-C"])
+            .output()
+            .await?;
+        
+        // Get unstaged changes, with line counts to tell real modifications from touched files
+        let unstaged_output = git_read_command(&self.repo_path)
+            .args(["diff", "--raw", "--numstat", "-M", "-C"])
+            .output()
+            .await?;
+        
+        if !staged_output.status.success() {
+            let error = String::from_utf8_lossy(&staged_output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get staged file changes: {}", error)).into());
+        }
+        
+        if !unstaged_output.status.success() {
+            let error = String::from_utf8_lossy(&unstaged_output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get unstaged file changes: {}", error)).into());
+        }
+        
+        // Parse both sets of changes
+        let staged_changes = String::from_utf8_lossy(&staged_output.stdout);
+        let unstaged_changes = without_touched_files(&String::from_utf8_lossy(&unstaged_output.stdout));
+        
+        // Combine the changes
+        let mut all_changes = Vec::new();
+        
+        // Add staged changes first
+        if !staged_changes.trim().is_empty() {
+            let mut changes = FileChange::parse_list(&staged_changes)?;
+            all_changes.append(&mut changes);
+        }
+        
+        // Add unstaged changes, avoiding duplicates
+        if !unstaged_changes.trim().is_empty() {
+            let changes = FileChange::parse_list(&unstaged_changes)?;
+            // Only add unstaged changes for files that aren't already in the list
+            for change in changes {
+                if !all_changes.iter().any(|c: &FileChange| c.file_path == change.file_path) {
+                    all_changes.push(change);
+                }
+            }
+        }
+        
+        Ok(all_changes)
+    }
+    
+    async fn untracked_in(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let output = git_read_command(&self.repo_path)
+            .args(["ls-files", "--others", "--exclude-standard", "--"])
+            .arg(directory)
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get untracked files: {}", error)).into());
+        }
+        
+        let files_text = String::from_utf8_lossy(&output.stdout);
+        Ok(files_text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| PathBuf::from(line.trim()))
+            .collect())
+    }
+    
+    async fn branch_name(&self) -> Result<String> {
+        let output = git_read_command(&self.repo_path)
+            .args(["branch", "--show-current"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get branch name: {}", error)).into());
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    async fn last_commit(&self) -> Result<Option<String>> {
+        let output = git_read_command(&self.repo_path)
+            .args(["log", "-1", "--pretty=%B"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            return Ok(None);
+        }
+        
+        let commit_msg = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if commit_msg.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(commit_msg))
+        }
+    }
+    
+    async fn staged_diff(&self) -> Result<String> {
+        let output = git_read_command(&self.repo_path)
+            .args(["diff", "--cached", "-M", "-C"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get staged diff: {}", error)).into());
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+    
+    async fn git_dir(&self) -> Result<PathBuf> {
+        let output = git_read_command(&self.repo_path)
+            .args(["rev-parse", "--git-dir"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to locate git directory: {}", error)).into());
+        }
+        
+        let git_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        Ok(self.repo_path.join(git_dir))
+    }
+}
+
+/// The `--raw` lines of `diff --raw --numstat` output, without modifications
+/// that have no line counts: files whose timestamp changed but whose content
+/// is what the index has
+fn without_touched_files(raw_and_numstat: &str) -> String {
+    let (raw, numstat): (Vec<&str>, Vec<&str>) = raw_and_numstat.lines()
+        .filter(|line| !line.trim().is_empty())
+        .partition(|line| line.starts_with(':'));
+    let counted: Vec<&str> = numstat.iter().filter_map(|line| line.splitn(3, '\t').nth(2)).collect();
+    raw.into_iter()
+        .filter(|line| {
+            let mut fields = line.split('\t');
+            let is_modified = fields.next().is_some_and(|info| info.ends_with(" M"));
+            !is_modified || fields.next().is_some_and(|path| counted.contains(&path))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
@@ -0,0 +1,73 @@
+//! Shallow and partial clones, where part of the history or content is missing
+//!
+//! CI checkouts are often `--depth 1` or `--filter=blob:none`. Commands still
+//! work there, but anything reaching back through history sees a truncated
+//! picture, so those features are skipped or explained instead.
+
+use crate::git::command::git_command;
+use std::path::Path;
+
+/// How much of the repository is available locally
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CloneInfo {
+    /// History ends at a boundary commit (`git clone --depth`)
+    pub shallow: bool,
+    /// Objects are fetched on demand from a promisor remote (`git clone --filter`)
+    pub partial: bool,
+}
+
+impl CloneInfo {
+    /// Inspect the repository; anything that can't be determined counts as complete
+    pub async fn detect(repo_path: &Path) -> Self {
+        let shallow = git_stdout(repo_path, &["rev-parse", "--is-shallow-repository"])
+            .await
+            .is_some_and(|out| out.trim() == "true");
+        
+        let partial_clone = git_stdout(repo_path, &["config", "--get", "extensions.partialclone"])
+            .await
+            .is_some_and(|out| !out.trim().is_empty());
+        let promisor = git_stdout(repo_path, &["config", "--get-regexp", r"^remote\..*\.promisor$"])
+            .await
+            .is_some_and(|out| out.lines().any(|line| line.trim_end().ends_with(" true")));
+        
+        Self { shallow, partial: partial_clone || promisor }
+    }
+    
+    /// Whether the whole history and every object are available
+    pub fn is_complete(&self) -> bool {
+        !self.shallow && !self.partial
+    }
+    
+    /// Kind of clone, e.g. "shallow clone"; `None` for a complete one
+    pub fn describe(&self) -> Option<&'static str> {
+        match (self.shallow, self.partial) {
+            (true, true) => Some("shallow, partial clone"),
+            (true, false) => Some("shallow clone"),
+            (false, true) => Some("partial clone"),
+            (false, false) => None,
+        }
+    }
+    
+    /// Warning shown once per run, explaining what is left out
+    pub fn warning(&self) -> Option<String> {
+        let kind = self.describe()?;
+        let mut warning = format!("This repository is a {}", kind);
+        if self.shallow {
+            warning.push_str("; history examples are skipped and older commits are unavailable");
+        }
+        if self.partial {
+            warning.push_str("; missing file contents may be fetched from the remote");
+        }
+        Some(warning)
+    }
+}
+
+/// Stdout of a successful git command
+pub(crate) async fn git_stdout(repo_path: &Path, args: &[&str]) -> Option<String> {
+    let output = git_command(repo_path)
+        .args(args)
+        .output()
+        .await
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
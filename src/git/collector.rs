@@ -1,12 +1,38 @@
-use crate::git::{GitStatus, DiffInfo, FileChange};
+use crate::analysis::language::{ProjectLayout, MANIFEST_PATHSPECS};
+use crate::git::{ChangeCategory, CloneInfo, CommitStats, CommitSummary, GitStatus, DiffInfo, FileChange, LanguageGroup};
+use crate::git::diff::FileStat;
+use crate::git::files::{is_scratch_file, ChangeType};
+use crate::git::hooks::HOOK_SKIP_ENV;
+use crate::git::project::{ProjectCache, ProjectInfo};
+use crate::git::backend::{CliBackend, GitBackend};
+use crate::git::clone::git_stdout;
+use crate::git::command::{git_command, git_read_command};
+use crate::git::lock::run_writing_index;
+use crate::git::push::PushTarget;
+use crate::git::sequence::Sequence;
 use crate::utils::error::GitAiError;
+use crate::utils::WriteMode;
 use anyhow::Result;
-use std::path::PathBuf;
-use tokio::process::Command;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Most untracked files listed when expanding untracked directories
+/// 
+/// Directories that would push the list past this stay as a single `dir/` entry.
+pub const MAX_UNTRACKED_FILES: usize = 200;
+
+/// Untracked files larger than this are listed after smaller ones
+/// 
+/// Big new files are more often data dumps or build output than the work being committed.
+pub const LARGE_UNTRACKED_BYTES: u64 = 1024 * 1024;
 
 /// Main git data collector that orchestrates all git operations
 pub struct GitCollector {
     repo_path: PathBuf,
+    write_mode: WriteMode,
+    backend: Arc<dyn GitBackend>,
 }
 
 /// Comprehensive git repository information
@@ -20,7 +46,78 @@ pub struct GitInfo {
     pub last_commit: Option<String>,
 }
 
+/// Counts of untracked directories and the files inside them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UntrackedSummary {
+    /// New directories `git status` reports as a single `dir/` entry
+    pub directories: usize,
+    /// Untracked files, including those found inside new directories
+    pub files: usize,
+    /// Directories whose files weren't listed because of `MAX_UNTRACKED_FILES`
+    pub unexpanded: usize,
+}
+
+fn is_directory_entry(path: &Path) -> bool {
+    path.to_string_lossy().ends_with('/')
+}
+
+/// Order untracked files so the ones most likely to matter come first
+/// 
+/// Most recently modified first, with files over `LARGE_UNTRACKED_BYTES`
+/// after the rest and scratch files (see `is_scratch_file`) last.
+pub fn prioritize_untracked(repo_path: &Path, files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut keyed: Vec<_> = files.into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(repo_path.join(&path)).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let large = metadata.is_some_and(|m| m.is_file() && m.len() > LARGE_UNTRACKED_BYTES);
+            ((is_scratch_file(&path), large, std::cmp::Reverse(modified)), path)
+        })
+        .collect();
+    keyed.sort_by_key(|(key, _)| *key);
+    keyed.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Mark the binary files among `changes` and note the size of those still in the working tree
+/// 
+/// The size is of the working-tree file, which is the staged content unless
+/// the file was edited again after staging.
+pub fn measure_changes(repo_path: &Path, changes: &mut [FileChange], diff_stat: &DiffInfo) {
+    for change in changes {
+        let path = change.file_path.to_string_lossy();
+        change.is_binary = diff_stat.file_stats.iter().any(|stat| stat.binary && stat.filename == path);
+        change.size = std::fs::metadata(repo_path.join(&change.file_path))
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len());
+    }
+}
+
+/// Whether `path` is inside a work tree that can be committed to
+/// 
+/// Git resolves the `.git` file of a linked worktree or submodule to its
+/// real git directory; a bare repository or the inside of a git directory
+/// has no work tree and doesn't count.
+pub async fn is_git_repository(path: &Path) -> Result<bool> {
+    let output = git_command(path)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .await?;
+    
+    Ok(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
 impl GitInfo {
+    /// Count untracked directories and files
+    pub fn untracked_summary(&self) -> UntrackedSummary {
+        let unexpanded = self.untracked_files.iter().filter(|p| is_directory_entry(p)).count();
+        UntrackedSummary {
+            directories: self.status.untracked_files.iter().filter(|p| is_directory_entry(p)).count(),
+            files: self.untracked_files.len() - unexpanded,
+            unexpanded,
+        }
+    }
+    
     /// Check if there are no changes to commit
     /// 
     /// # Arguments
@@ -38,6 +135,123 @@ impl GitInfo {
         }
     }
     
+    /// Modified, deleted and untracked files that aren't staged, without duplicates
+    pub fn unstaged_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for path in self.status.modified_files.iter()
+            .chain(&self.status.deleted_files)
+            .chain(&self.status.untracked_files)
+        {
+            if !self.status.staged_files.contains(path) && !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        paths
+    }
+    
+    /// Detect the single category shared by all committed changes
+    /// 
+    /// Only staged changes are considered when anything is staged. Mixed
+    /// changesets (or no changes at all) are reported as `ChangeCategory::Code`.
+    pub fn dominant_category(&self) -> ChangeCategory {
+        let staged: Vec<&FileChange> = self.file_changes.iter()
+            .filter(|c| self.status.staged_files.contains(&c.file_path))
+            .collect();
+        let changes: Vec<&FileChange> = if staged.is_empty() {
+            self.file_changes.iter().collect()
+        } else {
+            staged
+        };
+        
+        let mut categories = changes.iter().map(|c| c.category());
+        match categories.next() {
+            Some(first) if categories.all(|c| c == first) => first,
+            _ => ChangeCategory::Code,
+        }
+    }
+    
+    /// Languages the committed changes are written in, largest group first
+    /// 
+    /// Like [`GitInfo::dominant_category`], only staged changes count when
+    /// anything is staged. More than one group means the change spans
+    /// several components, e.g. a Rust backend and a TypeScript frontend.
+    pub fn language_groups(&self) -> Vec<LanguageGroup> {
+        let staged: Vec<&FileChange> = self.file_changes.iter()
+            .filter(|c| self.status.staged_files.contains(&c.file_path))
+            .collect();
+        if staged.is_empty() {
+            LanguageGroup::detect(&self.file_changes.iter().collect::<Vec<_>>())
+        } else {
+            LanguageGroup::detect(&staged)
+        }
+    }
+    
+    /// Whether every staged change only flips file modes (e.g. `chmod +x`)
+    pub fn is_mode_only(&self) -> bool {
+        let mut staged = self.file_changes.iter()
+            .filter(|c| self.status.staged_files.contains(&c.file_path))
+            .peekable();
+        staged.peek().is_some() && staged.all(|c| matches!(c.change_type, ChangeType::ModeChanged { .. }))
+    }
+    
+    /// Split the staged changes into groups of at most `size` files
+    /// 
+    /// Each chunk only carries its own staged files and their diff stats;
+    /// unstaged and untracked files are left out. Returns a single chunk
+    /// when everything fits.
+    pub fn staged_chunks(&self, size: usize) -> Vec<GitInfo> {
+        let staged: Vec<&FileChange> = self.file_changes.iter()
+            .filter(|c| self.status.staged_files.contains(&c.file_path))
+            .collect();
+        
+        staged
+            .chunks(size.max(1))
+            .map(|changes| {
+                let files: Vec<PathBuf> = changes.iter().map(|c| c.file_path.clone()).collect();
+                self.staged_subset(&files)
+            })
+            .collect()
+    }
+    
+    /// Only the staged changes to `files`, with their diff stats
+    /// 
+    /// Unstaged and untracked files are left out, as in [`GitInfo::staged_chunks`].
+    pub fn staged_subset(&self, files: &[PathBuf]) -> GitInfo {
+        let files: Vec<PathBuf> = files.iter()
+            .filter(|f| self.status.staged_files.contains(f))
+            .cloned()
+            .collect();
+        let file_stats: Vec<FileStat> = self.diff_stat.file_stats.iter()
+            .filter(|s| files.iter().any(|f| f.to_string_lossy() == s.filename))
+            .cloned()
+            .collect();
+        
+        GitInfo {
+            status: GitStatus {
+                staged_files: files.clone(),
+                modified_files: vec![],
+                untracked_files: vec![],
+                deleted_files: self.status.deleted_files.iter()
+                    .filter(|f| files.contains(f))
+                    .cloned()
+                    .collect(),
+            },
+            diff_stat: DiffInfo {
+                files_changed: file_stats.len(),
+                insertions: file_stats.iter().map(|s| s.insertions).sum(),
+                deletions: file_stats.iter().map(|s| s.deletions).sum(),
+                file_stats,
+            },
+            file_changes: self.file_changes.iter()
+                .filter(|c| files.contains(&c.file_path))
+                .cloned()
+                .collect(),
+            untracked_files: vec![],
+            branch_name: self.branch_name.clone(),
+            last_commit: self.last_commit.clone(),
+        }
+    }
+    
     pub fn display(&self) -> String {
         let mut output = String::new();
         
@@ -70,7 +284,20 @@ impl GitInfo {
 
 impl GitCollector {
     pub fn new(repo_path: PathBuf) -> Self {
-        Self { repo_path }
+        let backend = Arc::new(CliBackend::new(repo_path.clone()));
+        Self { repo_path, write_mode: WriteMode::default(), backend }
+    }
+    
+    /// Read the repository through `backend` instead of running `git`
+    pub fn with_backend(mut self, backend: Arc<dyn GitBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+    
+    /// Refuse operations that change the index or object database in read-only mode
+    pub fn with_write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
     }
     
     /// Collect all git information in parallel where possible
@@ -89,8 +316,13 @@ impl GitCollector {
         )?;
         
         // These depend on the status, so run sequentially
-        let file_changes = self.get_file_changes().await?;
-        let untracked_files = self.get_untracked_files().await?;
+        let mut file_changes = self.get_file_changes().await?;
+        measure_changes(&self.repo_path, &mut file_changes, &diff_stat);
+        let untracked_files = prioritize_untracked(&self.repo_path, self.get_untracked_files(&status).await?);
+        tracing::debug!(
+            "{} staged, {} modified and {} untracked files on '{}'",
+            status.staged_files.len(), status.modified_files.len(), untracked_files.len(), branch_name
+        );
         
         Ok(GitInfo {
             status,
@@ -103,174 +335,416 @@ impl GitCollector {
     }
     
     async fn get_status(&self) -> Result<GitStatus> {
-        let output = Command::new("git")
-            .args(&["status", "--porcelain=v1"])
-            .current_dir(&self.repo_path)
+        self.backend.status().await
+    }
+    
+    async fn get_diff_stat(&self) -> Result<DiffInfo> {
+        self.backend.diff_stat().await
+    }
+    
+    async fn get_file_changes(&self) -> Result<Vec<FileChange>> {
+        self.backend.file_changes().await
+    }
+    
+    /// Get the full patch of the staged changes
+    pub async fn get_staged_diff(&self) -> Result<String> {
+        self.backend.staged_diff().await
+    }
+    
+    /// Get the patch of the last commit (against its first parent for merges)
+    /// 
+    /// In a shallow clone cut off right at HEAD the parent is missing and the
+    /// patch would list every file as added, so that is an error instead.
+    pub async fn get_head_patch(&self) -> Result<String> {
+        if self.is_shallow_boundary("HEAD").await {
+            return Err(GitAiError::Git(
+                "The parent of the last commit is not in this shallow clone, so its changes can't be \
+                 described. Fetch it with git fetch --deepen=1".to_string()
+            ).into());
+        }
+        
+        let output = git_command(&self.repo_path)
+            .args(["show", "--format=", "--patch", "-m", "--first-parent", "HEAD"])
             .output()
             .await?;
-            
+        
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get git status: {}", error)).into());
+            return Err(GitAiError::Git(format!("Failed to read the last commit: {}", error)).into());
         }
         
-        let status_text = String::from_utf8_lossy(&output.stdout);
-        GitStatus::parse(&status_text)
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
     
-    async fn get_diff_stat(&self) -> Result<DiffInfo> {
-        // Get staged changes
-        let staged_output = Command::new("git")
-            .args(&["diff", "--cached", "--numstat"])
-            .current_dir(&self.repo_path)
+    /// Whether history was cut off at `rev`, leaving out its parents
+    async fn is_shallow_boundary(&self, rev: &str) -> bool {
+        let (Some(sha), Some(shallow_file)) = (
+            git_stdout(&self.repo_path, &["rev-parse", "--verify", "--quiet", rev]).await,
+            git_stdout(&self.repo_path, &["rev-parse", "--git-path", "shallow"]).await,
+        ) else {
+            return false;
+        };
+        
+        std::fs::read_to_string(self.repo_path.join(shallow_file.trim()))
+            .is_ok_and(|boundaries| boundaries.lines().any(|line| line.trim() == sha.trim()))
+    }
+    
+    /// Get the full message of the last commit
+    pub async fn get_head_message(&self) -> Result<String> {
+        let output = git_command(&self.repo_path)
+            .args(["log", "-1", "--format=%B"])
             .output()
             .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to read the last commit message: {}", error)).into());
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    /// List untracked files, expanding the `dir/` entries `git status` collapses
+    /// 
+    /// Expansion stops at `MAX_UNTRACKED_FILES`; directories beyond that are
+    /// kept as their `dir/` entry so they are still mentioned.
+    async fn get_untracked_files(&self, status: &GitStatus) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        
+        for entry in &status.untracked_files {
+            if !is_directory_entry(entry) {
+                files.push(entry.clone());
+                continue;
+            }
             
-        // Get unstaged changes
-        let unstaged_output = Command::new("git")
-            .args(&["diff", "--numstat"])
-            .current_dir(&self.repo_path)
+            let contained = self.list_untracked_in(entry).await?;
+            if files.len() + contained.len() > MAX_UNTRACKED_FILES {
+                files.push(entry.clone());
+            } else {
+                files.extend(contained);
+            }
+        }
+        
+        Ok(files)
+    }
+    
+    /// Untracked, non-ignored files under a directory
+    async fn list_untracked_in(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        self.backend.untracked_in(directory).await
+    }
+    
+    pub async fn get_branch_name(&self) -> Result<String> {
+        self.backend.branch_name().await
+    }
+    
+    async fn get_last_commit(&self) -> Result<Option<String>> {
+        self.backend.last_commit().await
+    }
+    
+    /// Up to `count` commits leading to HEAD, newest first, leaving out merges
+    /// 
+    /// A repository without commits has none.
+    pub async fn get_recent_commits(&self, count: usize) -> Result<Vec<CommitSummary>> {
+        if count == 0 || self.get_last_commit().await?.is_none() {
+            return Ok(Vec::new());
+        }
+        let output = git_command(&self.repo_path)
+            .args(["log", "--no-merges", CommitSummary::LOG_FORMAT, &format!("--max-count={}", count), "HEAD"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to list recent commits: {}", error.trim())).into());
+        }
+        
+        Ok(CommitSummary::parse_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+    
+    /// List the commits in a revision range such as `@{u}..HEAD`, newest first
+    pub async fn get_commits_in_range(&self, range: &str) -> Result<Vec<CommitSummary>> {
+        let output = git_command(&self.repo_path)
+            .args(["log", CommitSummary::LOG_FORMAT, range])
             .output()
             .await?;
             
-        if !staged_output.status.success() {
-            let error = String::from_utf8_lossy(&staged_output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get staged diff stats: {}", error)).into());
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            if error.contains("no upstream") {
+                return Err(GitAiError::Git(
+                    "No upstream branch configured. Push with -u first or pass --range <base>..HEAD".to_string()
+                ).into());
+            }
+            if CloneInfo::detect(&self.repo_path).await.shallow {
+                return Err(GitAiError::Git(format!(
+                    "Failed to list commits in {}: part of the range is missing from this shallow clone. \
+                     Fetch more history with git fetch --unshallow (or --deepen=<n>). {}",
+                    range, error.trim()
+                )).into());
+            }
+            return Err(GitAiError::Git(format!("Failed to list commits in {}: {}", range, error)).into());
         }
         
-        if !unstaged_output.status.success() {
-            let error = String::from_utf8_lossy(&unstaged_output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get unstaged diff stats: {}", error)).into());
+        Ok(CommitSummary::parse_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+    
+    /// Get the `--shortstat` summary of the changes in a revision range
+    pub async fn get_range_shortstat(&self, range: &str) -> Result<String> {
+        let output = git_read_command(&self.repo_path)
+            .args(["diff", "--shortstat", range])
+            .output()
+            .await?;
+            
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get diff stats for {}: {}", range, error)).into());
         }
         
-        // Parse both sets of changes
-        let staged_diff = String::from_utf8_lossy(&staged_output.stdout);
-        let unstaged_diff = String::from_utf8_lossy(&unstaged_output.stdout);
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+    
+    /// Combined patch of a revision range, e.g. `origin/main...HEAD` for a whole branch
+    pub async fn get_range_patch(&self, range: &str) -> Result<String> {
+        let output = git_read_command(&self.repo_path)
+            .args(["diff", range])
+            .output()
+            .await?;
         
-        // Combine the diffs
-        let mut combined_diff = staged_diff.to_string();
-        if !unstaged_diff.trim().is_empty() {
-            combined_diff.push_str("\n");
-            combined_diff.push_str(&unstaged_diff);
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to get the diff of {}: {}", range, error)).into());
         }
         
-        DiffInfo::parse(&combined_diff)
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
     
-    async fn get_file_changes(&self) -> Result<Vec<FileChange>> {
-        // Get staged changes
-        let staged_output = Command::new("git")
-            .args(&["diff", "--cached", "--name-status"])
-            .current_dir(&self.repo_path)
+    /// The `core.commentString` or `core.commentChar` setting, if either is set
+    pub async fn get_comment_setting(&self) -> Option<String> {
+        let setting = match git_stdout(&self.repo_path, &["config", "core.commentString"]).await {
+            Some(setting) => setting,
+            None => git_stdout(&self.repo_path, &["config", "core.commentChar"]).await?,
+        };
+        Some(setting.trim_end_matches(['\n', '\r']).to_string())
+    }
+    
+    /// Email of the configured git user, who commits the squashed branch
+    pub async fn get_user_email(&self) -> Option<String> {
+        git_stdout(&self.repo_path, &["config", "user.email"]).await.map(|email| email.trim().to_string())
+    }
+    
+    /// The committer as `Name <email>`, as `git commit --signoff` would credit them
+    pub async fn get_committer_identity(&self) -> Option<String> {
+        // The ident ends in a timestamp and time zone, e.g. "Dev <dev@example.com> 1700000000 +0100"
+        let ident = git_stdout(&self.repo_path, &["var", "GIT_COMMITTER_IDENT"]).await?;
+        let end = ident.rfind('>')?;
+        Some(ident[..=end].trim().to_string())
+    }
+    
+    /// Authors of the commits in a range as `Name <email>`, oldest first
+    pub async fn get_range_authors(&self, range: &str) -> Result<Vec<String>> {
+        let output = git_command(&self.repo_path)
+            .args(["log", "--reverse", "--format=%an <%ae>", range])
             .output()
             .await?;
-            
-        // Get unstaged changes
-        let unstaged_output = Command::new("git")
-            .args(&["diff", "--name-status"])
-            .current_dir(&self.repo_path)
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to list the authors of {}: {}", range, error)).into());
+        }
+        
+        Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+    }
+    
+    /// Describe the commit at HEAD, including how far it is from the upstream branch
+    pub async fn get_commit_stats(&self) -> Result<CommitStats> {
+        let output = git_command(&self.repo_path)
+            .args(["show", "--numstat", CommitStats::SHOW_FORMAT, "HEAD"])
             .output()
             .await?;
             
-        if !staged_output.status.success() {
-            let error = String::from_utf8_lossy(&staged_output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get staged file changes: {}", error)).into());
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to read the new commit: {}", error)).into());
         }
         
-        if !unstaged_output.status.success() {
-            let error = String::from_utf8_lossy(&unstaged_output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get unstaged file changes: {}", error)).into());
-        }
+        let stats = CommitStats::parse_show(&String::from_utf8_lossy(&output.stdout))?;
         
-        // Parse both sets of changes
-        let staged_changes = String::from_utf8_lossy(&staged_output.stdout);
-        let unstaged_changes = String::from_utf8_lossy(&unstaged_output.stdout);
+        // Without an upstream branch there is nothing to compare against
+        let output = git_command(&self.repo_path)
+            .args(["rev-list", "--left-right", "--count", "@{u}...HEAD"])
+            .output()
+            .await?;
         
-        // Combine the changes
-        let mut all_changes = Vec::new();
+        if !output.status.success() {
+            return Ok(stats);
+        }
         
-        // Add staged changes first
-        if !staged_changes.trim().is_empty() {
-            let mut changes = FileChange::parse_list(&staged_changes)?;
-            all_changes.append(&mut changes);
+        Ok(stats.with_ahead_behind(&String::from_utf8_lossy(&output.stdout)))
+    }
+    
+    /// Describe the project for the prompt, from the per-repository cache when it is fresh
+    /// 
+    /// Failures only lose context, so they fall back to whatever could be read.
+    pub async fn get_project_info(&self, readme_lines: usize) -> ProjectInfo {
+        let root = self.run_for_line(&["rev-parse", "--show-toplevel"]).await
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.repo_path.clone());
+        
+        let cache = ProjectCache::open_default();
+        if let Some(info) = cache.as_ref().and_then(|cache| cache.load(&root, readme_lines)) {
+            return info;
         }
         
-        // Add unstaged changes, avoiding duplicates
-        if !unstaged_changes.trim().is_empty() {
-            let changes = FileChange::parse_list(&unstaged_changes)?;
-            // Only add unstaged changes for files that aren't already in the list
-            for change in changes {
-                if !all_changes.iter().any(|c: &FileChange| c.file_path == change.file_path) {
-                    all_changes.push(change);
-                }
-            }
+        let remote_url = self.run_for_line(&["remote", "get-url", "origin"]).await;
+        let info = ProjectInfo::detect(&root, remote_url.as_deref(), readme_lines);
+        
+        if let Some(cache) = cache.filter(|_| !self.write_mode.is_read_only()) {
+            let _ = cache.store(&root, readme_lines, &info);
         }
+        info
+    }
+    
+    /// Crates, packages and Python packages declared by the manifests git tracks
+    /// 
+    /// Empty when the files can't be listed.
+    pub async fn get_project_layout(&self) -> ProjectLayout {
+        let Some(root) = self.run_for_line(&["rev-parse", "--show-toplevel"]).await.map(PathBuf::from) else {
+            return ProjectLayout::default();
+        };
+        let args = [&["ls-files", "-z", "--"][..], MANIFEST_PATHSPECS].concat();
+        let Some(files) = git_stdout(&root, &args).await else {
+            return ProjectLayout::default();
+        };
+        let manifests: Vec<PathBuf> = files.split('\0').filter(|path| !path.is_empty()).map(PathBuf::from).collect();
+        ProjectLayout::detect(&root, &manifests)
+    }
+    
+    /// First line of a git command's output, or `None` if it fails or prints nothing
+    async fn run_for_line(&self, args: &[&str]) -> Option<String> {
+        let output = git_command(&self.repo_path)
+            .args(args)
+            .output()
+            .await
+            .ok()?;
         
-        Ok(all_changes)
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let line = stdout.lines().next()?.trim();
+        (output.status.success() && !line.is_empty()).then(|| line.to_string())
+    }
+    
+    /// Upstream of the current branch as `remote/branch`, if one is configured
+    pub async fn get_upstream(&self) -> Option<String> {
+        self.run_for_line(&["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"]).await
     }
     
-    async fn get_untracked_files(&self) -> Result<Vec<PathBuf>> {
-        let output = Command::new("git")
-            .args(&["ls-files", "--others", "--exclude-standard"])
-            .current_dir(&self.repo_path)
+    /// Names of the configured remotes
+    pub async fn get_remotes(&self) -> Result<Vec<String>> {
+        let output = git_command(&self.repo_path)
+            .args(["remote"])
             .output()
             .await?;
-            
+        
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get untracked files: {}", error)).into());
+            return Err(GitAiError::Git(format!("Failed to list remotes: {}", error)).into());
         }
         
-        let files_text = String::from_utf8_lossy(&output.stdout);
-        Ok(files_text
+        Ok(String::from_utf8_lossy(&output.stdout)
             .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| PathBuf::from(line.trim()))
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
             .collect())
     }
     
-    async fn get_branch_name(&self) -> Result<String> {
-        let output = Command::new("git")
-            .args(&["branch", "--show-current"])
-            .current_dir(&self.repo_path)
+    /// Push HEAD to `target`
+    pub async fn push(&self, target: &PushTarget) -> Result<()> {
+        self.write_mode.check("pushing")?;
+        
+        let output = git_command(&self.repo_path)
+            .args(target.push_args())
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to push to {}: {}", target.remote, error.trim())).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the path of the repository's git directory
+    pub async fn git_dir(&self) -> Result<PathBuf> {
+        self.backend.git_dir().await
+    }
+    
+    /// Get the id of the tree currently staged in the index
+    /// 
+    /// This writes a tree object, so it is not available in read-only mode.
+    pub async fn staged_tree(&self) -> Result<String> {
+        self.write_mode.check("writing the staged tree")?;
+        
+        let output = git_command(&self.repo_path)
+            .args(["write-tree"])
             .output()
             .await?;
             
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(GitAiError::Git(format!("Failed to get branch name: {}", error)).into());
+            return Err(GitAiError::Git(format!("Failed to read staged tree: {}", error)).into());
         }
         
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
     
-    async fn get_last_commit(&self) -> Result<Option<String>> {
-        let output = Command::new("git")
-            .args(&["log", "-1", "--pretty=%B"])
-            .current_dir(&self.repo_path)
+    /// Get the directory git runs hooks from (honours `core.hooksPath`)
+    pub async fn hooks_dir(&self) -> Result<PathBuf> {
+        let output = git_command(&self.repo_path)
+            .args(["rev-parse", "--git-path", "hooks"])
             .output()
             .await?;
             
         if !output.status.success() {
-            return Ok(None);
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to locate hooks directory: {}", error)).into());
         }
         
-        let commit_msg = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        if commit_msg.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(commit_msg))
+        let hooks_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        Ok(self.repo_path.join(hooks_dir))
+    }
+    
+    /// Object ids of the working-tree content of `paths`, to notice files a hook rewrites
+    /// 
+    /// Paths that don't exist (deletions) are left out. Nothing is written to
+    /// the object database.
+    pub async fn worktree_hashes(&self, paths: &[PathBuf]) -> Result<BTreeMap<PathBuf, String>> {
+        let present: Vec<&PathBuf> = paths.iter().filter(|path| self.repo_path.join(path).is_file()).collect();
+        if present.is_empty() {
+            return Ok(BTreeMap::new());
         }
+        
+        let output = git_command(&self.repo_path)
+            .arg("hash-object")
+            .arg("--")
+            .args(&present)
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to hash working tree files: {}", error)).into());
+        }
+        
+        let hashes = String::from_utf8_lossy(&output.stdout);
+        Ok(present.into_iter().cloned().zip(hashes.lines().map(str::to_string)).collect())
     }
     
     /// Stage all unstaged changes in the working directory
     pub async fn stage_all_unstaged(&self) -> Result<()> {
+        self.write_mode.check("staging changes")?;
+        
         // First, stage modified and deleted files
-        let output = Command::new("git")
-            .args(&["add", "--update"])
-            .current_dir(&self.repo_path)
-            .output()
-            .await?;
+        let output = run_writing_index(&self.repo_path, &["add", "--update"]).await?;
             
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -278,11 +752,7 @@ impl GitCollector {
         }
         
         // Then, stage untracked files (but respect .gitignore)
-        let output = Command::new("git")
-            .args(&["add", "--all"])
-            .current_dir(&self.repo_path)
-            .output()
-            .await?;
+        let output = run_writing_index(&self.repo_path, &["add", "--all"]).await?;
             
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -291,4 +761,142 @@ impl GitCollector {
         
         Ok(())
     }
+    
+    /// Replace the message of the last commit, leaving staged changes out of it
+    /// 
+    /// `gpg_sign` is a `--gpg-sign` argument to sign the amended commit with.
+    pub async fn amend_message(&self, message: &str, gpg_sign: Option<&str>) -> Result<()> {
+        self.write_mode.check("amending the last commit")?;
+        
+        let mut amend = vec!["commit", "--amend", "--only"];
+        amend.extend(gpg_sign);
+        amend.extend(["-m", message]);
+        let output = run_writing_index(&self.repo_path, &amend).await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Git commit --amend failed: {}", error)).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Run `git <command> --continue` after committing the stopped step of `sequence`
+    /// 
+    /// Later steps keep their own messages, so git's editor is skipped.
+    pub async fn continue_sequence(&self, sequence: Sequence) -> Result<()> {
+        self.write_mode.check("continuing the sequence")?;
+        
+        let output = git_command(&self.repo_path)
+            .args([sequence.command(), "--continue"])
+            .env("GIT_EDITOR", "true")
+            .env(HOOK_SKIP_ENV, "1")
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!(
+                "git {} --continue stopped: {}", sequence.command(), error.trim()
+            )).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Attach `text` to HEAD as a note under `notes_ref`, replacing any note it already has
+    pub async fn add_note(&self, notes_ref: &str, text: &str) -> Result<()> {
+        self.write_mode.check("adding a note")?;
+        
+        let notes_ref = format!("--ref={}", notes_ref);
+        let output = git_command(&self.repo_path)
+            .args(["notes", &notes_ref, "add", "-f", "-m", text, "HEAD"])
+            .output()
+            .await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to add a note to HEAD: {}", error)).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// The note `commit` has under `notes_ref`, or `None` when it has none
+    pub async fn read_note(&self, notes_ref: &str, commit: &str) -> Result<Option<String>> {
+        let revision = format!("{}^{{commit}}", commit);
+        if git_stdout(&self.repo_path, &["rev-parse", "--verify", "--quiet", &revision]).await.is_none() {
+            return Err(GitAiError::Git(format!("'{}' is not a commit", commit)).into());
+        }
+        
+        let notes_ref = format!("--ref={}", notes_ref);
+        Ok(git_stdout(&self.repo_path, &["notes", &notes_ref, "show", commit]).await)
+    }
+    
+    /// Unstage everything, leaving the working tree alone, like `git reset`
+    pub async fn unstage_all(&self) -> Result<()> {
+        self.write_mode.check("unstaging changes")?;
+        
+        let output = run_writing_index(&self.repo_path, &["reset", "-q"]).await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to unstage changes: {}", error)).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Stage `paths` as they are in `tree`, e.g. one from [`GitCollector::staged_tree`]
+    /// 
+    /// Paths missing from the tree are removed from the index. The working
+    /// tree is left alone.
+    pub async fn stage_from_tree(&self, tree: &str, paths: &[PathBuf]) -> Result<()> {
+        self.write_mode.check("staging changes")?;
+        
+        let source = format!("--source={}", tree);
+        let mut args: Vec<&OsStr> = vec!["restore".as_ref(), "--staged".as_ref(), source.as_ref(), "--".as_ref()];
+        args.extend(paths.iter().map(|path| path.as_os_str()));
+        let output = run_writing_index(&self.repo_path, &args).await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to stage files from {}: {}", tree, error)).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Make the index match `tree` again, e.g. after a split commit failed halfway
+    pub async fn restore_staged_tree(&self, tree: &str) -> Result<()> {
+        self.write_mode.check("staging changes")?;
+        
+        let output = run_writing_index(&self.repo_path, &["read-tree", tree]).await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to restore the staged changes: {}", error)).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Stage the given files, including deletions and untracked files
+    pub async fn stage_files(&self, files: &[PathBuf]) -> Result<()> {
+        self.write_mode.check("staging changes")?;
+        if files.is_empty() {
+            return Ok(());
+        }
+        
+        let mut args: Vec<&OsStr> = vec!["add".as_ref(), "--all".as_ref(), "--".as_ref()];
+        args.extend(files.iter().map(|file| file.as_os_str()));
+        let output = run_writing_index(&self.repo_path, &args).await?;
+        
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(GitAiError::Git(format!("Failed to stage files: {}", error)).into());
+        }
+        
+        Ok(())
+    }
 }
@@ -0,0 +1,40 @@
+//! Environment of the git subprocesses whose output gets parsed
+//!
+//! Git translates its messages and some porcelain headers, so a German or
+//! Japanese locale would change the text matched here. Every git command runs
+//! in the C locale; the rest of the caller's environment is kept, since push,
+//! signing, credential helpers and hooks need proxies, the session bus and
+//! toolchains.
+
+use std::path::Path;
+use tokio::process::Command;
+
+/// Variables set on every git command, overriding the caller's locale
+pub const LOCALE_ENV: &[(&str, &str)] = &[("LC_ALL", "C"), ("LANG", "C")];
+
+/// Removed from the environment: gettext reads `LANGUAGE` before the locale
+const UNSET: &[&str] = &["LANGUAGE"];
+
+/// A `git` command running in `repo_path` in the C locale
+pub fn git_command(repo_path: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.envs(LOCALE_ENV.iter().copied()).current_dir(repo_path);
+    for name in UNSET {
+        command.env_remove(name);
+    }
+    command
+}
+
+/// A `git` command that only reads the repository
+/// 
+/// `status` and `diff` refresh the file timestamps in the index and write it
+/// back whenever they can take its lock. `GIT_OPTIONAL_LOCKS=0` stops
+/// `status` doing so and `diff.autoRefreshIndex=false` stops `diff`, so
+/// reads leave `.git` as it was (see `--no-write`) and never race a
+/// concurrent `git add` for the lock. Without the refresh, `diff --raw` also
+/// lists files whose timestamp changed but whose content didn't.
+pub fn git_read_command(repo_path: &Path) -> Command {
+    let mut command = git_command(repo_path);
+    command.env("GIT_OPTIONAL_LOCKS", "0").args(["-c", "diff.autoRefreshIndex=false"]);
+    command
+}
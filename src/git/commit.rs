@@ -0,0 +1,77 @@
+use crate::git::DiffInfo;
+use anyhow::Result;
+use serde::Serialize;
+
+/// Summary of a commit that was just created
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitStats {
+    pub sha: String,
+    /// Branch the commit landed on, empty for a detached HEAD
+    pub branch: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Commits ahead of the upstream branch, if one is configured
+    pub ahead: Option<usize>,
+    /// Commits behind the upstream branch, if one is configured
+    pub behind: Option<usize>,
+}
+
+impl CommitStats {
+    /// Format string for `git show --numstat` producing output understood by `parse_show`
+    pub const SHOW_FORMAT: &'static str = "--format=%h%x09%D";
+    
+    /// Parse `git show --numstat --format=%h%x09%D` output
+    pub fn parse_show(show_text: &str) -> Result<Self> {
+        let header = show_text.lines().next().unwrap_or_default();
+        let (sha, refs) = header.split_once('\t').unwrap_or((header, ""));
+        let branch = refs
+            .split(", ")
+            .find_map(|r| r.strip_prefix("HEAD -> "))
+            .unwrap_or_default();
+        
+        let diff = DiffInfo::parse(show_text)?;
+        
+        Ok(CommitStats {
+            sha: sha.trim().to_string(),
+            branch: branch.trim().to_string(),
+            files_changed: diff.files_changed,
+            insertions: diff.insertions,
+            deletions: diff.deletions,
+            ahead: None,
+            behind: None,
+        })
+    }
+    
+    /// Record `git rev-list --left-right --count @{u}...HEAD` output (`behind<TAB>ahead`)
+    pub fn with_ahead_behind(mut self, counts: &str) -> Self {
+        let mut parts = counts.split_whitespace().map(|n| n.parse::<usize>().ok());
+        if let (Some(Some(behind)), Some(Some(ahead))) = (parts.next(), parts.next()) {
+            self.ahead = Some(ahead);
+            self.behind = Some(behind);
+        }
+        self
+    }
+    
+    pub fn shortstat(&self) -> String {
+        format!(
+            "{} files changed, {} insertions(+), {} deletions(-)",
+            self.files_changed, self.insertions, self.deletions
+        )
+    }
+    
+    pub fn display(&self) -> String {
+        let branch = if self.branch.is_empty() { "detached HEAD" } else { &self.branch };
+        let mut output = format!("  Commit: {} on {}\n", self.sha, branch);
+        output.push_str(&format!("  Changes: {}\n", self.shortstat()));
+        
+        match (self.ahead, self.behind) {
+            (Some(ahead), Some(behind)) => {
+                output.push_str(&format!("  Upstream: {} ahead, {} behind\n", ahead, behind));
+            }
+            _ => output.push_str("  Upstream: none configured\n"),
+        }
+        
+        output
+    }
+}
@@ -1,5 +1,8 @@
 use anyhow::Result;
 
+/// Columns `stat_graph` fits its lines into, matching git's default
+pub const STAT_WIDTH: usize = 80;
+
 /// Git diff statistics
 #[derive(Debug, Clone)]
 pub struct DiffInfo {
@@ -9,11 +12,28 @@ pub struct DiffInfo {
     pub file_stats: Vec<FileStat>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct FileStat {
     pub filename: String,
     pub insertions: usize,
     pub deletions: usize,
+    /// Git counts no lines in binary files, reporting `-\t-` instead
+    pub binary: bool,
+}
+
+/// The new path of a `--numstat` entry, which names renames `old => new` or `src/{old => new}.rs`
+fn renamed_path(entry: &str) -> String {
+    let Some((before, after)) = entry.split_once(" => ") else {
+        return entry.to_string();
+    };
+    match (before.rfind('{'), after.find('}')) {
+        (Some(open), Some(close)) => {
+            let path = format!("{}{}{}", &before[..open], &after[..close], &after[close + 1..]);
+            // `{old => }` leaves an empty directory behind
+            path.replace("//", "/").trim_start_matches('/').to_string()
+        }
+        _ => after.to_string(),
+    }
 }
 
 impl DiffInfo {
@@ -33,9 +53,10 @@ impl DiffInfo {
                 continue;
             }
             
+            let binary = parts[0] == "-" && parts[1] == "-";
             let insertions = parts[0].parse::<usize>().unwrap_or(0);
             let deletions = parts[1].parse::<usize>().unwrap_or(0);
-            let filename = parts[2].to_string();
+            let filename = renamed_path(parts[2]);
             
             files_changed += 1;
             total_insertions += insertions;
@@ -45,6 +66,7 @@ impl DiffInfo {
                 filename,
                 insertions,
                 deletions,
+                binary,
             });
         }
         
@@ -56,6 +78,55 @@ impl DiffInfo {
         })
     }
     
+    /// Render the stats like `git diff --stat`, with a `+++---` histogram per file
+    /// 
+    /// Lines fit in `width` columns; bars are scaled down when the largest
+    /// change doesn't fit, the way git does.
+    pub fn stat_graph(&self, width: usize) -> String {
+        if self.file_stats.is_empty() {
+            return String::new();
+        }
+        
+        let name_width = self.file_stats.iter().map(|s| s.filename.chars().count()).max().unwrap_or(0);
+        let max_change = self.file_stats.iter().map(|s| s.insertions + s.deletions).max().unwrap_or(0);
+        let count_width = max_change.to_string().len();
+        let graph_width = width.saturating_sub(name_width + count_width + 5).max(8);
+        
+        let scale = |n: usize| {
+            if n == 0 || max_change <= graph_width {
+                n
+            } else {
+                1 + n * (graph_width - 1) / max_change
+            }
+        };
+        
+        let mut output = String::new();
+        for stat in &self.file_stats {
+            if stat.binary {
+                output.push_str(&format!(" {:<name_width$} | Bin\n", stat.filename));
+                continue;
+            }
+            let total = scale(stat.insertions + stat.deletions);
+            let added = scale(stat.insertions).min(total);
+            output.push_str(&format!(
+                " {:<name_width$} | {:>count_width$} {}{}\n",
+                stat.filename,
+                stat.insertions + stat.deletions,
+                "+".repeat(added),
+                "-".repeat(total - added),
+            ));
+        }
+        
+        let plural = |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+        output.push_str(&format!(
+            " {}, {}, {}\n",
+            plural(self.files_changed, "file changed", "files changed"),
+            plural(self.insertions, "insertion(+)", "insertions(+)"),
+            plural(self.deletions, "deletion(-)", "deletions(-)"),
+        ));
+        output
+    }
+    
     pub fn display(&self) -> String {
         if self.files_changed == 0 {
             return "  No changes in diff".to_string();
@@ -67,7 +138,9 @@ impl DiffInfo {
         );
         
         for stat in &self.file_stats {
-            if stat.insertions > 0 || stat.deletions > 0 {
+            if stat.binary {
+                output.push_str(&format!("    {}: binary\n", stat.filename));
+            } else if stat.insertions > 0 || stat.deletions > 0 {
                 output.push_str(&format!(
                     "    {}: +{} -{}\n",
                     stat.filename, stat.insertions, stat.deletions
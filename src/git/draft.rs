@@ -0,0 +1,66 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A generated commit message saved after the user cancelled the commit
+/// 
+/// Drafts are tied to the staged tree they were generated for, so they are
+/// only offered again while the index is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    pub tree: String,
+    pub message: String,
+}
+
+impl Draft {
+    /// File name of the draft inside the git directory
+    pub const FILE_NAME: &'static str = "AI_COMMIT_DRAFT";
+    
+    pub fn new(tree: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            tree: tree.into(),
+            message: message.into(),
+        }
+    }
+    
+    pub fn path(git_dir: &Path) -> PathBuf {
+        git_dir.join(Self::FILE_NAME)
+    }
+    
+    /// Load the saved draft, if there is a readable one
+    pub fn load(git_dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(git_dir)).ok()?;
+        let (header, message) = content.split_once("\n\n")?;
+        let tree = header.strip_prefix("tree ")?.trim();
+        let message = message.trim();
+        
+        if tree.is_empty() || message.is_empty() {
+            return None;
+        }
+        Some(Self::new(tree, message))
+    }
+    
+    /// Load the saved draft only if it was generated for `tree`
+    pub fn load_for_tree(git_dir: &Path, tree: &str) -> Option<Self> {
+        Self::load(git_dir).filter(|draft| draft.tree == tree)
+    }
+    
+    pub fn save(&self, git_dir: &Path) -> Result<PathBuf> {
+        let path = Self::path(git_dir);
+        let content = format!("tree {}\n\n{}\n", self.tree, self.message.trim());
+        fs::write(&path, content)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to save draft: {}", e)))?;
+        Ok(path)
+    }
+    
+    /// Remove the saved draft, if any
+    pub fn discard(git_dir: &Path) -> Result<()> {
+        let path = Self::path(git_dir);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| GitAiError::FileSystem(format!("Failed to remove draft: {}", e)))?;
+        }
+        Ok(())
+    }
+}
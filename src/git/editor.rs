@@ -0,0 +1,85 @@
+//! Editing a generated message in the user's editor before committing
+//!
+//! Mirrors `git commit -e`: the editor is the one git would use (`GIT_EDITOR`,
+//! `core.editor`, `VISUAL`, `EDITOR`), comment lines are stripped afterwards,
+//! and an empty message aborts the commit.
+
+use crate::git::command::git_command;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Help appended below the message, stripped again after editing
+const EDIT_HELP: &str = "\
+# Please enter the commit message for your changes. Lines starting
+# with '#' will be ignored, and an empty message aborts the commit.
+";
+
+/// Editor command git would open, as configured (e.g. `code --wait`)
+pub async fn git_editor(repo_path: &Path) -> Result<String> {
+    let output = git_command(repo_path)
+        .args(["var", "GIT_EDITOR"])
+        .output()
+        .await
+        .map_err(|e| GitAiError::Git(format!("Failed to run git var: {}", e)))?;
+    
+    let editor = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || editor.is_empty() {
+        return Err(GitAiError::Git(
+            "No editor is configured; set core.editor or $GIT_EDITOR".to_string()
+        ).into());
+    }
+    Ok(editor)
+}
+
+/// Open `message` in the git editor and return the edited message
+/// 
+/// Returns `None` when nothing but comments and whitespace is left.
+pub async fn edit_message(repo_path: &Path, message: &str) -> Result<Option<String>> {
+    let editor = git_editor(repo_path).await?;
+    
+    // Editors recognize the file name and highlight it as a commit message
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("COMMIT_EDITMSG");
+    fs::write(&path, format!("{}\n\n{}", message.trim_end(), EDIT_HELP))?;
+    
+    // Like git, run the editor through the shell so it may carry arguments; ":" edits nothing
+    if editor != ":" {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(&editor)
+            .arg(&path)
+            .current_dir(repo_path)
+            .status()
+            .await
+            .map_err(|e| GitAiError::Git(format!("Failed to start the editor '{}': {}", editor, e)))?;
+        if !status.success() {
+            return Err(GitAiError::Git(format!("There was a problem with the editor '{}'", editor)).into());
+        }
+    }
+    
+    let edited = cleanup_message(&fs::read_to_string(&path)?);
+    Ok((!edited.is_empty()).then_some(edited))
+}
+
+/// Clean up an edited message the way `git commit` does by default
+/// 
+/// Drops `#` comment lines and trailing whitespace, collapses runs of blank
+/// lines and trims blank lines at either end.
+pub fn cleanup_message(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().filter(|line| !line.starts_with('#')) {
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
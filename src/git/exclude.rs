@@ -0,0 +1,115 @@
+//! Paths left out of the prompt context (`exclude_paths`, `--exclude`)
+//!
+//! Lockfiles, generated code and vendored directories change by hundreds of
+//! lines that say little about a commit, yet would take the file list and
+//! diff budget from the changes that matter. Files matching one of the globs
+//! are only counted in the context, e.g. `1 lockfile updated`.
+//!
+//! Globs are read like `.gitignore`: `*` and `?` stay within a path segment,
+//! `**` spans any number of them, a glob without a `/` matches a name in any
+//! directory, and a directory matches everything in it.
+
+use regex::Regex;
+use std::path::Path;
+
+/// Lockfiles of common package managers, excluded unless `exclude_paths` says otherwise
+pub const LOCKFILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+];
+
+/// Globs of the paths kept out of the prompt context
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    patterns: Vec<Regex>,
+}
+
+impl PathFilter {
+    /// Exclude paths matching any of `globs`; empty ones match nothing
+    pub fn new(globs: &[String]) -> Self {
+        let patterns = globs.iter()
+            .map(|glob| glob.trim())
+            .filter(|glob| !glob.is_empty())
+            .map(|glob| Regex::new(&glob_to_regex(glob)).expect("escaped glob is a valid regex"))
+            .collect();
+        Self { patterns }
+    }
+    
+    /// Whether `path`, relative to the repository root, is left out
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.patterns.iter().any(|pattern| pattern.is_match(&path))
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// Whether `path` is the lockfile of a package manager
+pub fn is_lockfile(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| LOCKFILES.iter().any(|lockfile| name.to_string_lossy() == *lockfile))
+}
+
+/// What changed among the `excluded` paths, e.g. `1 lockfile updated, 3 other files`
+pub fn summarize(excluded: &[&Path]) -> String {
+    let lockfiles = excluded.iter().filter(|path| is_lockfile(path)).count();
+    let others = excluded.len() - lockfiles;
+    let mut parts = Vec::new();
+    if lockfiles > 0 {
+        parts.push(format!("{} lockfile{} updated", lockfiles, plural(lockfiles)));
+    }
+    if others > 0 {
+        let other = if lockfiles > 0 { "other " } else { "" };
+        parts.push(format!("{} {}file{} changed", others, other, plural(others)));
+    }
+    parts.join(", ")
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+/// An anchored regex matching the paths `glob` matches
+fn glob_to_regex(glob: &str) -> String {
+    let glob = glob.trim_end_matches('/');
+    // A glob without a slash names files or directories at any depth
+    let (anchored, glob) = match glob.strip_prefix('/') {
+        Some(rooted) => (true, rooted),
+        None => (glob.contains('/'), glob),
+    };
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                match chars.peek() {
+                    Some('/') => {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    }
+                    _ => regex.push_str(".*"),
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // A directory takes everything in it along
+    regex.push_str("(?:/.*)?$");
+    regex
+}
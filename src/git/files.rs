@@ -1,23 +1,251 @@
 use crate::utils::error::GitAiError;
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Represents a file change in git
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct FileChange {
     pub change_type: ChangeType,
     pub file_path: PathBuf,
     pub old_path: Option<PathBuf>, // For renames/copies
+    /// How much of a renamed or copied file matches its source, in percent (`R098`)
+    pub similarity: Option<u8>,
+    /// Git found no text in the content (`-` line counts in `--numstat`)
+    pub is_binary: bool,
+    /// Bytes in the working tree, for files that are there
+    pub size: Option<u64>,
 }
 
-#[derive(Debug, Clone)]
+/// Files larger than this are described by their size rather than by their content
+pub const LARGE_FILE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Default)]
 pub enum ChangeType {
     Added,
+    #[default]
     Modified,
     Deleted,
     Renamed,
     Copied,
     Unmerged,
+    /// Only the file mode changed (e.g. `chmod +x`); the content is identical
+    ModeChanged { old_mode: String, new_mode: String },
+    /// A symbolic link now points somewhere else, or a file became a link or vice versa
+    SymlinkChanged,
+}
+
+/// Git's file mode for symbolic links
+const SYMLINK_MODE: &str = "120000";
+
+impl ChangeType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeType::Added => "added",
+            ChangeType::Modified => "modified",
+            ChangeType::Deleted => "deleted",
+            ChangeType::Renamed => "renamed",
+            ChangeType::Copied => "copied",
+            ChangeType::Unmerged => "unmerged",
+            ChangeType::ModeChanged { .. } => "mode changed",
+            ChangeType::SymlinkChanged => "symlink changed",
+        }
+    }
+    
+    /// Classify a modification from the old and new modes and whether the content changed
+    /// 
+    /// Returns `None` when neither the mode nor a symlink is involved.
+    pub fn from_modes(old_mode: &str, new_mode: &str, content_changed: bool) -> Option<ChangeType> {
+        if old_mode == SYMLINK_MODE || new_mode == SYMLINK_MODE {
+            Some(ChangeType::SymlinkChanged)
+        } else if old_mode != new_mode && !content_changed {
+            Some(ChangeType::ModeChanged {
+                old_mode: old_mode.to_string(),
+                new_mode: new_mode.to_string(),
+            })
+        } else {
+            None
+        }
+    }
+    
+    /// Explanation for the model of changes that have no diff lines
+    pub fn prompt_note(&self) -> Option<String> {
+        match self {
+            ChangeType::ModeChanged { old_mode, new_mode } => Some(format!(
+                "[MODE CHANGE ONLY: {}, no content changed]",
+                ChangeType::describe_mode(old_mode, new_mode)
+            )),
+            ChangeType::SymlinkChanged => Some("[SYMLINK: the link target changed, not file content]".to_string()),
+            _ => None,
+        }
+    }
+    
+    /// Plain description of a mode-only change, e.g. `now executable`
+    pub fn describe_mode(old_mode: &str, new_mode: &str) -> String {
+        let executable = |mode: &str| mode == "100755";
+        match (executable(old_mode), executable(new_mode)) {
+            (false, true) => "now executable".to_string(),
+            (true, false) => "no longer executable".to_string(),
+            _ => format!("mode {} -> {}", old_mode, new_mode),
+        }
+    }
+}
+
+/// `2.3 MB`, `512 KB` or `80 bytes`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} byte{}", bytes, if bytes == 1 { "" } else { "s" });
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if size < 10.0 && unit > 0 {
+        format!("{:.1} {}", size, UNITS[unit])
+    } else {
+        format!("{:.0} {}", size, UNITS[unit])
+    }
+}
+
+/// Whether an untracked path is editor, OS or merge-tool debris rather than work
+/// 
+/// Covers vim swap files, emacs backups and lock files, `.DS_Store` and
+/// friends, and the `.orig`/`.rej` leftovers of merges and patches.
+pub fn is_scratch_file(path: &Path) -> bool {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_lowercase(),
+        None => return false,
+    };
+    
+    const NAMES: &[&str] = &[".ds_store", "thumbs.db", "desktop.ini", ".directory"];
+    const EXTENSIONS: &[&str] = &[".swp", ".swo", ".swn", ".tmp", ".bak", ".orig", ".rej"];
+    
+    NAMES.contains(&name.as_str())
+        || EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+        || name.ends_with('~')
+        || name.starts_with(".#")
+        || name.starts_with("._")
+        || name.starts_with(".~lock.")
+        || (name.starts_with('#') && name.ends_with('#'))
+        || path.components().any(|c| c.as_os_str() == "__MACOSX")
+}
+
+/// Broad category of a changed file, used to pick category-specific prompts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeCategory {
+    Docs,
+    Tests,
+    Deps,
+    Ci,
+    Code,
+}
+
+impl ChangeCategory {
+    /// Every category, in the order of the `[templates]` table
+    pub const ALL: [ChangeCategory; 5] = [
+        ChangeCategory::Docs,
+        ChangeCategory::Tests,
+        ChangeCategory::Deps,
+        ChangeCategory::Ci,
+        ChangeCategory::Code,
+    ];
+    
+    /// Key used for this category in the `[templates]` config table
+    pub fn key(&self) -> &'static str {
+        match self {
+            ChangeCategory::Docs => "docs",
+            ChangeCategory::Tests => "tests",
+            ChangeCategory::Deps => "deps",
+            ChangeCategory::Ci => "ci",
+            ChangeCategory::Code => "code",
+        }
+    }
+    
+    /// Conventional commit type implied by the category, if any
+    pub fn commit_type(&self) -> Option<&'static str> {
+        match self {
+            ChangeCategory::Docs => Some("docs"),
+            ChangeCategory::Tests => Some("test"),
+            ChangeCategory::Deps => Some("chore(deps)"),
+            ChangeCategory::Ci => Some("ci"),
+            ChangeCategory::Code => None,
+        }
+    }
+    
+    /// Force the message's conventional commit type to the one implied by the category
+    /// 
+    /// An existing `type(scope):` prefix is replaced, otherwise the type is prepended.
+    pub fn apply_commit_type(&self, message: &str) -> String {
+        let commit_type = match self.commit_type() {
+            Some(commit_type) => commit_type,
+            None => return message.to_string(),
+        };
+        
+        let message = message.trim();
+        let prefix = regex::Regex::new(r"^[a-zA-Z]+(\([^)]*\))?!?:\s*").unwrap();
+        let subject = prefix.replace(message, "");
+        format!("{}: {}", commit_type, subject)
+    }
+}
+
+/// Staged files written in one language, and the component they live in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageGroup {
+    pub language: &'static str,
+    /// Top-level directory shared by the files, or the language when they have none
+    pub component: String,
+    pub files: Vec<PathBuf>,
+}
+
+impl LanguageGroup {
+    /// Group `changes` by language, largest group first
+    /// 
+    /// Files that aren't code (docs, config, lock files) are left out, and
+    /// JavaScript counts as TypeScript when both appear, since they usually
+    /// belong to the same frontend.
+    pub fn detect(changes: &[&FileChange]) -> Vec<LanguageGroup> {
+        let languages: Vec<(&'static str, &PathBuf)> = changes.iter()
+            .filter_map(|change| change.language().map(|language| (language, &change.file_path)))
+            .collect();
+        let has_typescript = languages.iter().any(|(language, _)| *language == "TypeScript");
+        
+        let mut groups: Vec<LanguageGroup> = Vec::new();
+        for (language, path) in languages {
+            let language = if language == "JavaScript" && has_typescript { "TypeScript" } else { language };
+            match groups.iter_mut().find(|group| group.language == language) {
+                Some(group) => group.files.push(path.clone()),
+                None => groups.push(LanguageGroup { language, component: String::new(), files: vec![path.clone()] }),
+            }
+        }
+        
+        for group in &mut groups {
+            group.component = common_top_dir(&group.files).unwrap_or_else(|| group.language.to_lowercase());
+        }
+        groups.sort_by(|a, b| b.files.len().cmp(&a.files.len()).then(a.language.cmp(b.language)));
+        groups
+    }
+    
+    /// e.g. `backend (Rust)`, or just `rust` for files at the top of the repository
+    pub fn label(&self) -> String {
+        if self.component == self.language.to_lowercase() {
+            self.component.clone()
+        } else {
+            format!("{} ({})", self.component, self.language)
+        }
+    }
+}
+
+/// First path component every file shares, if they all have one
+fn common_top_dir(files: &[PathBuf]) -> Option<String> {
+    let top = |path: &PathBuf| {
+        let mut components = path.components();
+        let first = components.next()?;
+        components.next().map(|_| first.as_os_str().to_string_lossy().to_string())
+    };
+    let first = top(files.first()?)?;
+    files.iter().all(|file| top(file).as_ref() == Some(&first)).then_some(first)
 }
 
 impl FileChange {
@@ -37,6 +265,10 @@ impl FileChange {
     }
     
     fn parse_line(line: &str) -> Result<FileChange> {
+        if line.starts_with(':') {
+            return Self::parse_raw_line(line);
+        }
+        
         let parts: Vec<&str> = line.split('\t').collect();
         if parts.is_empty() {
             return Err(GitAiError::Git(format!("Invalid git status line: {}", line)).into());
@@ -53,6 +285,11 @@ impl FileChange {
             _ => return Err(GitAiError::Git(format!("Unknown git status: {}", status)).into()),
         };
         
+        let similarity = match change_type {
+            ChangeType::Renamed | ChangeType::Copied => status[1..].parse().ok(),
+            _ => None,
+        };
+        
         let (file_path, old_path) = match change_type {
             ChangeType::Renamed | ChangeType::Copied => {
                 if parts.len() < 3 {
@@ -72,10 +309,63 @@ impl FileChange {
             change_type,
             file_path,
             old_path,
+            similarity,
+            is_binary: false,
+            size: None,
         })
     }
     
+    /// Parse a `git diff --raw` line: `:<old mode> <new mode> <old sha> <new sha> <status>\t<path>`
+    /// 
+    /// The modes and blob ids let mode-only and symlink changes, which have
+    /// no lines in the diff, be told apart from ordinary modifications.
+    fn parse_raw_line(line: &str) -> Result<FileChange> {
+        let (header, paths) = line.split_once('\t')
+            .ok_or_else(|| GitAiError::Git(format!("Invalid raw diff line: {}", line)))?;
+        let fields: Vec<&str> = header.trim_start_matches(':').split_whitespace().collect();
+        if fields.len() < 5 {
+            return Err(GitAiError::Git(format!("Invalid raw diff line: {}", line)).into());
+        }
+        
+        let (old_mode, new_mode, old_sha, new_sha, status) =
+            (fields[0], fields[1], fields[2], fields[3], fields[4]);
+        let mut change = Self::parse_line(&format!("{}\t{}", status.replace('T', "M"), paths))?;
+        
+        if matches!(change.change_type, ChangeType::Modified) {
+            // Unstaged files have an all-zero new blob id, so their content can't be compared
+            let unhashed = new_sha.chars().all(|c| c == '0');
+            let content_changed = unhashed || old_sha != new_sha;
+            if let Some(change_type) = ChangeType::from_modes(old_mode, new_mode, content_changed) {
+                change.change_type = change_type;
+            }
+        }
+        
+        Ok(change)
+    }
+    
+    /// The path, or `old -> new` for renames and copies
+    pub fn path_display(&self) -> String {
+        match self.old_path {
+            Some(ref old_path) => format!("{} -> {}", old_path.display(), self.file_path.display()),
+            None => self.file_path.display().to_string(),
+        }
+    }
+    
     pub fn display(&self) -> String {
+        match self.content_note() {
+            Some(note) => format!("{} ({})", self.status_display(), note),
+            None => self.status_display(),
+        }
+    }
+    
+    fn status_display(&self) -> String {
+        match self.similarity {
+            Some(similarity) => format!("{} ({}% similar)", self.path_status(), similarity),
+            None => self.path_status(),
+        }
+    }
+    
+    fn path_status(&self) -> String {
         match &self.change_type {
             ChangeType::Added => format!("A  {}", self.file_path.display()),
             ChangeType::Modified => format!("M  {}", self.file_path.display()),
@@ -95,6 +385,52 @@ impl FileChange {
                 }
             }
             ChangeType::Unmerged => format!("U  {}", self.file_path.display()),
+            ChangeType::ModeChanged { old_mode, new_mode } => format!(
+                "M  {} (mode change only, {})",
+                self.file_path.display(),
+                ChangeType::describe_mode(old_mode, new_mode)
+            ),
+            ChangeType::SymlinkChanged => format!("M  {} (symlink target changed)", self.file_path.display()),
+        }
+    }
+    
+    /// Whether the file is over `LARGE_FILE_BYTES`
+    pub fn is_large(&self) -> bool {
+        self.size.is_some_and(|size| size > LARGE_FILE_BYTES)
+    }
+    
+    /// What changed in a binary or large file, whose diff says little,
+    /// e.g. `binary image added, 2.3 MB`
+    pub fn content_note(&self) -> Option<String> {
+        let what = if self.is_binary {
+            format!("binary {}", self.binary_kind())
+        } else if self.is_large() {
+            "large file".to_string()
+        } else {
+            return None;
+        };
+        let mut note = format!("{} {}", what, self.change_type.label());
+        if let Some(size) = self.size {
+            note.push_str(&format!(", {}", format_size(size)));
+        }
+        Some(note)
+    }
+    
+    /// What sort of binary the file is, from its extension
+    fn binary_kind(&self) -> &'static str {
+        let extension = self.file_path.extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "ico" | "tif" | "tiff" | "avif" | "heic" | "psd" => "image",
+            "ttf" | "otf" | "woff" | "woff2" | "eot" => "font",
+            "mp3" | "wav" | "ogg" | "flac" | "m4a" | "aac" => "audio file",
+            "mp4" | "mov" | "webm" | "mkv" | "avi" => "video",
+            "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" => "document",
+            "zip" | "gz" | "tgz" | "tar" | "xz" | "bz2" | "7z" | "rar" | "jar" | "whl" => "archive",
+            "exe" | "dll" | "so" | "dylib" | "a" | "o" | "lib" | "wasm" => "executable",
+            "db" | "sqlite" | "sqlite3" => "database",
+            _ => "file",
         }
     }
     
@@ -120,4 +456,79 @@ impl FileChange {
         
         config_files.iter().any(|&config| path_str.ends_with(config))
     }
+    
+    /// Programming language of the file, from its extension
+    /// 
+    /// Markup, styles and ambiguous extensions such as `.h` have none, so
+    /// they never make a changeset count as spanning several languages.
+    pub fn language(&self) -> Option<&'static str> {
+        let extension = self.file_path.extension()?.to_string_lossy().to_lowercase();
+        let language = match extension.as_str() {
+            "rs" => "Rust",
+            "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+            "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+            "py" => "Python",
+            "go" => "Go",
+            "java" => "Java",
+            "kt" | "kts" => "Kotlin",
+            "swift" => "Swift",
+            "c" => "C",
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => "C++",
+            "cs" => "C#",
+            "rb" => "Ruby",
+            "php" => "PHP",
+            "scala" => "Scala",
+            "dart" => "Dart",
+            "ex" | "exs" => "Elixir",
+            "sh" | "bash" | "zsh" => "Shell",
+            _ => return None,
+        };
+        Some(language)
+    }
+    
+    /// Classify the file into a broad change category
+    pub fn category(&self) -> ChangeCategory {
+        if self.is_ci_file() {
+            ChangeCategory::Ci
+        } else if self.is_dependency_file() {
+            ChangeCategory::Deps
+        } else if self.is_docs_file() {
+            ChangeCategory::Docs
+        } else if self.is_test_file() {
+            ChangeCategory::Tests
+        } else {
+            ChangeCategory::Code
+        }
+    }
+    
+    pub fn is_docs_file(&self) -> bool {
+        let path_str = self.file_path.to_string_lossy().to_lowercase();
+        let in_docs_dir = path_str.starts_with("docs/") || 
+            path_str.starts_with("doc/") || 
+            path_str.contains("/docs/");
+        let doc_extensions = [".md", ".markdown", ".rst", ".adoc", ".txt"];
+        
+        in_docs_dir || doc_extensions.iter().any(|&ext| path_str.ends_with(ext))
+    }
+    
+    pub fn is_ci_file(&self) -> bool {
+        let path_str = self.file_path.to_string_lossy().to_lowercase();
+        path_str.starts_with(".github/workflows/") ||
+        path_str.starts_with(".circleci/") ||
+        path_str.ends_with(".gitlab-ci.yml") ||
+        path_str.ends_with(".travis.yml") ||
+        path_str.ends_with("azure-pipelines.yml") ||
+        path_str.ends_with("jenkinsfile")
+    }
+    
+    pub fn is_dependency_file(&self) -> bool {
+        let path_str = self.file_path.to_string_lossy().to_lowercase();
+        let dependency_files = [
+            "cargo.lock", "package-lock.json", "yarn.lock", "pnpm-lock.yaml",
+            "poetry.lock", "pipfile.lock", "gemfile.lock", "composer.lock",
+            "go.sum", "go.mod", "requirements.txt"
+        ];
+        
+        dependency_files.iter().any(|&deps| path_str.ends_with(deps))
+    }
 }
@@ -0,0 +1,183 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marker line identifying hooks written by git-ai-commit
+pub const HOOK_MARKER: &str = "# Installed by git-ai-commit";
+
+/// Set for git commands run by git-ai-commit, whose commits already have a generated message
+pub const HOOK_SKIP_ENV: &str = "GIT_AI_COMMIT_SKIP_HOOK";
+
+/// Body of the prepare-commit-msg hook; a failing hook never blocks the commit
+pub const PREPARE_COMMIT_MSG_HOOK: &str = "git-ai-commit --hook \"$1\" --hook-source \"$2\" || true";
+
+/// Write a git hook script into the hooks directory
+/// 
+/// Existing hooks are only replaced if git-ai-commit installed them, so a
+/// user's own hook is never clobbered.
+pub fn install_hook(hooks_dir: &Path, name: &str, body: &str) -> Result<PathBuf> {
+    fs::create_dir_all(hooks_dir)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to create hooks directory: {}", e)))?;
+    
+    let hook_path = hooks_dir.join(name);
+    if let Ok(existing) = fs::read_to_string(&hook_path) {
+        if !existing.contains(HOOK_MARKER) {
+            return Err(GitAiError::Git(format!(
+                "A {} hook already exists at {}. Remove it or add git-ai-commit to it manually.",
+                name,
+                hook_path.display()
+            )).into());
+        }
+    }
+    
+    let script = format!("#!/bin/sh\n{}\n{}\n", HOOK_MARKER, body.trim_end());
+    fs::write(&hook_path, script)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to write {} hook: {}", name, e)))?;
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&hook_path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&hook_path, perms)?;
+    }
+    
+    Ok(hook_path)
+}
+
+/// Remove a hook git-ai-commit installed; returns its path if there was one
+/// 
+/// Hooks without the marker belong to the user and are left alone.
+pub fn uninstall_hook(hooks_dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    let hook_path = hooks_dir.join(name);
+    let existing = match fs::read_to_string(&hook_path) {
+        Ok(existing) => existing,
+        Err(_) => return Ok(None),
+    };
+    if !existing.contains(HOOK_MARKER) {
+        return Err(GitAiError::Git(format!(
+            "The {} hook at {} was not installed by git-ai-commit; leaving it in place.",
+            name,
+            hook_path.display()
+        )).into());
+    }
+    
+    fs::remove_file(&hook_path)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to remove {} hook: {}", name, e)))?;
+    Ok(Some(hook_path))
+}
+
+/// Hooks `git commit` runs that can stop it
+pub const COMMIT_HOOKS: &[&str] = &["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// The user's hooks among `names` that git runs from `hooks_dir`
+/// 
+/// Hooks git-ai-commit installed never fail a commit, so they are left out.
+pub fn active_hooks(hooks_dir: &Path, names: &[&str]) -> Vec<String> {
+    names.iter()
+        .filter(|name| {
+            let hook_path = hooks_dir.join(name);
+            is_executable(&hook_path)
+                && fs::read_to_string(&hook_path).map_or(true, |script| !script.contains(HOOK_MARKER))
+        })
+        .map(|name| name.to_string())
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Files whose content differs between two snapshots of the working tree
+pub fn changed_files(before: &BTreeMap<PathBuf, String>, after: &BTreeMap<PathBuf, String>) -> Vec<PathBuf> {
+    before.keys()
+        .chain(after.keys().filter(|path| !before.contains_key(*path)))
+        .filter(|path| before.get(*path) != after.get(*path))
+        .cloned()
+        .collect()
+}
+
+/// A commit that failed while the user's commit hooks were installed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    /// Hooks that ran, any of which may have stopped the commit
+    pub hooks: Vec<String>,
+    /// Files the hooks changed in the working tree, e.g. by reformatting them
+    pub modified: Vec<PathBuf>,
+    /// What git and the hooks printed
+    pub output: String,
+}
+
+impl HookFailure {
+    /// What happened, the files the hooks changed and their output
+    pub fn describe(&self) -> String {
+        let mut text = format!("The commit was rejected, most likely by the {} hook", self.hooks.join(" or "));
+        if !self.modified.is_empty() {
+            text.push_str(&format!("\nIt changed {} file(s) that aren't staged yet:", self.modified.len()));
+            for path in &self.modified {
+                text.push_str(&format!("\n  - {}", path.display()));
+            }
+        }
+        let output = self.output.trim();
+        if !output.is_empty() {
+            text.push_str(&format!("\n{}", output));
+        }
+        text
+    }
+    
+    /// How to get the commit through by hand
+    pub fn hint(&self) -> &'static str {
+        if self.modified.is_empty() {
+            "Fix what the hook reports and commit again, or pass --no-verify to skip the hooks"
+        } else {
+            "Stage the hook's changes with git add and commit again, or pass --no-verify to skip the hooks"
+        }
+    }
+    
+    /// Ways to go on, the likeliest first
+    pub fn recoveries(&self) -> Vec<HookRecovery> {
+        let mut recoveries = if self.modified.is_empty() {
+            vec![HookRecovery::Retry]
+        } else {
+            vec![HookRecovery::StageAndRegenerate, HookRecovery::StageAndRetry]
+        };
+        recoveries.extend([HookRecovery::NoVerify, HookRecovery::Cancel]);
+        recoveries
+    }
+}
+
+/// What to do after a hook stopped the commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookRecovery {
+    /// Stage the hook's changes and describe the result afresh
+    StageAndRegenerate,
+    /// Stage the hook's changes and commit with the same message
+    StageAndRetry,
+    /// Commit again as is, e.g. after fixing the problem in another terminal
+    Retry,
+    /// Commit without running the hooks
+    NoVerify,
+    /// Give up, keeping the message as a draft
+    Cancel,
+}
+
+impl HookRecovery {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HookRecovery::StageAndRegenerate => "Stage the hook's changes and regenerate the message",
+            HookRecovery::StageAndRetry => "Stage the hook's changes and commit with this message",
+            HookRecovery::Retry => "Commit again",
+            HookRecovery::NoVerify => "Commit with --no-verify, skipping the hooks",
+            HookRecovery::Cancel => "Cancel",
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Reading the repository in-process with libgit2
+//!
+//! Produces the same status, stats and changes as [`CliBackend`], built from
+//! libgit2's diff deltas instead of parsed `git` output.
+//!
+//! [`CliBackend`]: crate::git::backend::CliBackend
+
+use crate::git::backend::GitBackend;
+use crate::git::diff::FileStat;
+use crate::git::files::ChangeType;
+use crate::git::{DiffInfo, FileChange, GitStatus};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use git2::{Delta, Diff, DiffFindOptions, DiffFormat, DiffOptions, Patch, Repository, Status, StatusOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Reads the repository through libgit2
+pub struct Libgit2Backend {
+    repo: Mutex<Repository>,
+}
+
+impl Libgit2Backend {
+    /// Open the repository containing `repo_path`
+    pub fn open(repo_path: &Path) -> Result<Self> {
+        let repo = Repository::discover(repo_path)
+            .map_err(|e| GitAiError::Git(format!("Failed to open repository: {}", e.message())))?;
+        Ok(Self { repo: Mutex::new(repo) })
+    }
+    
+    fn repo(&self) -> MutexGuard<'_, Repository> {
+        self.repo.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Changes from HEAD to the index, like `git diff --cached`
+fn staged(repo: &Repository) -> Result<Diff<'_>, git2::Error> {
+    // Before the first commit everything in the index is new
+    let head = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut options = DiffOptions::new();
+    let mut diff = repo.diff_tree_to_index(head.as_ref(), None, Some(&mut options))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true).copies(true)))?;
+    Ok(diff)
+}
+
+/// Changes from the index to the working tree, like `git diff`
+fn unstaged(repo: &Repository) -> Result<Diff<'_>, git2::Error> {
+    repo.diff_index_to_workdir(None, Some(&mut DiffOptions::new()))
+}
+
+fn git_error(what: &str) -> impl Fn(git2::Error) -> anyhow::Error + '_ {
+    move |e| GitAiError::Git(format!("Failed to {}: {}", what, e.message())).into()
+}
+
+fn path_of(path: Option<&Path>) -> PathBuf {
+    path.map(Path::to_path_buf).unwrap_or_default()
+}
+
+/// Line counts per file, as `git diff --numstat` reports them
+fn file_stats(diff: &Diff) -> Result<Vec<FileStat>, git2::Error> {
+    let mut stats = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let delta = diff.get_delta(index).expect("index is within the deltas");
+        let ((_, insertions, deletions), binary) = match Patch::from_diff(diff, index)? {
+            Some(patch) => (patch.line_stats()?, patch.delta().flags().is_binary()),
+            None => ((0, 0, 0), delta.flags().is_binary()),
+        };
+        stats.push(FileStat {
+            filename: path_of(delta.new_file().path()).to_string_lossy().into_owned(),
+            insertions,
+            deletions,
+            binary,
+        });
+    }
+    Ok(stats)
+}
+
+/// The changed files of a diff, classified as in `git diff --raw`
+fn changes(diff: &Diff) -> Vec<FileChange> {
+    diff.deltas()
+        .filter_map(|delta| {
+            let (old, new) = (delta.old_file(), delta.new_file());
+            let change_type = match delta.status() {
+                Delta::Added => ChangeType::Added,
+                Delta::Deleted => ChangeType::Deleted,
+                Delta::Renamed => ChangeType::Renamed,
+                Delta::Copied => ChangeType::Copied,
+                Delta::Conflicted => ChangeType::Unmerged,
+                Delta::Modified | Delta::Typechange => {
+                    // Unstaged files have an all-zero new blob id, so their content can't be compared
+                    let content_changed = new.id().is_zero() || old.id() != new.id();
+                    let (old_mode, new_mode) = (format!("{:o}", u32::from(old.mode())), format!("{:o}", u32::from(new.mode())));
+                    ChangeType::from_modes(&old_mode, &new_mode, content_changed).unwrap_or(ChangeType::Modified)
+                }
+                _ => return None,
+            };
+            let old_path = matches!(change_type, ChangeType::Renamed | ChangeType::Copied).then(|| path_of(old.path()));
+            let file_path = match change_type {
+                ChangeType::Deleted => path_of(old.path()),
+                _ => path_of(new.path()),
+            };
+            // git2 doesn't expose the similarity libgit2 computes
+            Some(FileChange { change_type, file_path, old_path, similarity: None, is_binary: false, size: None })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl GitBackend for Libgit2Backend {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+    
+    async fn status(&self) -> Result<GitStatus> {
+        let repo = self.repo();
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(false).renames_head_to_index(true);
+        let statuses = repo.statuses(Some(&mut options)).map_err(git_error("get git status"))?;
+        
+        let mut status = GitStatus {
+            staged_files: Vec::new(),
+            modified_files: Vec::new(),
+            untracked_files: Vec::new(),
+            deleted_files: Vec::new(),
+        };
+        for entry in statuses.iter() {
+            let flags = entry.status();
+            let path = entry.head_to_index()
+                .and_then(|delta| delta.new_file().path().map(Path::to_path_buf))
+                .unwrap_or_else(|| PathBuf::from(String::from_utf8_lossy(entry.path_bytes()).into_owned()));
+            
+            if flags.intersects(Status::INDEX_NEW | Status::INDEX_MODIFIED | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE) {
+                status.staged_files.push(path.clone());
+            }
+            if flags.contains(Status::INDEX_DELETED) {
+                status.staged_files.push(path.clone());
+                status.deleted_files.push(path.clone());
+            }
+            if flags.contains(Status::WT_MODIFIED) {
+                status.modified_files.push(path.clone());
+            }
+            if flags.contains(Status::WT_DELETED) {
+                status.deleted_files.push(path.clone());
+            }
+            if flags.contains(Status::WT_NEW) {
+                status.untracked_files.push(path);
+            }
+        }
+        Ok(status)
+    }
+    
+    async fn diff_stat(&self) -> Result<DiffInfo> {
+        let repo = self.repo();
+        let mut stats = staged(&repo)
+            .and_then(|diff| file_stats(&diff))
+            .map_err(git_error("get staged diff stats"))?;
+        stats.extend(
+            unstaged(&repo)
+                .and_then(|diff| file_stats(&diff))
+                .map_err(git_error("get unstaged diff stats"))?
+        );
+        
+        Ok(DiffInfo {
+            files_changed: stats.len(),
+            insertions: stats.iter().map(|stat| stat.insertions).sum(),
+            deletions: stats.iter().map(|stat| stat.deletions).sum(),
+            file_stats: stats,
+        })
+    }
+    
+    async fn file_changes(&self) -> Result<Vec<FileChange>> {
+        let repo = self.repo();
+        let mut all_changes = changes(&staged(&repo).map_err(git_error("get staged file changes"))?);
+        let unstaged = changes(&unstaged(&repo).map_err(git_error("get unstaged file changes"))?);
+        
+        // Only add unstaged changes for files that aren't already in the list
+        for change in unstaged {
+            if !all_changes.iter().any(|c| c.file_path == change.file_path) {
+                all_changes.push(change);
+            }
+        }
+        Ok(all_changes)
+    }
+    
+    async fn untracked_in(&self, directory: &Path) -> Result<Vec<PathBuf>> {
+        let repo = self.repo();
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).pathspec(directory);
+        let statuses = repo.statuses(Some(&mut options)).map_err(git_error("get untracked files"))?;
+        
+        Ok(statuses.iter()
+            .filter(|entry| entry.status().contains(Status::WT_NEW))
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(entry.path_bytes()).into_owned()))
+            .collect())
+    }
+    
+    async fn branch_name(&self) -> Result<String> {
+        let repo = self.repo();
+        let head = repo.find_reference("HEAD").map_err(git_error("get branch name"))?;
+        
+        // An unborn branch has a name but no commit yet; a detached HEAD has no name
+        Ok(head.symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .unwrap_or_default()
+            .to_string())
+    }
+    
+    async fn last_commit(&self) -> Result<Option<String>> {
+        let repo = self.repo();
+        let message = repo.head()
+            .and_then(|head| head.peel_to_commit())
+            .ok()
+            .and_then(|commit| commit.message().map(|message| message.trim().to_string()));
+        Ok(message.filter(|message| !message.is_empty()))
+    }
+    
+    async fn staged_diff(&self) -> Result<String> {
+        let repo = self.repo();
+        let diff = staged(&repo).map_err(git_error("get staged diff"))?;
+        
+        let mut patch = Vec::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin() as u8);
+            }
+            patch.extend_from_slice(line.content());
+            true
+        }).map_err(git_error("get staged diff"))?;
+        
+        Ok(String::from_utf8_lossy(&patch).into_owned())
+    }
+    
+    async fn git_dir(&self) -> Result<PathBuf> {
+        Ok(self.repo().path().to_path_buf())
+    }
+}
@@ -0,0 +1,113 @@
+//! Waiting out an `index.lock` held by another git process
+//!
+//! IDEs and git GUIs refresh the index in the background, so a commit can
+//! race them for the lock. Short contention is waited out; a lock that stays
+//! is reported with what to do about it instead of git's raw stderr.
+
+use crate::git::command::git_command;
+use crate::git::hooks::HOOK_SKIP_ENV;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+
+/// Delays between attempts while the index is locked, about 3 seconds in total
+pub const LOCK_BACKOFF: [Duration; 5] = [
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+    Duration::from_millis(1500),
+];
+
+/// Locks older than this are most likely left behind by a crashed git process
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// Path of the repository's index lock (per worktree)
+pub async fn index_lock_path(repo_path: &Path) -> Result<PathBuf> {
+    let output = git_command(repo_path)
+        .args(["rev-parse", "--git-path", "index.lock"])
+        .output()
+        .await?;
+    
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(GitAiError::Git(format!("Failed to locate git directory: {}", error)).into());
+    }
+    
+    Ok(repo_path.join(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Whether git failed because another process holds the index lock
+pub fn is_lock_contention(stderr: &str) -> bool {
+    stderr.contains("index.lock")
+}
+
+/// Wait until `lock` is gone, sleeping for each of `delays` in turn
+pub async fn wait_for_index_lock(lock: &Path, delays: &[Duration]) -> Result<()> {
+    for delay in delays {
+        if !lock.exists() {
+            return Ok(());
+        }
+        tokio::time::sleep(*delay).await;
+    }
+    
+    if lock.exists() {
+        return Err(lock_error(lock));
+    }
+    Ok(())
+}
+
+/// Run a git command that writes the index, retrying while another process holds the lock
+pub async fn run_writing_index<S: AsRef<OsStr>>(repo_path: &Path, args: &[S]) -> Result<Output> {
+    let lock = index_lock_path(repo_path).await?;
+    wait_for_index_lock(&lock, &LOCK_BACKOFF).await?;
+    
+    let command: Vec<String> = args.iter().map(|arg| arg.as_ref().to_string_lossy().into_owned()).collect();
+    tracing::debug!("git {}", command.join(" "));
+    
+    let mut delays = LOCK_BACKOFF.iter();
+    loop {
+        // Commits made here already carry a generated message, so our hook stays out of it
+        let output = git_command(repo_path)
+            .args(args)
+            .env(HOOK_SKIP_ENV, "1")
+            .output()
+            .await?;
+        
+        if output.status.success() || !is_lock_contention(&String::from_utf8_lossy(&output.stderr)) {
+            return Ok(output);
+        }
+        tracing::debug!("The index is locked by another git process, retrying");
+        match delays.next() {
+            Some(delay) => tokio::time::sleep(*delay).await,
+            None => return Err(lock_error(&lock)),
+        }
+    }
+}
+
+/// Explain who likely holds the lock and how to get rid of it
+pub fn lock_error(lock: &Path) -> anyhow::Error {
+    let age = fs::metadata(lock)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+    
+    let message = match age {
+        Some(age) if age > STALE_AFTER => format!(
+            "The git index is locked: {} has existed for {}s, so it was probably left behind \
+             by a git process that crashed. If no git command is running, remove it with: rm \"{}\"",
+            lock.display(), age.as_secs(), lock.display()
+        ),
+        _ => format!(
+            "The git index is locked by another git process ({} exists). Wait for your IDE, \
+             git GUI or other git commands to finish and try again. If none is running, \
+             remove the stale lock with: rm \"{}\"",
+            lock.display(), lock.display()
+        ),
+    };
+    GitAiError::Git(message).into()
+}
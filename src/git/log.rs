@@ -0,0 +1,29 @@
+/// A commit's abbreviated hash and subject line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: String,
+    pub subject: String,
+}
+
+impl CommitSummary {
+    /// Format string for `git log` producing output understood by `parse_list`
+    pub const LOG_FORMAT: &'static str = "--format=%h%x09%s";
+    
+    /// Parse `git log --format=%h%x09%s` output
+    pub fn parse_list(log_text: &str) -> Vec<CommitSummary> {
+        log_text
+            .lines()
+            .filter_map(|line| {
+                let (sha, subject) = line.split_once('\t')?;
+                Some(CommitSummary {
+                    sha: sha.trim().to_string(),
+                    subject: subject.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+    
+    pub fn display(&self) -> String {
+        format!("{} {}", self.sha, self.subject)
+    }
+}
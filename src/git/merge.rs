@@ -0,0 +1,88 @@
+//! A merge waiting to be committed
+//!
+//! While `git merge` (or `git pull`) stops for conflicts, or runs with
+//! `--no-commit`, the git directory holds `MERGE_HEAD` with the commits being
+//! merged and `MERGE_MSG` with the message git prepared, listing the files
+//! that conflicted. The commit then finishes the merge, so its message should
+//! say what was merged rather than describe the staged changes as new work.
+
+use crate::git::files::ChangeType;
+use crate::git::GitInfo;
+use std::path::{Path, PathBuf};
+
+/// The state of an unfinished merge, read from the git directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeState {
+    /// Commits merged into HEAD, more than one for an octopus merge
+    pub heads: Vec<String>,
+    /// The message git prepared, without its comment lines and conflict list
+    pub message: String,
+    /// Files that had conflicts, as listed in `MERGE_MSG`
+    pub conflicts: Vec<PathBuf>,
+}
+
+impl MergeState {
+    /// The merge in progress in `git_dir`, if there is one
+    pub fn detect(git_dir: &Path) -> Option<MergeState> {
+        let heads = std::fs::read_to_string(git_dir.join("MERGE_HEAD")).ok()?;
+        let message = std::fs::read_to_string(git_dir.join("MERGE_MSG")).unwrap_or_default();
+        let mut state = MergeState::parse_message(&message);
+        state.heads = heads.lines().map(str::trim).filter(|head| !head.is_empty()).map(str::to_string).collect();
+        (!state.heads.is_empty()).then_some(state)
+    }
+    
+    /// Read the message and conflicted files from the contents of `MERGE_MSG`
+    /// 
+    /// Git lists conflicts in comment lines (`# Conflicts:` then `#\t<path>`);
+    /// versions before 1.8 wrote the same list uncommented.
+    pub fn parse_message(text: &str) -> MergeState {
+        let mut message = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut in_conflicts = false;
+        for line in text.lines() {
+            let uncommented = line.strip_prefix('#').map_or(line, |rest| rest.strip_prefix(' ').unwrap_or(rest));
+            if uncommented.trim_end() == "Conflicts:" {
+                in_conflicts = true;
+                continue;
+            }
+            if in_conflicts {
+                if let Some(path) = uncommented.strip_prefix('\t') {
+                    conflicts.push(PathBuf::from(path.trim()));
+                    continue;
+                }
+                in_conflicts = false;
+            }
+            if !line.starts_with('#') {
+                message.push(line);
+            }
+        }
+        
+        MergeState {
+            heads: Vec::new(),
+            message: message.join("\n").trim().to_string(),
+            conflicts,
+        }
+    }
+    
+    /// First line of git's message, e.g. `Merge branch 'feature/login' into main`
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or("Merge")
+    }
+    
+    /// Files still marked as conflicted in `git_info`
+    pub fn unresolved<'a>(&self, git_info: &'a GitInfo) -> Vec<&'a Path> {
+        git_info.file_changes.iter()
+            .filter(|change| matches!(change.change_type, ChangeType::Unmerged))
+            .map(|change| change.file_path.as_path())
+            .collect()
+    }
+    
+    /// Conflicted files that have since been resolved and staged
+    pub fn resolved(&self, git_info: &GitInfo) -> Vec<&Path> {
+        let unresolved = self.unresolved(git_info);
+        self.conflicts.iter()
+            .map(PathBuf::as_path)
+            .filter(|path| !unresolved.contains(path))
+            .collect()
+    }
+}
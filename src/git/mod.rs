@@ -4,8 +4,34 @@ pub mod collector;
 pub mod status;
 pub mod diff;
 pub mod files;
+pub mod exclude;
+pub mod merge;
+pub mod sequence;
+pub mod signing;
+pub mod patch;
+pub mod log;
+pub mod hooks;
+pub mod draft;
+pub mod commit;
+pub mod render;
+pub mod project;
+pub mod push;
+pub mod lock;
+pub mod clone;
+pub mod command;
+pub mod editor;
+pub mod plan;
+pub mod backend;
+#[cfg(feature = "libgit2")]
+pub mod libgit2;
 
-pub use collector::{GitCollector, GitInfo};
+pub use collector::{is_git_repository, GitCollector, GitInfo, UntrackedSummary};
 pub use status::GitStatus;
 pub use diff::DiffInfo;
-pub use files::FileChange;
+pub use files::{ChangeCategory, FileChange, LanguageGroup};
+pub use log::CommitSummary;
+pub use commit::CommitStats;
+pub use render::DisplayFormat;
+pub use project::ProjectInfo;
+pub use push::{PushStatus, PushTarget};
+pub use clone::CloneInfo;
@@ -0,0 +1,246 @@
+use crate::git::diff::FileStat;
+use crate::git::files::ChangeType;
+use crate::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// One file's section of a unified diff
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    pub change_type: ChangeType,
+    pub file_path: PathBuf,
+    pub old_path: Option<PathBuf>,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Hunk headers and hunk lines, without the file headers
+    pub lines: Vec<String>,
+    /// Modes from `old mode`/`new mode` or `index` headers, when present
+    pub old_mode: Option<String>,
+    pub new_mode: Option<String>,
+    /// From a `similarity index 98%` header of a rename or copy
+    pub similarity: Option<u8>,
+    /// Git left the content out (`Binary files ... differ` or a `GIT binary patch`)
+    pub binary: bool,
+}
+
+impl FilePatch {
+    fn new() -> Self {
+        Self {
+            change_type: ChangeType::Modified,
+            file_path: PathBuf::new(),
+            old_path: None,
+            insertions: 0,
+            deletions: 0,
+            lines: Vec::new(),
+            old_mode: None,
+            new_mode: None,
+            similarity: None,
+            binary: false,
+        }
+    }
+    
+    /// Reclassify a hunk-less modification as a mode or symlink change
+    fn classify_modes(&mut self) {
+        if !matches!(self.change_type, ChangeType::Modified) {
+            return;
+        }
+        if let (Some(old_mode), Some(new_mode)) = (&self.old_mode, &self.new_mode) {
+            let content_changed = !self.lines.is_empty();
+            if let Some(change_type) = ChangeType::from_modes(old_mode, new_mode, content_changed) {
+                self.change_type = change_type;
+            }
+        }
+    }
+    
+    /// Parse paths from a `diff --git a/<old> b/<new>` header
+    fn from_git_header(header: &str) -> Self {
+        let mut patch = Self::new();
+        if let Some(index) = header.rfind(" b/") {
+            let old = strip_path_prefix(&header[..index]);
+            let new = strip_path_prefix(&header[index + 1..]);
+            patch.file_path = PathBuf::from(new);
+            if old != new {
+                patch.old_path = Some(PathBuf::from(old));
+            }
+        }
+        patch
+    }
+}
+
+/// Split a unified diff into per-file patches
+/// 
+/// Understands `git diff` output (including new, deleted, renamed and copied
+/// files) as well as plain `diff -u`/`hg diff` output with only `---`/`+++` headers.
+pub fn parse_patch(text: &str) -> Vec<FilePatch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut patches = Vec::new();
+    let mut current: Option<FilePatch> = None;
+    let (mut old_remaining, mut new_remaining) = (0usize, 0usize);
+    
+    for (i, line) in lines.iter().enumerate() {
+        let in_hunk = old_remaining > 0 || new_remaining > 0;
+        
+        if in_hunk {
+            if let Some(patch) = current.as_mut() {
+                if line.starts_with('+') {
+                    patch.insertions += 1;
+                    new_remaining = new_remaining.saturating_sub(1);
+                } else if line.starts_with('-') {
+                    patch.deletions += 1;
+                    old_remaining = old_remaining.saturating_sub(1);
+                } else if !line.starts_with('\\') {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                }
+                patch.lines.push(line.to_string());
+            }
+            continue;
+        }
+        
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            patches.extend(current.take());
+            current = Some(FilePatch::from_git_header(header));
+            continue;
+        }
+        
+        if let Some(path) = line.strip_prefix("--- ") {
+            let next_is_new_path = lines.get(i + 1).is_some_and(|next| next.starts_with("+++ "));
+            let starts_new_file = current.as_ref().is_none_or(|patch| !patch.lines.is_empty());
+            if next_is_new_path && starts_new_file {
+                patches.extend(current.take());
+                current = Some(FilePatch::new());
+            }
+            if let Some(patch) = current.as_mut() {
+                let path = strip_path_prefix(path);
+                if path == "/dev/null" {
+                    patch.change_type = ChangeType::Added;
+                } else if patch.file_path.as_os_str().is_empty() {
+                    patch.file_path = PathBuf::from(path);
+                }
+            }
+            continue;
+        }
+        
+        let patch = match current.as_mut() {
+            Some(patch) => patch,
+            None => continue,
+        };
+        
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = strip_path_prefix(path);
+            if path == "/dev/null" {
+                patch.change_type = ChangeType::Deleted;
+            } else {
+                patch.file_path = PathBuf::from(path);
+            }
+        } else if line.starts_with("new file mode") {
+            patch.change_type = ChangeType::Added;
+        } else if line.starts_with("deleted file mode") {
+            patch.change_type = ChangeType::Deleted;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            patch.change_type = ChangeType::Renamed;
+            patch.old_path = Some(PathBuf::from(path));
+        } else if let Some(path) = line.strip_prefix("copy from ") {
+            patch.change_type = ChangeType::Copied;
+            patch.old_path = Some(PathBuf::from(path));
+        } else if let Some(mode) = line.strip_prefix("old mode ") {
+            patch.old_mode = Some(mode.trim().to_string());
+        } else if let Some(mode) = line.strip_prefix("new mode ") {
+            patch.new_mode = Some(mode.trim().to_string());
+        } else if let Some(index) = line.strip_prefix("index ") {
+            // `index <old>..<new> <mode>` carries the mode when it didn't change
+            if let Some(mode) = index.split_whitespace().nth(1) {
+                patch.old_mode.get_or_insert_with(|| mode.to_string());
+                patch.new_mode.get_or_insert_with(|| mode.to_string());
+            }
+        } else if let Some(similarity) = line.strip_prefix("similarity index ") {
+            patch.similarity = similarity.trim_end_matches('%').parse().ok();
+        } else if line.starts_with("Binary files ") || line.starts_with("GIT binary patch") {
+            patch.binary = true;
+        } else if line.starts_with("@@") {
+            (old_remaining, new_remaining) = parse_hunk_header(line);
+            patch.lines.push(line.to_string());
+        }
+    }
+    
+    patches.extend(current);
+    patches.retain(|patch| !patch.file_path.as_os_str().is_empty());
+    patches.iter_mut().for_each(FilePatch::classify_modes);
+    patches
+}
+
+/// Parse the old and new line counts from `@@ -a,b +c,d @@`
+fn parse_hunk_header(line: &str) -> (usize, usize) {
+    let count = |range: Option<&str>| -> usize {
+        range
+            .map(|r| r.split(',').nth(1).and_then(|n| n.parse().ok()).unwrap_or(1))
+            .unwrap_or(0)
+    };
+    
+    let mut parts = line.split_whitespace().skip(1);
+    let old = count(parts.next().and_then(|p| p.strip_prefix('-')));
+    let new = count(parts.next().and_then(|p| p.strip_prefix('+')));
+    (old, new)
+}
+
+/// Strip `a/`/`b/` prefixes and trailing timestamps from a diff header path
+fn strip_path_prefix(path: &str) -> &str {
+    let path = path.split('\t').next().unwrap_or(path).trim();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+impl GitInfo {
+    /// Build git information from a unified diff instead of a repository
+    /// 
+    /// Every file in the patch is treated as staged. Branch and commit
+    /// information are left empty since there is no repository to ask.
+    pub fn from_patch(patch: &str) -> Result<Self> {
+        let patches = parse_patch(patch);
+        
+        let file_changes: Vec<FileChange> = patches.iter()
+            .map(|p| FileChange {
+                change_type: p.change_type.clone(),
+                file_path: p.file_path.clone(),
+                old_path: p.old_path.clone(),
+                similarity: p.similarity,
+                is_binary: p.binary,
+                size: None,
+            })
+            .collect();
+        
+        let file_stats: Vec<FileStat> = patches.iter()
+            .map(|p| FileStat {
+                filename: p.file_path.to_string_lossy().to_string(),
+                insertions: p.insertions,
+                deletions: p.deletions,
+                binary: p.binary,
+            })
+            .collect();
+        
+        let deleted_files = file_changes.iter()
+            .filter(|c| matches!(c.change_type, ChangeType::Deleted))
+            .map(|c| c.file_path.clone())
+            .collect();
+        
+        Ok(GitInfo {
+            status: GitStatus {
+                staged_files: file_changes.iter().map(|c| c.file_path.clone()).collect(),
+                modified_files: vec![],
+                untracked_files: vec![],
+                deleted_files,
+            },
+            diff_stat: DiffInfo {
+                files_changed: file_stats.len(),
+                insertions: file_stats.iter().map(|s| s.insertions).sum(),
+                deletions: file_stats.iter().map(|s| s.deletions).sum(),
+                file_stats,
+            },
+            file_changes,
+            untracked_files: vec![],
+            branch_name: String::new(),
+            last_commit: None,
+        })
+    }
+}
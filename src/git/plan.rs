@@ -0,0 +1,91 @@
+//! Plans for splitting the staged changes into several commits
+//!
+//! With `--split` the model groups the staged files into logical commits.
+//! The plan makes sure every staged file is committed exactly once, whatever
+//! the model answered, and is committed group by group from the tree that
+//! was staged, so partially staged files keep only their staged hunks.
+
+use crate::git::FileChange;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Label of the group collecting staged files the model left out
+pub const LEFTOVER_LABEL: &str = "other";
+
+/// Files committed together, and the message they get
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitGroup {
+    /// Short name of the change, e.g. "tests"
+    pub label: String,
+    pub files: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl CommitGroup {
+    /// Paths whose index entries this commit changes, including the old side of renames
+    pub fn index_paths(&self, changes: &[FileChange]) -> Vec<PathBuf> {
+        let mut paths = self.files.clone();
+        for change in changes.iter().filter(|change| self.files.contains(&change.file_path)) {
+            if let Some(old_path) = &change.old_path {
+                if !paths.contains(old_path) {
+                    paths.push(old_path.clone());
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// Commits to make, in order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommitPlan {
+    pub commits: Vec<CommitGroup>,
+}
+
+impl CommitPlan {
+    /// Plan the `(label, files)` groups proposed for the `staged` files
+    /// 
+    /// Files that aren't staged are dropped, a file named twice stays in its
+    /// first group, and staged files no group names are committed last
+    /// under [`LEFTOVER_LABEL`]. Groups left empty are dropped.
+    pub fn new(groups: Vec<(String, Vec<PathBuf>)>, staged: &[PathBuf]) -> Self {
+        let mut planned: Vec<PathBuf> = Vec::new();
+        let mut commits = Vec::new();
+        for (label, files) in groups {
+            let mut group = Vec::new();
+            for file in files {
+                if staged.contains(&file) && !planned.contains(&file) {
+                    planned.push(file.clone());
+                    group.push(file);
+                }
+            }
+            if !group.is_empty() {
+                commits.push(CommitGroup { label, files: group, message: None });
+            }
+        }
+        
+        let leftover: Vec<PathBuf> = staged.iter().filter(|file| !planned.contains(file)).cloned().collect();
+        if !leftover.is_empty() {
+            commits.push(CommitGroup { label: LEFTOVER_LABEL.to_string(), files: leftover, message: None });
+        }
+        Self { commits }
+    }
+    
+    /// The plan as shown before committing
+    pub fn display(&self) -> String {
+        let mut output = String::new();
+        for (index, commit) in self.commits.iter().enumerate() {
+            output.push_str(&format!("{}. [{}] {} file(s)\n", index + 1, commit.label, commit.files.len()));
+            if let Some(message) = &commit.message {
+                for line in message.trim().lines() {
+                    output.push_str(&format!("   {}\n", line));
+                }
+            }
+            for file in &commit.files {
+                output.push_str(&format!("   - {}\n", file.display()));
+            }
+        }
+        output
+    }
+}
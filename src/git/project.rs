@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files read when describing the project, in the order they are tried
+const MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml"];
+
+const READMES: &[&str] = &["README.md", "README", "README.rst", "README.txt"];
+
+/// What the repository is, so the model can use the project's own terminology
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    /// Repository name from the remote URL, or the directory name
+    pub repo_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// First lines of the README, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme: Option<String>,
+}
+
+impl ProjectInfo {
+    /// Read the project's name and description from its manifest and README
+    /// 
+    /// `readme_lines` of 0 leaves the README out.
+    pub fn detect(repo_path: &Path, remote_url: Option<&str>, readme_lines: usize) -> Self {
+        let repo_name = remote_url
+            .and_then(repo_name_from_remote)
+            .or_else(|| repo_path.file_name().map(|name| name.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        
+        let (package_name, description) = MANIFESTS.iter()
+            .filter_map(|manifest| {
+                let content = fs::read_to_string(repo_path.join(manifest)).ok()?;
+                parse_manifest(manifest, &content)
+            })
+            .next()
+            .unwrap_or_default();
+        
+        let readme = (readme_lines > 0)
+            .then(|| READMES.iter().find_map(|readme| fs::read_to_string(repo_path.join(readme)).ok()))
+            .flatten()
+            .map(|content| readme_excerpt(&content, readme_lines))
+            .filter(|excerpt| !excerpt.is_empty());
+        
+        Self {
+            repo_name,
+            package_name,
+            description,
+            readme,
+        }
+    }
+    
+    /// Lines for the prompt's repository context
+    pub fn context(&self) -> String {
+        let mut context = String::new();
+        
+        if !self.repo_name.is_empty() {
+            context.push_str(&format!("Project: {}\n", self.repo_name));
+        }
+        if let Some(ref package_name) = self.package_name {
+            if *package_name != self.repo_name {
+                context.push_str(&format!("Package: {}\n", package_name));
+            }
+        }
+        if let Some(ref description) = self.description {
+            context.push_str(&format!("Description: {}\n", description));
+        }
+        if let Some(ref readme) = self.readme {
+            context.push_str(&format!("README excerpt:\n{}\n", readme));
+        }
+        
+        context
+    }
+}
+
+/// Repository name from an SSH or HTTPS remote URL
+pub fn repo_name_from_remote(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Package name and description from a manifest, if it declares any
+pub(crate) fn parse_manifest(manifest: &str, content: &str) -> Option<(Option<String>, Option<String>)> {
+    let (name, description) = if manifest == "package.json" {
+        let json: serde_json::Value = serde_json::from_str(content).ok()?;
+        let field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        (field("name"), field("description"))
+    } else {
+        let toml: toml::Value = toml::from_str(content).ok()?;
+        let table = toml.get("package")
+            .or_else(|| toml.get("project"))
+            .or_else(|| toml.get("tool").and_then(|tool| tool.get("poetry")))?;
+        let field = |key: &str| table.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        (field("name"), field("description"))
+    };
+    
+    (name.is_some() || description.is_some()).then_some((name, description))
+}
+
+/// First `lines` non-empty lines of a README, skipping badges
+fn readme_excerpt(content: &str, lines: usize) -> String {
+    content.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with("[!["))
+        .take(lines)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Per-repository cache of `ProjectInfo`, refreshed when a manifest or README changes
+#[derive(Debug, Clone)]
+pub struct ProjectCache {
+    dir: PathBuf,
+}
+
+impl ProjectCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    
+    /// Cache in the user's cache directory
+    pub fn open_default() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join("git-ai-commit").join("projects")))
+    }
+    
+    /// Entries are keyed by repository and README length, since both change the result
+    /// 
+    /// SHA-256 keeps the key the same across Rust versions, unlike `DefaultHasher`.
+    fn entry_path(&self, repo_path: &Path, readme_lines: usize) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(repo_path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(readme_lines.to_string().as_bytes());
+        let key: String = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.dir.join(format!("{}.json", key))
+    }
+    
+    /// Cached info for the repository, unless a source file changed since it was stored
+    pub fn load(&self, repo_path: &Path, readme_lines: usize) -> Option<ProjectInfo> {
+        let entry = self.entry_path(repo_path, readme_lines);
+        let cached_at = fs::metadata(&entry).and_then(|m| m.modified()).ok()?;
+        
+        let stale = MANIFESTS.iter()
+            .chain(READMES)
+            .filter_map(|file| fs::metadata(repo_path.join(file)).and_then(|m| m.modified()).ok())
+            .any(|modified| modified > cached_at);
+        if stale {
+            return None;
+        }
+        
+        serde_json::from_str(&fs::read_to_string(entry).ok()?).ok()
+    }
+    
+    pub fn store(&self, repo_path: &Path, readme_lines: usize, info: &ProjectInfo) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string(info)?;
+        fs::write(self.entry_path(repo_path, readme_lines), json)
+    }
+}
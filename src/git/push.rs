@@ -0,0 +1,118 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use serde::Serialize;
+
+/// Where a new commit is pushed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushTarget {
+    pub remote: String,
+    /// Branch on the remote
+    pub branch: String,
+    /// Whether the push also records the remote branch as the upstream (`-u`)
+    pub set_upstream: bool,
+}
+
+impl PushTarget {
+    /// Pick the remote and branch to push the current branch to
+    /// 
+    /// In order of preference: the `--remote` flag (pushing to a branch of the
+    /// same name), the configured upstream (`upstream` as `remote/branch`), the
+    /// only remote, or `origin`. Pushing anywhere but the existing upstream
+    /// sets the upstream when the branch doesn't have one yet.
+    pub fn resolve(
+        branch: &str,
+        upstream: Option<&str>,
+        remotes: &[String],
+        remote_flag: Option<&str>,
+        set_upstream_flag: bool,
+    ) -> Result<Self> {
+        if branch.is_empty() {
+            return Err(GitAiError::Git("Cannot push from a detached HEAD".to_string()).into());
+        }
+        
+        if let Some(remote) = remote_flag {
+            if !remotes.iter().any(|r| r == remote) {
+                return Err(GitAiError::Git(format!("No remote named '{}'", remote)).into());
+            }
+            let tracks_remote = upstream
+                .and_then(|u| split_upstream(u, remotes))
+                .is_some_and(|(upstream_remote, _)| upstream_remote == remote);
+            return Ok(Self {
+                remote: remote.to_string(),
+                branch: branch.to_string(),
+                set_upstream: set_upstream_flag || !tracks_remote,
+            });
+        }
+        
+        if let Some((remote, remote_branch)) = upstream.and_then(|u| split_upstream(u, remotes)) {
+            return Ok(Self {
+                remote,
+                branch: remote_branch,
+                set_upstream: set_upstream_flag,
+            });
+        }
+        
+        let remote = match remotes {
+            [] => return Err(GitAiError::Git("The repository has no remotes to push to".to_string()).into()),
+            [only] => only.clone(),
+            _ if remotes.iter().any(|r| r == "origin") => "origin".to_string(),
+            _ => {
+                return Err(GitAiError::Git(format!(
+                    "Branch '{}' has no upstream and there are several remotes ({}); choose one with --remote",
+                    branch,
+                    remotes.join(", ")
+                )).into())
+            }
+        };
+        
+        Ok(Self {
+            remote,
+            branch: branch.to_string(),
+            set_upstream: true,
+        })
+    }
+    
+    /// Arguments for `git push`
+    pub fn push_args(&self) -> Vec<String> {
+        let mut args = vec!["push".to_string()];
+        if self.set_upstream {
+            args.push("--set-upstream".to_string());
+        }
+        args.push(self.remote.clone());
+        args.push(format!("HEAD:{}", self.branch));
+        args
+    }
+}
+
+/// Split an upstream like `origin/feature/x` into remote and branch
+/// 
+/// Remote names may contain slashes too, so the longest matching remote wins.
+fn split_upstream(upstream: &str, remotes: &[String]) -> Option<(String, String)> {
+    remotes.iter()
+        .filter_map(|remote| {
+            let branch = upstream.strip_prefix(remote.as_str())?.strip_prefix('/')?;
+            Some((remote, branch))
+        })
+        .max_by_key(|(remote, _)| remote.len())
+        .map(|(remote, branch)| (remote.clone(), branch.to_string()))
+}
+
+/// Result of pushing the new commit, as reported in `--json` output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushStatus {
+    /// Where the commit was (or would have been) pushed; `None` if no target was found
+    pub target: Option<PushTarget>,
+    pub pushed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PushStatus {
+    pub fn pushed(target: PushTarget) -> Self {
+        Self { target: Some(target), pushed: true, error: None }
+    }
+    
+    pub fn failed(target: Option<PushTarget>, error: impl ToString) -> Self {
+        Self { target, pushed: false, error: Some(error.to_string()) }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::git::{FileChange, GitInfo};
+use comfy_table::{presets, Table};
+
+/// How `GitInfo` is rendered for dry-run output, and history is exported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DisplayFormat {
+    /// Indented plain text
+    #[default]
+    Plain,
+    /// Markdown suitable for PR descriptions and chat
+    Markdown,
+    /// Compact table
+    Table,
+    /// Comma-separated values for spreadsheets
+    Csv,
+    /// JSON for scripts and notebooks
+    Json,
+}
+
+/// `fields` as one line of CSV, quoting the ones that need it
+pub fn csv_line(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+impl GitInfo {
+    /// Render the analysis in the requested format
+    pub fn render(&self, format: DisplayFormat) -> String {
+        match format {
+            DisplayFormat::Plain => self.display(),
+            DisplayFormat::Markdown => self.render_markdown(),
+            DisplayFormat::Table => {
+                let mut output = self.file_table(presets::UTF8_FULL_CONDENSED).to_string();
+                output.push('\n');
+                output.push_str(&self.totals());
+                output.push('\n');
+                output
+            }
+            DisplayFormat::Csv => {
+                let header = ["status", "file", "insertions", "deletions"].map(str::to_string);
+                let mut output = csv_line(&header);
+                for row in self.file_rows() {
+                    output.push_str(&csv_line(&row));
+                }
+                output
+            }
+            DisplayFormat::Json => {
+                let files: Vec<serde_json::Value> = self.file_rows().into_iter()
+                    .map(|[status, file, insertions, deletions]| serde_json::json!({
+                        "status": status,
+                        "file": file,
+                        "insertions": insertions.parse::<usize>().ok(),
+                        "deletions": deletions.parse::<usize>().ok(),
+                    }))
+                    .collect();
+                let analysis = serde_json::json!({
+                    "branch": self.branch_name,
+                    "last_commit": self.last_commit.as_deref().and_then(|commit| commit.lines().next()),
+                    "files": files,
+                    "files_changed": self.diff_stat.files_changed,
+                    "insertions": self.diff_stat.insertions,
+                    "deletions": self.diff_stat.deletions,
+                });
+                format!("{}\n", serde_json::to_string_pretty(&analysis).expect("a JSON value always serializes"))
+            }
+        }
+    }
+    
+    fn render_markdown(&self) -> String {
+        let mut output = String::new();
+        
+        if !self.branch_name.is_empty() {
+            output.push_str(&format!("**Branch:** `{}`\n", self.branch_name));
+        }
+        if let Some(ref last_commit) = self.last_commit {
+            let subject = last_commit.lines().next().unwrap_or_default();
+            output.push_str(&format!("**Last commit:** {}\n", subject));
+        }
+        output.push('\n');
+        
+        output.push_str(&self.file_table(presets::ASCII_MARKDOWN).to_string());
+        output.push_str(&format!("\n\n**Total:** {}\n", self.totals()));
+        output
+    }
+    
+    /// One row per changed or untracked file
+    fn file_table(&self, preset: &str) -> Table {
+        let mut table = Table::new();
+        table.load_preset(preset);
+        table.set_header(vec!["Status", "File", "+", "-"]);
+        for row in self.file_rows() {
+            table.add_row(row.to_vec());
+        }
+        table
+    }
+    
+    /// Status, path and line counts of each changed or untracked file
+    fn file_rows(&self) -> Vec<[String; 4]> {
+        let mut rows = Vec::new();
+        for change in &self.file_changes {
+            let staged = if self.status.staged_files.contains(&change.file_path) {
+                "staged"
+            } else {
+                "unstaged"
+            };
+            let (insertions, deletions) = self.line_counts(change);
+            rows.push([
+                format!("{} {}", staged, change.change_type.label()),
+                change.path_display(),
+                insertions,
+                deletions,
+            ]);
+        }
+        
+        for file in &self.untracked_files {
+            rows.push([
+                "untracked".to_string(),
+                file.display().to_string(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        rows
+    }
+    
+    fn line_counts(&self, change: &FileChange) -> (String, String) {
+        let filename = change.file_path.to_string_lossy();
+        self.diff_stat.file_stats.iter()
+            .find(|s| s.filename == filename)
+            .map(|s| (s.insertions.to_string(), s.deletions.to_string()))
+            .unwrap_or_default()
+    }
+    
+    fn totals(&self) -> String {
+        format!(
+            "{} files changed, {} insertions(+), {} deletions(-)",
+            self.diff_stat.files_changed, self.diff_stat.insertions, self.diff_stat.deletions
+        )
+    }
+}
@@ -0,0 +1,64 @@
+//! Rebases, cherry-picks and reverts stopped on a commit
+//!
+//! When a rebase, cherry-pick or revert stops for a conflict, git records
+//! the commit being replayed in `REBASE_HEAD`, `CHERRY_PICK_HEAD` or
+//! `REVERT_HEAD` and expects `git <command> --continue` once it is resolved.
+//! A plain `git commit` there records the commit but leaves the rest of the
+//! sequence waiting, so committing is refused unless `--continue-sequence`
+//! asks to commit and then continue.
+
+use std::path::Path;
+
+/// The operation a repository is stopped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sequence {
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+impl Sequence {
+    /// The operation stopped in `git_dir`, if any
+    pub fn detect(git_dir: &Path) -> Option<Sequence> {
+        [Sequence::Rebase, Sequence::CherryPick, Sequence::Revert]
+            .into_iter()
+            .find(|sequence| git_dir.join(sequence.head_file()).is_file())
+    }
+    
+    /// The git command running the sequence, e.g. `cherry-pick`
+    pub fn command(&self) -> &'static str {
+        match self {
+            Sequence::Rebase => "rebase",
+            Sequence::CherryPick => "cherry-pick",
+            Sequence::Revert => "revert",
+        }
+    }
+    
+    fn head_file(&self) -> &'static str {
+        match self {
+            Sequence::Rebase => "REBASE_HEAD",
+            Sequence::CherryPick => "CHERRY_PICK_HEAD",
+            Sequence::Revert => "REVERT_HEAD",
+        }
+    }
+    
+    /// Whether git still has work to continue once the stopped commit is made
+    /// 
+    /// A rebase always does, if only to update the branch. A single cherry-pick
+    /// or revert is done with its commit; only a range leaves a `sequencer` directory.
+    pub fn has_remaining(&self, git_dir: &Path) -> bool {
+        match self {
+            Sequence::Rebase => git_dir.join("rebase-merge").is_dir() || git_dir.join("rebase-apply").is_dir(),
+            Sequence::CherryPick | Sequence::Revert => git_dir.join("sequencer").is_dir(),
+        }
+    }
+    
+    /// Why committing is refused without `--continue-sequence`
+    pub fn refusal(&self) -> String {
+        format!(
+            "A {} is in progress, and committing here would leave it unfinished. Run git {} --continue \
+             yourself, or rerun with --continue-sequence to commit with a generated message and continue",
+            self.command(), self.command()
+        )
+    }
+}
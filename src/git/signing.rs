@@ -0,0 +1,61 @@
+//! Signed commits
+//!
+//! `--gpg-sign` asks `git commit` to sign the commit, and `commit.gpgsign=true`
+//! makes git sign every commit on its own. Either way git runs gpg, or
+//! ssh-keygen with `gpg.format=ssh`, which may stop to ask for the key's
+//! passphrase in a pinentry dialog, so the confirmation prompt says so first.
+
+use crate::git::clone::git_stdout;
+use std::path::Path;
+
+/// How the next commit will be signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signing {
+    /// `gpg.format`: `openpgp`, `x509` or `ssh`
+    pub format: String,
+    /// The key given to `--gpg-sign`, or else `user.signingkey`
+    pub key: Option<String>,
+    /// Whether `--gpg-sign` asked for it rather than `commit.gpgsign`
+    pub requested: bool,
+}
+
+impl Signing {
+    /// How a commit in `repo_path` will be signed, if it will be
+    /// 
+    /// `requested` is the value of `--gpg-sign`: the key to sign with, or
+    /// empty for the default one.
+    pub async fn detect(repo_path: &Path, requested: Option<&str>) -> Option<Signing> {
+        if requested.is_none() && config(repo_path, &["--bool", "commit.gpgsign"]).await.as_deref() != Some("true") {
+            return None;
+        }
+        
+        let key = match requested.filter(|key| !key.is_empty()) {
+            Some(key) => Some(key.to_string()),
+            None => config(repo_path, &["user.signingkey"]).await,
+        };
+        Some(Signing {
+            format: config(repo_path, &["gpg.format"]).await.unwrap_or_else(|| "openpgp".to_string()),
+            key,
+            requested: requested.is_some(),
+        })
+    }
+    
+    /// What to expect while committing, e.g. a passphrase prompt
+    pub fn warning(&self) -> String {
+        let key = self.key.as_deref().map(|key| format!(" with {}", key)).unwrap_or_default();
+        let source = if self.requested { "--gpg-sign" } else { "commit.gpgsign" };
+        let wait = match self.format.as_str() {
+            "ssh" => "ssh-keygen may ask for the key's passphrase",
+            _ => "gpg may wait for the passphrase in a pinentry dialog",
+        };
+        format!("The commit will be signed{} ({}); {}", key, source, wait)
+    }
+}
+
+/// Value of a git config setting, if it is set
+async fn config(repo_path: &Path, args: &[&str]) -> Option<String> {
+    let args = [&["config", "--get"], args].concat();
+    let value = git_stdout(repo_path, &args).await?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
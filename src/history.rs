@@ -0,0 +1,328 @@
+//! Local record of generated commit messages
+//!
+//! Besides the history file, `--notes` attaches the details of a generation
+//! to its commit as a git note, so they travel with the repository.
+
+use crate::formatting::candidate::Candidate;
+use crate::formatting::provenance::{short_hash, Provenance};
+use crate::git::render::{csv_line, DisplayFormat};
+use crate::ollama::GenerationStats;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One generated message and what happened to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub repository: String,
+    pub model: String,
+    pub message: String,
+    /// SHA of the created commit; `None` for dry runs and cancelled commits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
+    /// The message as the tool proposed it, when the user changed it into `message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proposed: Option<String>,
+    #[serde(default)]
+    pub stats: GenerationStats,
+}
+
+impl HistoryEntry {
+    pub fn new(repository: &Path, model: &str, message: &str, stats: GenerationStats) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        
+        Self {
+            timestamp,
+            repository: repository.to_string_lossy().to_string(),
+            model: model.to_string(),
+            message: message.trim().to_string(),
+            sha: None,
+            proposed: None,
+            stats,
+        }
+    }
+    
+    pub fn with_sha(mut self, sha: impl Into<String>) -> Self {
+        self.sha = Some(sha.into());
+        self
+    }
+    
+    /// Remember what the tool proposed; nothing is stored when the user kept it as it was
+    pub fn with_proposed(mut self, proposed: &str) -> Self {
+        let proposed = proposed.trim();
+        self.proposed = (proposed != self.message).then(|| proposed.to_string());
+        self
+    }
+    
+    /// Characters inserted, deleted or replaced between the proposed and the kept message
+    pub fn edit_distance(&self) -> usize {
+        self.proposed.as_deref().map_or(0, |proposed| edit_distance(proposed, &self.message))
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// One history entry as `history export` writes it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRow {
+    pub timestamp: u64,
+    pub repository: String,
+    pub model: String,
+    /// Whether the message was committed
+    pub accepted: bool,
+    pub sha: Option<String>,
+    /// Time the model took, in milliseconds
+    pub latency_ms: Option<u64>,
+    pub prompt_tokens: Option<u64>,
+    pub generated_tokens: Option<u64>,
+    /// Characters the user changed in the proposed message
+    pub edit_distance: usize,
+    pub message: String,
+}
+
+impl From<&HistoryEntry> for ExportRow {
+    fn from(entry: &HistoryEntry) -> Self {
+        Self {
+            timestamp: entry.timestamp,
+            repository: entry.repository.clone(),
+            model: entry.model.clone(),
+            accepted: entry.sha.is_some(),
+            sha: entry.sha.clone(),
+            latency_ms: entry.stats.total_duration.map(|nanos| nanos / 1_000_000),
+            prompt_tokens: entry.stats.prompt_eval_count,
+            generated_tokens: entry.stats.eval_count,
+            edit_distance: entry.edit_distance(),
+            message: entry.message.clone(),
+        }
+    }
+}
+
+const EXPORT_COLUMNS: [&str; 10] = [
+    "timestamp", "repository", "model", "accepted", "sha",
+    "latency_ms", "prompt_tokens", "generated_tokens", "edit_distance", "message",
+];
+
+/// `entries` for spreadsheets (`Csv`, also the default `Plain`) or notebooks (`Json`)
+pub fn export(entries: &[HistoryEntry], format: DisplayFormat) -> Result<String> {
+    let rows: Vec<ExportRow> = entries.iter().map(ExportRow::from).collect();
+    match format {
+        DisplayFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(&rows)?)),
+        DisplayFormat::Plain | DisplayFormat::Csv => {
+            let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+            let mut csv = csv_line(&EXPORT_COLUMNS.map(str::to_string));
+            for row in rows {
+                csv.push_str(&csv_line(&[
+                    row.timestamp.to_string(),
+                    row.repository,
+                    row.model,
+                    row.accepted.to_string(),
+                    row.sha.unwrap_or_default(),
+                    optional(row.latency_ms),
+                    optional(row.prompt_tokens),
+                    optional(row.generated_tokens),
+                    row.edit_distance.to_string(),
+                    row.message,
+                ]));
+            }
+            Ok(csv)
+        }
+        DisplayFormat::Markdown | DisplayFormat::Table => Err(GitAiError::Validation(
+            "History is exported as csv or json".to_string()
+        ).into()),
+    }
+}
+
+/// Notes ref `--notes` attaches generation details to, i.e. `refs/notes/ai-commit`
+pub const NOTES_REF: &str = "ai-commit";
+
+/// How a commit's message was generated, stored as JSON in a git note
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitNote {
+    /// Version of git-ai-commit
+    pub version: String,
+    /// Model that wrote the message, `None` when it was described from file names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Short hash of the prompt the message was generated from
+    pub prompt_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_hash: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub stats: GenerationStats,
+    /// The messages the committed one was chosen from, best first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<NoteCandidate>,
+}
+
+/// A message that competed for the commit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteCandidate {
+    pub model: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl CommitNote {
+    /// Note for `message`, generated from `prompt` by what `generated_by` records
+    pub fn new(message: &str, prompt: &str, generated_by: Option<&Provenance>, stats: GenerationStats) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            model: generated_by.map(|generated_by| generated_by.model.clone()),
+            prompt_hash: short_hash(prompt),
+            template_hash: generated_by.map(|generated_by| generated_by.template_hash.clone()),
+            message: message.trim().to_string(),
+            stats,
+            candidates: Vec::new(),
+        }
+    }
+    
+    pub fn with_candidates(mut self, candidates: &[Candidate]) -> Self {
+        self.candidates = candidates.iter().map(NoteCandidate::from).collect();
+        self
+    }
+    
+    /// Read a note written by [`CommitNote::to_json`]
+    pub fn parse(note: &str) -> Result<Self> {
+        serde_json::from_str(note)
+            .map_err(|e| GitAiError::Parse(format!("The note is not git-ai-commit generation metadata: {}", e)).into())
+    }
+    
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    
+    /// Summary for `history show`
+    pub fn display(&self) -> String {
+        let mut lines = vec![
+            format!("Generator: git-ai-commit/{}", self.version),
+            format!("Model:     {}", self.model.as_deref().unwrap_or("none (described from file names)")),
+            format!("Prompt:    {}", self.prompt_hash),
+        ];
+        if let Some(template_hash) = &self.template_hash {
+            lines.push(format!("Template:  {}", template_hash));
+        }
+        lines.push(format!("Stats:     {}", self.stats.display()));
+        lines.push(format!("\n{}", self.message));
+        if !self.candidates.is_empty() {
+            lines.push(format!("\nCandidates ({}):", self.candidates.len()));
+            for (index, candidate) in self.candidates.iter().enumerate() {
+                let score = candidate.score.map(|score| format!(" score {:.2}", score)).unwrap_or_default();
+                let chosen = if candidate.message == self.message { " (chosen)" } else { "" };
+                lines.push(format!("{}. [{}]{}{}", index + 1, candidate.model, score, chosen));
+                lines.push(format!("   {}", candidate.message.lines().next().unwrap_or_default()));
+                for violation in &candidate.violations {
+                    lines.push(format!("   ! {}", violation));
+                }
+            }
+        }
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+impl From<&Candidate> for NoteCandidate {
+    fn from(candidate: &Candidate) -> Self {
+        Self {
+            model: candidate.model.clone(),
+            message: candidate.message.clone(),
+            violations: candidate.violations.iter().map(|violation| violation.describe()).collect(),
+            score: candidate.score.as_ref().map(|score| score.total),
+        }
+    }
+}
+
+/// Append-only history file with one JSON entry per line
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+    
+    /// History file in the user's data directory
+    pub fn open_default() -> Result<Self> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| GitAiError::FileSystem("Could not find data directory".to_string()))?
+            .join("git-ai-commit");
+        Ok(Self::new(data_dir.join("history.jsonl")))
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| GitAiError::FileSystem(format!("Failed to create history directory: {}", e)))?;
+        }
+        
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to open history file: {}", e)))?;
+        
+        let line = serde_json::to_string(entry)?;
+        writeln!(file, "{}", line)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write history: {}", e)))?;
+        Ok(())
+    }
+    
+    /// Load all entries, skipping lines that can't be parsed
+    pub fn load(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to read history file: {}", e)))?;
+        
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+    
+    /// Up to `count` messages committed in `repository`, most recent first
+    /// 
+    /// Dry runs and cancelled commits are left out, since nobody accepted them.
+    pub fn recent_messages(&self, repository: &Path, count: usize) -> Result<Vec<String>> {
+        let repository = repository.to_string_lossy();
+        Ok(self.load()?
+            .into_iter()
+            .rev()
+            .filter(|entry| entry.repository == repository && entry.sha.is_some())
+            .map(|entry| entry.message)
+            .take(count)
+            .collect())
+    }
+}
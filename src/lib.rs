@@ -3,11 +3,18 @@
 //! This library provides functionality to analyze git repositories,
 //! manage Ollama instances, and generate intelligent commit messages.
 
+pub mod analysis;
+pub mod cache;
 pub mod cli;
 pub mod config;
+pub mod doctor;
 pub mod git;
 pub mod ollama;
 pub mod formatting;
+pub mod history;
+pub mod report;
+pub mod self_test;
+pub mod serve;
 pub mod utils;
 
 pub use cli::Args;
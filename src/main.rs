@@ -1,178 +1,2617 @@
 use anyhow::Result;
+use colored::Colorize;
 use git_ai_commit::{
-    cli::Args,
-    git::GitCollector,
-    ollama::{OllamaManager, OllamaClient, OllamaClientTrait},
-    formatting::PromptBuilder,
-    utils::error::GitAiError,
+    analysis::language::ProjectLayout,
+    cache::{CachedResponse, ResponseCache},
+    cli::{ci, Args, CacheCommand, Command, ConfigCommand, DaemonCommand, HistoryCommand, HookCommand, ModelsCommand, QuickAction},
+    git::{backend, diff::STAT_WIDTH, draft::Draft, editor, hooks::{self, HookFailure, HookRecovery}, lock, merge::MergeState, plan::CommitPlan, sequence::Sequence, signing::Signing, is_git_repository, CloneInfo, CommitStats, CommitSummary, GitCollector, GitInfo, ProjectInfo, PushStatus, PushTarget},
+    doctor::{self, Check, CheckStatus},
+    history::{self, CommitNote, History, HistoryEntry},
+    report::{self, BugReport},
+    self_test::{self, StubClient},
+    serve::{self, SharedSuggestion, Suggestion},
+    ollama::{budget::{self, CallBudget}, client::REQUEST_TIMEOUT, context, cost::{CostMeter, Ledger}, daemon, host::OllamaHost, local, preflight::{Preflight, PREFLIGHT_TIMEOUT}, startup, Capabilities, Generation, GenerationStats, OllamaManager, OllamaClient, OllamaClientTrait, OpenAiClient, ServerEntry, ServerRegistry},
+    config::{Backend, CommitStyle, Config, HookFallback, HookMode, MixedLanguages, Provider, ProvenanceMode, RetryStrategy, TicketPlacement, ValidationConfig},
+    formatting::{
+        body,
+        candidate::Candidate,
+        compare,
+        conventional,
+        fast_path,
+        heuristic,
+        merge::{self, ExistingMessage},
+        postprocess,
+        split,
+        style,
+        structured::{self, StructuredMessage},
+        ticket,
+        provenance::Provenance,
+        raw,
+        score::{self, JudgeScorer, Ranker},
+        squash::{self, SquashMessage},
+        template,
+        trailers,
+        validate::{self, Violation},
+        PromptBuilder,
+    },
+    utils::{clipboard, error::{is_timeout_error, GitAiError}, WriteMode},
 };
 use std::env;
-use std::path::PathBuf;
-use tokio;
-use atty;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Token cap for `--quick`, enough for a one-line subject
+const QUICK_NUM_PREDICT: u32 = 48;
+
+/// Previously committed messages shown to the model as style examples
+const HISTORY_EXAMPLES: usize = 3;
+
+/// Progress output, sent to stderr when stdout is reserved for `--json` or `--raw`
+/// and left out with `--quiet`
+macro_rules! status {
+    ($args:expr, $($arg:tt)*) => {
+        if $args.quiet {
+            // Only errors and results are printed
+        } else if $args.json || $args.raw {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Progress output that always goes to stderr, e.g. while git runs the hook
+macro_rules! progress {
+    ($args:expr, $($arg:tt)*) => {
+        if !$args.quiet {
+            eprintln!($($arg)*);
+        }
+    };
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::load();
+    let mut args = Args::load();
     
-    // Handle --list-models flag
-    if args.list_models {
-        let client = OllamaClient::new(args.port);
-        if !client.is_running().await {
-            eprintln!("Error: Ollama is not running. Please start Ollama first.");
-            std::process::exit(1);
-        }
-        
-        match client.list_models().await {
-            Ok(models) => {
-                if models.is_empty() {
-                    println!("No models found. Install models with 'ollama pull <model>'");
-                } else {
-                    println!("Available models:");
-                    for model in models {
-                        println!("- {}", model);
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Failed to list models: {}", e);
-                std::process::exit(1);
-            }
-        }
+    // Read-only and raw runs never commit, whatever else was asked for
+    if args.no_write || args.raw {
+        args.dry_run = true;
+    }
+    
+    // `commit` is the default flow without the review step
+    if matches!(args.command, Some(Command::Commit)) {
+        args.no_confirm = true;
+    }
+    
+    // Fall back to another configured server or model when the primary one is down
+    if !args.config.backends.is_empty()
+        && args.provider() == Provider::Ollama
+        && args.host.is_none()
+        && !matches!(args.command, Some(Command::Daemon { .. } | Command::Config { .. } | Command::History { .. } | Command::SelfTest { stub: true }))
+    {
+        select_backend(&mut args).await;
+    }
+    
+    if args.list_models || matches!(args.command, Some(Command::Models { action: ModelsCommand::List })) {
+        return list_models(&args).await;
+    }
+    
+    if let Some(Command::Models { action: ModelsCommand::Pull { name } }) = &args.command {
+        return pull_model(&args, name).await;
+    }
+    
+    if let Some(Command::Models { action: ModelsCommand::Rm { name } }) = &args.command {
+        return remove_model(&args, name).await;
+    }
+    
+    if let Some(Command::Models { action: ModelsCommand::Info { name } }) = &args.command {
+        return show_model(&args, name).await;
+    }
+    
+    // Describe a patch from stdin without touching any repository
+    if args.stdin_diff {
+        return describe_stdin_diff(&args).await;
+    }
+    
+    // Warm servers are per user, so these work outside a repository too
+    if let Some(Command::Daemon { action }) = &args.command {
+        return manage_daemon(&args, action);
+    }
+    
+    if let Some(Command::History { action: HistoryCommand::Export }) = &args.command {
+        let history = History::open_default()?;
+        print!("{}", history::export(&history.load()?, args.format)?);
+        return Ok(());
+    }
+    
+    if let Some(Command::ReportBug { output, issue }) = &args.command {
+        return report_bug(&args, output, *issue).await;
+    }
+    
+    if let Some(Command::Config { action }) = &args.command {
+        return manage_config(&args, action);
+    }
+    
+    if let Some(Command::Doctor) = &args.command {
+        return run_doctor(&args).await;
+    }
+    
+    if let Some(Command::Cache { action: CacheCommand::Clear }) = &args.command {
+        args.write_mode().check("clearing the cache")?;
+        let cache = ResponseCache::open_default()?;
+        println!("Removed {} cached messages from {}", cache.clear()?, cache.dir().display());
         return Ok(());
     }
     
+    // The throwaway repository is the only one touched
+    if let Some(Command::SelfTest { stub }) = &args.command {
+        return run_self_test(&args, *stub).await;
+    }
+    
     // Check if we're in a git repository
-    let current_dir = env::current_dir()
-        .map_err(|e| GitAiError::Git(format!("Failed to get current directory: {}", e)))?;
+    let current_dir = args.repo_dir()?;
     
     if !is_git_repository(&current_dir).await? {
         eprintln!("Error: Not a git repository");
-        eprintln!("Please run this command from within a git repository.");
+        eprintln!("Please run this command from within a git repository, or point to one with --repo.");
         std::process::exit(1);
     }
     
-    println!("AI Commit Message Generator");
-    println!("==============================");
+    // Running from prepare-commit-msg: fill in the message file, never commit
+    if let Some(msg_file) = &args.hook {
+        if let Err(e) = prepare_hook_message(&args, &current_dir, msg_file).await {
+            tracing::warn!("git-ai-commit could not prepare a message: {}", e);
+        }
+        return Ok(());
+    }
     
-    // Initialize components
-    let git_collector = GitCollector::new(current_dir.clone());
-    let mut ollama_manager = OllamaManager::new(args.model.clone(), args.port)?;
-    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines);
+    if args.amend {
+        return amend_last_commit(&args, &current_dir).await;
+    }
     
-    // Ensure the model is available
-    println!("[CHECK] Checking if model '{}' is available...", args.model);
-    ollama_manager.ensure_model_available(&args.model).await?;
+    let hook_uninstall = match &args.command {
+        Some(Command::Hook { action }) => Some(matches!(action, HookCommand::Uninstall)),
+        Some(Command::InstallHook { uninstall }) => Some(*uninstall),
+        _ => None,
+    };
+    if let Some(uninstall) = hook_uninstall {
+        args.write_mode().check("installing hooks")?;
+        let git_collector = GitCollector::new(current_dir).with_write_mode(args.write_mode());
+        return install_commit_msg_hook(&git_collector, uninstall).await;
+    }
+    
+    if let Some(Command::PushSummary { range, install_hook }) = &args.command {
+        let git_collector = GitCollector::new(current_dir).with_write_mode(args.write_mode());
+        if *install_hook {
+            args.write_mode().check("installing hooks")?;
+            return install_pre_push_hook(&git_collector).await;
+        }
+        return push_summary(&args, &git_collector, range).await;
+    }
+    
+    if let Some(Command::SquashTitle { base, pr }) = &args.command {
+        let git_collector = GitCollector::new(current_dir).with_write_mode(args.write_mode());
+        return squash_title(&args, &git_collector, base, *pr).await;
+    }
+    
+    if let Some(Command::History { action: HistoryCommand::Show { commit } }) = &args.command {
+        let git_collector = GitCollector::new(current_dir);
+        return show_commit_note(&args, &git_collector, commit).await;
+    }
+    
+    if let Some(Command::Serve { socket }) = &args.command {
+        let socket = socket.clone();
+        // Suggestions only: nothing is staged, committed, recorded or downloaded
+        args.no_write = true;
+        return serve_suggestions(&args, &current_dir, socket).await;
+    }
+    
+    status!(args, "AI Commit Message Generator");
+    status!(args, "==============================");
+    
+    // Hosted APIs don't report model sizes, so quick mode keeps the configured model there
+    if args.quick && args.provider() == Provider::Ollama {
+        match OllamaClient::for_host(&args.ollama_host()?).smallest_model().await {
+            Ok(Some(model)) => {
+                status!(args, "[QUICK] Using the smallest installed model '{}'", model);
+                args.model = model;
+            }
+            _ => status!(args, "[QUICK] Could not list installed models, using '{}'", args.model),
+        }
+    }
+    
+    // Shallow and partial clones (common in CI) leave history-based context out
+    let clone = CloneInfo::detect(&current_dir).await;
+    if let Some(warning) = clone.warning() {
+        tracing::warn!("{}", warning);
+    }
+    
+    // Initialize components
+    let git_collector = git_collector(&args, &current_dir)?;
+    let mut ollama_manager = ollama_manager(&args)?;
+    if args.quick {
+        ollama_manager = ollama_manager.with_num_predict(QUICK_NUM_PREDICT);
+    }
+    // Files left out of the pick aren't part of this commit, so the model doesn't hear of them
+    let mut context_blocks = args.config.context.clone();
+    if args.interactive_stage {
+        context_blocks.unstaged = false;
+        context_blocks.untracked = false;
+    }
+    let mut prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(&args)?)
+        .with_context_blocks(context_blocks)
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_project(project_info(&args, &git_collector).await)
+        .with_layout(project_layout(&args, &git_collector).await)
+        .with_style(args.style)
+        .with_recent_commits(recent_commits(&args, &git_collector).await)
+        .with_examples(history_examples(&args, &current_dir, clone))
+        .with_quick(args.quick)
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages);
     
     // Collect initial git information
-    println!("[ANALYZE] Analyzing git repository...");
+    status!(args, "[ANALYZE] Analyzing git repository...");
     let mut git_info = git_collector.collect_all().await?;
     
     // If --add-unstaged flag is set, stage all unstaged changes and refresh git info
     let mut after_staging = false;
     if args.add_unstaged && (!git_info.status.modified_files.is_empty() || !git_info.status.untracked_files.is_empty()) {
-        println!("[STAGE] Staging all unstaged changes...");
+        status!(args, "[STAGE] Staging all unstaged changes...");
         git_collector.stage_all_unstaged().await?;
         
         // Refresh git info after staging
-        println!("[REFRESH] Refreshing repository status...");
+        status!(args, "[REFRESH] Refreshing repository status...");
         git_info = git_collector.collect_all().await?;
         after_staging = true;
         
         if git_info.is_empty(true) {  // true = after staging
-            println!("[INFO] No changes to commit after staging.");
+            status!(args, "[INFO] No changes to commit after staging.");
+            return nothing_to_commit(&args);
+        }
+    }
+    
+    // A stopped rebase, cherry-pick or revert has to be continued, not just committed
+    let git_dir = git_collector.git_dir().await?;
+    let sequence = Sequence::detect(&git_dir);
+    if let Some(sequence) = sequence {
+        if !args.continue_sequence && !args.dry_run && args.prepare_only.is_none() {
+            return Err(GitAiError::Git(sequence.refusal()).into());
+        }
+        status!(args, "[SEQUENCE] A {} is in progress; it continues once this commit is made", sequence.command());
+    }
+    
+    // Finishing a merge says what was merged, not what the staged changes do
+    if let Some(merge_state) = MergeState::detect(&git_dir).filter(|_| sequence.is_none()) {
+        return commit_merge(&args, &current_dir, &git_collector, &git_info, &merge_state).await;
+    }
+    
+    if args.interactive_stage {
+        if !atty::is(atty::Stream::Stdout) {
+            return Err(GitAiError::Validation("--interactive-stage needs a terminal to pick files in".to_string()).into());
+        }
+        if git_info.unstaged_paths().is_empty() {
+            status!(args, "[STAGE] No unstaged or untracked files to choose from");
+        } else if pick_files_to_stage(&args, &git_collector, &git_info).await? {
+            status!(args, "[REFRESH] Refreshing repository status...");
+            git_info = git_collector.collect_all().await?;
+        }
+        if git_info.status.staged_files.is_empty() {
+            status!(args, "[CANCEL] Nothing staged, no commit created");
             return Ok(());
         }
     }
     
     if git_info.is_empty(after_staging) {
-        println!("[INFO] No changes detected in the repository.");
-        println!("Please make some changes and stage them before generating a commit message.");
-        return Ok(());
+        status!(args, "[INFO] No changes detected in the repository.");
+        status!(args, "Please make some changes and stage them before generating a commit message.");
+        return nothing_to_commit(&args);
+    }
+    
+    // Check if we're in an interactive terminal
+    let is_interactive = atty::is(atty::Stream::Stdout) && !args.ci;
+    let confirm = is_interactive && !args.no_confirm;
+    
+    // Only unstaged changes: offer to stage them here rather than exit
+    if git_info.status.staged_files.is_empty() && !args.dry_run && !args.no_write {
+        if !is_interactive {
+            status!(args, "[INFO] Nothing is staged. Stage changes with git add or rerun with --add-unstaged.");
+            return nothing_to_commit(&args);
+        }
+        if !stage_interactively(&args, &git_collector, &git_info).await? {
+            status!(args, "[CANCEL] Nothing staged, no commit created");
+            return Ok(());
+        }
+        status!(args, "[REFRESH] Refreshing repository status...");
+        git_info = git_collector.collect_all().await?;
+        if git_info.status.staged_files.is_empty() {
+            status!(args, "[INFO] No changes to commit after staging.");
+            return nothing_to_commit(&args);
+        }
     }
     
     if args.dry_run {
-        println!("[DRY RUN] Dry run mode - will generate commit message but not commit");
-        println!("[ANALYSIS] Git Repository Analysis:");
-        println!("{}", git_info.display());
+        status!(args, "[DRY RUN] Dry run mode - will generate commit message but not commit");
+        status!(args, "[ANALYSIS] Git Repository Analysis:");
+        status!(args, "{}", git_info.render(args.format));
+        if args.config.context.stat_graph {
+            status!(args, "{}", git_info.diff_stat.stat_graph(STAT_WIDTH).trim_end());
+        }
     }
     
-    // Start Ollama if needed
-    println!("[START] Starting Ollama...");
-    ollama_manager.ensure_running().await?;
+    // Another process holding the index would only fail the commit after generation
+    if !args.dry_run && !args.no_write {
+        let index_lock = lock::index_lock_path(&current_dir).await?;
+        if index_lock.exists() {
+            status!(args, "[LOCK] Waiting for another git process to release the index...");
+            lock::wait_for_index_lock(&index_lock, &lock::LOCK_BACKOFF).await?;
+        }
+    }
     
-    // Generate commit message
-    println!("[GENERATE] Generating commit message...");
-    let prompt = prompt_builder.build(&git_info);
+    if args.config.mixed_languages == MixedLanguages::Split && !args.split && prompt_builder.spans_components(&git_info) {
+        let components: Vec<String> = git_info.language_groups().iter().map(|group| group.label()).collect();
+        status!(args, "[NOTE] The staged code spans {}; --split commits each part separately", components.join(", "));
+    }
     
-    if args.verbose {
-        println!("[PROMPT] Generated prompt:");
-        println!("{}", prompt);
-        println!("==============================");
+    if args.split && git_info.status.staged_files.len() > 1 {
+        return split_commits(&args, &current_dir, &git_collector, &mut ollama_manager, &prompt_builder, &git_info, confirm).await;
     }
     
-    let commit_message = ollama_manager.generate_commit(&prompt).await?;
+    // A draft saved for the same staged tree can be reused instead of regenerating
+    let staged_tree = git_collector.staged_tree().await.ok();
+    let mut commit_message = match staged_tree.as_deref() {
+        Some(tree) if confirm && !args.dry_run => match Draft::load_for_tree(&git_dir, tree) {
+            Some(draft) => resume_draft(&current_dir, &draft).await?,
+            None => None,
+        },
+        _ => None,
+    };
+    
+    // Trivial changes can be described without contacting the model
+    let staged_patch = git_collector.get_staged_diff().await.ok();
+    if commit_message.is_none() && args.config.fast_paths {
+        if let Some(fast) = staged_patch.as_deref().and_then(|patch| fast_path::trivial_message(&git_info, patch)) {
+            status!(args, "[FAST] Detected {}, skipping the model", fast.rule.describe());
+            commit_message = Some(apply_commit_style(&args, &prompt_builder, &git_info, fast.message));
+        }
+    }
+    
+    // Competing messages are ranked against the changes they describe
+    let ranker = Ranker::from_config(&args.config.scoring, &git_info, staged_patch.as_deref());
+    prompt_builder = prompt_builder.with_patch(staged_patch.clone());
+    let cache = response_cache(&args, &prompt_builder, &git_info, staged_patch.as_deref());
+    
+    // Usage is only known (and history only recorded) when the model was asked
+    let mut generation_stats = None;
+    let mut candidates = Vec::new();
+    let mut generated_by = None;
+    let mut prompt = None;
+    let commit_message = match commit_message {
+        Some(message) => message,
+        None => {
+            let cached = cache.as_ref().and_then(|(cache, key)| cache.get(key));
+            let ranked = if let Some(cached) = cached {
+                status!(args, "[CACHE] Reusing the message '{}' wrote for these changes; --no-cache asks again", cached.model);
+                Ranked {
+                    generation: Generation { text: cached.message, stats: GenerationStats::default() },
+                    model: Some(cached.model),
+                    prompt: prompt_builder.build(&git_info),
+                    candidates: Vec::new(),
+                }
+            } else if args.candidate_models().is_empty() {
+                let ranked = loop {
+                    match generate_with_model(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker).await {
+                        Err(e) if retry_after_timeout(&args, &mut ollama_manager, &e) => continue,
+                        ranked => break ranked?,
+                    }
+                };
+                // Only the model's own messages are kept, not the file-name fallback
+                if let (Some((cache, key)), Some(model), false) = (&cache, &ranked.model, args.write_mode().is_read_only()) {
+                    if let Err(e) = cache.put(key, &CachedResponse::new(model, &ranked.generation.text)) {
+                        tracing::warn!("Message not cached: {}", e);
+                    }
+                }
+                ranked
+            } else {
+                generate_ensemble(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker, confirm).await?
+            };
+            generation_stats = Some(ranked.generation.stats);
+            candidates = ranked.candidates;
+            generated_by = generator(&prompt_builder, &git_info, ranked.model.as_deref());
+            prompt = Some(ranked.prompt);
+            ranked.generation.text
+        }
+    };
+    let trailers = run_trailers(&args, &git_collector, generated_by.as_ref()).await?;
+    let mut commit_message = finalize_message(&args, &commit_message, &trailers)?;
+    // What the tool proposed, before the user had a chance to edit it
+    let mut proposed = commit_message.clone();
+    
+    // Copying is a convenience, so a missing clipboard only warns
+    if args.copy {
+        match clipboard::copy_to_clipboard(&commit_message) {
+            Ok(()) => status!(args, "[COPY] Copied commit message to the clipboard"),
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    
+    // Raw output is the message alone, so nothing else may reach stdout
+    if args.raw {
+        record_history(&args, &current_dir, &commit_message, None, generation_stats.as_ref(), None);
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(raw::raw_message(&commit_message, args.no_wrap).as_bytes())?;
+        stdout.flush()?;
+        return Ok(());
+    }
     
     // In dry-run mode, just show the message without committing
     if args.dry_run {
-        println!("\n[DRY RUN] Generated Commit Message (not committed):");
-        println!("==============================");
-        println!("{}", commit_message.trim());
-        println!("==============================");
-        println!("\nThis was a dry run. To actually commit, run without --dry-run");
+        status!(args, "\n[DRY RUN] Generated Commit Message (not committed):");
+        status!(args, "==============================");
+        status!(args, "{}", commit_message.trim());
+        status!(args, "==============================");
+        status!(args, "\nThis was a dry run. To actually commit, run without --dry-run");
+        record_history(&args, &current_dir, &commit_message, None, generation_stats.as_ref(), None);
+        if args.json {
+            print_json_report(&commit_message, generation_stats.as_ref(), &candidates, None, None)?;
+        }
         return Ok(());
     }
 
-    // Display the generated commit message
-    println!("\n[COMMIT] Generated Commit Message:");
-    println!("==============================");
-    println!("{}", commit_message.trim());
-    println!("==============================");
+    // Leave the actual commit to git when only preparing the message
+    if let Some(path) = &args.prepare_only {
+        let path = path.clone().unwrap_or_else(|| git_dir.join("COMMIT_EDITMSG"));
+        std::fs::write(&path, format!("{}\n", commit_message.trim()))
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write {}: {}", path.display(), e)))?;
+        
+        status!(args, "\n[PREPARED] Wrote commit message to {}:", path.display());
+        status!(args, "==============================");
+        status!(args, "{}", commit_message.trim());
+        status!(args, "==============================");
+        status!(args, "\nFinish with: git commit -e -F {}", path.display());
+        record_history(&args, &current_dir, &commit_message, None, generation_stats.as_ref(), None);
+        if args.json {
+            print_json_report(&commit_message, generation_stats.as_ref(), &candidates, None, None)?;
+        }
+        return Ok(());
+    }
     
-    // Check if we're in an interactive terminal
-    let is_interactive = atty::is(atty::Stream::Stdout);
+    // Display the generated commit message
+    show_message(&args, &commit_message);
     
     // Skip confirmation if not in an interactive terminal or if --no-confirm is set
-    if !is_interactive || args.no_confirm {
+    if !confirm {
         // Auto-confirm if not interactive
-        println!("[AUTO] Auto-confirmed (non-interactive terminal or --no-confirm)");
-        perform_commit(&commit_message, &current_dir).await?;
+        status!(args, "[AUTO] Auto-confirmed (non-interactive terminal or --no-confirm)");
     } else {
-        // Interactive confirmation
-        use dialoguer::Confirm;
+        // Interactive confirmation, one key per action
+        warn_signing(&args, &current_dir).await;
+        let term = console::Term::stdout();
+        loop {
+            match QuickAction::read(&term)? {
+                QuickAction::Commit => break,
+                QuickAction::Edit => {
+                    if let Some(edited) = editor::edit_message(&current_dir, &commit_message).await? {
+                        commit_message = edited;
+                    }
+                    show_message(&args, &commit_message);
+                }
+                action @ (QuickAction::Regenerate | QuickAction::RegenerateWithInstructions) => {
+                    if action == QuickAction::RegenerateWithInstructions {
+                        let instructions: String = dialoguer::Input::new()
+                            .with_prompt("Instructions for the new message")
+                            .allow_empty(true)
+                            .interact_text()?;
+                        prompt_builder = prompt_builder.with_instructions(Some(instructions));
+                    }
+                    let ranked = if args.candidate_models().is_empty() {
+                        generate_with_model(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker).await
+                    } else {
+                        generate_ensemble(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker, confirm).await
+                    };
+                    let ranked = match ranked {
+                        Err(e) if budget::is_exhausted_error(&e) => {
+                            status!(args, "[BUDGET] {}; keeping the current message", e);
+                            show_message(&args, &commit_message);
+                            continue;
+                        }
+                        result => result?,
+                    };
+                    generation_stats.get_or_insert_with(GenerationStats::default).accumulate(&ranked.generation.stats);
+                    candidates = ranked.candidates;
+                    generated_by = generator(&prompt_builder, &git_info, ranked.model.as_deref());
+                    prompt = Some(ranked.prompt);
+                    let trailers = run_trailers(&args, &git_collector, generated_by.as_ref()).await?;
+                    commit_message = finalize_message(&args, &ranked.generation.text, &trailers)?;
+                    proposed = commit_message.clone();
+                    show_message(&args, &commit_message);
+                }
+                QuickAction::Copy => match clipboard::copy_to_clipboard(&commit_message) {
+                    Ok(()) => status!(args, "[COPY] Copied commit message to the clipboard"),
+                    Err(e) => tracing::warn!("{}", e),
+                },
+                QuickAction::ShowDiff => {
+                    status!(args, "{}", git_collector.get_staged_diff().await?.trim_end());
+                    show_message(&args, &commit_message);
+                }
+                QuickAction::Quit => {
+                    status!(args, "[CANCEL] Commit cancelled by user");
+                    if let Some(tree) = staged_tree {
+                        let draft_path = Draft::new(tree, commit_message.trim()).save(&git_dir)?;
+                        status!(args, "[DRAFT] Saved message to {}; it will be offered again while the staged changes are unchanged", draft_path.display());
+                    }
+                    if args.json {
+                        print_json_report(&commit_message, generation_stats.as_ref(), &candidates, None, None)?;
+                    }
+                    record_history(&args, &current_dir, &commit_message, Some(&proposed), generation_stats.as_ref(), None);
+                    return Ok(());
+                }
+            }
+        }
+    }
+    
+    // A last look in the editor, as with `git commit -e`
+    if args.edit {
+        commit_message = editor::edit_message(&current_dir, &commit_message).await?
+            .ok_or_else(|| GitAiError::Git("Aborting commit due to empty commit message".to_string()))?;
+    }
+    
+    // A hook that rejects the commit gets explained, with a way to carry on
+    loop {
+        let watched = git_info.status.staged_files.clone();
+        let before = git_collector.worktree_hashes(&watched).await.unwrap_or_default();
+        let error = match perform_commit(&args, &commit_message, &current_dir).await {
+            Ok(()) => break,
+            Err(e) => e,
+        };
+        let active = hooks::active_hooks(&git_collector.hooks_dir().await?, hooks::COMMIT_HOOKS);
+        if active.is_empty() || args.no_verify {
+            return Err(error);
+        }
+        let after = git_collector.worktree_hashes(&watched).await.unwrap_or_default();
+        let failure = HookFailure {
+            hooks: active,
+            modified: hooks::changed_files(&before, &after),
+            output: error.to_string(),
+        };
+        if !confirm {
+            return Err(GitAiError::Git(format!("{}\n{}", failure.describe(), failure.hint())).into());
+        }
         
-        if Confirm::new()
-            .with_prompt("Commit these changes?")
-            .default(true)
-            .interact()?
-        {
-            perform_commit(&commit_message, &current_dir).await?;
-        } else {
-            println!("[CANCEL] Commit cancelled by user");
-            return Ok(());
+        tracing::warn!("{}", failure.describe());
+        let recoveries = failure.recoveries();
+        let labels: Vec<&str> = recoveries.iter().map(HookRecovery::label).collect();
+        let choice = dialoguer::Select::new()
+            .with_prompt("How do you want to go on?")
+            .items(&labels)
+            .default(0)
+            .interact()?;
+        match recoveries[choice] {
+            HookRecovery::StageAndRetry => git_collector.stage_files(&failure.modified).await?,
+            HookRecovery::StageAndRegenerate => {
+                git_collector.stage_files(&failure.modified).await?;
+                status!(args, "[REFRESH] Refreshing repository status...");
+                git_info = git_collector.collect_all().await?;
+                let staged_patch = git_collector.get_staged_diff().await.ok();
+                let ranker = Ranker::from_config(&args.config.scoring, &git_info, staged_patch.as_deref());
+                prompt_builder = prompt_builder.with_patch(staged_patch);
+                let ranked = if args.candidate_models().is_empty() {
+                    generate_with_model(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker).await
+                } else {
+                    generate_ensemble(&args, &mut ollama_manager, &prompt_builder, &git_info, &ranker, confirm).await
+                };
+                let ranked = match ranked {
+                    Err(e) if budget::is_exhausted_error(&e) => {
+                        status!(args, "[BUDGET] {}; keeping the current message", e);
+                        continue;
+                    }
+                    result => result?,
+                };
+                generation_stats.get_or_insert_with(GenerationStats::default).accumulate(&ranked.generation.stats);
+                candidates = ranked.candidates;
+                generated_by = generator(&prompt_builder, &git_info, ranked.model.as_deref());
+                prompt = Some(ranked.prompt);
+                let trailers = run_trailers(&args, &git_collector, generated_by.as_ref()).await?;
+                commit_message = finalize_message(&args, &ranked.generation.text, &trailers)?;
+                proposed = commit_message.clone();
+                show_message(&args, &commit_message);
+            }
+            HookRecovery::Retry => {}
+            HookRecovery::NoVerify => args.no_verify = true,
+            HookRecovery::Cancel => {
+                status!(args, "[CANCEL] Nothing was committed");
+                if let Ok(tree) = git_collector.staged_tree().await {
+                    let draft_path = Draft::new(tree, commit_message.trim()).save(&git_dir)?;
+                    status!(args, "[DRAFT] Saved message to {}; it will be offered again while the staged changes are unchanged", draft_path.display());
+                }
+                record_history(&args, &current_dir, &commit_message, Some(&proposed), generation_stats.as_ref(), None);
+                return Ok(());
+            }
+        }
+    }
+    status!(args, "[DONE] Commit created successfully!");
+    note_generator(&args, &git_collector, generated_by.as_ref()).await;
+    if let (true, Some(prompt)) = (args.notes, &prompt) {
+        let note = CommitNote::new(&commit_message, prompt, generated_by.as_ref(), generation_stats.clone().unwrap_or_default())
+            .with_candidates(&candidates);
+        attach_commit_note(&args, &git_collector, &note).await;
+    }
+    
+    let push_status = if args.push {
+        Some(push_commit(&args, &git_collector).await)
+    } else {
+        None
+    };
+    
+    // Stats are informational; the commit already succeeded
+    match git_collector.get_commit_stats().await {
+        Ok(stats) => {
+            status!(args, "{}", stats.display().trim_end());
+            if args.json {
+                print_json_report(&commit_message, generation_stats.as_ref(), &candidates, Some(&stats), push_status.as_ref())?;
+            }
+            record_history(&args, &current_dir, &commit_message, Some(&proposed), generation_stats.as_ref(), Some(&stats.sha));
         }
+        Err(e) => tracing::warn!("{}", e),
+    }
+    
+    if let Err(e) = Draft::discard(&git_dir) {
+        tracing::warn!("{}", e);
+    }
+    
+    // Notes and stats above belong to the commit just made, not the ones replayed after it
+    if let Some(sequence) = sequence.filter(|sequence| sequence.has_remaining(&git_dir)) {
+        status!(args, "[CONTINUE] Running git {} --continue...", sequence.command());
+        git_collector.continue_sequence(sequence).await?;
+        status!(args, "[DONE] git {} --continue finished", sequence.command());
+    }
+    
+    if let Some(PushStatus { error: Some(error), .. }) = push_status {
+        return Err(GitAiError::Git(error).into());
     }
-    println!("[DONE] Commit created successfully!");
     
     Ok(())
 }
 
-async fn is_git_repository(path: &PathBuf) -> Result<bool> {
-    let output = tokio::process::Command::new("git")
-        .args(&["rev-parse", "--git-dir"])
-        .current_dir(path)
-        .output()
-        .await?;
+/// Generate a new message from the changes in HEAD and amend it
+async fn amend_last_commit(args: &Args, repo_path: &Path) -> Result<()> {
+    use dialoguer::Confirm;
+    
+    let git_collector = git_collector(args, repo_path)?;
+    
+    status!(args, "[ANALYZE] Reading the last commit...");
+    let patch = git_collector.get_head_patch().await?;
+    let mut git_info = GitInfo::from_patch(&patch)?;
+    if git_info.file_changes.is_empty() {
+        return Err(GitAiError::Git("The last commit has no file changes to describe".to_string()).into());
+    }
+    git_info.branch_name = git_collector.get_branch_name().await.unwrap_or_default();
+    let current_message = git_collector.get_head_message().await?;
+    
+    let mut ollama_manager = ollama_manager(args)?;
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args)?)
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_project(project_info(args, &git_collector).await)
+        .with_layout(project_layout(args, &git_collector).await)
+        .with_style(args.style)
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages);
+    
+    status!(args, "[START] Starting Ollama...");
+    ollama_manager.ensure_running().await?;
+    ollama_manager.ensure_model_available(&args.model).await?;
+    
+    status!(args, "[GENERATE] Generating a new message for {} files...", git_info.file_changes.len());
+    let prompt = prompt_builder.build_with_patch(&git_info, &patch);
+    
+    if args.verbose > 0 {
+        status!(args, "[PROMPT] Generated prompt:");
+        status!(args, "{}", prompt);
+        status!(args, "==============================");
+    }
+    
+    let generation = ollama_manager.generate_commit_with_stats(&prompt).await?;
+    let message = apply_commit_style(args, &prompt_builder, &git_info, generation.text);
+    if validate::validate(&message, &args.config.validation).iter().any(Violation::is_unusable) {
+        return Err(GitAiError::Validation("The model gave no usable message; the last commit is unchanged".to_string()).into());
+    }
+    let generated_by = generator(&prompt_builder, &git_info, Some(ollama_manager.model()));
+    let trailers = run_trailers(args, &git_collector, generated_by.as_ref()).await?;
+    let mut message = finalize_message(args, &message, &trailers)?;
+    
+    status!(args, "\n[AMEND] Current message:");
+    status!(args, "{}", current_message);
+    show_message(args, &message);
+    
+    if args.dry_run {
+        status!(args, "\nThis was a dry run. To amend the last commit, run without --dry-run");
+        if args.json {
+            print_json_report(&message, Some(&generation.stats), &[], None, None)?;
+        }
+        return Ok(());
+    }
+    
+    let confirm = atty::is(atty::Stream::Stdout) && !args.no_confirm;
+    if confirm {
+        warn_signing(args, repo_path).await;
+    }
+    if confirm && !Confirm::new().with_prompt("Amend the last commit with this message?").default(true).interact()? {
+        status!(args, "[CANCEL] Amend cancelled by user");
+        return Ok(());
+    }
+    if args.edit {
+        message = editor::edit_message(repo_path, &message).await?
+            .ok_or_else(|| GitAiError::Git("Aborting amend due to empty commit message".to_string()))?;
+    }
+    
+    git_collector.amend_message(&message, args.gpg_sign_arg().as_deref()).await?;
+    status!(args, "[DONE] Last commit amended");
+    note_generator(args, &git_collector, generated_by.as_ref()).await;
+    if args.notes {
+        let note = CommitNote::new(&message, &prompt, generated_by.as_ref(), generation.stats.clone());
+        attach_commit_note(args, &git_collector, &note).await;
+    }
+    
+    match git_collector.get_commit_stats().await {
+        Ok(stats) => {
+            status!(args, "{}", stats.display().trim_end());
+            if args.json {
+                print_json_report(&message, Some(&generation.stats), &[], Some(&stats), None)?;
+            }
+            record_history(args, repo_path, &message, None, Some(&generation.stats), Some(&stats.sha));
+        }
+        Err(e) => tracing::warn!("{}", e),
+    }
     
-    Ok(output.status.success())
+    Ok(())
 }
 
-async fn perform_commit(message: &str, repo_path: &PathBuf) -> Result<()> {
-    let output = tokio::process::Command::new("git")
-        .args(&["commit", "-m", message])
-        .current_dir(repo_path)
-        .output()
-        .await?;
+/// Commit the merge in progress with git's subject and a generated body
+/// 
+/// The body describes what the merged commits bring in and which conflicts
+/// were resolved; `--no-edit` commits git's prepared message unchanged.
+async fn commit_merge(args: &Args, repo_path: &Path, git_collector: &GitCollector, git_info: &GitInfo, merge_state: &MergeState) -> Result<()> {
+    use dialoguer::Confirm;
+    
+    let unresolved = merge_state.unresolved(git_info);
+    if !unresolved.is_empty() {
+        let paths: Vec<String> = unresolved.iter().map(|path| path.display().to_string()).collect();
+        return Err(GitAiError::Git(format!(
+            "The merge still has conflicts in {}. Resolve them and git add the files before committing",
+            paths.join(", ")
+        )).into());
+    }
+    status!(args, "[MERGE] Finishing the merge: {}", merge_state.subject());
+    
+    let mut generation_stats = None;
+    let mut generated_by = None;
+    let message = if args.no_edit {
+        merge_state.message.clone()
+    } else {
+        let mut commits = Vec::new();
+        for head in &merge_state.heads {
+            commits.extend(git_collector.get_commits_in_range(&format!("HEAD..{}", head)).await?);
+        }
+        let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+            .with_context_blocks(args.config.context.clone())
+            .with_excluded_paths(args.excluded_paths());
+        let prompt = prompt_builder.build_merge(git_info, merge_state, &commits);
+        
+        if args.verbose > 0 {
+            status!(args, "[PROMPT] Generated prompt:");
+            status!(args, "{}", prompt);
+            status!(args, "==============================");
+        }
+        
+        let mut ollama_manager = ollama_manager(args)?;
+        ollama_manager.ensure_running().await?;
+        ollama_manager.ensure_model_available(&args.model).await?;
+        
+        status!(args, "[GENERATE] Describing the merge of {} commits...", commits.len());
+        let generation = ollama_manager.generate_commit_with_stats(&prompt).await?;
+        let body = postprocess::clean(&generation.text, &args.config.postprocess);
+        generation_stats = Some(generation.stats);
+        generated_by = generator(&prompt_builder, git_info, Some(ollama_manager.model()));
+        merge::merge_with_user_message(&merge_state.message, &body)
+    };
+    let trailers = run_trailers(args, git_collector, generated_by.as_ref()).await?;
+    let mut message = finalize_message(args, &message, &trailers)?;
+    show_message(args, &message);
+    
+    if args.dry_run {
+        status!(args, "\nThis was a dry run. To commit the merge, run without --dry-run");
+        if args.json {
+            print_json_report(&message, generation_stats.as_ref(), &[], None, None)?;
+        }
+        return Ok(());
+    }
+    
+    let confirm = atty::is(atty::Stream::Stdout) && !args.ci && !args.no_confirm;
+    if confirm {
+        warn_signing(args, repo_path).await;
+    }
+    if confirm && !Confirm::new().with_prompt("Commit the merge with this message?").default(true).interact()? {
+        status!(args, "[CANCEL] The merge is still in progress; nothing was committed");
+        return Ok(());
+    }
+    if args.edit && !args.no_edit {
+        message = editor::edit_message(repo_path, &message).await?
+            .ok_or_else(|| GitAiError::Git("Aborting commit due to empty commit message".to_string()))?;
+    }
+    
+    perform_commit(args, &message, repo_path).await?;
+    status!(args, "[DONE] Merge committed");
+    note_generator(args, git_collector, generated_by.as_ref()).await;
+    
+    match git_collector.get_commit_stats().await {
+        Ok(stats) => {
+            status!(args, "{}", stats.display().trim_end());
+            if args.json {
+                print_json_report(&message, generation_stats.as_ref(), &[], Some(&stats), None)?;
+            }
+            record_history(args, repo_path, &message, None, generation_stats.as_ref(), Some(&stats.sha));
+        }
+        Err(e) => tracing::warn!("{}", e),
+    }
+    
+    Ok(())
+}
+
+/// Push the new commit to the remote chosen by `--remote` or the branch's upstream
+/// 
+/// Failures are returned in the status so the JSON report can include them.
+async fn push_commit(args: &Args, git_collector: &GitCollector) -> PushStatus {
+    let target = async {
+        let branch = git_collector.get_branch_name().await?;
+        let upstream = git_collector.get_upstream().await;
+        let remotes = git_collector.get_remotes().await?;
+        PushTarget::resolve(&branch, upstream.as_deref(), &remotes, args.remote.as_deref(), args.set_upstream)
+    }.await;
+    
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => return PushStatus::failed(None, e),
+    };
+    
+    status!(args, "[PUSH] Pushing to {}/{}{}", target.remote, target.branch,
+        if target.set_upstream { " (setting upstream)" } else { "" });
+    match git_collector.push(&target).await {
+        Ok(()) => {
+            status!(args, "[DONE] Pushed to {}/{}", target.remote, target.branch);
+            PushStatus::pushed(target)
+        }
+        Err(e) => PushStatus::failed(Some(target), e),
+    }
+}
+
+/// Apply the banned word policy and configured trailers to a generated message
+fn finalize_message(args: &Args, message: &str, trailers: &RunTrailers) -> Result<String> {
+    let message = validate::enforce_banned_words(message, &args.config.validation)?;
+    let message = if args.body { body::wrap(&message, body::BODY_WIDTH) } else { message };
+    let message = match &trailers.ticket {
+        Some(ticket) => ticket::inject(&message, ticket, &args.config.ticket),
+        None => message,
+    };
+    let message = merge::append_trailers(&message, &args.config.trailers);
+    Ok(merge::append_trailers(&message, &trailers.lines))
+}
+
+/// What this run adds to a message besides the configured trailers
+struct RunTrailers {
+    lines: Vec<String>,
+    /// Ticket of the branch, for the subject or a trailer
+    ticket: Option<String>,
+}
+
+/// Trailers this run adds after the configured ones: co-authors, what
+/// generated the message (`None` when no model did) and `--signoff`, and
+/// the ticket found in the branch name
+async fn run_trailers(args: &Args, git_collector: &GitCollector, generated_by: Option<&Provenance>) -> Result<RunTrailers> {
+    let ticket = match args.config.ticket.placement {
+        TicketPlacement::Off => None,
+        _ => git_collector.get_branch_name().await.ok()
+            .and_then(|branch| ticket::find(&branch, &args.config.ticket.patterns)),
+    };
+    let mut run = Vec::new();
+    run.extend(args.config.co_authors.iter().map(|author| trailers::co_authored_by(author)));
+    if let Some(generated_by) = generated_by {
+        if args.config.ai_trailer {
+            run.push(trailers::ai_generated_by(&generated_by.model));
+        }
+        if args.config.provenance.mode == ProvenanceMode::Footer {
+            run.extend(generated_by.lines());
+        }
+    }
+    if args.signoff {
+        let identity = git_collector.get_committer_identity().await
+            .ok_or_else(|| GitAiError::Git("--signoff needs user.name and user.email to be set".to_string()))?;
+        run.push(trailers::signed_off_by(&identity));
+    }
+    Ok(RunTrailers { lines: run, ticket })
+}
+
+/// The record of what generated a message `model` wrote; `None` when no model did
+fn generator(prompt_builder: &PromptBuilder, git_info: &GitInfo, model: Option<&str>) -> Option<Provenance> {
+    model.map(|model| Provenance::new(model, &prompt_builder.template_for(git_info)))
+}
+
+/// Note what generated the message on the commit just made, in `notes` provenance mode
+/// 
+/// The commit already exists, so a failure only warns.
+async fn note_generator(args: &Args, git_collector: &GitCollector, generated_by: Option<&Provenance>) {
+    let Some(generated_by) = generated_by.filter(|_| args.config.provenance.mode == ProvenanceMode::Notes) else {
+        return;
+    };
+    match git_collector.add_note(&args.config.provenance.notes_ref, &generated_by.note()).await {
+        Ok(()) => status!(args, "[NOTE] Recorded the generator in refs/notes/{}", args.config.provenance.notes_ref),
+        Err(e) => tracing::warn!("{}", e),
+    }
+}
+
+/// Attach the generation details to the commit just made, for `--notes`
+/// 
+/// As with `note_generator`, the commit already exists, so a failure only warns.
+async fn attach_commit_note(args: &Args, git_collector: &GitCollector, note: &CommitNote) {
+    let attached = match note.to_json() {
+        Ok(json) => git_collector.add_note(history::NOTES_REF, &json).await,
+        Err(e) => Err(e),
+    };
+    match attached {
+        Ok(()) => status!(args, "[NOTE] Attached the generation details to refs/notes/{}", history::NOTES_REF),
+        Err(e) => tracing::warn!("{}", e),
+    }
+}
+
+/// Print the generation details `--notes` attached to `commit`
+async fn show_commit_note(args: &Args, git_collector: &GitCollector, commit: &str) -> Result<()> {
+    let note = git_collector.read_note(history::NOTES_REF, commit).await?
+        .ok_or_else(|| GitAiError::Git(format!(
+            "{} has no generation details; they are only attached to commits made with --notes", commit
+        )))?;
+    let note = CommitNote::parse(&note)?;
+    
+    if args.json {
+        println!("{}", note.to_json()?);
+    } else {
+        print!("{}", note.display());
+    }
+    Ok(())
+}
+
+/// Show the message that is about to be committed
+fn show_message(args: &Args, message: &str) {
+    // Quiet runs still show the result, just without the frame
+    if args.quiet && !args.json && !args.raw {
+        println!("{}", message.trim());
+        return;
+    }
+    status!(args, "\n[COMMIT] Generated Commit Message:");
+    status!(args, "==============================");
+    status!(args, "{}", message.trim());
+    status!(args, "==============================");
+}
+
+/// Print the `--json` result; `commit` is only set once a commit was created,
+/// `push` only when --push was given
+/// 
+/// `candidates` are the scored messages the one committed was chosen from,
+/// left out when there was no choice.
+fn print_json_report(
+    message: &str,
+    generation: Option<&GenerationStats>,
+    candidates: &[Candidate],
+    commit: Option<&CommitStats>,
+    push: Option<&PushStatus>,
+) -> Result<()> {
+    let mut report = serde_json::json!({
+        "message": message.trim(),
+        "committed": commit.is_some(),
+        "generation": generation,
+        "commit": commit,
+        "push": push,
+    });
+    if !candidates.is_empty() {
+        report["candidates"] = serde_json::to_value(candidates)?;
+    }
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// Model backend for this run, set up from the command line and the config
+/// 
+/// `--ci` only uses a backend that is already reachable and enforces the timeout.
+fn ollama_manager(args: &Args) -> Result<OllamaManager> {
+    let mut manager = OllamaManager::new(args.model.clone(), args.port)?
+        .with_write_mode(args.write_mode())
+        .with_daemon(args.config.daemon.clone())
+        .with_host(args.ollama_host()?)
+        .with_retries(args.config.max_retries)
+        .with_provider(args.provider(), &args.config.openai)
+        .with_local(args.provider(), &args.config.local)
+        .with_capabilities(args.config.capabilities.clone())
+        .with_options(args.generation_options())
+        .with_redaction(args.config.redaction)
+        .with_progress(!args.quiet && !args.json)
+        .with_call_budget(CallBudget::new(args.config.max_model_calls))
+        .with_attach_only(args.ci)
+        .with_generation_timeout(Duration::from_secs(args.timeout_seconds));
+    if args.provider() == Provider::OpenAi {
+        let ledger = Ledger::open_default()
+            .inspect_err(|e| tracing::warn!("Monthly spending not tracked: {}", e))
+            .ok();
+        manager = manager.with_cost_meter(CostMeter::new(args.config.cost.clone(), ledger)
+            .with_write_mode(args.write_mode())
+            .with_force(args.force));
+    }
+    // The client's own limit would otherwise cut a longer generation short
+    if args.ci || Duration::from_secs(args.timeout_seconds) > REQUEST_TIMEOUT {
+        manager = manager.with_timeout(Duration::from_secs(args.timeout_seconds));
+    }
+    Ok(manager)
+}
+
+/// Whether to generate again after `error`, a generation that ran out of time
+/// 
+/// Asked only in a terminal; the model gets twice as long on the next try.
+fn retry_after_timeout(args: &Args, ollama_manager: &mut OllamaManager, error: &anyhow::Error) -> bool {
+    let interactive = atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout) && !args.ci && !args.json && !args.raw;
+    let Some(limit) = ollama_manager.generation_timeout().filter(|_| interactive && is_timeout_error(error)) else {
+        return false;
+    };
+    eprintln!("[TIMEOUT] {}", error);
+    let longer = limit * 2;
+    let retry = dialoguer::Confirm::new()
+        .with_prompt(format!("Try again and wait up to {}s?", longer.as_secs()))
+        .default(true)
+        .interact()
+        .unwrap_or(false);
+    if retry {
+        ollama_manager.set_generation_timeout(longer);
+    }
+    retry
+}
+
+/// Cache of model answers and the key of this run's
+/// 
+/// `None` when several messages are wanted to choose from, since the cache
+/// keeps only one. With `--no-cache` nothing is read from it, but the new
+/// answer is still stored.
+fn response_cache(args: &Args, prompt_builder: &PromptBuilder, git_info: &GitInfo, patch: Option<&str>) -> Option<(ResponseCache, String)> {
+    if !args.candidate_models().is_empty() {
+        return None;
+    }
+    let cache = ResponseCache::open_default()
+        .inspect_err(|e| tracing::debug!("No response cache: {}", e))
+        .ok()?
+        .with_refresh(args.no_cache);
+    let (provider, endpoint) = match args.provider() {
+        Provider::Ollama => ("ollama", args.ollama_host().ok()?.base_url()),
+        Provider::OpenAi => ("openai", args.config.openai.base_url.clone()),
+        Provider::Local => ("local", local::describe(&args.config.local)),
+    };
+    let key = ResponseCache::key(provider, &endpoint, &args.model, patch?, &prompt_builder.build(git_info));
+    Some((cache, key))
+}
+
+/// Collector for the repository at `repo_path`, reading it through the configured backend
+fn git_collector(args: &Args, repo_path: &Path) -> Result<GitCollector> {
+    let backend = backend::open(args.config.git_backend, repo_path)?;
+    Ok(GitCollector::new(repo_path.to_path_buf())
+        .with_write_mode(args.write_mode())
+        .with_backend(backend))
+}
+
+/// End a run that found nothing to commit; `--ci` reports it in the exit code
+fn nothing_to_commit(args: &Args) -> Result<()> {
+    if args.ci {
+        std::process::exit(ci::EXIT_NOTHING_TO_COMMIT);
+    }
+    Ok(())
+}
+
+/// Packages of the repository for scope suggestions, affected workspace
+/// packages and which Rust crates are libraries, unless nothing in the config uses them
+async fn project_layout(args: &Args, git_collector: &GitCollector) -> ProjectLayout {
+    let context = &args.config.context;
+    if !(context.scopes || context.packages || context.breaking || args.config.validation.package_scope) || args.quick {
+        return ProjectLayout::default();
+    }
+    git_collector.get_project_layout().await
+}
+
+/// Project description for the prompt, unless disabled in the config
+async fn project_info(args: &Args, git_collector: &GitCollector) -> Option<ProjectInfo> {
+    if !args.config.project_context || args.quick {
+        return None;
+    }
+    Some(git_collector.get_project_info(args.config.readme_lines).await)
+}
+
+/// Messages committed in this repository before, unless disabled in the config
+/// 
+/// Shallow clones (usually CI) skip them; their history is cut short anyway.
+fn history_examples(args: &Args, repo_path: &Path, clone: CloneInfo) -> Vec<String> {
+    if !args.config.context.history || args.quick || clone.shallow {
+        return Vec::new();
+    }
+    History::open_default()
+        .and_then(|history| history.recent_messages(repo_path, HISTORY_EXAMPLES))
+        .unwrap_or_default()
+}
+
+/// Latest commits of the repository as style examples, unless disabled in the config
+async fn recent_commits(args: &Args, git_collector: &GitCollector) -> Vec<CommitSummary> {
+    if !args.config.context.history || args.quick {
+        return Vec::new();
+    }
+    git_collector.get_recent_commits(args.config.context.recent_commits).await
+        .inspect_err(|e| tracing::debug!("No recent commits for the prompt: {}", e))
+        .unwrap_or_default()
+}
+
+/// Record a model-generated message in the history file
+/// 
+/// `proposed` is the message the tool suggested, when the user could edit it
+/// before `message` was kept. Messages from drafts and fast paths have no
+/// usage and are not recorded. History is best effort, so failures only
+/// print a warning.
+fn record_history(
+    args: &Args,
+    repo_path: &Path,
+    message: &str,
+    proposed: Option<&str>,
+    stats: Option<&GenerationStats>,
+    sha: Option<&str>,
+) {
+    let stats = match stats {
+        Some(stats) if args.config.history && !args.no_write && !args.quick => stats,
+        _ => return,
+    };
+    
+    let mut entry = HistoryEntry::new(repo_path, &args.model, message, stats.clone());
+    if let Some(proposed) = proposed {
+        entry = entry.with_proposed(proposed);
+    }
+    if let Some(sha) = sha {
+        entry = entry.with_sha(sha);
+    }
+    
+    if let Err(e) = History::open_default().and_then(|history| history.append(&entry)) {
+        tracing::warn!("Failed to record history: {}", e);
+    }
+}
+
+/// Offer to reuse, edit or discard a saved draft
+/// 
+/// Returns `None` when a new message should be generated.
+async fn resume_draft(repo_path: &Path, draft: &Draft) -> Result<Option<String>> {
+    use dialoguer::Select;
+    
+    println!("\n[DRAFT] Found a saved draft for the currently staged changes:");
+    println!("==============================");
+    println!("{}", draft.message);
+    println!("==============================");
+    
+    let choice = Select::new()
+        .with_prompt("What would you like to do?")
+        .items(&["Use the draft", "Edit the draft", "Generate a new message"])
+        .default(0)
+        .interact()?;
+    
+    match choice {
+        0 => Ok(Some(draft.message.clone())),
+        1 => {
+            let edited = editor::edit_message(repo_path, &draft.message).await?;
+            Ok(Some(edited.unwrap_or_else(|| draft.message.clone())))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Ask how to stage changes when nothing is staged yet
+/// 
+/// Returns whether anything was staged; `false` means the user aborted.
+async fn stage_interactively(args: &Args, git_collector: &GitCollector, git_info: &GitInfo) -> Result<bool> {
+    use dialoguer::Select;
+    
+    status!(args, "[STAGE] Nothing is staged yet.");
+    let choice = Select::new()
+        .with_prompt("What would you like to do?")
+        .items(&["Stage all changes", "Choose files to stage", "Abort"])
+        .default(0)
+        .interact()?;
+    
+    match choice {
+        0 => {
+            status!(args, "[STAGE] Staging all unstaged changes...");
+            git_collector.stage_all_unstaged().await?;
+            Ok(true)
+        }
+        1 => pick_files_to_stage(args, git_collector, git_info).await,
+        _ => Ok(false),
+    }
+}
+
+/// Stage the unstaged and untracked files the user picks from a list
+/// 
+/// Returns whether anything was staged.
+async fn pick_files_to_stage(args: &Args, git_collector: &GitCollector, git_info: &GitInfo) -> Result<bool> {
+    let files = git_info.unstaged_paths();
+    let labels: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+    let selected = dialoguer::MultiSelect::new()
+        .with_prompt("Files to stage (space to select, enter to confirm)")
+        .items(&labels)
+        .interact()?;
+    if selected.is_empty() {
+        return Ok(false);
+    }
+    let chosen: Vec<PathBuf> = selected.into_iter().map(|i| files[i].clone()).collect();
+    status!(args, "[STAGE] Staging {} file(s)...", chosen.len());
+    git_collector.stage_files(&chosen).await?;
+    Ok(true)
+}
+
+/// A generated message and the ranked candidates it was chosen from, best first
+struct Ranked {
+    generation: Generation,
+    /// Model that wrote the message, `None` when it was described from file names
+    model: Option<String>,
+    /// Prompt the message was generated from
+    prompt: String,
+    /// Empty when there was nothing to choose from
+    candidates: Vec<Candidate>,
+}
+
+/// Make sure Ollama and the model are ready, then generate the commit message
+/// 
+/// When attempts are rejected and retried, the best scored one is used.
+async fn generate_with_model(
+    args: &Args,
+    ollama_manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    git_info: &GitInfo,
+    ranker: &Ranker,
+) -> Result<Ranked> {
+    // Ensure the model is available
+    status!(args, "[CHECK] Checking if model '{}' is available...", args.model);
+    ollama_manager.ensure_model_available(&args.model).await?;
+    
+    // Start Ollama if needed
+    status!(args, "[START] Starting Ollama...");
+    ollama_manager.ensure_running().await?;
+    
+    let mut stats = GenerationStats::default();
+    
+    // Too many files for one prompt: summarize them in chunks, then combine
+    let mut chunks = git_info.staged_chunks(args.max_files);
+    let mut chunked = args.config.chunk_large_commits && !args.quick && chunks.len() > 1;
+    let mut reason = format!("{} staged files exceed --max-files {}", git_info.status.staged_files.len(), args.max_files);
+    
+    // A prompt longer than the context window would lose its instructions
+    if !chunked {
+        match fit_context(args, ollama_manager, &prompt_builder.build(git_info), git_info).await {
+            ContextFit::Fits => {}
+            ContextFit::Switch(model) => {
+                ollama_manager.ensure_model_available(&model).await?;
+                ollama_manager.set_model(model);
+            }
+            ContextFit::Chunk(count) => {
+                let staged = git_info.status.staged_files.len();
+                chunks = git_info.staged_chunks(staged.div_ceil(count));
+                chunked = chunks.len() > 1;
+                reason = format!("The prompt is longer than the context of '{}'", ollama_manager.model());
+            }
+        }
+    }
+    
+    // Every chunk and the final message each take a call
+    if chunked && !ollama_manager.budget().allows(chunks.len() + 1) {
+        status!(args, "[BUDGET] Too few model calls left to summarize {} chunks, leaving files out instead", chunks.len());
+    }
+    let prompt = if chunked && ollama_manager.budget().allows(chunks.len() + 1) {
+        status!(args, "[CHUNK] {}, summarizing in {} chunks...", reason, chunks.len());
+        let mut partials = Vec::with_capacity(chunks.len());
+        for (index, chunk) in chunks.iter().enumerate() {
+            let chunk_prompt = prompt_builder.build_chunk_summary(chunk, index + 1, chunks.len());
+            let generation = ollama_manager.generate_commit_with_stats(&chunk_prompt).await?;
+            stats.accumulate(&generation.stats);
+            partials.push(postprocess::clean(&generation.text, &args.config.postprocess));
+        }
+        prompt_builder.build_synthesis(git_info, &partials)
+    } else {
+        prompt_builder.build(git_info)
+    };
+    
+    // Generate commit message
+    status!(args, "[GENERATE] Generating commit message...");
+    
+    if args.verbose > 0 {
+        status!(args, "[PROMPT] Generated prompt:");
+        status!(args, "{}", prompt);
+        status!(args, "==============================");
+    }
+    
+    let rules = &args.config.validation;
+    let packages = prompt_builder.affected_packages(git_info);
+    let mut attempt_prompt = prompt.clone();
+    let mut attempts = Vec::new();
+    
+    // Watch the message being written, unless the output is for a program or the backend can't stream
+    let mut stream = !args.no_stream && !args.json && !args.raw && atty::is(atty::Stream::Stdout);
+    let capabilities = ollama_manager.capabilities().await;
+    if args.verbose > 0 {
+        status!(args, "[CAPABILITIES] {}", capabilities.describe());
+    }
+    if stream && !capabilities.streaming {
+        tracing::debug!("'{}' doesn't stream; showing the message once it is done", ollama_manager.model());
+        stream = false;
+    }
+    // Messages nobody watches being written come as JSON fields, so no chatter
+    // around them gets committed; custom templates and styles without a
+    // conventional header keep the format they ask for
+    let format = match capabilities {
+        _ if stream
+            || args.template.is_some()
+            || prompt_builder.uses_category_template(git_info)
+            || !prompt_builder.uses_conventional_headers() => None,
+        Capabilities { json_schema: true, .. } => Some(structured::schema()),
+        Capabilities { json_mode: true, .. } => Some(serde_json::json!("json")),
+        _ => None,
+    };
+    
+    let retries = if args.quick { 0 } else { rules.retries };
+    for attempt in 0..=retries {
+        // Out of calls after a rejected attempt: settle for the closest one
+        if attempt > 0 && ollama_manager.budget().is_exhausted() {
+            status!(args, "[BUDGET] No model calls left for another attempt");
+            break;
+        }
+        let generation = if stream {
+            let mut stdout = std::io::stdout();
+            let mut print_token = |token: &str| {
+                let _ = write!(stdout, "{}", token);
+                let _ = stdout.flush();
+            };
+            let generation = ollama_manager.generate_commit_stream(&attempt_prompt, &mut print_token).await?;
+            println!();
+            generation
+        } else if let Some(ref format) = format {
+            let generation = ollama_manager.generate_commit_json(&structured::request(&attempt_prompt), format).await?;
+            match StructuredMessage::parse(&generation.text) {
+                Some(message) => Generation { text: message.render(), ..generation },
+                None => {
+                    tracing::debug!("The answer isn't the JSON asked for; using it as written");
+                    generation
+                }
+            }
+        } else {
+            ollama_manager.generate_commit_with_stats(&attempt_prompt).await?
+        };
+        stats.accumulate(&generation.stats);
+        
+        let message = apply_commit_style(args, prompt_builder, git_info, generation.text);
+        let candidate = Candidate::for_changes(format!("{} #{}", ollama_manager.model(), attempt + 1), message, rules, git_info)
+            .with_affected_packages(&packages, rules);
+        if candidate.violations.is_empty() {
+            attempts.push(candidate);
+            break;
+        }
+        
+        let summary: Vec<String> = candidate.violations.iter().map(|v| v.describe()).collect();
+        status!(args, "[RETRY] Attempt {} rejected: {}", attempt + 1, summary.join("; "));
+        
+        // An empty answer or a refusal would only come back again for the same prompt
+        let unusable = candidate.violations.iter().any(Violation::is_unusable);
+        if rules.retry_strategy == RetryStrategy::Feedback || unusable {
+            attempt_prompt = PromptBuilder::build_retry(&prompt, &candidate.message, &candidate.violations);
+        }
+        attempts.push(candidate);
+    }
+    
+    status!(args, "[STATS] {}", stats.display());
+    if let Some(cost) = ollama_manager.cost().display(ollama_manager.model()) {
+        status!(args, "[COST] {}", cost);
+    }
+    if args.verbose > 0 {
+        status!(args, "[BUDGET] {}", ollama_manager.budget().display());
+    }
+    
+    // Surface the best scored attempt rather than failing outright
+    let attempts = ranker.rank(attempts);
+    if args.verbose > 0 && attempts.len() > 1 {
+        show_scores(args, &attempts);
+    }
+    let best = &attempts[0];
+    
+    // Never commit an empty message or a refusal; file names still say something
+    let (text, model) = if best.violations.iter().any(Violation::is_unusable) {
+        status!(args, "[FALLBACK] The model gave no usable message, describing the changes from file names");
+        (heuristic::heuristic_message(git_info), None)
+    } else {
+        for violation in &best.violations {
+            status!(args, "[WARN] {}", violation.describe());
+        }
+        (best.message.clone(), Some(ollama_manager.model().to_string()))
+    };
+    
+    let candidates = if attempts.len() > 1 { attempts } else { Vec::new() };
+    Ok(Ranked { generation: Generation { text, stats }, model, prompt, candidates })
+}
+
+/// Commit the staged changes as several commits, grouped by the model
+/// 
+/// The model proposes the groups, then each group gets its own message. The
+/// staged tree is saved first, so each commit takes its files exactly as
+/// they were staged, and a failure halfway leaves the rest staged.
+async fn split_commits(
+    args: &Args,
+    repo_path: &Path,
+    git_collector: &GitCollector,
+    ollama_manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    git_info: &GitInfo,
+    confirm: bool,
+) -> Result<()> {
+    status!(args, "[CHECK] Checking if model '{}' is available...", args.model);
+    ollama_manager.ensure_model_available(&args.model).await?;
+    status!(args, "[START] Starting Ollama...");
+    ollama_manager.ensure_running().await?;
+    
+    status!(args, "[SPLIT] Grouping {} staged files into commits...", git_info.status.staged_files.len());
+    let answer = ollama_manager.generate_commit(&prompt_builder.build_split(git_info)).await?;
+    let answer = postprocess::clean(&answer, &args.config.postprocess);
+    let mut plan = CommitPlan::new(split::parse_groups(&answer), &git_info.status.staged_files);
+    
+    let total = plan.commits.len();
+    let mut prompts = Vec::with_capacity(total);
+    for (index, commit) in plan.commits.iter_mut().enumerate() {
+        status!(args, "\n[SPLIT] Commit {} of {}: {}", index + 1, total, commit.label);
+        let group = git_info.staged_subset(&commit.files);
+        let ranker = Ranker::from_config(&args.config.scoring, &group, None);
+        let ranked = generate_with_model(args, ollama_manager, prompt_builder, &group, &ranker).await?;
+        let generated_by = generator(prompt_builder, &group, ranked.model.as_deref());
+        let trailers = run_trailers(args, git_collector, generated_by.as_ref()).await?;
+        commit.message = Some(finalize_message(args, &ranked.generation.text, &trailers)?);
+        prompts.push((ranked.prompt, generated_by, ranked.generation.stats));
+    }
+    
+    status!(args, "\n[PLAN] {} commits:", total);
+    status!(args, "{}", plan.display().trim_end());
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    }
+    
+    if args.dry_run {
+        status!(args, "\nThis was a dry run. To create these commits, run without --dry-run");
+        return Ok(());
+    }
+    if confirm {
+        warn_signing(args, repo_path).await;
+    }
+    if confirm && !dialoguer::Confirm::new().with_prompt(format!("Create these {} commits?", total)).default(true).interact()? {
+        status!(args, "[CANCEL] Commits cancelled by user");
+        return Ok(());
+    }
+    
+    let tree = git_collector.staged_tree().await?;
+    git_collector.unstage_all().await?;
+    for (index, (commit, (prompt, generated_by, stats))) in plan.commits.iter().zip(prompts).enumerate() {
+        let message = commit.message.as_deref().unwrap_or_default();
+        let committed = async {
+            git_collector.stage_from_tree(&tree, &commit.index_paths(&git_info.file_changes)).await?;
+            perform_commit(args, message, repo_path).await
+        };
+        if let Err(e) = committed.await {
+            // Whatever wasn't committed yet is staged again, as it was
+            if let Err(restore) = git_collector.restore_staged_tree(&tree).await {
+                tracing::warn!("{}", restore);
+            }
+            return Err(e);
+        }
+        status!(args, "[DONE] Commit {} of {} created: {}", index + 1, total, message.lines().next().unwrap_or_default());
+        
+        note_generator(args, git_collector, generated_by.as_ref()).await;
+        if args.notes {
+            attach_commit_note(args, git_collector, &CommitNote::new(message, &prompt, generated_by.as_ref(), stats.clone())).await;
+        }
+        match git_collector.get_commit_stats().await {
+            Ok(commit_stats) => record_history(args, repo_path, message, None, Some(&stats), Some(&commit_stats.sha)),
+            Err(e) => tracing::warn!("{}", e),
+        }
+    }
+    
+    if args.push {
+        if let PushStatus { error: Some(error), .. } = push_commit(args, git_collector).await {
+            return Err(GitAiError::Git(error).into());
+        }
+    }
+    Ok(())
+}
+
+/// What to do about a prompt longer than the model's context window
+enum ContextFit {
+    Fits,
+    /// Generate with `large_context_model`, whose window is large enough
+    Switch(String),
+    /// Summarize the staged files in this many chunks
+    Chunk(usize),
+}
+
+/// Check `prompt` against the context window of the model, which Ollama
+/// would otherwise fill by silently dropping the beginning of the prompt
+async fn fit_context(args: &Args, ollama_manager: &OllamaManager, prompt: &str, git_info: &GitInfo) -> ContextFit {
+    let tokens = context::estimate_tokens(prompt);
+    if context::fits(prompt, context::MIN_WINDOW) {
+        return ContextFit::Fits;
+    }
+    let model = ollama_manager.model().to_string();
+    let Some(window) = ollama_manager.context_window(&model).await else {
+        return ContextFit::Fits;
+    };
+    if context::fits(prompt, window) {
+        return ContextFit::Fits;
+    }
+    status!(args, "[CONTEXT] The prompt (~{} tokens) is longer than the {}-token context of '{}'", tokens, window, model);
+    
+    if let Some(large) = args.config.large_context_model.as_ref().filter(|large| **large != model) {
+        // A model that isn't installed yet can't be asked; download it and trust the config
+        match ollama_manager.context_window(large).await {
+            Some(window) if !context::fits(prompt, window) => {
+                status!(args, "[CONTEXT] large_context_model '{}' reads only {} tokens either", large, window);
+            }
+            _ => {
+                status!(args, "[CONTEXT] Switching to large_context_model '{}'", large);
+                return ContextFit::Switch(large.clone());
+            }
+        }
+    }
+    
+    if args.config.chunk_large_commits && !args.quick && git_info.status.staged_files.len() > 1 {
+        return ContextFit::Chunk(context::chunks_needed(tokens, window));
+    }
+    status!(args, "[WARN] Ollama will cut off the beginning of the prompt; set large_context_model or lower --max-diff-lines");
+    ContextFit::Fits
+}
+
+/// `--verbose` scores of ranked candidates, best first
+fn show_scores(args: &Args, candidates: &[Candidate]) {
+    for candidate in candidates {
+        if let Some(score) = &candidate.score {
+            status!(args, "[SCORE] {} {}", score.display(), candidate.label());
+        }
+    }
+}
+
+/// Generate candidates with every `--ensemble` model, or `--candidates` times
+/// with the one model, and settle on one
+/// 
+/// A `--judge` model picks or merges the best candidate, or rates them when
+/// `scoring.judge` gives its ratings a weight. Without one the candidates are
+/// offered to choose from, or the best ranked one is used when nobody is
+/// there to choose.
+async fn generate_ensemble(
+    args: &Args,
+    ollama_manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    git_info: &GitInfo,
+    ranker: &Ranker,
+    interactive: bool,
+) -> Result<Ranked> {
+    status!(args, "[START] Starting Ollama...");
+    ollama_manager.ensure_running().await?;
+    
+    let models = args.candidate_models();
+    let mut required: Vec<&String> = models.iter().chain(args.judge.iter()).collect();
+    required.dedup();
+    for model in required {
+        status!(args, "[CHECK] Checking if model '{}' is available...", model);
+        ollama_manager.ensure_model_available(model).await?;
+    }
+    
+    match args.candidates {
+        Some(count) => status!(args, "[GENERATE] Generating {} candidates with '{}'...", count, args.model),
+        None => status!(args, "[GENERATE] Generating candidates with {} models...", models.len()),
+    }
+    let prompt = prompt_builder.build(git_info);
+    
+    if args.verbose > 0 {
+        status!(args, "[PROMPT] Generated prompt:");
+        status!(args, "{}", prompt);
+        status!(args, "==============================");
+    }
+    
+    let packages = prompt_builder.affected_packages(git_info);
+    let mut stats = GenerationStats::default();
+    let mut candidates = Vec::new();
+    let mut answered = false;
+    for (index, (model, result)) in ollama_manager.generate_with_models(&models, &prompt).await.into_iter().enumerate() {
+        // Candidates from the same model are told apart by number
+        let model = match args.candidates {
+            Some(_) => format!("{} #{}", model, index + 1),
+            None => model,
+        };
+        match result {
+            Ok(generation) => {
+                stats.accumulate(&generation.stats);
+                answered = true;
+                let message = apply_commit_style(args, prompt_builder, git_info, generation.text);
+                let candidate = Candidate::for_changes(model, message, &args.config.validation, git_info)
+                    .with_affected_packages(&packages, &args.config.validation);
+                if candidate.violations.iter().any(Violation::is_unusable) {
+                    status!(args, "[WARN] Model '{}' gave no usable message", candidate.model);
+                } else {
+                    candidates.push(candidate);
+                }
+            }
+            Err(e) => status!(args, "[WARN] Model '{}' failed: {}", model, e),
+        }
+    }
+    
+    if candidates.is_empty() && answered {
+        status!(args, "[FALLBACK] No model gave a usable message, describing the changes from file names");
+        let generation = Generation { text: heuristic::heuristic_message(git_info), stats };
+        return Ok(Ranked { generation, model: None, prompt, candidates });
+    }
+    if candidates.is_empty() {
+        return Err(GitAiError::Ollama("Every ensemble model failed to generate a message".to_string()).into());
+    }
+    let mut candidates = ranker.rank(candidates);
+    
+    status!(args, "\n[CANDIDATES] {} candidates (differences highlighted):", candidates.len());
+    let comparison = compare::render_comparison(&candidates, |word| word.yellow().bold().to_string());
+    status!(args, "{}", comparison.trim_end());
+    
+    let judge = match &args.judge {
+        Some(_) if ollama_manager.budget().is_exhausted() => {
+            status!(args, "[BUDGET] No model call left for the judge, using the best ranked candidate");
+            None
+        }
+        judge => judge.as_ref(),
+    };
+    let mut merged_by = None;
+    let text = if let Some(judge) = judge.filter(|_| args.config.scoring.judge > 0.0) {
+        status!(args, "[JUDGE] Asking '{}' to rate the candidates...", judge);
+        let judge_prompt = prompt_builder.build_judge_scores(git_info, &candidates);
+        let generation = ollama_manager.generate_with_model(judge, &judge_prompt).await?;
+        stats.accumulate(&generation.stats);
+        let ratings = score::parse_judge_ratings(&generation.text, candidates.len());
+        if ratings.iter().all(Option::is_none) {
+            status!(args, "[WARN] The judge gave no ratings, keeping the ranking without them");
+        }
+        let judge_scorer = JudgeScorer::new(&candidates, &ratings);
+        candidates = ranker.clone().with_scorer(judge_scorer, args.config.scoring.judge).rank(candidates);
+        candidates[0].message.clone()
+    } else if let Some(judge) = judge {
+        merged_by = Some(judge.clone());
+        status!(args, "[JUDGE] Asking '{}' to pick the best candidate...", judge);
+        let judge_prompt = prompt_builder.build_judge(git_info, &candidates);
+        let generation = ollama_manager.generate_with_model(judge, &judge_prompt).await?;
+        stats.accumulate(&generation.stats);
+        let verdict = apply_commit_style(args, prompt_builder, git_info, generation.text);
+        if validate::validate(&verdict, &args.config.validation).iter().any(Violation::is_unusable) {
+            status!(args, "[WARN] The judge gave no usable message, using the best ranked candidate");
+            merged_by = None;
+            candidates[0].message.clone()
+        } else {
+            verdict
+        }
+    } else if interactive && !args.json {
+        let labels: Vec<String> = candidates.iter().map(|c| c.label()).collect();
+        let choice = dialoguer::Select::new()
+            .with_prompt("Which message should be used?")
+            .items(&labels)
+            .default(0)
+            .interact()?;
+        candidates[choice].message.clone()
+    } else {
+        for violation in &candidates[0].violations {
+            status!(args, "[WARN] {}", violation.describe());
+        }
+        candidates[0].message.clone()
+    };
+    
+    if args.verbose > 0 {
+        show_scores(args, &candidates);
+        for rejected in candidates.iter().filter(|c| c.message != text) {
+            status!(args, "[REJECTED] {}", rejected.label());
+            status!(args, "{}", rejected.message.trim());
+        }
+    }
+    
+    status!(args, "[STATS] {}", stats.display());
+    if let Some(cost) = ollama_manager.cost().display(ollama_manager.model()) {
+        status!(args, "[COST] {}", cost);
+    }
+    if args.verbose > 0 {
+        status!(args, "[BUDGET] {}", ollama_manager.budget().display());
+    }
+    // A judge's own message is the judge's; otherwise the chosen candidate's model wrote it
+    let model = merged_by.or_else(|| match args.candidates {
+        Some(_) => Some(args.model.clone()),
+        None => candidates.iter().find(|c| c.message == text).map(|c| c.model.clone()),
+    });
+    Ok(Ranked { generation: Generation { text, stats }, model, prompt, candidates })
+}
+
+/// Staged diff for templates with a `{DIFF}` placeholder; other prompts don't show it
+async fn template_patch(prompt_builder: &PromptBuilder, git_collector: &GitCollector) -> Option<String> {
+    if !prompt_builder.uses_diff() {
+        return None;
+    }
+    git_collector.get_staged_diff().await.ok()
+}
+
+/// Text of the `--template` file (or the `template` setting), once it and
+/// the category templates are known to have valid placeholders
+/// 
+/// Without one, the prompt of the `--style`, if any, is used.
+fn custom_template(args: &Args) -> Result<Option<String>> {
+    args.config.check_templates()?;
+    let Some(path) = &args.template else {
+        return Ok(args.style.map(|message_style| style::template(message_style).to_string()));
+    };
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to read template {}: {}", path.display(), e)))?;
+    template::check(&text, &format!("Template {}", path.display()))?;
+    Ok(Some(text))
+}
+
+/// The answer is cleaned up first; see [`postprocess`]. Category templates
+/// always produce the category's commit type.
+/// 
+/// Otherwise a `--style` brings the subject into its format, and with
+/// `commit_style = "conventional"` a header that almost follows the grammar
+/// is repaired before it is validated.
+fn apply_commit_style(
+    args: &Args,
+    prompt_builder: &PromptBuilder,
+    git_info: &GitInfo,
+    commit_message: String,
+) -> String {
+    let commit_message = postprocess::clean(&commit_message, &args.config.postprocess);
+    let category = git_info.dominant_category();
+    if prompt_builder.uses_category_template(git_info) {
+        tracing::debug!("Using the '{}' category template", category.key());
+        return category.apply_commit_type(&commit_message);
+    }
+    
+    if let Some(message_style) = args.style {
+        return style::finish(message_style, &commit_message, git_info);
+    }
+    if args.config.validation.commit_style != CommitStyle::Conventional {
+        return commit_message;
+    }
+    let repaired = conventional::repair(&commit_message, category.commit_type());
+    if repaired != commit_message.trim() {
+        tracing::debug!("Rewrote the subject as a conventional commit: {}", repaired.lines().next().unwrap_or_default());
+    }
+    repaired
+}
+
+/// Write a generated message into the file git passed to prepare-commit-msg
+/// 
+/// A message the user already started (`-m`, `-F`, a commit template, merge
+/// or squash text) is handled according to `hook_mode`: `merge` keeps it and
+/// appends a generated body, `replace` overwrites it, `keep` leaves it alone.
+/// Amends and `-c`/`-C` reuse a finished message and are never touched.
+async fn prepare_hook_message(args: &Args, repo_path: &Path, msg_file: &Path) -> Result<()> {
+    if args.hook_source.as_deref() == Some("commit") || env::var_os(hooks::HOOK_SKIP_ENV).is_some() {
+        return Ok(());
+    }
+    args.write_mode().check("updating the commit message file")?;
+    
+    let git_collector = git_collector(args, repo_path)?;
+    let existing = std::fs::read_to_string(msg_file)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to read {}: {}", msg_file.display(), e)))?;
+    let comment = merge::comment_prefix(git_collector.get_comment_setting().await.as_deref(), &existing);
+    let existing = ExistingMessage::parse_with(&existing, &comment);
+    let mode = args.config.hook_mode;
+    
+    if existing.has_content() && mode == HookMode::Keep {
+        return Ok(());
+    }
+    
+    let git_info = git_collector.collect_all().await?;
+    if git_info.status.staged_files.is_empty() {
+        return Ok(());
+    }
+    
+    let mut ollama_manager = ollama_manager(args)?;
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args)?)
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_project(project_info(args, &git_collector).await)
+        .with_layout(project_layout(args, &git_collector).await)
+        .with_style(args.style)
+        .with_recent_commits(recent_commits(args, &git_collector).await)
+        .with_examples(history_examples(args, repo_path, CloneInfo::detect(repo_path).await))
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages);
+    let patch = template_patch(&prompt_builder, &git_collector).await;
+    let prompt_builder = prompt_builder.with_patch(patch);
+    
+    // `git commit` waits on this hook, so the model only gets a short budget
+    let generation = async {
+        ollama_manager.ensure_running().await?;
+        ollama_manager.ensure_model_available(&args.model).await?;
+        
+        let message = match existing.subject() {
+            Some(subject) if mode == HookMode::Merge => {
+                progress!(args, "[GENERATE] Generating a body for your commit message...");
+                let prompt = prompt_builder.build_body(&git_info, subject);
+                let body = ollama_manager.generate_commit(&prompt).await?;
+                let body = postprocess::clean(&body, &args.config.postprocess);
+                merge::merge_with_user_message(&existing.content, &body)
+            }
+            _ => {
+                progress!(args, "[GENERATE] Generating commit message...");
+                let prompt = prompt_builder.build(&git_info);
+                let commit_message = ollama_manager.generate_commit(&prompt).await?;
+                apply_commit_style(args, &prompt_builder, &git_info, commit_message)
+            }
+        };
+        Ok::<_, anyhow::Error>(message)
+    };
+    
+    let budget = Duration::from_secs(args.config.hook_timeout_seconds);
+    let (message, model) = match tokio::time::timeout(budget, generation).await {
+        Ok(message) => (message?, Some(args.model.as_str())),
+        Err(_) => {
+            progress!(args, "[TIMEOUT] No message after {}s, continuing without the model", budget.as_secs());
+            match args.config.hook_timeout_fallback {
+                // A message the user started is better than a heuristic one
+                HookFallback::Heuristic if !existing.has_content() => (heuristic::heuristic_message(&git_info), None),
+                _ => return Ok(()),
+            }
+        }
+    };
+    
+    let trailers = run_trailers(args, &git_collector, generator(&prompt_builder, &git_info, model).as_ref()).await?;
+    let message = finalize_message(args, &message, &trailers)?;
+    std::fs::write(msg_file, existing.render_with(&message))
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to write {}: {}", msg_file.display(), e)))?;
+    
+    Ok(())
+}
+
+/// Generate a message for a unified diff read from stdin
+/// 
+/// Progress goes to stderr so stdout only contains the message.
+async fn describe_stdin_diff(args: &Args) -> Result<()> {
+    let mut patch = String::new();
+    std::io::stdin()
+        .read_to_string(&mut patch)
+        .map_err(|e| GitAiError::Parse(format!("Failed to read diff from stdin: {}", e)))?;
+    
+    let git_info = GitInfo::from_patch(&patch)?;
+    if git_info.file_changes.is_empty() {
+        return Err(GitAiError::Parse("No file changes found in the diff on stdin".to_string()).into());
+    }
+    
+    // The diff may come from anywhere; run inside a repository, its layout still applies
+    let layout = match args.repo_dir() {
+        Ok(dir) if is_git_repository(&dir).await.unwrap_or(false) => project_layout(args, &GitCollector::new(dir)).await,
+        _ => ProjectLayout::default(),
+    };
+    let mut ollama_manager = ollama_manager(args)?;
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args)?)
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages)
+        .with_layout(layout)
+        .with_style(args.style);
+    
+    progress!(args, "[START] Starting Ollama...");
+    ollama_manager.ensure_running().await?;
+    ollama_manager.ensure_model_available(&args.model).await?;
+    
+    progress!(args, "[GENERATE] Generating commit message for {} files...", git_info.file_changes.len());
+    let prompt = prompt_builder.build_with_patch(&git_info, &patch);
+    
+    if args.verbose > 0 {
+        eprintln!("[PROMPT] Generated prompt:");
+        eprintln!("{}", prompt);
+        eprintln!("==============================");
+    }
+    
+    let commit_message = ollama_manager.generate_commit(&prompt).await?;
+    let commit_message = apply_commit_style(args, &prompt_builder, &git_info, commit_message);
+    let commit_message = validate::enforce_banned_words(&commit_message, &args.config.validation)?;
+    let commit_message = merge::append_trailers(&commit_message, &args.config.trailers);
+    println!("{}", commit_message.trim());
+    
+    Ok(())
+}
+
+/// Summarize the commits in `range` into a short paragraph
+async fn push_summary(args: &Args, git_collector: &GitCollector, range: &str) -> Result<()> {
+    let commits = git_collector.get_commits_in_range(range).await?;
+    if commits.is_empty() {
+        status!(args, "[INFO] No commits to push.");
+        return Ok(());
+    }
+    let shortstat = git_collector.get_range_shortstat(range).await?;
+    
+    let mut ollama_manager = ollama_manager(args)?;
+    ollama_manager.ensure_running().await?;
+    ollama_manager.ensure_model_available(&args.model).await?;
+    
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines);
+    let prompt = prompt_builder.build_push_summary(&commits, &shortstat);
+    
+    if args.verbose > 0 {
+        status!(args, "[PROMPT] Generated prompt:");
+        status!(args, "{}", prompt);
+        status!(args, "==============================");
+    }
+    
+    status!(args, "[GENERATE] Summarizing {} commits...", commits.len());
+    let summary = ollama_manager.generate_commit(&prompt).await?;
+    let summary = postprocess::clean(&summary, &args.config.postprocess);
+    
+    // Quiet runs still show the summary, just without the frame
+    if args.json {
+        let report = serde_json::json!({ "range": range, "commits": commits.len(), "summary": summary });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if args.raw || args.quiet {
+        println!("{}", summary);
+    } else {
+        println!("\n[SUMMARY] {} commits to be pushed ({}):", commits.len(), range);
+        println!("==============================");
+        println!("{}", summary);
+        println!("==============================");
+    }
+    
+    Ok(())
+}
+
+/// Write the squash-merge title and body for the commits between `base` and HEAD
+async fn squash_title(args: &Args, git_collector: &GitCollector, base: &str, pr: Option<u32>) -> Result<()> {
+    let commits_range = format!("{}..HEAD", base);
+    let commits = git_collector.get_commits_in_range(&commits_range).await?;
+    if commits.is_empty() {
+        return Err(GitAiError::Git(format!("HEAD has no commits that are not on {}", base)).into());
+    }
+    
+    // Three dots: what the pull request shows, even if the base moved on since
+    let patch = git_collector.get_range_patch(&format!("{}...HEAD", base)).await?;
+    let git_info = GitInfo::from_patch(&patch)?;
+    
+    let mut ollama_manager = ollama_manager(args)?;
+    ollama_manager.ensure_running().await?;
+    ollama_manager.ensure_model_available(&args.model).await?;
+    
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths());
+    let prompt = prompt_builder.build_squash(&git_info, &commits, &patch);
+    
+    if args.verbose > 0 {
+        status!(args, "[PROMPT] Generated prompt:");
+        status!(args, "{}", prompt);
+        status!(args, "==============================");
+    }
+    
+    // GitHub shows the title on its own, so it is always a conventional header
+    let rules = ValidationConfig { commit_style: CommitStyle::Conventional, ..args.config.validation.clone() };
+    status!(args, "[GENERATE] Writing the squash-merge message for {} commits...", commits.len());
+    let mut attempt_prompt = prompt.clone();
+    let mut message = String::new();
+    let mut violations = Vec::new();
+    for attempt in 0..=rules.retries {
+        if attempt > 0 && ollama_manager.budget().is_exhausted() {
+            status!(args, "[BUDGET] No model calls left for another attempt");
+            break;
+        }
+        let generation = ollama_manager.generate_commit_with_stats(&attempt_prompt).await?;
+        let text = postprocess::clean(&generation.text, &args.config.postprocess);
+        message = conventional::repair(&text, git_info.dominant_category().commit_type());
+        violations = validate::validate(&message, &rules);
+        if violations.is_empty() {
+            break;
+        }
+        let summary: Vec<String> = violations.iter().map(|v| v.describe()).collect();
+        status!(args, "[RETRY] Attempt {} rejected: {}", attempt + 1, summary.join("; "));
+        attempt_prompt = PromptBuilder::build_retry(&prompt, &message, &violations);
+    }
+    
+    if violations.iter().any(Violation::is_unusable) {
+        return Err(GitAiError::Validation("The model gave no usable squash-merge message".to_string()).into());
+    }
+    for violation in &violations {
+        status!(args, "[WARN] {}", violation.describe());
+    }
+    
+    let message = validate::enforce_banned_words(&message, &rules)?;
+    let authors = git_collector.get_range_authors(&commits_range).await?;
+    let committer = git_collector.get_user_email().await.unwrap_or_default();
+    let squash = SquashMessage::new(&message, pr, &squash::co_authors(&authors, &committer));
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&squash)?);
+    } else if args.raw {
+        println!("{}", squash.render());
+    } else {
+        println!("\n[SQUASH] Squash-merge message for {} commits ({}):", commits.len(), commits_range);
+        println!("==============================");
+        println!("{}", squash.render());
+        println!("==============================");
+    }
+    
+    Ok(())
+}
+
+/// Keep a suggestion for the staged changes up to date and serve it on a socket until interrupted
+async fn serve_suggestions(args: &Args, repo_path: &Path, socket: Option<PathBuf>) -> Result<()> {
+    let git_collector = git_collector(args, repo_path)?;
+    let git_dir = git_collector.git_dir().await?;
+    let socket = socket.unwrap_or_else(|| git_dir.join(serve::SOCKET_NAME));
+    
+    let mut ollama_manager = ollama_manager(args)?;
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args)?)
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_project(project_info(args, &git_collector).await)
+        .with_layout(project_layout(args, &git_collector).await)
+        .with_style(args.style)
+        .with_recent_commits(recent_commits(args, &git_collector).await)
+        .with_examples(history_examples(args, repo_path, CloneInfo::detect(repo_path).await))
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages);
+    
+    let suggestion = SharedSuggestion::default();
+    let watcher = async {
+        let mut index = None;
+        let mut staged_patch = None;
+        let mut last_error = None;
+        loop {
+            // The index is rewritten for more than staging, so only a different patch counts
+            let fingerprint = serve::index_fingerprint(&git_dir);
+            if fingerprint != index {
+                match git_collector.get_staged_diff().await {
+                    Ok(patch) => {
+                        index = fingerprint;
+                        last_error = None;
+                        if staged_patch.as_ref() != Some(&patch) {
+                            staged_patch = Some(patch);
+                            suggestion.set(suggest(args, &git_collector, &mut ollama_manager, &prompt_builder, &suggestion).await);
+                        }
+                    }
+                    // The index may be mid-write; keep serving and read it again on the next check
+                    Err(e) => {
+                        let error = e.to_string();
+                        if last_error.as_ref() != Some(&error) {
+                            status!(args, "[WARN] {}", error);
+                            suggestion.set(Suggestion::failed(&error, 0));
+                            last_error = Some(error);
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(serve::WATCH_INTERVAL).await;
+        }
+    };
+    
+    status!(args, "[SERVE] Serving suggestions on {} (Ctrl-C to stop)", socket.display());
+    tokio::select! {
+        result = serve::serve_socket(&socket, suggestion.clone()) => result,
+        () = watcher => Ok(()),
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+/// A suggestion for what is staged right now; failures are reported to clients, not fatal
+async fn suggest(
+    args: &Args,
+    git_collector: &GitCollector,
+    ollama_manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    suggestion: &SharedSuggestion,
+) -> Suggestion {
+    let git_info = match git_collector.collect_all().await {
+        Ok(git_info) => git_info,
+        Err(e) => return Suggestion::failed(&e.to_string(), 0),
+    };
+    let staged_files = git_info.status.staged_files.len();
+    if staged_files == 0 {
+        return Suggestion::idle();
+    }
+    
+    suggestion.set(Suggestion::generating(staged_files));
+    if args.verbose > 0 {
+        status!(args, "[GENERATE] Suggesting a message for {} staged files...", staged_files);
+    }
+    // Every suggestion gets the budget of a whole run
+    ollama_manager.budget().reset();
+    let prompt_builder = &prompt_builder.clone().with_patch(template_patch(prompt_builder, git_collector).await);
+    let generation = async {
+        ollama_manager.ensure_running().await?;
+        ollama_manager.ensure_model_available(&args.model).await?;
+        let text = ollama_manager.generate_commit(&prompt_builder.build(&git_info)).await?;
+        let message = apply_commit_style(args, prompt_builder, &git_info, text);
+        let (message, model) = if validate::validate(&message, &args.config.validation).iter().any(Violation::is_unusable) {
+            (heuristic::heuristic_message(&git_info), None)
+        } else {
+            (message, Some(ollama_manager.model()))
+        };
+        let trailers = run_trailers(args, git_collector, generator(prompt_builder, &git_info, model).as_ref()).await?;
+        finalize_message(args, &message, &trailers)
+    };
+    
+    match generation.await {
+        Ok(message) => Suggestion::ready(&message, staged_files),
+        Err(e) => {
+            status!(args, "[WARN] {}", e);
+            Suggestion::failed(&e.to_string(), staged_files)
+        }
+    }
+}
+
+/// Switch to the first healthy backend, trying the command line's model and port first
+/// 
+/// When none answers, the primary is kept and Ollama is started for it as usual.
+async fn select_backend(args: &mut Args) {
+    let mut backends = vec![Backend::new(args.port, args.model.clone())];
+    backends.extend(args.config.backends.iter().cloned());
+    
+    let preflight = Preflight::run(&backends, PREFLIGHT_TIMEOUT).await;
+    if args.verbose > 0 {
+        for (backend, reason) in &preflight.skipped {
+            status!(args, "[PREFLIGHT] Skipped {}: {}", backend, reason);
+        }
+    }
+    
+    match preflight.selected {
+        Some(backend) => {
+            if backend != backends[0] {
+                status!(args, "[PREFLIGHT] Using {}", backend);
+            }
+            args.port = backend.port;
+            args.model = backend.model;
+        }
+        None if args.verbose > 0 => {
+            status!(args, "[PREFLIGHT] No backend is ready, starting Ollama on port {}", args.port);
+        }
+        None => {}
+    }
+}
+
+/// List or stop the Ollama servers kept warm between runs
+fn manage_daemon(args: &Args, action: &DaemonCommand) -> Result<()> {
+    let registry = ServerRegistry::open_default()?;
+    
+    match action {
+        DaemonCommand::List => {
+            for entry in registry.sweep_and_stop(args.config.daemon.idle_timeout(), None)? {
+                println!("[DAEMON] Stopped idle server on port {}", entry.port);
+            }
+            
+            let entries = registry.load();
+            if entries.is_empty() {
+                println!("No warm servers. Set `daemon.keep_warm = true` in the config to keep them running.");
+            } else {
+                println!("{}", daemon::server_table(&entries, daemon::now_secs()));
+            }
+        }
+        DaemonCommand::Stop { port } => {
+            let entries: Vec<ServerEntry> = registry.load()
+                .into_iter()
+                .filter(|entry| port.is_none_or(|port| entry.port == port))
+                .collect();
+            if entries.is_empty() {
+                println!("No warm servers to stop");
+            }
+            for entry in entries {
+                // A reused process id belongs to another program now; only forget the entry
+                if daemon::is_registered_server(&entry) {
+                    daemon::stop_process(entry.pid)?;
+                    println!("[DAEMON] Stopped server on port {} (pid {})", entry.port, entry.pid);
+                } else {
+                    println!("[DAEMON] Server on port {} (pid {}) had already exited", entry.port, entry.pid);
+                }
+                registry.remove(entry.port)?;
+            }
+        }
+        DaemonCommand::Watch { port, pid } => {
+            daemon::watch(&registry, *port, *pid, args.config.daemon.idle_timeout())?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Print the models the provider offers
+async fn list_models(args: &Args) -> Result<()> {
+    let client: Box<dyn OllamaClientTrait> = match args.provider() {
+        Provider::Ollama => Box::new(OllamaClient::for_host(&args.ollama_host()?)),
+        Provider::OpenAi => {
+            Box::new(OpenAiClient::from_config(&args.config.openai))
+        }
+        Provider::Local => local::open(&args.config.local, None)?,
+    };
+    if !client.is_running().await {
+        match args.provider() {
+            Provider::Ollama => eprintln!("Error: Ollama is not running. Please start Ollama first."),
+            Provider::OpenAi => eprintln!("Error: Cannot reach {}", args.config.openai.base_url),
+            Provider::Local => eprintln!("Error: Cannot read {}", local::describe(&args.config.local)),
+        }
+        std::process::exit(1);
+    }
+    
+    match client.list_models().await {
+        Ok(models) => {
+            if models.is_empty() {
+                println!("No models found. Install one with 'git-ai-commit models pull <model>'");
+            } else {
+                println!("Available models:");
+                for model in models {
+                    println!("- {}", model);
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to list models: {}", e);
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+/// Download a model into the Ollama server, starting the server if needed
+async fn pull_model(args: &Args, name: &str) -> Result<()> {
+    if args.provider() != Provider::Ollama {
+        return Err(GitAiError::Provider("Models can only be downloaded with the ollama provider".to_string()).into());
+    }
+    args.write_mode().check(&format!("downloading the model '{}'", name))?;
+    
+    let mut ollama_manager = OllamaManager::new(name.to_string(), args.port)?
+        .with_write_mode(args.write_mode())
+        .with_daemon(args.config.daemon.clone())
+        .with_host(args.ollama_host()?)
+        .with_progress(!args.quiet && !args.json);
+    ollama_manager.ensure_running().await?;
+    ollama_manager.ensure_model_available(name).await?;
+    println!("[DONE] Model '{}' is installed", name);
+    Ok(())
+}
+
+/// Client for the running Ollama server that `models rm` and `models info` talk to
+async fn running_ollama(args: &Args) -> Result<OllamaClient> {
+    if args.provider() != Provider::Ollama {
+        return Err(GitAiError::Provider("Models can only be managed with the ollama provider".to_string()).into());
+    }
+    let host = args.ollama_host()?;
+    let client = OllamaClient::for_host(&host);
+    if !client.is_running().await {
+        return Err(GitAiError::Ollama(format!("Ollama is not running at {}", host)).into());
+    }
+    Ok(client)
+}
+
+/// Delete a model from the Ollama server
+async fn remove_model(args: &Args, name: &str) -> Result<()> {
+    args.write_mode().check(&format!("deleting the model '{}'", name))?;
+    running_ollama(args).await?.delete_model(name).await?;
+    println!("[DONE] Removed model '{}'", name);
+    Ok(())
+}
+
+/// Print what the Ollama server knows about an installed model
+async fn show_model(args: &Args, name: &str) -> Result<()> {
+    let details = running_ollama(args).await?.show_model(name).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+        return Ok(());
+    }
+    
+    let or_unknown = |value: &str| if value.is_empty() { "unknown".to_string() } else { value.to_string() };
+    println!("Model:        {}", details.name);
+    println!("Size:         {}", details.size.map_or("unknown".to_string(), daemon::format_memory));
+    println!("Family:       {}", or_unknown(&details.family));
+    println!("Parameters:   {}", or_unknown(&details.parameter_size));
+    println!("Quantization: {}", or_unknown(&details.quantization_level));
+    println!("Context:      {} tokens", details.context_window());
+    Ok(())
+}
+
+/// Read, change or create the config file
+fn manage_config(args: &Args, action: &ConfigCommand) -> Result<()> {
+    let path = Config::default_path()?;
+    match action {
+        ConfigCommand::Get { key } => println!("{}", args.config.get_key(key)?),
+        ConfigCommand::Set { key, value } => {
+            check_config_writable(args)?;
+            let mut config = Config::load_from_path(&path)?;
+            config.set_key(key, value)?;
+            config.save()?;
+            println!("[DONE] Set {} = {} in {}", key, config.get_key(key)?, path.display());
+        }
+        ConfigCommand::List => {
+            for (key, value) in args.config.entries()? {
+                println!("{} = {}", key, value);
+            }
+        }
+        ConfigCommand::Show => {
+            let config = toml::to_string_pretty(&args.config)
+                .map_err(|e| GitAiError::Config(format!("Failed to serialize config: {}", e)))?;
+            print!("{}", config);
+        }
+        ConfigCommand::Path => println!("{}", path.display()),
+        ConfigCommand::Init => {
+            if path.exists() {
+                println!("[INFO] {} already exists", path.display());
+                return Ok(());
+            }
+            check_config_writable(args)?;
+            Config::default().save()?;
+            println!("[DONE] Wrote the default config to {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Refuse config writes in read-only and CI runs
+fn check_config_writable(args: &Args) -> Result<()> {
+    if args.ci {
+        return Err(GitAiError::Config("--ci never writes the config file".to_string()).into());
+    }
+    args.write_mode().check("writing the config file")
+}
+
+/// Print every doctor check and fail if any of them did
+async fn run_doctor(args: &Args) -> Result<()> {
+    let current_dir = args.repo_dir()?;
+    
+    let mut checks = vec![doctor::check_git(&current_dir).await];
+    checks.extend(doctor::check_repository(&current_dir).await);
+    checks.push(match Config::default_path() {
+        Ok(path) => doctor::check_config(&path),
+        Err(e) => Check::fail("config", e.to_string()),
+    });
+    checks.extend(doctor::check_repo_config(&current_dir));
+    match args.ollama_host() {
+        Err(e) if args.provider() == Provider::Ollama => checks.push(Check::fail("ollama", e.to_string())),
+        host => {
+            // Other providers never talk to the Ollama host
+            let host = host.unwrap_or_else(|_| OllamaHost::local(args.port));
+            checks.extend(doctor::check_provider(&args.config, args.provider(), &host, &args.model).await);
+        }
+    }
+    
+    for check in &checks {
+        println!("{}", check.render());
+    }
+    
+    let failed = checks.iter().filter(|check| check.status == CheckStatus::Fail).count();
+    if failed > 0 {
+        return Err(GitAiError::Validation(format!("{} of {} checks failed", failed, checks.len())).into());
+    }
+    Ok(())
+}
+
+/// Run the pipeline in a temporary repository and report each stage
+async fn run_self_test(args: &Args, stub: bool) -> Result<()> {
+    let repo = tempfile::Builder::new()
+        .prefix("git-ai-commit-self-test")
+        .tempdir()
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to create a temporary repository: {}", e)))?;
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args)?)
+        .with_context_blocks(args.config.context.clone())
+        .with_body(args.body)
+        .with_style(args.style);
+    let mut manager = ollama_manager(args)?;
+    if stub {
+        manager = manager.with_client(Arc::new(StubClient));
+        manager.set_model("stub".to_string());
+    }
+    
+    let checks = self_test::run(repo.path(), &mut manager, &prompt_builder, &args.config.validation, stub).await;
+    for check in &checks {
+        println!("{}", check.render());
+    }
+    
+    let failed = checks.iter().filter(|check| check.status == CheckStatus::Fail).count();
+    if failed > 0 {
+        return Err(GitAiError::Validation(format!("{} of {} stages failed", failed, checks.len())).into());
+    }
+    Ok(())
+}
+
+/// Collect a redacted bug report and write it once the user has reviewed it
+async fn report_bug(args: &Args, output: &Path, issue: bool) -> Result<()> {
+    use dialoguer::Confirm;
+    
+    let report = BugReport::new(
+        &report_environment(args).await,
+        &toml::to_string(&args.config).unwrap_or_default(),
+        report_prompt(args).await.as_deref(),
+        &report_history(),
+    )
+    .with_log(&report_log(args));
+    
+    if issue {
+        print!("{}", report.issue_body());
+        return Ok(());
+    }
+    
+    for (name, content) in report.files() {
+        println!("--- {} ({} bytes) ---", name, content.len());
+        println!("{}", content.trim_end());
+    }
+    println!("---");
+    
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Write these files to {}?", output.display()))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        println!("Bug report discarded");
+        return Ok(());
+    }
+    
+    args.write_mode().check("writing the bug report")?;
+    report.write_tar(output)?;
+    println!("[DONE] Wrote {}; attach it to the GitHub issue", output.display());
+    Ok(())
+}
+
+/// Versions of the tool, OS, git and the Ollama server's models
+async fn report_environment(args: &Args) -> String {
+    let git_version = tokio::process::Command::new("git")
+        .arg("--version")
+        .output()
+        .await
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|e| format!("unavailable ({})", e));
+    
+    let ollama = match args.ollama_host() {
+        Ok(host) => {
+            // The name of a private server stays out of the report
+            let location = if host.is_remote() { "a remote host".to_string() } else { format!("port {}", host.port()) };
+            let client = OllamaClient::for_host(&host).with_timeout(PREFLIGHT_TIMEOUT);
+            match client.list_models().await {
+                Ok(models) => format!("running on {} with models: {}", location, models.join(", ")),
+                Err(_) => format!("not reachable on {}", location),
+            }
+        }
+        Err(e) => e.to_string(),
+    };
+    let provider = match args.provider() {
+        Provider::Ollama => "ollama".to_string(),
+        Provider::OpenAi => format!("openai at {}", args.config.openai.base_url),
+        Provider::Local => format!("local {}", local::describe(&args.config.local)),
+    };
+    
+    format!(
+        "git-ai-commit {}\nos: {} {}\ngit: {}\nollama: {}\nprovider: {}\nmodel: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        git_version,
+        ollama,
+        provider,
+        args.model,
+    )
+}
+
+/// Prompt for the changes in the current repository, if there are any
+async fn report_prompt(args: &Args) -> Option<String> {
+    let current_dir = args.repo_dir().ok()?;
+    if !is_git_repository(&current_dir).await.unwrap_or(false) {
+        return None;
+    }
+    
+    let git_collector = GitCollector::new(current_dir).with_write_mode(WriteMode::ReadOnly);
+    let git_info = git_collector.collect_all().await.ok()?;
+    if git_info.file_changes.is_empty() {
+        return None;
+    }
+    
+    let prompt_builder = PromptBuilder::new(args.max_files, args.max_diff_lines)
+        .with_category_templates(args.config.templates.clone())
+        .with_template(custom_template(args).ok().flatten())
+        .with_context_blocks(args.config.context.clone())
+        .with_diff_summary(args.config.summarize_diffs)
+        .with_excluded_paths(args.excluded_paths())
+        .with_project(project_info(args, &git_collector).await)
+        .with_layout(project_layout(args, &git_collector).await)
+        .with_style(args.style)
+        .with_body(args.body)
+        .with_mixed_languages(args.config.mixed_languages);
+    let patch = template_patch(&prompt_builder, &git_collector).await;
+    Some(prompt_builder.with_patch(patch).build(&git_info))
+}
+
+/// The last few history entries as JSON lines
+fn report_history() -> String {
+    let entries = History::open_default()
+        .and_then(|history| history.load())
+        .unwrap_or_default();
+    entries[entries.len().saturating_sub(report::HISTORY_ENTRIES)..]
+        .iter()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// The last lines of the log of the Ollama server started for this port, if any
+fn report_log(args: &Args) -> String {
+    startup::log_tail(&startup::server_log_path(args.port), report::LOG_LINES).unwrap_or_default()
+}
+
+/// Install a pre-push hook that prints the push summary
+/// 
+/// The hook never blocks the push, even if the summary fails.
+async fn install_pre_push_hook(git_collector: &GitCollector) -> Result<()> {
+    let hooks_dir = git_collector.hooks_dir().await?;
+    let hook_path = hooks::install_hook(&hooks_dir, "pre-push", "git-ai-commit push-summary || true")?;
+    println!("[DONE] Installed pre-push hook at {}", hook_path.display());
+    Ok(())
+}
+
+/// Install (or remove) the prepare-commit-msg hook that fills in messages for `git commit`
+async fn install_commit_msg_hook(git_collector: &GitCollector, uninstall: bool) -> Result<()> {
+    let hooks_dir = git_collector.hooks_dir().await?;
+    if uninstall {
+        match hooks::uninstall_hook(&hooks_dir, "prepare-commit-msg")? {
+            Some(hook_path) => println!("[DONE] Removed prepare-commit-msg hook at {}", hook_path.display()),
+            None => println!("[INFO] No prepare-commit-msg hook installed"),
+        }
+        return Ok(());
+    }
+    
+    let hook_path = hooks::install_hook(&hooks_dir, "prepare-commit-msg", hooks::PREPARE_COMMIT_MSG_HOOK)?;
+    println!("[DONE] Installed prepare-commit-msg hook at {}", hook_path.display());
+    println!("Run git commit as usual; the editor opens with a generated message.");
+    Ok(())
+}
+
+/// Say before the confirmation prompt when git will sign the commit, as
+/// signing may stop to ask for a passphrase
+async fn warn_signing(args: &Args, repo_path: &Path) {
+    if let Some(signing) = Signing::detect(repo_path, args.gpg_sign.as_deref()).await {
+        status!(args, "[SIGN] {}", signing.warning());
+    }
+}
+
+async fn perform_commit(args: &Args, message: &str, repo_path: &Path) -> Result<()> {
+    args.write_mode().check("committing")?;
+    
+    // git would refuse with "Aborting commit due to empty commit message"
+    if message.trim().is_empty() {
+        return Err(GitAiError::Validation("The commit message is empty; nothing was committed".to_string()).into());
+    }
+    
+    let gpg_sign = args.gpg_sign_arg();
+    let mut commit = vec!["commit"];
+    if args.no_verify {
+        commit.push("--no-verify");
+    }
+    commit.extend(gpg_sign.as_deref());
+    commit.extend(body::commit_args(message));
+    let output = lock::run_writing_index(repo_path, &commit).await?;
     
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -0,0 +1,77 @@
+//! Cap on model calls made by a single run
+//!
+//! Validation retries, regenerations, chunk summaries, ensembles and judges
+//! all ask the model again. A budget bounds the worst case, so a model that
+//! keeps producing rejected messages can't keep the run going indefinitely.
+
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Model calls allowed in one run, and how many were made
+#[derive(Debug, Default)]
+pub struct CallBudget {
+    /// Most calls allowed; `None` for no limit
+    limit: Option<usize>,
+    used: AtomicUsize,
+}
+
+impl CallBudget {
+    /// Budget of `limit` calls, where 0 means no limit
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: (limit > 0).then_some(limit),
+            used: AtomicUsize::new(0),
+        }
+    }
+    
+    /// Take one call from the budget, or fail if none is left
+    pub fn spend(&self) -> Result<()> {
+        let spent = self.used.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+            self.limit.is_none_or(|limit| used < limit).then_some(used + 1)
+        });
+        match spent {
+            Ok(_) => Ok(()),
+            Err(used) => Err(GitAiError::Budget(format!(
+                "all {} model calls of this run are used (max_model_calls)",
+                used
+            )).into()),
+        }
+    }
+    
+    /// Start counting again, e.g. for the next suggestion of `serve`
+    pub fn reset(&self) {
+        self.used.store(0, Ordering::SeqCst);
+    }
+    
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::SeqCst)
+    }
+    
+    /// Calls left; `None` without a limit
+    pub fn remaining(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_sub(self.used()))
+    }
+    
+    /// Whether at least `calls` more calls fit in the budget
+    pub fn allows(&self, calls: usize) -> bool {
+        self.remaining().is_none_or(|remaining| remaining >= calls)
+    }
+    
+    pub fn is_exhausted(&self) -> bool {
+        !self.allows(1)
+    }
+    
+    /// Usage for verbose output, e.g. "3 of 12 model calls used"
+    pub fn display(&self) -> String {
+        match self.limit {
+            Some(limit) => format!("{} of {} model calls used", self.used(), limit),
+            None => format!("{} model calls used (no limit)", self.used()),
+        }
+    }
+}
+
+/// Whether `error` means the budget ran out, rather than the model failing
+pub fn is_exhausted_error(error: &anyhow::Error) -> bool {
+    matches!(error.downcast_ref::<GitAiError>(), Some(GitAiError::Budget(_)))
+}
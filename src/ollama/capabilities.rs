@@ -0,0 +1,108 @@
+//! What a backend can do, known before a request is sent
+//!
+//! Each client reports the features its server and model offer, and the
+//! `[capabilities]` config table overrides what was detected. The pipeline
+//! checks them before relying on a feature, so an old Ollama or a minimal
+//! OpenAI-compatible server gets a plainer request rather than failing on
+//! one it doesn't understand.
+
+use crate::config::CapabilityOverrides;
+use serde::Serialize;
+
+/// Features a backend offers for a model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Tokens are passed on as they are generated
+    pub streaming: bool,
+    /// Answers can be constrained to valid JSON
+    pub json_mode: bool,
+    /// Answers can be constrained to a JSON schema
+    pub json_schema: bool,
+    /// Instructions can be sent apart from the prompt, as a system message
+    pub system_messages: bool,
+    /// Text can be turned into embedding vectors
+    pub embeddings: bool,
+    /// Tokens the model reads before the beginning of the prompt is cut off, when known
+    pub max_context: Option<u64>,
+}
+
+impl Capabilities {
+    /// One blocking completion from a single prompt, which every backend can do
+    pub const BASIC: Self = Self {
+        streaming: false,
+        json_mode: false,
+        json_schema: false,
+        system_messages: false,
+        embeddings: false,
+        max_context: None,
+    };
+    
+    /// What an Ollama server of `version` offers; unknown versions get the basics and streaming
+    pub fn for_ollama(version: Option<&str>) -> Self {
+        let Some(version) = version.and_then(parse_version) else {
+            return Self { streaming: true, ..Self::BASIC };
+        };
+        Self {
+            streaming: true,
+            system_messages: true,
+            json_mode: version >= OLLAMA_JSON_MODE,
+            json_schema: version >= OLLAMA_JSON_SCHEMA,
+            embeddings: version >= OLLAMA_EMBEDDINGS,
+            max_context: None,
+        }
+    }
+    
+    /// Apply what the config says, which wins over what was detected
+    pub fn with_overrides(self, overrides: &CapabilityOverrides) -> Self {
+        Self {
+            streaming: overrides.streaming.unwrap_or(self.streaming),
+            json_mode: overrides.json_mode.unwrap_or(self.json_mode),
+            json_schema: overrides.json_schema.unwrap_or(self.json_schema),
+            system_messages: overrides.system_messages.unwrap_or(self.system_messages),
+            embeddings: overrides.embeddings.unwrap_or(self.embeddings),
+            max_context: overrides.max_context.or(self.max_context),
+        }
+    }
+    
+    /// The features offered, e.g. `streaming, json mode, 8192-token context`
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = [
+            (self.streaming, "streaming"),
+            (self.json_mode, "json mode"),
+            (self.json_schema, "json schema"),
+            (self.system_messages, "system messages"),
+            (self.embeddings, "embeddings"),
+        ]
+        .into_iter()
+        .filter(|(offered, _)| *offered)
+        .map(|(_, name)| name.to_string())
+        .collect();
+        if let Some(tokens) = self.max_context {
+            parts.push(format!("{}-token context", tokens));
+        }
+        if parts.is_empty() {
+            return "plain completions only".to_string();
+        }
+        parts.join(", ")
+    }
+}
+
+/// `format: "json"` on `/api/generate`
+const OLLAMA_JSON_MODE: (u32, u32, u32) = (0, 1, 9);
+
+/// `/api/embeddings`
+const OLLAMA_EMBEDDINGS: (u32, u32, u32) = (0, 1, 26);
+
+/// A JSON schema as `format` (structured outputs)
+const OLLAMA_JSON_SCHEMA: (u32, u32, u32) = (0, 5, 0);
+
+/// `0.5.7` or `v0.1.32-rc1` as numbers; missing parts count as 0
+pub fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
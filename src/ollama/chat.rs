@@ -0,0 +1,63 @@
+//! Prompts sent as chat messages
+//!
+//! `/api/chat` takes the instructions apart from the changes they are about,
+//! as a system message, which newer models follow more closely than one long
+//! prompt. Prompts are still built as one text, so they are taken apart here:
+//! the opening role (`You are ...`) with the task, up to where the changes are
+//! inserted (`Repository Context:` or a diff), becomes the system message.
+//! Everything from there on is the user message, so nothing in the changes can
+//! end up among the instructions. A custom template without a role is sent
+//! whole as the user message.
+
+use serde_json::{json, Value};
+
+/// A prompt as the system and user messages of a chat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessages {
+    /// Role and rules; empty when the prompt has neither
+    pub system: String,
+    /// The changes to describe
+    pub user: String,
+}
+
+impl ChatMessages {
+    /// Take the instructions before the changes in `prompt` apart from the rest
+    pub fn split(prompt: &str) -> Self {
+        let whole = Self { system: String::new(), user: prompt.to_string() };
+        let paragraphs: Vec<&str> = prompt.split("\n\n").collect();
+        if !paragraphs[0].trim_start().starts_with("You are ") {
+            return whole;
+        }
+        // The task may take several paragraphs before the changes are inserted
+        let lead = paragraphs.iter()
+            .position(|paragraph| is_context_heading(paragraph) || is_diff(paragraph))
+            .unwrap_or(1);
+        
+        let user = paragraphs[lead..].join("\n\n");
+        // Nothing to describe would leave the model without a question
+        if user.trim().is_empty() {
+            return whole;
+        }
+        Self { system: paragraphs[..lead].join("\n\n"), user }
+    }
+    
+    /// The `messages` of a chat request, without an empty system message
+    pub fn to_json(&self) -> Value {
+        let mut messages = Vec::new();
+        if !self.system.trim().is_empty() {
+            messages.push(json!({ "role": "system", "content": self.system }));
+        }
+        messages.push(json!({ "role": "user", "content": self.user }));
+        Value::Array(messages)
+    }
+}
+
+/// Whether `paragraph` starts with a heading such as `Repository Context:`
+fn is_context_heading(paragraph: &str) -> bool {
+    paragraph.lines().next().is_some_and(|heading| heading.ends_with("Context:"))
+}
+
+/// Whether `paragraph` holds part of a diff, which a template may insert before any context
+fn is_diff(paragraph: &str) -> bool {
+    paragraph.lines().any(|line| line.starts_with("diff --git ") || line.starts_with("@@ "))
+}
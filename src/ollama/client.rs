@@ -1,27 +1,323 @@
+use crate::config::GenerationOptions;
 use crate::utils::error::GitAiError;
-use crate::ollama::OllamaClientTrait;
+use crate::ollama::{retry, Capabilities, OllamaClientTrait};
 use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::Client;
-use serde::Deserialize;
+use crate::ollama::chat::ChatMessages;
+use crate::ollama::host::OllamaHost;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// HTTP client for communicating with Ollama API
 #[derive(Clone)]
 pub struct OllamaClient {
     client: Client,
+    host: OllamaHost,
     base_url: String,
+    options: GenerationOptions,
+    /// Cleared once the server turns out to have no `/api/chat`, shared by clones
+    chat: Arc<AtomicBool>,
 }
 
+/// A `/api/generate` answer, or a `/api/chat` one with the text in `message`
 #[derive(Deserialize)]
 struct GenerateResponse {
+    #[serde(default)]
     response: String,
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(flatten)]
+    stats: GenerationStats,
+}
+
+impl GenerateResponse {
+    fn text(self) -> String {
+        self.message.map_or(self.response, |message| message.content)
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: String,
+}
+
+/// Token counts and timings Ollama reports for a generation
+/// 
+/// Durations are in nanoseconds, as returned by the API. Fields are
+/// `None` when the backend does not report them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_eval_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eval_duration: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_duration: Option<u64>,
+}
+
+impl GenerationStats {
+    /// Generated tokens per second, if Ollama reported the eval timing
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let (count, duration) = (self.eval_count?, self.eval_duration?);
+        if duration == 0 {
+            return None;
+        }
+        Some(count as f64 / (duration as f64 / 1e9))
+    }
+    
+    /// Add the usage of another generation, e.g. a retry
+    pub fn accumulate(&mut self, other: &GenerationStats) {
+        fn add(total: &mut Option<u64>, value: Option<u64>) {
+            if let Some(value) = value {
+                *total = Some(total.unwrap_or(0) + value);
+            }
+        }
+        add(&mut self.prompt_eval_count, other.prompt_eval_count);
+        add(&mut self.eval_count, other.eval_count);
+        add(&mut self.eval_duration, other.eval_duration);
+        add(&mut self.total_duration, other.total_duration);
+    }
+    
+    pub fn display(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(count) = self.prompt_eval_count {
+            parts.push(format!("{} prompt tokens", count));
+        }
+        if let Some(count) = self.eval_count {
+            parts.push(format!("{} generated tokens", count));
+        }
+        if let Some(duration) = self.total_duration {
+            parts.push(format!("{:.2}s total", duration as f64 / 1e9));
+        }
+        if let Some(rate) = self.tokens_per_second() {
+            parts.push(format!("{:.1} tokens/s", rate));
+        }
+        
+        if parts.is_empty() {
+            return "no usage reported".to_string();
+        }
+        parts.join(", ")
+    }
+}
+
+/// Generated text together with the usage Ollama reported for it
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub text: String,
+    pub stats: GenerationStats,
+}
+
+/// Tokens Ollama reads when neither the request nor the model sets `num_ctx`
+pub const DEFAULT_NUM_CTX: u64 = 4096;
+
+/// What `models info` shows about an installed model
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModelDetails {
+    pub name: String,
+    /// Size on disk in bytes, `None` when the model isn't listed by `/api/tags`
+    pub size: Option<u64>,
+    pub family: String,
+    /// Number of parameters, e.g. `3.2B`
+    pub parameter_size: String,
+    /// Quantization, e.g. `Q4_K_M`
+    pub quantization_level: String,
+    /// Longest context the model was trained for, in tokens
+    pub context_length: Option<u64>,
+    /// Context the model's parameters ask Ollama for, in tokens
+    pub num_ctx: Option<u64>,
+}
+
+impl ModelDetails {
+    /// Tokens Ollama reads before it cuts off the beginning of the prompt
+    pub fn context_window(&self) -> u64 {
+        self.num_ctx.unwrap_or(DEFAULT_NUM_CTX).min(self.context_length.unwrap_or(u64::MAX))
+    }
+}
+
+/// The `details` of a `/api/show` response
+#[derive(Deserialize, Default)]
+struct ShowDetails {
+    #[serde(default)]
+    family: String,
+    #[serde(default)]
+    parameter_size: String,
+    #[serde(default)]
+    quantization_level: String,
+}
+
+#[derive(Deserialize)]
+struct ShowResponse {
+    #[serde(default)]
+    details: ShowDetails,
+    /// Modelfile parameters, one `name value` per line
+    #[serde(default)]
+    parameters: String,
+    /// Architecture facts, e.g. `llama.context_length`
+    #[serde(default)]
+    model_info: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ShowResponse {
+    fn context_length(&self) -> Option<u64> {
+        self.model_info.iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+    }
+    
+    fn num_ctx(&self) -> Option<u64> {
+        self.parameters.lines()
+            .filter_map(|line| line.trim().strip_prefix("num_ctx"))
+            .find_map(|value| value.trim().parse().ok())
+    }
 }
 
 #[derive(Deserialize)]
 struct ModelInfo {
     name: String,
+    /// Size on disk in bytes
+    #[serde(default)]
+    size: u64,
+}
+
+/// One line of a streamed `/api/generate` or `/api/chat` response
+#[derive(Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    error: Option<String>,
+    /// Only the final chunk (`done: true`) carries the counts and timings
+    #[serde(flatten)]
+    stats: GenerationStats,
+}
+
+/// Reassembles a streamed generation from newline-delimited JSON
+/// 
+/// Network chunks can end mid-line, so bytes are buffered until a full
+/// line arrives. Each token is passed to `on_token` as soon as it's decoded.
+#[derive(Debug, Default)]
+pub struct StreamDecoder {
+    buffer: Vec<u8>,
+    text: String,
+    stats: GenerationStats,
+}
+
+impl StreamDecoder {
+    /// Decode every complete line in `bytes` and any left over from earlier calls
+    pub fn feed(&mut self, bytes: &[u8], on_token: &mut dyn FnMut(&str)) -> Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            self.decode_line(&line, on_token)?;
+        }
+        Ok(())
+    }
+    
+    /// Decode a final line without a trailing newline and return the whole generation
+    pub fn finish(mut self, on_token: &mut dyn FnMut(&str)) -> Result<Generation> {
+        let rest = std::mem::take(&mut self.buffer);
+        self.decode_line(&rest, on_token)?;
+        Ok(Generation { text: self.text, stats: self.stats })
+    }
+    
+    fn decode_line(&mut self, line: &[u8], on_token: &mut dyn FnMut(&str)) -> Result<()> {
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        
+        let chunk: StreamChunk = serde_json::from_str(line.trim())
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse streamed response: {}", e)))?;
+        if let Some(error) = chunk.error {
+            return Err(GitAiError::Ollama(error).into());
+        }
+        
+        let token = chunk.message.map_or(chunk.response, |message| message.content);
+        if !token.is_empty() {
+            on_token(&token);
+            self.text.push_str(&token);
+        }
+        self.stats.accumulate(&chunk.stats);
+        Ok(())
+    }
+}
+
+/// One line of a streamed `/api/pull` response
+/// 
+/// Layers are downloaded one after another; while one is, `digest` names it
+/// and `total` and `completed` count its bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PullProgress {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub completed: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Splits a streamed pull into progress updates, like [`StreamDecoder`] does for generations
+#[derive(Debug, Default)]
+pub struct PullDecoder {
+    buffer: Vec<u8>,
+    succeeded: bool,
+}
+
+impl PullDecoder {
+    /// Decode every complete line in `bytes` and any left over from earlier calls
+    pub fn feed(&mut self, bytes: &[u8], on_progress: &mut dyn FnMut(&PullProgress)) -> Result<()> {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            self.decode_line(&line, on_progress)?;
+        }
+        Ok(())
+    }
+    
+    /// Decode a final line without a trailing newline; a pull that never reported success failed
+    pub fn finish(mut self, on_progress: &mut dyn FnMut(&PullProgress)) -> Result<()> {
+        let rest = std::mem::take(&mut self.buffer);
+        self.decode_line(&rest, on_progress)?;
+        if !self.succeeded {
+            return Err(GitAiError::Ollama("Model download ended before it completed".to_string()).into());
+        }
+        Ok(())
+    }
+    
+    fn decode_line(&mut self, line: &[u8], on_progress: &mut dyn FnMut(&PullProgress)) -> Result<()> {
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        
+        let progress: PullProgress = serde_json::from_str(line.trim())
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse pull progress: {}", e)))?;
+        if let Some(error) = progress.error {
+            return Err(GitAiError::Ollama(format!("Failed to pull model: {}", error)).into());
+        }
+        
+        self.succeeded |= progress.status == "success";
+        on_progress(&progress);
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
 }
 
 #[derive(Deserialize)]
@@ -33,63 +329,42 @@ struct ModelsResponse {
 impl OllamaClientTrait for OllamaClient {
     async fn is_running(&self) -> bool {
         let url = format!("{}/api/tags", self.base_url);
-        self.client.get(&url).send().await.is_ok()
+        self.request(Method::GET, &url).send().await.is_ok()
     }
 
     async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
-        let url = format!("{}/api/generate", self.base_url);
-        
-        let payload = json!({
-            "model": model,
-            "prompt": prompt,
-            "stream": false,
-            "options": {
-                "temperature": 0.7,
-                "top_p": 0.9,
-                "max_tokens": 200
-            }
-        });
-        
-        let response = self.client
-            .post(&url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to send request: {}", e)))?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(GitAiError::Ollama(format!("Request failed with status {}: {}", status, text)).into());
-        }
-        
-        let generate_response: GenerateResponse = response
-            .json()
-            .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to parse response: {}", e)))?;
-        
-        Ok(generate_response.response)
+        Ok(self.generate_with_stats(model, prompt).await?.text)
+    }
+    
+    async fn generate_with_stats(&self, model: &str, prompt: &str) -> Result<Generation> {
+        self.complete(model, prompt, None).await
+    }
+    
+    async fn generate_json(&self, model: &str, prompt: &str, format: &serde_json::Value) -> Result<Generation> {
+        self.complete(model, prompt, Some(format)).await
     }
 
-    async fn list_models(&self) -> Result<Vec<String>> {
-        let url = format!("{}/api/tags", self.base_url);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to get models: {}", e)))?;
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        tracing::debug!("Streaming from {} with a {}-character prompt", model, prompt.len());
         
-        if !response.status().is_success() {
-            return Err(GitAiError::Ollama("Failed to fetch models".to_string()).into());
+        let mut response = self.send_generation(model, prompt, true, None).await?;
+        let mut decoder = StreamDecoder::default();
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to read response: {}", e)), e))?
+        {
+            decoder.feed(&chunk, on_token)?;
         }
-        
-        let models_response: ModelsResponse = response
-            .json()
-            .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to parse models response: {}", e)))?;
-            
-        let models = models_response.models.into_iter()
+        decoder.finish(on_token)
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let models = self.installed_models().await?
+            .into_iter()
             .map(|m| m.name)
             .collect();
             
@@ -107,27 +382,55 @@ impl OllamaClientTrait for OllamaClient {
     }
     
     async fn pull_model(&self, model_name: &str) -> Result<()> {
+        self.pull_model_with_progress(model_name, &mut |_| {}).await
+    }
+    
+    async fn pull_model_with_progress(
+        &self,
+        model_name: &str,
+        on_progress: &mut (dyn for<'p> FnMut(&'p PullProgress) + Send),
+    ) -> Result<()> {
         let url = format!("{}/api/pull", self.base_url);
         
         let payload = json!({
             "name": model_name,
-            "stream": false
+            "stream": true
         });
         
-        let response = self.client
-            .post(&url)
+        let mut response = self.request(Method::POST, &url)
             .json(&payload)
             .send()
             .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to pull model: {}", e)))?;
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to pull model: {}", e)), e))?;
             
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(GitAiError::Ollama(format!("Failed to pull model: {} - {}", status, text)).into());
+            return Err(retry::with_status(GitAiError::Ollama(format!("Failed to pull model: {} - {}", status, text)), status));
         }
         
-        Ok(())
+        let mut decoder = PullDecoder::default();
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to read pull progress: {}", e)), e))?
+        {
+            decoder.feed(&chunk, on_progress)?;
+        }
+        decoder.finish(on_progress)
+    }
+    
+    async fn context_window(&self, model_name: &str) -> Result<Option<u64>> {
+        Ok(Some(self.show_model(model_name).await?.context_window()))
+    }
+    
+    async fn capabilities(&self, model_name: &str) -> Capabilities {
+        let version = self.version().await.ok();
+        if version.is_none() {
+            tracing::debug!("Ollama didn't report its version; assuming an old one");
+        }
+        Capabilities {
+            max_context: self.context_window(model_name).await.ok().flatten(),
+            ..Capabilities::for_ollama(version.as_deref())
+        }
     }
     
     async fn delete_model(&self, model_name: &str) -> Result<()> {
@@ -137,8 +440,7 @@ impl OllamaClientTrait for OllamaClient {
             "name": model_name,
         });
         
-        let response = self.client
-            .delete(&url)
+        let response = self.request(Method::DELETE, &url)
             .json(&payload)
             .send()
             .await
@@ -153,17 +455,234 @@ impl OllamaClientTrait for OllamaClient {
     }
 }
 
+/// `response` when it succeeded, otherwise its status and body
+async fn successful(response: reqwest::Response) -> std::result::Result<reqwest::Response, (reqwest::StatusCode, String)> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    Err((status, response.text().await.unwrap_or_default()))
+}
+
+fn request_failed(status: reqwest::StatusCode, text: &str) -> anyhow::Error {
+    retry::with_status(GitAiError::Ollama(format!("Request failed with status {}: {}", status, text)), status)
+}
+
+/// Whether a failed response says the endpoint doesn't exist
+/// 
+/// Ollama also answers 404 for a missing model, but with a JSON `error`;
+/// servers without the route give a plain-text page or no body at all.
+fn is_unknown_endpoint(status: reqwest::StatusCode, body: &str) -> bool {
+    match status {
+        reqwest::StatusCode::METHOD_NOT_ALLOWED | reqwest::StatusCode::NOT_IMPLEMENTED => true,
+        reqwest::StatusCode::NOT_FOUND => serde_json::from_str::<serde_json::Value>(body)
+            .map_or(true, |value| value.get("error").is_none()),
+        _ => false,
+    }
+}
+
+/// Limit on each request unless `with_timeout` sets another, long enough for model downloads
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
 impl OllamaClient {
     pub fn new(port: u16) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(300)) // 5 minute timeout for long operations
-            .build()
-            .expect("Failed to create HTTP client");
-
+        Self::for_host(&OllamaHost::local(port))
+    }
+    
+    /// Client for the server at `host`, logging in with its credentials
+    pub fn for_host(host: &OllamaHost) -> Self {
         Self {
-            client,
-            base_url: format!("http://localhost:{}", port),
+            client: host.http_client(REQUEST_TIMEOUT),
+            host: host.clone(),
+            base_url: host.base_url(),
+            options: GenerationOptions::default(),
+            chat: Arc::new(AtomicBool::new(true)),
+        }
+    }
+    
+    /// Give up on requests after `timeout` instead of the default five minutes
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.host.http_client(timeout);
+        self
+    }
+    
+    /// Generate through `/api/chat` with the instructions as a system message,
+    /// or through `/api/generate` with the prompt as written
+    pub fn with_chat(mut self, chat: bool) -> Self {
+        self.chat = Arc::new(AtomicBool::new(chat));
+        self
+    }
+    
+    /// Sample with `options` instead of the defaults
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self
+    }
+    
+    /// Stop generating after this many tokens
+    pub fn with_num_predict(mut self, num_predict: u32) -> Self {
+        self.options.num_predict = Some(num_predict);
+        self
+    }
+    
+    /// One answer, the whole of it at once; `format` constrains it to JSON
+    async fn complete(&self, model: &str, prompt: &str, format: Option<&serde_json::Value>) -> Result<Generation> {
+        tracing::debug!("Generating with {} from a {}-character prompt", model, prompt.len());
+        let started = std::time::Instant::now();
+        
+        let response = self.send_generation(model, prompt, false, format).await?;
+        let generate_response: GenerateResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse response: {}", e)))?;
+        tracing::debug!("{} answered in {:.1}s", model, started.elapsed().as_secs_f64());
+        
+        let stats = generate_response.stats.clone();
+        let text = generate_response.text();
+        tracing::trace!("Response: {}", text);
+        Ok(Generation { text, stats })
+    }
+    
+    /// Post a generation to `/api/chat`, or to `/api/generate` when the server
+    /// is too old to chat, and return the successful response
+    async fn send_generation(
+        &self,
+        model: &str,
+        prompt: &str,
+        stream: bool,
+        format: Option<&serde_json::Value>,
+    ) -> Result<reqwest::Response> {
+        let mut payload = self.generate_payload(model, prompt, stream);
+        if let Some(format) = format {
+            payload["format"] = format.clone();
+        }
+        if self.chat.load(Ordering::Relaxed) {
+            let mut chat = payload.clone();
+            if let Some(fields) = chat.as_object_mut() {
+                fields.remove("prompt");
+                fields.insert("messages".to_string(), ChatMessages::split(prompt).to_json());
+            }
+            match successful(self.post(&format!("{}/api/chat", self.base_url), &chat).await?).await {
+                Ok(response) => return Ok(response),
+                Err((status, text)) if is_unknown_endpoint(status, &text) => {
+                    tracing::debug!("{} has no /api/chat; generating with /api/generate", self.base_url);
+                    self.chat.store(false, Ordering::Relaxed);
+                }
+                Err((status, text)) => return Err(request_failed(status, &text)),
+            }
+        }
+        
+        successful(self.post(&format!("{}/api/generate", self.base_url), &payload).await?).await
+            .map_err(|(status, text)| request_failed(status, &text))
+    }
+    
+    async fn post(&self, url: &str, payload: &serde_json::Value) -> Result<reqwest::Response> {
+        self.request(Method::POST, url)
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to send request: {}", e)), e))
+    }
+    
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.host.authorize(self.client.request(method, url))
+    }
+    
+    fn generate_payload(&self, model: &str, prompt: &str, stream: bool) -> serde_json::Value {
+        json!({
+            "model": model,
+            "prompt": prompt,
+            "stream": stream,
+            "options": self.options,
+        })
+    }
+    
+    /// Version of the running server, e.g. `0.5.7`
+    pub async fn version(&self) -> Result<String> {
+        let url = format!("{}/api/version", self.base_url);
+        
+        let response = self.request(Method::GET, &url)
+            .send()
+            .await
+            .map_err(|e| GitAiError::Ollama(format!("Failed to get the Ollama version: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(GitAiError::Ollama(format!("Failed to get the Ollama version: {}", response.status())).into());
+        }
+        
+        let version: VersionResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse the Ollama version: {}", e)))?;
+        Ok(version.version)
+    }
+    
+    /// Name of the installed model that takes the least disk space
+    pub async fn smallest_model(&self) -> Result<Option<String>> {
+        Ok(self.installed_models().await?
+            .into_iter()
+            .min_by_key(|m| m.size)
+            .map(|m| m.name))
+    }
+    
+    /// Family, parameter count, quantization and size of an installed model
+    pub async fn show_model(&self, model_name: &str) -> Result<ModelDetails> {
+        let url = format!("{}/api/show", self.base_url);
+        
+        let response = self.request(Method::POST, &url)
+            .json(&json!({ "model": model_name }))
+            .send()
+            .await
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to get model info: {}", e)), e))?;
+        
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(GitAiError::Ollama(format!("Model '{}' is not installed", model_name)).into());
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(retry::with_status(GitAiError::Ollama(format!("Failed to get model info: {} - {}", status, text)), status));
+        }
+        
+        let show: ShowResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse model info: {}", e)))?;
+        let size = self.installed_models().await?
+            .into_iter()
+            // Ollama lists untagged names with their implicit tag
+            .find(|m| m.name == model_name || m.name == format!("{}:latest", model_name))
+            .map(|m| m.size);
+        
+        Ok(ModelDetails {
+            name: model_name.to_string(),
+            size,
+            context_length: show.context_length(),
+            num_ctx: show.num_ctx(),
+            family: show.details.family,
+            parameter_size: show.details.parameter_size,
+            quantization_level: show.details.quantization_level,
+        })
+    }
+    
+    async fn installed_models(&self) -> Result<Vec<ModelInfo>> {
+        let url = format!("{}/api/tags", self.base_url);
+        
+        let response = self.request(Method::GET, &url)
+            .send()
+            .await
+            .map_err(|e| retry::with_source(GitAiError::Ollama(format!("Failed to get models: {}", e)), e))?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(retry::with_status(GitAiError::Ollama(format!("Failed to fetch models: {}", status)), status));
         }
+        
+        let models_response: ModelsResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Ollama(format!("Failed to parse models response: {}", e)))?;
+        
+        Ok(models_response.models)
     }
 }
 
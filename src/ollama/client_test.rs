@@ -165,6 +165,136 @@ async fn test_generate() {
     assert_eq!(response, "This is a test response");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generate_with_stats() {
+    // Start a mock server
+    let mut server = Server::new_async().await;
+    
+    // Mock the /api/generate endpoint with usage fields
+    let mock_response = json!({
+        "response": "feat: add login",
+        "done": true,
+        "prompt_eval_count": 412,
+        "eval_count": 20,
+        "eval_duration": 2_000_000_000u64,
+        "total_duration": 2_500_000_000u64
+    });
+    
+    let _m = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(mock_response.to_string())
+        .create_async()
+        .await;
+    
+    let url = server.url();
+    let port: u16 = url.split(':').nth(2).unwrap().parse().unwrap();
+    let client = OllamaClient::new(port);
+    
+    let generation = client.generate_with_stats("test-model", "Test prompt").await.unwrap();
+    assert_eq!(generation.text, "feat: add login");
+    assert_eq!(generation.stats.prompt_eval_count, Some(412));
+    assert_eq!(generation.stats.eval_count, Some(20));
+    assert_eq!(generation.stats.tokens_per_second(), Some(10.0));
+    assert_eq!(
+        generation.stats.display(),
+        "412 prompt tokens, 20 generated tokens, 2.50s total, 10.0 tokens/s"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_smallest_model_and_num_predict() {
+    let mut server = Server::new_async().await;
+    
+    let mock_models = json!({ "models": [
+        { "name": "llama3:8b", "size": 4_661_224_676u64 },
+        { "name": "qwen2.5:0.5b", "size": 397_821_319u64 },
+        { "name": "gemma3:4b", "size": 3_338_801_804u64 }
+    ]});
+    let _m_tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(mock_models.to_string())
+        .create_async()
+        .await;
+    
+    // The token cap is sent as Ollama's num_predict option
+    let _m_generate = server
+        .mock("POST", "/api/generate")
+        .match_body(mockito::Matcher::PartialJson(json!({ "options": { "num_predict": 48 } })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "response": "wip: tweak parser", "done": true }).to_string())
+        .create_async()
+        .await;
+    
+    let url = server.url();
+    let port: u16 = url.split(':').nth(2).unwrap().parse().unwrap();
+    let client = OllamaClient::new(port).with_num_predict(48);
+    
+    assert_eq!(client.smallest_model().await.unwrap().as_deref(), Some("qwen2.5:0.5b"));
+    assert_eq!(client.generate("qwen2.5:0.5b", "Test prompt").await.unwrap(), "wip: tweak parser");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_show_model() {
+    let mut server = Server::new_async().await;
+    
+    let _m_show = server
+        .mock("POST", "/api/show")
+        .match_body(mockito::Matcher::PartialJson(json!({ "model": "llama3.2" })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({
+            "details": {
+                "format": "gguf",
+                "family": "llama",
+                "parameter_size": "3.2B",
+                "quantization_level": "Q4_K_M"
+            },
+            "parameters": "stop \"<|eot_id|>\"\nnum_ctx 8192",
+            "model_info": { "general.architecture": "llama", "llama.context_length": 131072 }
+        }).to_string())
+        .create_async()
+        .await;
+    let _m_missing = server
+        .mock("POST", "/api/show")
+        .match_body(mockito::Matcher::PartialJson(json!({ "model": "missing" })))
+        .with_status(404)
+        .with_body(json!({ "error": "model 'missing' not found" }).to_string())
+        .create_async()
+        .await;
+    let _m_tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "models": [{ "name": "llama3.2:latest", "size": 2_019_393_189u64 }] }).to_string())
+        .create_async()
+        .await;
+    
+    let url = server.url();
+    let port: u16 = url.split(':').nth(2).unwrap().parse().unwrap();
+    let client = OllamaClient::new(port);
+    
+    // An untagged name finds the size of its :latest tag
+    let details = client.show_model("llama3.2").await.unwrap();
+    assert_eq!(details, ModelDetails {
+        name: "llama3.2".to_string(),
+        size: Some(2_019_393_189),
+        family: "llama".to_string(),
+        parameter_size: "3.2B".to_string(),
+        quantization_level: "Q4_K_M".to_string(),
+        context_length: Some(131_072),
+        num_ctx: Some(8192),
+    });
+    assert_eq!(details.context_window(), 8192);
+    
+    let error = client.show_model("missing").await.unwrap_err();
+    assert!(error.to_string().contains("'missing' is not installed"), "{}", error);
+}
+
 #[tokio::test]
 async fn test_get_last_model_empty_list() {
     // Start a mock server
@@ -0,0 +1,32 @@
+//! Fitting prompts into a model's context window
+//!
+//! Ollama doesn't refuse a prompt longer than the model's context: it drops
+//! the beginning, which is where the instructions are. Prompts are measured
+//! before they are sent, so a run can switch to a model with a larger window
+//! or summarize in chunks instead.
+
+/// Tokens kept free for the answer
+pub const ANSWER_TOKENS: u64 = 256;
+
+/// Smallest window any model has; shorter prompts aren't checked
+pub const MIN_WINDOW: u64 = 2048;
+
+/// Rough token count of `text`
+/// 
+/// Tokenizers average about four characters per token for English and
+/// fewer for code, so three errs on the long side.
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(3)
+}
+
+/// Whether `prompt` and an answer fit into `window` tokens
+pub fn fits(prompt: &str, window: u64) -> bool {
+    estimate_tokens(prompt) + ANSWER_TOKENS <= window
+}
+
+/// Chunks a prompt of `tokens` needs so that each chunk's changes take at most
+/// half of `window`, leaving the rest for instructions and the answer
+pub fn chunks_needed(tokens: u64, window: u64) -> usize {
+    let per_chunk = (window.saturating_sub(ANSWER_TOKENS) / 2).max(1);
+    tokens.div_ceil(per_chunk).max(2) as usize
+}
@@ -0,0 +1,239 @@
+//! What generations on a paid API cost, and caps on it
+//!
+//! Prices are per million tokens, from `[cost.prices]` or the list prices of
+//! common hosted models. Before a call, its cost is estimated from the prompt;
+//! afterwards the usage the API reports is charged. Every charge is added to a
+//! ledger in the state directory, so `monthly_budget` holds across runs.
+
+use crate::config::{CostConfig, ModelPrice};
+use crate::ollama::context::{estimate_tokens, ANSWER_TOKENS};
+use crate::ollama::GenerationStats;
+use crate::utils::error::GitAiError;
+use crate::utils::WriteMode;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// List prices of hosted models, in dollars per million input and output tokens
+pub const BUILTIN_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("o4-mini", 1.10, 4.40),
+    ("o3-mini", 1.10, 4.40),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+];
+
+/// Price of `model` in `prices` or, failing that, among the built-in ones
+/// 
+/// Dated versions (`gpt-4o-mini-2024-07-18`) take the price of the longest
+/// name they start with, and a router's `openai/` prefix is ignored.
+pub fn price_of(model: &str, prices: &BTreeMap<String, ModelPrice>) -> Option<ModelPrice> {
+    let configured = prices.iter().map(|(known, price)| (known.as_str(), *price));
+    let builtin = BUILTIN_PRICES.iter().map(|(known, input, output)| (*known, ModelPrice { input: *input, output: *output }));
+    best_match(model, configured).or_else(|| best_match(model, builtin))
+}
+
+/// Price of `model` itself, else of the longest name it starts with
+fn best_match<'a>(model: &str, table: impl Iterator<Item = (&'a str, ModelPrice)>) -> Option<ModelPrice> {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    table
+        .filter(|(known, _)| model == *known || name.starts_with(known))
+        .max_by_key(|(known, _)| if model == *known { usize::MAX } else { known.len() })
+        .map(|(_, price)| price)
+}
+
+/// Month of a Unix timestamp in UTC, e.g. `2026-10`
+pub fn month_of(seconds: u64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}", year, month)
+}
+
+/// The current month in UTC
+pub fn current_month() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    month_of(now)
+}
+
+/// Dollars spent per month, as a JSON object like `{"2026-10": 1.25}`
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    path: PathBuf,
+}
+
+impl Ledger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+    
+    /// Ledger in the user's state directory (the data directory where there is none)
+    pub fn open_default() -> Result<Self> {
+        let state_dir = dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .ok_or_else(|| GitAiError::FileSystem("Could not find state directory".to_string()))?
+            .join("git-ai-commit");
+        Ok(Self::new(state_dir.join("spend.json")))
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Dollars spent in every month with a charge; an unreadable ledger is empty
+    pub fn load(&self) -> BTreeMap<String, f64> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    /// Dollars spent in `month`
+    pub fn spent(&self, month: &str) -> f64 {
+        self.load().get(month).copied().unwrap_or(0.0)
+    }
+    
+    /// Add `dollars` to what `month` has spent
+    pub fn charge(&self, month: &str, dollars: f64) -> Result<()> {
+        let mut months = self.load();
+        *months.entry(month.to_string()).or_insert(0.0) += dollars;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| GitAiError::FileSystem(format!("Failed to create state directory: {}", e)))?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&months)?)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write {}: {}", self.path.display(), e)).into())
+    }
+}
+
+/// Spending of one run against the caps of `[cost]`
+/// 
+/// The default meter is for free backends: it prices nothing and never refuses.
+#[derive(Debug, Default)]
+pub struct CostMeter {
+    config: CostConfig,
+    /// Whether the backend charges for tokens at all
+    paid: bool,
+    /// Go over the caps instead of refusing (`--force`)
+    force: bool,
+    ledger: Option<Ledger>,
+    /// Read the ledger without charging to it (`--no-write`)
+    write_mode: WriteMode,
+    month: String,
+    /// Dollars spent this month before the run started
+    spent_before: f64,
+    /// Dollars spent by this run
+    spent: Mutex<f64>,
+}
+
+impl CostMeter {
+    /// Meter for a paid backend, charging to `ledger` if there is one
+    pub fn new(config: CostConfig, ledger: Option<Ledger>) -> Self {
+        let month = current_month();
+        let spent_before = ledger.as_ref().map_or(0.0, |ledger| ledger.spent(&month));
+        Self {
+            config,
+            paid: true,
+            force: false,
+            ledger,
+            write_mode: WriteMode::default(),
+            month,
+            spent_before,
+            spent: Mutex::new(0.0),
+        }
+    }
+    
+    /// Go over the caps instead of refusing
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+    
+    /// Leave the ledger as it is in read-only mode
+    pub fn with_write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
+    }
+    
+    /// Price of `model`, `None` on a free backend or for a model without one
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.paid.then(|| price_of(model, &self.config.prices)).flatten()
+    }
+    
+    /// Dollars a call of `model` with `prompt` will likely cost
+    pub fn estimate(&self, model: &str, prompt: &str) -> Option<f64> {
+        self.price(model).map(|price| price.cost(estimate_tokens(prompt), ANSWER_TOKENS))
+    }
+    
+    /// Dollars spent by this run so far
+    pub fn spent(&self) -> f64 {
+        *self.spent.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Fail if a call of `model` with `prompt` would likely go over a cap
+    pub fn check(&self, model: &str, prompt: &str) -> Result<()> {
+        let Some(estimate) = self.estimate(model, prompt).filter(|_| !self.force) else {
+            return Ok(());
+        };
+        let run = self.spent() + estimate;
+        if let Some(cap) = self.config.max_per_run.filter(|cap| run > *cap) {
+            return Err(GitAiError::Budget(format!(
+                "calling {} would bring this run to about ${:.4}, over cost.max_per_run of ${:.2}; pass --force to go over it",
+                model, run, cap
+            )).into());
+        }
+        let month = self.spent_before + run;
+        if let Some(cap) = self.config.monthly_budget.filter(|cap| month > *cap) {
+            return Err(GitAiError::Budget(format!(
+                "calling {} would bring {} to about ${:.4}, over cost.monthly_budget of ${:.2}; pass --force to go over it",
+                model, self.month, month, cap
+            )).into());
+        }
+        Ok(())
+    }
+    
+    /// Charge a call of `model`: the usage the API reported, or the estimate
+    /// for `prompt` when it reported none. Returns the dollars charged.
+    pub fn charge(&self, model: &str, prompt: &str, stats: &GenerationStats) -> Option<f64> {
+        let price = self.price(model)?;
+        let dollars = match (stats.prompt_eval_count, stats.eval_count) {
+            (None, None) => price.cost(estimate_tokens(prompt), ANSWER_TOKENS),
+            (prompt_tokens, generated_tokens) => price.cost(prompt_tokens.unwrap_or(0), generated_tokens.unwrap_or(0)),
+        };
+        *self.spent.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) += dollars;
+        if let Some(ledger) = self.ledger.as_ref().filter(|_| !self.write_mode.is_read_only()) {
+            if let Err(e) = ledger.charge(&self.month, dollars) {
+                tracing::warn!("Spending not recorded: {}", e);
+            }
+        }
+        Some(dollars)
+    }
+    
+    /// Spending for output, e.g. "$0.0012 this run, $1.25 of $20.00 in 2026-10";
+    /// `None` when `model` has no price
+    pub fn display(&self, model: &str) -> Option<String> {
+        self.price(model)?;
+        let mut parts = vec![format!("${:.4} this run", self.spent())];
+        if let Some(cap) = self.config.max_per_run {
+            parts[0].push_str(&format!(" (max ${:.2})", cap));
+        }
+        let month = self.spent_before + self.spent();
+        match self.config.monthly_budget {
+            Some(cap) => parts.push(format!("${:.2} of ${:.2} in {}", month, cap, self.month)),
+            None => parts.push(format!("${:.2} in {}", month, self.month)),
+        }
+        Some(parts.join(", "))
+    }
+}
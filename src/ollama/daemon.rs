@@ -0,0 +1,433 @@
+//! Registry of Ollama servers kept warm between runs
+//! 
+//! Servers are tracked per user rather than per repository or worktree, so
+//! every checkout on the machine shares the same warm server for a port.
+//! A registered process is only ever stopped while it is still the
+//! `ollama serve` for its port, since process ids are reused.
+
+use crate::ollama::host::DEFAULT_PORT;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use comfy_table::{presets, Table};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A server started by git-ai-commit and left running
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerEntry {
+    pub pid: u32,
+    pub port: u16,
+    /// Model the server last generated with
+    pub model: String,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    pub last_used: u64,
+}
+
+impl ServerEntry {
+    pub fn new(pid: u32, port: u16, model: &str) -> Self {
+        let now = now_secs();
+        Self {
+            pid,
+            port,
+            model: model.to_string(),
+            started_at: now,
+            last_used: now,
+        }
+    }
+    
+    /// How long the server has gone unused as of `now` (seconds since the epoch)
+    pub fn idle_for(&self, now: u64) -> Duration {
+        Duration::from_secs(now.saturating_sub(self.last_used))
+    }
+    
+    /// Resident memory of the server process, where the platform reports it
+    pub fn memory_bytes(&self) -> Option<u64> {
+        process_memory(self.pid)
+    }
+}
+
+/// Servers that are no longer wanted, as decided by `ServerRegistry::sweep`
+#[derive(Debug, Default)]
+pub struct Sweep {
+    /// Servers that were still running but idle for too long
+    pub expired: Vec<ServerEntry>,
+    /// Entries whose process had exited or is no longer the server
+    pub gone: Vec<ServerEntry>,
+}
+
+/// JSON file listing the warm servers of the current user
+#[derive(Debug, Clone)]
+pub struct ServerRegistry {
+    path: PathBuf,
+}
+
+impl ServerRegistry {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+    
+    /// Registry in the user's data directory
+    pub fn open_default() -> Result<Self> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| GitAiError::FileSystem("Could not find data directory".to_string()))?
+            .join("git-ai-commit");
+        Ok(Self::new(data_dir.join("servers.json")))
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// All registered servers; a missing or unreadable registry is empty
+    pub fn load(&self) -> Vec<ServerEntry> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    pub fn save(&self, entries: &[ServerEntry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| GitAiError::FileSystem(format!("Failed to create registry directory: {}", e)))?;
+        }
+        let json = serde_json::to_string_pretty(entries)?;
+        fs::write(&self.path, json)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write server registry: {}", e)))?;
+        Ok(())
+    }
+    
+    /// Add a server, replacing any previous entry for the same port
+    pub fn register(&self, entry: ServerEntry) -> Result<()> {
+        let mut entries = self.load();
+        entries.retain(|existing| existing.port != entry.port);
+        entries.push(entry);
+        self.save(&entries)
+    }
+    
+    /// Record that the server on `port` was just used with `model`
+    /// 
+    /// Servers that weren't started by git-ai-commit aren't registered and are left alone.
+    pub fn touch(&self, port: u16, model: &str) -> Result<()> {
+        let mut entries = self.load();
+        let Some(entry) = entries.iter_mut().find(|entry| entry.port == port) else {
+            return Ok(());
+        };
+        entry.last_used = now_secs();
+        if !model.is_empty() {
+            entry.model = model.to_string();
+        }
+        self.save(&entries)
+    }
+    
+    /// Remove the entry for `port`, returning it
+    pub fn remove(&self, port: u16) -> Result<Option<ServerEntry>> {
+        let mut entries = self.load();
+        let index = entries.iter().position(|entry| entry.port == port);
+        let removed = index.map(|index| entries.remove(index));
+        if removed.is_some() {
+            self.save(&entries)?;
+        }
+        Ok(removed)
+    }
+    
+    /// Drop entries whose process exited and those idle longer than `idle_timeout`
+    /// 
+    /// The server on `in_use`, which is about to be used again, never expires.
+    /// Only decides; the caller stops the expired servers (see `sweep_and_stop`).
+    pub fn sweep(
+        &self,
+        idle_timeout: Duration,
+        now: u64,
+        in_use: Option<u16>,
+        is_alive: impl Fn(&ServerEntry) -> bool,
+    ) -> Result<Sweep> {
+        let entries = self.load();
+        let mut sweep = Sweep::default();
+        let mut kept = Vec::new();
+        
+        for entry in entries {
+            if !is_alive(&entry) {
+                sweep.gone.push(entry);
+            } else if Some(entry.port) != in_use && entry.idle_for(now) > idle_timeout {
+                sweep.expired.push(entry);
+            } else {
+                kept.push(entry);
+            }
+        }
+        
+        if !sweep.expired.is_empty() || !sweep.gone.is_empty() {
+            self.save(&kept)?;
+        }
+        Ok(sweep)
+    }
+    
+    /// Shut down servers that have been idle too long and forget exited ones
+    /// 
+    /// An entry whose process id now belongs to another program is forgotten
+    /// without stopping anything.
+    pub fn sweep_and_stop(&self, idle_timeout: Duration, in_use: Option<u16>) -> Result<Vec<ServerEntry>> {
+        let sweep = self.sweep(idle_timeout, now_secs(), in_use, is_registered_server)?;
+        for entry in &sweep.expired {
+            if let Err(e) = stop_process(entry.pid) {
+                tracing::warn!("Failed to stop idle Ollama server on port {}: {}", entry.port, e);
+            }
+        }
+        Ok(sweep.expired)
+    }
+}
+
+/// How often the watchdog looks at the server it watches, at most
+const WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stop the server registered on `port` as `pid` once it has been idle for `idle_timeout`
+/// 
+/// Runs detached from the run that started the server (see `spawn_watchdog`),
+/// so an idle server goes away without waiting for the next run. Returns once
+/// the server is stopped, has exited, or was replaced by another one.
+pub fn watch(registry: &ServerRegistry, port: u16, pid: u32, idle_timeout: Duration) -> Result<()> {
+    loop {
+        let Some(entry) = registry.load().into_iter().find(|entry| entry.port == port && entry.pid == pid) else {
+            return Ok(());
+        };
+        if !is_registered_server(&entry) {
+            registry.remove(port)?;
+            return Ok(());
+        }
+        
+        let idle = entry.idle_for(now_secs());
+        if idle > idle_timeout {
+            stop_process(pid)?;
+            registry.remove(port)?;
+            return Ok(());
+        }
+        std::thread::sleep((idle_timeout - idle).min(WATCH_INTERVAL) + Duration::from_secs(1));
+    }
+}
+
+/// Start `daemon watch` for a server that was just registered
+/// 
+/// The watchdog is this program, so nothing is started when running as
+/// anything else (such as a test binary).
+pub fn spawn_watchdog(port: u16, pid: u32) -> Result<()> {
+    let exe = std::env::current_exe()
+        .map_err(|e| GitAiError::Ollama(format!("Failed to find the git-ai-commit executable: {}", e)))?;
+    if exe.file_stem().and_then(|stem| stem.to_str()) != Some(env!("CARGO_PKG_NAME")) {
+        return Ok(());
+    }
+    
+    let mut command = Command::new(exe);
+    command
+        .args(["daemon", "watch", "--port", &port.to_string(), "--pid", &pid.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Outlive the terminal the server was started from, like the server itself
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn()
+        .map_err(|e| GitAiError::Ollama(format!("Failed to start the idle watchdog: {}", e)))?;
+    Ok(())
+}
+
+/// Table of warm servers for `daemon list`
+pub fn server_table(entries: &[ServerEntry], now: u64) -> String {
+    let mut table = Table::new();
+    table.load_preset(presets::UTF8_FULL_CONDENSED);
+    table.set_header(["PID", "Port", "Model", "Memory", "Idle"]);
+    for entry in entries {
+        table.add_row([
+            entry.pid.to_string(),
+            entry.port.to_string(),
+            entry.model.clone(),
+            entry.memory_bytes().map(format_memory).unwrap_or_else(|| "-".to_string()),
+            format_idle(entry.idle_for(now)),
+        ]);
+    }
+    table.to_string()
+}
+
+/// Seconds since the Unix epoch
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Whether a process with this id is still running
+pub fn process_alive(pid: u32) -> bool {
+    if cfg!(windows) {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    } else {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+/// Whether `entry` still names the `ollama serve` it was registered for
+/// 
+/// The process must be alive, run Ollama's `serve`, and serve the entry's
+/// port; a process id reused by anything else never matches.
+pub fn is_registered_server(entry: &ServerEntry) -> bool {
+    process_alive(entry.pid) && runs_ollama_serve(entry.pid) && serves_port(entry.pid, entry.port)
+}
+
+/// Whether the arguments of a process are those of `ollama serve`
+pub fn is_ollama_serve(args: &[String]) -> bool {
+    let Some(program) = args.first() else {
+        return false;
+    };
+    let name = Path::new(program)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    name.starts_with("ollama") && args[1..].iter().any(|arg| arg == "serve")
+}
+
+/// Whether the process runs `ollama serve`; Windows only reports the image name
+fn runs_ollama_serve(pid: u32) -> bool {
+    if let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", pid)) {
+        let args: Vec<String> = cmdline.split(|byte| *byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        return is_ollama_serve(&args);
+    }
+    
+    if cfg!(windows) {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_lowercase().starts_with("\"ollama"))
+            .unwrap_or(false)
+    } else {
+        Command::new("ps")
+            .args(["-p", &pid.to_string(), "-o", "command="])
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| {
+                let command = String::from_utf8_lossy(&output.stdout);
+                is_ollama_serve(&command.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Whether the process serves `port`
+/// 
+/// On Linux this is the `OLLAMA_HOST` it was started with; elsewhere the
+/// port it listens on.
+fn serves_port(pid: u32, port: u16) -> bool {
+    if let Ok(environ) = fs::read(format!("/proc/{}/environ", pid)) {
+        let host = environ.split(|byte| *byte == 0)
+            .filter_map(|var| std::str::from_utf8(var).ok())
+            .find_map(|var| var.strip_prefix("OLLAMA_HOST="));
+        return host.map_or(Some(DEFAULT_PORT), host_port) == Some(port);
+    }
+    
+    if cfg!(windows) {
+        Command::new("netstat")
+            .args(["-ano", "-p", "TCP"])
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    fields.len() == 5
+                        && fields[3] == "LISTENING"
+                        && fields[4] == pid.to_string()
+                        && host_port(fields[1]) == Some(port)
+                })
+            })
+            .unwrap_or(false)
+    } else {
+        Command::new("lsof")
+            .args(["-nP", "-a", "-p", &pid.to_string(), &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| !output.stdout.trim_ascii().is_empty())
+            .unwrap_or(false)
+    }
+}
+
+/// Port of a `host:port` address, or of an `OLLAMA_HOST` without one
+fn host_port(address: &str) -> Option<u16> {
+    let address = address.rsplit("://").next().unwrap_or(address).trim_end_matches('/');
+    match address.rsplit_once(':') {
+        Some((_, port)) => port.parse().ok(),
+        None => Some(DEFAULT_PORT),
+    }
+}
+
+/// Ask a process to exit
+pub fn stop_process(pid: u32) -> Result<()> {
+    let status = if cfg!(windows) {
+        Command::new("taskkill").args(["/PID", &pid.to_string(), "/F"]).stdout(Stdio::null()).status()
+    } else {
+        Command::new("kill").arg(pid.to_string()).stderr(Stdio::null()).status()
+    };
+    
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(GitAiError::Ollama(format!("Process {} could not be stopped", pid)).into()),
+        Err(e) => Err(GitAiError::Ollama(format!("Failed to stop process {}: {}", pid, e)).into()),
+    }
+}
+
+/// Resident set size of a process; only available on Linux
+fn process_memory(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let kilobytes = status.lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Short human-readable duration, e.g. `3m 12s`
+pub fn format_idle(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Memory in the largest whole unit, e.g. `512 MB`
+pub fn format_memory(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit >= 3 {
+        format!("{:.1} {}", value, UNITS[unit])
+    } else {
+        format!("{:.0} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,297 @@
+//! In-process inference with candle
+//!
+//! The model is loaded on the first generation and kept for the rest of the
+//! run. Generation runs on a blocking thread; streamed text is handed back
+//! through a channel as each token is decoded.
+//!
+//! This uses candle rather than llama.cpp bindings (`llama-cpp-2`), which
+//! need a C++ toolchain and CMake at build time. Unlike llama.cpp, candle
+//! only runs the architectures it has quantized models for (see `Weights`),
+//! runs on the CPU only, doesn't use the tokenizer or chat template embedded
+//! in the GGUF file (hence `local.tokenizer_path` and `local.template`), and
+//! is slower than llama.cpp on the same hardware.
+
+use crate::config::LocalConfig;
+use crate::ollama::local::apply_template;
+use crate::ollama::{Capabilities, Generation, GenerationStats, OllamaClientTrait};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use candle_core::quantized::gguf_file;
+use candle_core::{DType, Device, Tensor};
+use candle_transformers::generation::LogitsProcessor;
+use candle_transformers::models::{quantized_gemma3, quantized_llama, quantized_qwen2, quantized_qwen3};
+use std::fs::File;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokenizers::Tokenizer;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Tokens that end a turn in common chat formats, besides the model's own end token
+const END_OF_TURN_TOKENS: &[&str] = &["<|eot_id|>", "<|im_end|>", "<end_of_turn>", "<|end|>", "<|endoftext|>"];
+
+/// Runs a GGUF model file on the CPU
+pub struct GgufClient {
+    config: LocalConfig,
+    max_tokens: u32,
+    loaded: Arc<Mutex<Option<LoadedModel>>>,
+}
+
+impl GgufClient {
+    pub fn new(config: LocalConfig, max_tokens: u32) -> Self {
+        Self { config, max_tokens, loaded: Arc::new(Mutex::new(None)) }
+    }
+    
+    fn name(&self) -> String {
+        self.config.model_name().unwrap_or_default().to_string()
+    }
+    
+    /// Generate on a blocking thread, sending each decoded piece to `pieces`
+    async fn run(&self, prompt: &str, pieces: Option<UnboundedSender<String>>) -> Result<Generation> {
+        let config = self.config.clone();
+        let loaded = Arc::clone(&self.loaded);
+        let max_tokens = self.max_tokens;
+        let prompt = apply_template(&config.template, prompt);
+        
+        tokio::task::spawn_blocking(move || {
+            let mut loaded = loaded.lock().map_err(|_| GitAiError::Provider("The local model crashed earlier in this run".to_string()))?;
+            if loaded.is_none() {
+                *loaded = Some(LoadedModel::load(&config)?);
+            }
+            let model = loaded.as_mut().expect("model was just loaded");
+            model.generate(&prompt, max_tokens, &config, pieces.as_ref())
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl OllamaClientTrait for GgufClient {
+    async fn is_running(&self) -> bool {
+        self.config.model_path.as_deref().is_some_and(Path::is_file)
+    }
+    
+    async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+        Ok(self.generate_with_stats(model, prompt).await?.text)
+    }
+    
+    async fn generate_with_stats(&self, _model: &str, prompt: &str) -> Result<Generation> {
+        self.run(prompt, None).await
+    }
+    
+    async fn generate_stream(
+        &self,
+        _model: &str,
+        prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let generation = self.run(prompt, Some(sender));
+        tokio::pin!(generation);
+        
+        loop {
+            tokio::select! {
+                Some(piece) = receiver.recv() => on_token(&piece),
+                result = &mut generation => {
+                    while let Ok(piece) = receiver.try_recv() {
+                        on_token(&piece);
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(vec![self.name()])
+    }
+    
+    async fn has_model(&self, model_name: &str) -> Result<bool> {
+        Ok(model_name == self.name())
+    }
+    
+    async fn pull_model(&self, model_name: &str) -> Result<()> {
+        Err(GitAiError::Provider(format!(
+            "Model '{}' can't be downloaded by the local provider; point local.model_path at a .gguf file",
+            model_name
+        )).into())
+    }
+    
+    async fn context_window(&self, _model_name: &str) -> Result<Option<u64>> {
+        let Some(path) = self.config.model_path.as_deref() else {
+            return Ok(None);
+        };
+        let content = read_header(path)?;
+        let length = architecture(&content)
+            .ok()
+            .and_then(|arch| content.metadata.get(&format!("{}.context_length", arch)))
+            .and_then(|value| value.to_u64().ok());
+        Ok(length)
+    }
+    
+    /// The prompt goes through `local.template` whole, and the model decides when to stop
+    async fn capabilities(&self, model_name: &str) -> Capabilities {
+        Capabilities {
+            streaming: true,
+            max_context: self.context_window(model_name).await.ok().flatten(),
+            ..Capabilities::BASIC
+        }
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        Ok(Some(self.name()))
+    }
+    
+    async fn delete_model(&self, model_name: &str) -> Result<()> {
+        Err(GitAiError::Provider(format!(
+            "Model '{}' is a file git-ai-commit doesn't manage; delete it yourself",
+            model_name
+        )).into())
+    }
+}
+
+/// Weights of one of the supported architectures
+enum Weights {
+    Llama(quantized_llama::ModelWeights),
+    Qwen2(quantized_qwen2::ModelWeights),
+    Qwen3(quantized_qwen3::ModelWeights),
+    Gemma3(quantized_gemma3::ModelWeights),
+}
+
+impl Weights {
+    fn forward(&mut self, input: &Tensor, position: usize) -> candle_core::Result<Tensor> {
+        match self {
+            Weights::Llama(model) => model.forward(input, position),
+            Weights::Qwen2(model) => model.forward(input, position),
+            Weights::Qwen3(model) => model.forward(input, position),
+            Weights::Gemma3(model) => model.forward(input, position),
+        }
+    }
+    
+    /// Forget the previous prompt; the others start over at position 0 by themselves
+    fn reset(&mut self) {
+        if let Weights::Qwen3(model) = self {
+            model.clear_kv_cache();
+        }
+    }
+}
+
+/// A loaded model with its tokenizer
+struct LoadedModel {
+    weights: Weights,
+    tokenizer: Tokenizer,
+    end_tokens: Vec<u32>,
+}
+
+impl LoadedModel {
+    fn load(config: &LocalConfig) -> Result<Self> {
+        let path = config.model_path.as_deref()
+            .ok_or_else(|| GitAiError::Config("Set local.model_path to a .gguf file".to_string()))?;
+        let cannot_load = |e: &dyn std::fmt::Display| GitAiError::Provider(format!("Cannot load {}: {}", path.display(), e));
+        
+        let started = Instant::now();
+        let mut file = File::open(path).map_err(|e| cannot_load(&e))?;
+        let content = gguf_file::Content::read(&mut file).map_err(|e| cannot_load(&e))?;
+        let arch = architecture(&content).map_err(|e| cannot_load(&e))?.to_string();
+        let mut end_tokens: Vec<u32> = content.metadata.get("tokenizer.ggml.eos_token_id")
+            .and_then(|value| value.to_u32().ok())
+            .into_iter()
+            .collect();
+        
+        let device = Device::Cpu;
+        let weights = match arch.as_str() {
+            "llama" => Weights::Llama(quantized_llama::ModelWeights::from_gguf(content, &mut file, &device).map_err(|e| cannot_load(&e))?),
+            "qwen2" => Weights::Qwen2(quantized_qwen2::ModelWeights::from_gguf(content, &mut file, &device).map_err(|e| cannot_load(&e))?),
+            "qwen3" => Weights::Qwen3(quantized_qwen3::ModelWeights::from_gguf(content, &mut file, &device).map_err(|e| cannot_load(&e))?),
+            "gemma3" => Weights::Gemma3(quantized_gemma3::ModelWeights::from_gguf(content, &mut file, &device).map_err(|e| cannot_load(&e))?),
+            other => return Err(GitAiError::Provider(format!(
+                "{} is a '{}' model; the local provider runs llama, qwen2, qwen3 and gemma3 models",
+                path.display(), other
+            )).into()),
+        };
+        
+        let tokenizer_path = config.tokenizer().unwrap_or_default();
+        let tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| GitAiError::Provider(format!("Cannot load {}: {}", tokenizer_path.display(), e)))?;
+        end_tokens.extend(END_OF_TURN_TOKENS.iter().filter_map(|token| tokenizer.token_to_id(token)));
+        
+        tracing::debug!("Loaded {} ({}) in {:.1}s", path.display(), arch, started.elapsed().as_secs_f64());
+        Ok(Self { weights, tokenizer, end_tokens })
+    }
+    
+    fn generate(
+        &mut self,
+        prompt: &str,
+        max_tokens: u32,
+        config: &LocalConfig,
+        pieces: Option<&UnboundedSender<String>>,
+    ) -> Result<Generation> {
+        let failed = |e: &dyn std::fmt::Display| GitAiError::Provider(format!("Local generation failed: {}", e));
+        let started = Instant::now();
+        let prompt_tokens = self.tokenizer.encode(prompt, true).map_err(|e| failed(&e))?.get_ids().to_vec();
+        if prompt_tokens.is_empty() {
+            return Err(GitAiError::Provider("The prompt is empty".to_string()).into());
+        }
+        
+        let temperature = (config.temperature > 0.0).then_some(config.temperature);
+        let mut sampler = LogitsProcessor::new(config.seed, temperature, None);
+        self.weights.reset();
+        
+        let mut input = prompt_tokens.clone();
+        let mut position = 0;
+        let mut answer: Vec<u32> = Vec::new();
+        let mut decoded = String::new();
+        let mut eval_started = None;
+        while answer.len() < max_tokens as usize {
+            let tokens = Tensor::new(input.as_slice(), &Device::Cpu).and_then(|t| t.unsqueeze(0)).map_err(|e| failed(&e))?;
+            let logits = self.weights.forward(&tokens, position)
+                .and_then(|logits| logits.flatten_all())
+                .and_then(|logits| logits.to_dtype(DType::F32))
+                .map_err(|e| failed(&e))?;
+            let next = sampler.sample(&logits).map_err(|e| failed(&e))?;
+            eval_started.get_or_insert_with(Instant::now);
+            if self.end_tokens.contains(&next) {
+                break;
+            }
+            
+            position += input.len();
+            input = vec![next];
+            answer.push(next);
+            if let Some(pieces) = pieces {
+                // Decode the whole answer so multi-token characters come out whole
+                let text = self.tokenizer.decode(&answer, true).map_err(|e| failed(&e))?;
+                if let Some(piece) = text.get(decoded.len()..).filter(|piece| !piece.is_empty()) {
+                    let _ = pieces.send(piece.to_string());
+                    decoded = text;
+                }
+            }
+        }
+        
+        let text = self.tokenizer.decode(&answer, true).map_err(|e| failed(&e))?;
+        let eval_duration = eval_started.map(|at| at.elapsed().as_nanos() as u64);
+        Ok(Generation {
+            text,
+            stats: GenerationStats {
+                prompt_eval_count: Some(prompt_tokens.len() as u64),
+                eval_count: Some(answer.len() as u64),
+                eval_duration,
+                total_duration: Some(started.elapsed().as_nanos() as u64),
+            },
+        })
+    }
+}
+
+/// Metadata of the GGUF file at `path`, without its tensors
+fn read_header(path: &Path) -> Result<gguf_file::Content> {
+    let mut file = File::open(path)?;
+    gguf_file::Content::read(&mut file)
+        .map_err(|e| GitAiError::Provider(format!("{} is not a GGUF file: {}", path.display(), e)).into())
+}
+
+fn architecture(content: &gguf_file::Content) -> candle_core::Result<&String> {
+    match content.metadata.get("general.architecture") {
+        Some(value) => value.to_string(),
+        None => candle_core::bail!("no general.architecture in the metadata"),
+    }
+}
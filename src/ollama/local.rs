@@ -0,0 +1,60 @@
+//! Generation from a GGUF model file, without any server
+//!
+//! Built with the `local-gguf` feature, `provider = "local"` loads the model
+//! at `local.model_path` in-process and generates on the CPU, for users who
+//! want no daemon at all. Without the feature the provider is refused with a
+//! hint to rebuild.
+
+use crate::config::LocalConfig;
+use crate::ollama::OllamaClientTrait;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::path::Path;
+
+/// Tokens generated when nothing caps the answer, enough for a subject and body
+pub const DEFAULT_MAX_TOKENS: u32 = 256;
+
+/// Where the prompt goes in `local.template`
+pub const PROMPT_PLACEHOLDER: &str = "{PROMPT}";
+
+/// The prompt in the model's chat format
+pub fn apply_template(template: &str, prompt: &str) -> String {
+    if template.contains(PROMPT_PLACEHOLDER) {
+        template.replace(PROMPT_PLACEHOLDER, prompt)
+    } else {
+        format!("{}{}", template, prompt)
+    }
+}
+
+/// Check that the model and tokenizer files exist, without loading them
+pub fn check_files(config: &LocalConfig) -> Result<()> {
+    let model = config.model_path.as_deref().ok_or_else(|| {
+        GitAiError::Config("Set local.model_path to a .gguf file to use the local provider".to_string())
+    })?;
+    for (path, key) in [(Some(model), "local.model_path"), (config.tokenizer().as_deref(), "local.tokenizer_path")] {
+        if let Some(path) = path.filter(|path| !path.is_file()) {
+            return Err(GitAiError::Config(format!("{} not found; check {}", path.display(), key)).into());
+        }
+    }
+    Ok(())
+}
+
+/// Client running the model `config` points at, capped at `max_tokens`
+#[cfg(feature = "local-gguf")]
+pub fn open(config: &LocalConfig, max_tokens: Option<u32>) -> Result<Box<dyn OllamaClientTrait + Send + Sync>> {
+    check_files(config)?;
+    Ok(Box::new(crate::ollama::gguf::GgufClient::new(config.clone(), max_tokens.unwrap_or(DEFAULT_MAX_TOKENS))))
+}
+
+/// Client running the model `config` points at, capped at `max_tokens`
+#[cfg(not(feature = "local-gguf"))]
+pub fn open(_config: &LocalConfig, _max_tokens: Option<u32>) -> Result<Box<dyn OllamaClientTrait + Send + Sync>> {
+    Err(GitAiError::Config(
+        "provider = \"local\" needs git-ai-commit built with --features local-gguf".to_string()
+    ).into())
+}
+
+/// Where a model file is, for messages
+pub fn describe(config: &LocalConfig) -> String {
+    config.model_path.as_deref().map_or_else(|| "no local.model_path".to_string(), |path: &Path| path.display().to_string())
+}
@@ -1,9 +1,24 @@
-use crate::ollama::{OllamaClient, OllamaBinary, OllamaClientTrait};
+use crate::config::{CapabilityOverrides, DaemonConfig, GenerationOptions, LocalConfig, OpenAiConfig, Provider, Redaction};
+use crate::ollama::budget::CallBudget;
+use crate::ollama::cost::CostMeter;
+use crate::ollama::daemon::{self, ServerEntry, ServerRegistry};
+use crate::ollama::host::OllamaHost;
+use crate::ollama::progress::PullBar;
+use crate::ollama::retry::RetryingClient;
+use crate::ollama::local;
+use crate::ollama::startup::{self, Backoff, ServerStart};
+use crate::ollama::{Capabilities, Generation, GenerationStats, OllamaClient, OllamaBinary, OllamaClientTrait, OpenAiClient};
+use crate::report::redact_prompt;
 use crate::utils::error::GitAiError;
+use crate::utils::WriteMode;
 use anyhow::Result;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
 use tokio::process::{Child, Command};
 
 /// Manages Ollama binary lifecycle and AI generation
@@ -13,6 +28,36 @@ pub struct OllamaManager {
     model: String,
     process: Option<Child>,
     port: u16,
+    /// Server requests go to; only one on this machine is started when down
+    host: OllamaHost,
+    write_mode: WriteMode,
+    daemon: DaemonConfig,
+    num_predict: Option<u32>,
+    /// Sampling options sent to Ollama
+    options: GenerationOptions,
+    /// Set when generating through an OpenAI-compatible API instead of Ollama
+    openai: Option<OpenAiConfig>,
+    /// Set when generating with a model file loaded in-process
+    local: Option<LocalConfig>,
+    budget: CallBudget,
+    /// Prices and spending caps of a paid API
+    cost: CostMeter,
+    /// Use only a server that already runs: never start one or download models
+    attach_only: bool,
+    /// Limit on each request to the backend, instead of the client's default
+    timeout: Option<Duration>,
+    /// Limit on each generation, from `timeout_seconds`
+    generation_timeout: Option<Duration>,
+    /// Times a call is tried again after a transient error
+    max_retries: u32,
+    /// Where the started server writes its output
+    server_log: Option<PathBuf>,
+    /// Capabilities the config asserts, over what the backend reports
+    overrides: CapabilityOverrides,
+    /// What is removed from every prompt before it is sent
+    redaction: Redaction,
+    /// Draw a progress bar while a model downloads
+    show_progress: bool,
 }
 
 impl OllamaManager {
@@ -26,13 +71,276 @@ impl OllamaManager {
             model,
             process: None,
             port,
+            host: OllamaHost::local(port),
+            write_mode: WriteMode::default(),
+            daemon: DaemonConfig::default(),
+            num_predict: None,
+            options: GenerationOptions::default(),
+            openai: None,
+            local: None,
+            budget: CallBudget::default(),
+            cost: CostMeter::default(),
+            attach_only: false,
+            timeout: None,
+            generation_timeout: None,
+            max_retries: 0,
+            server_log: None,
+            overrides: CapabilityOverrides::default(),
+            redaction: Redaction::default(),
+            show_progress: true,
         })
     }
     
+    /// Refuse to download missing models in read-only mode
+    pub fn with_write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
+    }
+    
+    /// Remove secrets (or more) from prompts before they are sent
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+    
+    /// Download models without a progress bar, e.g. for `--quiet` or `--json`
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+    
+    /// Refuse to call the model more than `budget` allows
+    pub fn with_call_budget(mut self, budget: CallBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+    
+    /// Model calls allowed in this run and how many were made
+    pub fn budget(&self) -> &CallBudget {
+        &self.budget
+    }
+    
+    /// Estimate and cap what generating costs, for a paid API
+    pub fn with_cost_meter(mut self, cost: CostMeter) -> Self {
+        self.cost = cost;
+        self
+    }
+    
+    /// What this run spent on a paid API
+    pub fn cost(&self) -> &CostMeter {
+        &self.cost
+    }
+    
+    /// Model messages are generated with
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+    
+    /// Generate with `model` from now on
+    pub fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+    
+    /// Context window of `model` in tokens, `None` when the backend doesn't say
+    pub async fn context_window(&self, model: &str) -> Option<u64> {
+        if let Some(max_context) = self.overrides.max_context {
+            return Some(max_context);
+        }
+        self.client.context_window(model).await.ok().flatten()
+    }
+    
+    /// Assume the backend has or lacks the features `overrides` sets, whatever it reports
+    pub fn with_capabilities(mut self, overrides: CapabilityOverrides) -> Self {
+        self.overrides = overrides;
+        self.client = self.build_client();
+        self
+    }
+    
+    /// What the backend offers for the current model, with the config's overrides applied
+    pub async fn capabilities(&self) -> Capabilities {
+        self.client.capabilities(&self.model).await.with_overrides(&self.overrides)
+    }
+    
+    /// Fail instead of starting a server or downloading a missing model
+    pub fn with_attach_only(mut self, attach_only: bool) -> Self {
+        self.attach_only = attach_only;
+        self
+    }
+    
+    /// Talk to the Ollama server at `host`; one on another machine is never started
+    pub fn with_host(mut self, host: OllamaHost) -> Self {
+        if !host.is_remote() {
+            self.port = host.port();
+        }
+        self.host = host;
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Give up on each request to the backend after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Give up on each generation after `timeout` with a timeout error
+    pub fn with_generation_timeout(mut self, timeout: Duration) -> Self {
+        self.generation_timeout = Some(timeout);
+        self
+    }
+    
+    /// How long a generation may take, `None` for no limit
+    pub fn generation_timeout(&self) -> Option<Duration> {
+        self.generation_timeout
+    }
+    
+    /// Give later generations `timeout`, e.g. more time after one timed out
+    pub fn set_generation_timeout(&mut self, timeout: Duration) {
+        self.generation_timeout = Some(timeout);
+    }
+    
+    /// Sample Ollama's answers with `options`; `with_num_predict` wins over their cap
+    pub fn with_options(mut self, options: GenerationOptions) -> Self {
+        self.options = options;
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Cap every generation at `num_predict` tokens
+    pub fn with_num_predict(mut self, num_predict: u32) -> Self {
+        self.num_predict = Some(num_predict);
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Generate through `provider`; for OpenAI-compatible APIs no local server is started
+    pub fn with_provider(mut self, provider: Provider, openai: &OpenAiConfig) -> Self {
+        self.openai = (provider == Provider::OpenAi).then(|| openai.clone());
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Generate with the model file in `local` when `provider` is local; no server is started
+    pub fn with_local(mut self, provider: Provider, local: &LocalConfig) -> Self {
+        self.local = (provider == Provider::Local).then(|| local.clone());
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Try backend calls again up to `max_retries` times after transient errors
+    pub fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self.client = self.build_client();
+        self
+    }
+    
+    /// Generate through `client` instead of the configured backend, e.g. the self-test's stub
+    pub fn with_client(mut self, client: Arc<dyn OllamaClientTrait + Send + Sync>) -> Self {
+        self.client = client;
+        self
+    }
+    
+    fn build_client(&self) -> Arc<dyn OllamaClientTrait + Send + Sync> {
+        // A model that can't be opened is reported by `ensure_running`
+        if let Some(Ok(client)) = self.local.as_ref().map(|local| local::open(local, self.num_predict)) {
+            return Arc::from(client);
+        }
+        let client: Arc<dyn OllamaClientTrait + Send + Sync> = match self.openai {
+            Some(ref openai) => {
+                let mut client = OpenAiClient::from_config(openai);
+                if let Some(max_tokens) = self.num_predict {
+                    client = client.with_max_tokens(max_tokens);
+                }
+                if let Some(timeout) = self.timeout {
+                    client = client.with_timeout(timeout);
+                }
+                Arc::new(client)
+            }
+            None => {
+                // Without system messages the prompt goes to `/api/generate` as written
+                let mut client = OllamaClient::for_host(&self.host)
+                    .with_chat(self.overrides.system_messages != Some(false))
+                    .with_options(self.options);
+                if let Some(num_predict) = self.num_predict {
+                    client = client.with_num_predict(num_predict);
+                }
+                if let Some(timeout) = self.timeout {
+                    client = client.with_timeout(timeout);
+                }
+                Arc::new(client)
+            }
+        };
+        // Only servers drop connections or get overloaded
+        if self.max_retries > 0 {
+            return Arc::new(RetryingClient::new(client, self.max_retries));
+        }
+        client
+    }
+    
+    /// Check that the OpenAI-compatible endpoint answers; there is nothing to start
+    async fn ensure_provider_reachable(&self, openai: &OpenAiConfig) -> Result<()> {
+        if !self.client.is_running().await {
+            return Err(GitAiError::Provider(format!(
+                "Cannot reach {}; check `openai.base_url` and the API key in ${}",
+                openai.base_url, openai.api_key_env
+            )).into());
+        }
+        if self.model.is_empty() {
+            return Err(GitAiError::Config("Set a model to use with the openai provider".to_string()).into());
+        }
+        Ok(())
+    }
+    
+    /// Keep started servers warm and shut down idle ones as configured
+    pub fn with_daemon(mut self, daemon: DaemonConfig) -> Self {
+        self.daemon = daemon;
+        self
+    }
+    
     /// Ensure Ollama is running and ready to accept requests
     pub async fn ensure_running(&mut self) -> Result<()> {
+        if let Some(ref openai) = self.openai {
+            return self.ensure_provider_reachable(openai).await;
+        }
+        if let Some(ref local) = self.local {
+            self.client = Arc::from(local::open(local, self.num_predict)?);
+            return Ok(());
+        }
+        
+        if self.host.is_remote() {
+            if self.client.is_running().await {
+                return Ok(());
+            }
+            return Err(GitAiError::Ollama(format!(
+                "Cannot reach the Ollama server at {}; check --host or OLLAMA_HOST",
+                self.host
+            )).into());
+        }
+        
+        if self.attach_only {
+            if self.client.is_running().await {
+                return Ok(());
+            }
+            return Err(GitAiError::Ollama(format!(
+                "No Ollama server is running on port {}; --ci never starts one",
+                self.port
+            )).into());
+        }
+        
+        // Sweeping stops servers and rewrites the registry, which --no-write rules out
+        let registry = ServerRegistry::open_default().ok().filter(|_| !self.write_mode.is_read_only());
+        if let Some(ref registry) = registry {
+            if let Err(e) = registry.sweep_and_stop(self.daemon.idle_timeout(), Some(self.port)) {
+                tracing::warn!("{}", e);
+            }
+        }
+        
         // Check if Ollama is already running
         if self.client.is_running().await {
+            if let Some(ref registry) = registry {
+                let _ = registry.touch(self.port, &self.model);
+            }
             return Ok(());
         }
         
@@ -41,13 +349,13 @@ impl OllamaManager {
         self.start_ollama_server(&binary_path).await?;
         
         // Wait for server to be ready
-        self.wait_for_server().await?;
+        self.wait_for_server(&binary_path).await?;
         
         // If no model is specified, try to use the last available model
         if self.model.is_empty() {
             if let Ok(models) = self.client.list_models().await {
                 if let Some(last_model) = models.last() {
-                    println!("[INFO] No model specified, using last available model: {}", last_model);
+                    tracing::info!("No model specified, using last available model: {}", last_model);
                     self.model = last_model.clone();
                 }
             }
@@ -60,52 +368,225 @@ impl OllamaManager {
         // Ensure default model is available
         self.ensure_default_model_available().await?;
         
+        if self.daemon.keep_warm {
+            self.keep_warm(registry.as_ref());
+        }
+        
         Ok(())
     }
     
+    /// Detach the started server so it outlives this run, and register it
+    fn keep_warm(&mut self, registry: Option<&ServerRegistry>) {
+        let (Some(registry), Some(process)) = (registry, self.process.as_ref()) else {
+            return;
+        };
+        let Some(pid) = process.id() else {
+            return;
+        };
+        
+        match registry.register(ServerEntry::new(pid, self.port, &self.model)) {
+            Ok(()) => {
+                // Dropping the handle leaves the process running
+                self.process = None;
+                if let Err(e) = daemon::spawn_watchdog(self.port, pid) {
+                    tracing::warn!("{}; idle servers are stopped on the next run instead", e);
+                }
+            }
+            Err(e) => tracing::warn!("Not keeping the Ollama server warm: {}", e),
+        }
+    }
+    
     /// Generate a commit message using the AI model
     pub async fn generate_commit(&self, prompt: &str) -> Result<String> {
-        self.client
-            .generate(&self.model, prompt)
-            .await
-            .map_err(|e| GitAiError::Ollama(format!("Failed to generate commit message: {}", e)).into())
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        self.cost.check(&self.model, prompt)?;
+        self.budget.spend()?;
+        let text = within(self.generation_timeout, &self.model, self.client.generate(&self.model, prompt))
+            .await?
+            .map_err(|e| GitAiError::Ollama(format!("Failed to generate commit message: {}", e)))?;
+        self.cost.charge(&self.model, prompt, &GenerationStats::default());
+        Ok(text)
+    }
+    
+    /// Generate a commit message along with the token usage reported for it
+    pub async fn generate_commit_with_stats(&self, prompt: &str) -> Result<Generation> {
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        self.cost.check(&self.model, prompt)?;
+        self.budget.spend()?;
+        let generation = within(self.generation_timeout, &self.model, self.client.generate_with_stats(&self.model, prompt))
+            .await?
+            .map_err(|e| GitAiError::Ollama(format!("Failed to generate commit message: {}", e)))?;
+        self.cost.charge(&self.model, prompt, &generation.stats);
+        Ok(generation)
+    }
+    
+    /// Generate a commit message as JSON constrained to `format`; see [`OllamaClientTrait::generate_json`]
+    pub async fn generate_commit_json(&self, prompt: &str, format: &serde_json::Value) -> Result<Generation> {
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        self.cost.check(&self.model, prompt)?;
+        self.budget.spend()?;
+        let generation = within(self.generation_timeout, &self.model, self.client.generate_json(&self.model, prompt, format))
+            .await?
+            .map_err(|e| GitAiError::Ollama(format!("Failed to generate commit message: {}", e)))?;
+        self.cost.charge(&self.model, prompt, &generation.stats);
+        Ok(generation)
+    }
+    
+    /// Generate a commit message, passing each token to `on_token` as it arrives
+    pub async fn generate_commit_stream(
+        &self,
+        prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        self.cost.check(&self.model, prompt)?;
+        self.budget.spend()?;
+        let generation = within(self.generation_timeout, &self.model, self.client.generate_stream(&self.model, prompt, on_token))
+            .await?
+            .map_err(|e| GitAiError::Ollama(format!("Failed to generate commit message: {}", e)))?;
+        self.cost.charge(&self.model, prompt, &generation.stats);
+        Ok(generation)
+    }
+    
+    /// Generate with several models concurrently
+    /// 
+    /// Results are returned in the order of `models`, each with its own outcome
+    /// so one failing model doesn't discard the others. Models past the end of
+    /// the call budget, or over the cost caps, fail without being asked.
+    pub async fn generate_with_models(&self, models: &[String], prompt: &str) -> Vec<(String, Result<Generation>)> {
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        let mut results = Vec::with_capacity(models.len());
+        let mut tasks = JoinSet::new();
+        for (index, model) in models.iter().enumerate() {
+            if let Err(e) = self.cost.check(model, prompt).and_then(|()| self.budget.spend()) {
+                results.push((index, model.clone(), Err(e)));
+                continue;
+            }
+            let client = Arc::clone(&self.client);
+            let model = model.clone();
+            let prompt = prompt.to_string();
+            let timeout = self.generation_timeout;
+            tasks.spawn(async move {
+                let result = within(timeout, &model, client.generate_with_stats(&model, &prompt)).await.and_then(|result| result);
+                (index, model, result)
+            });
+        }
+        
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok(result) => results.push(result),
+                Err(e) => results.push((usize::MAX, String::new(), Err(GitAiError::Ollama(e.to_string()).into()))),
+            }
+        }
+        results.sort_by_key(|(index, _, _)| *index);
+        for (_, model, result) in &results {
+            if let Ok(generation) = result {
+                self.cost.charge(model, prompt, &generation.stats);
+            }
+        }
+        results.into_iter().map(|(_, model, result)| (model, result)).collect()
+    }
+    
+    /// Generate with a specific model instead of the default one
+    pub async fn generate_with_model(&self, model: &str, prompt: &str) -> Result<Generation> {
+        let prompt = &*redact_prompt(prompt, self.redaction);
+        self.cost.check(model, prompt)?;
+        self.budget.spend()?;
+        let generation = within(self.generation_timeout, model, self.client.generate_with_stats(model, prompt))
+            .await?
+            .map_err(|e| GitAiError::Ollama(format!("Failed to generate with '{}': {}", model, e)))?;
+        self.cost.charge(model, prompt, &generation.stats);
+        Ok(generation)
     }
     
     async fn start_ollama_server(&mut self, binary_path: &PathBuf) -> Result<()> {
         let mut cmd = Command::new(binary_path);
+        if self.daemon.keep_warm {
+            // Unload the model from memory once the server goes idle
+            cmd.env("OLLAMA_KEEP_ALIVE", format!("{}m", self.daemon.idle_timeout_minutes));
+        }
         cmd.arg("serve")
            .env("OLLAMA_HOST", format!("0.0.0.0:{}", self.port))
-           .stdin(Stdio::null())
-           .stdout(Stdio::null())
-           .stderr(Stdio::null());
+           .stdin(Stdio::null());
+        
+        // The server's output explains why it didn't come up, if it doesn't;
+        // with --no-write it goes nowhere
+        let log_path = startup::server_log_path(self.port);
+        let log = log_path.parent()
+            .filter(|_| !self.write_mode.is_read_only())
+            .and_then(|dir| fs::create_dir_all(dir).ok())
+            .and_then(|_| File::create(&log_path).ok());
+        match log.and_then(|file| Some((file.try_clone().ok()?, file))) {
+            Some((stdout, stderr)) => {
+                cmd.stdout(stdout).stderr(stderr);
+                self.server_log = Some(log_path);
+            }
+            None => {
+                cmd.stdout(Stdio::null()).stderr(Stdio::null());
+                self.server_log = None;
+            }
+        }
         
         let child = cmd.spawn()
-            .map_err(|e| GitAiError::Ollama(format!("Failed to start Ollama: {}", e)))?;
+            .map_err(|e| GitAiError::Ollama(format!("Failed to start Ollama from {}: {}", binary_path.display(), e)))?;
         
         self.process = Some(child);
         Ok(())
     }
     
-    async fn wait_for_server(&self) -> Result<()> {
-        let max_attempts = 30;
-        let delay = std::time::Duration::from_secs(1);
-        
-        for _ in 0..max_attempts {
-            if self.client.is_running().await {
-                return Ok(());
-            }
-            tokio::time::sleep(delay).await;
+    async fn wait_for_server(&mut self, binary_path: &Path) -> Result<()> {
+        let Some(process) = self.process.as_mut() else {
+            return Ok(());
+        };
+        let start = ServerStart {
+            binary: binary_path,
+            port: self.port,
+            log: self.server_log.as_deref(),
+        };
+        let backoff = Backoff::new(uuid::Uuid::new_v4().as_u128() as u64);
+        let waited = startup::wait_for_server(&*self.client, process, &start, self.daemon.start_timeout(), backoff).await;
+        if waited.is_err() {
+            self.process = None;
         }
-        
-        Err(GitAiError::Ollama("Timed out waiting for Ollama server to start".to_string()).into())
+        waited
     }
     
     /// Ensure the specified model is available, downloading it if necessary
     pub async fn ensure_model_available(&self, model_name: &str) -> Result<()> {
+        // Hosted APIs can't download models, and some don't list them at all
+        if let Some(ref openai) = self.openai {
+            return match self.client.has_model(model_name).await {
+                Ok(false) => Err(GitAiError::Provider(format!(
+                    "Model '{}' is not available at {}",
+                    model_name, openai.base_url
+                )).into()),
+                _ => Ok(()),
+            };
+        }
+        if let Some(ref local) = self.local {
+            if !self.client.has_model(model_name).await? {
+                return Err(GitAiError::Config(format!(
+                    "Model '{}' is not {}; the local provider only runs local.model_path",
+                    model_name, local::describe(local)
+                )).into());
+            }
+            return Ok(());
+        }
+        
         if !self.client.has_model(model_name).await? {
-            println!("[DOWN] Model '{}' not found. Downloading...", model_name);
-            self.client.pull_model(model_name).await?;
-            println!("[ OK ] Successfully downloaded model '{}'", model_name);
+            if self.attach_only {
+                return Err(GitAiError::Ollama(format!(
+                    "Model '{}' is not installed; --ci never downloads models",
+                    model_name
+                )).into());
+            }
+            self.write_mode.check(&format!("downloading the missing model '{}'", model_name))?;
+            tracing::info!("Model '{}' not found. Downloading...", model_name);
+            let mut bar = if self.show_progress { PullBar::new() } else { PullBar::hidden() };
+            let pulled = self.client.pull_model_with_progress(model_name, &mut |progress| bar.update(progress)).await;
+            bar.finish();
+            pulled?;
+            tracing::info!("Successfully downloaded model '{}'", model_name);
         }
         Ok(())
     }
@@ -124,3 +605,20 @@ impl Drop for OllamaManager {
         }
     }
 }
+
+/// Wait at most `limit` for `generation` by `model`
+/// 
+/// The outer error is the timeout, so the backend's own errors can still be
+/// told apart from it.
+async fn within<T>(limit: Option<Duration>, model: &str, generation: impl Future<Output = Result<T>>) -> Result<Result<T>> {
+    let Some(limit) = limit else {
+        return Ok(generation.await);
+    };
+    tokio::time::timeout(limit, generation).await.map_err(|_| {
+        GitAiError::Timeout(format!(
+            "'{}' gave no answer within {:?}; raise --timeout-seconds or `timeout_seconds` in the config",
+            model,
+            limit
+        )).into()
+    })
+}
@@ -4,9 +4,24 @@ use async_trait::async_trait;
 use anyhow::Result;
 
 pub mod manager;
+pub mod budget;
+pub mod capabilities;
+pub mod chat;
 pub mod client;
+pub mod context;
+pub mod cost;
 pub mod binary;
 pub mod model_manager;
+pub mod daemon;
+pub mod preflight;
+pub mod host;
+pub mod retry;
+pub mod progress;
+pub mod startup;
+pub mod openai;
+pub mod local;
+#[cfg(feature = "local-gguf")]
+pub mod gguf;
 
 #[cfg(test)]
 mod client_test;
@@ -15,10 +30,73 @@ mod client_test;
 pub trait OllamaClientTrait: Send + Sync {
     async fn is_running(&self) -> bool;
     async fn generate(&self, model: &str, prompt: &str) -> Result<String>;
+    
+    /// Generate text and return the token counts and timings reported with it
+    /// 
+    /// Backends that don't report usage can rely on the default, which
+    /// returns empty stats.
+    async fn generate_with_stats(&self, model: &str, prompt: &str) -> Result<Generation> {
+        let text = self.generate(model, prompt).await?;
+        Ok(Generation { text, stats: GenerationStats::default() })
+    }
+    
+    /// Generate text, passing each token to `on_token` as it arrives
+    /// 
+    /// Backends that can't stream rely on the default, which passes the whole
+    /// text at once when it is done.
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        let generation = self.generate_with_stats(model, prompt).await?;
+        on_token(&generation.text);
+        Ok(generation)
+    }
+    
+    /// Generate an answer constrained to `format`, a JSON schema or `"json"`
+    /// 
+    /// Backends that can't constrain answers rely on the default, which
+    /// generates without it, so the prompt has to ask for JSON as well.
+    async fn generate_json(&self, model: &str, prompt: &str, _format: &serde_json::Value) -> Result<Generation> {
+        self.generate_with_stats(model, prompt).await
+    }
     async fn list_models(&self) -> Result<Vec<String>>;
     async fn has_model(&self, model_name: &str) -> Result<bool>;
     async fn pull_model(&self, model_name: &str) -> Result<()>;
     
+    /// Download a model, passing each progress update to `on_progress`
+    /// 
+    /// Backends that can't report progress rely on the default, which
+    /// downloads without any.
+    async fn pull_model_with_progress(
+        &self,
+        model_name: &str,
+        _on_progress: &mut (dyn for<'p> FnMut(&'p PullProgress) + Send),
+    ) -> Result<()> {
+        self.pull_model(model_name).await
+    }
+    
+    /// Tokens of prompt and answer `model_name` reads before the beginning is cut off
+    /// 
+    /// Backends that don't say rely on the default, `None`, and prompts
+    /// aren't checked against a window.
+    async fn context_window(&self, _model_name: &str) -> Result<Option<u64>> {
+        Ok(None)
+    }
+    
+    /// What the backend offers for `model_name`
+    /// 
+    /// Backends that can't say rely on the default: plain completions, with
+    /// the context window when it is known.
+    async fn capabilities(&self, model_name: &str) -> Capabilities {
+        Capabilities {
+            max_context: self.context_window(model_name).await.ok().flatten(),
+            ..Capabilities::BASIC
+        }
+    }
+    
     /// Get the last available model from the list of installed models
     /// Returns None if no models are installed
     async fn get_last_model(&self) -> Result<Option<String>>;
@@ -35,6 +113,9 @@ pub trait OllamaClientTrait: Send + Sync {
 }
 
 pub use manager::OllamaManager;
-pub use client::OllamaClient;
+pub use client::{DEFAULT_NUM_CTX, Generation, GenerationStats, ModelDetails, OllamaClient, PullDecoder, PullProgress, StreamDecoder};
+pub use openai::OpenAiClient;
 pub use binary::OllamaBinary;
+pub use capabilities::Capabilities;
 pub use model_manager::ModelManager;
+pub use daemon::{ServerEntry, ServerRegistry};
@@ -11,6 +11,12 @@ pub struct ModelInfo {
 
 pub struct ModelManager;
 
+impl Default for ModelManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModelManager {
     pub fn new() -> Self {
         ModelManager
@@ -87,9 +93,9 @@ impl ModelManager {
     /// Ensure a model is available, pulling it if necessary
     pub fn ensure_model_available(&self, model_name: &str) -> Result<()> {
         if !self.has_model(model_name)? {
-            println!("Model '{}' not found. Downloading...", model_name);
+            tracing::info!("Model '{}' not found. Downloading...", model_name);
             self.pull_model(model_name)?;
-            println!("Successfully downloaded model '{}'", model_name);
+            tracing::info!("Successfully downloaded model '{}'", model_name);
         }
         Ok(())
     }
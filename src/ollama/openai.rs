@@ -0,0 +1,258 @@
+//! Client for OpenAI-compatible chat completion APIs
+//! 
+//! Works with OpenAI itself, hosted services such as OpenRouter and Groq, and
+//! local servers that expose the same API, such as LM Studio, vLLM, the
+//! llama.cpp server and llamafile. Models are managed by the service, so
+//! pulling and deleting them is not supported.
+
+use crate::config::OpenAiConfig;
+use crate::ollama::client::REQUEST_TIMEOUT;
+use crate::ollama::retry;
+use crate::ollama::{Capabilities, Generation, GenerationStats, OllamaClientTrait};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// HTTP client for an OpenAI-compatible `/chat/completions` endpoint
+#[derive(Clone)]
+pub struct OpenAiClient {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    headers: Vec<(String, String)>,
+    max_tokens: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    prompt_tokens: Option<u64>,
+    #[serde(default)]
+    completion_tokens: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+impl OpenAiClient {
+    /// Client for the API under `base_url` (e.g. `https://api.openai.com/v1`)
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        let client = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client");
+        
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            headers: Vec::new(),
+            max_tokens: None,
+        }
+    }
+    
+    /// Client for the API an `[openai]` section describes, with its key and headers
+    pub fn from_config(config: &OpenAiConfig) -> Self {
+        Self::new(&config.base_url, config.api_key()).with_headers(&config.headers)
+    }
+    
+    /// Send these headers with every request
+    pub fn with_headers(mut self, headers: &BTreeMap<String, String>) -> Self {
+        self.headers = headers.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        self
+    }
+    
+    /// Give up on requests after `timeout` instead of the default five minutes
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+        self
+    }
+    
+    /// Stop generating after this many tokens
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+    
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    
+    /// Add the API key and the configured headers
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = self.headers.iter().fold(request, |request, (name, value)| request.header(name, value));
+        match self.api_key {
+            Some(ref key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+    
+    /// One chat completion; `response_format` asks for JSON
+    async fn complete(&self, model: &str, prompt: &str, response_format: Option<serde_json::Value>) -> Result<Generation> {
+        let url = format!("{}/chat/completions", self.base_url);
+        
+        let mut payload = json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "temperature": 0.7,
+            "top_p": 0.9,
+        });
+        if let Some(max_tokens) = self.max_tokens {
+            payload["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(response_format) = response_format {
+            payload["response_format"] = response_format;
+        }
+        
+        let response = self.authorized(self.client.post(&url))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| retry::with_source(GitAiError::Provider(format!("Failed to send request: {}", e)), e))?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(retry::with_status(GitAiError::Provider(format!("Request failed with status {}: {}", status, text)), status));
+        }
+        
+        let chat: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Provider(format!("Failed to parse response: {}", e)))?;
+        
+        let text = chat.choices.into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| GitAiError::Provider("Response contained no message".to_string()))?;
+        let stats = chat.usage
+            .map(|usage| GenerationStats {
+                prompt_eval_count: usage.prompt_tokens,
+                eval_count: usage.completion_tokens,
+                ..GenerationStats::default()
+            })
+            .unwrap_or_default();
+        
+        Ok(Generation { text, stats })
+    }
+}
+
+#[async_trait]
+impl OllamaClientTrait for OpenAiClient {
+    /// Some compatible servers have no `/models`; their 404 or 405 still
+    /// means the API is up
+    async fn is_running(&self) -> bool {
+        let url = format!("{}/models", self.base_url);
+        self.authorized(self.client.get(&url))
+            .send()
+            .await
+            .is_ok_and(|response| {
+                let status = response.status();
+                status.is_success() || status == StatusCode::NOT_FOUND || status == StatusCode::METHOD_NOT_ALLOWED
+            })
+    }
+    
+    async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+        Ok(self.generate_with_stats(model, prompt).await?.text)
+    }
+    
+    async fn generate_with_stats(&self, model: &str, prompt: &str) -> Result<Generation> {
+        self.complete(model, prompt, None).await
+    }
+    
+    /// OpenAI's JSON mode only promises valid JSON, not the schema
+    async fn generate_json(&self, model: &str, prompt: &str, _format: &serde_json::Value) -> Result<Generation> {
+        self.complete(model, prompt, Some(json!({ "type": "json_object" }))).await
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let url = format!("{}/models", self.base_url);
+        
+        let response = self.authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| retry::with_source(GitAiError::Provider(format!("Failed to get models: {}", e)), e))?;
+        
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(retry::with_status(GitAiError::Provider(format!("Failed to fetch models: {}", status)), status));
+        }
+        
+        let models: ModelsResponse = response
+            .json()
+            .await
+            .map_err(|e| GitAiError::Provider(format!("Failed to parse models response: {}", e)))?;
+        
+        Ok(models.data.into_iter().map(|m| m.id).collect())
+    }
+    
+    async fn has_model(&self, model_name: &str) -> Result<bool> {
+        let models = self.list_models().await?;
+        Ok(models.iter().any(|m| m == model_name))
+    }
+    
+    async fn pull_model(&self, model_name: &str) -> Result<()> {
+        Err(GitAiError::Provider(format!(
+            "Model '{}' is not available at {} and can't be downloaded through this API",
+            model_name, self.base_url
+        )).into())
+    }
+    
+    /// Every compatible server takes a system message; the rest is only
+    /// assumed of OpenAI itself, since many compatible servers lack it
+    async fn capabilities(&self, _model_name: &str) -> Capabilities {
+        let official = self.base_url.starts_with("https://api.openai.com/");
+        Capabilities {
+            system_messages: true,
+            json_mode: official,
+            json_schema: official,
+            embeddings: official,
+            ..Capabilities::BASIC
+        }
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        let models = self.list_models().await?;
+        Ok(models.last().cloned())
+    }
+    
+    async fn delete_model(&self, model_name: &str) -> Result<()> {
+        Err(GitAiError::Provider(format!(
+            "Model '{}' is managed by {}, not by git-ai-commit",
+            model_name, self.base_url
+        )).into())
+    }
+}
@@ -0,0 +1,115 @@
+//! Startup check of the configured backends
+//! 
+//! Every backend is pinged at once with a short timeout, so a dead primary
+//! server costs one timeout instead of a slow failure at generation time.
+
+use crate::config::Backend;
+use crate::ollama::{OllamaClient, OllamaClientTrait};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+/// How long a backend has to answer before it is skipped
+pub const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Why a backend was passed over
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The server didn't answer in time
+    Unreachable(String),
+    /// The server answered but doesn't have the model installed
+    MissingModel,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Unreachable(error) => write!(f, "server unreachable ({})", error),
+            SkipReason::MissingModel => write!(f, "model not installed"),
+        }
+    }
+}
+
+/// Outcome of pinging every backend
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    /// Highest-priority backend that is up with its model installed
+    pub selected: Option<Backend>,
+    /// Backends ahead of the selected one (or all of them) and why they were skipped
+    pub skipped: Vec<(Backend, SkipReason)>,
+}
+
+impl Preflight {
+    /// Ping `backends` in parallel and pick the first healthy one in list order
+    pub async fn run(backends: &[Backend], timeout: Duration) -> Self {
+        let mut tasks = JoinSet::new();
+        for (index, backend) in backends.iter().enumerate() {
+            let backend = backend.clone();
+            tasks.spawn(async move {
+                let health = check(&backend, timeout).await;
+                (index, health)
+            });
+        }
+        
+        let mut results = Vec::with_capacity(backends.len());
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(result) = joined {
+                results.push(result);
+            }
+        }
+        
+        Self::select(backends, results)
+    }
+    
+    /// Pick the first healthy backend from per-backend results
+    /// 
+    /// Backends without a result (e.g. a panicked check) count as unreachable.
+    pub fn select(
+        backends: &[Backend],
+        results: impl IntoIterator<Item = (usize, Result<(), SkipReason>)>,
+    ) -> Self {
+        let mut health: Vec<Result<(), SkipReason>> =
+            vec![Err(SkipReason::Unreachable("no answer".to_string())); backends.len()];
+        for (index, result) in results {
+            if let Some(slot) = health.get_mut(index) {
+                *slot = result;
+            }
+        }
+        
+        let mut preflight = Self::default();
+        for (backend, result) in backends.iter().zip(health) {
+            match result {
+                Ok(()) => {
+                    preflight.selected = Some(backend.clone());
+                    break;
+                }
+                Err(reason) => preflight.skipped.push((backend.clone(), reason)),
+            }
+        }
+        preflight
+    }
+}
+
+/// Whether the backend's server answers and has its model
+async fn check(backend: &Backend, timeout: Duration) -> Result<(), SkipReason> {
+    let client = OllamaClient::new(backend.port).with_timeout(timeout);
+    let models = client.list_models().await
+        .map_err(|e| SkipReason::Unreachable(e.to_string()))?;
+    
+    if model_installed(&models, &backend.model) {
+        Ok(())
+    } else {
+        Err(SkipReason::MissingModel)
+    }
+}
+
+/// Whether `model` is in the list, treating an untagged name as `:latest`
+/// 
+/// An empty model matches any installed one, since the manager then picks one itself.
+pub fn model_installed(models: &[String], model: &str) -> bool {
+    if model.is_empty() {
+        return !models.is_empty();
+    }
+    models.iter().any(|installed| {
+        installed == model || (!model.contains(':') && *installed == format!("{}:latest", model))
+    })
+}
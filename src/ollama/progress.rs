@@ -0,0 +1,83 @@
+//! Progress bar for model downloads
+//!
+//! A pull reports each layer of the model in turn. The bar follows the layer
+//! being downloaded and numbers it, e.g. "layer 2"; the steps before and
+//! after (fetching the manifest, verifying digests) show as a message only.
+
+use crate::ollama::PullProgress;
+use indicatif::{ProgressBar, ProgressStyle};
+
+const TEMPLATE: &str = "{msg:<24} [{bar:30}] {bytes}/{total_bytes} ({percent}%, {eta})";
+
+/// Renders the progress of one pull on stderr
+pub struct PullBar {
+    bar: ProgressBar,
+    /// Digests of the layers seen so far, in download order
+    layers: Vec<String>,
+    /// Index in `layers` of the one the bar shows
+    current: Option<usize>,
+}
+
+impl PullBar {
+    /// A bar on stderr, drawn only when stderr is a terminal
+    pub fn new() -> Self {
+        Self::with_bar(ProgressBar::new(0))
+    }
+    
+    /// A bar that tracks progress without drawing anything
+    pub fn hidden() -> Self {
+        Self::with_bar(ProgressBar::hidden())
+    }
+    
+    fn with_bar(bar: ProgressBar) -> Self {
+        bar.set_style(
+            ProgressStyle::with_template(TEMPLATE)
+                .expect("the progress template is valid")
+                .progress_chars("=> "),
+        );
+        Self { bar, layers: Vec::new(), current: None }
+    }
+    
+    pub fn update(&mut self, progress: &PullProgress) {
+        let (Some(digest), Some(total)) = (&progress.digest, progress.total) else {
+            self.bar.set_message(progress.status.clone());
+            return;
+        };
+        
+        let layer = match self.layers.iter().position(|layer| layer == digest) {
+            Some(index) => index,
+            None => {
+                self.layers.push(digest.clone());
+                self.layers.len() - 1
+            }
+        };
+        if self.current != Some(layer) {
+            self.current = Some(layer);
+            self.bar.reset();
+            self.bar.set_length(total);
+        }
+        self.bar.set_position(progress.completed.unwrap_or_default());
+        self.bar.set_message(format!("layer {}", layer + 1));
+    }
+    
+    /// Remove the bar from the terminal
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+    
+    /// Text next to the bar
+    pub fn message(&self) -> String {
+        self.bar.message()
+    }
+    
+    /// Bytes downloaded of the current layer and its size
+    pub fn position(&self) -> (u64, Option<u64>) {
+        (self.bar.position(), self.bar.length())
+    }
+}
+
+impl Default for PullBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,210 @@
+//! Retrying backend calls that failed for a passing reason
+//!
+//! A dropped connection, a 429 from a rate-limited API or a 5xx from a server
+//! that is still loading the model usually go away on their own. Calls that
+//! fail like that are tried again up to `max_retries` times, waiting twice as
+//! long each time with some jitter. Anything else, like an unknown model, fails
+//! right away.
+
+use crate::ollama::startup::Backoff;
+use crate::ollama::{Capabilities, Generation, OllamaClientTrait, PullProgress};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Wait before the first retry
+pub const FIRST_RETRY: Duration = Duration::from_millis(500);
+
+/// Longest wait between two tries
+pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(8);
+
+/// Status of the response a backend error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("HTTP status {0}")]
+pub struct HttpStatus(pub u16);
+
+/// `error` for a response with `status`, which tells a retry whether to try again
+pub fn with_status(error: GitAiError, status: reqwest::StatusCode) -> anyhow::Error {
+    anyhow::Error::new(HttpStatus(status.as_u16())).context(error)
+}
+
+/// `error` for a request that failed with `source` before or while answering
+pub fn with_source(error: GitAiError, source: reqwest::Error) -> anyhow::Error {
+    anyhow::Error::new(source).context(error)
+}
+
+/// Whether `error` may go away when the call is made again
+/// 
+/// Rate limits, server errors and connections that broke off are. Refused
+/// connections aren't, since nothing listens (a server being started is
+/// waited for elsewhere), and neither are timeouts, since the next try would
+/// take as long.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(HttpStatus(status)) = cause.downcast_ref::<HttpStatus>() {
+            return *status == 429 || (500..600).contains(status);
+        }
+        if let Some(request) = cause.downcast_ref::<reqwest::Error>() {
+            return !request.is_timeout() && !request.is_connect() && (request.is_request() || request.is_body());
+        }
+        cause.downcast_ref::<std::io::Error>().is_some_and(|io| {
+            matches!(io.kind(), ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe)
+        })
+    })
+}
+
+/// A client whose calls are tried again after transient errors
+pub struct RetryingClient {
+    inner: Arc<dyn OllamaClientTrait + Send + Sync>,
+    max_retries: u32,
+    first_delay: Duration,
+}
+
+impl RetryingClient {
+    pub fn new(inner: Arc<dyn OllamaClientTrait + Send + Sync>, max_retries: u32) -> Self {
+        Self { inner, max_retries, first_delay: FIRST_RETRY }
+    }
+    
+    /// Wait `first_delay` before the first retry instead of [`FIRST_RETRY`]
+    pub fn with_first_delay(mut self, first_delay: Duration) -> Self {
+        self.first_delay = first_delay;
+        self
+    }
+    
+    fn retries(&self) -> Retries {
+        let seed = uuid::Uuid::new_v4().as_u128() as u64;
+        Retries {
+            delays: Backoff::between(seed, self.first_delay, MAX_RETRY_DELAY.max(self.first_delay)),
+            made: 0,
+            max: self.max_retries,
+        }
+    }
+    
+    /// Make the call `attempt` returns until it succeeds, fails for good or
+    /// runs out of retries
+    async fn retry<T, F, Fut>(&self, what: &str, mut attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retries = self.retries();
+        loop {
+            let result = attempt().await;
+            match retries.after(what, &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return result,
+            }
+        }
+    }
+}
+
+/// Retries left for one call, and the waits before them
+struct Retries {
+    delays: Backoff,
+    made: u32,
+    max: u32,
+}
+
+impl Retries {
+    /// How long to wait before trying again after `result`, `None` to give up
+    /// or when it succeeded
+    fn after<T>(&mut self, what: &str, result: &Result<T>) -> Option<Duration> {
+        let error = result.as_ref().err().filter(|error| self.made < self.max && is_transient(error))?;
+        self.made += 1;
+        let delay = self.delays.next()?;
+        tracing::warn!("{} failed: {}; retrying in {:.1}s ({} of {})", what, error, delay.as_secs_f64(), self.made, self.max);
+        Some(delay)
+    }
+}
+
+#[async_trait]
+impl OllamaClientTrait for RetryingClient {
+    async fn is_running(&self) -> bool {
+        self.inner.is_running().await
+    }
+    
+    async fn generate(&self, model: &str, prompt: &str) -> Result<String> {
+        self.retry("Generating", || self.inner.generate(model, prompt)).await
+    }
+    
+    async fn generate_with_stats(&self, model: &str, prompt: &str) -> Result<Generation> {
+        self.retry("Generating", || self.inner.generate_with_stats(model, prompt)).await
+    }
+    
+    async fn generate_json(&self, model: &str, prompt: &str, format: &serde_json::Value) -> Result<Generation> {
+        self.retry("Generating", || self.inner.generate_json(model, prompt, format)).await
+    }
+    
+    /// Tried again only while no token has arrived, so none is shown twice
+    async fn generate_stream(
+        &self,
+        model: &str,
+        prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        let mut retries = self.retries();
+        loop {
+            let mut streamed = false;
+            let mut forward = |token: &str| {
+                streamed = true;
+                on_token(token);
+            };
+            let result = self.inner.generate_stream(model, prompt, &mut forward).await;
+            if streamed {
+                return result;
+            }
+            match retries.after("Generating", &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return result,
+            }
+        }
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.retry("Listing models", || self.inner.list_models()).await
+    }
+    
+    async fn has_model(&self, model_name: &str) -> Result<bool> {
+        self.retry("Listing models", || self.inner.has_model(model_name)).await
+    }
+    
+    async fn pull_model(&self, model_name: &str) -> Result<()> {
+        self.retry("Downloading", || self.inner.pull_model(model_name)).await
+    }
+    
+    /// A download that broke off resumes where it stopped
+    async fn pull_model_with_progress(
+        &self,
+        model_name: &str,
+        on_progress: &mut (dyn for<'p> FnMut(&'p PullProgress) + Send),
+    ) -> Result<()> {
+        let mut retries = self.retries();
+        loop {
+            let result = self.inner.pull_model_with_progress(model_name, on_progress).await;
+            match retries.after("Downloading", &result) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return result,
+            }
+        }
+    }
+    
+    async fn context_window(&self, model_name: &str) -> Result<Option<u64>> {
+        self.retry("Reading model details", || self.inner.context_window(model_name)).await
+    }
+    
+    async fn capabilities(&self, model_name: &str) -> Capabilities {
+        self.inner.capabilities(model_name).await
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        self.retry("Listing models", || self.inner.get_last_model()).await
+    }
+    
+    async fn delete_model(&self, model_name: &str) -> Result<()> {
+        self.inner.delete_model(model_name).await
+    }
+}
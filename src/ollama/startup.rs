@@ -0,0 +1,143 @@
+//! Waiting for a started Ollama server to answer
+//!
+//! Polls back off with jitter up to an overall deadline and stop early when
+//! the server process exits. A server that never answers is killed, and the
+//! error names the binary and port that were tried along with the end of the
+//! server's log, which usually says why.
+
+use crate::ollama::OllamaClientTrait;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Child;
+use tokio::time::Instant;
+
+/// Delay before the second poll
+pub const FIRST_POLL: Duration = Duration::from_millis(100);
+
+/// Longest delay between two polls
+pub const MAX_POLL: Duration = Duration::from_secs(2);
+
+/// Lines of the server log included in startup errors
+pub const LOG_TAIL_LINES: usize = 20;
+
+/// Delays between polls, doubling from [`FIRST_POLL`] up to [`MAX_POLL`]
+/// 
+/// Each delay is shifted by up to a quarter either way, so runs started
+/// together don't poll in step.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    delay: Duration,
+    max: Duration,
+    state: u64,
+}
+
+impl Backoff {
+    pub fn new(seed: u64) -> Self {
+        Self::between(seed, FIRST_POLL, MAX_POLL)
+    }
+    
+    /// Delays doubling from `first` up to `max` instead of the polling ones
+    pub fn between(seed: u64, first: Duration, max: Duration) -> Self {
+        Self { delay: first, max, state: seed | 1 }
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+    
+    fn next(&mut self) -> Option<Duration> {
+        // xorshift64, plenty for spreading out polls
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        let factor = 0.75 + (self.state % 501) as f64 / 1000.0;
+        
+        let delay = self.delay.mul_f64(factor);
+        self.delay = (self.delay * 2).min(self.max);
+        Some(delay)
+    }
+}
+
+/// File a server started for `port` writes its output to
+pub fn server_log_path(port: u16) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("git-ai-commit")
+        .join(format!("ollama-{}.log", port))
+}
+
+/// Last `lines` non-empty lines of the log at `path`, if it has any
+pub fn log_tail(path: &Path, lines: usize) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let kept: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+    let tail = kept[kept.len().saturating_sub(lines)..].join("\n");
+    (!tail.is_empty()).then_some(tail)
+}
+
+/// The server that was started, described in the error when it doesn't answer
+#[derive(Debug, Clone, Copy)]
+pub struct ServerStart<'a> {
+    pub binary: &'a Path,
+    pub port: u16,
+    /// Where its output went; `None` when it couldn't be captured
+    pub log: Option<&'a Path>,
+}
+
+impl ServerStart<'_> {
+    /// The binary and port tried, then the end of the log
+    fn diagnostics(&self) -> String {
+        let mut text = format!("binary: {}\nport: {}", self.binary.display(), self.port);
+        match self.log {
+            Some(log) => match log_tail(log, LOG_TAIL_LINES) {
+                Some(tail) => text.push_str(&format!("\nlast lines of {}:\n{}", log.display(), tail)),
+                None => text.push_str(&format!("\nthe server wrote nothing to {}", log.display())),
+            },
+            None => text.push_str("\nthe server's output wasn't captured"),
+        }
+        text
+    }
+}
+
+/// Poll `client` until the server answers, `process` exits or `deadline` passes
+/// 
+/// Every poll is cut short at the deadline, so a hanging request can't
+/// stretch the wait. A server that is still starting at the deadline is
+/// killed rather than left behind.
+pub async fn wait_for_server(
+    client: &dyn OllamaClientTrait,
+    process: &mut Child,
+    start: &ServerStart<'_>,
+    deadline: Duration,
+    mut backoff: Backoff,
+) -> Result<()> {
+    let started = Instant::now();
+    let give_up = started + deadline;
+    
+    loop {
+        if tokio::time::timeout_at(give_up, client.is_running()).await == Ok(true) {
+            return Ok(());
+        }
+        
+        if let Some(status) = process.try_wait()? {
+            return Err(GitAiError::Ollama(format!(
+                "The Ollama server exited ({}) before answering\n{}",
+                status, start.diagnostics()
+            )).into());
+        }
+        
+        let now = Instant::now();
+        if now >= give_up {
+            let _ = process.kill().await;
+            return Err(GitAiError::Timeout(format!(
+                "The Ollama server didn't answer within {:.1}s; raise daemon.start_timeout_seconds on slow machines\n{}",
+                (now - started).as_secs_f64(), start.diagnostics()
+            )).into());
+        }
+        
+        let delay = backoff.next().unwrap_or(MAX_POLL);
+        tokio::time::sleep_until((now + delay).min(give_up)).await;
+    }
+}
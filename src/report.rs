@@ -0,0 +1,180 @@
+//! Bug report bundles with personal details removed
+//! 
+//! A report holds the environment, the effective config, the prompt for the
+//! current changes, recent history and the end of the Ollama server's log. The prompt is stored under a name
+//! derived from its SHA-256, so the same prompt always becomes the same
+//! fixture and duplicate reports are easy to spot.
+
+use crate::config::Redaction;
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::Path;
+
+/// Number of history entries included in a report
+pub const HISTORY_ENTRIES: usize = 5;
+
+/// Number of server log lines included in a report
+pub const LOG_LINES: usize = 50;
+
+/// Everything a report contains, already redacted
+#[derive(Debug, Clone, Default)]
+pub struct BugReport {
+    /// Tool, OS, git and Ollama versions
+    pub environment: String,
+    /// Effective configuration as TOML
+    pub config: String,
+    /// Prompt that would be sent for the current changes
+    pub prompt: Option<String>,
+    /// Recent history entries, one JSON object per line
+    pub history: String,
+    /// Last lines of the Ollama server's log
+    pub log: String,
+}
+
+impl BugReport {
+    /// Build a report, redacting every part
+    pub fn new(environment: &str, config: &str, prompt: Option<&str>, history: &str) -> Self {
+        let home = dirs::home_dir();
+        let redact = |text: &str| redact(text, home.as_deref());
+        
+        Self {
+            environment: redact(environment),
+            config: redact(config),
+            prompt: prompt.map(redact),
+            history: redact(history),
+            log: String::new(),
+        }
+    }
+    
+    /// Add the end of the server's log, redacted like the rest
+    pub fn with_log(mut self, log: &str) -> Self {
+        self.log = redact(log, dirs::home_dir().as_deref());
+        self
+    }
+    
+    /// Fixture path of the prompt, named by the first 16 hex digits of its SHA-256
+    pub fn prompt_fixture(&self) -> Option<String> {
+        let prompt = self.prompt.as_ref()?;
+        let digest = Sha256::digest(prompt.as_bytes());
+        let hex: String = digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+        Some(format!("prompts/{}.txt", hex))
+    }
+    
+    /// Files of the bundle as `(path, content)`, in the order they are shown and written
+    pub fn files(&self) -> Vec<(String, String)> {
+        let mut files = vec![
+            ("environment.txt".to_string(), self.environment.clone()),
+            ("config.toml".to_string(), self.config.clone()),
+        ];
+        if let (Some(name), Some(prompt)) = (self.prompt_fixture(), self.prompt.as_ref()) {
+            files.push((name, prompt.clone()));
+        }
+        if !self.history.is_empty() {
+            files.push(("history.jsonl".to_string(), self.history.clone()));
+        }
+        if !self.log.is_empty() {
+            files.push(("ollama.log".to_string(), self.log.clone()));
+        }
+        files
+    }
+    
+    /// Markdown to paste into a GitHub issue
+    pub fn issue_body(&self) -> String {
+        let mut body = String::from("## Description\n\n<!-- What happened, and what did you expect? -->\n\n");
+        body.push_str(&format!("## Environment\n\n```\n{}\n```\n\n", self.environment.trim_end()));
+        body.push_str(&details("Config", "toml", &self.config));
+        if let (Some(name), Some(prompt)) = (self.prompt_fixture(), self.prompt.as_ref()) {
+            body.push_str(&details(&format!("Prompt (`{}`)", name), "", prompt));
+        }
+        if !self.history.is_empty() {
+            body.push_str(&details("Recent history", "json", &self.history));
+        }
+        if !self.log.is_empty() {
+            body.push_str(&details("Ollama server log", "", &self.log));
+        }
+        body
+    }
+    
+    /// Write the files into an uncompressed tarball
+    pub fn write_tar(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to create {}: {}", path.display(), e)))?;
+        let mut archive = tar::Builder::new(file);
+        
+        for (name, content) in self.files() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(crate::ollama::daemon::now_secs());
+            header.set_cksum();
+            archive.append_data(&mut header, format!("git-ai-commit-report/{}", name), content.as_bytes())
+                .map_err(|e| GitAiError::FileSystem(format!("Failed to write bug report: {}", e)))?;
+        }
+        
+        archive.finish()
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to write bug report: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Collapsed issue section
+/// 
+/// Four-backtick fences keep code blocks inside diffs from ending the block early.
+fn details(summary: &str, language: &str, content: &str) -> String {
+    format!(
+        "<details>\n<summary>{}</summary>\n\n````{}\n{}\n````\n\n</details>\n\n",
+        summary,
+        language,
+        content.trim_end()
+    )
+}
+
+/// Remove secrets, credentials, email addresses and the home directory from `text`
+/// 
+/// Values of keys that look secret (`api_key`, `token`, `password`, ...) are
+/// replaced, as are `user:pass@` in URLs and `sk-...` style API keys. The
+/// `git@host` user of SSH remotes is kept since it identifies no one.
+pub fn redact(text: &str, home: Option<&Path>) -> String {
+    let email = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap();
+    
+    let text = redact_secrets(text);
+    let text = email.replace_all(&text, |caps: &Captures| {
+        let matched = &caps[0];
+        if matched.starts_with("git@") {
+            matched.to_string()
+        } else {
+            "<email>".to_string()
+        }
+    });
+    
+    match home.map(|home| home.to_string_lossy().to_string()) {
+        Some(home) if !home.is_empty() && home != "/" => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+/// Remove secret values, credentials in URLs and API keys from `text`
+pub fn redact_secrets(text: &str) -> String {
+    let secret_value = Regex::new(
+        r#"(?i)((?:api[_-]?key|token|secret|password|authorization)"?\s*[=:]\s*)("[^"]*"|'[^']*'|[^\s,}]+)"#
+    ).unwrap();
+    let url_credentials = Regex::new(r"([a-zA-Z][a-zA-Z0-9+.-]*://)[^/\s@]+@").unwrap();
+    let api_key = Regex::new(r"\bsk-[A-Za-z0-9_-]{16,}").unwrap();
+    
+    let text = secret_value.replace_all(text, "${1}\"<redacted>\"");
+    let text = url_credentials.replace_all(&text, "${1}<redacted>@");
+    api_key.replace_all(&text, "<redacted>").into_owned()
+}
+
+/// `prompt` with what `redaction` asks for removed, before it goes to a model
+pub fn redact_prompt(prompt: &str, redaction: Redaction) -> Cow<'_, str> {
+    match redaction {
+        Redaction::Off => Cow::Borrowed(prompt),
+        Redaction::Secrets => Cow::Owned(redact_secrets(prompt)),
+        Redaction::Strict => Cow::Owned(redact(prompt, dirs::home_dir().as_deref())),
+    }
+}
@@ -0,0 +1,253 @@
+//! The pipeline behind `git-ai-commit self-test`
+//!
+//! A throwaway repository gets a few synthetic changes, and every stage of a
+//! normal run takes its turn on them: collecting the changes, building the
+//! prompt, generating with the configured backend (or a built-in stub, so the
+//! install can be checked without a model), validating and committing. Each
+//! stage reports like a doctor check; the stages after a failed one are
+//! skipped.
+
+use crate::config::ValidationConfig;
+use crate::doctor::{Check, CheckStatus};
+use crate::formatting::body;
+use crate::formatting::candidate::Candidate;
+use crate::formatting::validate::Violation;
+use crate::formatting::PromptBuilder;
+use crate::git::clone::git_stdout;
+use crate::git::command::git_command;
+use crate::git::{lock, GitCollector, GitInfo};
+use crate::ollama::context::estimate_tokens;
+use crate::ollama::{OllamaClientTrait, OllamaManager};
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+/// Stages in the order they run
+pub const STAGES: &[&str] = &["repository", "backend", "collect", "prompt", "generate", "validate", "commit"];
+
+/// What the built-in stub answers to every prompt
+pub const STUB_MESSAGE: &str = "feat(greeting): add a greeting helper";
+
+/// The synthetic changes: a new file and an edited one
+const NEW_FILE: (&str, &str) = ("src/greeting.rs", "pub fn greet(name: &str) -> String {\n    format!(\"Hello, {}!\", name)\n}\n");
+const EDITED_FILE: (&str, &str, &str) = ("README.md", "# Self-test\n", "# Self-test\n\nGreets people by name.\n");
+
+/// Backend that answers without a model, to check everything else
+#[derive(Debug, Default)]
+pub struct StubClient;
+
+#[async_trait]
+impl OllamaClientTrait for StubClient {
+    async fn is_running(&self) -> bool {
+        true
+    }
+    
+    async fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+        Ok(STUB_MESSAGE.to_string())
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(vec!["stub".to_string()])
+    }
+    
+    async fn has_model(&self, _model_name: &str) -> Result<bool> {
+        Ok(true)
+    }
+    
+    async fn pull_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        Ok(Some("stub".to_string()))
+    }
+    
+    async fn delete_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Run every stage in `repo_path`, an empty directory, and report each
+/// 
+/// With `stub`, the manager is expected to generate through [`StubClient`]
+/// and the backend isn't started.
+pub async fn run(
+    repo_path: &Path,
+    manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    rules: &ValidationConfig,
+    stub: bool,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if pipeline(repo_path, manager, prompt_builder, rules, stub, &mut checks).await.is_none() {
+        let failed = checks.last().map_or("a stage", |check: &Check| check.name);
+        for name in &STAGES[checks.len()..] {
+            checks.push(Check::warn(name, format!("skipped, {} failed", failed)));
+        }
+    }
+    checks
+}
+
+/// Push a check per stage; `None` once one failed
+async fn pipeline(
+    repo_path: &Path,
+    manager: &mut OllamaManager,
+    prompt_builder: &PromptBuilder,
+    rules: &ValidationConfig,
+    stub: bool,
+    checks: &mut Vec<Check>,
+) -> Option<()> {
+    passed(checks, "repository", create_repo(repo_path).await.map(|()| "2 synthetic changes staged".to_string()))?;
+    
+    let backend = if stub {
+        Ok("built-in stub, no model called".to_string())
+    } else {
+        start_backend(manager).await
+    };
+    passed(checks, "backend", backend)?;
+    
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await
+        .and_then(|git_info| Ok((describe_changes(&git_info)?, git_info)));
+    let git_info = stage(checks, "collect", git_info)?;
+    
+    let prompt = prompt_builder.build(&git_info);
+    let mentioned = prompt.contains(NEW_FILE.0);
+    checks.push(if mentioned {
+        Check::ok("prompt", format!("{} characters, about {} tokens", prompt.len(), estimate_tokens(&prompt)))
+    } else {
+        Check::fail("prompt", format!("the prompt doesn't mention the staged {}", NEW_FILE.0))
+    });
+    mentioned.then_some(())?;
+    
+    let started = Instant::now();
+    let generation = manager.generate_commit_with_stats(&prompt).await.map(|generation| {
+        let subject = generation.text.trim().lines().next().unwrap_or_default().to_string();
+        let detail = format!("{:?} from {} in {:.1}s", subject, manager.model(), started.elapsed().as_secs_f64());
+        (detail, generation.text)
+    });
+    let message = stage(checks, "generate", generation)?;
+    
+    let candidate = Candidate::for_changes(manager.model(), message, rules, &git_info);
+    let issues: Vec<String> = candidate.violations.iter().map(Violation::describe).collect();
+    checks.push(if candidate.violations.iter().any(Violation::is_unusable) {
+        Check::fail("validate", issues.join("; "))
+    } else if issues.is_empty() {
+        Check::ok("validate", "no rule broken")
+    } else {
+        // A weaker message still commits; the install works
+        Check::warn("validate", issues.join("; "))
+    });
+    if checks.last()?.status == CheckStatus::Fail {
+        return None;
+    }
+    
+    passed(checks, "commit", commit(repo_path, &candidate.message).await)
+}
+
+/// Push the outcome of stage `name`; `None` if it failed
+fn passed(checks: &mut Vec<Check>, name: &'static str, outcome: Result<String>) -> Option<()> {
+    stage(checks, name, outcome.map(|detail| (detail, ())))
+}
+
+/// Push the outcome of stage `name` and pass on what it produced
+fn stage<T>(checks: &mut Vec<Check>, name: &'static str, outcome: Result<(String, T)>) -> Option<T> {
+    match outcome {
+        Ok((detail, output)) => {
+            checks.push(Check::ok(name, detail));
+            Some(output)
+        }
+        Err(e) => {
+            checks.push(Check::fail(name, format!("{:#}", e)));
+            None
+        }
+    }
+}
+
+/// A repository in the empty directory `repo_path`, with one commit and the
+/// synthetic changes staged on top
+/// 
+/// It has its own identity and no hooks or signing, so the user's git config
+/// can't get in the way of the commit.
+pub async fn create_repo(repo_path: &Path) -> Result<()> {
+    // Never add commits to a repository that holds anything else
+    if fs::read_dir(repo_path).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(GitAiError::FileSystem(format!("{} is not empty", repo_path.display())).into());
+    }
+    let hooks_dir = repo_path.join(".git").join("no-hooks");
+    git(repo_path, &["init", "--quiet"]).await?;
+    for (key, value) in [
+        ("user.name", "git-ai-commit self-test"),
+        ("user.email", "self-test@localhost"),
+        ("commit.gpgsign", "false"),
+        ("core.hooksPath", &hooks_dir.to_string_lossy()),
+    ] {
+        git(repo_path, &["config", key, value]).await?;
+    }
+    
+    let (readme, before, after) = EDITED_FILE;
+    write(repo_path, readme, before)?;
+    git(repo_path, &["add", "--all"]).await?;
+    git(repo_path, &["commit", "--quiet", "-m", "Initial commit"]).await?;
+    
+    write(repo_path, readme, after)?;
+    write(repo_path, NEW_FILE.0, NEW_FILE.1)?;
+    git(repo_path, &["add", "--all"]).await
+}
+
+/// Start the configured backend and make sure it has the model
+async fn start_backend(manager: &mut OllamaManager) -> Result<String> {
+    manager.ensure_running().await?;
+    let model = manager.model().to_string();
+    manager.ensure_model_available(&model).await?;
+    Ok(format!("model {}", model))
+}
+
+/// The collected changes, if they are the synthetic ones
+fn describe_changes(git_info: &GitInfo) -> Result<String> {
+    let staged = git_info.status.staged_files.len();
+    if staged != 2 {
+        return Err(GitAiError::Git(format!("expected 2 staged files, found {}", staged)).into());
+    }
+    Ok(format!(
+        "{} staged files, +{} -{}",
+        staged, git_info.diff_stat.insertions, git_info.diff_stat.deletions
+    ))
+}
+
+/// Commit with `message` and read the commit back
+async fn commit(repo_path: &Path, message: &str) -> Result<String> {
+    let mut args = vec!["commit", "--quiet"];
+    args.extend(body::commit_args(message));
+    let output = lock::run_writing_index(repo_path, &args).await?;
+    if !output.status.success() {
+        return Err(GitAiError::Git(format!("Git commit failed: {}", String::from_utf8_lossy(&output.stderr).trim())).into());
+    }
+    let head = git_stdout(repo_path, &["log", "-1", "--format=%h %s"]).await
+        .ok_or_else(|| GitAiError::Git("the new commit can't be read back".to_string()))?;
+    Ok(head.trim().to_string())
+}
+
+async fn git(repo_path: &Path, args: &[&str]) -> Result<()> {
+    let output = git_command(repo_path)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| GitAiError::Git(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(GitAiError::Git(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim())).into());
+    }
+    Ok(())
+}
+
+fn write(repo_path: &Path, name: &str, content: &str) -> Result<()> {
+    let path = repo_path.join(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    fs::write(&path, content)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to write {}: {}", path.display(), e)).into())
+}
@@ -0,0 +1,151 @@
+//! Serving the suggested message to editors
+//!
+//! `git-ai-commit serve` watches the index and generates a new suggestion
+//! whenever the staged changes change. Editors read it from a Unix socket:
+//! every connection receives the current suggestion as one line of JSON and
+//! is closed, so a statusline can poll it without starting a process.
+
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Socket created in the git directory unless `--socket` is given
+pub const SOCKET_NAME: &str = "git-ai-commit.sock";
+
+/// How often the index is checked for newly staged changes
+pub const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the server is doing for the current staged changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionState {
+    /// Nothing is staged
+    #[default]
+    Idle,
+    Generating,
+    Ready,
+    Failed,
+}
+
+/// The latest suggestion, as sent to every client
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub state: SuggestionState,
+    /// Short text for a statusline, e.g. `suggested: feat(cli): add --amend`
+    pub statusline: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub staged_files: usize,
+    /// Seconds since the Unix epoch
+    pub updated: u64,
+}
+
+impl Suggestion {
+    pub fn idle() -> Self {
+        Self::stamped(SuggestionState::Idle, String::new(), 0)
+    }
+    
+    pub fn generating(staged_files: usize) -> Self {
+        Self::stamped(SuggestionState::Generating, "suggesting...".to_string(), staged_files)
+    }
+    
+    pub fn ready(message: &str, staged_files: usize) -> Self {
+        let message = message.trim();
+        let subject = message.lines().next().unwrap_or_default();
+        Self {
+            message: Some(message.to_string()),
+            ..Self::stamped(SuggestionState::Ready, format!("suggested: {}", subject), staged_files)
+        }
+    }
+    
+    pub fn failed(error: &str, staged_files: usize) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            ..Self::stamped(SuggestionState::Failed, "no suggestion".to_string(), staged_files)
+        }
+    }
+    
+    fn stamped(state: SuggestionState, statusline: String, staged_files: usize) -> Self {
+        let updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self { state, statusline, message: None, error: None, staged_files, updated }
+    }
+}
+
+/// The latest suggestion, shared by the watcher and the socket
+#[derive(Debug, Clone, Default)]
+pub struct SharedSuggestion(Arc<RwLock<Suggestion>>);
+
+impl SharedSuggestion {
+    pub fn get(&self) -> Suggestion {
+        self.0.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+    
+    pub fn set(&self, suggestion: Suggestion) {
+        *self.0.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = suggestion;
+    }
+}
+
+/// Modification time and size of the index, which change whenever something is staged
+/// 
+/// Git also rewrites the index to refresh file stats, so a changed
+/// fingerprint only means the staged changes may have changed.
+pub fn index_fingerprint(git_dir: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(git_dir.join("index")).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Answer every connection to `path` with the current suggestion, until an error or until dropped
+/// 
+/// A socket left behind by a server that is gone is replaced; one that still
+/// answers belongs to another server and is an error.
+#[cfg(unix)]
+pub async fn serve_socket(path: &Path, suggestion: SharedSuggestion) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{UnixListener, UnixStream};
+    
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(GitAiError::Validation(format!(
+                "Another git-ai-commit serve is already answering on {}", path.display()
+            )).into());
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| GitAiError::FileSystem(format!("Failed to remove stale socket {}: {}", path.display(), e)))?;
+    }
+    
+    let listener = UnixListener::bind(path)
+        .map_err(|e| GitAiError::FileSystem(format!("Failed to listen on {}: {}", path.display(), e)))?;
+    let _socket = SocketFile(path.to_path_buf());
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let line = format!("{}\n", serde_json::to_string(&suggestion.get())?);
+        // A client that stops reading must not hold up the others
+        tokio::spawn(async move {
+            let _ = stream.write_all(line.as_bytes()).await;
+        });
+    }
+}
+
+/// Removes the socket when the server stops, however it stops
+#[cfg(unix)]
+struct SocketFile(std::path::PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn serve_socket(_path: &Path, _suggestion: SharedSuggestion) -> Result<()> {
+    Err(GitAiError::UnsupportedPlatform("serve needs Unix domain sockets".to_string()).into())
+}
@@ -0,0 +1,16 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+
+/// Place `text` on the system clipboard
+/// 
+/// On Linux the clipboard belongs to the process that set it; arboard hands
+/// the contents to a running clipboard manager when this process exits, so
+/// without one the text may disappear once git-ai-commit finishes.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| GitAiError::UnsupportedPlatform(format!("Clipboard is not available: {}", e)))?;
+    clipboard
+        .set_text(text.trim())
+        .map_err(|e| GitAiError::UnsupportedPlatform(format!("Failed to copy to the clipboard: {}", e)))?;
+    Ok(())
+}
@@ -9,6 +9,9 @@ pub enum GitAiError {
     #[error("Ollama operation failed: {0}")]
     Ollama(String),
     
+    #[error("Provider request failed: {0}")]
+    Provider(String),
+    
     #[error("File system operation failed: {0}")]
     FileSystem(String),
     
@@ -24,6 +27,12 @@ pub enum GitAiError {
     #[error("Timeout error: {0}")]
     Timeout(String),
     
+    #[error("Commit message rejected: {0}")]
+    Validation(String),
+    
+    #[error("Model call budget exhausted: {0}")]
+    Budget(String),
+    
     #[error("Platform not supported: {0}")]
     UnsupportedPlatform(String),
 }
@@ -37,6 +46,10 @@ impl GitAiError {
         Self::Ollama(msg.into())
     }
     
+    pub fn provider(msg: impl Into<String>) -> Self {
+        Self::Provider(msg.into())
+    }
+    
     pub fn filesystem(msg: impl Into<String>) -> Self {
         Self::FileSystem(msg.into())
     }
@@ -57,7 +70,20 @@ impl GitAiError {
         Self::Timeout(msg.into())
     }
     
+    pub fn validation(msg: impl Into<String>) -> Self {
+        Self::Validation(msg.into())
+    }
+    
+    pub fn budget(msg: impl Into<String>) -> Self {
+        Self::Budget(msg.into())
+    }
+    
     pub fn unsupported_platform(msg: impl Into<String>) -> Self {
         Self::UnsupportedPlatform(msg.into())
     }
 }
+
+/// Whether `error` means a generation took longer than allowed
+pub fn is_timeout_error(error: &anyhow::Error) -> bool {
+    matches!(error.downcast_ref::<GitAiError>(), Some(GitAiError::Timeout(_)))
+}
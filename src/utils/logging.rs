@@ -0,0 +1,114 @@
+//! Diagnostics on stderr, filtered by `--quiet` and `-v`/`-vv`
+//!
+//! Modules report through the `tracing` macros: warnings and notes users
+//! should see at `warn!`/`info!`, what the tool is doing at `debug!` and
+//! raw data such as the parsed config at `trace!`. Progress output and
+//! results are printed by the binary itself and aren't affected.
+
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Most verbose level written, as an index into `LEVELS`
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(2);
+
+static INSTALL: Once = Once::new();
+
+const LEVELS: [Level; 5] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE];
+
+/// Level for the command-line flags: errors only with `--quiet`, notes by
+/// default, the tool's steps with `-v` and everything with `-vv`
+pub fn level_for(quiet: bool, verbose: u8) -> Level {
+    match (quiet, verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    }
+}
+
+/// Write diagnostics up to `level` to stderr from now on
+/// 
+/// The first call installs the subscriber; later ones only change the level,
+/// so it can be set before the config is read and again once the full
+/// command line is known.
+pub fn init(level: Level) {
+    let index = LEVELS.iter().position(|l| *l == level).unwrap_or(2);
+    MAX_LEVEL.store(index, Ordering::Relaxed);
+    INSTALL.call_once(|| {
+        // Fails only when the embedding program installed its own subscriber
+        let _ = tracing::subscriber::set_global_default(StderrSubscriber);
+    });
+}
+
+/// Whether events at `level` are written
+pub fn enabled(level: Level) -> bool {
+    level <= LEVELS[MAX_LEVEL.load(Ordering::Relaxed)]
+}
+
+/// One diagnostic line, e.g. `[WARN] No upstream configured`
+/// 
+/// Debug and trace lines name the module they come from.
+pub fn format_line(level: Level, target: &str, message: &str) -> String {
+    let target = target.strip_prefix("git_ai_commit::").unwrap_or(target);
+    if level >= Level::DEBUG {
+        format!("[{}] {}: {}", level, target, message)
+    } else {
+        format!("[{}] {}", level, message)
+    }
+}
+
+/// Subscriber printing each event as one line; spans aren't used
+struct StderrSubscriber;
+
+impl Subscriber for StderrSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        enabled(*metadata.level())
+    }
+    
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        eprintln!("{}", format_line(*metadata.level(), metadata.target(), &visitor.text));
+    }
+    
+    fn enter(&self, _span: &Id) {}
+    
+    fn exit(&self, _span: &Id) {}
+}
+
+/// The event's message followed by its other fields as `key=value`
+#[derive(Default)]
+struct MessageVisitor {
+    text: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.text, "{:?}", value);
+        } else {
+            let _ = write!(self.text, " {}={:?}", field.name(), value);
+        }
+    }
+    
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.text.push_str(value);
+        } else {
+            let _ = write!(self.text, " {}={}", field.name(), value);
+        }
+    }
+}
@@ -1,6 +1,10 @@
 //! Utility modules for cross-platform support and error handling
 
+pub mod clipboard;
 pub mod cross_platform;
 pub mod error;
+pub mod logging;
+pub mod write_guard;
 
 pub use error::GitAiError;
+pub use write_guard::WriteMode;
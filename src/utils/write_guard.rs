@@ -0,0 +1,35 @@
+use crate::utils::error::GitAiError;
+use anyhow::Result;
+
+/// Whether a run may change the repository, the config, or installed models
+/// 
+/// Every mutating operation checks this before acting, so `--no-write`
+/// holds no matter which other flags are combined with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    #[default]
+    ReadWrite,
+    ReadOnly,
+}
+
+impl WriteMode {
+    pub fn from_no_write(no_write: bool) -> Self {
+        if no_write {
+            WriteMode::ReadOnly
+        } else {
+            WriteMode::ReadWrite
+        }
+    }
+    
+    pub fn is_read_only(&self) -> bool {
+        *self == WriteMode::ReadOnly
+    }
+    
+    /// Fail with a clear error if `action` is not allowed
+    pub fn check(&self, action: &str) -> Result<()> {
+        if self.is_read_only() {
+            return Err(GitAiError::Config(format!("--no-write prevents {}", action)).into());
+        }
+        Ok(())
+    }
+}
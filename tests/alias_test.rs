@@ -0,0 +1,74 @@
+use git_ai_commit::cli::alias::expand_aliases;
+use git_ai_commit::config::Config;
+use std::collections::BTreeMap;
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+fn aliases(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn test_expand_alias_keeps_trailing_args() {
+    // Given
+    let aliases = aliases(&[("ship", "--add-unstaged --confirm"), ("wip", "--quick --add-unstaged")]);
+    
+    // When
+    let expanded = expand_aliases(argv(&["git-ai-commit", "ship", "--verbose"]), &aliases, &[]).unwrap();
+    
+    // Then
+    assert_eq!(expanded, argv(&["git-ai-commit", "--add-unstaged", "--confirm", "--verbose"]));
+}
+
+#[test]
+fn test_expand_nested_and_quoted_aliases() {
+    let aliases = aliases(&[
+        ("review", "--dry-run --format 'markdown'"),
+        ("pr", "review --profile \"work laptop\""),
+    ]);
+    
+    let expanded = expand_aliases(argv(&["git-ai-commit", "pr"]), &aliases, &[]).unwrap();
+    
+    assert_eq!(
+        expanded,
+        argv(&["git-ai-commit", "--dry-run", "--format", "markdown", "--profile", "work laptop"])
+    );
+}
+
+#[test]
+fn test_aliases_do_not_override_builtins_or_flags() {
+    let aliases = aliases(&[("push-summary", "--dry-run"), ("--verbose", "--quick")]);
+    
+    let args = argv(&["git-ai-commit", "push-summary"]);
+    assert_eq!(expand_aliases(args.clone(), &aliases, &["push-summary"]).unwrap(), args);
+    
+    let args = argv(&["git-ai-commit", "--verbose"]);
+    assert_eq!(expand_aliases(args.clone(), &aliases, &[]).unwrap(), args);
+    
+    let args = argv(&["git-ai-commit"]);
+    assert_eq!(expand_aliases(args.clone(), &aliases, &[]).unwrap(), args);
+}
+
+#[test]
+fn test_alias_errors() {
+    let looping = aliases(&[("a", "b"), ("b", "a --verbose")]);
+    let err = expand_aliases(argv(&["git-ai-commit", "a"]), &looping, &[]).unwrap_err();
+    assert!(err.to_string().contains("Alias loop: a -> b -> a"), "Unexpected error: {}", err);
+    
+    let unterminated = aliases(&[("bad", "--profile 'work")]);
+    assert!(expand_aliases(argv(&["git-ai-commit", "bad"]), &unterminated, &[]).is_err());
+}
+
+#[test]
+fn test_alias_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "alias.ship = \"--add-unstaged --confirm\"\nalias.wip = \"--quick\"\n").unwrap();
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    
+    assert_eq!(config.alias.get("ship").map(String::as_str), Some("--add-unstaged --confirm"));
+    assert_eq!(config.alias.get("wip").map(String::as_str), Some("--quick"));
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::git;
+use git_ai_commit::git::{GitCollector, GitInfo};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_head_patch_describes_the_last_commit() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("README.md"), "hello\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    std::fs::write(repo_path.join("README.md"), "hello\nworld\n").unwrap();
+    std::fs::write(repo_path.join("lib.rs"), "fn main() {}\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "wip"]);
+    
+    // When
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let patch = git_collector.get_head_patch().await.unwrap();
+    let git_info = GitInfo::from_patch(&patch).unwrap();
+    
+    // Then
+    let mut files: Vec<PathBuf> = git_info.file_changes.iter().map(|c| c.file_path.clone()).collect();
+    files.sort();
+    assert_eq!(files, vec![PathBuf::from("README.md"), PathBuf::from("lib.rs")]);
+    assert_eq!(git_info.diff_stat.insertions, 2);
+    assert_eq!(git_collector.get_head_message().await.unwrap(), "wip");
+}
+
+#[tokio::test]
+async fn test_amend_rewords_without_taking_staged_changes() {
+    // Given a "wip" commit and an unrelated staged change
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    git(repo_path, &["config", "user.name", "Test"]);
+    git(repo_path, &["config", "user.email", "test@example.com"]);
+    std::fs::write(repo_path.join("a.txt"), "a\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "wip"]);
+    std::fs::write(repo_path.join("b.txt"), "b\n").unwrap();
+    git(repo_path, &["add", "b.txt"]);
+    
+    // When
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    git_collector.amend_message("feat: add a.txt", None).await.unwrap();
+    
+    // Then
+    assert_eq!(git(repo_path, &["log", "--format=%s"]).trim(), "feat: add a.txt");
+    assert_eq!(git(repo_path, &["show", "--format=", "--name-only", "HEAD"]).trim(), "a.txt");
+    assert_eq!(git(repo_path, &["diff", "--cached", "--name-only"]).trim(), "b.txt");
+}
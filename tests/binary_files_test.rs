@@ -0,0 +1,107 @@
+mod common;
+
+use common::git;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::files::{format_size, ChangeType, LARGE_FILE_BYTES};
+use git_ai_commit::git::{DiffInfo, FileChange, GitCollector, GitInfo};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn change(path: &str, change_type: ChangeType, is_binary: bool, size: Option<u64>) -> FileChange {
+    FileChange { change_type, file_path: PathBuf::from(path), old_path: None, is_binary, size, ..Default::default() }
+}
+
+#[test]
+fn test_sizes_are_readable() {
+    assert_eq!(format_size(1), "1 byte");
+    assert_eq!(format_size(80), "80 bytes");
+    assert_eq!(format_size(2048), "2 KB");
+    assert_eq!(format_size(2_411_724), "2.3 MB");
+    assert_eq!(format_size(50 * 1024 * 1024), "50 MB");
+}
+
+#[test]
+fn test_binary_and_large_files_are_described() {
+    let logo = change("assets/logo.png", ChangeType::Added, true, Some(2_411_724));
+    assert_eq!(logo.content_note().unwrap(), "binary image added, 2.3 MB");
+    assert_eq!(logo.display(), "A  assets/logo.png (binary image added, 2.3 MB)");
+    
+    // Deleted files have no size left to report
+    let font = change("fonts/Inter.woff2", ChangeType::Deleted, true, None);
+    assert_eq!(font.content_note().unwrap(), "binary font deleted");
+    
+    let dump = change("data/cities.json", ChangeType::Modified, false, Some(LARGE_FILE_BYTES * 3));
+    assert_eq!(dump.content_note().unwrap(), "large file modified, 3.0 MB");
+    
+    let source = change("src/main.rs", ChangeType::Modified, false, Some(4096));
+    assert_eq!(source.content_note(), None);
+    assert_eq!(source.display(), "M  src/main.rs");
+}
+
+#[test]
+fn test_numstat_marks_binary_files() {
+    // Given
+    let numstat = "-\t-\tassets/logo.png\n3\t1\tsrc/main.rs\n";
+    
+    // When
+    let diff = DiffInfo::parse(numstat).unwrap();
+    
+    // Then
+    assert!(diff.file_stats[0].binary);
+    assert!(!diff.file_stats[1].binary);
+    assert!(diff.stat_graph(80).contains(" assets/logo.png | Bin\n"), "{}", diff.stat_graph(80));
+}
+
+#[test]
+fn test_binary_patches_are_described_in_the_diff() {
+    // Given
+    let patch = "\
+diff --git a/assets/logo.png b/assets/logo.png
+new file mode 100644
+index 0000000..3f4a2b1
+Binary files /dev/null and b/assets/logo.png differ
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { run(); }
+";
+
+    // When
+    let git_info = GitInfo::from_patch(patch).unwrap();
+    let prompt = PromptBuilder::new(10, 50).build_with_patch(&git_info, patch);
+    
+    // Then
+    assert!(git_info.file_changes[0].is_binary);
+    assert!(prompt.contains("--- assets/logo.png\n[binary image added]\n"), "{}", prompt);
+    assert!(prompt.contains("+fn main() { run(); }"), "{}", prompt);
+}
+
+#[tokio::test]
+async fn test_collector_labels_binary_files() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("README.md"), "readme").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0];
+    png.resize(3000, 0);
+    std::fs::write(repo_path.join("logo.png"), &png).unwrap();
+    std::fs::write(repo_path.join("README.md"), "readme\nmore").unwrap();
+    git(repo_path, &["add", "."]);
+    
+    // When
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await.unwrap();
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    
+    // Then
+    let logo = git_info.file_changes.iter().find(|c| c.file_path == Path::new("logo.png")).unwrap();
+    assert!(logo.is_binary);
+    assert_eq!(logo.size, Some(3000));
+    assert!(prompt.contains("logo.png (binary image added, 3 KB)"), "{}", prompt);
+    assert!(!prompt.contains("README.md ("), "{}", prompt);
+}
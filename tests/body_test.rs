@@ -0,0 +1,59 @@
+use git_ai_commit::config::Config;
+use git_ai_commit::formatting::body::{commit_args, split, wrap, BODY_WIDTH};
+
+#[test]
+fn test_split_subject_and_body() {
+    assert_eq!(split("feat: add --body\n\n- ask for bullets\n"), ("feat: add --body", Some("- ask for bullets")));
+    assert_eq!(split("  fix: trim input  \n\n\n"), ("fix: trim input", None));
+    assert_eq!(split("docs: typo"), ("docs: typo", None));
+}
+
+#[test]
+fn test_commit_args_pass_the_body_as_its_own_paragraph() {
+    assert_eq!(
+        commit_args("feat: add --body\n\n- ask for bullets\n- wrap them"),
+        vec!["-m", "feat: add --body", "-m", "- ask for bullets\n- wrap them"]
+    );
+    assert_eq!(commit_args("docs: typo\n"), vec!["-m", "docs: typo"]);
+}
+
+#[test]
+fn test_wrap_keeps_bullets_aligned() {
+    // Given a bullet far longer than the width
+    let message = "feat(cli): add --body\n\
+                   - Ask the model for a subject line followed by bullet points that list the key changes of the commit\n\
+                   - Short bullet";
+    
+    // When
+    let wrapped = wrap(message, BODY_WIDTH);
+    
+    // Then the subject stays alone and continuation lines hang under the text
+    assert_eq!(
+        wrapped,
+        "feat(cli): add --body\n\n\
+         - Ask the model for a subject line followed by bullet points that list\n  \
+         the key changes of the commit\n\
+         - Short bullet"
+    );
+    assert!(wrapped.lines().all(|line| line.chars().count() <= BODY_WIDTH));
+}
+
+#[test]
+fn test_wrap_leaves_unbreakable_words_and_subjects_alone() {
+    let url = format!("https://example.com/{}", "a".repeat(80));
+    let message = format!("{}\n\nSee {} for details", "x".repeat(90), url);
+    
+    assert_eq!(wrap(&message, BODY_WIDTH), format!("{}\n\nSee\n{}\nfor details", "x".repeat(90), url));
+    assert_eq!(wrap("fix: no body", BODY_WIDTH), "fix: no body");
+}
+
+#[test]
+fn test_include_body_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    assert!(!Config::load_from_path(&config_path).unwrap().include_body);
+    
+    std::fs::write(&config_path, "include_body = true\n").unwrap();
+    assert!(Config::load_from_path(&config_path).unwrap().include_body);
+}
@@ -0,0 +1,245 @@
+use git_ai_commit::analysis::breaking::{self, BreakingChange, BreakingKind};
+use git_ai_commit::analysis::language::ProjectLayout;
+use git_ai_commit::config::{ContextBlocks, MessageStyle};
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::GitInfo;
+use std::path::PathBuf;
+use tempfile::{tempdir, TempDir};
+
+/// Layout of a repository holding one crate, a library when `library` is set
+fn crate_layout(library: bool) -> (TempDir, ProjectLayout) {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    std::fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+    std::fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"parser\"\n").unwrap();
+    if library {
+        std::fs::write(temp_dir.path().join("src/lib.rs"), "pub mod parser;\n").unwrap();
+    }
+    let layout = ProjectLayout::detect(temp_dir.path(), &[PathBuf::from("Cargo.toml")]);
+    (temp_dir, layout)
+}
+
+const RUST_PATCH: &str = "\
+diff --git a/src/parser.rs b/src/parser.rs
+index 1111111..2222222 100644
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -1,9 +1,9 @@
+-pub fn parse(input: &str) -> Ast {
++pub fn parse(input: &str, strict: bool) -> Ast {
+     Ast::default()
+ }
+
+-pub fn parse_lenient(input: &str) -> Ast {
+-    parse(input)
+-}
+-
+-pub struct Token;
++pub(crate) struct Token;
++
++pub fn tokenize(input: &str) -> Vec<Token> {
++    Vec::new()
++}
+diff --git a/src/lexer.rs b/src/lexer.rs
+index 3333333..4444444 100644
+--- a/src/lexer.rs
++++ b/src/lexer.rs
+@@ -1,3 +1,3 @@
+-pub fn tokenize(input: &str) -> Vec<Token> {
+-    Vec::new()
+-}
++fn skip_whitespace(input: &str) -> &str {
++    input.trim_start()
++}
+diff --git a/tests/parser_test.rs b/tests/parser_test.rs
+index 5555555..6666666 100644
+--- a/tests/parser_test.rs
++++ b/tests/parser_test.rs
+@@ -1,3 +0,0 @@
+-pub fn fixture() -> String {
+-    String::new()
+-}
+";
+
+#[test]
+fn test_removed_and_changed_rust_items_are_found() {
+    // When
+    let changes = breaking::detect(RUST_PATCH, |_| true);
+    
+    // Then
+    assert_eq!(changes, vec![
+        BreakingChange {
+            path: PathBuf::from("src/parser.rs"),
+            item: "fn parse".to_string(),
+            kind: BreakingKind::SignatureChanged {
+                before: "pub fn parse(input: &str) -> Ast".to_string(),
+                after: "pub fn parse(input: &str, strict: bool) -> Ast".to_string(),
+            },
+        },
+        BreakingChange {
+            path: PathBuf::from("src/parser.rs"),
+            item: "fn parse_lenient".to_string(),
+            kind: BreakingKind::Removed,
+        },
+        BreakingChange {
+            path: PathBuf::from("src/parser.rs"),
+            item: "struct Token".to_string(),
+            kind: BreakingKind::Removed,
+        },
+    ]);
+    assert_eq!(changes[1].describe(), "src/parser.rs: fn parse_lenient was removed or made private");
+}
+
+#[test]
+fn test_removed_and_changed_exports_are_found() {
+    // Given
+    let patch = "\
+diff --git a/src/api.ts b/src/api.ts
+index 1111111..2222222 100644
+--- a/src/api.ts
++++ b/src/api.ts
+@@ -1,7 +1,5 @@
+-export async function fetchUser(id: number): Promise<User> {
++export async function fetchUser(id: string): Promise<User> {
+   return get(`/users/${id}`);
+ }
+-export interface LegacyUser {
+-  name: string;
+-}
+-export const   DEFAULT_PAGE = 1;
++export const DEFAULT_PAGE = 1;
++export class Client {}
+diff --git a/src/api.test.ts b/src/api.test.ts
+deleted file mode 100644
+index 3333333..0000000
+--- a/src/api.test.ts
++++ /dev/null
+@@ -1 +0,0 @@
+-export function setup() {}
+";
+
+    // When
+    let changes: Vec<String> = breaking::detect(patch, |_| false).iter().map(BreakingChange::describe).collect();
+    
+    // Then
+    assert_eq!(changes, vec![
+        "src/api.ts: function fetchUser changed from `export async function fetchUser(id: number): Promise<User>` \
+         to `export async function fetchUser(id: string): Promise<User>`",
+        "src/api.ts: interface LegacyUser was removed or made private",
+    ]);
+}
+
+#[test]
+fn test_prompt_asks_to_mark_breaking_changes() {
+    // Given
+    let (_repo, layout) = crate_layout(true);
+    let git_info = GitInfo::from_patch(RUST_PATCH).unwrap();
+    let prompt_builder = PromptBuilder::new(10, 50).with_patch(Some(RUST_PATCH.to_string())).with_layout(layout);
+    
+    // When
+    let prompt = prompt_builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("Likely breaking changes to the public API:\n  - src/parser.rs: fn parse changed from"), "{}", prompt);
+    assert!(prompt.contains("  - src/parser.rs: struct Token was removed or made private\n"), "{}", prompt);
+    assert!(prompt.contains("BREAKING CHANGE: <what callers must change>"), "{}", prompt);
+    
+    let prompt = prompt_builder.clone()
+        .with_context_blocks(ContextBlocks { breaking: false, ..ContextBlocks::default() })
+        .build(&git_info);
+    assert!(!prompt.contains("BREAKING CHANGE"), "{}", prompt);
+    
+    // Styles without conventional headers have nowhere to put the marker
+    let prompt = prompt_builder.with_style(Some(MessageStyle::Gitmoji)).build(&git_info);
+    assert!(!prompt.contains("BREAKING CHANGE"), "{}", prompt);
+}
+
+#[test]
+fn test_binaries_and_private_modules_have_no_public_api() {
+    // Given
+    let patch = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +0,0 @@
+-pub fn run() {}
+diff --git a/src/bin/tool.rs b/src/bin/tool.rs
+index 1111111..2222222 100644
+--- a/src/bin/tool.rs
++++ b/src/bin/tool.rs
+@@ -1 +0,0 @@
+-pub struct Options;
+diff --git a/src/parser.rs b/src/parser.rs
+index 3333333..4444444 100644
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -10,9 +10,4 @@ pub fn parse(input: &str) -> Ast {
+ }
+
+-mod internal {
+-    pub fn helper() {}
+-}
+-
+ #[cfg(test)]
+ mod tests {
+-    pub fn fixture() -> Ast {
+-        Ast::default()
+-    }
+ }
+@@ -40,3 +35,2 @@ impl Ast {
+     pub fn len(&self) -> usize { 0 }
+-    pub fn is_empty(&self) -> bool { true }
+ }
+@@ -60,3 +54,2 @@ pub mod visit {
+     pub fn walk(ast: &Ast) {}
+-    pub fn walk_mut(ast: &mut Ast) {}
+ }
+";
+
+    // When
+    let changes: Vec<String> = breaking::detect(patch, |_| true).iter().map(BreakingChange::describe).collect();
+    
+    // Then only methods of public types and items of public modules count
+    assert_eq!(changes, vec![
+        "src/parser.rs: fn is_empty was removed or made private",
+        "src/parser.rs: fn walk_mut was removed or made private",
+    ]);
+}
+
+#[test]
+fn test_only_library_crates_are_checked() {
+    // Given
+    let (_repo, binary) = crate_layout(false);
+    let (_repo, library) = crate_layout(true);
+    
+    // When
+    let in_binary = breaking::detect(RUST_PATCH, |path| binary.is_rust_library(path));
+    let in_library = breaking::detect(RUST_PATCH, |path| library.is_rust_library(path));
+    
+    // Then
+    assert!(in_binary.is_empty(), "{:?}", in_binary);
+    assert_eq!(in_library.len(), 3);
+}
+
+#[test]
+fn test_no_note_without_breaking_changes() {
+    // Given
+    let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+ pub fn run() {}
++pub fn stop() {}
+-fn helper() {}
+";
+    let git_info = GitInfo::from_patch(patch).unwrap();
+    
+    // When
+    let prompt = PromptBuilder::new(10, 50).with_patch(Some(patch.to_string())).build(&git_info);
+    
+    // Then
+    assert!(breaking::detect(patch, |_| true).is_empty());
+    assert!(!prompt.contains("Likely breaking changes"), "{}", prompt);
+}
@@ -0,0 +1,87 @@
+use git_ai_commit::ollama::budget::{is_exhausted_error, CallBudget};
+use git_ai_commit::OllamaManager;
+use mockito::Server;
+use serde_json::json;
+
+#[test]
+fn test_budget_spends_up_to_the_limit() {
+    // Given
+    let budget = CallBudget::new(2);
+    
+    // When
+    let first = budget.spend();
+    let second = budget.spend();
+    let third = budget.spend();
+    
+    // Then
+    assert!(first.is_ok() && second.is_ok());
+    let error = third.unwrap_err();
+    assert!(is_exhausted_error(&error));
+    assert_eq!(budget.used(), 2);
+    assert_eq!(budget.remaining(), Some(0));
+    assert!(budget.is_exhausted());
+    assert_eq!(budget.display(), "2 of 2 model calls used");
+}
+
+#[test]
+fn test_budget_allows() {
+    let budget = CallBudget::new(3);
+    budget.spend().unwrap();
+    
+    assert!(budget.allows(2));
+    assert!(!budget.allows(3));
+}
+
+#[test]
+fn test_budget_reset() {
+    let budget = CallBudget::new(1);
+    budget.spend().unwrap();
+    assert!(budget.is_exhausted());
+    
+    budget.reset();
+    
+    assert_eq!(budget.used(), 0);
+    assert!(budget.spend().is_ok());
+}
+
+#[test]
+fn test_zero_budget_is_unlimited() {
+    let budget = CallBudget::new(0);
+    for _ in 0..100 {
+        budget.spend().unwrap();
+    }
+    
+    assert_eq!(budget.remaining(), None);
+    assert!(!budget.is_exhausted());
+    assert_eq!(budget.display(), "100 model calls used (no limit)");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_manager_stops_calling_the_model_when_the_budget_is_used() {
+    // Given a manager allowed two calls
+    let mut server = Server::new_async().await;
+    let mock = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "response": "fix: one" }).to_string())
+        .expect(2)
+        .create_async()
+        .await;
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    let manager = OllamaManager::new("llama3".to_string(), port)
+        .unwrap()
+        .with_call_budget(CallBudget::new(2));
+    
+    // When
+    manager.generate_commit_with_stats("prompt").await.unwrap();
+    let models = vec!["llama3".to_string(), "qwen2.5-coder".to_string()];
+    let results = manager.generate_with_models(&models, "prompt").await;
+    let after = manager.generate_commit("prompt").await;
+    
+    // Then only the first ensemble model was asked
+    assert!(results[0].1.is_ok());
+    assert!(is_exhausted_error(results[1].1.as_ref().unwrap_err()));
+    assert!(is_exhausted_error(&after.unwrap_err()));
+    mock.assert_async().await;
+}
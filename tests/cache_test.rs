@@ -0,0 +1,119 @@
+use clap::Parser;
+use git_ai_commit::cache::{CachedResponse, ResponseCache, MAX_AGE};
+use git_ai_commit::cli::{Args, CacheCommand, Command};
+use tempfile::tempdir;
+
+#[test]
+fn test_key_covers_model_diff_and_prompt() {
+    const OLLAMA: &str = "http://localhost:11434";
+    let key = ResponseCache::key("ollama", OLLAMA, "llama3", "+fn main() {}", "prompt");
+    
+    assert_eq!(key.len(), 64);
+    assert_eq!(key, ResponseCache::key("ollama", OLLAMA, "llama3", "+fn main() {}", "prompt"));
+    assert_ne!(key, ResponseCache::key("ollama", OLLAMA, "mistral", "+fn main() {}", "prompt"));
+    assert_ne!(key, ResponseCache::key("ollama", OLLAMA, "llama3", "+fn main() { }", "prompt"));
+    assert_ne!(key, ResponseCache::key("ollama", OLLAMA, "llama3", "+fn main() {}", "other template"));
+    assert_ne!(key, ResponseCache::key("ollama", "https://gpu.example.com", "llama3", "+fn main() {}", "prompt"));
+    assert_ne!(key, ResponseCache::key("openai", OLLAMA, "llama3", "+fn main() {}", "prompt"));
+    // Parts can't run into each other
+    assert_ne!(ResponseCache::key("", "", "ab", "c", ""), ResponseCache::key("", "", "a", "bc", ""));
+}
+
+#[test]
+fn test_cached_messages_round_trip() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let cache = ResponseCache::new(temp_dir.path().join("responses"));
+    let key = ResponseCache::key("ollama", "http://localhost:11434", "llama3", "diff", "prompt");
+    assert_eq!(cache.get(&key), None);
+    
+    // When
+    cache.put(&key, &CachedResponse::new("llama3", "feat: add cache")).unwrap();
+    cache.put(&key, &CachedResponse::new("llama3", "feat: cache answers")).unwrap();
+    
+    // Then the newest message wins
+    let cached = cache.get(&key).unwrap();
+    assert_eq!(cached.model, "llama3");
+    assert_eq!(cached.message, "feat: cache answers");
+    assert!(cached.created > 0);
+}
+
+#[test]
+fn test_refreshing_replaces_the_cached_message() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let cache = ResponseCache::new(temp_dir.path().join("responses"));
+    cache.put("a", &CachedResponse::new("llama3", "fix: old")).unwrap();
+    
+    // When: a --no-cache run doesn't see the old message but stores its own
+    let refreshing = cache.clone().with_refresh(true);
+    assert_eq!(refreshing.get("a"), None);
+    refreshing.put("a", &CachedResponse::new("llama3", "fix: new")).unwrap();
+    
+    // Then the next normal run reuses the new one
+    assert_eq!(cache.get("a").unwrap().message, "fix: new");
+}
+
+#[test]
+fn test_old_messages_are_dropped() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let cache = ResponseCache::new(temp_dir.path().join("responses")).with_max_entries(2);
+    let stale = CachedResponse { created: 0, ..CachedResponse::new("llama3", "fix: stale") };
+    cache.put("stale", &stale).unwrap();
+    
+    // When
+    for key in ["a", "b", "c"] {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.put(key, &CachedResponse::new("llama3", format!("fix: {}", key))).unwrap();
+    }
+    
+    // Then only the two most recently written are left
+    assert_eq!(cache.get("stale"), None);
+    assert_eq!(cache.get("a"), None);
+    assert_eq!(cache.get("b").unwrap().message, "fix: b");
+    assert_eq!(cache.get("c").unwrap().message, "fix: c");
+    assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 2);
+}
+
+#[test]
+fn test_expired_messages_are_not_reused() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let cache = ResponseCache::new(temp_dir.path().join("responses"));
+    let created = CachedResponse::new("llama3", "fix: a").created - MAX_AGE.as_secs() - 1;
+    
+    // When
+    cache.put("a", &CachedResponse { created, ..CachedResponse::new("llama3", "fix: a") }).unwrap();
+    
+    // Then
+    assert_eq!(cache.get("a"), None);
+}
+
+#[test]
+fn test_clear_removes_every_message() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let cache = ResponseCache::new(temp_dir.path().join("responses"));
+    assert_eq!(cache.clear().unwrap(), 0);
+    cache.put("a", &CachedResponse::new("llama3", "fix: a")).unwrap();
+    cache.put("b", &CachedResponse::new("llama3", "fix: b")).unwrap();
+    std::fs::write(cache.dir().join("c.json"), "{ broken").unwrap();
+    assert_eq!(cache.get("c"), None);
+    
+    // When
+    let removed = cache.clear().unwrap();
+    
+    // Then
+    assert_eq!(removed, 3);
+    assert_eq!(cache.get("a"), None);
+}
+
+#[test]
+fn test_cache_flags() {
+    let args = Args::try_parse_from(["git-ai-commit", "--no-cache"]).expect("Failed to parse args");
+    assert!(args.no_cache);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "cache", "clear"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Cache { action: CacheCommand::Clear })));
+}
@@ -0,0 +1,113 @@
+use git_ai_commit::config::{CapabilityOverrides, Config};
+use git_ai_commit::ollama::capabilities::parse_version;
+use git_ai_commit::ollama::{Capabilities, OllamaClient, OllamaClientTrait, OpenAiClient};
+use mockito::Server;
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_version() {
+    assert_eq!(parse_version("0.5.7"), Some((0, 5, 7)));
+    assert_eq!(parse_version("v0.1.32-rc1"), Some((0, 1, 32)));
+    assert_eq!(parse_version("0.6"), Some((0, 6, 0)));
+    assert_eq!(parse_version("0.0.0+dev"), Some((0, 0, 0)));
+    assert_eq!(parse_version("unknown"), None);
+}
+
+#[test]
+fn test_ollama_features_follow_the_version() {
+    let old = Capabilities::for_ollama(Some("0.1.20"));
+    assert!(old.streaming && old.json_mode);
+    assert!(!old.embeddings && !old.json_schema);
+    
+    let current = Capabilities::for_ollama(Some("0.5.7"));
+    assert!(current.json_mode && current.json_schema && current.embeddings && current.system_messages);
+    
+    // A server that won't say what it is gets only what every version had
+    let unknown = Capabilities::for_ollama(None);
+    assert_eq!(unknown, Capabilities { streaming: true, ..Capabilities::BASIC });
+}
+
+#[test]
+fn test_overrides_win_over_detection() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = r#"
+        [capabilities]
+        json_mode = false
+        max_context = 8192
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    // When
+    let config = Config::load_from_path(&config_path).unwrap();
+    let capabilities = Capabilities::for_ollama(Some("0.5.7")).with_overrides(&config.capabilities);
+    
+    // Then only what the config sets changes
+    assert!(!capabilities.json_mode);
+    assert!(capabilities.json_schema);
+    assert_eq!(capabilities.max_context, Some(8192));
+    assert_eq!(Capabilities::BASIC.with_overrides(&CapabilityOverrides::default()), Capabilities::BASIC);
+}
+
+#[test]
+fn test_zero_max_context_is_rejected() {
+    let mut config = Config::default();
+    config.capabilities.max_context = Some(0);
+    
+    assert!(config.check().is_err());
+}
+
+#[test]
+fn test_describe() {
+    let capabilities = Capabilities { streaming: true, json_mode: true, max_context: Some(4096), ..Capabilities::BASIC };
+    
+    assert_eq!(capabilities.describe(), "streaming, json mode, 4096-token context");
+    assert_eq!(Capabilities::BASIC.describe(), "plain completions only");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ollama_client_reports_its_capabilities() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _m_version = server
+        .mock("GET", "/api/version")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "version": "0.3.12" }).to_string())
+        .create_async()
+        .await;
+    let _m_show = server
+        .mock("POST", "/api/show")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "parameters": "num_ctx 8192", "model_info": { "llama.context_length": 131072 } }).to_string())
+        .create_async()
+        .await;
+    let _m_tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "models": [] }).to_string())
+        .create_async()
+        .await;
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    
+    // When
+    let capabilities = OllamaClient::new(port).capabilities("llama3.2:1b").await;
+    
+    // Then
+    assert_eq!(capabilities, Capabilities { max_context: Some(8192), ..Capabilities::for_ollama(Some("0.3.12")) });
+    assert!(capabilities.embeddings && !capabilities.json_schema);
+}
+
+#[tokio::test]
+async fn test_compatible_servers_are_assumed_minimal() {
+    let hosted = OpenAiClient::new("https://api.openai.com/v1", None).capabilities("gpt-4o-mini").await;
+    assert!(hosted.json_mode && hosted.system_messages);
+    assert!(!hosted.streaming);
+    
+    let compatible = OpenAiClient::new("http://localhost:1234/v1", None).capabilities("qwen2.5-coder").await;
+    assert_eq!(compatible, Capabilities { system_messages: true, ..Capabilities::BASIC });
+}
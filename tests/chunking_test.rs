@@ -0,0 +1,93 @@
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::diff::FileStat;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+
+/// A commit with `staged` staged files and one unstaged file
+fn git_info_with_files(staged: usize) -> GitInfo {
+    let files: Vec<PathBuf> = (0..staged).map(|i| PathBuf::from(format!("src/module_{}.rs", i))).collect();
+    let mut file_changes: Vec<FileChange> = files.iter()
+        .map(|f| FileChange {
+            change_type: ChangeType::Modified,
+            file_path: f.clone(),
+            old_path: None,
+            ..Default::default()
+        })
+        .collect();
+    file_changes.push(FileChange {
+        change_type: ChangeType::Modified,
+        file_path: PathBuf::from("README.md"),
+        old_path: None,
+        ..Default::default()
+    });
+    let file_stats: Vec<FileStat> = files.iter()
+        .map(|f| FileStat {
+            filename: f.to_string_lossy().to_string(),
+            insertions: 2,
+            deletions: 1,
+            ..Default::default()
+        })
+        .collect();
+    
+    GitInfo {
+        status: GitStatus {
+            staged_files: files,
+            modified_files: vec![PathBuf::from("README.md")],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: staged,
+            insertions: staged * 2,
+            deletions: staged,
+            file_stats,
+        },
+        file_changes,
+        untracked_files: vec![],
+        branch_name: "feature/refactor".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_staged_chunks() {
+    // Given
+    let git_info = git_info_with_files(5);
+    
+    // When
+    let chunks = git_info.staged_chunks(2);
+    
+    // Then: only staged files are split, each chunk with its own stats
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[0].status.staged_files, vec![PathBuf::from("src/module_0.rs"), PathBuf::from("src/module_1.rs")]);
+    assert_eq!(chunks[2].status.staged_files, vec![PathBuf::from("src/module_4.rs")]);
+    assert_eq!(chunks[0].diff_stat.files_changed, 2);
+    assert_eq!(chunks[0].diff_stat.insertions, 4);
+    assert!(chunks.iter().all(|c| c.status.modified_files.is_empty()));
+    assert!(chunks.iter().all(|c| c.branch_name == "feature/refactor"));
+}
+
+#[test]
+fn test_small_commit_is_one_chunk() {
+    assert_eq!(git_info_with_files(3).staged_chunks(10).len(), 1);
+}
+
+#[test]
+fn test_chunk_and_synthesis_prompts() {
+    let git_info = git_info_with_files(3);
+    let builder = PromptBuilder::new(2, 100);
+    let chunks = git_info.staged_chunks(2);
+    
+    let chunk_prompt = builder.build_chunk_summary(&chunks[1], 2, 2);
+    assert!(chunk_prompt.contains("part 2 of 2"));
+    assert!(chunk_prompt.contains("src/module_2.rs"));
+    assert!(!chunk_prompt.contains("src/module_0.rs"));
+    
+    let partials = vec!["Split parser into modules".to_string(), "Update module docs".to_string()];
+    let prompt = builder.build_synthesis(&git_info, &partials);
+    assert!(prompt.contains("Diff summary: 3 files changed, 6 insertions(+), 3 deletions(-)"));
+    assert!(prompt.contains("Part 1: Split parser into modules"));
+    assert!(prompt.contains("Part 2: Update module docs"));
+    assert!(prompt.contains("Generate only the commit message"));
+}
@@ -0,0 +1,98 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::OllamaManager;
+use mockito::Server;
+use serde_json::json;
+
+fn port_of(server: &Server) -> u16 {
+    server.url().rsplit(':').next().unwrap().parse().unwrap()
+}
+
+/// A port nothing is listening on
+fn closed_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+#[test]
+fn test_ci_preset() {
+    // Given
+    let mut args = Args::try_parse_from(["git-ai-commit", "--ci"]).expect("Failed to parse args");
+    args.no_confirm = false;
+    args.config.history = true;
+    args.config.daemon.keep_warm = true;
+    args.edit = true;
+    
+    // When
+    args.apply_ci_preset();
+    
+    // Then
+    assert!(args.json);
+    assert!(args.no_confirm);
+    assert!(args.no_stream);
+    assert!(!args.config.history);
+    assert!(!args.config.daemon.keep_warm);
+    assert!(!args.edit);
+    assert!(!args.no_write, "CI runs still commit");
+    assert!(!args.dry_run);
+}
+
+#[test]
+fn test_ci_preset_needs_the_flag() {
+    let mut args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    args.config.history = true;
+    
+    args.apply_ci_preset();
+    
+    assert!(!args.json);
+    assert!(args.config.history);
+}
+
+#[test]
+fn test_ci_conflicts_with_interactive_and_raw_output() {
+    assert!(Args::try_parse_from(["git-ai-commit", "--ci", "--confirm"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--ci", "--raw"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "commit", "--ci", "--add-unstaged"]).is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_attach_only_manager_does_not_start_a_server() {
+    // Given
+    let port = closed_port();
+    let mut manager = OllamaManager::new("llama3".to_string(), port)
+        .unwrap()
+        .with_attach_only(true);
+    
+    // When
+    let result = manager.ensure_running().await;
+    
+    // Then
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains(&format!("No Ollama server is running on port {}", port)), "{}", error);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_attach_only_manager_does_not_download_models() {
+    // Given a running server without the model
+    let mut server = Server::new_async().await;
+    let _tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "models": [{ "name": "llama3:latest" }] }).to_string())
+        .create_async()
+        .await;
+    let pull = server.mock("POST", "/api/pull").expect(0).create_async().await;
+    let mut manager = OllamaManager::new("qwen2.5-coder".to_string(), port_of(&server))
+        .unwrap()
+        .with_attach_only(true);
+    
+    // When
+    manager.ensure_running().await.unwrap();
+    let result = manager.ensure_model_available("qwen2.5-coder").await;
+    
+    // Then
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("never downloads models"), "{}", error);
+    pull.assert_async().await;
+}
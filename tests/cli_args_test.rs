@@ -1,4 +1,5 @@
-use git_ai_commit::cli::Args;
+use git_ai_commit::cli::{Args, Command, ConfigCommand, DaemonCommand, HistoryCommand, HookCommand, ModelsCommand};
+use git_ai_commit::git::DisplayFormat;
 use std::path::PathBuf;
 use clap::Parser;
 
@@ -13,7 +14,8 @@ fn test_default_args() {
     assert!(args.no_confirm, "no_confirm should be true by default (no confirmation needed)");
     assert!(args.template.is_none());
     assert!(!args.dry_run);
-    assert!(!args.verbose);
+    assert_eq!(args.verbose, 0);
+    assert!(!args.quiet);
     assert_eq!(args.port, 11434);
     assert_eq!(args.timeout_seconds, 60);
     assert!(!args.add_unstaged);
@@ -79,11 +81,22 @@ fn test_debug_options() {
     
     // Test verbose (long form)
     let args = Args::try_parse_from(["git-ai-commit", "--verbose"]).expect("Failed to parse args");
-    assert!(args.verbose);
+    assert_eq!(args.verbose, 1);
     
     // Test verbose (short form)
     let args = Args::try_parse_from(["git-ai-commit", "-v"]).expect("Failed to parse args");
-    assert!(args.verbose);
+    assert_eq!(args.verbose, 1);
+    
+    // Repeated for more detail
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "-vv"]).expect("Failed to parse args");
+    assert_eq!(args.verbose, 2);
+    
+    // Quiet, which can't be combined with verbose
+    let args = Args::try_parse_from(["git-ai-commit", "-q"]).expect("Failed to parse args");
+    assert!(args.quiet);
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "--quiet"]).expect("Failed to parse args");
+    assert!(args.quiet);
+    assert!(Args::try_parse_from(["git-ai-commit", "-q", "-v"]).is_err());
 }
 
 #[test]
@@ -110,6 +123,16 @@ fn test_staging_options() {
     // Test add unstaged
     let args = Args::try_parse_from(["git-ai-commit", "--add-unstaged"]).expect("Failed to parse args");
     assert!(args.add_unstaged);
+    
+    // Test picking the files to stage
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "--interactive-stage"]).expect("Failed to parse args");
+    assert!(args.interactive_stage);
+    assert!(!args.add_unstaged);
+    
+    // Picking needs a person at the terminal and an index to change
+    for other in ["--add-unstaged", "--ci", "--dry-run", "--amend"] {
+        assert!(Args::try_parse_from(["git-ai-commit", "--interactive-stage", other]).is_err(), "{}", other);
+    }
 }
 
 #[test]
@@ -135,7 +158,7 @@ fn test_combined_options() {
     assert!(!args.no_confirm, "--confirm should set no_confirm to false");
     assert_eq!(args.template, Some(PathBuf::from("custom.tpl")));
     assert!(args.dry_run);
-    assert!(args.verbose);
+    assert_eq!(args.verbose, 1);
     assert_eq!(args.port, 12345);
     assert_eq!(args.timeout_seconds, 120);
     assert!(args.add_unstaged);
@@ -151,3 +174,330 @@ fn test_list_models_flag() {
     let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
     assert!(!args.list_models);
 }
+
+#[test]
+fn test_stdin_diff_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--stdin-diff"]).expect("Failed to parse args");
+    assert!(args.stdin_diff);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.stdin_diff);
+}
+
+#[test]
+fn test_push_summary_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "push-summary"]).expect("Failed to parse args");
+    match args.command {
+        Some(Command::PushSummary { range, install_hook }) => {
+            assert_eq!(range, "@{u}..HEAD");
+            assert!(!install_hook);
+        }
+        other => panic!("Unexpected command: {:?}", other),
+    }
+    
+    let args = Args::try_parse_from(["git-ai-commit", "push-summary", "--range", "main..HEAD", "--install-hook"])
+        .expect("Failed to parse args");
+    assert!(matches!(
+        args.command,
+        Some(Command::PushSummary { ref range, install_hook: true }) if range == "main..HEAD"
+    ));
+    
+    // No subcommand keeps the default generate flow
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(args.command.is_none());
+}
+
+#[test]
+fn test_install_hook_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "install-hook"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::InstallHook { uninstall: false })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "install-hook", "--uninstall"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::InstallHook { uninstall: true })));
+}
+
+#[test]
+fn test_daemon_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "daemon", "list"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Daemon { action: DaemonCommand::List })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "daemon", "stop", "--port", "11500"])
+        .expect("Failed to parse args");
+    assert!(matches!(
+        args.command,
+        Some(Command::Daemon { action: DaemonCommand::Stop { port: Some(11500) } })
+    ));
+    
+    assert!(Args::try_parse_from(["git-ai-commit", "daemon"]).is_err());
+}
+
+#[test]
+fn test_report_bug_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "report-bug"]).expect("Failed to parse args");
+    match args.command {
+        Some(Command::ReportBug { output, issue }) => {
+            assert_eq!(output, PathBuf::from("git-ai-commit-report.tar"));
+            assert!(!issue);
+        }
+        other => panic!("Unexpected command: {:?}", other),
+    }
+    
+    let args = Args::try_parse_from(["git-ai-commit", "report-bug", "--issue"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::ReportBug { issue: true, .. })));
+}
+
+#[test]
+fn test_prepare_only_flag() {
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert_eq!(args.prepare_only, None);
+    
+    // Without a value the message goes to .git/COMMIT_EDITMSG
+    let args = Args::try_parse_from(["git-ai-commit", "--prepare-only"]).expect("Failed to parse args");
+    assert_eq!(args.prepare_only, Some(None));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "--prepare-only", "msg.txt"]).expect("Failed to parse args");
+    assert_eq!(args.prepare_only, Some(Some(PathBuf::from("msg.txt"))));
+}
+
+#[test]
+fn test_hook_flags() {
+    let args = Args::try_parse_from(["git-ai-commit", "--hook", ".git/COMMIT_EDITMSG", "--hook-source", "message"])
+        .expect("Failed to parse args");
+    assert_eq!(args.hook, Some(PathBuf::from(".git/COMMIT_EDITMSG")));
+    assert_eq!(args.hook_source.as_deref(), Some("message"));
+    
+    // The source is only meaningful together with the message file
+    assert!(Args::try_parse_from(["git-ai-commit", "--hook-source", "message"]).is_err());
+}
+
+#[test]
+fn test_json_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--json"]).expect("Failed to parse args");
+    assert!(args.json);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.json);
+}
+
+#[test]
+fn test_ensemble_flags() {
+    let args = Args::try_parse_from(["git-ai-commit", "--ensemble", "llama3,qwen2.5-coder", "--judge", "gemma3:4b"])
+        .expect("Failed to parse args");
+    assert_eq!(args.ensemble, vec!["llama3", "qwen2.5-coder"]);
+    assert_eq!(args.judge.as_deref(), Some("gemma3:4b"));
+    
+    // A judge without candidates to judge makes no sense
+    assert!(Args::try_parse_from(["git-ai-commit", "--judge", "gemma3:4b"]).is_err());
+}
+
+#[test]
+fn test_candidates_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--candidates", "3", "--model", "llama3"])
+        .expect("Failed to parse args");
+    assert_eq!(args.candidates, Some(3));
+    assert_eq!(args.candidate_models(), vec!["llama3", "llama3", "llama3"]);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "--ensemble", "llama3,qwen2.5-coder"]).expect("Failed to parse args");
+    assert_eq!(args.candidate_models(), vec!["llama3", "qwen2.5-coder"]);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(args.candidate_models().is_empty());
+    
+    assert!(Args::try_parse_from(["git-ai-commit", "--candidates", "1"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--candidates", "11"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--candidates", "3", "--ensemble", "llama3,gemma3:4b"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--candidates", "3", "--quick"]).is_err());
+}
+
+#[test]
+fn test_profile_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--profile", "work"]).expect("Failed to parse args");
+    assert_eq!(args.profile.as_deref(), Some("work"));
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert_eq!(args.profile, None);
+}
+
+#[test]
+fn test_copy_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--copy", "--dry-run"]).expect("Failed to parse args");
+    assert!(args.copy);
+    assert!(args.dry_run);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.copy);
+}
+
+#[test]
+fn test_quick_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--quick"]).expect("Failed to parse args");
+    assert!(args.quick);
+    
+    // Quick mode picks its own model
+    assert!(Args::try_parse_from(["git-ai-commit", "--quick", "--model", "llama3"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--quick", "--ensemble", "llama3,gemma3:4b"]).is_err());
+}
+
+#[test]
+fn test_provider_flag() {
+    use git_ai_commit::config::Provider;
+    
+    let args = Args::try_parse_from(["git-ai-commit", "--provider", "openai"]).expect("Failed to parse args");
+    assert_eq!(args.provider, Some(Provider::OpenAi));
+    assert_eq!(args.provider(), Provider::OpenAi);
+    
+    // Without the flag the config decides
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert_eq!(args.provider(), Provider::Ollama);
+    
+    assert!(Args::try_parse_from(["git-ai-commit", "--provider", "anthropic"]).is_err());
+}
+
+#[test]
+fn test_push_flags() {
+    let args = Args::try_parse_from(["git-ai-commit", "--push", "--remote", "fork", "--set-upstream"])
+        .expect("Failed to parse args");
+    assert!(args.push);
+    assert_eq!(args.remote.as_deref(), Some("fork"));
+    assert!(args.set_upstream);
+    
+    // Remote selection only applies when pushing
+    assert!(Args::try_parse_from(["git-ai-commit", "--remote", "fork"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--set-upstream"]).is_err());
+}
+
+#[test]
+fn test_no_stream_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--no-stream"]).expect("Failed to parse args");
+    assert!(args.no_stream);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.no_stream);
+}
+
+#[test]
+fn test_edit_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--edit"]).expect("Failed to parse args");
+    assert!(args.edit);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "-e"]).expect("Failed to parse args");
+    assert!(args.edit);
+    
+    // CI runs never wait on an editor
+    assert!(Args::try_parse_from(["git-ai-commit", "--ci", "--edit"]).is_err());
+}
+
+#[test]
+fn test_body_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--body"]).expect("Failed to parse args");
+    assert!(args.body);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "--body"]).expect("Failed to parse args");
+    assert!(args.body);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.body);
+}
+
+#[test]
+fn test_amend_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--amend"]).expect("Failed to parse args");
+    assert!(args.amend);
+    
+    // Amending only rewords HEAD, so staging, pushing and stdin input don't apply
+    assert!(Args::try_parse_from(["git-ai-commit", "--amend", "--add-unstaged"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--amend", "--push"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--amend", "--stdin-diff"]).is_err());
+}
+
+#[test]
+fn test_generate_and_commit_subcommands() {
+    let args = Args::try_parse_from(["git-ai-commit", "generate"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Generate)));
+    
+    // Options work before and after the subcommand name
+    let args = Args::try_parse_from(["git-ai-commit", "generate", "--dry-run", "--model", "llama3"])
+        .expect("Failed to parse args");
+    assert!(args.dry_run);
+    assert_eq!(args.model, "llama3");
+    
+    let args = Args::try_parse_from(["git-ai-commit", "--add-unstaged", "commit", "-f", "20"])
+        .expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Commit)));
+    assert!(args.add_unstaged);
+    assert_eq!(args.max_files, 20);
+}
+
+#[test]
+fn test_models_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "models", "list"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Models { action: ModelsCommand::List })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "models", "pull", "qwen2.5-coder"])
+        .expect("Failed to parse args");
+    match args.command {
+        Some(Command::Models { action: ModelsCommand::Pull { ref name } }) => assert_eq!(name, "qwen2.5-coder"),
+        other => panic!("Unexpected command: {:?}", other),
+    }
+    
+    assert!(Args::try_parse_from(["git-ai-commit", "models", "pull"]).is_err());
+    
+    let args = Args::try_parse_from(["git-ai-commit", "models", "rm", "llama3.2"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Models { action: ModelsCommand::Rm { ref name } }) if name == "llama3.2"));
+    let args = Args::try_parse_from(["git-ai-commit", "models", "delete", "llama3.2"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Models { action: ModelsCommand::Rm { .. } })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "models", "info", "llama3.2", "--json"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Models { action: ModelsCommand::Info { ref name } }) if name == "llama3.2"));
+    assert!(args.json);
+}
+
+#[test]
+fn test_notes_flag_and_history_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "--notes"]).expect("Failed to parse args");
+    assert!(args.notes);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "history", "show", "a1b2c3d"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::History { action: HistoryCommand::Show { ref commit } }) if commit == "a1b2c3d"));
+    
+    // The last commit unless one is given
+    let args = Args::try_parse_from(["git-ai-commit", "history", "show"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::History { action: HistoryCommand::Show { ref commit } }) if commit == "HEAD"));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "history", "export", "--format", "json"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::History { action: HistoryCommand::Export })));
+    assert_eq!(args.format, DisplayFormat::Json);
+}
+
+#[test]
+fn test_config_hook_and_doctor_subcommands() {
+    let args = Args::try_parse_from(["git-ai-commit", "config", "show"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Config { action: ConfigCommand::Show })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "config", "init", "--no-write"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Config { action: ConfigCommand::Init })));
+    assert!(args.no_write);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "config", "set", "alias.wip", "--quick --add-unstaged"])
+        .expect("Failed to parse args");
+    match args.command {
+        Some(Command::Config { action: ConfigCommand::Set { ref key, ref value } }) => {
+            assert_eq!(key, "alias.wip");
+            assert_eq!(value, "--quick --add-unstaged");
+        }
+        other => panic!("Unexpected command: {:?}", other),
+    }
+    assert!(!args.quick);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "config", "get", "validation.retries"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Config { action: ConfigCommand::Get { .. } })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "config", "list"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Config { action: ConfigCommand::List })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "hook", "uninstall"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Hook { action: HookCommand::Uninstall })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "doctor"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Doctor)));
+}
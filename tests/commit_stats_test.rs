@@ -0,0 +1,55 @@
+mod common;
+
+use common::git;
+use git_ai_commit::git::{CommitStats, GitCollector};
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_show_output() {
+    let show = "a1b2c3d\tHEAD -> feature/login, origin/main\n\n3\t1\tsrc/main.rs\n10\t0\tsrc/login.rs\n";
+    
+    let stats = CommitStats::parse_show(show).unwrap();
+    
+    assert_eq!(stats.sha, "a1b2c3d");
+    assert_eq!(stats.branch, "feature/login");
+    assert_eq!(stats.files_changed, 2);
+    assert_eq!(stats.insertions, 13);
+    assert_eq!(stats.deletions, 1);
+    assert_eq!(stats.shortstat(), "2 files changed, 13 insertions(+), 1 deletions(-)");
+    assert_eq!((stats.ahead, stats.behind), (None, None));
+}
+
+#[test]
+fn test_ahead_behind() {
+    let stats = CommitStats::parse_show("a1b2c3d\tHEAD -> main\n").unwrap();
+    
+    let stats = stats.with_ahead_behind("1\t3\n");
+    
+    assert_eq!(stats.ahead, Some(3));
+    assert_eq!(stats.behind, Some(1));
+    assert!(stats.display().contains("3 ahead, 1 behind"));
+}
+
+#[tokio::test]
+async fn test_commit_stats_from_repository() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    
+    git(repo_path, &["init", "-b", "main"]);
+    std::fs::write(repo_path.join("one.txt"), "one\ntwo\n").unwrap();
+    std::fs::write(repo_path.join("two.txt"), "three\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Initial commit"]);
+    
+    let stats = GitCollector::new(repo_path.to_path_buf()).get_commit_stats().await.unwrap();
+    
+    assert!(!stats.sha.is_empty());
+    assert_eq!(stats.branch, "main");
+    assert_eq!(stats.files_changed, 2);
+    assert_eq!(stats.insertions, 3);
+    assert_eq!(stats.deletions, 0);
+    
+    // No upstream is configured in a fresh repository
+    assert_eq!(stats.ahead, None);
+    assert!(stats.display().contains("Upstream: none configured"));
+}
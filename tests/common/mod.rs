@@ -0,0 +1,35 @@
+// Each test crate uses only some of these
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Author and committer of the commits the tests make
+pub const TEST_USER: (&str, &str) = ("Test", "test@example.com");
+
+fn run(repo_path: &Path, author: (&str, &str), args: &[&str]) -> Output {
+    Command::new("git")
+        .args(["-c", &format!("user.name={}", author.0), "-c", &format!("user.email={}", author.1)])
+        .args(["-c", "init.defaultBranch=main"])
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .expect("Failed to run git")
+}
+
+/// Run git in `repo_path` and return what it printed; panics if it fails
+pub fn git(repo_path: &Path, args: &[&str]) -> String {
+    git_as(repo_path, TEST_USER, args)
+}
+
+/// [`git`] with `author` making any commits
+pub fn git_as(repo_path: &Path, author: (&str, &str), args: &[&str]) -> String {
+    let output = run(repo_path, author, args);
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Whether git succeeded, for commands that are expected to stop on a conflict
+pub fn git_succeeds(repo_path: &Path, args: &[&str]) -> bool {
+    run(repo_path, TEST_USER, args).status.success()
+}
@@ -1,4 +1,5 @@
-use git_ai_commit::config::Config;
+use git_ai_commit::config::{normalize_remote, repo_config_path, Backend, CommitStyle, Config, GitBackendKind, MessageStyle, Provider, HookFallback, HookMode, Redaction, RetryStrategy, REPO_CONFIG_FILE};
+use git_ai_commit::git::ChangeCategory;
 use std::fs;
 
 #[test]
@@ -93,9 +94,11 @@ fn test_save_and_load_config() {
     // Test saving and then loading a config
     let _temp_dir = tempfile::tempdir().unwrap();
     
-    let mut config = Config::default();
-    config.model = "custom-model".to_string();
-    config.max_files = 42;
+    let config = Config {
+        model: "custom-model".to_string(),
+        max_files: 42,
+        ..Config::default()
+    };
     
     // Save the config
     config.save().unwrap();
@@ -114,3 +117,416 @@ fn test_save_and_load_config() {
         fs::remove_file(saved_config_path).unwrap();
     }
 }
+
+#[test]
+fn test_category_templates_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config_content = r#"
+        model = "llama3"
+        
+        [templates]
+        docs = "Write a docs commit for:\n{CONTEXT}"
+        ci = "Write a ci commit for:\n{CONTEXT}"
+    "#;
+    
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.templates.get(ChangeCategory::Docs), Some("Write a docs commit for:\n{CONTEXT}"));
+    assert_eq!(config.templates.get(ChangeCategory::Ci), Some("Write a ci commit for:\n{CONTEXT}"));
+    assert_eq!(config.templates.get(ChangeCategory::Code), None);
+}
+
+#[test]
+fn test_hook_mode_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    // Defaults to merging with messages the user already started
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.hook_mode, HookMode::Merge);
+    assert_eq!(config.hook_timeout_seconds, 8);
+    assert_eq!(config.hook_timeout_fallback, HookFallback::Heuristic);
+    
+    std::fs::write(&config_path, "hook_mode = \"keep\"\nhook_timeout_seconds = 3\nhook_timeout_fallback = \"skip\"\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.hook_mode, HookMode::Keep);
+    assert_eq!(config.hook_timeout_seconds, 3);
+    assert_eq!(config.hook_timeout_fallback, HookFallback::Skip);
+}
+
+#[test]
+fn test_validation_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.validation.max_subject_length, 72);
+    assert_eq!(config.validation.retries, 2);
+    assert_eq!(config.validation.retry_strategy, RetryStrategy::Feedback);
+    assert_eq!(config.validation.commit_style, CommitStyle::Plain);
+    
+    let config_content = r#"
+        [validation]
+        retries = 0
+        retry_strategy = "resample"
+        commit_style = "conventional"
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.validation.max_subject_length, 72);
+    assert_eq!(config.validation.retries, 0);
+    assert_eq!(config.validation.retry_strategy, RetryStrategy::Resample);
+    assert_eq!(config.validation.commit_style, CommitStyle::Conventional);
+}
+
+#[test]
+fn test_daemon_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(!config.daemon.keep_warm);
+    assert_eq!(config.daemon.idle_timeout().as_secs(), 15 * 60);
+    assert_eq!(config.daemon.start_timeout().as_secs(), 30);
+    
+    std::fs::write(&config_path, "[daemon]\nkeep_warm = true\nidle_timeout_minutes = 5\nstart_timeout_seconds = 90\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(config.daemon.keep_warm);
+    assert_eq!(config.daemon.idle_timeout().as_secs(), 300);
+    assert_eq!(config.daemon.start_timeout().as_secs(), 90);
+    
+    let mut config = Config::default();
+    assert!(config.set_key("daemon.start_timeout_seconds", "0").is_err());
+}
+
+#[test]
+fn test_scoring_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.scoring.validation, 1.0);
+    assert_eq!(config.scoring.specificity, 0.5);
+    assert_eq!(config.scoring.judge, 0.0);
+    
+    std::fs::write(&config_path, "[scoring]\nspecificity = 0.0\njudge = 2.0\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.scoring.validation, 1.0);
+    assert_eq!(config.scoring.specificity, 0.0);
+    assert_eq!(config.scoring.judge, 2.0);
+    
+    let mut config = Config::default();
+    config.set_key("scoring.judge", "1.5").unwrap();
+    assert_eq!(config.scoring.judge, 1.5);
+    assert!(config.set_key("scoring.specificity", "-1").is_err());
+}
+
+#[test]
+fn test_large_context_model_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.large_context_model, None);
+    
+    std::fs::write(&config_path, "large_context_model = \"qwen2.5-coder:7b\"\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.large_context_model.as_deref(), Some("qwen2.5-coder:7b"));
+    
+    let mut config = Config::default();
+    config.set_key("large_context_model", "llama3.1:8b").unwrap();
+    assert_eq!(config.get_key("large_context_model").unwrap(), "llama3.1:8b");
+}
+
+#[test]
+fn test_git_backend_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.git_backend, GitBackendKind::Cli);
+    
+    std::fs::write(&config_path, "git_backend = \"libgit2\"\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.git_backend, GitBackendKind::Libgit2);
+    
+    std::fs::write(&config_path, "git_backend = \"gix\"\n").unwrap();
+    assert!(Config::load_from_path(&config_path).is_err());
+}
+
+#[test]
+fn test_max_model_calls_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.max_model_calls, 12);
+    
+    std::fs::write(&config_path, "max_model_calls = 0\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.max_model_calls, 0);
+}
+
+#[test]
+fn test_context_blocks_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    // Every block but the stat graph is on by default
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(config.context.branch && config.context.staged && config.context.diff && config.context.history);
+    assert!(!config.context.stat_graph);
+    
+    std::fs::write(&config_path, "[context]\nunstaged = false\nhistory = false\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(!config.context.unstaged);
+    assert!(!config.context.history);
+    assert!(config.context.staged && config.context.untracked && config.context.stats);
+}
+
+#[test]
+fn test_backends_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config_content = r#"
+        [[backends]]
+        port = 11500
+        model = "llama3"
+        
+        [[backends]]
+        model = "gemma3:1b"
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.backends, vec![Backend::new(11500, "llama3"), Backend::new(11434, "gemma3:1b")]);
+}
+
+#[test]
+fn test_openai_provider_config() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.provider, Provider::Ollama);
+    assert_eq!(config.openai.base_url, "https://api.openai.com/v1");
+    assert_eq!(config.openai.api_key_env, "OPENAI_API_KEY");
+    
+    let config_content = r#"
+        provider = "openai"
+        
+        [openai]
+        base_url = "http://localhost:1234/v1"
+        api_key = "sk-team"
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.provider, Provider::OpenAi);
+    assert_eq!(config.openai.base_url, "http://localhost:1234/v1");
+    assert_eq!(config.openai.api_key().as_deref(), Some("sk-team"));
+}
+
+#[test]
+fn test_profiles_selected_by_remote() {
+    // Given
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = r#"
+        model = "gemma3:4b"
+        
+        [profiles.work]
+        remotes = ["github.com/acme/*", "gitlab.acme.internal/*"]
+        model = "qwen2.5-coder"
+        style = "gitmoji"
+        redaction = "strict"
+        trailers = ["Refs: ACME"]
+        
+        [profiles.work.validation]
+        banned_words = ["Project Falcon"]
+        
+        [profiles.personal]
+        remotes = ["github.com/me/*"]
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    let mut config = Config::load_from_path(&config_path).unwrap();
+    
+    // When
+    let work = config.profile_for_remote("git@github.com:acme/api.git");
+    let https = config.profile_for_remote("https://ci@gitlab.acme.internal/platform/infra.git");
+    let personal = config.profile_for_remote("https://github.com/me/dotfiles");
+    let none = config.profile_for_remote("https://github.com/acme-corp/api.git");
+    
+    // Then
+    assert_eq!(work, Some("work"));
+    assert_eq!(https, Some("work"));
+    assert_eq!(personal, Some("personal"));
+    assert_eq!(none, None);
+    
+    assert_eq!(config.redaction, Redaction::Off);
+    config.apply_profile("work").unwrap();
+    assert_eq!(config.model, "qwen2.5-coder");
+    assert_eq!(config.style, Some(MessageStyle::Gitmoji));
+    assert_eq!(config.redaction, Redaction::Strict);
+    assert_eq!(config.trailers, vec!["Refs: ACME".to_string()]);
+    assert_eq!(config.validation.banned_words, vec!["Project Falcon".to_string()]);
+    assert_eq!(config.validation.max_subject_length, 72);
+    assert!(config.apply_profile("missing").is_err());
+}
+
+#[test]
+fn test_normalize_remote() {
+    assert_eq!(normalize_remote("git@github.com:Acme/API.git"), "github.com/acme/api");
+    assert_eq!(normalize_remote("https://github.com/acme/api/"), "github.com/acme/api");
+    assert_eq!(normalize_remote("ssh://git@example.com/acme/api.git"), "example.com/acme/api");
+}
+
+#[test]
+fn test_config_get_and_set_keys() {
+    // Given
+    let mut config = Config::default();
+    
+    // When
+    config.set_key("model", "llama3").unwrap();
+    config.set_key("port", "11500").unwrap();
+    config.set_key("validation.retries", "4").unwrap();
+    config.set_key("context.stat_graph", "true").unwrap();
+    config.set_key("provider", "openai").unwrap();
+    config.set_key("openai.api_key", "sk-test").unwrap();
+    config.set_key("alias.wip", "--quick --add-unstaged").unwrap();
+    
+    // Then
+    assert_eq!(config.model, "llama3");
+    assert_eq!(config.port, 11500);
+    assert_eq!(config.validation.retries, 4);
+    assert!(config.context.stat_graph);
+    assert_eq!(config.provider, Provider::OpenAi);
+    assert_eq!(config.get_key("openai.api_key").unwrap(), "sk-test");
+    assert_eq!(config.get_key("alias.wip").unwrap(), "--quick --add-unstaged");
+    assert_eq!(config.get_key("port").unwrap(), "11500");
+    
+    let entries = config.entries().unwrap();
+    assert!(entries.contains(&("model".to_string(), "\"llama3\"".to_string())));
+    assert!(entries.contains(&("validation.retries".to_string(), "4".to_string())));
+}
+
+#[test]
+fn test_config_set_rejects_bad_keys_and_values() {
+    let mut config = Config::default();
+    
+    for (key, value) in [
+        ("modle", "llama3"),
+        ("validation.nope", "1"),
+        ("port", "0"),
+        ("port", "70000"),
+        ("timeout_seconds", "0"),
+        ("timeout_seconds", "-5"),
+        ("validation.retries", "many"),
+        ("fast_paths", "yes"),
+        ("provider", "anthropic"),
+        ("templates.unknown", "x"),
+    ] {
+        assert!(config.set_key(key, value).is_err(), "{} = {} should be rejected", key, value);
+    }
+    
+    // Nothing changed
+    assert_eq!(config.port, 11434);
+    assert_eq!(config.timeout_seconds, 60);
+    assert!(config.get_key("openai.api_key").is_err());
+    assert!(config.get_key("modle").is_err());
+}
+
+#[test]
+fn test_repository_config_overrides_the_user_config() {
+    // Given a user config and a repository config pinning a model and style
+    let temp_dir = tempfile::tempdir().unwrap();
+    let global = temp_dir.path().join("config.toml");
+    std::fs::write(&global, r#"
+        model = "llama3"
+        max_files = 20
+        provider = "openai"
+        
+        [validation]
+        retries = 3
+    "#).unwrap();
+    
+    let repo_dir = temp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo_dir).unwrap();
+    let repo = repo_dir.join(REPO_CONFIG_FILE);
+    std::fs::write(&repo, r#"
+        model = "qwen2.5-coder"
+        template = "prompts/commit.txt"
+        provider = "ollama"
+        
+        [validation]
+        commit_style = "conventional"
+        
+        [openai]
+        base_url = "https://example.invalid/v1"
+    "#).unwrap();
+    
+    // When
+    let config = Config::load_layered(&global, Some(&repo)).unwrap();
+    
+    // Then the repository wins where it may, and tables are merged
+    assert_eq!(config.model, "qwen2.5-coder");
+    assert_eq!(config.max_files, 20);
+    assert_eq!(config.validation.retries, 3);
+    assert_eq!(config.validation.commit_style, CommitStyle::Conventional);
+    assert_eq!(config.template, Some(repo_dir.join("prompts/commit.txt")));
+    
+    // Where diffs are sent is only up to the user
+    assert_eq!(config.provider, Provider::OpenAi);
+    assert_eq!(config.openai.base_url, "https://api.openai.com/v1");
+}
+
+#[test]
+fn test_invalid_host_headers_are_rejected_on_load() {
+    // Given
+    let temp_dir = tempfile::tempdir().unwrap();
+    let bad_name = temp_dir.path().join("bad_name.toml");
+    std::fs::write(&bad_name, "[host_headers]\n\"X Api Key\" = \"proxy-key\"\n").unwrap();
+    let bad_value = temp_dir.path().join("bad_value.toml");
+    std::fs::write(&bad_value, "[host_headers]\nX-Api-Key = \"proxy-key\\nX-Injected: 1\"\n").unwrap();
+    let good = temp_dir.path().join("good.toml");
+    std::fs::write(&good, "[host_headers]\nX-Api-Key = \"proxy-key\"\n").unwrap();
+    
+    // When
+    let bad_name_error = Config::load_layered(&bad_name, None).unwrap_err().to_string();
+    let bad_value_error = Config::load_from_path(&bad_value).unwrap_err().to_string();
+    
+    // Then
+    assert!(bad_name_error.contains("'X Api Key' is not a valid header name"), "{}", bad_name_error);
+    assert!(bad_value_error.contains("the value of 'X-Api-Key' is not a valid header value"), "{}", bad_value_error);
+    assert!(!bad_value_error.contains("proxy-key"), "{}", bad_value_error);
+    assert_eq!(Config::load_layered(&good, None).unwrap().host_headers["X-Api-Key"], "proxy-key");
+}
+
+#[test]
+fn test_layered_config_without_files_is_the_default() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config = Config::load_layered(&temp_dir.path().join("missing.toml"), None).unwrap();
+    
+    assert_eq!(config.model, Config::default().model);
+    assert_eq!(config.max_model_calls, Config::default().max_model_calls);
+    assert_eq!(config.template, None);
+}
+
+#[test]
+fn test_repo_config_path_is_found_from_subdirectories() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    std::fs::create_dir_all(root.join("src/deep")).unwrap();
+    
+    assert_eq!(repo_config_path(&root.join("src/deep")), None);
+    
+    std::fs::write(root.join(REPO_CONFIG_FILE), "model = \"llama3\"\n").unwrap();
+    assert_eq!(repo_config_path(&root.join("src/deep")), Some(root.join(REPO_CONFIG_FILE)));
+    assert_eq!(repo_config_path(root), Some(root.join(REPO_CONFIG_FILE)));
+}
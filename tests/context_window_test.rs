@@ -0,0 +1,69 @@
+use git_ai_commit::ollama::context::{chunks_needed, estimate_tokens, fits, ANSWER_TOKENS};
+use git_ai_commit::ollama::{ModelDetails, OllamaClient, OllamaClientTrait, DEFAULT_NUM_CTX};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+#[test]
+fn test_prompts_are_measured_against_the_window() {
+    // Given a prompt of about 3000 tokens
+    let prompt = "x".repeat(9000);
+    
+    // Then
+    assert_eq!(estimate_tokens(&prompt), 3000);
+    assert!(fits(&prompt, 3000 + ANSWER_TOKENS));
+    assert!(!fits(&prompt, 3000 + ANSWER_TOKENS - 1));
+}
+
+#[test]
+fn test_chunks_needed_leaves_room_for_instructions() {
+    // Each chunk's changes may take half of what the answer leaves
+    assert_eq!(chunks_needed(3000, 4096), 2);
+    assert_eq!(chunks_needed(10_000, 4096), 6);
+    // Anything worth chunking takes at least two chunks
+    assert_eq!(chunks_needed(100, 4096), 2);
+}
+
+#[test]
+fn test_context_window_of_model_details() {
+    // Without a num_ctx parameter Ollama uses its default, but never more than the model knows
+    let mut details = ModelDetails { context_length: Some(131_072), ..ModelDetails::default() };
+    assert_eq!(details.context_window(), DEFAULT_NUM_CTX);
+    
+    details.num_ctx = Some(32_768);
+    assert_eq!(details.context_window(), 32_768);
+    
+    details.context_length = Some(2048);
+    assert_eq!(details.context_window(), 2048);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_reports_the_context_window() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _m_show = server
+        .mock("POST", "/api/show")
+        .match_body(Matcher::PartialJson(json!({ "model": "qwen2.5-coder:7b" })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({
+            "details": { "family": "qwen2" },
+            "parameters": "num_ctx 16384",
+            "model_info": { "qwen2.context_length": 32768 }
+        }).to_string())
+        .create_async()
+        .await;
+    let _m_tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "models": [] }).to_string())
+        .create_async()
+        .await;
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    
+    // When
+    let window = OllamaClient::new(port).context_window("qwen2.5-coder:7b").await.unwrap();
+    
+    // Then
+    assert_eq!(window, Some(16_384));
+}
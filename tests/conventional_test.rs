@@ -0,0 +1,61 @@
+use git_ai_commit::config::{CommitStyle, ValidationConfig};
+use git_ai_commit::formatting::conventional::{check, repair, ConventionalHeader};
+use git_ai_commit::formatting::validate::{validate, Violation};
+
+#[test]
+fn test_parse_conventional_header() {
+    let header = ConventionalHeader::parse("feat(api)!: drop the v1 endpoints").unwrap();
+    assert_eq!(header.commit_type, "feat");
+    assert_eq!(header.scope.as_deref(), Some("api"));
+    assert!(header.breaking);
+    assert_eq!(header.description, "drop the v1 endpoints");
+    assert_eq!(header.to_string(), "feat(api)!: drop the v1 endpoints");
+    
+    assert!(ConventionalHeader::parse("Fix: typo").is_none());
+    assert!(ConventionalHeader::parse("fix:typo").is_none());
+    assert!(ConventionalHeader::parse("fix typo in readme").is_none());
+}
+
+#[test]
+fn test_check_reports_missing_header_and_unknown_type() {
+    assert_eq!(check("fix(parser): handle empty input"), None);
+    assert_eq!(check("Handle empty input"), Some(Violation::NotConventional));
+    assert_eq!(check("update: bump deps"), Some(Violation::UnknownType("update".to_string())));
+}
+
+#[test]
+fn test_repair_near_misses() {
+    assert_eq!(repair("Feature(ui): add dark mode", None), "feat(ui): add dark mode");
+    assert_eq!(repair("BUGFIX - handle timeouts.", None), "fix: handle timeouts");
+    assert_eq!(repair("docs:update install steps", None), "docs: update install steps");
+    assert_eq!(repair("fix ( parser ) ! : reject tabs", None), "fix(parser)!: reject tabs");
+    
+    // The body is kept as is
+    assert_eq!(
+        repair("Fix: retry uploads\n\nUploads failed on flaky networks.", None),
+        "fix: retry uploads\n\nUploads failed on flaky networks."
+    );
+}
+
+#[test]
+fn test_repair_adds_known_type_only() {
+    assert_eq!(repair("Update install steps", Some("docs")), "docs: Update install steps");
+    
+    // Without a type to add, validation rejects it and the model is asked again
+    assert_eq!(repair("Update install steps", None), "Update install steps");
+    assert_eq!(repair("refactor: split parser", Some("docs")), "refactor: split parser");
+}
+
+#[test]
+fn test_conventional_style_is_validated() {
+    let plain = ValidationConfig::default();
+    let conventional = ValidationConfig { commit_style: CommitStyle::Conventional, ..ValidationConfig::default() };
+    
+    assert!(validate("Speed up diff parsing", &plain).is_empty());
+    assert_eq!(validate("Speed up diff parsing", &conventional), vec![Violation::NotConventional]);
+    assert!(validate("perf(diff): speed up parsing", &conventional).is_empty());
+    
+    let violations = validate("enhancement: speed up parsing", &conventional);
+    assert_eq!(violations, vec![Violation::UnknownType("enhancement".to_string())]);
+    assert!(violations[0].describe().contains("feat, fix, docs"));
+}
@@ -0,0 +1,161 @@
+use git_ai_commit::config::{Config, CostConfig, ModelPrice, OpenAiConfig, Provider};
+use git_ai_commit::ollama::budget;
+use git_ai_commit::ollama::cost::{current_month, month_of, price_of, CostMeter, Ledger};
+use git_ai_commit::ollama::{GenerationStats, OllamaManager};
+use git_ai_commit::utils::WriteMode;
+use mockito::Server;
+use serde_json::json;
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn caps(max_per_run: Option<f64>, monthly_budget: Option<f64>) -> CostConfig {
+    CostConfig { max_per_run, monthly_budget, ..CostConfig::default() }
+}
+
+fn usage(prompt_tokens: u64, generated_tokens: u64) -> GenerationStats {
+    GenerationStats { prompt_eval_count: Some(prompt_tokens), eval_count: Some(generated_tokens), ..GenerationStats::default() }
+}
+
+#[test]
+fn test_prices_match_dated_and_routed_names() {
+    let none = BTreeMap::new();
+    let mini = ModelPrice { input: 0.15, output: 0.60 };
+    
+    assert_eq!(price_of("gpt-4o-mini", &none), Some(mini));
+    assert_eq!(price_of("gpt-4o-mini-2024-07-18", &none), Some(mini));
+    assert_eq!(price_of("openai/gpt-4o-mini", &none), Some(mini));
+    assert_eq!(price_of("gpt-4o", &none), Some(ModelPrice { input: 2.5, output: 10.0 }));
+    assert_eq!(price_of("qwen2.5-coder:7b", &none), None);
+    
+    // The config's prices come first
+    let prices = BTreeMap::from([("gpt-4o-mini".to_string(), ModelPrice { input: 1.0, output: 2.0 })]);
+    assert_eq!(price_of("gpt-4o-mini-2024-07-18", &prices), Some(ModelPrice { input: 1.0, output: 2.0 }));
+    assert_eq!(ModelPrice { input: 1.0, output: 2.0 }.cost(1_000_000, 500_000), 2.0);
+}
+
+#[test]
+fn test_month_of() {
+    assert_eq!(month_of(0), "1970-01");
+    assert_eq!(month_of(946_684_799), "1999-12");
+    assert_eq!(month_of(951_955_199), "2000-03");
+    assert_eq!(month_of(1_709_251_199), "2024-02");
+    assert_eq!(month_of(1_792_195_199), "2026-10");
+}
+
+#[test]
+fn test_ledger_adds_up_per_month() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let ledger = Ledger::new(temp_dir.path().join("state").join("spend.json"));
+    
+    // When
+    ledger.charge("2026-09", 0.5).unwrap();
+    ledger.charge("2026-10", 0.25).unwrap();
+    ledger.charge("2026-10", 0.5).unwrap();
+    
+    // Then
+    assert_eq!(ledger.spent("2026-10"), 0.75);
+    assert_eq!(ledger.spent("2026-09"), 0.5);
+    assert_eq!(ledger.spent("2026-11"), 0.0);
+}
+
+#[test]
+fn test_run_cap_refuses_without_force() {
+    // Given a run that already spent 0.9 of its dollar
+    let meter = CostMeter::new(caps(Some(1.0), None), None);
+    meter.charge("gpt-4o", "", &usage(160_000, 50_000));
+    assert!((meter.spent() - 0.9).abs() < 1e-9);
+    
+    // When
+    let long_prompt = "x".repeat(150_000);
+    let error = meter.check("gpt-4o", &long_prompt).unwrap_err();
+    
+    // Then
+    assert!(budget::is_exhausted_error(&error));
+    assert!(error.to_string().contains("over cost.max_per_run of $1.00; pass --force"), "{}", error);
+    assert!(meter.check("gpt-4o", "short prompt").is_ok());
+    assert!(CostMeter::new(caps(Some(1.0), None), None).with_force(true).check("gpt-4o", &long_prompt).is_ok());
+}
+
+#[test]
+fn test_monthly_budget_counts_earlier_runs() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let ledger = Ledger::new(temp_dir.path().join("spend.json"));
+    ledger.charge(&current_month(), 4.99).unwrap();
+    
+    // When
+    let meter = CostMeter::new(caps(None, Some(5.0)), Some(ledger.clone()));
+    let error = meter.check("gpt-4o", &"x".repeat(30_000)).unwrap_err();
+    
+    // Then
+    assert!(error.to_string().contains("over cost.monthly_budget of $5.00"), "{}", error);
+    meter.charge("gpt-4o-mini", "", &usage(1_000_000, 0));
+    assert!((ledger.spent(&current_month()) - 5.14).abs() < 1e-9);
+    assert!(meter.display("gpt-4o-mini").unwrap().contains("of $5.00 in"));
+}
+
+#[test]
+fn test_read_only_runs_dont_charge_the_ledger() {
+    let temp_dir = tempdir().unwrap();
+    let ledger = Ledger::new(temp_dir.path().join("spend.json"));
+    let meter = CostMeter::new(CostConfig::default(), Some(ledger.clone())).with_write_mode(WriteMode::ReadOnly);
+    
+    assert_eq!(meter.charge("gpt-4o-mini", "", &usage(1_000_000, 0)), Some(0.15));
+    
+    assert!(!ledger.path().exists());
+}
+
+#[test]
+fn test_free_backends_are_never_priced() {
+    let meter = CostMeter::default();
+    
+    assert_eq!(meter.price("gpt-4o"), None);
+    assert_eq!(meter.charge("gpt-4o", "prompt", &usage(1000, 10)), None);
+    assert_eq!(meter.display("gpt-4o"), None);
+    assert_eq!(CostMeter::new(CostConfig::default(), None).display("llama3.2"), None);
+}
+
+#[test]
+fn test_negative_caps_are_rejected() {
+    let mut config = Config::default();
+    config.cost.monthly_budget = Some(-1.0);
+    
+    assert!(config.check().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_manager_refuses_before_calling_the_api() {
+    // Given
+    let mut server = Server::new_async().await;
+    let completion = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({
+            "choices": [{ "message": { "content": "docs: fix typo" } }],
+            "usage": { "prompt_tokens": 2000, "completion_tokens": 10 },
+        }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+    let config = OpenAiConfig {
+        base_url: format!("{}/v1", server.url()),
+        api_key: Some("sk-test".to_string()),
+        ..OpenAiConfig::default()
+    };
+    let manager = OllamaManager::new("gpt-4o".to_string(), 1)
+        .unwrap()
+        .with_provider(Provider::OpenAi, &config)
+        .with_cost_meter(CostMeter::new(caps(Some(0.007), None), None));
+    
+    // When
+    let generation = manager.generate_commit_with_stats("prompt").await.unwrap();
+    let refused = manager.generate_commit_with_stats("prompt").await;
+    
+    // Then the first call is charged from the reported usage, the second not made
+    assert_eq!(generation.text, "docs: fix typo");
+    assert!((manager.cost().spent() - 0.0051).abs() < 1e-9);
+    assert!(refused.is_err());
+    completion.assert_async().await;
+}
@@ -0,0 +1,119 @@
+use git_ai_commit::ollama::daemon::{
+    self, format_idle, format_memory, is_ollama_serve, server_table, ServerEntry, ServerRegistry,
+};
+use std::time::Duration;
+use tempfile::tempdir;
+
+fn entry(pid: u32, port: u16, last_used: u64) -> ServerEntry {
+    ServerEntry {
+        pid,
+        port,
+        model: "llama3".to_string(),
+        started_at: 0,
+        last_used,
+    }
+}
+
+#[test]
+fn test_register_touch_and_remove() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let registry = ServerRegistry::new(temp_dir.path().join("servers.json"));
+    assert!(registry.load().is_empty());
+    
+    // When a server is registered twice on the same port
+    registry.register(entry(100, 11434, 0)).unwrap();
+    registry.register(entry(200, 11434, 0)).unwrap();
+    registry.register(entry(300, 11500, 0)).unwrap();
+    
+    // Then the newest one replaces the old entry
+    let entries = registry.load();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries.iter().find(|e| e.port == 11434).unwrap().pid, 200);
+    
+    // Touching records the model and refreshes the idle time
+    registry.touch(11434, "gemma3:4b").unwrap();
+    let touched = registry.load().into_iter().find(|e| e.port == 11434).unwrap();
+    assert_eq!(touched.model, "gemma3:4b");
+    assert!(touched.last_used > 0);
+    
+    // Unregistered ports are ignored
+    registry.touch(9999, "llama3").unwrap();
+    assert_eq!(registry.load().len(), 2);
+    
+    assert_eq!(registry.remove(11500).unwrap().map(|e| e.pid), Some(300));
+    assert!(registry.remove(11500).unwrap().is_none());
+    assert_eq!(registry.load().len(), 1);
+}
+
+#[test]
+fn test_sweep_idle_and_exited_servers() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let registry = ServerRegistry::new(temp_dir.path().join("servers.json"));
+    registry.register(entry(1, 11434, 1_000)).unwrap(); // idle, about to be used
+    registry.register(entry(2, 11435, 1_000)).unwrap(); // idle
+    registry.register(entry(3, 11436, 1_900)).unwrap(); // recently used
+    registry.register(entry(4, 11437, 1_900)).unwrap(); // exited
+    
+    // When
+    let sweep = registry
+        .sweep(Duration::from_secs(600), 2_000, Some(11434), |entry| entry.pid != 4)
+        .unwrap();
+    
+    // Then
+    let ports = |entries: &[ServerEntry]| entries.iter().map(|e| e.port).collect::<Vec<_>>();
+    assert_eq!(ports(&sweep.expired), vec![11435]);
+    assert_eq!(ports(&sweep.gone), vec![11437]);
+    assert_eq!(ports(&registry.load()), vec![11434, 11436]);
+}
+
+#[test]
+fn test_reused_process_ids_are_never_stopped() {
+    // Given: the registered pid now belongs to this test, not to `ollama serve`
+    let temp_dir = tempdir().unwrap();
+    let registry = ServerRegistry::new(temp_dir.path().join("servers.json"));
+    let reused = entry(std::process::id(), 11500, 0);
+    registry.register(reused.clone()).unwrap();
+    
+    // When
+    let registered = daemon::is_registered_server(&reused);
+    let stopped = registry.sweep_and_stop(Duration::from_secs(600), None).unwrap();
+    registry.register(reused.clone()).unwrap();
+    daemon::watch(&registry, 11500, reused.pid, Duration::from_secs(600)).unwrap();
+    
+    // Then: the entry is forgotten and this process is still running
+    assert!(!registered);
+    assert!(stopped.is_empty());
+    assert!(registry.load().is_empty());
+}
+
+#[test]
+fn test_ollama_serve_is_recognized() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert!(is_ollama_serve(&args(&["/home/me/.cache/git-ai-commit/ollama", "serve"])));
+    assert!(is_ollama_serve(&args(&["ollama.exe", "serve"])));
+    assert!(!is_ollama_serve(&args(&["ollama", "run", "llama3"])));
+    assert!(!is_ollama_serve(&args(&["/usr/bin/python3", "serve"])));
+    assert!(!is_ollama_serve(&[]));
+}
+
+#[test]
+fn test_server_table() {
+    let table = server_table(&[entry(4242, 11434, 100)], 250);
+    assert!(table.contains("4242"));
+    assert!(table.contains("11434"));
+    assert!(table.contains("llama3"));
+    assert!(table.contains("2m 30s"));
+}
+
+#[test]
+fn test_formatting() {
+    assert_eq!(format_idle(Duration::from_secs(42)), "42s");
+    assert_eq!(format_idle(Duration::from_secs(3 * 60 + 5)), "3m 5s");
+    assert_eq!(format_idle(Duration::from_secs(2 * 3600 + 60)), "2h 1m");
+    
+    assert_eq!(format_memory(512), "512 B");
+    assert_eq!(format_memory(300 * 1024 * 1024), "300 MB");
+    assert_eq!(format_memory(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+}
@@ -0,0 +1,88 @@
+use git_ai_commit::doctor::{check_config, check_repo_config, check_repository, Check, CheckStatus};
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn init_repo(repo_path: &Path) {
+    let status = Command::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success());
+}
+
+#[test]
+fn test_check_render() {
+    assert_eq!(Check::ok("git", "git version 2.43.0").render(), "[ OK ] git: git version 2.43.0");
+    assert_eq!(Check::warn("model", "missing").render(), "[WARN] model: missing");
+    assert_eq!(Check::fail("config", "broken").render(), "[FAIL] config: broken");
+}
+
+#[test]
+fn test_check_config() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    
+    // A missing file just means defaults
+    let path = temp_dir.path().join("config.toml");
+    assert_eq!(check_config(&path).status, CheckStatus::Ok);
+    
+    std::fs::write(&path, "model = \"llama3\"\n").unwrap();
+    assert_eq!(check_config(&path).status, CheckStatus::Ok);
+    
+    std::fs::write(&path, "model = [unclosed\n").unwrap();
+    let check = check_config(&path);
+    assert_eq!(check.status, CheckStatus::Fail);
+    assert!(check.detail.contains("config.toml"));
+}
+
+#[tokio::test]
+async fn test_check_repository_outside_a_repository() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    
+    // When
+    let checks = check_repository(temp_dir.path()).await;
+    
+    // Then
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].status, CheckStatus::Warn);
+}
+
+#[tokio::test]
+async fn test_check_repository_reports_a_held_index_lock() {
+    // Given a fresh repository whose index is locked
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    std::fs::write(temp_dir.path().join(".git").join("index.lock"), "").unwrap();
+    
+    // When
+    let checks = check_repository(temp_dir.path()).await;
+    
+    // Then
+    let status = |name: &str| checks.iter().find(|check| check.name == name).map(|check| check.status);
+    assert_eq!(status("repository"), Some(CheckStatus::Ok));
+    assert_eq!(status("clone"), Some(CheckStatus::Ok));
+    assert_eq!(status("index"), Some(CheckStatus::Fail));
+    assert_eq!(status("hook"), Some(CheckStatus::Ok));
+}
+
+#[test]
+fn test_check_repo_config() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    std::fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+    assert_eq!(check_repo_config(temp_dir.path()), None);
+    
+    let path = temp_dir.path().join(".git-ai-commit.toml");
+    std::fs::write(&path, "model = \"llama3\"\n").unwrap();
+    assert_eq!(check_repo_config(temp_dir.path()).unwrap().status, CheckStatus::Ok);
+    
+    // Settings only the user config may set are ignored, which is worth knowing
+    std::fs::write(&path, "model = \"llama3\"\nprovider = \"openai\"\n").unwrap();
+    let check = check_repo_config(temp_dir.path()).unwrap();
+    assert_eq!(check.status, CheckStatus::Warn);
+    assert!(check.detail.contains("provider"));
+    
+    std::fs::write(&path, "model = [\n").unwrap();
+    assert_eq!(check_repo_config(temp_dir.path()).unwrap().status, CheckStatus::Fail);
+}
@@ -0,0 +1,61 @@
+use git_ai_commit::git::draft::Draft;
+use git_ai_commit::git::GitCollector;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_draft_round_trip() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let git_dir = temp_dir.path();
+    
+    assert!(Draft::load(git_dir).is_none());
+    
+    let draft = Draft::new("abc123", "feat: add drafts\n\nSaved after cancelling.");
+    let path = draft.save(git_dir).unwrap();
+    assert_eq!(path, git_dir.join("AI_COMMIT_DRAFT"));
+    
+    assert_eq!(Draft::load(git_dir), Some(draft.clone()));
+    assert_eq!(Draft::load_for_tree(git_dir, "abc123"), Some(draft));
+    assert!(Draft::load_for_tree(git_dir, "def456").is_none(), "Draft for another tree should be ignored");
+    
+    Draft::discard(git_dir).unwrap();
+    assert!(Draft::load(git_dir).is_none());
+    // Discarding without a draft is fine
+    Draft::discard(git_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_staged_tree_tracks_index() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    
+    Command::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to initialize git repo");
+    
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let git_dir = git_collector.git_dir().await.unwrap();
+    assert!(git_dir.ends_with(".git"));
+    
+    std::fs::write(repo_path.join("file.txt"), "one").unwrap();
+    Command::new("git")
+        .args(["add", "file.txt"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to stage file");
+    let first_tree = git_collector.staged_tree().await.unwrap();
+    assert_eq!(git_collector.staged_tree().await.unwrap(), first_tree);
+    
+    // Unstaged edits don't change the staged tree
+    std::fs::write(repo_path.join("file.txt"), "two").unwrap();
+    assert_eq!(git_collector.staged_tree().await.unwrap(), first_tree);
+    
+    Command::new("git")
+        .args(["add", "file.txt"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to stage file");
+    assert_ne!(git_collector.staged_tree().await.unwrap(), first_tree);
+}
@@ -0,0 +1,93 @@
+use git_ai_commit::git::editor::{cleanup_message, edit_message, git_editor};
+use serial_test::serial;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn init_repo(repo_path: &Path) {
+    let status = Command::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success());
+}
+
+fn set_core_editor(repo_path: &Path, editor: &str) {
+    let status = Command::new("git")
+        .args(["config", "core.editor", editor])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success());
+}
+
+#[test]
+fn test_cleanup_message() {
+    let edited = "\n\nfeat: add editing   \n\n\n\nOpened before committing.\n# Please enter the commit message\n#\n\n";
+    
+    assert_eq!(cleanup_message(edited), "feat: add editing\n\nOpened before committing.");
+    assert_eq!(cleanup_message("# only comments\n\n  \n"), "");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_git_editor_follows_core_editor_and_git_editor() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    set_core_editor(temp_dir.path(), "nano -w");
+    
+    // Then
+    std::env::remove_var("GIT_EDITOR");
+    assert_eq!(git_editor(temp_dir.path()).await.unwrap(), "nano -w");
+    
+    std::env::set_var("GIT_EDITOR", "vim");
+    assert_eq!(git_editor(temp_dir.path()).await.unwrap(), "vim");
+    std::env::remove_var("GIT_EDITOR");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_edit_message_uses_the_edited_file() {
+    // Given an editor that turns the fix into a feature
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    std::env::remove_var("GIT_EDITOR");
+    set_core_editor(temp_dir.path(), "sed -i -e 's/^fix:/feat:/'");
+    
+    // When
+    let edited = edit_message(temp_dir.path(), "fix: handle empty diffs\n\nNo more panics.").await;
+    
+    // Then the help comment is gone again
+    assert_eq!(edited.unwrap().as_deref(), Some("feat: handle empty diffs\n\nNo more panics."));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_edit_message_emptied_aborts() {
+    // Given an editor that deletes every line that isn't a comment
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    std::env::remove_var("GIT_EDITOR");
+    set_core_editor(temp_dir.path(), "sed -i -e '/^[^#]/d'");
+    
+    // When
+    let edited = edit_message(temp_dir.path(), "fix: handle empty diffs").await;
+    
+    // Then
+    assert_eq!(edited.unwrap(), None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_edit_message_reports_a_failing_editor() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    std::env::remove_var("GIT_EDITOR");
+    set_core_editor(temp_dir.path(), "false");
+    
+    let error = edit_message(temp_dir.path(), "fix: handle empty diffs").await.unwrap_err();
+    
+    assert!(error.to_string().contains("There was a problem with the editor 'false'"), "{}", error);
+}
@@ -0,0 +1,125 @@
+use git_ai_commit::config::ValidationConfig;
+use git_ai_commit::formatting::candidate::{rank, Candidate};
+use git_ai_commit::formatting::compare::render_comparison;
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::{DiffInfo, GitInfo, GitStatus};
+use git_ai_commit::ollama::OllamaManager;
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+fn empty_git_info() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: vec![],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_rank_prefers_valid_candidates() {
+    let rules = ValidationConfig::default();
+    let candidates = vec![
+        Candidate::new("llama3", "fix: added retry.", &rules),
+        Candidate::new("qwen2.5-coder", "fix: retry failed uploads", &rules),
+        Candidate::new("gemma3:4b", "fix: retry uploads", &rules),
+    ];
+    
+    let ranked = rank(candidates);
+    
+    let models: Vec<_> = ranked.iter().map(|c| c.model.as_str()).collect();
+    assert_eq!(models, vec!["qwen2.5-coder", "gemma3:4b", "llama3"]);
+    assert_eq!(ranked[0].label(), "[qwen2.5-coder] fix: retry failed uploads");
+    assert_eq!(ranked[2].label(), "[llama3] fix: added retry. (2 issues)");
+}
+
+#[test]
+fn test_judge_prompt_lists_candidates() {
+    let rules = ValidationConfig::default();
+    let candidates = vec![
+        Candidate::new("llama3", "fix: retry uploads", &rules),
+        Candidate::new("qwen2.5-coder", "feat(upload): add retry with backoff", &rules),
+    ];
+    
+    let prompt = PromptBuilder::new(10, 50).build_judge(&empty_git_info(), &candidates);
+    
+    assert!(prompt.contains("Candidate 1:\nfix: retry uploads"));
+    assert!(prompt.contains("Candidate 2:\nfeat(upload): add retry with backoff"));
+    assert!(prompt.contains("Current branch: main"));
+}
+
+#[test]
+fn test_comparison_highlights_differing_words() {
+    let rules = ValidationConfig::default();
+    let candidates = vec![
+        Candidate::new("llama3", "fix: retry failed uploads", &rules),
+        Candidate::new("qwen2.5-coder", "fix: retry uploads with backoff\n\nUses exponential delays.", &rules),
+    ];
+    
+    let output = render_comparison(&candidates, |word| format!("<{}>", word));
+    
+    assert_eq!(
+        output,
+        "  1. [llama3]\n     \
+              fix: retry <failed> uploads\n  \
+            2. [qwen2.5-coder]\n     \
+              fix: retry uploads <with> <backoff>\n\n     \
+              <Uses> <exponential> <delays.>\n"
+    );
+}
+
+#[test]
+fn test_single_candidate_has_no_highlights() {
+    let candidates = vec![Candidate::new("llama3", "fix: retry uploads", &ValidationConfig::default())];
+    
+    let output = render_comparison(&candidates, |word| format!("<{}>", word));
+    
+    assert_eq!(output, "  1. [llama3]\n     fix: retry uploads\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generate_with_models_keeps_order() {
+    let mut server = Server::new_async().await;
+    for (model, message) in [("llama3", "fix: one"), ("qwen2.5-coder", "fix: two")] {
+        server
+            .mock("POST", "/api/generate")
+            .match_body(Matcher::PartialJson(json!({ "model": model })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "response": message, "eval_count": 5 }).to_string())
+            .create_async()
+            .await;
+    }
+    server
+        .mock("POST", "/api/generate")
+        .match_body(Matcher::PartialJson(json!({ "model": "missing" })))
+        .with_status(404)
+        .with_body("model not found")
+        .create_async()
+        .await;
+    
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    let manager = OllamaManager::new("llama3".to_string(), port).unwrap();
+    let models = vec!["llama3".to_string(), "missing".to_string(), "qwen2.5-coder".to_string()];
+    
+    let results = manager.generate_with_models(&models, "prompt").await;
+    
+    let names: Vec<_> = results.iter().map(|(model, _)| model.as_str()).collect();
+    assert_eq!(names, vec!["llama3", "missing", "qwen2.5-coder"]);
+    assert_eq!(results[0].1.as_ref().unwrap().text, "fix: one");
+    assert!(results[1].1.is_err());
+    assert_eq!(results[2].1.as_ref().unwrap().text, "fix: two");
+    assert_eq!(results[2].1.as_ref().unwrap().stats.eval_count, Some(5));
+}
@@ -0,0 +1,144 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::Config;
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::diff::FileStat;
+use git_ai_commit::git::exclude::{self, PathFilter};
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::{Path, PathBuf};
+
+fn filter(globs: &[&str]) -> PathFilter {
+    PathFilter::new(&globs.iter().map(|glob| glob.to_string()).collect::<Vec<_>>())
+}
+
+fn staged(paths: &[(&str, usize)]) -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: paths.iter().map(|(path, _)| PathBuf::from(path)).collect(),
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: paths.len(),
+            insertions: paths.iter().map(|(_, lines)| lines).sum(),
+            deletions: 0,
+            file_stats: paths.iter()
+                .map(|(path, lines)| FileStat { filename: path.to_string(), insertions: *lines, deletions: 0, ..Default::default() })
+                .collect(),
+        },
+        file_changes: paths.iter()
+            .map(|(path, _)| FileChange { change_type: ChangeType::Modified, file_path: PathBuf::from(path), old_path: None, ..Default::default() })
+            .collect(),
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_globs_are_read_like_gitignore() {
+    // A name matches in any directory
+    let names = filter(&["Cargo.lock", "*.pb.go"]);
+    assert!(names.is_excluded(Path::new("Cargo.lock")));
+    assert!(names.is_excluded(Path::new("crates/core/Cargo.lock")));
+    assert!(names.is_excluded(Path::new("api/v1/user.pb.go")));
+    assert!(!names.is_excluded(Path::new("Cargo.lock.md")));
+    assert!(!names.is_excluded(Path::new("api/v1/user.go")));
+    
+    // A directory takes everything in it
+    let directories = filter(&["vendor/", "/third_party"]);
+    assert!(directories.is_excluded(Path::new("vendor/github.com/x/y.go")));
+    assert!(directories.is_excluded(Path::new("web/vendor/lib.js")));
+    assert!(directories.is_excluded(Path::new("third_party/zlib/zlib.h")));
+    assert!(!directories.is_excluded(Path::new("src/third_party/zlib.h")));
+    assert!(!directories.is_excluded(Path::new("vendors.rs")));
+    
+    // `*` stays within a directory, `**` doesn't
+    let nested = filter(&["src/*.rs", "gen/**/*.ts"]);
+    assert!(nested.is_excluded(Path::new("src/lib.rs")));
+    assert!(!nested.is_excluded(Path::new("src/git/mod.rs")));
+    assert!(nested.is_excluded(Path::new("gen/api.ts")));
+    assert!(nested.is_excluded(Path::new("gen/v1/deep/api.ts")));
+    
+    assert!(filter(&["", "  "]).is_empty());
+}
+
+#[test]
+fn test_excluded_files_are_summarized() {
+    assert_eq!(exclude::summarize(&[Path::new("Cargo.lock")]), "1 lockfile updated");
+    assert_eq!(
+        exclude::summarize(&[Path::new("web/yarn.lock"), Path::new("go.sum"), Path::new("vendor/a.go")]),
+        "2 lockfiles updated, 1 other file changed"
+    );
+    assert_eq!(exclude::summarize(&[Path::new("gen/a.ts"), Path::new("gen/b.ts")]), "2 files changed");
+}
+
+#[test]
+fn test_lockfiles_are_excluded_by_default() {
+    let config = Config::default();
+    assert!(config.exclude_paths.contains(&"Cargo.lock".to_string()));
+    assert!(config.exclude_paths.contains(&"package-lock.json".to_string()));
+}
+
+#[test]
+fn test_excluded_files_only_count_in_the_prompt() {
+    // Given
+    let git_info = staged(&[("src/parser.rs", 12), ("Cargo.lock", 480)]);
+    let builder = PromptBuilder::new(10, 100).with_excluded_paths(filter(&["Cargo.lock"]));
+    
+    // When
+    let prompt = builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("  - M  src/parser.rs"), "{}", prompt);
+    assert!(prompt.contains("Also changed (not shown): 1 lockfile updated"), "{}", prompt);
+    assert!(!prompt.contains("Cargo.lock"), "{}", prompt);
+}
+
+#[test]
+fn test_excluded_files_leave_the_diff() {
+    // Given
+    let patch = "\
+diff --git a/src/parser.rs b/src/parser.rs
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -1 +1 @@
+-fn parse() {}
++fn parse() -> Ast {}
+diff --git a/package-lock.json b/package-lock.json
+--- a/package-lock.json
++++ b/package-lock.json
+@@ -1 +1 @@
+-\"version\": \"1.0.0\"
++\"version\": \"1.0.1\"
+";
+    let git_info = staged(&[("src/parser.rs", 1), ("package-lock.json", 1)]);
+    let builder = PromptBuilder::new(10, 100).with_excluded_paths(filter(&["package-lock.json"]));
+    
+    // When
+    let prompt = builder.build_with_patch(&git_info, patch);
+    
+    // Then
+    assert!(prompt.contains("+fn parse() -> Ast {}"), "{}", prompt);
+    assert!(!prompt.contains("1.0.1"), "{}", prompt);
+    assert!(prompt.contains("... diff not shown for 1 lockfile updated"), "{}", prompt);
+}
+
+#[test]
+fn test_exclude_flag_adds_to_the_config() {
+    // Given
+    let mut args = Args::try_parse_from(["git-ai-commit", "--exclude", "vendor/,*.pb.go"]).unwrap();
+    args.config.exclude_paths = vec!["Cargo.lock".to_string()];
+    
+    // When
+    let excluded = args.excluded_paths();
+    
+    // Then
+    assert_eq!(args.exclude, vec!["vendor/", "*.pb.go"]);
+    assert!(excluded.is_excluded(Path::new("Cargo.lock")));
+    assert!(excluded.is_excluded(Path::new("vendor/lib.go")));
+    assert!(excluded.is_excluded(Path::new("api/user.pb.go")));
+    assert!(!excluded.is_excluded(Path::new("src/main.rs")));
+}
@@ -0,0 +1,192 @@
+use git_ai_commit::formatting::fast_path::{trivial_message, FastPathRule};
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use git_ai_commit::git::files::ChangeType;
+use std::path::PathBuf;
+
+fn single_file_info(path: &str) -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from(path)],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 1,
+            deletions: 1,
+            file_stats: vec![],
+        },
+        file_changes: vec![FileChange {
+            change_type: ChangeType::Modified,
+            file_path: PathBuf::from(path),
+            old_path: None,
+            ..Default::default()
+        }],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_version_bump() {
+    let patch = r#"diff --git a/Cargo.toml b/Cargo.toml
+index 1111111..2222222 100644
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -1,6 +1,6 @@
+ [package]
+ name = "git-ai-commit"
+-version = "0.1.2"
++version = "0.1.3"
+ edition = "2021"
+"#;
+    
+    let fast = trivial_message(&single_file_info("Cargo.toml"), patch).expect("Should match version bump");
+    assert_eq!(fast.rule, FastPathRule::VersionBump);
+    assert_eq!(fast.message, "chore: bump version to 0.1.3");
+}
+
+#[test]
+fn test_formatting_only() {
+    let patch = "diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,5 @@
+-fn main() { run(); }
++fn main() {
++    run();
++}
+";
+    
+    let fast = trivial_message(&single_file_info("src/lib.rs"), patch).expect("Should match formatting");
+    assert_eq!(fast.rule, FastPathRule::FormattingOnly);
+    assert_eq!(fast.message, "style: format src/lib.rs");
+}
+
+#[test]
+fn test_dependency_versions_are_not_version_bumps() {
+    let cargo = r#"diff --git a/Cargo.toml b/Cargo.toml
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -10,3 +10,3 @@
+ [dependencies.serde]
+-version = "1.0.199"
++version = "1.0.200"
+ features = ["derive"]
+"#;
+    let package_json = r#"diff --git a/package.json b/package.json
+--- a/package.json
++++ b/package.json
+@@ -1,6 +1,6 @@
+ {
+   "name": "acme",
+   "bundled": {
+-    "version": "2.0.0"
++    "version": "2.1.0"
+   }
+"#;
+    let readme = r#"diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-version = "0.1.2"
++version = "0.1.3"
+"#;
+    
+    assert!(trivial_message(&single_file_info("Cargo.toml"), cargo).is_none());
+    assert!(trivial_message(&single_file_info("package.json"), package_json).is_none());
+    assert!(trivial_message(&single_file_info("README.md"), readme).is_none());
+    
+    let own = package_json.replace("   \"bundled\": {\n-    \"version\": \"2.0.0\"\n+    \"version\": \"2.1.0\"\n   }\n",
+        "-  \"version\": \"2.0.0\",\n+  \"version\": \"2.1.0\",\n   \"private\": true\n");
+    let fast = trivial_message(&single_file_info("package.json"), &own).expect("Should match version bump");
+    assert_eq!(fast.message, "chore: bump version to 2.1.0");
+}
+
+#[test]
+fn test_changes_to_words_are_not_formatting() {
+    let patch = |path: &str, body: &str| {
+        format!("diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n{1}", path, body)
+    };
+    
+    // Removing the space between two words
+    let joined = patch("src/lib.rs", "@@ -1 +1 @@\n-    return x;\n+    returnx;\n");
+    assert!(trivial_message(&single_file_info("src/lib.rs"), &joined).is_none());
+    let literal = patch("src/lib.rs", "@@ -1 +1 @@\n-let name = \"a b\";\n+let name = \"ab\";\n");
+    assert!(trivial_message(&single_file_info("src/lib.rs"), &literal).is_none());
+    
+    // Re-indenting where indentation is syntax
+    let python = patch("app.py", "@@ -1,3 +1,3 @@\n if ready:\n-    start()\n+start()\n");
+    assert!(trivial_message(&single_file_info("app.py"), &python).is_none());
+    let makefile = patch("Makefile", "@@ -1,2 +1,2 @@\n build:\n-\tcargo build\n+        cargo build\n");
+    assert!(trivial_message(&single_file_info("Makefile"), &makefile).is_none());
+    
+    // Moving a statement to another hunk
+    let moved = patch("src/lib.rs", "@@ -1,3 +1,2 @@\n fn a() {\n-    init();\n }\n@@ -20,2 +19,3 @@\n fn b() {\n+    init();\n }\n");
+    assert!(trivial_message(&single_file_info("src/lib.rs"), &moved).is_none());
+}
+
+#[test]
+fn test_comment_typo() {
+    let patch = "diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-// Recieve the message from the server
++// Receive the message from the server
+ fn receive() {}
+";
+    
+    let fast = trivial_message(&single_file_info("src/lib.rs"), patch).expect("Should match comment typo");
+    assert_eq!(fast.rule, FastPathRule::CommentTypo);
+    assert_eq!(fast.message, "docs: fix typo in src/lib.rs comment");
+}
+
+#[test]
+fn test_comment_markers_depend_on_the_language() {
+    let patch = |path: &str, old: &str, new: &str| {
+        format!("diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n@@ -1 +1 @@\n-{1}\n+{2}\n", path, old, new)
+    };
+    
+    // Python comments, but not C includes or Rust attributes
+    let python = patch("tools/build.py", "# Recieve the build", "# Receive the build");
+    assert!(trivial_message(&single_file_info("tools/build.py"), &python).is_some());
+    let include = patch("src/main.c", "#include <stdio.h>", "#include <studio.h>");
+    assert!(trivial_message(&single_file_info("src/main.c"), &include).is_none());
+    let attribute = patch("src/lib.rs", "#[derive(Debug)]", "#[derive(Clone)]");
+    assert!(trivial_message(&single_file_info("src/lib.rs"), &attribute).is_none());
+    
+    // `--` only comments SQL-like languages; `*` needs to open a comment line
+    let flag = patch("run.sh", "--verbos", "--verbose");
+    assert!(trivial_message(&single_file_info("run.sh"), &flag).is_none());
+    let pointer = patch("src/io.c", "*len = 1;", "*len = 2;");
+    assert!(trivial_message(&single_file_info("src/io.c"), &pointer).is_none());
+}
+
+#[test]
+fn test_code_change_is_not_trivial() {
+    let patch = "diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-    let timeout = 30;
++    let timeout = 60;
+";
+    
+    assert!(trivial_message(&single_file_info("src/lib.rs"), patch).is_none());
+}
+
+#[test]
+fn test_multiple_files_are_not_trivial() {
+    let mut git_info = single_file_info("Cargo.toml");
+    git_info.status.staged_files.push(PathBuf::from("README.md"));
+    let patch = r#"diff --git a/Cargo.toml b/Cargo.toml
+@@ -1 +1 @@
+-version = "0.1.2"
++version = "0.1.3"
+"#;
+    
+    assert!(trivial_message(&git_info, patch).is_none());
+}
@@ -0,0 +1,153 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::{Config, GenerationOptions};
+use git_ai_commit::ollama::{OllamaClient, OllamaClientTrait, OllamaManager};
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::json;
+
+fn port(server: &ServerGuard) -> u16 {
+    server.url().split(':').nth(2).unwrap().parse().unwrap()
+}
+
+#[test]
+fn test_options_default_to_the_previous_sampling() {
+    let options = Config::default().ollama.options;
+    
+    assert_eq!(options.temperature, 0.7);
+    assert_eq!(options.top_p, 0.9);
+    assert_eq!(options.num_predict, None);
+    assert_eq!(options.seed, None);
+}
+
+#[test]
+fn test_options_are_read_from_the_config() {
+    // Given
+    let mut config = Config::default();
+    
+    // When
+    config.set_key("ollama.options.temperature", "0.2").unwrap();
+    config.set_key("ollama.options.seed", "42").unwrap();
+    config.set_key("ollama.options.num_predict", "120").unwrap();
+    
+    // Then
+    assert_eq!(config.ollama.options, GenerationOptions {
+        temperature: 0.2,
+        top_p: 0.9,
+        num_predict: Some(120),
+        seed: Some(42),
+    });
+    
+    // And out of range values are refused
+    for (key, value) in [
+        ("ollama.options.temperature", "3"),
+        ("ollama.options.top_p", "0"),
+        ("ollama.options.top_p", "1.5"),
+        ("ollama.options.num_predict", "0"),
+        ("ollama.options.seed", "-1"),
+    ] {
+        assert!(config.set_key(key, value).is_err(), "{} = {} should be rejected", key, value);
+    }
+}
+
+#[test]
+fn test_flags_override_the_config() {
+    // Given
+    let mut args = Args::try_parse_from(["git-ai-commit", "--temperature", "0", "--seed", "7"]).unwrap();
+    args.config.ollama.options.top_p = 0.5;
+    args.config.ollama.options.seed = Some(1);
+    
+    // When
+    let options = args.generation_options();
+    
+    // Then
+    assert_eq!(options, GenerationOptions { temperature: 0.0, top_p: 0.5, num_predict: None, seed: Some(7) });
+}
+
+#[test]
+fn test_flags_are_checked() {
+    for flags in [
+        ["--temperature", "2.5"],
+        ["--temperature", "warm"],
+        ["--top-p", "0"],
+        ["--top-p", "1.1"],
+        ["--num-predict", "0"],
+        ["--seed", "-3"],
+    ] {
+        let args = Args::try_parse_from(["git-ai-commit", flags[0], flags[1]]);
+        assert!(args.is_err(), "{:?} should be rejected", flags);
+    }
+}
+
+#[tokio::test]
+async fn test_options_are_sent_to_ollama() {
+    // Given
+    let mut server = Server::new_async().await;
+    let generate = server
+        .mock("POST", "/api/generate")
+        .match_body(Matcher::PartialJson(json!({
+            "options": { "temperature": 0.0, "top_p": 0.5, "num_predict": 80, "seed": 42 },
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "response": "test: pin seed" }).to_string())
+        .create_async()
+        .await;
+    let options = GenerationOptions { temperature: 0.0, top_p: 0.5, num_predict: Some(80), seed: Some(42) };
+    
+    // When
+    let text = OllamaClient::new(port(&server))
+        .with_chat(false)
+        .with_options(options)
+        .generate("llama3", "prompt")
+        .await
+        .unwrap();
+    
+    // Then
+    assert_eq!(text, "test: pin seed");
+    generate.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_default_options_are_sent() {
+    // Given
+    let mut server = Server::new_async().await;
+    let generate = server
+        .mock("POST", "/api/generate")
+        .match_body(Matcher::PartialJson(json!({ "options": { "temperature": 0.7, "top_p": 0.9 } })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "response": "chore: tidy" }).to_string())
+        .create_async()
+        .await;
+    
+    // When
+    OllamaClient::new(port(&server)).with_chat(false).generate("llama3", "prompt").await.unwrap();
+    
+    // Then
+    generate.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_quick_mode_cap_wins_over_the_options() {
+    // Given
+    let mut server = Server::new_async().await;
+    let chat = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJson(json!({ "options": { "num_predict": 60, "seed": 42 } })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "message": { "role": "assistant", "content": "wip" }, "done": true }).to_string())
+        .create_async()
+        .await;
+    let options = GenerationOptions { num_predict: Some(500), seed: Some(42), ..GenerationOptions::default() };
+    let manager = OllamaManager::new("llama3".to_string(), port(&server)).unwrap()
+        .with_options(options)
+        .with_num_predict(60);
+    
+    // When
+    let text = manager.generate_commit("prompt").await.unwrap();
+    
+    // Then
+    assert_eq!(text, "wip");
+    chat.assert_async().await;
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use common::git;
+use git_ai_commit::config::GitBackendKind;
+use git_ai_commit::git::backend::{self, CliBackend, GitBackend};
+use git_ai_commit::git::GitCollector;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+fn write(repo_path: &Path, file: &str, content: &str) {
+    let path = repo_path.join(file);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+}
+
+/// A repository with staged, unstaged and untracked changes of every kind
+fn busy_repo(repo_path: &Path) {
+    git(repo_path, &["init", "-b", "main"]);
+    write(repo_path, "README.md", "# Demo\n");
+    write(repo_path, "src/lib.rs", "pub fn one() {}\n");
+    write(repo_path, "src/old.rs", "pub fn old() {}\npub fn kept() {}\npub fn also_kept() {}\n");
+    write(repo_path, "gone.txt", "bye\n");
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Initial commit"]);
+    
+    write(repo_path, "src/lib.rs", "pub fn one() {}\npub fn two() {}\n");
+    write(repo_path, "src/new.rs", "pub fn new() {}\n");
+    git(repo_path, &["mv", "src/old.rs", "src/renamed.rs"]);
+    git(repo_path, &["rm", "-q", "gone.txt"]);
+    git(repo_path, &["add", "src"]);
+    write(repo_path, "README.md", "# Demo\n\nMore.\n");
+    write(repo_path, "notes/todo.txt", "later\n");
+}
+
+#[tokio::test]
+async fn test_cli_backend_reads_the_repository() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    busy_repo(temp_dir.path());
+    let backend = CliBackend::new(temp_dir.path().to_path_buf());
+    
+    // When
+    let git_info = GitCollector::new(temp_dir.path().to_path_buf())
+        .with_backend(Arc::new(CliBackend::new(temp_dir.path().to_path_buf())))
+        .collect_all()
+        .await
+        .unwrap();
+    
+    // Then
+    assert_eq!(backend.name(), "cli");
+    assert_eq!(git_info.branch_name, "main");
+    assert_eq!(git_info.last_commit.as_deref(), Some("Initial commit"));
+    assert_eq!(git_info.status.untracked_files, vec![Path::new("notes/")]);
+    assert!(backend.staged_diff().await.unwrap().contains("+pub fn two() {}"));
+    assert!(backend.git_dir().await.unwrap().ends_with(".git"));
+}
+
+#[cfg(not(feature = "libgit2"))]
+#[test]
+fn test_libgit2_backend_needs_the_feature() {
+    let error = backend::open(GitBackendKind::Libgit2, Path::new(".")).err().unwrap();
+    
+    assert!(error.to_string().contains("--features libgit2"), "{}", error);
+}
+
+#[cfg(feature = "libgit2")]
+mod libgit2 {
+    use super::*;
+    use git_ai_commit::git::GitInfo;
+    use std::path::PathBuf;
+    
+    async fn collect(repo_path: &Path, kind: GitBackendKind) -> GitInfo {
+        GitCollector::new(repo_path.to_path_buf())
+            .with_backend(backend::open(kind, repo_path).unwrap())
+            .collect_all()
+            .await
+            .unwrap()
+    }
+    
+    fn sorted(paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut paths = paths.to_vec();
+        paths.sort();
+        paths
+    }
+    
+    #[tokio::test]
+    async fn test_libgit2_backend_matches_git() {
+        // Given
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+        busy_repo(repo_path);
+        
+        // When
+        let cli = collect(repo_path, GitBackendKind::Cli).await;
+        let library = collect(repo_path, GitBackendKind::Libgit2).await;
+        
+        // Then
+        assert_eq!(library.branch_name, cli.branch_name);
+        assert_eq!(library.last_commit, cli.last_commit);
+        assert_eq!(sorted(&library.status.modified_files), sorted(&cli.status.modified_files));
+        assert_eq!(sorted(&library.status.deleted_files), sorted(&cli.status.deleted_files));
+        assert_eq!(sorted(&library.status.untracked_files), sorted(&cli.status.untracked_files));
+        assert_eq!(sorted(&library.untracked_files), sorted(&cli.untracked_files));
+        assert_eq!(library.diff_stat.insertions, cli.diff_stat.insertions);
+        assert_eq!(library.diff_stat.deletions, cli.diff_stat.deletions);
+        
+        let describe = |info: &GitInfo| -> Vec<String> {
+            let mut changes: Vec<String> = info.file_changes.iter().map(|change| change.display()).collect();
+            changes.sort();
+            changes
+        };
+        assert_eq!(describe(&library), describe(&cli));
+        assert!(describe(&library).contains(&"R  src/old.rs -> src/renamed.rs".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_libgit2_staged_patch_and_unborn_branch() {
+        // Given a repository without commits
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+        git(repo_path, &["init", "-b", "trunk"]);
+        write(repo_path, "src/lib.rs", "pub fn one() {}\n");
+        git(repo_path, &["add", "."]);
+        let backend = backend::open(GitBackendKind::Libgit2, repo_path).unwrap();
+        
+        // When
+        let patch = backend.staged_diff().await.unwrap();
+        
+        // Then
+        assert_eq!(backend.name(), "libgit2");
+        assert!(patch.contains("diff --git a/src/lib.rs b/src/lib.rs"), "{}", patch);
+        assert!(patch.contains("\n+pub fn one() {}\n"), "{}", patch);
+        assert_eq!(backend.branch_name().await.unwrap(), "trunk");
+        assert_eq!(backend.last_commit().await.unwrap(), None);
+        assert_eq!(
+            backend.git_dir().await.unwrap().canonicalize().unwrap(),
+            repo_path.join(".git").canonicalize().unwrap()
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use git_ai_commit::git::command::{git_command, git_read_command};
+use std::ffi::OsStr;
+use tempfile::tempdir;
+
+#[test]
+fn test_git_command_forces_the_c_locale() {
+    // When
+    let command = git_command(tempdir().expect("Failed to create temp dir").path());
+    
+    // Then git sees only the C locale, whatever the session's
+    let envs: Vec<(&OsStr, Option<&OsStr>)> = command.as_std().get_envs().collect();
+    assert!(envs.contains(&(OsStr::new("LC_ALL"), Some(OsStr::new("C")))));
+    assert!(envs.contains(&(OsStr::new("LANG"), Some(OsStr::new("C")))));
+    assert!(envs.contains(&(OsStr::new("LANGUAGE"), None)));
+}
+
+#[tokio::test]
+async fn test_git_command_keeps_the_caller_environment() {
+    // Given an alias that prints the environment git's children see
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    
+    // When
+    let output = git_command(temp_dir.path())
+        .args(["-c", "alias.showenv=!env", "showenv"])
+        .output()
+        .await
+        .expect("Failed to run git");
+    
+    // Then (cargo sets CARGO_* for the test process; hooks and helpers need such variables)
+    assert!(output.status.success());
+    let env = String::from_utf8_lossy(&output.stdout);
+    assert!(env.lines().any(|line| line == "LC_ALL=C"));
+    assert!(env.lines().any(|line| line.starts_with("CARGO_")));
+}
+
+#[test]
+fn test_git_read_command_takes_no_optional_locks() {
+    // When
+    let command = git_read_command(tempdir().expect("Failed to create temp dir").path());
+    
+    // Then
+    let envs: Vec<(&OsStr, Option<&OsStr>)> = command.as_std().get_envs().collect();
+    assert!(envs.contains(&(OsStr::new("GIT_OPTIONAL_LOCKS"), Some(OsStr::new("0")))));
+    assert!(envs.contains(&(OsStr::new("LC_ALL"), Some(OsStr::new("C")))));
+}
@@ -1,4 +1,4 @@
-use git_ai_commit::git::{GitInfo, GitStatus, DiffInfo, FileChange};
+use git_ai_commit::git::{ChangeCategory, GitInfo, GitStatus, DiffInfo, FileChange};
 use git_ai_commit::git::files::ChangeType;
 use std::path::PathBuf;
 
@@ -46,6 +46,7 @@ fn test_git_info_is_empty_after_staging() {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("staged.txt"),
                 old_path: None,
+                ..Default::default()
             }],
         untracked_files: vec![],
         branch_name: "main".to_string(),
@@ -76,11 +77,13 @@ fn test_git_info_with_unstaged_changes() {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("modified.txt"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Added,
                 file_path: PathBuf::from("new.txt"),
                 old_path: None,
+                ..Default::default()
             },
         ],
         untracked_files: vec![PathBuf::from("new.txt")],
@@ -112,16 +115,19 @@ fn test_git_info_with_mixed_changes() {
                 change_type: ChangeType::Added,
                 file_path: PathBuf::from("staged.txt"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("modified.txt"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Added,
                 file_path: PathBuf::from("new.txt"),
                 old_path: None,
+                ..Default::default()
             },
         ],
         untracked_files: vec![PathBuf::from("new.txt")],
@@ -132,3 +138,121 @@ fn test_git_info_with_mixed_changes() {
     assert!(!git_info.is_empty(false), "Should not be empty with mixed changes");
     assert!(!git_info.is_empty(true), "Should not be empty after staging with mixed changes");
 }
+
+#[test]
+fn test_dominant_category_docs_only() {
+    let git_info = GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("README.md"), PathBuf::from("docs/usage.md")],
+            modified_files: vec![PathBuf::from("src/main.rs")],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 3,
+            insertions: 12,
+            deletions: 4,
+            file_stats: vec![],
+        },
+        file_changes: vec![
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("README.md"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Added,
+                file_path: PathBuf::from("docs/usage.md"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("src/main.rs"),
+                old_path: None,
+                ..Default::default()
+            },
+        ],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    };
+    
+    // Unstaged code changes don't affect the category of the commit
+    assert_eq!(git_info.dominant_category(), ChangeCategory::Docs);
+}
+
+#[test]
+fn test_dominant_category_mixed_is_code() {
+    let git_info = GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("Cargo.lock"), PathBuf::from(".github/workflows/ci.yml")],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 2,
+            insertions: 8,
+            deletions: 8,
+            file_stats: vec![],
+        },
+        file_changes: vec![
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("Cargo.lock"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from(".github/workflows/ci.yml"),
+                old_path: None,
+                ..Default::default()
+            },
+        ],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    };
+    
+    assert_eq!(git_info.file_changes[0].category(), ChangeCategory::Deps);
+    assert_eq!(git_info.file_changes[1].category(), ChangeCategory::Ci);
+    assert_eq!(git_info.dominant_category(), ChangeCategory::Code);
+}
+
+#[test]
+fn test_apply_commit_type() {
+    assert_eq!(ChangeCategory::Docs.apply_commit_type("update usage section"), "docs: update usage section");
+    assert_eq!(ChangeCategory::Docs.apply_commit_type("chore(readme): fix typo"), "docs: fix typo");
+    assert_eq!(ChangeCategory::Code.apply_commit_type("feat: add flag"), "feat: add flag");
+}
+
+#[test]
+fn test_parse_raw_mode_and_symlink_changes() {
+    // Given
+    let raw = ":100644 100755 1a2b3c4 1a2b3c4 M\tscripts/release.sh\n\
+               :120000 120000 5d6e7f8 9a8b7c6 M\tcurrent\n\
+               :100644 100644 1111111 2222222 M\tsrc/lib.rs\n\
+               :100644 100755 3333333 0000000 M\tscripts/unstaged.sh\n\
+               :100644 100644 4444444 4444444 R100\tsrc/old.rs\tsrc/new.rs\n";
+    
+    // When
+    let changes = FileChange::parse_list(raw).unwrap();
+    
+    // Then
+    assert_eq!(changes.len(), 5);
+    match &changes[0].change_type {
+        ChangeType::ModeChanged { old_mode, new_mode } => {
+            assert_eq!((old_mode.as_str(), new_mode.as_str()), ("100644", "100755"));
+        }
+        other => panic!("expected a mode change, got {:?}", other),
+    }
+    assert_eq!(changes[0].display(), "M  scripts/release.sh (mode change only, now executable)");
+    assert!(matches!(changes[1].change_type, ChangeType::SymlinkChanged));
+    assert!(matches!(changes[2].change_type, ChangeType::Modified));
+    assert!(matches!(changes[3].change_type, ChangeType::Modified));
+    assert!(matches!(changes[4].change_type, ChangeType::Renamed));
+    assert_eq!(changes[4].old_path, Some(PathBuf::from("src/old.rs")));
+}
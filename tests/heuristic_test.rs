@@ -0,0 +1,64 @@
+use git_ai_commit::formatting::heuristic::heuristic_message;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+
+fn staged(changes: Vec<(ChangeType, &str)>) -> GitInfo {
+    let file_changes: Vec<FileChange> = changes.into_iter()
+        .map(|(change_type, path)| FileChange {
+            change_type,
+            file_path: PathBuf::from(path),
+            old_path: None,
+            ..Default::default()
+        })
+        .collect();
+    
+    GitInfo {
+        status: GitStatus {
+            staged_files: file_changes.iter().map(|c| c.file_path.clone()).collect(),
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: file_changes.len(),
+            insertions: 0,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes,
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_single_modified_file() {
+    let git_info = staged(vec![(ChangeType::Modified, "src/main.rs")]);
+    assert_eq!(heuristic_message(&git_info), "chore: update src/main.rs");
+}
+
+#[test]
+fn test_added_files_in_one_directory() {
+    let git_info = staged(vec![
+        (ChangeType::Added, "src/export/csv.rs"),
+        (ChangeType::Added, "src/export/json.rs"),
+    ]);
+    assert_eq!(heuristic_message(&git_info), "feat: add 2 files in src/export");
+}
+
+#[test]
+fn test_category_sets_the_type() {
+    let git_info = staged(vec![
+        (ChangeType::Modified, "README.md"),
+        (ChangeType::Deleted, "docs/old.md"),
+    ]);
+    assert_eq!(heuristic_message(&git_info), "docs: update 2 files");
+}
+
+#[test]
+fn test_removed_file() {
+    let git_info = staged(vec![(ChangeType::Deleted, "src/legacy.rs")]);
+    assert_eq!(heuristic_message(&git_info), "chore: remove src/legacy.rs");
+}
@@ -0,0 +1,206 @@
+use git_ai_commit::config::ValidationConfig;
+use git_ai_commit::formatting::candidate::Candidate;
+use git_ai_commit::formatting::provenance::Provenance;
+use git_ai_commit::git::GitCollector;
+use git_ai_commit::git::DisplayFormat;
+use git_ai_commit::history::{edit_distance, export, CommitNote, History, HistoryEntry, NOTES_REF};
+use git_ai_commit::ollama::GenerationStats;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_history_round_trip() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let history = History::new(temp_dir.path().join("nested").join("history.jsonl"));
+    let stats = GenerationStats {
+        prompt_eval_count: Some(412),
+        eval_count: Some(20),
+        eval_duration: Some(2_000_000_000),
+        total_duration: Some(2_500_000_000),
+    };
+    
+    // When
+    history
+        .append(&HistoryEntry::new(Path::new("/repo"), "gemma3:4b", "feat: add login\n", stats.clone()).with_sha("a1b2c3d"))
+        .unwrap();
+    history
+        .append(&HistoryEntry::new(Path::new("/repo"), "gemma3:1b", "fix: typo", GenerationStats::default()))
+        .unwrap();
+    
+    // Then
+    let entries = history.load().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].message, "feat: add login");
+    assert_eq!(entries[0].sha.as_deref(), Some("a1b2c3d"));
+    assert_eq!(entries[0].stats, stats);
+    assert_eq!(entries[1].model, "gemma3:1b");
+    assert_eq!(entries[1].sha, None);
+}
+
+#[test]
+fn test_history_skips_unreadable_lines() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let path = temp_dir.path().join("history.jsonl");
+    std::fs::write(&path, "not json\n").unwrap();
+    
+    let history = History::new(&path);
+    history
+        .append(&HistoryEntry::new(Path::new("/repo"), "gemma3:4b", "docs: update readme", GenerationStats::default()))
+        .unwrap();
+    
+    let entries = history.load().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].message, "docs: update readme");
+}
+
+#[test]
+fn test_missing_history_is_empty() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let history = History::new(temp_dir.path().join("history.jsonl"));
+    assert!(history.load().unwrap().is_empty());
+}
+
+#[test]
+fn test_recent_messages_are_committed_ones_from_the_repository() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let history = History::new(temp_dir.path().join("history.jsonl"));
+    let entries = [
+        ("/repo", "feat: add login", Some("a1")),
+        ("/other", "fix: other repo", Some("b2")),
+        ("/repo", "docs: dry run", None),
+        ("/repo", "fix: handle timeouts", Some("c3")),
+        ("/repo", "refactor: split parser", Some("d4")),
+    ];
+    for (repo, message, sha) in entries {
+        let mut entry = HistoryEntry::new(Path::new(repo), "gemma3:4b", message, GenerationStats::default());
+        if let Some(sha) = sha {
+            entry = entry.with_sha(sha);
+        }
+        history.append(&entry).unwrap();
+    }
+    
+    // When
+    let messages = history.recent_messages(Path::new("/repo"), 2).unwrap();
+    
+    // Then
+    assert_eq!(messages, vec!["refactor: split parser", "fix: handle timeouts"]);
+}
+
+#[test]
+fn test_commit_note_round_trip() {
+    // Given
+    let rules = ValidationConfig::default();
+    let candidates = vec![
+        Candidate::new("llama3", "fix: retry uploads", &rules),
+        Candidate::new("qwen2.5-coder", "fix: added retry.", &rules),
+    ];
+    let generated_by = Provenance::new("llama3", "{CONTEXT}");
+    let stats = GenerationStats { eval_count: Some(20), ..GenerationStats::default() };
+    
+    // When
+    let note = CommitNote::new("fix: retry uploads\n", "the prompt", Some(&generated_by), stats.clone())
+        .with_candidates(&candidates);
+    let parsed = CommitNote::parse(&note.to_json().unwrap()).unwrap();
+    
+    // Then
+    assert_eq!(parsed, note);
+    assert_eq!(parsed.model.as_deref(), Some("llama3"));
+    assert_eq!(parsed.template_hash, Some(generated_by.template_hash));
+    assert_eq!(parsed.message, "fix: retry uploads");
+    assert_eq!(parsed.stats, stats);
+    assert_eq!(parsed.candidates[1].model, "qwen2.5-coder");
+    assert!(!parsed.candidates[1].violations.is_empty());
+    
+    let display = parsed.display();
+    assert!(display.contains("Model:     llama3"), "{}", display);
+    assert!(display.contains("1. [llama3] (chosen)"), "{}", display);
+    
+    // Only notes written by git-ai-commit are read
+    assert!(CommitNote::parse("Reviewed by Dev").is_err());
+}
+
+#[tokio::test]
+async fn test_commit_note_is_read_back_from_the_commit() {
+    // Given a repository with one commit
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    for args in [
+        vec!["init", "-q"],
+        vec!["config", "user.name", "Dev"],
+        vec!["config", "user.email", "dev@example.com"],
+        vec!["commit", "-q", "--allow-empty", "-m", "feat: start"],
+    ] {
+        let status = Command::new("git").args(&args).current_dir(repo_path).status().unwrap();
+        assert!(status.success());
+    }
+    let collector = GitCollector::new(repo_path.to_path_buf());
+    assert_eq!(collector.read_note(NOTES_REF, "HEAD").await.unwrap(), None);
+    
+    // When
+    let note = CommitNote::new("feat: start", "the prompt", None, GenerationStats::default());
+    collector.add_note(NOTES_REF, &note.to_json().unwrap()).await.unwrap();
+    
+    // Then
+    let read = collector.read_note(NOTES_REF, "HEAD").await.unwrap().expect("HEAD has a note");
+    assert_eq!(CommitNote::parse(&read).unwrap(), note);
+    assert!(collector.read_note(NOTES_REF, "no-such-commit").await.is_err());
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance("fix: typo", "fix: typo"), 0);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "feat"), 4);
+    assert_eq!(edit_distance("fix: naïve", "fix: naive"), 1);
+}
+
+#[test]
+fn test_proposed_message_is_kept_only_when_edited() {
+    let entry = HistoryEntry::new(Path::new("/repo"), "gemma3:4b", "fix: typo", GenerationStats::default())
+        .with_proposed("fix: typo\n");
+    assert_eq!(entry.proposed, None);
+    assert_eq!(entry.edit_distance(), 0);
+    
+    let entry = HistoryEntry::new(Path::new("/repo"), "gemma3:4b", "fix(ui): typo", GenerationStats::default())
+        .with_proposed("fix: typo");
+    assert_eq!(entry.proposed.as_deref(), Some("fix: typo"));
+    assert_eq!(entry.edit_distance(), 4);
+}
+
+#[test]
+fn test_export_history() {
+    // Given an edited, committed message and a dry run
+    let stats = GenerationStats {
+        prompt_eval_count: Some(412),
+        eval_count: Some(20),
+        total_duration: Some(2_500_000_000),
+        ..GenerationStats::default()
+    };
+    let entries = vec![
+        HistoryEntry::new(Path::new("/repo"), "gemma3:4b", "feat: add login, logout", stats)
+            .with_proposed("feat: add login")
+            .with_sha("a1b2c3d"),
+        HistoryEntry::new(Path::new("/repo"), "llama3", "fix: typo", GenerationStats::default()),
+    ];
+    
+    // When
+    let csv = export(&entries, DisplayFormat::Csv).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&export(&entries, DisplayFormat::Json).unwrap()).unwrap();
+    
+    // Then
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "timestamp,repository,model,accepted,sha,latency_ms,prompt_tokens,generated_tokens,edit_distance,message");
+    assert!(lines[1].ends_with(",/repo,gemma3:4b,true,a1b2c3d,2500,412,20,8,\"feat: add login, logout\""), "{}", lines[1]);
+    assert!(lines[2].ends_with(",/repo,llama3,false,,,,,0,fix: typo"), "{}", lines[2]);
+    assert_eq!(export(&entries, DisplayFormat::Plain).unwrap(), csv);
+    
+    assert_eq!(json[0]["accepted"], true);
+    assert_eq!(json[0]["latency_ms"], 2500);
+    assert_eq!(json[0]["edit_distance"], 8);
+    assert_eq!(json[1]["sha"], serde_json::Value::Null);
+    
+    assert!(export(&entries, DisplayFormat::Table).is_err());
+}
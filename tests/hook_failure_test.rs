@@ -0,0 +1,126 @@
+mod common;
+
+use common::git;
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::git::hooks::{active_hooks, changed_files, install_hook, HookFailure, HookRecovery, COMMIT_HOOKS};
+use git_ai_commit::git::lock::run_writing_index;
+use git_ai_commit::git::GitCollector;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+#[cfg(unix)]
+fn write_hook(hooks_dir: &Path, name: &str, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(hooks_dir).unwrap();
+    let hook_path = hooks_dir.join(name);
+    std::fs::write(&hook_path, script).unwrap();
+    std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_only_the_users_hooks_are_active() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let hooks_dir = temp_dir.path();
+    write_hook(hooks_dir, "pre-commit", "#!/bin/sh\nexit 1\n");
+    install_hook(hooks_dir, "prepare-commit-msg", "git-ai-commit --hook \"$1\" || true").unwrap();
+    std::fs::write(hooks_dir.join("commit-msg"), "#!/bin/sh\nexit 1\n").unwrap();
+    std::fs::write(hooks_dir.join("pre-push.sample"), "#!/bin/sh\n").unwrap();
+    
+    // When
+    let active = active_hooks(hooks_dir, COMMIT_HOOKS);
+    
+    // Then ours never fails and git skips hooks that aren't executable
+    assert_eq!(active, vec!["pre-commit"]);
+}
+
+#[test]
+fn test_changed_files() {
+    let before: BTreeMap<PathBuf, String> = [("src/a.rs", "1"), ("src/b.rs", "2"), ("old.txt", "3")]
+        .into_iter()
+        .map(|(path, hash)| (PathBuf::from(path), hash.to_string()))
+        .collect();
+    let after: BTreeMap<PathBuf, String> = [("src/a.rs", "1"), ("src/b.rs", "9"), ("new.txt", "4")]
+        .into_iter()
+        .map(|(path, hash)| (PathBuf::from(path), hash.to_string()))
+        .collect();
+    
+    assert_eq!(changed_files(&before, &after), vec![PathBuf::from("old.txt"), PathBuf::from("src/b.rs"), PathBuf::from("new.txt")]);
+    assert!(changed_files(&before, &before).is_empty());
+}
+
+#[test]
+fn test_failure_description_and_choices() {
+    // Given a formatter hook that rewrote a file
+    let failure = HookFailure {
+        hooks: vec!["pre-commit".to_string()],
+        modified: vec![PathBuf::from("src/main.rs")],
+        output: "Git error: Git commit failed: black....Failed\n- files were modified by this hook".to_string(),
+    };
+    
+    // Then
+    let description = failure.describe();
+    assert!(description.starts_with("The commit was rejected, most likely by the pre-commit hook"), "{}", description);
+    assert!(description.contains("It changed 1 file(s) that aren't staged yet:\n  - src/main.rs"), "{}", description);
+    assert!(description.contains("files were modified by this hook"), "{}", description);
+    assert!(failure.hint().contains("git add"));
+    assert_eq!(failure.recoveries(), vec![
+        HookRecovery::StageAndRegenerate,
+        HookRecovery::StageAndRetry,
+        HookRecovery::NoVerify,
+        HookRecovery::Cancel,
+    ]);
+    
+    // A hook that only rejected can be retried once the problem is fixed
+    let rejected = HookFailure { modified: vec![], ..failure };
+    assert!(!rejected.describe().contains("It changed"));
+    assert_eq!(rejected.recoveries()[0], HookRecovery::Retry);
+    assert!(rejected.hint().contains("--no-verify"));
+}
+
+#[test]
+fn test_no_verify_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--no-verify"]).expect("Failed to parse args");
+    assert!(args.no_verify);
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "-n"]).expect("Failed to parse args");
+    assert!(args.no_verify);
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert!(!args.no_verify);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_files_a_hook_rewrites_are_noticed_and_restaged() {
+    // Given a pre-commit hook that trims trailing spaces and fails when it had to
+    let temp_dir = tempdir().unwrap();
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init", "-q"]);
+    git(repo_path, &["commit", "-q", "--allow-empty", "-m", "Initial commit"]);
+    write_hook(&repo_path.join(".git/hooks"), "pre-commit", "#!/bin/sh\n\
+        if grep -q ' $' lib.rs; then sed -i 's/ *$//' lib.rs; echo 'lib.rs was reformatted'; exit 1; fi\n");
+    std::fs::write(repo_path.join("lib.rs"), "fn main() {}   \n").unwrap();
+    std::fs::write(repo_path.join("notes.md"), "notes\n").unwrap();
+    git(repo_path, &["add", "."]);
+    let collector = GitCollector::new(repo_path.to_path_buf());
+    let staged = collector.collect_all().await.unwrap().status.staged_files;
+    
+    // When the commit is attempted
+    let before = collector.worktree_hashes(&staged).await.unwrap();
+    let output = run_writing_index(repo_path, &["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-m", "add lib"]).await.unwrap();
+    let after = collector.worktree_hashes(&staged).await.unwrap();
+    
+    // Then the hook's output and the file it rewrote are known
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lib.rs was reformatted"));
+    let modified = changed_files(&before, &after);
+    assert_eq!(modified, vec![PathBuf::from("lib.rs")]);
+    assert_eq!(active_hooks(&collector.hooks_dir().await.unwrap(), COMMIT_HOOKS), vec!["pre-commit"]);
+    
+    // And once it is staged, the commit goes through
+    collector.stage_files(&modified).await.unwrap();
+    git(repo_path, &["commit", "-q", "-m", "add lib"]);
+    assert_eq!(git(repo_path, &["show", "HEAD:lib.rs"]), "fn main() {}\n");
+}
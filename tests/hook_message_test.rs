@@ -0,0 +1,132 @@
+use git_ai_commit::config::PostprocessSteps;
+use git_ai_commit::formatting::merge::{append_trailers, comment_prefix, merge_with_user_message, ExistingMessage};
+use git_ai_commit::formatting::postprocess;
+
+const GIT_COMMENTS: &str = "# Please enter the commit message for your changes.\n# Changes to be committed:\n#\tmodified:   src/main.rs";
+
+#[test]
+fn test_parse_empty_message_file() {
+    // Given: the file git prepares for a plain `git commit`
+    let text = format!("\n{}\n", GIT_COMMENTS);
+    
+    // When
+    let existing = ExistingMessage::parse(&text);
+    
+    // Then
+    assert!(!existing.has_content());
+    assert_eq!(existing.subject(), None);
+    assert_eq!(existing.comments, GIT_COMMENTS);
+}
+
+#[test]
+fn test_parse_user_message() {
+    // Given: `git commit -m "fix login redirect"`
+    let text = format!("fix login redirect\n\n{}\n", GIT_COMMENTS);
+    
+    // When
+    let existing = ExistingMessage::parse(&text);
+    
+    // Then
+    assert!(existing.has_content());
+    assert_eq!(existing.subject(), Some("fix login redirect"));
+    assert_eq!(existing.content, "fix login redirect");
+}
+
+#[test]
+fn test_merge_keeps_user_subject_and_body() {
+    let merged = merge_with_user_message(
+        "fix login redirect\n\nReported by QA.",
+        "Redirect to the original page after the session is refreshed.\n",
+    );
+    
+    assert_eq!(
+        merged,
+        "fix login redirect\n\nReported by QA.\n\nRedirect to the original page after the session is refreshed."
+    );
+}
+
+#[test]
+fn test_merge_drops_the_models_thinking() {
+    // Given: a reasoning model's answer to the body prompt
+    let answer = "<think>\nThe user already wrote the subject, so only a body is needed.\n</think>\n\nRedirect to the original page after the session is refreshed.";
+    
+    // When: the hook cleans it before merging
+    let body = postprocess::clean(answer, &PostprocessSteps::default());
+    let merged = merge_with_user_message("fix login redirect", &body);
+    
+    // Then
+    assert_eq!(merged, "fix login redirect\n\nRedirect to the original page after the session is refreshed.");
+}
+
+#[test]
+fn test_merge_with_empty_body_keeps_user_message() {
+    assert_eq!(merge_with_user_message("fix login redirect", "  \n"), "fix login redirect");
+}
+
+#[test]
+fn test_render_preserves_comments() {
+    let existing = ExistingMessage::parse(&format!("fix login redirect\n\n{}\n", GIT_COMMENTS));
+    
+    let rendered = existing.render_with("fix login redirect\n\nRedirect after refresh.");
+    
+    assert_eq!(
+        rendered,
+        format!("fix login redirect\n\nRedirect after refresh.\n\n{}\n", GIT_COMMENTS)
+    );
+}
+
+#[test]
+fn test_append_trailers() {
+    let trailers = vec!["Refs: ACME".to_string(), "Signed-off-by: Dev <dev@acme.test>".to_string()];
+    
+    assert_eq!(
+        append_trailers("fix: retry uploads", &trailers),
+        "fix: retry uploads\n\nRefs: ACME\nSigned-off-by: Dev <dev@acme.test>"
+    );
+    assert_eq!(
+        append_trailers("fix: retry uploads\n\nRefs: ACME", &trailers),
+        "fix: retry uploads\n\nRefs: ACME\nSigned-off-by: Dev <dev@acme.test>"
+    );
+    assert_eq!(append_trailers("fix: retry uploads\n", &[]), "fix: retry uploads");
+}
+
+#[test]
+fn test_parse_stops_at_the_scissors_line() {
+    // Given: `git commit -v -m "fix login redirect"`
+    let text = format!(
+        "fix login redirect\n\n{}\n# ------------------------ >8 ------------------------\n\
+         # Do not modify or remove the line above.\n\
+         diff --git a/src/main.rs b/src/main.rs\n\
+         +fn main() {{}}\n",
+        GIT_COMMENTS
+    );
+    
+    // When
+    let existing = ExistingMessage::parse(&text);
+    
+    // Then the diff below the scissors is not part of the message
+    assert_eq!(existing.content, "fix login redirect");
+    assert!(existing.comments.ends_with("+fn main() {}"));
+}
+
+#[test]
+fn test_parse_with_a_custom_comment_char() {
+    // Given: `core.commentChar = ;`
+    let text = "fix login redirect\n\n#1 is the issue this fixes\n; Please enter the commit message for your changes.\n";
+    let comment = comment_prefix(Some(";"), text);
+    
+    // When
+    let existing = ExistingMessage::parse_with(text, &comment);
+    
+    // Then
+    assert_eq!(existing.content, "fix login redirect\n\n#1 is the issue this fixes");
+    assert_eq!(existing.comments, "; Please enter the commit message for your changes.");
+}
+
+#[test]
+fn test_comment_prefix_of_auto_is_read_from_the_file() {
+    assert_eq!(comment_prefix(None, "fix\n# comment\n"), "#");
+    assert_eq!(comment_prefix(Some("auto"), "#1 fix\n\n; Please enter the commit message\n;\n"), ";");
+    assert_eq!(comment_prefix(Some("auto"), "#1 fix\n@ ------------------------ >8 ------------------------\ndiff --git a/x b/x\n"), "@");
+    assert_eq!(comment_prefix(Some("//"), "fix\n"), "//");
+}
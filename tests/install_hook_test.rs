@@ -0,0 +1,64 @@
+mod common;
+
+use common::git;
+use git_ai_commit::git::hooks::{install_hook, uninstall_hook, HOOK_MARKER, HOOK_SKIP_ENV, PREPARE_COMMIT_MSG_HOOK};
+use git_ai_commit::git::lock::run_writing_index;
+use tempfile::tempdir;
+
+#[test]
+fn test_install_and_uninstall_prepare_commit_msg_hook() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let hooks_dir = temp_dir.path().join("hooks");
+    
+    // When
+    let hook_path = install_hook(&hooks_dir, "prepare-commit-msg", PREPARE_COMMIT_MSG_HOOK).unwrap();
+    
+    // Then the hook runs git-ai-commit in hook mode with git's arguments
+    let script = std::fs::read_to_string(&hook_path).unwrap();
+    assert!(script.contains(HOOK_MARKER));
+    assert!(script.contains("git-ai-commit --hook \"$1\" --hook-source \"$2\" || true"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&hook_path).unwrap().permissions().mode() & 0o777, 0o755);
+    }
+    
+    assert_eq!(uninstall_hook(&hooks_dir, "prepare-commit-msg").unwrap(), Some(hook_path.clone()));
+    assert!(!hook_path.exists());
+    assert_eq!(uninstall_hook(&hooks_dir, "prepare-commit-msg").unwrap(), None);
+}
+
+#[test]
+fn test_user_hooks_are_left_alone() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let hooks_dir = temp_dir.path();
+    std::fs::write(hooks_dir.join("prepare-commit-msg"), "#!/bin/sh\nmy-own-tool \"$1\"\n").unwrap();
+    
+    assert!(install_hook(hooks_dir, "prepare-commit-msg", PREPARE_COMMIT_MSG_HOOK).is_err());
+    assert!(uninstall_hook(hooks_dir, "prepare-commit-msg").is_err());
+    assert!(hooks_dir.join("prepare-commit-msg").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_own_commits_tell_the_hook_to_skip() {
+    // Given a hook that records whether it was asked to skip
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    git(repo_path, &["config", "user.name", "Test"]);
+    git(repo_path, &["config", "user.email", "test@example.com"]);
+    let record = repo_path.join("skip.txt");
+    let body = format!("echo \"${}\" > \"{}\"", HOOK_SKIP_ENV, record.display());
+    install_hook(&repo_path.join(".git").join("hooks"), "prepare-commit-msg", &body).unwrap();
+    std::fs::write(repo_path.join("a.txt"), "a").unwrap();
+    git(repo_path, &["add", "a.txt"]);
+    
+    // When git-ai-commit commits
+    let output = run_writing_index(repo_path, &["commit", "-m", "feat: add a"]).await.unwrap();
+    
+    // Then
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(&record).unwrap().trim(), "1");
+}
@@ -20,7 +20,7 @@ async fn test_interactive_stage_unstaged() {
     // Initialize a new git repository
     Command::new("git")
         .args(["init"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to initialize git repo");
     
@@ -35,13 +35,13 @@ async fn test_interactive_stage_unstaged() {
     // Add and commit both files
     Command::new("git")
         .args(["add", "."])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to add files");
     
     Command::new("git")
         .args(["commit", "-m", "Initial commit"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to commit files");
     
@@ -52,7 +52,7 @@ async fn test_interactive_stage_unstaged() {
     // Stage only file1
     Command::new("git")
         .args(["add", "file1.txt"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to stage file1");
     
@@ -84,7 +84,7 @@ async fn test_interactive_stage_and_regenerate() {
     // Initialize git
     Command::new("git")
         .args(["init"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to initialize git repo");
     
@@ -99,13 +99,13 @@ async fn test_interactive_stage_and_regenerate() {
     // Add and commit both files
     Command::new("git")
         .args(["add", "."])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to add files");
     
     Command::new("git")
         .args(["commit", "-m", "Initial commit"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to commit files");
     
@@ -116,7 +116,7 @@ async fn test_interactive_stage_and_regenerate() {
     // Stage only file1
     Command::new("git")
         .args(["add", "file1.rs"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .status()
         .expect("Failed to stage file1");
     
@@ -151,11 +151,13 @@ async fn test_prompt_shows_staged_and_unstaged() {
                     filename: "src/main.rs".to_string(),
                     insertions: 10,
                     deletions: 2,
+                    ..Default::default()
                 },
                 FileStat {
                     filename: "Cargo.toml".to_string(),
                     insertions: 5,
                     deletions: 1,
+                    ..Default::default()
                 },
             ],
         },
@@ -164,11 +166,13 @@ async fn test_prompt_shows_staged_and_unstaged() {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("src/main.rs"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("Cargo.toml"),
                 old_path: None,
+                ..Default::default()
             },
         ],
         untracked_files: vec![],
@@ -185,3 +189,50 @@ async fn test_prompt_shows_staged_and_unstaged() {
     assert!(prompt.contains("Unstaged changes (will NOT be committed):"));
     assert!(prompt.contains("Cargo.toml"));
 }
+
+#[tokio::test]
+async fn test_stage_chosen_files_when_nothing_is_staged() {
+    // Given: a modified, a deleted and an untracked file, none of them staged
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(repo_path)
+            .status()
+            .expect("Failed to run git");
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init"]);
+    std::fs::write(repo_path.join("kept.txt"), "one").unwrap();
+    std::fs::write(repo_path.join("removed.txt"), "two").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-m", "Initial commit"]);
+    std::fs::write(repo_path.join("kept.txt"), "changed").unwrap();
+    std::fs::remove_file(repo_path.join("removed.txt")).unwrap();
+    std::fs::write(repo_path.join("new.txt"), "new").unwrap();
+    
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let git_info = git_collector.collect_all().await.expect("Failed to collect status");
+    assert!(git_info.status.staged_files.is_empty());
+    let mut unstaged = git_info.unstaged_paths();
+    unstaged.sort();
+    assert_eq!(
+        unstaged,
+        vec![PathBuf::from("kept.txt"), PathBuf::from("new.txt"), PathBuf::from("removed.txt")]
+    );
+    
+    // When: only the deletion and the new file are chosen
+    git_collector
+        .stage_files(&[PathBuf::from("removed.txt"), PathBuf::from("new.txt")])
+        .await
+        .expect("Failed to stage files");
+    
+    // Then
+    let git_info = git_collector.collect_all().await.expect("Failed to collect status");
+    let mut staged = git_info.status.staged_files.clone();
+    staged.sort();
+    assert_eq!(staged, vec![PathBuf::from("new.txt"), PathBuf::from("removed.txt")]);
+    assert_eq!(git_info.unstaged_paths(), vec![PathBuf::from("kept.txt")]);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::git;
+use git_ai_commit::analysis::language::{Ecosystem, ProjectLayout};
+use git_ai_commit::config::MessageStyle;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::GitCollector;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// Write `files` under `root`, creating their directories
+fn write_files(root: &Path, files: &[(&str, &str)]) -> Vec<PathBuf> {
+    for (path, content) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    files.iter().map(|(path, _)| PathBuf::from(path)).collect()
+}
+
+fn scope(layout: &ProjectLayout, path: &str) -> Option<String> {
+    layout.classify(Path::new(path)).map(|file_scope| file_scope.scope)
+}
+
+#[test]
+fn test_monorepo_files_are_scoped_by_package() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let manifests = write_files(temp_dir.path(), &[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+        ("crates/parser/Cargo.toml", "[package]\nname = \"acme-parser\"\n"),
+        ("packages/ui/package.json", "{\"name\": \"@acme/ui\"}"),
+        ("packages/ui/node_modules/left-pad/package.json", "{\"name\": \"left-pad\"}"),
+        ("tools/pyproject.toml", "[project]\nname = \"acme-tools\"\n"),
+        ("tools/src/acme_tools/__init__.py", ""),
+    ]);
+    
+    // When
+    let layout = ProjectLayout::detect(temp_dir.path(), &manifests);
+    
+    // Then
+    assert_eq!(layout.packages().len(), 3);
+    assert_eq!(scope(&layout, "crates/parser/src/lexer.rs"), Some("acme-parser".to_string()));
+    assert_eq!(scope(&layout, "crates/parser/README.md"), Some("acme-parser".to_string()));
+    assert_eq!(scope(&layout, "packages/ui/src/Button.tsx"), Some("ui".to_string()));
+    assert_eq!(scope(&layout, "tools/src/acme_tools/cli.py"), Some("acme_tools".to_string()));
+    assert_eq!(layout.classify(Path::new("tools/src/acme_tools/cli.py")).unwrap().package, "acme-tools");
+    assert_eq!(scope(&layout, "docs/index.md"), None);
+}
+
+#[test]
+fn test_single_package_files_are_scoped_by_module() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let manifests = write_files(temp_dir.path(), &[
+        ("Cargo.toml", "[package]\nname = \"git-ai-commit\"\n"),
+        ("pyproject.toml", "[tool.poetry]\nname = \"helpers\"\n"),
+        ("helpers/__init__.py", ""),
+        ("helpers/api/__init__.py", ""),
+    ]);
+    
+    // When
+    let layout = ProjectLayout::detect(temp_dir.path(), &manifests);
+    
+    // Then
+    assert_eq!(scope(&layout, "src/git/collector.rs"), Some("git".to_string()));
+    assert_eq!(scope(&layout, "src/config.rs"), Some("config".to_string()));
+    assert_eq!(scope(&layout, "src/main.rs"), None);
+    assert_eq!(scope(&layout, "tests/config_test.rs"), None);
+    assert_eq!(scope(&layout, "helpers/api/views.py"), Some("api".to_string()));
+    assert_eq!(scope(&layout, "helpers/models.py"), Some("models".to_string()));
+    assert_eq!(scope(&layout, "helpers/__init__.py"), Some("helpers".to_string()));
+    
+    let suggestions = layout.suggest(["src/git/diff.rs", "src/config.rs", "src/git/files.rs"].map(Path::new));
+    assert_eq!(suggestions[0].scope, "git");
+    assert_eq!(suggestions[0].ecosystem, Ecosystem::Rust);
+    assert_eq!(suggestions[0].describe(), "git (Rust crate git-ai-commit, 2 files)");
+    assert_eq!(suggestions[1].describe(), "config (Rust crate git-ai-commit, 1 file)");
+}
+
+#[tokio::test]
+async fn test_prompt_suggests_scopes_for_staged_files() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    write_files(repo_path, &[
+        ("crates/parser/Cargo.toml", "[package]\nname = \"acme-parser\"\n"),
+        ("crates/parser/src/lib.rs", "pub fn parse() {}\n"),
+        ("packages/ui/package.json", "{\"name\": \"@acme/ui\"}"),
+        ("packages/ui/src/index.ts", "export const ui = 1;\n"),
+    ]);
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    write_files(repo_path, &[
+        ("crates/parser/src/lib.rs", "pub fn parse(strict: bool) {}\n"),
+        ("crates/parser/src/lexer.rs", "pub fn lex() {}\n"),
+        ("packages/ui/src/index.ts", "export const ui = 2;\n"),
+    ]);
+    git(repo_path, &["add", "."]);
+    let git_collector = GitCollector::new(repo_path.join("crates"));
+    
+    // When
+    let layout = git_collector.get_project_layout().await;
+    let git_info = git_collector.collect_all().await.unwrap();
+    let prompt_builder = PromptBuilder::new(10, 50).with_layout(layout);
+    let prompt = prompt_builder.build(&git_info);
+    let plain_prompt = prompt_builder.with_style(Some(MessageStyle::Plain)).build(&git_info);
+    
+    // Then
+    assert!(prompt.contains(
+        "Scopes from the project layout (use one of these as the conventional scope):\n  \
+         - acme-parser (Rust crate acme-parser, 2 files)\n  \
+         - ui (npm package @acme/ui, 1 file)\n"
+    ), "{}", prompt);
+    assert!(!plain_prompt.contains("Scopes from the project layout"), "{}", plain_prompt);
+}
@@ -0,0 +1,119 @@
+use git_ai_commit::config::{Config, LocalConfig, Provider};
+use git_ai_commit::ollama::local::{self, apply_template};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+#[test]
+fn test_local_provider_config() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = r#"
+        provider = "local"
+        
+        [local]
+        model_path = "/models/qwen2.5-coder-1.5b-instruct-q4_k_m.gguf"
+        template = "<|im_start|>user\n{PROMPT}<|im_end|>\n<|im_start|>assistant\n"
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    // When
+    let config = Config::load_from_path(&config_path).unwrap();
+    
+    // Then the model goes by its file name and the tokenizer is looked for next to it
+    assert_eq!(config.provider, Provider::Local);
+    assert_eq!(config.model_for(Provider::Local), "qwen2.5-coder-1.5b-instruct-q4_k_m");
+    assert_eq!(config.local.tokenizer(), Some(PathBuf::from("/models/tokenizer.json")));
+    assert_eq!(config.local.temperature, 0.2);
+    assert_eq!(
+        apply_template(&config.local.template, "describe the diff"),
+        "<|im_start|>user\ndescribe the diff<|im_end|>\n<|im_start|>assistant\n"
+    );
+}
+
+#[test]
+fn test_template_without_placeholder_goes_before_the_prompt() {
+    assert_eq!(apply_template("{PROMPT}", "prompt"), "prompt");
+    assert_eq!(apply_template("<s>", "prompt"), "<s>prompt");
+}
+
+#[test]
+fn test_local_settings_are_checked() {
+    let mut config = Config::default();
+    assert!(config.set_key("local.model_path", "/models/llama.gguf").is_ok());
+    assert_eq!(config.local.model_path.as_deref(), Some(Path::new("/models/llama.gguf")));
+    assert!(config.set_key("local.temperature", "3.5").is_err());
+    assert!(config.set_key("provider", "local").is_ok());
+}
+
+#[test]
+fn test_missing_model_files_are_reported() {
+    let temp_dir = tempdir().unwrap();
+    let model = temp_dir.path().join("model.gguf");
+    
+    let error = local::check_files(&LocalConfig::default()).unwrap_err();
+    assert!(error.to_string().contains("local.model_path"), "{}", error);
+    
+    let config = LocalConfig { model_path: Some(model.clone()), ..LocalConfig::default() };
+    let error = local::check_files(&config).unwrap_err();
+    assert!(error.to_string().contains("model.gguf not found"), "{}", error);
+    
+    std::fs::write(&model, b"GGUF").unwrap();
+    let error = local::check_files(&config).unwrap_err();
+    assert!(error.to_string().contains("tokenizer.json not found; check local.tokenizer_path"), "{}", error);
+    
+    std::fs::write(temp_dir.path().join("tokenizer.json"), "{}").unwrap();
+    assert!(local::check_files(&config).is_ok());
+}
+
+#[cfg(not(feature = "local-gguf"))]
+#[test]
+fn test_local_provider_needs_the_feature() {
+    let error = local::open(&LocalConfig::default(), None).err().unwrap();
+    
+    assert!(error.to_string().contains("--features local-gguf"), "{}", error);
+}
+
+#[cfg(feature = "local-gguf")]
+mod gguf {
+    use super::*;
+    use candle_core::quantized::gguf_file::{self, Value};
+    use std::fs::File;
+    
+    /// A GGUF file holding metadata only, and an empty tokenizer next to it
+    fn model_files(dir: &Path, architecture: &str) -> LocalConfig {
+        let model = dir.join("tiny.gguf");
+        let mut file = File::create(&model).unwrap();
+        let architecture = Value::String(architecture.to_string());
+        let context_length = Value::U32(2048);
+        let context_key = format!("{}.context_length", architecture.to_string().unwrap());
+        gguf_file::write(&mut file, &[("general.architecture", &architecture), (&context_key, &context_length)], &[]).unwrap();
+        std::fs::write(dir.join("tokenizer.json"), "{}").unwrap();
+        LocalConfig { model_path: Some(model), ..LocalConfig::default() }
+    }
+    
+    #[tokio::test]
+    async fn test_model_file_metadata() {
+        let temp_dir = tempdir().unwrap();
+        let config = model_files(temp_dir.path(), "llama");
+        
+        let client = local::open(&config, None).unwrap();
+        
+        assert!(client.is_running().await);
+        assert_eq!(client.list_models().await.unwrap(), vec!["tiny"]);
+        assert!(client.has_model("tiny").await.unwrap());
+        assert!(!client.has_model("llama3.2:1b").await.unwrap());
+        assert_eq!(client.context_window("tiny").await.unwrap(), Some(2048));
+        assert!(client.pull_model("llama3.2:1b").await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_unsupported_architecture_is_named() {
+        let temp_dir = tempdir().unwrap();
+        let config = model_files(temp_dir.path(), "gpt2");
+        
+        let error = local::open(&config, None).unwrap().generate("tiny", "prompt").await.unwrap_err();
+        
+        assert!(error.to_string().contains("'gpt2' model"), "{}", error);
+    }
+}
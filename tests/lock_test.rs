@@ -0,0 +1,88 @@
+use git_ai_commit::git::lock::{index_lock_path, is_lock_contention, wait_for_index_lock};
+use git_ai_commit::git::GitCollector;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tempfile::tempdir;
+
+fn init_repo(repo_path: &Path) {
+    let status = Command::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success());
+}
+
+#[test]
+fn test_is_lock_contention() {
+    let stderr = "fatal: Unable to create '/repo/.git/index.lock': File exists.\n\n\
+                  Another git process seems to be running in this repository";
+    assert!(is_lock_contention(stderr));
+    assert!(!is_lock_contention("error: pathspec 'missing.txt' did not match any file(s) known to git"));
+}
+
+#[tokio::test]
+async fn test_wait_returns_once_the_lock_is_released() {
+    // Given a lock another process releases shortly
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    let lock = index_lock_path(temp_dir.path()).await.unwrap();
+    assert_eq!(lock, temp_dir.path().join(".git").join("index.lock"));
+    std::fs::write(&lock, "").unwrap();
+    
+    let released = lock.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        std::fs::remove_file(released).unwrap();
+    });
+    
+    // When
+    let delays = [Duration::from_millis(20); 10];
+    let result = wait_for_index_lock(&lock, &delays).await;
+    
+    // Then
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_persistent_lock_is_reported_with_a_fix() {
+    // Given a lock nobody releases
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    init_repo(temp_dir.path());
+    let lock = index_lock_path(temp_dir.path()).await.unwrap();
+    std::fs::write(&lock, "").unwrap();
+    
+    // When
+    let error = wait_for_index_lock(&lock, &[Duration::from_millis(5); 2]).await.unwrap_err();
+    
+    // Then the error names the lock and how to remove it
+    let message = error.to_string();
+    assert!(message.contains("The git index is locked"), "{}", message);
+    assert!(message.contains(&format!("rm \"{}\"", lock.display())), "{}", message);
+}
+
+#[tokio::test]
+async fn test_staging_waits_for_a_released_lock() {
+    // Given an IDE briefly holding the index while a file is staged
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    init_repo(repo_path);
+    std::fs::write(repo_path.join("new.txt"), "new").unwrap();
+    let lock = index_lock_path(repo_path).await.unwrap();
+    std::fs::write(&lock, "").unwrap();
+    
+    let released = lock.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        std::fs::remove_file(released).unwrap();
+    });
+    
+    // When
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    git_collector.stage_files(&[PathBuf::from("new.txt")]).await.expect("Staging should wait for the lock");
+    
+    // Then
+    let git_info = git_collector.collect_all().await.unwrap();
+    assert_eq!(git_info.status.staged_files, vec![PathBuf::from("new.txt")]);
+}
@@ -0,0 +1,40 @@
+use git_ai_commit::utils::logging::{enabled, format_line, init, level_for};
+use tracing::Level;
+
+#[test]
+fn test_level_for_flags() {
+    assert_eq!(level_for(true, 0), Level::ERROR);
+    assert_eq!(level_for(false, 0), Level::INFO);
+    assert_eq!(level_for(false, 1), Level::DEBUG);
+    assert_eq!(level_for(false, 2), Level::TRACE);
+    assert_eq!(level_for(false, 5), Level::TRACE);
+}
+
+#[test]
+fn test_format_line() {
+    assert_eq!(format_line(Level::WARN, "git_ai_commit::config", "No upstream configured"), "[WARN] No upstream configured");
+    assert_eq!(format_line(Level::INFO, "git_ai_commit::ollama::manager", "Using profile: work"), "[INFO] Using profile: work");
+    assert_eq!(
+        format_line(Level::DEBUG, "git_ai_commit::git::lock", "git commit -m test"),
+        "[DEBUG] git::lock: git commit -m test"
+    );
+}
+
+#[test]
+fn test_init_sets_the_level() {
+    // Given
+    init(Level::ERROR);
+    
+    // Then quiet runs only see errors
+    assert!(enabled(Level::ERROR));
+    assert!(!enabled(Level::WARN));
+    
+    // When the level is raised later, e.g. after the full command line is parsed
+    init(Level::DEBUG);
+    
+    // Then
+    assert!(enabled(Level::INFO));
+    assert!(enabled(Level::DEBUG));
+    assert!(!enabled(Level::TRACE));
+    assert!(tracing::enabled!(Level::DEBUG));
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{git, git_succeeds};
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::merge::MergeState;
+use git_ai_commit::git::GitCollector;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// A repository in the middle of merging `feature/login` into `main`, stopped on a conflict in `auth.rs`
+fn conflicted_merge(repo_path: &Path) {
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("auth.rs"), "fn login() {}\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    git(repo_path, &["checkout", "-b", "feature/login"]);
+    std::fs::write(repo_path.join("auth.rs"), "fn login(token: &str) {}\n").unwrap();
+    std::fs::write(repo_path.join("session.rs"), "struct Session;\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Require a token to log in"]);
+    
+    git(repo_path, &["checkout", "main"]);
+    std::fs::write(repo_path.join("auth.rs"), "pub fn login() {}\n").unwrap();
+    git(repo_path, &["commit", "-am", "Export login"]);
+    
+    // Stops with the conflict
+    assert!(!git_succeeds(repo_path, &["merge", "feature/login"]));
+}
+
+#[test]
+fn test_conflicts_are_read_from_the_prepared_message() {
+    // Given
+    let message = "Merge branch 'feature/login' into main\n\n# Conflicts:\n#\tsrc/auth.rs\n#\tREADME.md\n#\n# It looks like you may be committing a merge.\n";
+    
+    // When
+    let state = MergeState::parse_message(message);
+    
+    // Then
+    assert_eq!(state.message, "Merge branch 'feature/login' into main");
+    assert_eq!(state.conflicts, vec![PathBuf::from("src/auth.rs"), PathBuf::from("README.md")]);
+    
+    // Old versions of git listed them uncommented
+    let state = MergeState::parse_message("Merge branch 'fix'\n\nConflicts:\n\tsrc/auth.rs\n");
+    assert_eq!(state.message, "Merge branch 'fix'");
+    assert_eq!(state.conflicts, vec![PathBuf::from("src/auth.rs")]);
+}
+
+#[test]
+fn test_no_merge_without_merge_head() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    std::fs::write(temp_dir.path().join("MERGE_MSG"), "Merge branch 'old'\n").unwrap();
+    assert_eq!(MergeState::detect(temp_dir.path()), None);
+}
+
+#[tokio::test]
+async fn test_merge_in_progress_is_detected() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    conflicted_merge(repo_path);
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    
+    // When
+    let state = MergeState::detect(&git_collector.git_dir().await.unwrap()).unwrap();
+    let git_info = git_collector.collect_all().await.unwrap();
+    
+    // Then
+    assert_eq!(state.heads.len(), 1);
+    assert_eq!(state.subject(), "Merge branch 'feature/login'");
+    assert_eq!(state.conflicts, vec![PathBuf::from("auth.rs")]);
+    assert_eq!(state.unresolved(&git_info), vec![Path::new("auth.rs")]);
+    assert!(state.resolved(&git_info).is_empty());
+}
+
+#[tokio::test]
+async fn test_merge_prompt_describes_the_merge() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    conflicted_merge(repo_path);
+    std::fs::write(repo_path.join("auth.rs"), "pub fn login(token: &str) {}\n").unwrap();
+    git(repo_path, &["add", "auth.rs"]);
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let state = MergeState::detect(&git_collector.git_dir().await.unwrap()).unwrap();
+    let git_info = git_collector.collect_all().await.unwrap();
+    let commits = git_collector.get_commits_in_range(&format!("HEAD..{}", state.heads[0])).await.unwrap();
+    
+    // When
+    let prompt = PromptBuilder::new(10, 50).build_merge(&git_info, &state, &commits);
+    
+    // Then
+    assert!(state.unresolved(&git_info).is_empty());
+    assert!(prompt.contains("The subject line is already written: Merge branch 'feature/login'"), "{}", prompt);
+    assert!(prompt.contains("Commits being merged (1):\n  - Require a token to log in"), "{}", prompt);
+    assert!(prompt.contains("Conflicts resolved by hand:\n  - auth.rs"), "{}", prompt);
+    assert!(prompt.contains("session.rs"), "{}", prompt);
+}
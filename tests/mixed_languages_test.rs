@@ -0,0 +1,134 @@
+use git_ai_commit::config::{Config, MixedLanguages};
+use git_ai_commit::formatting::heuristic::heuristic_message;
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn staged(paths: &[&str]) -> GitInfo {
+    let file_changes: Vec<FileChange> = paths.iter()
+        .map(|path| FileChange {
+            change_type: ChangeType::Modified,
+            file_path: PathBuf::from(path),
+            old_path: None,
+            ..Default::default()
+        })
+        .collect();
+    
+    GitInfo {
+        status: GitStatus {
+            staged_files: file_changes.iter().map(|c| c.file_path.clone()).collect(),
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: file_changes.len(),
+            insertions: 0,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes,
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_language_groups_by_component() {
+    // Given a frontend file listed first, and a larger backend change
+    let git_info = staged(&[
+        "web/src/login.tsx",
+        "backend/src/auth.rs",
+        "backend/src/session.rs",
+        "web/vite.config.js",
+        "README.md",
+    ]);
+    
+    // When
+    let groups = git_info.language_groups();
+    
+    // Then the largest group comes first, JavaScript joins TypeScript and docs are left out
+    let labels: Vec<String> = groups.iter().map(|group| group.label()).collect();
+    assert_eq!(labels, vec!["backend (Rust)", "web (TypeScript)"]);
+    assert_eq!(groups[1].files, vec![PathBuf::from("web/src/login.tsx"), PathBuf::from("web/vite.config.js")]);
+}
+
+#[test]
+fn test_single_language_is_one_group() {
+    let git_info = staged(&["src/main.rs", "src/lib.rs", "Cargo.toml", "include/api.h"]);
+    let groups = git_info.language_groups();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].label(), "src (Rust)");
+    
+    // Files at the top of the repository are named after their language
+    let groups = staged(&["build.rs", "setup.py"]).language_groups();
+    let labels: Vec<String> = groups.iter().map(|group| group.label()).collect();
+    assert_eq!(labels, vec!["python", "rust"]);
+}
+
+#[test]
+fn test_prompt_asks_for_a_paragraph_per_component() {
+    // Given
+    let git_info = staged(&["backend/src/auth.rs", "web/src/login.tsx"]);
+    
+    // When
+    let prompt = PromptBuilder::new(10, 50).with_body(true).build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("The staged code spans several components:"));
+    assert!(prompt.contains("  - backend (Rust): 1 file(s)"));
+    assert!(prompt.contains("  - web (TypeScript): 1 file(s)"));
+    assert!(prompt.contains("one short paragraph per component"));
+    assert!(!prompt.contains("2-5 bullet points"));
+}
+
+#[test]
+fn test_split_mode_keeps_the_usual_format() {
+    let git_info = staged(&["backend/src/auth.rs", "web/src/login.tsx"]);
+    
+    let prompt = PromptBuilder::new(10, 50)
+        .with_mixed_languages(MixedLanguages::Split)
+        .build(&git_info);
+    assert!(prompt.contains("  - backend (Rust): 1 file(s)"));
+    assert!(prompt.contains("covers every component"));
+    assert!(!prompt.contains("paragraph per component"));
+    
+    let prompt = PromptBuilder::new(10, 50)
+        .with_mixed_languages(MixedLanguages::Off)
+        .build(&git_info);
+    assert!(!prompt.contains("components"));
+}
+
+#[test]
+fn test_one_language_prompt_is_unchanged() {
+    let git_info = staged(&["src/auth.rs", "src/session.rs"]);
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    assert!(!prompt.contains("components"));
+}
+
+#[test]
+fn test_heuristic_names_the_components() {
+    let git_info = staged(&["web/src/login.tsx", "backend/src/auth.rs", "backend/src/session.rs"]);
+    assert_eq!(heuristic_message(&git_info), "chore: update backend and web");
+}
+
+#[test]
+fn test_mixed_languages_config() {
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.mixed_languages, MixedLanguages::Body);
+    
+    std::fs::write(&config_path, "mixed_languages = \"split\"\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.mixed_languages, MixedLanguages::Split);
+    
+    let mut config = Config::default();
+    assert!(config.set_key("mixed_languages", "off").is_ok());
+    assert_eq!(config.mixed_languages, MixedLanguages::Off);
+    assert!(config.set_key("mixed_languages", "sometimes").is_err());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::git;
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::git::GitCollector;
+use git_ai_commit::utils::WriteMode;
+use tempfile::tempdir;
+
+#[test]
+fn test_no_write_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--no-write", "--add-unstaged"]).expect("Failed to parse args");
+    assert!(args.no_write);
+    assert_eq!(args.write_mode(), WriteMode::ReadOnly);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert_eq!(args.write_mode(), WriteMode::ReadWrite);
+}
+
+#[test]
+fn test_write_mode_check() {
+    assert!(WriteMode::ReadWrite.check("committing").is_ok());
+    
+    let err = WriteMode::ReadOnly.check("committing").unwrap_err();
+    assert!(err.to_string().contains("--no-write prevents committing"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn test_read_only_collector_does_not_stage() {
+    // Given: a repository with an unstaged file
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("new.txt"), "new").unwrap();
+    
+    // When
+    let git_collector = GitCollector::new(repo_path.to_path_buf()).with_write_mode(WriteMode::ReadOnly);
+    let staged = git_collector.stage_all_unstaged().await;
+    let tree = git_collector.staged_tree().await;
+    
+    // Then: both are refused and the index is untouched
+    assert!(staged.is_err());
+    assert!(tree.is_err());
+    assert_eq!(git(repo_path, &["status", "--porcelain"]), "?? new.txt\n");
+    
+    // Analysis still works
+    let git_info = git_collector.collect_all().await.unwrap();
+    assert_eq!(git_info.status.untracked_files.len(), 1);
+}
+
+#[tokio::test]
+async fn test_analysis_does_not_refresh_the_index() {
+    // Given: a tracked file whose timestamp no longer matches the index
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("a.txt"), "a").unwrap();
+    git(repo_path, &["add", "a.txt"]);
+    git(repo_path, &["commit", "-m", "init"]);
+    let file = std::fs::File::options().write(true).open(repo_path.join("a.txt")).unwrap();
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+    let index = std::fs::read(repo_path.join(".git/index")).unwrap();
+    
+    // When
+    let git_collector = GitCollector::new(repo_path.to_path_buf()).with_write_mode(WriteMode::ReadOnly);
+    let git_info = git_collector.collect_all().await.unwrap();
+    
+    // Then: status and diff left the index as it was, and the file isn't a change
+    assert_eq!(std::fs::read(repo_path.join(".git/index")).unwrap(), index);
+    assert!(git_info.file_changes.is_empty(), "{:?}", git_info.file_changes);
+    assert!(git_info.status.modified_files.is_empty());
+}
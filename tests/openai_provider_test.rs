@@ -0,0 +1,231 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::{Config, OpenAiConfig, Provider};
+use git_ai_commit::ollama::{OllamaClientTrait, OllamaManager, OpenAiClient};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+fn openai_config(base_url: &str) -> OpenAiConfig {
+    OpenAiConfig {
+        base_url: base_url.to_string(),
+        api_key: Some("sk-test".to_string()),
+        ..OpenAiConfig::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_generate_with_chat_completions() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _completion = server
+        .mock("POST", "/v1/chat/completions")
+        .match_header("authorization", "Bearer sk-test")
+        .match_body(Matcher::PartialJson(json!({
+            "model": "gpt-4o-mini",
+            "messages": [{ "role": "user", "content": "describe the diff" }],
+            "max_tokens": 48,
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({
+            "choices": [{ "message": { "role": "assistant", "content": "fix: retry uploads" } }],
+            "usage": { "prompt_tokens": 120, "completion_tokens": 6, "total_tokens": 126 },
+        }).to_string())
+        .create_async()
+        .await;
+    let client = OpenAiClient::new(&format!("{}/v1/", server.url()), Some("sk-test".to_string()))
+        .with_max_tokens(48);
+    
+    // When
+    let generation = client.generate_with_stats("gpt-4o-mini", "describe the diff").await.unwrap();
+    
+    // Then
+    assert_eq!(generation.text, "fix: retry uploads");
+    assert_eq!(generation.stats.prompt_eval_count, Some(120));
+    assert_eq!(generation.stats.eval_count, Some(6));
+    assert_eq!(generation.stats.eval_duration, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_list_models_and_errors() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _models = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "object": "list", "data": [{ "id": "gpt-4o-mini" }, { "id": "local-model" }] }).to_string())
+        .create_async()
+        .await;
+    let _failure = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(401)
+        .with_body("invalid api key")
+        .create_async()
+        .await;
+    let client = OpenAiClient::new(&format!("{}/v1", server.url()), None);
+    
+    // Then
+    assert!(client.is_running().await);
+    assert_eq!(client.list_models().await.unwrap(), vec!["gpt-4o-mini", "local-model"]);
+    assert!(client.has_model("local-model").await.unwrap());
+    assert!(client.pull_model("llama3").await.is_err());
+    
+    let err = client.generate("gpt-4o-mini", "prompt").await.unwrap_err();
+    assert!(err.to_string().contains("401"), "Unexpected error: {}", err);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_manager_uses_provider_without_starting_ollama() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _models = server
+        .mock("GET", "/v1/models")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "data": [{ "id": "gpt-4o-mini" }] }).to_string())
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let _completion = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "choices": [{ "message": { "content": "docs: fix typo" } }] }).to_string())
+        .create_async()
+        .await;
+    let config = openai_config(&format!("{}/v1", server.url()));
+    
+    // When
+    let mut manager = OllamaManager::new("gpt-4o-mini".to_string(), 1)
+        .unwrap()
+        .with_provider(Provider::OpenAi, &config);
+    manager.ensure_running().await.unwrap();
+    manager.ensure_model_available("gpt-4o-mini").await.unwrap();
+    
+    // Then
+    assert_eq!(manager.generate_commit("prompt").await.unwrap(), "docs: fix typo");
+    let err = manager.ensure_model_available("llama3").await.unwrap_err();
+    assert!(err.to_string().contains("not available"), "Unexpected error: {}", err);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_provider_without_a_model_list_is_reachable() {
+    // Given a server that only serves chat completions
+    let mut server = Server::new_async().await;
+    let _models = server
+        .mock("GET", "/v1/models")
+        .with_status(404)
+        .create_async()
+        .await;
+    let _completion = server
+        .mock("POST", "/v1/chat/completions")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "choices": [{ "message": { "content": "docs: fix typo" } }] }).to_string())
+        .create_async()
+        .await;
+    let config = openai_config(&format!("{}/v1", server.url()));
+    
+    // When
+    let mut manager = OllamaManager::new("gpt-4o-mini".to_string(), 1)
+        .unwrap()
+        .with_provider(Provider::OpenAi, &config);
+    manager.ensure_running().await.unwrap();
+    manager.ensure_model_available("gpt-4o-mini").await.unwrap();
+    
+    // Then
+    assert_eq!(manager.generate_commit("prompt").await.unwrap(), "docs: fix typo");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_manager_reports_unreachable_provider() {
+    let config = openai_config("http://127.0.0.1:1/v1");
+    let mut manager = OllamaManager::new("gpt-4o-mini".to_string(), 1)
+        .unwrap()
+        .with_provider(Provider::OpenAi, &config);
+    
+    let err = manager.ensure_running().await.unwrap_err();
+    assert!(err.to_string().contains("Cannot reach http://127.0.0.1:1/v1"), "Unexpected error: {}", err);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_from_config_sends_extra_headers() {
+    // Given an OpenRouter-style endpoint that wants to know who is calling
+    let mut server = Server::new_async().await;
+    let _completion = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_header("authorization", "Bearer sk-test")
+        .match_header("http-referer", "https://github.com/npiesco/gitAIcommit")
+        .match_header("x-title", "git-ai-commit")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "choices": [{ "message": { "content": "feat: add export" } }] }).to_string())
+        .create_async()
+        .await;
+    let mut config = openai_config(&format!("{}/api/v1", server.url()));
+    config.headers.insert("HTTP-Referer".to_string(), "https://github.com/npiesco/gitAIcommit".to_string());
+    config.headers.insert("X-Title".to_string(), "git-ai-commit".to_string());
+    
+    // When
+    let text = OpenAiClient::from_config(&config).generate("meta-llama/llama-3.1-8b-instruct", "prompt").await;
+    
+    // Then
+    assert_eq!(text.unwrap(), "feat: add export");
+}
+
+#[test]
+fn test_generic_openai_endpoint_config() {
+    // Given
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = r#"
+        provider = "generic-openai"
+        model = "llama3.2:1b"
+        
+        [openai]
+        base_url = "https://api.groq.com/openai/v1"
+        api_key_env = "GROQ_API_KEY"
+        model = "llama-3.1-8b-instant"
+        
+        [profiles.local]
+        provider = "ollama"
+        
+        [profiles.vllm]
+        [profiles.vllm.openai]
+        base_url = "http://localhost:8000/v1"
+        model = "Qwen/Qwen2.5-7B-Instruct"
+    "#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    // When
+    let config = Config::load_from_path(&config_path).unwrap();
+    
+    // Then the OpenAI-compatible model is used only with that provider
+    assert_eq!(config.provider, Provider::OpenAi);
+    assert_eq!(config.openai.api_key_env, "GROQ_API_KEY");
+    assert_eq!(config.model_for(Provider::OpenAi), "llama-3.1-8b-instant");
+    assert_eq!(config.model_for(Provider::Ollama), "llama3.2:1b");
+    
+    // And each profile can pick its own provider and endpoint
+    let mut local = config.clone();
+    local.apply_profile("local").unwrap();
+    assert_eq!(local.provider, Provider::Ollama);
+    
+    let mut vllm = config.clone();
+    vllm.apply_profile("vllm").unwrap();
+    assert_eq!(vllm.openai.base_url, "http://localhost:8000/v1");
+    assert_eq!(vllm.openai.api_key_env, "OPENAI_API_KEY");
+    assert_eq!(vllm.model_for(vllm.provider), "Qwen/Qwen2.5-7B-Instruct");
+}
+
+#[test]
+fn test_generic_openai_provider_flag_and_checks() {
+    let args = Args::try_parse_from(["git-ai-commit", "--provider", "generic-openai"]).expect("Failed to parse args");
+    assert_eq!(args.provider, Some(Provider::OpenAi));
+    
+    let mut config = Config::default();
+    assert!(config.set_key("openai.model", "gpt-4o-mini").is_ok());
+    assert_eq!(config.model_for(Provider::OpenAi), "gpt-4o-mini");
+    assert!(config.set_key("openai.base_url", "localhost:1234/v1").is_err());
+}
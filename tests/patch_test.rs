@@ -0,0 +1,136 @@
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::patch::parse_patch;
+use git_ai_commit::git::GitInfo;
+use std::path::PathBuf;
+
+const GIT_PATCH: &str = r#"diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ pub mod cli;
++pub mod config;
+ pub mod git;
+-pub mod old;
++pub mod ollama;
+diff --git a/docs/new.md b/docs/new.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/docs/new.md
+@@ -0,0 +1,2 @@
++# New
++--- not a header
+diff --git a/src/gone.rs b/src/gone.rs
+deleted file mode 100644
+index 4444444..0000000
+--- a/src/gone.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn gone() {}
+diff --git a/src/old_name.rs b/src/new_name.rs
+similarity index 100%
+rename from src/old_name.rs
+rename to src/new_name.rs
+"#;
+
+#[test]
+fn test_parse_git_patch() {
+    let patches = parse_patch(GIT_PATCH);
+    assert_eq!(patches.len(), 4);
+    
+    assert_eq!(patches[0].file_path, PathBuf::from("src/lib.rs"));
+    assert!(matches!(patches[0].change_type, ChangeType::Modified));
+    assert_eq!((patches[0].insertions, patches[0].deletions), (2, 1));
+    
+    assert_eq!(patches[1].file_path, PathBuf::from("docs/new.md"));
+    assert!(matches!(patches[1].change_type, ChangeType::Added));
+    assert_eq!((patches[1].insertions, patches[1].deletions), (2, 0));
+    
+    assert_eq!(patches[2].file_path, PathBuf::from("src/gone.rs"));
+    assert!(matches!(patches[2].change_type, ChangeType::Deleted));
+    
+    assert_eq!(patches[3].file_path, PathBuf::from("src/new_name.rs"));
+    assert_eq!(patches[3].old_path, Some(PathBuf::from("src/old_name.rs")));
+    assert!(matches!(patches[3].change_type, ChangeType::Renamed));
+}
+
+#[test]
+fn test_parse_plain_unified_diff() {
+    let patch = "--- a/foo.c\tMon Jan  1 00:00:00 2024
++++ b/foo.c\tMon Jan  1 00:00:01 2024
+@@ -1,2 +1,2 @@
+-int x = 1;
++int x = 2;
+ int y = 3;
+--- bar.c
++++ bar.c
+@@ -1 +1,2 @@
+ int z;
++int w;
+";
+
+    let patches = parse_patch(patch);
+    assert_eq!(patches.len(), 2);
+    assert_eq!(patches[0].file_path, PathBuf::from("foo.c"));
+    assert_eq!((patches[0].insertions, patches[0].deletions), (1, 1));
+    assert_eq!(patches[1].file_path, PathBuf::from("bar.c"));
+    assert_eq!((patches[1].insertions, patches[1].deletions), (1, 0));
+}
+
+#[test]
+fn test_git_info_from_patch() {
+    let git_info = GitInfo::from_patch(GIT_PATCH).unwrap();
+    
+    assert_eq!(git_info.file_changes.len(), 4);
+    assert_eq!(git_info.status.staged_files.len(), 4);
+    assert_eq!(git_info.status.deleted_files, vec![PathBuf::from("src/gone.rs")]);
+    assert_eq!(git_info.diff_stat.files_changed, 4);
+    assert_eq!(git_info.diff_stat.insertions, 4);
+    assert_eq!(git_info.diff_stat.deletions, 2);
+    assert!(git_info.branch_name.is_empty());
+}
+
+#[test]
+fn test_prompt_with_patch_truncates_diff() {
+    // Given
+    let builder = PromptBuilder::new(1, 2);
+    let git_info = GitInfo::from_patch(GIT_PATCH).unwrap();
+    
+    // When
+    let prompt = builder.build_with_patch(&git_info, GIT_PATCH);
+    
+    // Then
+    assert!(!prompt.contains("Current branch:"));
+    assert!(prompt.contains("Diff:\n--- src/lib.rs\n@@ -1,3 +1,4 @@\n pub mod cli;\n"));
+    assert!(prompt.contains("... (4 more lines truncated)"));
+    assert!(prompt.contains("... and 3 more files"));
+    assert!(!prompt.contains("+# New"));
+}
+
+#[test]
+fn test_parse_mode_only_and_symlink_patches() {
+    let patch = "diff --git a/run.sh b/run.sh\n\
+                 old mode 100644\n\
+                 new mode 100755\n\
+                 diff --git a/latest b/latest\n\
+                 index 1111111..2222222 120000\n\
+                 --- a/latest\n\
+                 +++ b/latest\n\
+                 @@ -1 +1 @@\n\
+                 -v1\n\
+                 \\ No newline at end of file\n\
+                 +v2\n\
+                 \\ No newline at end of file\n";
+    
+    let patches = parse_patch(patch);
+    assert_eq!(patches.len(), 2);
+    assert!(matches!(patches[0].change_type, ChangeType::ModeChanged { .. }));
+    assert!(matches!(patches[1].change_type, ChangeType::SymlinkChanged));
+    
+    let git_info = GitInfo::from_patch(patch).unwrap();
+    let prompt = PromptBuilder::new(10, 50).build_with_patch(&git_info, patch);
+    assert!(prompt.contains("[MODE CHANGE ONLY: now executable, no content changed]"));
+    assert!(prompt.contains("[SYMLINK: the link target changed, not file content]"));
+}
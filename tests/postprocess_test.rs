@@ -0,0 +1,111 @@
+use git_ai_commit::config::{Config, PostprocessSteps};
+use git_ai_commit::formatting::postprocess::{self, clean};
+
+fn all_steps() -> PostprocessSteps {
+    PostprocessSteps::default()
+}
+
+#[test]
+fn test_plain_answers_are_only_trimmed() {
+    let answer = "\n  feat(parser): add array literals\n\n- Parse [1, 2, 3]\n- Reject trailing commas  \n";
+    assert_eq!(clean(answer, &all_steps()), "feat(parser): add array literals\n\n- Parse [1, 2, 3]\n- Reject trailing commas");
+}
+
+#[test]
+fn test_thinking_is_dropped() {
+    let answer = "<think>\nThe diff adds a retry loop, so this is a feature.\n</think>\n\nfeat: retry dropped connections";
+    assert_eq!(clean(answer, &all_steps()), "feat: retry dropped connections");
+    
+    // The template may open the tag, leaving only its end
+    assert_eq!(postprocess::strip_thinking("Okay, a fix.</think>\nfix: typo"), "\nfix: typo");
+    
+    // An answer cut off while thinking has no message
+    assert_eq!(clean("<THINKING>Let me look at the", &all_steps()), "");
+}
+
+#[test]
+fn test_lead_ins_are_dropped() {
+    for answer in [
+        "Commit message: fix: handle empty diffs",
+        "Here is the commit message for the staged changes:\nfix: handle empty diffs",
+        "**Suggested commit message:**\n\nfix: handle empty diffs",
+        "Subject: fix: handle empty diffs",
+    ] {
+        assert_eq!(clean(answer, &all_steps()), "fix: handle empty diffs", "{}", answer);
+    }
+}
+
+#[test]
+fn test_code_fences_are_dropped() {
+    let answer = "Here's a commit message:\n\n```text\ndocs: fix broken link\n\nThe link pointed at the old wiki.\n```\n\nThis message follows the conventional format.";
+    assert_eq!(clean(answer, &all_steps()), "docs: fix broken link\n\nThe link pointed at the old wiki.");
+    
+    // A fence that was never closed
+    assert_eq!(clean("```\nci: cache cargo", &all_steps()), "ci: cache cargo");
+}
+
+#[test]
+fn test_quotes_are_dropped() {
+    assert_eq!(clean("\"chore: bump serde\"", &all_steps()), "chore: bump serde");
+    assert_eq!(clean("`chore: bump serde`", &all_steps()), "chore: bump serde");
+    assert_eq!(clean("“chore: bump serde”", &all_steps()), "chore: bump serde");
+    assert_eq!(clean("'fix: typo'\n\nIn the README.", &all_steps()), "fix: typo\n\nIn the README.");
+    
+    // Quotes inside the message stay
+    assert_eq!(clean("\"a\" and \"b\"", &all_steps()), "\"a\" and \"b\"");
+    assert_eq!(clean("fix: accept \"none\" as a level", &all_steps()), "fix: accept \"none\" as a level");
+}
+
+#[test]
+fn test_trailing_explanations_are_dropped() {
+    let answer = "refactor: split the parser\n\nMove expressions into their own module.\n\nExplanation: the subject uses the imperative mood.\n\nI've kept it under 50 characters.";
+    assert_eq!(clean(answer, &all_steps()), "refactor: split the parser\n\nMove expressions into their own module.");
+    
+    // The subject is never an explanation
+    assert_eq!(clean("Explanation: fix typo", &all_steps()), "Explanation: fix typo");
+}
+
+#[test]
+fn test_explanations_before_footers_are_kept() {
+    // Only trailing paragraphs are dropped
+    let answer = "fix: retry on timeout\n\nI've raised the limit to three tries.\n\nSigned-off-by: Dev <dev@example.com>";
+    assert_eq!(clean(answer, &all_steps()), answer);
+    
+    // A paragraph with footer lines is part of the message
+    let answer = "fix: retry on timeout\n\nExplanation: the limit was too low.\nRefs #12";
+    assert_eq!(clean(answer, &all_steps()), answer);
+    
+    // Bodies that merely mention the message stay
+    let answer = "docs: reword the guide\n\nThe commit message section now covers trailers.";
+    assert_eq!(clean(answer, &all_steps()), answer);
+}
+
+#[test]
+fn test_steps_can_be_turned_off() {
+    // Given
+    let steps = PostprocessSteps { code_fences: false, quotes: false, ..all_steps() };
+    
+    // When
+    let fenced = clean("```\nfix: typo\n```", &steps);
+    let quoted = clean("\"fix: typo\"", &steps);
+    
+    // Then
+    assert_eq!(fenced, "```\nfix: typo\n```");
+    assert_eq!(quoted, "\"fix: typo\"");
+    assert_eq!(clean("<think>hm</think>fix: typo", &steps), "fix: typo");
+}
+
+#[test]
+fn test_steps_are_configured_per_key() {
+    // Given
+    let mut config = Config::default();
+    assert_eq!(config.postprocess, all_steps());
+    
+    // When
+    config.set_key("postprocess.explanations", "false").unwrap();
+    
+    // Then
+    assert!(!config.postprocess.explanations);
+    assert!(config.postprocess.thinking);
+    assert!(config.set_key("postprocess.emoji", "false").is_err());
+}
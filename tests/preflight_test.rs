@@ -0,0 +1,82 @@
+use git_ai_commit::config::Backend;
+use git_ai_commit::ollama::preflight::{model_installed, Preflight, SkipReason};
+use mockito::Server;
+use serde_json::json;
+use std::time::Duration;
+
+fn port_of(server: &Server) -> u16 {
+    server.url().rsplit(':').next().unwrap().parse().unwrap()
+}
+
+/// A port nothing is listening on
+fn closed_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_preflight_picks_first_healthy_backend() {
+    // Given a dead primary, a server without the model and a healthy fallback
+    let mut server = Server::new_async().await;
+    let _tags = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "models": [{ "name": "llama3:latest" }] }).to_string())
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let port = port_of(&server);
+    let backends = vec![
+        Backend::new(closed_port(), "gemma3:4b"),
+        Backend::new(port, "qwen2.5-coder"),
+        Backend::new(port, "llama3"),
+        Backend::new(closed_port(), "gemma3:1b"),
+    ];
+    
+    // When
+    let preflight = Preflight::run(&backends, Duration::from_secs(2)).await;
+    
+    // Then the first healthy one wins and only those ahead of it are reported
+    assert_eq!(preflight.selected, Some(Backend::new(port, "llama3")));
+    assert_eq!(preflight.skipped.len(), 2);
+    assert!(matches!(preflight.skipped[0].1, SkipReason::Unreachable(_)));
+    assert_eq!(preflight.skipped[1].1, SkipReason::MissingModel);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_preflight_without_healthy_backend() {
+    let backends = vec![Backend::new(closed_port(), "gemma3:4b")];
+    
+    let preflight = Preflight::run(&backends, Duration::from_secs(1)).await;
+    
+    assert!(preflight.selected.is_none());
+    assert_eq!(preflight.skipped.len(), 1);
+}
+
+#[test]
+fn test_select_follows_priority_not_completion_order() {
+    // Given results that arrived out of order
+    let backends = vec![Backend::new(1, "a"), Backend::new(2, "b"), Backend::new(3, "c")];
+    let results = vec![(2, Ok(())), (1, Ok(())), (0, Err(SkipReason::MissingModel))];
+    
+    // When
+    let preflight = Preflight::select(&backends, results);
+    
+    // Then
+    assert_eq!(preflight.selected, Some(Backend::new(2, "b")));
+    assert_eq!(preflight.skipped, vec![(Backend::new(1, "a"), SkipReason::MissingModel)]);
+}
+
+#[test]
+fn test_model_installed() {
+    let models = vec!["llama3:latest".to_string(), "gemma3:4b".to_string()];
+    assert!(model_installed(&models, "llama3"));
+    assert!(model_installed(&models, "gemma3:4b"));
+    assert!(!model_installed(&models, "gemma3"));
+    assert!(!model_installed(&models, "llama3:8b"));
+    
+    // No model requested means any installed one will do
+    assert!(model_installed(&models, ""));
+    assert!(!model_installed(&[], ""));
+}
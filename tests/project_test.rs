@@ -0,0 +1,101 @@
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::project::{repo_name_from_remote, ProjectCache};
+use git_ai_commit::git::{DiffInfo, GitInfo, GitStatus, ProjectInfo};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_repo_name_from_remote() {
+    assert_eq!(repo_name_from_remote("git@github.com:npiesco/gitAIcommit.git").as_deref(), Some("gitAIcommit"));
+    assert_eq!(repo_name_from_remote("https://github.com/npiesco/gitAIcommit/").as_deref(), Some("gitAIcommit"));
+    assert_eq!(repo_name_from_remote(""), None);
+}
+
+#[test]
+fn test_detect_from_manifest_and_readme() {
+    // Given
+    let temp_dir = tempdir().unwrap();
+    let repo_path = temp_dir.path();
+    fs::write(
+        repo_path.join("Cargo.toml"),
+        "[package]\nname = \"git-ai-commit\"\ndescription = \"AI-powered commit messages\"\n",
+    ).unwrap();
+    fs::write(repo_path.join("package.json"), r#"{"name": "ignored"}"#).unwrap();
+    fs::write(repo_path.join("README.md"), "# GitAICommit\n\n[![CI](badge.svg)](ci)\nLocal commit messages.\nMore.\n").unwrap();
+    
+    // When
+    let info = ProjectInfo::detect(repo_path, Some("git@github.com:npiesco/gitAIcommit.git"), 2);
+    
+    // Then
+    assert_eq!(info.repo_name, "gitAIcommit");
+    assert_eq!(info.package_name.as_deref(), Some("git-ai-commit"));
+    assert_eq!(info.description.as_deref(), Some("AI-powered commit messages"));
+    assert_eq!(info.readme.as_deref(), Some("# GitAICommit\nLocal commit messages."));
+    
+    let without_readme = ProjectInfo::detect(repo_path, None, 0);
+    assert_eq!(without_readme.repo_name, repo_path.file_name().unwrap().to_string_lossy());
+    assert_eq!(without_readme.readme, None);
+}
+
+#[test]
+fn test_detect_from_package_json() {
+    let temp_dir = tempdir().unwrap();
+    fs::write(temp_dir.path().join("package.json"), r#"{"name": "web-app", "description": "Storefront"}"#).unwrap();
+    
+    let info = ProjectInfo::detect(temp_dir.path(), None, 0);
+    
+    assert_eq!(info.package_name.as_deref(), Some("web-app"));
+    assert_eq!(info.description.as_deref(), Some("Storefront"));
+}
+
+#[test]
+fn test_project_cache_is_refreshed_when_manifest_changes() {
+    // Given
+    let repo_dir = tempdir().unwrap();
+    let cache_dir = tempdir().unwrap();
+    let cache = ProjectCache::new(cache_dir.path());
+    let info = ProjectInfo { repo_name: "shop".to_string(), ..ProjectInfo::default() };
+    
+    // When
+    cache.store(repo_dir.path(), 0, &info).unwrap();
+    
+    // Then
+    assert_eq!(cache.load(repo_dir.path(), 0), Some(info));
+    assert_eq!(cache.load(repo_dir.path(), 5), None);
+    
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    fs::write(repo_dir.path().join("Cargo.toml"), "[package]\nname = \"shop\"\n").unwrap();
+    assert_eq!(cache.load(repo_dir.path(), 0), None);
+}
+
+#[test]
+fn test_prompt_includes_project_context() {
+    let git_info = GitInfo {
+        status: GitStatus {
+            staged_files: vec![],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: vec![],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    };
+    let project = ProjectInfo {
+        repo_name: "gitAIcommit".to_string(),
+        package_name: Some("git-ai-commit".to_string()),
+        description: Some("AI-powered commit messages".to_string()),
+        readme: None,
+    };
+    
+    let prompt = PromptBuilder::new(10, 50).with_project(Some(project)).build(&git_info);
+    
+    assert!(prompt.contains("Project: gitAIcommit\nPackage: git-ai-commit\nDescription: AI-powered commit messages\n"));
+}
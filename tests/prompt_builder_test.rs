@@ -1,3 +1,4 @@
+use git_ai_commit::config::{CategoryTemplates, ContextBlocks};
 use git_ai_commit::formatting::prompt::PromptBuilder;
 use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
 use git_ai_commit::git::diff::FileStat;
@@ -56,11 +57,13 @@ fn test_prompt_builder_with_file_changes() {
                     filename: "src/main.rs".to_string(),
                     insertions: 10,
                     deletions: 2,
+                    ..Default::default()
                 },
                 FileStat {
                     filename: "Cargo.toml".to_string(),
                     insertions: 5,
                     deletions: 1,
+                    ..Default::default()
                 },
             ],
         },
@@ -69,11 +72,13 @@ fn test_prompt_builder_with_file_changes() {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("src/main.rs"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("Cargo.toml"),
                 old_path: None,
+                ..Default::default()
             },
         ],
         untracked_files: vec![],
@@ -163,11 +168,13 @@ fn test_prompt_includes_both_staged_and_unstaged_changes() {
                     filename: "staged.txt".to_string(),
                     insertions: 3,
                     deletions: 1,
+                    ..Default::default()
                 },
                 FileStat {
                     filename: "unstaged.txt".to_string(),
                     insertions: 2,
                     deletions: 1,
+                    ..Default::default()
                 },
             ],
         },
@@ -176,11 +183,13 @@ fn test_prompt_includes_both_staged_and_unstaged_changes() {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("staged.txt"),
                 old_path: None,
+                ..Default::default()
             },
             FileChange {
                 change_type: ChangeType::Modified,
                 file_path: PathBuf::from("unstaged.txt"),
                 old_path: None,
+                ..Default::default()
             },
         ],
         untracked_files: vec![],
@@ -215,3 +224,245 @@ fn test_prompt_includes_both_staged_and_unstaged_changes() {
         );
     }
 }
+
+#[test]
+fn test_prompt_builder_uses_category_template() {
+    // Given
+    let templates = CategoryTemplates {
+        docs: Some("Docs only:\n{CONTEXT}".to_string()),
+        ..CategoryTemplates::default()
+    };
+    let builder = PromptBuilder::new(10, 100).with_category_templates(templates);
+    let git_info = GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("README.md")],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 2,
+            deletions: 1,
+            file_stats: vec![],
+        },
+        file_changes: vec![FileChange {
+            change_type: ChangeType::Modified,
+            file_path: PathBuf::from("README.md"),
+            old_path: None,
+            ..Default::default()
+        }],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    };
+    
+    // When
+    let prompt = builder.build(&git_info);
+    
+    // Then
+    assert!(builder.uses_category_template(&git_info));
+    assert_eq!(builder.template_for(&git_info), "Docs only:\n{CONTEXT}");
+    assert!(prompt.starts_with("Docs only:\n"));
+    assert!(prompt.contains("README.md"));
+    assert!(!prompt.contains("You are an expert software developer"));
+}
+
+#[test]
+fn test_quick_prompt_lists_only_staged_files() {
+    // Given
+    let builder = PromptBuilder::new(10, 100).with_quick(true);
+    let git_info = GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("src/parser.rs")],
+            modified_files: vec![PathBuf::from("README.md")],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 7,
+            deletions: 2,
+            file_stats: vec![],
+        },
+        file_changes: vec![
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("src/parser.rs"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("README.md"),
+                old_path: None,
+                ..Default::default()
+            },
+        ],
+        untracked_files: vec![PathBuf::from("notes.txt")],
+        branch_name: "main".to_string(),
+        last_commit: Some("abc123 previous commit".to_string()),
+    };
+    
+    // When
+    let prompt = builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("M  src/parser.rs\n+7 -2 lines\n"));
+    assert!(!prompt.contains("README.md"));
+    assert!(!prompt.contains("notes.txt"));
+    assert!(!prompt.contains("Current branch"));
+    assert!(prompt.len() < 400, "Quick prompt is {} bytes", prompt.len());
+}
+
+fn mixed_git_info() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("src/lib.rs")],
+            modified_files: vec![PathBuf::from("notes.md")],
+            untracked_files: vec![PathBuf::from("draft.rs")],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 4,
+            deletions: 1,
+            file_stats: vec![FileStat {
+                filename: "src/lib.rs".to_string(),
+                insertions: 4,
+                deletions: 1,
+                ..Default::default()
+            }],
+        },
+        file_changes: vec![
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("src/lib.rs"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("notes.md"),
+                old_path: None,
+                ..Default::default()
+            },
+        ],
+        untracked_files: vec![PathBuf::from("draft.rs")],
+        branch_name: "feature/parser".to_string(),
+        last_commit: Some("fix: handle empty input".to_string()),
+    }
+}
+
+#[test]
+fn test_context_blocks_can_be_disabled() {
+    // Given
+    let git_info = mixed_git_info();
+    let examples = vec!["feat(parser): support nested lists\n\nBody.".to_string()];
+    let full = PromptBuilder::new(10, 100).with_examples(examples.clone());
+    let trimmed = PromptBuilder::new(10, 100)
+        .with_examples(examples)
+        .with_context_blocks(ContextBlocks {
+            branch: false,
+            last_commit: false,
+            unstaged: false,
+            untracked: false,
+            stats: false,
+            history: false,
+            ..ContextBlocks::default()
+        });
+    
+    // When
+    let full = full.build(&git_info);
+    let trimmed = trimmed.build(&git_info);
+    
+    // Then
+    for block in [
+        "Current branch: feature/parser",
+        "Last commit: fix: handle empty input",
+        "Unstaged changes",
+        "Untracked files",
+        "Diff summary",
+        "Recent commit messages in this repository",
+        "  - feat(parser): support nested lists\n",
+    ] {
+        assert!(full.contains(block), "Default prompt should contain: {}", block);
+        assert!(!trimmed.contains(block), "Trimmed prompt should not contain: {}", block);
+    }
+    assert!(trimmed.contains("Staged changes (will be committed):\n  - M  src/lib.rs"));
+}
+
+#[test]
+fn test_diff_block_can_be_disabled() {
+    // Given
+    let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                 --- a/src/lib.rs\n\
+                 +++ b/src/lib.rs\n\
+                 @@ -1 +1 @@\n\
+                 -old\n\
+                 +new\n";
+    let git_info = GitInfo::from_patch(patch).unwrap();
+    let without_diff = PromptBuilder::new(10, 100)
+        .with_context_blocks(ContextBlocks { diff: false, ..ContextBlocks::default() });
+    
+    // When
+    let with = PromptBuilder::new(10, 100).build_with_patch(&git_info, patch);
+    let without = without_diff.build_with_patch(&git_info, patch);
+    
+    // Then
+    assert!(with.contains("+new"));
+    assert!(!without.contains("+new"));
+    assert!(without.contains("src/lib.rs"));
+}
+
+#[test]
+fn test_prompt_includes_user_instructions() {
+    // Given
+    let git_info = mixed_git_info();
+    let builder = PromptBuilder::new(10, 100)
+        .with_instructions(Some("Mention the parser rewrite ".to_string()));
+    
+    // When
+    let prompt = builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("Additional instructions from the user:\nMention the parser rewrite\n"));
+    
+    // Empty instructions leave the prompt unchanged
+    let cleared = builder.with_instructions(Some("  ".to_string())).build(&git_info);
+    assert_eq!(cleared, PromptBuilder::new(10, 100).build(&git_info));
+}
+
+#[test]
+fn test_prompt_asks_for_a_body_when_enabled() {
+    let git_info = mixed_git_info();
+    
+    let prompt = PromptBuilder::new(10, 100).with_body(true).build(&git_info);
+    assert!(prompt.contains("bullet points"));
+    assert!(prompt.contains("at most 72 characters"));
+    
+    let prompt = PromptBuilder::new(10, 100).build(&git_info);
+    assert!(!prompt.contains("bullet points"));
+}
+
+#[test]
+fn test_custom_template_replaces_the_default_prompt() {
+    // Given
+    let git_info = mixed_git_info();
+    let builder = PromptBuilder::new(10, 100)
+        .with_template(Some("Team prompt. Use gitmoji.\n{CONTEXT}\nEnd.".to_string()));
+    
+    // When
+    let prompt = builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.starts_with("Team prompt. Use gitmoji.\n"));
+    assert!(prompt.contains("src/lib.rs"));
+    assert!(prompt.trim_end().ends_with("End."));
+    
+    // No template keeps the default one
+    assert_eq!(
+        PromptBuilder::new(10, 100).with_template(None).build(&git_info),
+        PromptBuilder::new(10, 100).build(&git_info)
+    );
+}
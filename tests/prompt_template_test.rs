@@ -0,0 +1,182 @@
+use git_ai_commit::config::{CategoryTemplates, Config};
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::formatting::template::{self, fill, placeholders, TemplateVars};
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use git_ai_commit::git::files::ChangeType;
+use std::path::PathBuf;
+
+const PATCH: &str = "diff --git a/src/parser.rs b/src/parser.rs
+index 1111111..2222222 100644
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -1,3 +1,4 @@
+ fn parse() {
++    skip_comments();
+ }
+";
+
+fn parser_change() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("src/parser.rs")],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 1,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: vec![FileChange {
+            change_type: ChangeType::Modified,
+            file_path: PathBuf::from("src/parser.rs"),
+            old_path: None,
+            ..Default::default()
+        }],
+        untracked_files: vec![],
+        branch_name: "feature/comments".to_string(),
+        last_commit: Some("fix: handle empty input".to_string()),
+    }
+}
+
+#[test]
+fn test_placeholders_are_capitalized_names() {
+    let template = "{FILES}\n{\"type\": \"feat\"} type(scope) {files} {DIFF}{FILES}";
+    
+    assert_eq!(placeholders(template), vec!["FILES", "DIFF"]);
+}
+
+#[test]
+fn test_fill_replaces_in_one_pass() {
+    // Given a diff that itself mentions a placeholder
+    let vars = TemplateVars {
+        diff: "+ let s = \"{BRANCH}\";".to_string(),
+        branch: "main".to_string(),
+        ..TemplateVars::default()
+    };
+    
+    // When
+    let prompt = fill("On {BRANCH}:\n{DIFF}", &vars);
+    
+    // Then
+    assert_eq!(prompt, "On main:\n+ let s = \"{BRANCH}\";");
+}
+
+#[test]
+fn test_unknown_placeholders_are_named_with_a_suggestion() {
+    let error = template::check("{CONTEXT}\n{DIF}", "Template prompt.txt").unwrap_err().to_string();
+    assert!(error.contains("Template prompt.txt uses the unknown placeholder {DIF} (did you mean {DIFF}?)"), "{}", error);
+    assert!(error.contains("Available: {CONTEXT}, {DIFF}, {BRANCH}, {LAST_COMMITS}, {FILES}, {LANGUAGE}"), "{}", error);
+    
+    let error = template::check("{CONTEXT} {TICKET}", "Template prompt.txt").unwrap_err().to_string();
+    assert!(!error.contains("did you mean"), "{}", error);
+}
+
+#[test]
+fn test_a_template_must_show_the_changes() {
+    let error = template::check("Commit on {BRANCH}", "templates.docs").unwrap_err().to_string();
+    assert!(error.contains("templates.docs has no {CONTEXT}, {DIFF} or {FILES} placeholder"), "{}", error);
+    
+    assert!(template::check("{FILES}", "t").is_ok());
+    assert!(template::check("{DIFF}", "t").is_ok());
+}
+
+#[test]
+fn test_every_placeholder_is_filled() {
+    // Given
+    let template = "Branch: {BRANCH}\nLanguage: {LANGUAGE}\nRecent:\n{LAST_COMMITS}Files:\n{FILES}Diff:\n{DIFF}";
+    let builder = PromptBuilder::new(10, 100)
+        .with_template(Some(template.to_string()))
+        .with_examples(vec!["feat(parser): support nested lists\n\nBody.".to_string()])
+        .with_patch(Some(PATCH.to_string()));
+    
+    // When
+    let prompt = builder.build(&parser_change());
+    
+    // Then
+    assert!(prompt.starts_with("Branch: feature/comments\nLanguage: Rust\n"), "{}", prompt);
+    assert!(prompt.contains("Recent:\n- feat(parser): support nested lists\n"), "{}", prompt);
+    assert!(prompt.contains("Files:\n- "), "{}", prompt);
+    assert!(prompt.contains("src/parser.rs"), "{}", prompt);
+    assert!(prompt.contains("Diff:\n--- src/parser.rs\n"), "{}", prompt);
+    assert!(prompt.contains("+    skip_comments();"), "{}", prompt);
+}
+
+#[test]
+fn test_diff_isnt_repeated_in_the_context() {
+    // Given
+    let builder = PromptBuilder::new(10, 100).with_template(Some("{CONTEXT}\n---\n{DIFF}".to_string()));
+    
+    // When
+    let prompt = builder.build_with_patch(&parser_change(), PATCH);
+    
+    // Then
+    assert_eq!(prompt.matches("skip_comments").count(), 1, "{}", prompt);
+    assert!(builder.uses_diff());
+    assert!(!PromptBuilder::new(10, 100).uses_diff());
+}
+
+#[test]
+fn test_without_examples_the_last_commit_is_used() {
+    let builder = PromptBuilder::new(10, 100).with_template(Some("{FILES}{LAST_COMMITS}".to_string()));
+    
+    let prompt = builder.build(&parser_change());
+    
+    assert!(prompt.ends_with("- fix: handle empty input\n"), "{}", prompt);
+}
+
+#[test]
+fn test_category_templates_are_checked() {
+    let mut config = Config::default();
+    assert!(config.check_templates().is_ok());
+    
+    config.templates = CategoryTemplates { docs: Some("Docs:\n{CONTXT}".to_string()), ..CategoryTemplates::default() };
+    let error = config.check_templates().unwrap_err().to_string();
+    
+    assert!(error.contains("templates.docs uses the unknown placeholder {CONTXT} (did you mean {CONTEXT}?)"), "{}", error);
+    assert!(config.set_key("max_files", "20").is_err());
+}
+
+#[test]
+fn test_partials_include_sections_of_the_default_prompt() {
+    // Given
+    let builder = PromptBuilder::new(10, 100)
+        .with_template(Some("Write a gitmoji commit message.\n\n{{> context_block}}\n\n{{> footer}}".to_string()));
+    
+    // When
+    let prompt = builder.build(&parser_change());
+    
+    // Then
+    assert!(prompt.starts_with("Write a gitmoji commit message.\n\nRepository Context:\nCurrent branch: feature/comments\n"), "{}", prompt);
+    assert!(prompt.ends_with("Generate only the commit message, no additional explanation:"), "{}", prompt);
+    assert!(template::check("{{> context_block}}", "t").is_ok());
+}
+
+#[test]
+fn test_sections_override_only_their_part_of_the_default() {
+    // Given rules that extend the default ones
+    let overrides = "{{#rules}}\n{{> rules}}\n11. Mention the ticket from the branch name\n{{/rules}}\n\n{{#footer}}Reply with the message only:{{/footer}}\n";
+    
+    // When
+    let prompt = PromptBuilder::new(10, 100).with_template(Some(overrides.to_string())).build(&parser_change());
+    let default = PromptBuilder::new(10, 100).build(&parser_change());
+    
+    // Then the header and context are the default's
+    let (header, _) = default.split_once("Guidelines for the commit message:").unwrap();
+    assert!(prompt.starts_with(header), "{}", prompt);
+    assert!(prompt.contains("10. Only include changes that are staged for commit in the commit message\n11. Mention the ticket from the branch name\n\nReply with the message only:"), "{}", prompt);
+    assert!(template::check(overrides, "t").is_ok());
+}
+
+#[test]
+fn test_broken_sections_are_named() {
+    let error = |template: &str| template::check(template, "Template prompt.txt").unwrap_err().to_string();
+    
+    assert_eq!(error("{{> context}}"), "Configuration error: Template prompt.txt uses the unknown section context. Available: header, context_block, rules, footer");
+    assert!(error("{{#rules}}Be brief{{/footer}}").contains("has {{/footer}} inside the section rules, which isn't closed with {{/rules}}"));
+    assert!(error("{{#rules}}Be brief").contains("doesn't close the section rules"));
+    assert!(error("Intro\n{{#rules}}Be brief{{/rules}}").contains("must be inside {{#section}}...{{/section}} blocks; found \"Intro\""));
+    assert!(error("{{#rules}}a{{/rules}}{{#rules}}b{{/rules}}").contains("replaces the section rules twice"));
+}
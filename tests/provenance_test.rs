@@ -0,0 +1,82 @@
+mod common;
+
+use common::git;
+use git_ai_commit::config::{Config, ProvenanceMode};
+use git_ai_commit::formatting::provenance::{short_hash, Provenance};
+use git_ai_commit::git::GitCollector;
+use git_ai_commit::utils::WriteMode;
+use tempfile::tempdir;
+
+#[test]
+fn test_provenance_lines() {
+    // Given
+    let provenance = Provenance::new("gemma3:4b", "Write a commit message for:\n{CONTEXT}");
+    
+    // When
+    let lines = provenance.lines();
+    
+    // Then
+    assert_eq!(lines, vec![
+        format!("Generator: git-ai-commit/{}", env!("CARGO_PKG_VERSION")),
+        "Generator-Model: gemma3:4b".to_string(),
+        format!("Generator-Template: {}", provenance.template_hash),
+    ]);
+    assert_eq!(provenance.note(), lines.join("\n"));
+}
+
+#[test]
+fn test_short_hash_identifies_the_text() {
+    let hash = short_hash("Write a commit message for:\n{CONTEXT}");
+    assert_eq!(hash.len(), 12);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert_eq!(hash, short_hash("Write a commit message for:\n{CONTEXT}"));
+    assert_ne!(hash, short_hash("Describe these changes:\n{CONTEXT}"));
+}
+
+#[test]
+fn test_provenance_config() {
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.provenance.mode, ProvenanceMode::Off);
+    assert_eq!(config.provenance.notes_ref, "git-ai-commit");
+    
+    std::fs::write(&config_path, "[provenance]\nmode = \"notes\"\nnotes_ref = \"generated\"\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert_eq!(config.provenance.mode, ProvenanceMode::Notes);
+    assert_eq!(config.provenance.notes_ref, "generated");
+    
+    let mut config = Config::default();
+    assert!(config.set_key("provenance.mode", "footer").is_ok());
+    assert_eq!(config.provenance.mode, ProvenanceMode::Footer);
+    assert!(config.set_key("provenance.mode", "commit").is_err());
+    assert!(config.set_key("provenance.notes_ref", "").is_err());
+}
+
+#[tokio::test]
+async fn test_add_note_leaves_the_message_alone() {
+    // Given a repository with one commit
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init", "-q"]);
+    git(repo_path, &["config", "user.name", "Dev"]);
+    git(repo_path, &["config", "user.email", "dev@example.com"]);
+    git(repo_path, &["commit", "-q", "--allow-empty", "-m", "feat: start"]);
+    let provenance = Provenance::new("gemma3:4b", "{CONTEXT}");
+    
+    // When
+    let collector = GitCollector::new(repo_path.to_path_buf());
+    collector.add_note("git-ai-commit", &provenance.note()).await.unwrap();
+    // A second note on the same commit replaces the first
+    collector.add_note("git-ai-commit", &provenance.note()).await.unwrap();
+    
+    // Then
+    let note = git(repo_path, &["notes", "--ref=git-ai-commit", "show", "HEAD"]);
+    assert_eq!(note.trim(), provenance.note());
+    assert_eq!(git(repo_path, &["log", "-1", "--format=%B"]).trim(), "feat: start");
+    
+    // Read-only runs write nothing
+    let collector = collector.with_write_mode(WriteMode::ReadOnly);
+    assert!(collector.add_note("git-ai-commit", &provenance.note()).await.is_err());
+}
@@ -0,0 +1,95 @@
+use git_ai_commit::ollama::progress::PullBar;
+use git_ai_commit::ollama::{OllamaClient, OllamaClientTrait, PullDecoder, PullProgress};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+const PULL: &str = "{\"status\":\"pulling manifest\"}\n\
+                    {\"status\":\"pulling aaa\",\"digest\":\"sha256:aaa\",\"total\":1000,\"completed\":250}\n\
+                    {\"status\":\"pulling aaa\",\"digest\":\"sha256:aaa\",\"total\":1000,\"completed\":1000}\n\
+                    {\"status\":\"pulling bbb\",\"digest\":\"sha256:bbb\",\"total\":40,\"completed\":10}\n\
+                    {\"status\":\"verifying sha256 digest\"}\n\
+                    {\"status\":\"success\"}";
+
+fn port(server: &Server) -> u16 {
+    server.url().split(':').nth(2).unwrap().parse().unwrap()
+}
+
+#[test]
+fn test_decoder_reports_every_update() {
+    // Given a pull whose chunk boundaries fall mid-line
+    let (first, rest) = PULL.split_at(50);
+    
+    // When it's fed piece by piece
+    let mut updates = Vec::new();
+    let mut on_progress = |progress: &PullProgress| updates.push(progress.clone());
+    let mut decoder = PullDecoder::default();
+    decoder.feed(first.as_bytes(), &mut on_progress).unwrap();
+    decoder.feed(rest.as_bytes(), &mut on_progress).unwrap();
+    decoder.finish(&mut on_progress).unwrap();
+    
+    // Then each line arrives once, in order
+    let statuses: Vec<&str> = updates.iter().map(|p| p.status.as_str()).collect();
+    assert_eq!(statuses, vec![
+        "pulling manifest", "pulling aaa", "pulling aaa", "pulling bbb", "verifying sha256 digest", "success",
+    ]);
+    assert_eq!(updates[1].digest.as_deref(), Some("sha256:aaa"));
+    assert_eq!((updates[1].completed, updates[1].total), (Some(250), Some(1000)));
+}
+
+#[test]
+fn test_decoder_fails_pulls_that_do_not_succeed() {
+    let mut decoder = PullDecoder::default();
+    let error = decoder.feed(b"{\"error\":\"pull model manifest: file does not exist\"}\n", &mut |_| {})
+        .unwrap_err();
+    assert!(error.to_string().contains("file does not exist"), "{}", error);
+    
+    // A stream that stops early never said "success"
+    let mut decoder = PullDecoder::default();
+    decoder.feed(b"{\"status\":\"pulling manifest\"}\n", &mut |_| {}).unwrap();
+    assert!(decoder.finish(&mut |_| {}).is_err());
+}
+
+#[test]
+fn test_bar_follows_the_layer_being_downloaded() {
+    // Given
+    let mut bar = PullBar::hidden();
+    let mut decoder = PullDecoder::default();
+    let mut seen = Vec::new();
+    
+    // When
+    decoder.feed(PULL.as_bytes(), &mut |progress| {
+        bar.update(progress);
+        seen.push((bar.message(), bar.position()));
+    }).unwrap();
+    
+    // Then each layer gets its own bar, and the other steps only a message
+    assert_eq!(seen[0].0, "pulling manifest");
+    assert_eq!(seen[1], ("layer 1".to_string(), (250, Some(1000))));
+    assert_eq!(seen[2], ("layer 1".to_string(), (1000, Some(1000))));
+    assert_eq!(seen[3], ("layer 2".to_string(), (10, Some(40))));
+    assert_eq!(seen[4].0, "verifying sha256 digest");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_streams_the_pull() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _m_pull = server
+        .mock("POST", "/api/pull")
+        .match_body(Matcher::PartialJson(json!({ "name": "llama3.2", "stream": true })))
+        .with_status(200)
+        .with_header("content-type", "application/x-ndjson")
+        .with_body(PULL)
+        .create_async()
+        .await;
+    let client = OllamaClient::new(port(&server));
+    
+    // When
+    let mut completed = Vec::new();
+    client.pull_model_with_progress("llama3.2", &mut |progress| completed.extend(progress.completed))
+        .await
+        .unwrap();
+    
+    // Then
+    assert_eq!(completed, vec![250, 1000, 10]);
+}
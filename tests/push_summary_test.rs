@@ -0,0 +1,68 @@
+mod common;
+
+use common::git;
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::hooks::{install_hook, HOOK_MARKER};
+use git_ai_commit::git::GitCollector;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_commits_in_range() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    
+    git(repo_path, &["init"]);
+    std::fs::write(repo_path.join("base.txt"), "base").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Initial commit"]);
+    git(repo_path, &["tag", "base"]);
+    
+    std::fs::write(repo_path.join("feature.txt"), "one\ntwo\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Add feature file"]);
+    std::fs::write(repo_path.join("base.txt"), "changed").unwrap();
+    git(repo_path, &["commit", "-am", "Update base file"]);
+    
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let commits = git_collector.get_commits_in_range("base..HEAD").await.unwrap();
+    
+    let subjects: Vec<_> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["Update base file", "Add feature file"]);
+    assert!(commits.iter().all(|c| !c.sha.is_empty()));
+    
+    let shortstat = git_collector.get_range_shortstat("base..HEAD").await.unwrap();
+    assert!(shortstat.contains("2 files changed"), "Unexpected shortstat: {}", shortstat);
+    
+    // Without an upstream the default range gives an actionable error
+    let err = git_collector.get_commits_in_range("@{u}..HEAD").await.unwrap_err();
+    assert!(err.to_string().contains("No upstream branch configured"), "Unexpected error: {}", err);
+    
+    let prompt = PromptBuilder::new(10, 50).build_push_summary(&commits, &shortstat);
+    assert!(prompt.contains("Commits to be pushed (2):"));
+    assert!(prompt.contains("Add feature file"));
+    assert!(prompt.contains("Overall: 2 files changed"));
+}
+
+#[tokio::test]
+async fn test_install_hook() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    let hooks_dir = git_collector.hooks_dir().await.unwrap();
+    assert!(hooks_dir.ends_with(".git/hooks"));
+    
+    // Installing twice replaces our own hook
+    install_hook(&hooks_dir, "pre-push", "git-ai-commit push-summary || true").unwrap();
+    let hook_path = install_hook(&hooks_dir, "pre-push", "git-ai-commit push-summary || true").unwrap();
+    let script = std::fs::read_to_string(&hook_path).unwrap();
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains(HOOK_MARKER));
+    assert!(script.contains("git-ai-commit push-summary || true"));
+    
+    // A user's own hook is left alone
+    std::fs::write(&hook_path, "#!/bin/sh\nmake lint\n").unwrap();
+    assert!(install_hook(&hooks_dir, "pre-push", "git-ai-commit push-summary").is_err());
+    assert_eq!(std::fs::read_to_string(&hook_path).unwrap(), "#!/bin/sh\nmake lint\n");
+}
@@ -0,0 +1,98 @@
+use git_ai_commit::git::{PushStatus, PushTarget};
+
+fn remotes(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_resolve_uses_upstream() {
+    // Given
+    let remotes = remotes(&["origin", "fork"]);
+    
+    // When
+    let target = PushTarget::resolve("feature", Some("fork/feature-x"), &remotes, None, false).unwrap();
+    
+    // Then
+    assert_eq!(target.remote, "fork");
+    assert_eq!(target.branch, "feature-x");
+    assert!(!target.set_upstream);
+    assert_eq!(target.push_args(), vec!["push", "fork", "HEAD:feature-x"]);
+}
+
+#[test]
+fn test_resolve_upstream_with_slashes() {
+    // Given a remote name that is a prefix of another
+    let remotes = remotes(&["team", "team/mirror"]);
+    
+    // When
+    let target = PushTarget::resolve("main", Some("team/mirror/release/1.0"), &remotes, None, false).unwrap();
+    
+    // Then the longest remote wins
+    assert_eq!(target.remote, "team/mirror");
+    assert_eq!(target.branch, "release/1.0");
+}
+
+#[test]
+fn test_resolve_without_upstream() {
+    // A single remote is used whatever its name
+    let target = PushTarget::resolve("feature", None, &remotes(&["gitlab"]), None, false).unwrap();
+    assert_eq!(target.remote, "gitlab");
+    assert_eq!(target.branch, "feature");
+    assert!(target.set_upstream);
+    assert_eq!(target.push_args(), vec!["push", "--set-upstream", "gitlab", "HEAD:feature"]);
+    
+    // Several remotes fall back to origin
+    let target = PushTarget::resolve("feature", None, &remotes(&["fork", "origin"]), None, false).unwrap();
+    assert_eq!(target.remote, "origin");
+    
+    // Without origin the choice is left to the user
+    let err = PushTarget::resolve("feature", None, &remotes(&["fork", "gitlab"]), None, false).unwrap_err();
+    assert!(err.to_string().contains("--remote"));
+    
+    assert!(PushTarget::resolve("feature", None, &[], None, false).is_err());
+}
+
+#[test]
+fn test_resolve_remote_flag() {
+    let remotes = remotes(&["origin", "fork"]);
+    
+    // Pushing to the upstream's remote keeps the upstream
+    let target = PushTarget::resolve("feature", Some("origin/feature"), &remotes, Some("origin"), false).unwrap();
+    assert_eq!((target.remote.as_str(), target.branch.as_str()), ("origin", "feature"));
+    assert!(!target.set_upstream);
+    
+    // Another remote gets the branch under its own name
+    let target = PushTarget::resolve("feature", Some("origin/main"), &remotes, Some("fork"), false).unwrap();
+    assert_eq!((target.remote.as_str(), target.branch.as_str()), ("fork", "feature"));
+    assert!(target.set_upstream);
+    
+    // --set-upstream is honored even when it isn't needed
+    let target = PushTarget::resolve("feature", Some("origin/feature"), &remotes, Some("origin"), true).unwrap();
+    assert!(target.set_upstream);
+    
+    assert!(PushTarget::resolve("feature", None, &remotes, Some("missing"), false).is_err());
+}
+
+#[test]
+fn test_resolve_detached_head() {
+    let err = PushTarget::resolve("", Some("origin/main"), &remotes(&["origin"]), None, false).unwrap_err();
+    assert!(err.to_string().contains("detached HEAD"));
+}
+
+#[test]
+fn test_push_status_json() {
+    // Given
+    let target = PushTarget::resolve("feature", None, &remotes(&["origin"]), None, false).unwrap();
+    
+    // When
+    let pushed = serde_json::to_value(PushStatus::pushed(target.clone())).unwrap();
+    let failed = serde_json::to_value(PushStatus::failed(Some(target), "rejected")).unwrap();
+    
+    // Then
+    assert_eq!(pushed["pushed"], true);
+    assert_eq!(pushed["target"]["remote"], "origin");
+    assert_eq!(pushed["target"]["set_upstream"], true);
+    assert!(pushed.get("error").is_none());
+    assert_eq!(failed["pushed"], false);
+    assert_eq!(failed["error"], "rejected");
+}
@@ -0,0 +1,23 @@
+use console::Key;
+use git_ai_commit::cli::QuickAction;
+
+#[test]
+fn test_quick_action_keys() {
+    assert_eq!(QuickAction::from_key(&Key::Char('y')), Some(QuickAction::Commit));
+    assert_eq!(QuickAction::from_key(&Key::Char('E')), Some(QuickAction::Edit));
+    assert_eq!(QuickAction::from_key(&Key::Char('r')), Some(QuickAction::Regenerate));
+    assert_eq!(QuickAction::from_key(&Key::Char('i')), Some(QuickAction::RegenerateWithInstructions));
+    assert_eq!(QuickAction::from_key(&Key::Char('c')), Some(QuickAction::Copy));
+    assert_eq!(QuickAction::from_key(&Key::Char('s')), Some(QuickAction::ShowDiff));
+    assert_eq!(QuickAction::from_key(&Key::Char('q')), Some(QuickAction::Quit));
+}
+
+#[test]
+fn test_quick_action_defaults_and_unknown_keys() {
+    // Enter accepts the message, Escape backs out like 'q'
+    assert_eq!(QuickAction::from_key(&Key::Enter), Some(QuickAction::Commit));
+    assert_eq!(QuickAction::from_key(&Key::Escape), Some(QuickAction::Quit));
+    
+    assert_eq!(QuickAction::from_key(&Key::Char('x')), None);
+    assert_eq!(QuickAction::from_key(&Key::ArrowUp), None);
+}
@@ -0,0 +1,47 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::formatting::raw::raw_message;
+
+#[test]
+fn test_raw_message_is_byte_exact() {
+    // Given: a message with colors, CRLF endings, trailing spaces and blank lines
+    let message = "\n\x1b[1;33mfix(upload): retry failed chunks\x1b[0m  \r\n\r\nRetries use exponential\r\nbackoff.\t\n\n\n";
+    
+    // When
+    let output = raw_message(message, false);
+    
+    // Then
+    assert_eq!(
+        output.as_bytes(),
+        b"fix(upload): retry failed chunks\n\nRetries use exponential\nbackoff.\n"
+    );
+}
+
+#[test]
+fn test_raw_message_without_wrapping() {
+    let message = "feat: add export\n\nWrites every project to CSV\nor JSON, chosen by extension.\n\n- csv writer\n  with quoting\n- json writer\n";
+    
+    let output = raw_message(message, true);
+    
+    assert_eq!(
+        output,
+        "feat: add export\n\nWrites every project to CSV or JSON, chosen by extension.\n\n- csv writer with quoting\n- json writer\n"
+    );
+}
+
+#[test]
+fn test_raw_message_single_trailing_newline() {
+    assert_eq!(raw_message("chore: bump version", false), "chore: bump version\n");
+    assert_eq!(raw_message("chore: bump version\n\n", true), "chore: bump version\n");
+}
+
+#[test]
+fn test_raw_flags() {
+    let args = Args::try_parse_from(["git-ai-commit", "--raw", "--no-wrap"]).expect("Failed to parse args");
+    assert!(args.raw);
+    assert!(args.no_wrap);
+    
+    // Raw output and JSON both claim stdout
+    assert!(Args::try_parse_from(["git-ai-commit", "--raw", "--json"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--no-wrap"]).is_err());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::git;
+use git_ai_commit::config::{Config, ContextBlocks};
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::git::{CommitSummary, DiffInfo, GitCollector, GitInfo, GitStatus};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn commit(subject: &str) -> CommitSummary {
+    CommitSummary { sha: "1a2b3c4".to_string(), subject: subject.to_string() }
+}
+
+fn staged_readme() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("README.md")],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 1,
+            insertions: 1,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: vec![],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: Some("docs: describe --style\n\nWith an example.".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_recent_commits_skip_merges() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init", "-b", "main"]);
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    assert!(git_collector.get_recent_commits(5).await.unwrap().is_empty());
+    
+    std::fs::write(repo_path.join("a.txt"), "a").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "feat: add a"]);
+    git(repo_path, &["checkout", "-b", "topic"]);
+    std::fs::write(repo_path.join("b.txt"), "b").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "feat: add b"]);
+    git(repo_path, &["checkout", "main"]);
+    std::fs::write(repo_path.join("c.txt"), "c").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "fix: add c"]);
+    git(repo_path, &["merge", "--no-ff", "-m", "Merge branch 'topic'", "topic"]);
+    
+    // When
+    let commits = git_collector.get_recent_commits(2).await.unwrap();
+    
+    // Then
+    let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects.len(), 2);
+    assert!(!subjects.iter().any(|s| s.starts_with("Merge")), "{:?}", subjects);
+    assert!(git_collector.get_recent_commits(0).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_recent_commits_lead_the_examples() {
+    // Given a history example that was also committed
+    let builder = PromptBuilder::new(10, 100)
+        .with_recent_commits(vec![commit("docs: describe --style"), commit("feat(style): add kernel style")])
+        .with_examples(vec!["feat(style): add kernel style\n\nBody.".to_string(), "fix: keep emoji".to_string()]);
+    
+    // When
+    let prompt = builder.build(&staged_readme());
+    
+    // Then each subject is listed once, newest commits first
+    let block = "Recent commit messages in this repository (match their style):\n  - docs: describe --style\n  - feat(style): add kernel style\n  - fix: keep emoji\n";
+    assert!(prompt.contains(block), "{}", prompt);
+}
+
+#[test]
+fn test_last_commits_placeholder_lists_recent_subjects() {
+    let template = Some("{FILES}{LAST_COMMITS}".to_string());
+    
+    let with_commits = PromptBuilder::new(10, 100)
+        .with_template(template.clone())
+        .with_recent_commits(vec![commit("docs: describe --style"), commit("fix: keep emoji")])
+        .build(&staged_readme());
+    let without = PromptBuilder::new(10, 100).with_template(template).build(&staged_readme());
+    
+    assert!(with_commits.ends_with("- docs: describe --style\n- fix: keep emoji\n"), "{}", with_commits);
+    assert!(without.ends_with("- docs: describe --style\n"), "{}", without);
+}
+
+#[test]
+fn test_history_block_setting_covers_recent_commits() {
+    let builder = PromptBuilder::new(10, 100)
+        .with_recent_commits(vec![commit("docs: describe --style")])
+        .with_context_blocks(ContextBlocks { history: false, ..ContextBlocks::default() });
+    
+    assert!(!builder.build(&staged_readme()).contains("Recent commit messages"));
+    assert_eq!(Config::default().context.recent_commits, 8);
+}
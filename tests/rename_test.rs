@@ -0,0 +1,100 @@
+mod common;
+
+use common::git;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitCollector, GitInfo};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[test]
+fn test_similarity_is_parsed_from_the_status() {
+    // Given
+    let changes = "\
+R098\tsrc/old.rs\tsrc/new.rs
+:100644 100644 1111111 2222222 C075\tsrc/base.rs\tsrc/copy.rs
+M\tsrc/main.rs
+";
+
+    // When
+    let changes = FileChange::parse_list(changes).unwrap();
+    
+    // Then
+    assert_eq!(changes[0].similarity, Some(98));
+    assert_eq!(changes[0].display(), "R  src/old.rs -> src/new.rs (98% similar)");
+    assert!(matches!(changes[1].change_type, ChangeType::Copied));
+    assert_eq!(changes[1].similarity, Some(75));
+    assert_eq!(changes[1].display(), "C  src/base.rs -> src/copy.rs (75% similar)");
+    assert_eq!(changes[2].similarity, None);
+}
+
+#[test]
+fn test_numstat_renames_count_for_the_new_path() {
+    // Given
+    let numstat = "\
+1\t1\tsrc/{parser.rs => parse.rs}
+0\t0\tdocs/old.md => guide/new.md
+2\t0\t{lib => src}/util.rs
+0\t0\tsrc/{legacy => }/io.rs
+";
+
+    // When
+    let diff = DiffInfo::parse(numstat).unwrap();
+    
+    // Then
+    let names: Vec<&str> = diff.file_stats.iter().map(|stat| stat.filename.as_str()).collect();
+    assert_eq!(names, vec!["src/parse.rs", "guide/new.md", "src/util.rs", "src/io.rs"]);
+}
+
+#[test]
+fn test_similarity_is_read_from_patches() {
+    // Given
+    let patch = "\
+diff --git a/src/parser.rs b/src/parse.rs
+similarity index 91%
+rename from src/parser.rs
+rename to src/parse.rs
+index 1111111..2222222 100644
+--- a/src/parser.rs
++++ b/src/parse.rs
+@@ -1 +1 @@
+-pub fn parse() {}
++pub fn parse() -> Ast {}
+";
+
+    // When
+    let git_info = GitInfo::from_patch(patch).unwrap();
+    
+    // Then
+    assert_eq!(git_info.file_changes[0].similarity, Some(91));
+    assert_eq!(git_info.file_changes[0].display(), "R  src/parser.rs -> src/parse.rs (91% similar)");
+}
+
+#[tokio::test]
+async fn test_renames_show_their_similarity() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    let lines: String = (0..40).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(repo_path.join("parser.rs"), &lines).unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    git(repo_path, &["mv", "parser.rs", "parse.rs"]);
+    std::fs::write(repo_path.join("parse.rs"), format!("{}line 40\n", lines)).unwrap();
+    git(repo_path, &["add", "."]);
+    
+    // When
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await.unwrap();
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    
+    // Then
+    let rename = &git_info.file_changes[0];
+    assert_eq!(rename.old_path, Some(PathBuf::from("parser.rs")));
+    assert!(rename.similarity.is_some_and(|similarity| similarity > 90 && similarity < 100), "{:?}", rename);
+    let line = format!("R  parser.rs -> parse.rs ({}% similar)", rename.similarity.unwrap());
+    assert!(git_info.display().contains(&line), "{}", git_info.display());
+    assert!(prompt.contains(&line), "{}", prompt);
+    assert_eq!(git_info.diff_stat.file_stats[0].filename, "parse.rs");
+}
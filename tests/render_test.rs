@@ -0,0 +1,169 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::ContextBlocks;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::diff::FileStat;
+use git_ai_commit::git::render::csv_line;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, DisplayFormat, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+
+fn sample_git_info() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("src/main.rs")],
+            modified_files: vec![PathBuf::from("README.md")],
+            untracked_files: vec![PathBuf::from("notes.txt")],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 2,
+            insertions: 12,
+            deletions: 3,
+            file_stats: vec![
+                FileStat { filename: "src/main.rs".to_string(), insertions: 10, deletions: 2, ..Default::default() },
+                FileStat { filename: "README.md".to_string(), insertions: 2, deletions: 1, ..Default::default() },
+            ],
+        },
+        file_changes: vec![
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("src/main.rs"),
+                old_path: None,
+                ..Default::default()
+            },
+            FileChange {
+                change_type: ChangeType::Modified,
+                file_path: PathBuf::from("README.md"),
+                old_path: None,
+                ..Default::default()
+            },
+        ],
+        untracked_files: vec![PathBuf::from("notes.txt")],
+        branch_name: "main".to_string(),
+        last_commit: Some("Initial commit\n".to_string()),
+    }
+}
+
+#[test]
+fn test_plain_matches_display() {
+    let git_info = sample_git_info();
+    assert_eq!(git_info.render(DisplayFormat::Plain), git_info.display());
+}
+
+#[test]
+fn test_markdown_render() {
+    let output = sample_git_info().render(DisplayFormat::Markdown);
+    
+    assert!(output.starts_with("**Branch:** `main`\n**Last commit:** Initial commit\n"));
+    assert!(output.contains("| Status"));
+    assert!(output.contains("| staged modified"));
+    assert!(output.contains("| src/main.rs"));
+    assert!(output.contains("| untracked"));
+    assert!(output.contains("**Total:** 2 files changed, 12 insertions(+), 3 deletions(-)"));
+}
+
+#[test]
+fn test_table_render() {
+    let output = sample_git_info().render(DisplayFormat::Table);
+    
+    let main_row = output.lines().find(|l| l.contains("src/main.rs")).expect("row for src/main.rs");
+    assert!(main_row.contains("staged modified"));
+    assert!(main_row.contains("10"));
+    assert!(output.lines().any(|l| l.contains("unstaged modified") && l.contains("README.md")));
+    assert!(output.ends_with("2 files changed, 12 insertions(+), 3 deletions(-)\n"));
+}
+
+#[test]
+fn test_csv_and_json_render() {
+    let git_info = sample_git_info();
+    
+    let csv = git_info.render(DisplayFormat::Csv);
+    assert_eq!(
+        csv,
+        "status,file,insertions,deletions\n\
+         staged modified,src/main.rs,10,2\n\
+         unstaged modified,README.md,2,1\n\
+         untracked,notes.txt,,\n"
+    );
+    
+    let json: serde_json::Value = serde_json::from_str(&git_info.render(DisplayFormat::Json)).unwrap();
+    assert_eq!(json["branch"], "main");
+    assert_eq!(json["last_commit"], "Initial commit");
+    assert_eq!(json["files"][0]["file"], "src/main.rs");
+    assert_eq!(json["files"][0]["insertions"], 10);
+    assert_eq!(json["files"][2]["insertions"], serde_json::Value::Null);
+    assert_eq!(json["insertions"], 12);
+}
+
+#[test]
+fn test_csv_line_quotes_when_needed() {
+    let fields = ["plain", "a, b", "say \"hi\"", "two\nlines"].map(str::to_string);
+    assert_eq!(csv_line(&fields), "plain,\"a, b\",\"say \"\"hi\"\"\",\"two\nlines\"\n");
+}
+
+#[test]
+fn test_format_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--dry-run", "--format", "markdown"]).expect("Failed to parse args");
+    assert_eq!(args.format, DisplayFormat::Markdown);
+    
+    let args = Args::try_parse_from(["git-ai-commit"]).expect("Failed to parse args");
+    assert_eq!(args.format, DisplayFormat::Plain);
+    
+    assert!(Args::try_parse_from(["git-ai-commit", "--format", "html"]).is_err());
+}
+
+#[test]
+fn test_stat_graph() {
+    let output = sample_git_info().diff_stat.stat_graph(80);
+    
+    assert_eq!(
+        output,
+        " src/main.rs | 12 ++++++++++--\n \
+          README.md   |  3 ++-\n \
+          2 files changed, 12 insertions(+), 3 deletions(-)\n"
+    );
+}
+
+#[test]
+fn test_stat_graph_scales_large_changes() {
+    // Given a change too large for the line
+    let diff = DiffInfo {
+        files_changed: 2,
+        insertions: 301,
+        deletions: 100,
+        file_stats: vec![
+            FileStat { filename: "big.rs".to_string(), insertions: 300, deletions: 100, ..Default::default() },
+            FileStat { filename: "one.rs".to_string(), insertions: 1, deletions: 0, ..Default::default() },
+        ],
+    };
+    
+    // When
+    let output = diff.stat_graph(40);
+    
+    // Then bars fit the width, keep their proportions and never vanish
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[..2].iter().all(|line| line.len() <= 40), "{}", output);
+    assert!(lines[0].starts_with(" big.rs | 400 +++") && lines[0].ends_with('-'));
+    assert_eq!(lines[1], " one.rs |   1 +");
+    assert_eq!(lines[2], " 2 files changed, 301 insertions(+), 100 deletions(-)");
+    assert_eq!(DiffInfo { files_changed: 1, insertions: 1, deletions: 0, file_stats: diff.file_stats[1..].to_vec() }
+        .stat_graph(80)
+        .lines()
+        .last(), Some(" 1 file changed, 1 insertion(+), 0 deletions(-)"));
+}
+
+#[test]
+fn test_stat_graph_in_prompt() {
+    let git_info = sample_git_info();
+    
+    let default_prompt = PromptBuilder::new(10, 50).build(&git_info);
+    assert!(default_prompt.contains("Detailed changes per file:"));
+    assert!(!default_prompt.contains("git diff --stat"));
+    
+    let prompt = PromptBuilder::new(10, 50)
+        .with_context_blocks(ContextBlocks { stat_graph: true, ..ContextBlocks::default() })
+        .build(&git_info);
+    assert!(prompt.contains("Diff stat (git diff --stat):\n src/main.rs | 12 ++++++++++--\n"));
+    assert!(!prompt.contains("Detailed changes per file:"));
+}
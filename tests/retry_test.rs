@@ -0,0 +1,165 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use git_ai_commit::ollama::retry::{is_transient, with_status, RetryingClient};
+use git_ai_commit::ollama::{Generation, OllamaClient, OllamaClientTrait, OllamaManager};
+use git_ai_commit::utils::error::GitAiError;
+use mockito::{Server, ServerGuard};
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn port(server: &ServerGuard) -> u16 {
+    server.url().split(':').nth(2).unwrap().parse().unwrap()
+}
+
+fn retrying(server: &ServerGuard, max_retries: u32) -> RetryingClient {
+    RetryingClient::new(Arc::new(OllamaClient::new(port(server))), max_retries).with_first_delay(Duration::from_millis(1))
+}
+
+/// Streams a token, then loses the connection, every time
+struct DroppingStream {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl OllamaClientTrait for DroppingStream {
+    async fn is_running(&self) -> bool {
+        true
+    }
+    
+    async fn generate(&self, _model: &str, _prompt: &str) -> Result<String> {
+        unreachable!()
+    }
+    
+    async fn generate_stream(
+        &self,
+        _model: &str,
+        _prompt: &str,
+        on_token: &mut (dyn for<'t> FnMut(&'t str) + Send),
+    ) -> Result<Generation> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        on_token("fix:");
+        Err(with_status(GitAiError::Ollama("Request failed with status 502".to_string()), reqwest::StatusCode::BAD_GATEWAY))
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+    
+    async fn has_model(&self, _model_name: &str) -> Result<bool> {
+        Ok(true)
+    }
+    
+    async fn pull_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+    
+    async fn delete_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_which_errors_are_transient() {
+    let status = |code: u16| with_status(GitAiError::Ollama("failed".to_string()), reqwest::StatusCode::from_u16(code).unwrap());
+    
+    assert!(is_transient(&status(429)));
+    assert!(is_transient(&status(500)));
+    assert!(is_transient(&status(503)));
+    assert!(!is_transient(&status(400)));
+    assert!(!is_transient(&status(404)));
+    assert!(!is_transient(&GitAiError::Ollama("Model 'llama3' is not installed".to_string()).into()));
+    
+    // The message is still the backend's
+    assert_eq!(status(503).to_string(), "Ollama operation failed: failed");
+    assert!(matches!(status(503).downcast_ref::<GitAiError>(), Some(GitAiError::Ollama(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_server_errors_are_retried() {
+    // Given a server that is overloaded twice
+    let mut server = Server::new_async().await;
+    let overloaded = server.mock("POST", "/api/generate").with_status(503).expect(2).create_async().await;
+    let answered = server
+        .mock("POST", "/api/generate")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "response": "fix: typo" }).to_string())
+        .expect(1)
+        .create_async()
+        .await;
+    
+    // When
+    let text = retrying(&server, 3).generate("llama3", "prompt").await.unwrap();
+    
+    // Then
+    assert_eq!(text, "fix: typo");
+    overloaded.assert_async().await;
+    answered.assert_async().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_retries_run_out() {
+    let mut server = Server::new_async().await;
+    let limited = server.mock("GET", "/api/tags").with_status(429).expect(3).create_async().await;
+    
+    let error = retrying(&server, 2).list_models().await.unwrap_err();
+    
+    assert!(is_transient(&error));
+    limited.assert_async().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_client_errors_fail_right_away() {
+    // Given
+    let mut server = Server::new_async().await;
+    let missing = server.mock("POST", "/api/generate").with_status(404).with_body("model not found").expect(1).create_async().await;
+    
+    // When
+    let error = retrying(&server, 3).generate("llama3", "prompt").await.unwrap_err();
+    
+    // Then
+    assert!(error.to_string().contains("404"), "{}", error);
+    missing.assert_async().await;
+    
+    // Nothing listening isn't worth waiting for either
+    let refused = RetryingClient::new(Arc::new(OllamaClient::new(1)), 3).list_models().await.unwrap_err();
+    assert!(!is_transient(&refused), "{:#}", refused);
+}
+
+#[tokio::test]
+async fn test_streams_are_not_retried_once_tokens_arrived() {
+    // Given
+    let inner = Arc::new(DroppingStream { calls: AtomicUsize::new(0) });
+    let client = RetryingClient::new(inner.clone(), 3).with_first_delay(Duration::from_millis(1));
+    
+    // When
+    let mut tokens = Vec::new();
+    let result = client.generate_stream("llama3", "prompt", &mut |token| tokens.push(token.to_string())).await;
+    
+    // Then the token isn't shown twice
+    assert!(result.is_err());
+    assert_eq!(tokens, vec!["fix:"]);
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_manager_retries_as_configured() {
+    // Given
+    let mut server = Server::new_async().await;
+    let failing = server.mock("POST", "/api/generate").with_status(500).expect(1).create_async().await;
+    let manager = OllamaManager::new("llama3".to_string(), port(&server)).unwrap();
+    
+    // When retries are off
+    let error = manager.generate_commit_with_stats("prompt").await.unwrap_err();
+    
+    // Then
+    assert!(error.to_string().contains("500"), "{}", error);
+    failing.assert_async().await;
+    assert_eq!(git_ai_commit::Config::default().max_retries, 3);
+}
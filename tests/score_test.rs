@@ -0,0 +1,132 @@
+use git_ai_commit::config::{ScoringConfig, ValidationConfig};
+use git_ai_commit::formatting::candidate::Candidate;
+use git_ai_commit::formatting::score::{
+    changed_symbols, parse_judge_ratings, JudgeScorer, Ranker, Scorer, SpecificityScorer, ValidationScorer,
+};
+use git_ai_commit::git::GitInfo;
+
+const PATCH: &str = "\
+diff --git a/src/upload.rs b/src/upload.rs
+index 1111111..2222222 100644
+--- a/src/upload.rs
++++ b/src/upload.rs
+@@ -1,3 +1,7 @@
++pub fn retry_with_backoff(attempts: u32) {
++}
++
+ pub fn upload() {
+-    struct Pending;
++    send();
+ }
+";
+
+fn models(candidates: &[Candidate]) -> Vec<&str> {
+    candidates.iter().map(|c| c.model.as_str()).collect()
+}
+
+#[test]
+fn test_changed_symbols_come_from_added_and_removed_lines() {
+    assert_eq!(changed_symbols(PATCH), vec!["retry_with_backoff", "pending"]);
+}
+
+#[test]
+fn test_specificity_rewards_naming_the_changes() {
+    // Given
+    let rules = ValidationConfig::default();
+    let git_info = GitInfo::from_patch(PATCH).unwrap();
+    let scorer = SpecificityScorer::for_changes(&git_info, Some(PATCH));
+    
+    // When
+    let vague = scorer.score(&Candidate::new("a", "fix: improve code", &rules));
+    let file = scorer.score(&Candidate::new("b", "fix(upload): retry failed requests", &rules));
+    let both = scorer.score(&Candidate::new("c", "feat(upload): add retry_with_backoff", &rules));
+    
+    // Then
+    assert_eq!(vague, 0.0);
+    assert!(file > vague);
+    assert!(both > file);
+    assert!(both <= 1.0);
+}
+
+#[test]
+fn test_validation_scorer_counts_violations() {
+    let rules = ValidationConfig::default();
+    
+    assert_eq!(ValidationScorer.score(&Candidate::new("a", "fix: retry uploads", &rules)), 1.0);
+    assert_eq!(ValidationScorer.score(&Candidate::new("b", "fix: added retry.", &rules)), 0.5);
+}
+
+#[test]
+fn test_ranker_combines_weighted_scores() {
+    // Given one valid but vague message and one specific message with a trailing period
+    let rules = ValidationConfig::default();
+    let git_info = GitInfo::from_patch(PATCH).unwrap();
+    let candidates = vec![
+        Candidate::new("vague", "fix: improve code", &rules),
+        Candidate::new("specific", "feat(upload): add retry_with_backoff.", &rules),
+    ];
+    
+    // When validation alone counts, and when specificity counts as much
+    let by_validation = Ranker::new().with_scorer(ValidationScorer, 1.0).rank(candidates.clone());
+    let scoring = ScoringConfig { specificity: 1.0, ..ScoringConfig::default() };
+    let by_both = Ranker::from_config(&scoring, &git_info, Some(PATCH)).rank(candidates);
+    
+    // Then
+    assert_eq!(models(&by_validation), vec!["vague", "specific"]);
+    assert_eq!(models(&by_both), vec!["specific", "vague"]);
+    let score = by_both[0].score.as_ref().unwrap();
+    assert_eq!(score.parts["validation"], 0.75);
+    assert!((score.total - (0.75 + 2.0 / 3.0)).abs() < 1e-9);
+    assert_eq!(score.display(), "1.42 (specificity 0.67, validation 0.75)");
+}
+
+#[test]
+fn test_unusable_candidates_score_nothing() {
+    let rules = ValidationConfig::default();
+    let refusal = Candidate::new("a", "I'm sorry, but I cannot help with that request.", &rules);
+    
+    let score = Ranker::new().with_scorer(ValidationScorer, 1.0).score(&refusal);
+    
+    assert_eq!(score.total, 0.0);
+    assert!(score.parts.is_empty());
+}
+
+#[test]
+fn test_judge_ratings_reorder_candidates() {
+    // Given
+    let rules = ValidationConfig::default();
+    let candidates = vec![
+        Candidate::new("llama3", "fix: retry uploads", &rules),
+        Candidate::new("qwen2.5-coder", "feat(upload): add retry with backoff", &rules),
+    ];
+    let reply = "Candidate 1: 4\nCandidate 2: 9/10\nCandidate 7: 10";
+    
+    // When
+    let ratings = parse_judge_ratings(reply, candidates.len());
+    let judge = JudgeScorer::new(&candidates, &ratings);
+    let ranked = Ranker::new()
+        .with_scorer(ValidationScorer, 1.0)
+        .with_scorer(judge, 1.0)
+        .rank(candidates);
+    
+    // Then
+    assert_eq!(ratings, vec![Some(0.4), Some(0.9)]);
+    assert_eq!(models(&ranked), vec!["qwen2.5-coder", "llama3"]);
+    assert_eq!(ranked[0].score.as_ref().unwrap().parts["judge"], 0.9);
+}
+
+#[test]
+fn test_scored_candidates_serialize_for_json() {
+    let rules = ValidationConfig::default();
+    let ranked = Ranker::new()
+        .with_scorer(ValidationScorer, 1.0)
+        .rank(vec![Candidate::new("llama3", "fix: added retry.", &rules)]);
+    
+    let json = serde_json::to_value(&ranked[0]).unwrap();
+    
+    assert_eq!(json["model"], "llama3");
+    assert_eq!(json["violations"].as_array().unwrap().len(), 2);
+    assert!(json["violations"][0].is_string());
+    assert_eq!(json["score"]["total"], 0.5);
+    assert_eq!(json["score"]["parts"]["validation"], 0.5);
+}
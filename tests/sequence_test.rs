@@ -0,0 +1,94 @@
+mod common;
+
+use common::{git, git_succeeds};
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::git::sequence::Sequence;
+use git_ai_commit::git::GitCollector;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn log(repo_path: &Path) -> String {
+    let output = Command::new("git").args(["log", "--format=%s"]).current_dir(repo_path).output().unwrap();
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// `main` and `topic` both change `config.rs`; `topic` then adds `docs.md`
+fn diverged(repo_path: &Path) {
+    git(repo_path, &["init"]);
+    // `rebase --continue` commits the replayed steps with the repository's identity
+    git(repo_path, &["config", "user.name", "Test"]);
+    git(repo_path, &["config", "user.email", "test@example.com"]);
+    std::fs::write(repo_path.join("config.rs"), "const LIMIT: u32 = 1;\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    git(repo_path, &["checkout", "-b", "topic"]);
+    std::fs::write(repo_path.join("config.rs"), "const LIMIT: u32 = 2;\n").unwrap();
+    git(repo_path, &["commit", "-am", "Raise the limit"]);
+    std::fs::write(repo_path.join("docs.md"), "# Limits\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "Document the limit"]);
+    
+    git(repo_path, &["checkout", "main"]);
+    std::fs::write(repo_path.join("config.rs"), "const LIMIT: u32 = 5;\n").unwrap();
+    git(repo_path, &["commit", "-am", "Lower the default"]);
+}
+
+#[test]
+fn test_clean_repository_has_no_sequence() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    diverged(temp_dir.path());
+    assert_eq!(Sequence::detect(&temp_dir.path().join(".git")), None);
+}
+
+#[test]
+fn test_stopped_cherry_pick_is_detected() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    diverged(repo_path);
+    
+    // When
+    assert!(!git_succeeds(repo_path, &["cherry-pick", "topic~1"]));
+    let sequence = Sequence::detect(&repo_path.join(".git")).unwrap();
+    
+    // Then
+    assert_eq!(sequence, Sequence::CherryPick);
+    assert!(sequence.refusal().contains("git cherry-pick --continue"), "{}", sequence.refusal());
+    
+    // A single pick is over once committed
+    assert!(!sequence.has_remaining(&repo_path.join(".git")));
+}
+
+#[tokio::test]
+async fn test_rebase_continues_after_the_commit() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    diverged(repo_path);
+    git(repo_path, &["checkout", "topic"]);
+    assert!(!git_succeeds(repo_path, &["rebase", "main"]));
+    let git_dir = repo_path.join(".git");
+    assert_eq!(Sequence::detect(&git_dir), Some(Sequence::Rebase));
+    
+    std::fs::write(repo_path.join("config.rs"), "const LIMIT: u32 = 5;\nconst MAX: u32 = 2;\n").unwrap();
+    git(repo_path, &["add", "config.rs"]);
+    git(repo_path, &["commit", "-m", "Add a separate maximum"]);
+    
+    // When
+    assert!(Sequence::Rebase.has_remaining(&git_dir));
+    GitCollector::new(repo_path.to_path_buf()).continue_sequence(Sequence::Rebase).await.unwrap();
+    
+    // Then
+    assert_eq!(Sequence::detect(&git_dir), None);
+    assert_eq!(log(repo_path), "Document the limit\nAdd a separate maximum\nLower the default\ninit\n");
+}
+
+#[test]
+fn test_continue_sequence_does_not_push_or_split() {
+    assert!(Args::try_parse_from(["git-ai-commit", "--continue-sequence"]).unwrap().continue_sequence);
+    assert!(Args::try_parse_from(["git-ai-commit", "--continue-sequence", "--push"]).is_err());
+    assert!(Args::try_parse_from(["git-ai-commit", "--continue-sequence", "--split"]).is_err());
+}
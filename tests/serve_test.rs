@@ -0,0 +1,118 @@
+use clap::Parser;
+use git_ai_commit::cli::{Args, Command};
+use git_ai_commit::serve::{index_fingerprint, SharedSuggestion, Suggestion, SuggestionState};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_suggestion_statusline() {
+    let ready = Suggestion::ready("feat(cli): add --amend\n\nRewrites the last message.\n", 2);
+    
+    assert_eq!(ready.state, SuggestionState::Ready);
+    assert_eq!(ready.statusline, "suggested: feat(cli): add --amend");
+    assert_eq!(ready.message.as_deref(), Some("feat(cli): add --amend\n\nRewrites the last message."));
+    assert_eq!(ready.staged_files, 2);
+    assert!(ready.updated > 0);
+    
+    assert_eq!(Suggestion::idle().statusline, "");
+    assert_eq!(Suggestion::generating(1).statusline, "suggesting...");
+    let failed = Suggestion::failed("Ollama is not running", 1);
+    assert_eq!(failed.state, SuggestionState::Failed);
+    assert_eq!(failed.error.as_deref(), Some("Ollama is not running"));
+}
+
+#[test]
+fn test_serve_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "serve"]).expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::Serve { socket: None })));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "serve", "--socket", "/tmp/hints.sock"])
+        .expect("Failed to parse args");
+    match args.command {
+        Some(Command::Serve { socket }) => assert_eq!(socket, Some(PathBuf::from("/tmp/hints.sock"))),
+        other => panic!("Unexpected command: {:?}", other),
+    }
+}
+
+#[test]
+fn test_index_fingerprint_changes_when_staging() {
+    // Given
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git").args(args).current_dir(repo_path).status().unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    std::fs::write(repo_path.join("a.txt"), "a\n").unwrap();
+    git(&["add", "a.txt"]);
+    let before = index_fingerprint(&repo_path.join(".git"));
+    
+    // When
+    std::fs::write(repo_path.join("b.txt"), "b\n").unwrap();
+    git(&["add", "b.txt"]);
+    
+    // Then
+    assert!(before.is_some());
+    assert_ne!(index_fingerprint(&repo_path.join(".git")), before);
+    assert_eq!(index_fingerprint(&repo_path.join("missing")), None);
+}
+
+#[cfg(unix)]
+mod socket {
+    use super::*;
+    use git_ai_commit::serve::serve_socket;
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::UnixStream;
+    
+    async fn read(path: &Path) -> Suggestion {
+        let mut stream = UnixStream::connect(path).await.expect("Failed to connect");
+        let mut line = String::new();
+        stream.read_to_string(&mut line).await.unwrap();
+        assert!(line.ends_with('\n'));
+        serde_json::from_str(&line).unwrap()
+    }
+    
+    async fn wait_for(path: &Path) {
+        for _ in 0..100 {
+            if UnixStream::connect(path).await.is_ok() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("{} never answered", path.display());
+    }
+    
+    #[tokio::test]
+    async fn test_every_connection_gets_the_latest_suggestion() {
+        // Given a socket path where a previous server left its socket behind
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("git-ai-commit.sock");
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let suggestion = SharedSuggestion::default();
+        let server = {
+            let (path, suggestion) = (path.clone(), suggestion.clone());
+            tokio::spawn(async move { serve_socket(&path, suggestion).await })
+        };
+        wait_for(&path).await;
+        
+        // When
+        let idle = read(&path).await;
+        suggestion.set(Suggestion::ready("fix: retry uploads", 1));
+        let ready = read(&path).await;
+        
+        // Then
+        assert_eq!(idle.state, SuggestionState::Idle);
+        assert_eq!(ready.statusline, "suggested: fix: retry uploads");
+        
+        // A second server refuses the socket and leaves it alone
+        let error = serve_socket(&path, SharedSuggestion::default()).await.unwrap_err();
+        assert!(error.to_string().contains("already answering"), "{}", error);
+        assert_eq!(read(&path).await.state, SuggestionState::Ready);
+        
+        // Stopping the server removes its socket
+        server.abort();
+        let _ = server.await;
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,107 @@
+use git_ai_commit::ollama::startup::{log_tail, wait_for_server, Backoff, ServerStart, FIRST_POLL, MAX_POLL};
+use git_ai_commit::ollama::OllamaClient;
+use mockito::Server;
+use std::fs::File;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+use tokio::process::Command;
+
+/// A port nothing listens on
+fn closed_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+#[test]
+fn test_backoff_grows_with_jitter_up_to_the_cap() {
+    let delays: Vec<Duration> = Backoff::new(42).take(12).collect();
+    
+    assert!(delays[0] >= FIRST_POLL.mul_f64(0.75) && delays[0] <= FIRST_POLL.mul_f64(1.25));
+    assert!(delays[3] > delays[0]);
+    assert!(delays.iter().all(|delay| *delay <= MAX_POLL.mul_f64(1.25)));
+    assert!(delays[8..].iter().all(|delay| *delay >= MAX_POLL.mul_f64(0.75)));
+    
+    // Different runs don't poll in step
+    let other: Vec<Duration> = Backoff::new(7).take(12).collect();
+    assert_ne!(delays, other);
+}
+
+#[test]
+fn test_log_tail() {
+    let temp_dir = tempdir().unwrap();
+    let log = temp_dir.path().join("ollama.log");
+    std::fs::write(&log, "starting\n\nlistening on 127.0.0.1:11434\nError: address already in use\n\n").unwrap();
+    
+    assert_eq!(log_tail(&log, 2).unwrap(), "listening on 127.0.0.1:11434\nError: address already in use");
+    assert_eq!(log_tail(&log, 10).unwrap().lines().count(), 3);
+    assert_eq!(log_tail(&temp_dir.path().join("missing.log"), 10), None);
+}
+
+#[tokio::test]
+async fn test_wait_returns_once_the_server_answers() {
+    // Given
+    let mut server = Server::new_async().await;
+    let _m_tags = server.mock("GET", "/api/tags").with_status(200).with_body("{\"models\":[]}").create_async().await;
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    let mut process = Command::new("sleep").arg("30").kill_on_drop(true).spawn().unwrap();
+    let start = ServerStart { binary: Path::new("/usr/bin/ollama"), port, log: None };
+    
+    // When
+    let waited = wait_for_server(&OllamaClient::new(port), &mut process, &start, Duration::from_secs(5), Backoff::new(1)).await;
+    
+    // Then
+    assert!(waited.is_ok());
+}
+
+#[tokio::test]
+async fn test_wait_stops_when_the_server_exits() {
+    // Given a server that fails right away, writing why to its log
+    let temp_dir = tempdir().unwrap();
+    let log = temp_dir.path().join("ollama.log");
+    let port = closed_port();
+    let mut process = Command::new("sh")
+        .args(["-c", "echo 'Error: listen tcp: address already in use' >&2; exit 1"])
+        .stderr(Stdio::from(File::create(&log).unwrap()))
+        .spawn()
+        .unwrap();
+    let start = ServerStart { binary: Path::new("/opt/ollama/bin/ollama"), port, log: Some(&log) };
+    
+    // When
+    let began = Instant::now();
+    let error = wait_for_server(&OllamaClient::new(port), &mut process, &start, Duration::from_secs(30), Backoff::new(1))
+        .await
+        .unwrap_err()
+        .to_string();
+    
+    // Then it fails long before the deadline, saying what was tried and why
+    assert!(began.elapsed() < Duration::from_secs(10));
+    assert!(error.contains("exited"), "{}", error);
+    assert!(error.contains("/opt/ollama/bin/ollama"), "{}", error);
+    assert!(error.contains(&format!("port: {}", port)), "{}", error);
+    assert!(error.contains("address already in use"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_wait_gives_up_at_the_deadline_and_kills_the_server() {
+    // Given a server that never answers
+    let temp_dir = tempdir().unwrap();
+    let log = temp_dir.path().join("ollama.log");
+    let port = closed_port();
+    let mut process = Command::new("sleep").arg("30").kill_on_drop(true).spawn().unwrap();
+    let start = ServerStart { binary: Path::new("/usr/bin/ollama"), port, log: Some(&log) };
+    
+    // When
+    let began = Instant::now();
+    let error = wait_for_server(&OllamaClient::new(port), &mut process, &start, Duration::from_millis(500), Backoff::new(1))
+        .await
+        .unwrap_err()
+        .to_string();
+    
+    // Then
+    assert!(began.elapsed() < Duration::from_secs(3));
+    assert!(error.contains("didn't answer within"), "{}", error);
+    assert!(error.contains("daemon.start_timeout_seconds"), "{}", error);
+    assert!(error.contains("the server wrote nothing to"), "{}", error);
+    assert!(process.try_wait().unwrap().is_some(), "the server should have been killed");
+}
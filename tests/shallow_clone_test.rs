@@ -0,0 +1,100 @@
+mod common;
+
+use common::git;
+use git_ai_commit::git::{CloneInfo, GitCollector};
+use std::path::PathBuf;
+use tempfile::{tempdir, TempDir};
+
+/// An upstream repository with three commits, and a clone of it made with `clone_args`
+fn clone_fixture(clone_args: &[&str]) -> (TempDir, PathBuf) {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let upstream = temp_dir.path().join("upstream");
+    std::fs::create_dir(&upstream).unwrap();
+    git(&upstream, &["init"]);
+    git(&upstream, &["config", "uploadpack.allowFilter", "true"]);
+    for (i, content) in ["one", "two", "three"].iter().enumerate() {
+        std::fs::write(upstream.join("notes.txt"), content).unwrap();
+        git(&upstream, &["add", "."]);
+        git(&upstream, &["commit", "-m", &format!("commit {}", i + 1)]);
+    }
+    
+    let url = format!("file://{}", upstream.display());
+    let mut args = vec!["clone"];
+    args.extend_from_slice(clone_args);
+    args.extend_from_slice(&[url.as_str(), "clone"]);
+    git(temp_dir.path(), &args);
+    
+    let clone = temp_dir.path().join("clone");
+    (temp_dir, clone)
+}
+
+#[tokio::test]
+async fn test_detects_shallow_clone() {
+    // Given
+    let (_temp_dir, clone) = clone_fixture(&["--depth", "1"]);
+    
+    // When
+    let info = CloneInfo::detect(&clone).await;
+    
+    // Then
+    assert_eq!(info, CloneInfo { shallow: true, partial: false });
+    assert!(!info.is_complete());
+    assert!(info.warning().unwrap().contains("shallow clone; history examples are skipped"));
+}
+
+#[tokio::test]
+async fn test_detects_partial_clone() {
+    let (_temp_dir, clone) = clone_fixture(&["--filter=blob:none"]);
+    
+    let info = CloneInfo::detect(&clone).await;
+    
+    assert_eq!(info, CloneInfo { shallow: false, partial: true });
+    assert_eq!(info.describe(), Some("partial clone"));
+}
+
+#[tokio::test]
+async fn test_full_clone_is_complete() {
+    let (_temp_dir, clone) = clone_fixture(&[]);
+    
+    let info = CloneInfo::detect(&clone).await;
+    
+    assert!(info.is_complete());
+    assert_eq!(info.warning(), None);
+}
+
+#[tokio::test]
+async fn test_repository_context_works_in_a_shallow_clone() {
+    // Given a depth 1 clone with a staged change
+    let (_temp_dir, clone) = clone_fixture(&["--depth", "1"]);
+    std::fs::write(clone.join("notes.txt"), "four").unwrap();
+    git(&clone, &["add", "."]);
+    
+    // When
+    let git_collector = GitCollector::new(clone.clone());
+    let git_info = git_collector.collect_all().await.expect("Failed to collect git info");
+    
+    // Then the last commit is still known
+    assert_eq!(git_info.status.staged_files, vec![PathBuf::from("notes.txt")]);
+    assert!(git_info.last_commit.unwrap().contains("commit 3"));
+}
+
+#[tokio::test]
+async fn test_history_operations_explain_missing_history() {
+    // Given a depth 1 clone, cut off at HEAD
+    let (_temp_dir, clone) = clone_fixture(&["--depth", "1"]);
+    let git_collector = GitCollector::new(clone.clone());
+    
+    // When / Then: HEAD's patch would list every file as added
+    let error = git_collector.get_head_patch().await.unwrap_err().to_string();
+    assert!(error.contains("git fetch --deepen=1"), "{}", error);
+    
+    // Ranges reaching past the boundary name the fix
+    let error = git_collector.get_commits_in_range("HEAD~2..HEAD").await.unwrap_err().to_string();
+    assert!(error.contains("shallow clone"), "{}", error);
+    
+    // A commit made on top of the clone has its parent, so it can be described
+    std::fs::write(clone.join("notes.txt"), "four").unwrap();
+    git(&clone, &["commit", "-am", "wip"]);
+    let patch = git_collector.get_head_patch().await.unwrap();
+    assert!(patch.contains("+four"));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::git;
+use clap::Parser;
+use git_ai_commit::cli::{Args, Command as Subcommand};
+use git_ai_commit::git::signing::Signing;
+use git_ai_commit::git::GitCollector;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+/// A repository with one commit, committing as `Test`
+fn repository(repo_path: &Path) {
+    git(repo_path, &["init"]);
+    git(repo_path, &["config", "user.name", "Test"]);
+    git(repo_path, &["config", "user.email", "test@example.com"]);
+    std::fs::write(repo_path.join("a.txt"), "a\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+}
+
+#[test]
+fn test_gpg_sign_takes_an_optional_key() {
+    let args = Args::try_parse_from(["git-ai-commit", "--gpg-sign"]).unwrap();
+    assert_eq!(args.gpg_sign.as_deref(), Some(""));
+    assert_eq!(args.gpg_sign_arg().as_deref(), Some("--gpg-sign"));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "--gpg-sign=0xDEADBEEF"]).unwrap();
+    assert_eq!(args.gpg_sign_arg().as_deref(), Some("--gpg-sign=0xDEADBEEF"));
+    
+    let args = Args::try_parse_from(["git-ai-commit", "-S"]).unwrap();
+    assert_eq!(args.gpg_sign_arg().as_deref(), Some("--gpg-sign"));
+    
+    // The key needs `=`, so a following subcommand isn't taken for one
+    let args = Args::try_parse_from(["git-ai-commit", "--gpg-sign", "commit"]).unwrap();
+    assert_eq!(args.gpg_sign.as_deref(), Some(""));
+    assert!(matches!(args.command, Some(Subcommand::Commit)));
+    
+    assert_eq!(Args::try_parse_from(["git-ai-commit"]).unwrap().gpg_sign_arg(), None);
+}
+
+#[tokio::test]
+async fn test_signing_is_detected_from_the_config() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    repository(repo_path);
+    assert_eq!(Signing::detect(repo_path, None).await, None);
+    
+    // When
+    git(repo_path, &["config", "commit.gpgsign", "yes"]);
+    git(repo_path, &["config", "user.signingkey", "ABCD1234"]);
+    let signing = Signing::detect(repo_path, None).await.unwrap();
+    
+    // Then
+    assert_eq!(signing.format, "openpgp");
+    assert_eq!(signing.key.as_deref(), Some("ABCD1234"));
+    assert!(!signing.requested);
+    assert_eq!(
+        signing.warning(),
+        "The commit will be signed with ABCD1234 (commit.gpgsign); gpg may wait for the passphrase in a pinentry dialog"
+    );
+    
+    // A key given on the command line wins
+    let signing = Signing::detect(repo_path, Some("0xFEED")).await.unwrap();
+    assert_eq!(signing.key.as_deref(), Some("0xFEED"));
+    assert!(signing.warning().contains("(--gpg-sign)"), "{}", signing.warning());
+}
+
+#[tokio::test]
+async fn test_requested_signing_without_config() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    repository(temp_dir.path());
+    git(temp_dir.path(), &["config", "gpg.format", "ssh"]);
+    
+    let signing = Signing::detect(temp_dir.path(), Some("")).await.unwrap();
+    
+    assert_eq!(signing.key, None);
+    assert_eq!(signing.warning(), "The commit will be signed (--gpg-sign); ssh-keygen may ask for the key's passphrase");
+}
+
+#[tokio::test]
+async fn test_amended_commit_is_signed_with_an_ssh_key() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    repository(repo_path);
+    let key = repo_path.join("signing_key");
+    let keygen = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .output();
+    if !keygen.is_ok_and(|output| output.status.success()) {
+        eprintln!("ssh-keygen is not available; skipping");
+        return;
+    }
+    git(repo_path, &["config", "gpg.format", "ssh"]);
+    git(repo_path, &["config", "user.signingkey", key.to_str().unwrap()]);
+    
+    // When
+    GitCollector::new(repo_path.to_path_buf())
+        .amend_message("Add a.txt", Some("--gpg-sign"))
+        .await
+        .unwrap();
+    
+    // Then
+    let commit = git(repo_path, &["cat-file", "commit", "HEAD"]);
+    assert!(commit.contains("-----BEGIN SSH SIGNATURE-----"), "{}", commit);
+    assert!(commit.ends_with("Add a.txt\n"), "{}", commit);
+}
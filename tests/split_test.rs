@@ -0,0 +1,174 @@
+mod common;
+
+use common::git;
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::formatting::split::parse_groups;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::plan::{CommitPlan, LEFTOVER_LABEL};
+use git_ai_commit::git::{FileChange, GitCollector};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn paths(files: &[&str]) -> Vec<PathBuf> {
+    files.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn test_parse_groups_ignores_decoration() {
+    // Given
+    let answer = "Here is the plan:\n\
+                  1. refactor: `src/parser.rs`, src/lexer.rs\n\
+                  - **tests**: tests/parser_test.rs\n\
+                  \n\
+                  docs:\n";
+    
+    // When
+    let groups = parse_groups(answer);
+    
+    // Then lines without files are skipped
+    assert_eq!(groups, vec![
+        ("refactor".to_string(), paths(&["src/parser.rs", "src/lexer.rs"])),
+        ("tests".to_string(), paths(&["tests/parser_test.rs"])),
+    ]);
+}
+
+#[test]
+fn test_plan_commits_every_staged_file_once() {
+    // Given a model that repeats one file, invents another and forgets a third
+    let staged = paths(&["src/a.rs", "src/b.rs", "tests/a_test.rs", "README.md"]);
+    let groups = vec![
+        ("feat".to_string(), paths(&["src/a.rs", "src/b.rs"])),
+        ("tests".to_string(), paths(&["tests/a_test.rs", "src/a.rs"])),
+        ("build".to_string(), paths(&["Cargo.toml"])),
+    ];
+    
+    // When
+    let plan = CommitPlan::new(groups, &staged);
+    
+    // Then
+    let planned: Vec<(&str, Vec<PathBuf>)> = plan.commits.iter()
+        .map(|commit| (commit.label.as_str(), commit.files.clone()))
+        .collect();
+    assert_eq!(planned, vec![
+        ("feat", paths(&["src/a.rs", "src/b.rs"])),
+        ("tests", paths(&["tests/a_test.rs"])),
+        (LEFTOVER_LABEL, paths(&["README.md"])),
+    ]);
+    
+    // An answer that can't be read still commits everything, in one commit
+    let plan = CommitPlan::new(parse_groups("I can't help with that."), &staged);
+    assert_eq!(plan.commits.len(), 1);
+    assert_eq!(plan.commits[0].files, staged);
+}
+
+#[test]
+fn test_renames_take_their_old_path_along() {
+    let plan = CommitPlan::new(vec![("refactor".to_string(), paths(&["src/new.rs"]))], &paths(&["src/new.rs"]));
+    let changes = vec![FileChange {
+        change_type: ChangeType::Renamed,
+        file_path: PathBuf::from("src/new.rs"),
+        old_path: Some(PathBuf::from("src/old.rs")),
+        ..Default::default()
+    }];
+    assert_eq!(plan.commits[0].index_paths(&changes), paths(&["src/new.rs", "src/old.rs"]));
+}
+
+#[test]
+fn test_split_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "--split"]).expect("Failed to parse args");
+    assert!(args.split);
+    
+    for other in ["--amend", "--raw", "--stdin-diff"] {
+        assert!(Args::try_parse_from(["git-ai-commit", "--split", other]).is_err(), "{}", other);
+    }
+}
+
+#[tokio::test]
+async fn test_split_commits_keep_what_was_staged() {
+    // Given two staged files, one with a further change that isn't staged
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init", "-q"]);
+    std::fs::write(repo_path.join("lib.rs"), "one\n").unwrap();
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-q", "-m", "Initial commit"]);
+    std::fs::write(repo_path.join("lib.rs"), "two\n").unwrap();
+    std::fs::write(repo_path.join("notes.md"), "notes\n").unwrap();
+    git(repo_path, &["add", "."]);
+    std::fs::write(repo_path.join("lib.rs"), "three\n").unwrap();
+    
+    let collector = GitCollector::new(repo_path.to_path_buf());
+    let git_info = collector.collect_all().await.unwrap();
+    let plan = CommitPlan::new(
+        vec![("docs".to_string(), paths(&["notes.md"])), ("fix".to_string(), paths(&["lib.rs"]))],
+        &git_info.status.staged_files,
+    );
+    
+    // When each group is staged from the saved tree and committed in turn
+    let tree = collector.staged_tree().await.unwrap();
+    collector.unstage_all().await.unwrap();
+    for commit in &plan.commits {
+        collector.stage_from_tree(&tree, &commit.index_paths(&git_info.file_changes)).await.unwrap();
+        git(repo_path, &["commit", "-q", "-m", &commit.label]);
+    }
+    
+    // Then each commit holds its own files, as staged
+    assert_eq!(git(repo_path, &["show", "--name-only", "--format=%s", "HEAD~1"]).trim(), "docs\n\nnotes.md");
+    assert_eq!(git(repo_path, &["show", "--name-only", "--format=%s", "HEAD"]).trim(), "fix\n\nlib.rs");
+    assert_eq!(git(repo_path, &["show", "HEAD:lib.rs"]), "two\n");
+    
+    // And the change that wasn't staged still isn't
+    assert_eq!(git(repo_path, &["diff", "--cached", "--name-only"]), "");
+    assert_eq!(git(repo_path, &["diff", "--name-only"]).trim(), "lib.rs");
+    assert_eq!(std::fs::read_to_string(repo_path.join("lib.rs")).unwrap(), "three\n");
+}
+
+#[tokio::test]
+async fn test_failed_split_restores_the_staged_changes() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init", "-q"]);
+    git(repo_path, &["commit", "-q", "--allow-empty", "-m", "Initial commit"]);
+    std::fs::write(repo_path.join("a.txt"), "a\n").unwrap();
+    std::fs::write(repo_path.join("b.txt"), "b\n").unwrap();
+    git(repo_path, &["add", "."]);
+    let collector = GitCollector::new(repo_path.to_path_buf());
+    let tree = collector.staged_tree().await.unwrap();
+    
+    // When only the first group was committed
+    collector.unstage_all().await.unwrap();
+    collector.stage_from_tree(&tree, &paths(&["a.txt"])).await.unwrap();
+    git(repo_path, &["commit", "-q", "-m", "add a"]);
+    collector.restore_staged_tree(&tree).await.unwrap();
+    
+    // Then the rest is staged again
+    assert_eq!(git(repo_path, &["diff", "--cached", "--name-only"]).trim(), "b.txt");
+}
+
+#[test]
+fn test_split_prompt_lists_the_staged_files() {
+    let git_info = git_ai_commit::git::GitInfo {
+        status: git_ai_commit::git::GitStatus {
+            staged_files: paths(&["src/parser.rs", "tests/parser_test.rs"]),
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: git_ai_commit::git::DiffInfo { files_changed: 2, insertions: 5, deletions: 1, file_stats: vec![] },
+        file_changes: paths(&["src/parser.rs", "tests/parser_test.rs"]).into_iter()
+            .map(|file_path| FileChange { change_type: ChangeType::Modified, file_path, old_path: None, ..Default::default() })
+            .collect(),
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    };
+    
+    let prompt = PromptBuilder::new(10, 50).build_split(&git_info);
+    
+    assert!(prompt.contains("src/parser.rs"));
+    assert!(prompt.contains("tests/parser_test.rs"));
+    assert!(prompt.contains("Use every staged file exactly once"));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::git_as;
+use clap::Parser;
+use git_ai_commit::cli::{Args, Command};
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::formatting::squash::{co_authors, SquashMessage};
+use git_ai_commit::git::{GitCollector, GitInfo};
+use tempfile::tempdir;
+
+const ME: (&str, &str) = ("Dev", "dev@example.com");
+const REVIEWER: (&str, &str) = ("Reviewer", "reviewer@example.com");
+
+#[test]
+fn test_squash_message_follows_github_conventions() {
+    // Given
+    let generated = "feat(auth): add session refresh\n\nTokens are refreshed before they expire.";
+    let co_authors = vec!["Reviewer <reviewer@example.com>".to_string()];
+    
+    // When
+    let squash = SquashMessage::new(generated, Some(42), &co_authors);
+    
+    // Then
+    assert_eq!(squash.title, "feat(auth): add session refresh (#42)");
+    assert_eq!(
+        squash.body,
+        "Tokens are refreshed before they expire.\n\nCo-authored-by: Reviewer <reviewer@example.com>"
+    );
+    assert_eq!(squash.render(), format!("{}\n\n{}", squash.title, squash.body));
+}
+
+#[test]
+fn test_squash_message_without_body_or_pr() {
+    let squash = SquashMessage::new("fix: handle empty diffs (#7)\n", Some(7), &[]);
+    
+    assert_eq!(squash.title, "fix: handle empty diffs (#7)");
+    assert_eq!(squash.body, "");
+    assert_eq!(squash.render(), "fix: handle empty diffs (#7)");
+}
+
+#[test]
+fn test_co_authors_leaves_out_the_committer_and_duplicates() {
+    let authors = vec![
+        "Dev <dev@example.com>".to_string(),
+        "Reviewer <reviewer@example.com>".to_string(),
+        "Reviewer R. <Reviewer@Example.com>".to_string(),
+        "Pair <pair@example.com>".to_string(),
+    ];
+    
+    assert_eq!(
+        co_authors(&authors, "dev@example.com"),
+        vec!["Reviewer <reviewer@example.com>", "Pair <pair@example.com>"]
+    );
+}
+
+#[test]
+fn test_squash_title_subcommand() {
+    let args = Args::try_parse_from(["git-ai-commit", "squash-title"]).expect("Failed to parse args");
+    match args.command {
+        Some(Command::SquashTitle { ref base, pr }) => {
+            assert_eq!(base, "origin/main");
+            assert_eq!(pr, None);
+        }
+        other => panic!("Unexpected command: {:?}", other),
+    }
+    
+    let args = Args::try_parse_from(["git-ai-commit", "squash-title", "--base", "upstream/dev", "--pr", "42", "--json"])
+        .expect("Failed to parse args");
+    assert!(matches!(args.command, Some(Command::SquashTitle { pr: Some(42), .. })));
+    assert!(args.json);
+}
+
+#[tokio::test]
+async fn test_branch_patch_authors_and_prompt() {
+    // Given a branch with two commits, one of them by a reviewer
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git_as(repo_path, ME, &["init", "-b", "main"]);
+    std::fs::write(repo_path.join("base.txt"), "base\n").unwrap();
+    git_as(repo_path, ME, &["add", "."]);
+    git_as(repo_path, ME, &["commit", "-m", "Initial commit"]);
+    git_as(repo_path, ME, &["checkout", "-b", "feature"]);
+    std::fs::write(repo_path.join("session.rs"), "fn refresh() {}\n").unwrap();
+    git_as(repo_path, ME, &["add", "."]);
+    git_as(repo_path, ME, &["commit", "-m", "wip"]);
+    std::fs::write(repo_path.join("session.rs"), "fn refresh_session() {}\n").unwrap();
+    git_as(repo_path, REVIEWER, &["commit", "-am", "address review"]);
+    git_as(repo_path, ME, &["config", "user.email", ME.1]);
+    let git_collector = GitCollector::new(repo_path.to_path_buf());
+    
+    // When
+    let commits = git_collector.get_commits_in_range("main..HEAD").await.unwrap();
+    let patch = git_collector.get_range_patch("main...HEAD").await.unwrap();
+    let authors = git_collector.get_range_authors("main..HEAD").await.unwrap();
+    let committer = git_collector.get_user_email().await.unwrap();
+    let git_info = GitInfo::from_patch(&patch).unwrap();
+    let prompt = PromptBuilder::new(10, 50).build_squash(&git_info, &commits, &patch);
+    
+    // Then the branch is one new file, whatever happened in between
+    assert!(patch.contains("+fn refresh_session() {}"));
+    assert!(!patch.contains("fn refresh() {}"));
+    assert_eq!(authors, vec!["Dev <dev@example.com>", "Reviewer <reviewer@example.com>"]);
+    assert_eq!(co_authors(&authors, &committer), vec!["Reviewer <reviewer@example.com>"]);
+    assert!(prompt.contains("Commits on the branch (2):"));
+    assert!(prompt.contains("  - address review"));
+    assert!(prompt.contains("session.rs"));
+    assert!(prompt.contains("squash-merge"));
+}
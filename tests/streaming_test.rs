@@ -0,0 +1,79 @@
+use git_ai_commit::ollama::{OllamaClient, OllamaClientTrait, StreamDecoder};
+use mockito::{Matcher, Server};
+use serde_json::json;
+
+#[test]
+fn test_decoder_joins_lines_split_across_chunks() {
+    // Given a stream whose chunk boundaries fall mid-line
+    let body = "{\"response\":\"fix: \",\"done\":false}\n\
+                {\"response\":\"retry uploads\",\"done\":false}\n\
+                {\"response\":\"\",\"done\":true,\"prompt_eval_count\":12,\"eval_count\":4}";
+    let (first, rest) = body.split_at(20);
+    let (second, third) = rest.split_at(30);
+    
+    // When it's fed piece by piece
+    let mut tokens = Vec::new();
+    let mut on_token = |token: &str| tokens.push(token.to_string());
+    let mut decoder = StreamDecoder::default();
+    decoder.feed(first.as_bytes(), &mut on_token).unwrap();
+    decoder.feed(second.as_bytes(), &mut on_token).unwrap();
+    decoder.feed(third.as_bytes(), &mut on_token).unwrap();
+    let generation = decoder.finish(&mut on_token).unwrap();
+    
+    // Then every token arrives once, in order, with the final stats
+    assert_eq!(tokens, vec!["fix: ", "retry uploads"]);
+    assert_eq!(generation.text, "fix: retry uploads");
+    assert_eq!(generation.stats.prompt_eval_count, Some(12));
+    assert_eq!(generation.stats.eval_count, Some(4));
+}
+
+#[test]
+fn test_decoder_surfaces_stream_errors() {
+    // Given a stream that fails partway through
+    let body = "{\"response\":\"fix\"}\n{\"error\":\"model runner has unexpectedly stopped\"}\n";
+    
+    // When it's decoded
+    let mut decoder = StreamDecoder::default();
+    let result = decoder.feed(body.as_bytes(), &mut |_| {});
+    
+    // Then the server's error is reported
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("model runner has unexpectedly stopped"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_client_streams_generate_response() {
+    // Given a server answering with newline-delimited JSON
+    let mut server = Server::new_async().await;
+    let body = [
+        json!({ "response": "feat: ", "done": false }),
+        json!({ "response": "add streaming", "done": false }),
+        json!({ "response": "", "done": true, "eval_count": 3 }),
+    ]
+    .iter()
+    .map(|line| format!("{}\n", line))
+    .collect::<String>();
+    let mock = server
+        .mock("POST", "/api/generate")
+        .match_body(Matcher::PartialJson(json!({ "model": "llama3", "stream": true })))
+        .with_status(200)
+        .with_header("content-type", "application/x-ndjson")
+        .with_body(body)
+        .create_async()
+        .await;
+    
+    // When generating with streaming
+    let port: u16 = server.url().split(':').nth(2).unwrap().parse().unwrap();
+    let client = OllamaClient::new(port);
+    let mut tokens = Vec::new();
+    let generation = client
+        .generate_stream("llama3", "prompt", &mut |token: &str| tokens.push(token.to_string()))
+        .await
+        .unwrap();
+    
+    // Then tokens are delivered as they arrive and the full text is returned
+    mock.assert_async().await;
+    assert_eq!(tokens, vec!["feat: ", "add streaming"]);
+    assert_eq!(generation.text, "feat: add streaming");
+    assert_eq!(generation.stats.eval_count, Some(3));
+}
@@ -0,0 +1,109 @@
+use git_ai_commit::formatting::structured::{self, StructuredMessage};
+use git_ai_commit::ollama::{OllamaClient, OllamaClientTrait, OpenAiClient};
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::json;
+
+fn port(server: &ServerGuard) -> u16 {
+    server.url().split(':').nth(2).unwrap().parse().unwrap()
+}
+
+#[test]
+fn test_message_is_assembled_from_the_fields() {
+    // Given
+    let answer = r#"{"type": "Feat", "scope": "auth", "subject": "add token refresh", "body": "Refresh tokens a minute before they expire.", "breaking": true}"#;
+    
+    // When
+    let message = StructuredMessage::parse(answer).unwrap();
+    
+    // Then
+    assert_eq!(message.render(), "feat(auth)!: add token refresh\n\nRefresh tokens a minute before they expire.");
+}
+
+#[test]
+fn test_empty_fields_are_left_out() {
+    let message = StructuredMessage::parse(r#"{"type": "fix", "scope": " ", "subject": "handle empty diffs ", "body": null, "breaking": false}"#).unwrap();
+    assert_eq!(message.render(), "fix: handle empty diffs");
+    
+    // A missing type is left for the style to repair
+    let message = StructuredMessage::parse(r#"{"subject": "Update readme"}"#).unwrap();
+    assert_eq!(message.render(), "Update readme");
+}
+
+#[test]
+fn test_chatter_around_the_object_is_dropped() {
+    // Given
+    let answer = "Here is your commit message:\n```json\n{\"type\": \"docs\", \"subject\": \"fix link\"}\n```";
+    
+    // When
+    let message = StructuredMessage::parse(answer).unwrap();
+    
+    // Then
+    assert_eq!(message.render(), "docs: fix link");
+}
+
+#[test]
+fn test_answers_without_a_subject_are_not_messages() {
+    assert_eq!(StructuredMessage::parse("docs: fix link"), None);
+    assert_eq!(StructuredMessage::parse(r#"{"type": "docs", "subject": ""}"#), None);
+    assert_eq!(StructuredMessage::parse(r#"{"type": "docs"}"#), None);
+}
+
+#[test]
+fn test_schema_and_request_name_the_same_fields() {
+    let schema = structured::schema();
+    let request = structured::request("Describe the changes.\n");
+    
+    for field in ["type", "scope", "subject", "body", "breaking"] {
+        assert!(schema["properties"].get(field).is_some(), "{}", field);
+        assert!(request.contains(&format!("\"{}\"", field)), "{}", field);
+    }
+    assert!(request.starts_with("Describe the changes.\n\nAnswer with a JSON object"), "{}", request);
+}
+
+#[tokio::test]
+async fn test_ollama_is_asked_for_the_schema() {
+    // Given
+    let mut server = Server::new_async().await;
+    let chat = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJson(json!({ "format": structured::schema(), "stream": false })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "message": { "role": "assistant", "content": "{\"type\": \"fix\", \"subject\": \"typo\"}" }, "done": true }).to_string())
+        .create_async()
+        .await;
+    
+    // When
+    let generation = OllamaClient::new(port(&server))
+        .generate_json("llama3", "prompt", &structured::schema())
+        .await
+        .unwrap();
+    
+    // Then
+    assert_eq!(StructuredMessage::parse(&generation.text).unwrap().render(), "fix: typo");
+    chat.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_openai_is_asked_for_json() {
+    // Given
+    let mut server = Server::new_async().await;
+    let completions = server
+        .mock("POST", "/chat/completions")
+        .match_body(Matcher::PartialJson(json!({ "response_format": { "type": "json_object" } })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "choices": [{ "message": { "content": "{\"type\": \"ci\", \"subject\": \"cache cargo\"}" } }] }).to_string())
+        .create_async()
+        .await;
+    
+    // When
+    let generation = OpenAiClient::new(&server.url(), None)
+        .generate_json("gpt-4o-mini", "prompt", &structured::schema())
+        .await
+        .unwrap();
+    
+    // Then
+    assert_eq!(StructuredMessage::parse(&generation.text).unwrap().render(), "ci: cache cargo");
+    completions.assert_async().await;
+}
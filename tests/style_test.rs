@@ -0,0 +1,117 @@
+use clap::Parser;
+use git_ai_commit::cli::args::Args;
+use git_ai_commit::config::{CommitStyle, Config, MessageStyle, ValidationConfig};
+use git_ai_commit::formatting::style::{finish, template};
+use git_ai_commit::formatting::template::check;
+use git_ai_commit::formatting::validate::{validate, Violation};
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn staged(paths: &[&str]) -> GitInfo {
+    let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    GitInfo {
+        status: GitStatus {
+            staged_files: paths.clone(),
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: paths.len(),
+            insertions: 1,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: paths.into_iter().map(|file_path| FileChange {
+            change_type: ChangeType::Modified,
+            file_path,
+            old_path: None,
+            ..Default::default()
+        }).collect(),
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_every_style_has_a_valid_template() {
+    for style in [MessageStyle::Conventional, MessageStyle::Gitmoji, MessageStyle::Angular, MessageStyle::Plain, MessageStyle::Kernel] {
+        assert!(check(template(style), "style").is_ok(), "{:?}", style);
+    }
+}
+
+#[test]
+fn test_gitmoji_replaces_types_and_shortcodes() {
+    let code = staged(&["src/parser.rs"]);
+    
+    assert_eq!(finish(MessageStyle::Gitmoji, "feat(parser): add comment skipping.", &code), "✨ Add comment skipping");
+    assert_eq!(finish(MessageStyle::Gitmoji, "Fix: crash on empty input", &code), "🐛 Crash on empty input");
+    assert_eq!(finish(MessageStyle::Gitmoji, ":bug: fix crash on empty input", &code), "🐛 Fix crash on empty input");
+    assert_eq!(finish(MessageStyle::Gitmoji, "🐛 Fix crash on empty input", &code), "🐛 Fix crash on empty input");
+    
+    // Without a type, the staged files pick the emoji
+    let docs = staged(&["README.md"]);
+    assert_eq!(finish(MessageStyle::Gitmoji, "describe the --style flag", &docs), "📝 Describe the --style flag");
+}
+
+#[test]
+fn test_kernel_prefixes_the_subsystem() {
+    // Given files in one directory
+    let parser = staged(&["src/parser/lexer.rs", "src/parser/mod.rs"]);
+    
+    // When / Then the directory becomes the subsystem, unless a scope names one
+    assert_eq!(finish(MessageStyle::Kernel, "feat: Skip comments.", &parser), "parser: skip comments");
+    assert_eq!(finish(MessageStyle::Kernel, "fix(lexer): handle tabs", &parser), "lexer: handle tabs");
+    assert_eq!(finish(MessageStyle::Kernel, "net: fix socket leak", &parser), "net: fix socket leak");
+    
+    // Files without a shared directory leave the summary as it is
+    let scattered = staged(&["src/main.rs", "README.md"]);
+    assert_eq!(finish(MessageStyle::Kernel, "refactor: split the CLI", &scattered), "Split the CLI");
+}
+
+#[test]
+fn test_angular_uses_its_own_types() {
+    let code = staged(&["src/parser.rs"]);
+    
+    assert_eq!(finish(MessageStyle::Angular, "chore: Bump the MSRV", &code), "build: bump the MSRV");
+    assert_eq!(finish(MessageStyle::Angular, "style(parser): Reorder imports", &code), "refactor(parser): reorder imports");
+    assert_eq!(finish(MessageStyle::Angular, "docs: README typo", &code), "docs: README typo");
+}
+
+#[test]
+fn test_plain_drops_the_prefix_and_keeps_the_body() {
+    let message = "feat(parser): add comment skipping\n\n- Skip line comments";
+    
+    let finished = finish(MessageStyle::Plain, message, &staged(&["src/parser.rs"]));
+    
+    assert_eq!(finished, "Add comment skipping\n\n- Skip line comments");
+}
+
+#[test]
+fn test_emoji_subjects_are_validated_past_the_emoji() {
+    let violations = validate("✨ Added comment skipping", &ValidationConfig::default());
+    
+    assert_eq!(violations, vec![Violation::PastTense("added".to_string())]);
+}
+
+#[test]
+fn test_style_flag_and_setting() {
+    // Given
+    let args = Args::try_parse_from(["git-ai-commit", "--style", "gitmoji"]).expect("Failed to parse args");
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "style = \"kernel\"\n").unwrap();
+    
+    // When
+    let config = Config::load_from_path(&config_path).unwrap();
+    
+    // Then
+    assert_eq!(args.style, Some(MessageStyle::Gitmoji));
+    assert_eq!(config.style, Some(MessageStyle::Kernel));
+    assert_eq!(MessageStyle::Angular.commit_style(), CommitStyle::Conventional);
+    assert_eq!(MessageStyle::Gitmoji.commit_style(), CommitStyle::Plain);
+    assert!(Args::try_parse_from(["git-ai-commit", "--style", "emoji"]).is_err());
+}
@@ -0,0 +1,88 @@
+use git_ai_commit::formatting::prompt::PromptBuilder;
+use git_ai_commit::formatting::summarize::DiffSummarizer;
+use git_ai_commit::git::GitInfo;
+
+fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+}
+
+#[test]
+fn test_short_hunks_are_kept_whole() {
+    let hunk = lines("@@ -1,2 +1,2 @@\n-a\n+b");
+    
+    let (kept, omitted) = DiffSummarizer::new().summarize(&hunk, 10);
+    
+    assert_eq!(kept, hunk);
+    assert_eq!(omitted, 0);
+}
+
+#[test]
+fn test_keeps_signatures_logs_and_renames() {
+    // Given
+    let hunk = lines(
+        "@@ -1,9 +1,10 @@ impl Client\n\
+          context line\n\
+         -    let x = 1;\n\
+         +    let x = 2;\n\
+         +pub fn retry_upload(&self) -> Result<()> {\n\
+         +    }\n\
+         +    warn!(\"retrying upload\");\n\
+         -    let total = compute(a);\n\
+         +    let sum = compute(a);",
+    );
+    
+    // When
+    let (kept, omitted) = DiffSummarizer::new().summarize(&hunk, 5);
+    
+    // Then: the signature, log line and rename win, in original order
+    assert_eq!(
+        kept,
+        lines(
+            "@@ -1,9 +1,10 @@ impl Client\n\
+             +pub fn retry_upload(&self) -> Result<()> {\n\
+             +    warn!(\"retrying upload\");\n\
+             -    let total = compute(a);\n\
+             +    let sum = compute(a);"
+        )
+    );
+    assert_eq!(omitted, 4);
+}
+
+#[test]
+fn test_prompt_uses_summary_when_enabled() {
+    let mut patch = String::from("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -0,0 +1,6 @@\n");
+    for i in 0..5 {
+        patch.push_str(&format!("+// note {}\n", i));
+    }
+    patch.push_str("+pub fn parse_config() {}\n");
+    let git_info = GitInfo::from_patch(&patch).unwrap();
+    
+    let truncated = PromptBuilder::new(10, 2).build_with_patch(&git_info, &patch);
+    assert!(!truncated.contains("parse_config"));
+    
+    let summarized = PromptBuilder::new(10, 2)
+        .with_diff_summary(true)
+        .build_with_patch(&git_info, &patch);
+    assert!(summarized.contains("+pub fn parse_config() {}"));
+    assert!(summarized.contains("... (5 less informative lines omitted)"));
+}
+
+#[test]
+fn test_default_prompt_shows_summarized_diffs() {
+    // Given
+    let mut patch = String::from("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -0,0 +1,6 @@\n");
+    for i in 0..5 {
+        patch.push_str(&format!("+// note {}\n", i));
+    }
+    patch.push_str("+pub fn parse_config() {}\n");
+    let git_info = GitInfo::from_patch(&patch).unwrap();
+    let prompt_builder = PromptBuilder::new(10, 2).with_patch(Some(patch));
+    
+    // When
+    let plain = prompt_builder.build(&git_info);
+    let summarized = prompt_builder.with_diff_summary(true).build(&git_info);
+    
+    // Then
+    assert!(!plain.contains("parse_config"), "{}", plain);
+    assert!(summarized.contains("\nDiff:\n--- src/lib.rs\n@@ -0,0 +1,6 @@\n+pub fn parse_config() {}\n"), "{}", summarized);
+}
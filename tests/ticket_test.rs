@@ -0,0 +1,86 @@
+use git_ai_commit::config::{Config, TicketConfig, TicketPlacement};
+use git_ai_commit::formatting::ticket::{find, inject};
+use tempfile::tempdir;
+
+fn placed(placement: TicketPlacement) -> TicketConfig {
+    TicketConfig { placement, ..TicketConfig::default() }
+}
+
+#[test]
+fn test_default_pattern_finds_issue_keys() {
+    let patterns = TicketConfig::default().patterns;
+    
+    assert_eq!(find("feature/JIRA-1234-add-login", &patterns).as_deref(), Some("JIRA-1234"));
+    assert_eq!(find("PROJ2-7", &patterns).as_deref(), Some("PROJ2-7"));
+    assert_eq!(find("feature/add-login", &patterns), None);
+    assert_eq!(find("release-2024-10", &patterns), None);
+    assert_eq!(find("fix/A-1-typo", &patterns), None);
+    assert_eq!(find("fix/UTF-8-names", &patterns), None);
+    assert_eq!(find("feat/SHA-256-for-ISO-8601-dates-PROJ-42", &patterns).as_deref(), Some("PROJ-42"));
+}
+
+#[test]
+fn test_patterns_are_tried_in_order_and_use_the_first_group() {
+    // Given GitHub issue numbers as a fallback, as `#123`
+    let patterns = vec![r"([A-Z]+-\d+)".to_string(), r"^(?:fix|feat)/(\d+)-".to_string(), "[".to_string()];
+    
+    // When
+    let jira = find("fix/ABC-12-typo", &patterns);
+    let issue = find("fix/123-typo", &patterns).map(|number| format!("#{}", number));
+    
+    // Then
+    assert_eq!(jira.as_deref(), Some("ABC-12"));
+    assert_eq!(issue.as_deref(), Some("#123"));
+}
+
+#[test]
+fn test_footer_adds_a_trailer() {
+    let footer = placed(TicketPlacement::Footer);
+    
+    assert_eq!(inject("feat(auth): add login", "JIRA-1234", &footer), "feat(auth): add login\n\nRefs: JIRA-1234");
+    assert_eq!(
+        inject("feat(auth): add login\n\nSigned-off-by: A <a@example.com>", "JIRA-1234", &footer),
+        "feat(auth): add login\n\nSigned-off-by: A <a@example.com>\nRefs: JIRA-1234"
+    );
+    
+    let closes = TicketConfig { trailer: "Closes".to_string(), ..footer };
+    assert!(inject("fix: typo", "ABC-1", &closes).ends_with("\n\nCloses: ABC-1"));
+}
+
+#[test]
+fn test_subject_placement_goes_after_the_type() {
+    let subject = placed(TicketPlacement::Subject);
+    
+    assert_eq!(inject("feat(auth): add login\n\n- Form", "JIRA-1234", &subject), "feat(auth): JIRA-1234 add login\n\n- Form");
+    assert_eq!(inject("Add login", "JIRA-1234", &subject), "JIRA-1234 Add login");
+}
+
+#[test]
+fn test_a_mentioned_ticket_is_left_alone() {
+    assert_eq!(inject("fix: JIRA-1234 crash", "JIRA-1234", &TicketConfig::default()), "fix: JIRA-1234 crash");
+    assert_eq!(inject("fix: crash", "JIRA-1234", &placed(TicketPlacement::Off)), "fix: crash");
+}
+
+#[test]
+fn test_ticket_settings() {
+    // Given a repository config with its own tracker
+    let temp_dir = tempdir().unwrap();
+    let global = temp_dir.path().join("config.toml");
+    let repo = temp_dir.path().join("repo.toml");
+    std::fs::write(&global, "").unwrap();
+    std::fs::write(&repo, "[ticket]\npatterns = ['(GH-\\d+)']\nplacement = \"subject\"\n").unwrap();
+    
+    // When
+    let config = Config::load_layered(&global, Some(&repo)).unwrap();
+    
+    // Then
+    assert_eq!(config.ticket.patterns, vec![r"(GH-\d+)".to_string()]);
+    assert_eq!(config.ticket.placement, TicketPlacement::Subject);
+    assert_eq!(config.ticket.trailer, "Refs");
+    
+    let mut broken = Config::default();
+    broken.ticket.patterns = vec!["(".to_string()];
+    assert!(broken.check().is_err());
+    broken.ticket = TicketConfig { trailer: "Refs:".to_string(), ..TicketConfig::default() };
+    assert!(broken.check().is_err());
+}
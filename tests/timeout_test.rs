@@ -0,0 +1,109 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use git_ai_commit::ollama::{OllamaClientTrait, OllamaManager};
+use git_ai_commit::utils::error::is_timeout_error;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A backend that takes `delay` to answer, or fails when it has no answer
+struct SlowClient {
+    delay: Duration,
+    answer: Option<&'static str>,
+}
+
+#[async_trait]
+impl OllamaClientTrait for SlowClient {
+    async fn is_running(&self) -> bool {
+        true
+    }
+    
+    async fn generate(&self, model: &str, _prompt: &str) -> Result<String> {
+        tokio::time::sleep(self.delay).await;
+        match self.answer {
+            Some(answer) if model != "slow" => Ok(answer.to_string()),
+            Some(_) => {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(String::new())
+            }
+            None => anyhow::bail!("model crashed"),
+        }
+    }
+    
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+    
+    async fn has_model(&self, _model_name: &str) -> Result<bool> {
+        Ok(true)
+    }
+    
+    async fn pull_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn get_last_model(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+    
+    async fn delete_model(&self, _model_name: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn manager(model: &str, delay: Duration, answer: Option<&'static str>) -> OllamaManager {
+    OllamaManager::new(model.to_string(), 1)
+        .unwrap()
+        .with_client(Arc::new(SlowClient { delay, answer }))
+}
+
+#[tokio::test]
+async fn test_slow_generation_times_out() {
+    // Given
+    let manager = manager("llama3", Duration::from_secs(30), Some("fix: typo"))
+        .with_generation_timeout(Duration::from_millis(50));
+    
+    // When
+    let error = manager.generate_commit_with_stats("prompt").await.unwrap_err();
+    
+    // Then
+    assert!(is_timeout_error(&error), "{}", error);
+    assert!(error.to_string().contains("'llama3' gave no answer within 50ms; raise --timeout-seconds"), "{}", error);
+    assert!(is_timeout_error(&manager.generate_commit("prompt").await.unwrap_err()));
+    assert!(is_timeout_error(&manager.generate_with_model("mistral", "prompt").await.unwrap_err()));
+}
+
+#[tokio::test]
+async fn test_backend_errors_are_not_timeouts() {
+    let manager = manager("llama3", Duration::ZERO, None).with_generation_timeout(Duration::from_secs(5));
+    
+    let error = manager.generate_commit_with_stats("prompt").await.unwrap_err();
+    
+    assert!(!is_timeout_error(&error));
+    assert!(error.to_string().contains("model crashed"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_a_longer_timeout_lets_the_model_finish() {
+    // Given a generation that timed out
+    let mut manager = manager("llama3", Duration::from_millis(200), Some("fix: typo"))
+        .with_generation_timeout(Duration::from_millis(20));
+    assert!(manager.generate_commit_with_stats("prompt").await.is_err());
+    
+    // When
+    manager.set_generation_timeout(Duration::from_secs(5));
+    let generation = manager.generate_commit_with_stats("prompt").await.unwrap();
+    
+    // Then
+    assert_eq!(generation.text, "fix: typo");
+    assert_eq!(manager.generation_timeout(), Some(Duration::from_secs(5)));
+}
+
+#[tokio::test]
+async fn test_each_ensemble_model_has_its_own_timeout() {
+    let manager = manager("llama3", Duration::ZERO, Some("fix: typo")).with_generation_timeout(Duration::from_millis(100));
+    
+    let results = manager.generate_with_models(&["fast".to_string(), "slow".to_string()], "prompt").await;
+    
+    assert_eq!(results[0].1.as_ref().unwrap().text, "fix: typo");
+    assert!(is_timeout_error(results[1].1.as_ref().unwrap_err()));
+}
@@ -0,0 +1,88 @@
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::Config;
+use git_ai_commit::formatting::merge::append_trailers;
+use git_ai_commit::formatting::trailers::{ai_generated_by, co_authored_by, is_identity, signed_off_by};
+use git_ai_commit::git::GitCollector;
+use tempfile::tempdir;
+
+#[test]
+fn test_trailers_follow_git_conventions() {
+    // Given
+    let trailers = vec![
+        co_authored_by(" Pair <pair@example.com> "),
+        ai_generated_by("gemma3:4b"),
+        signed_off_by("Dev <dev@example.com>"),
+    ];
+    
+    // When
+    let message = append_trailers("feat: add --signoff\n\nCredit the committer.", &trailers);
+    
+    // Then they form one trailer block after the body
+    assert_eq!(
+        message,
+        "feat: add --signoff\n\nCredit the committer.\n\n\
+         Co-authored-by: Pair <pair@example.com>\n\
+         AI-Generated-By: git-ai-commit/gemma3:4b\n\
+         Signed-off-by: Dev <dev@example.com>"
+    );
+    
+    // Trailers the message already has aren't repeated
+    assert_eq!(append_trailers(&message, &trailers), message);
+}
+
+#[test]
+fn test_is_identity() {
+    assert!(is_identity("Pair Programmer <pair@example.com>"));
+    assert!(!is_identity("pair@example.com"));
+    assert!(!is_identity("<pair@example.com>"));
+    assert!(!is_identity("Pair <pair>"));
+}
+
+#[test]
+fn test_co_authors_and_ai_trailer_config() {
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(config.co_authors.is_empty());
+    assert!(!config.ai_trailer);
+    
+    std::fs::write(&config_path, "ai_trailer = true\nco_authors = [\"Pair <pair@example.com>\"]\n").unwrap();
+    let config = Config::load_from_path(&config_path).unwrap();
+    assert!(config.ai_trailer);
+    assert_eq!(config.co_authors, vec!["Pair <pair@example.com>"]);
+    
+    let config = Config { co_authors: vec!["pair@example.com".to_string()], ..Config::default() };
+    assert!(config.check().unwrap_err().to_string().contains("Name <email>"));
+}
+
+#[test]
+fn test_signoff_flag() {
+    let args = Args::try_parse_from(["git-ai-commit", "--signoff"]).expect("Failed to parse args");
+    assert!(args.signoff);
+    
+    let args = Args::try_parse_from(["git-ai-commit", "commit", "-s"]).expect("Failed to parse args");
+    assert!(args.signoff);
+}
+
+#[tokio::test]
+async fn test_committer_identity() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    for args in [
+        vec!["init", "-q"],
+        vec!["config", "user.name", "Dev Eloper"],
+        vec!["config", "user.email", "dev@example.com"],
+    ] {
+        let status = std::process::Command::new("git").args(&args).current_dir(repo_path).status().unwrap();
+        assert!(status.success());
+    }
+    
+    // When
+    let identity = GitCollector::new(repo_path.to_path_buf()).get_committer_identity().await;
+    
+    // Then
+    assert_eq!(identity.as_deref(), Some("Dev Eloper <dev@example.com>"));
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::git;
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::{GitCollector, UntrackedSummary};
+use git_ai_commit::git::collector::{prioritize_untracked, LARGE_UNTRACKED_BYTES, MAX_UNTRACKED_FILES};
+use git_ai_commit::git::files::is_scratch_file;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn write(repo_path: &Path, file: &str) {
+    let path = repo_path.join(file);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, file).unwrap();
+}
+
+#[tokio::test]
+async fn test_untracked_directories_are_expanded() {
+    // Given: one untracked file and a new directory with nested files
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    write(repo_path, "README.md");
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    write(repo_path, "notes.txt");
+    write(repo_path, "src/export/mod.rs");
+    write(repo_path, "src/export/csv.rs");
+    write(repo_path, "src/export/json/writer.rs");
+    
+    // When
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await.unwrap();
+    
+    // Then
+    assert!(git_info.status.untracked_files.contains(&PathBuf::from("src/")));
+    assert!(git_info.untracked_files.contains(&PathBuf::from("src/export/json/writer.rs")));
+    assert_eq!(git_info.untracked_summary(), UntrackedSummary { directories: 1, files: 4, unexpanded: 0 });
+    
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    assert!(prompt.contains("Untracked files (4 files; 1 new directories)"), "Unexpected prompt: {}", prompt);
+}
+
+#[tokio::test]
+async fn test_large_untracked_directories_stay_collapsed() {
+    // Given: a new directory with more files than are expanded
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    write(repo_path, "README.md");
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    for i in 0..=MAX_UNTRACKED_FILES {
+        write(repo_path, &format!("vendor/file{}.txt", i));
+    }
+    
+    // When
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await.unwrap();
+    
+    // Then
+    assert_eq!(git_info.untracked_files, vec![PathBuf::from("vendor/")]);
+    assert_eq!(git_info.untracked_summary(), UntrackedSummary { directories: 1, files: 0, unexpanded: 1 });
+    
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    assert!(prompt.contains("(1 directories too large to list)"));
+}
+
+#[test]
+fn test_is_scratch_file() {
+    for scratch in [
+        ".main.rs.swp", "src/.lib.rs.swo", "notes.txt~", ".#draft.md", "#draft.md#",
+        ".DS_Store", "docs/Thumbs.db", "._photo.png", "merge.rs.orig", "patch.rej", ".~lock.sheet.ods#",
+        "__MACOSX/readme.txt",
+    ] {
+        assert!(is_scratch_file(Path::new(scratch)), "{} should be scratch", scratch);
+    }
+    for work in ["src/swap.rs", "README.md", "src/#include.rs", "temp/config.toml", "src/export/"] {
+        assert!(!is_scratch_file(Path::new(work)), "{} should not be scratch", work);
+    }
+}
+
+#[test]
+fn test_prioritize_untracked() {
+    // Given files written oldest first, one large and one scratch file
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    let now = std::time::SystemTime::now();
+    let files = [("old.rs", 300), ("dump.json", 200), (".new.rs.swp", 0), ("new.rs", 100)];
+    for (file, age) in files {
+        write(repo_path, file);
+        let handle = std::fs::File::options().write(true).open(repo_path.join(file)).unwrap();
+        if file == "dump.json" {
+            handle.set_len(LARGE_UNTRACKED_BYTES + 1).unwrap();
+        }
+        handle.set_modified(now - std::time::Duration::from_secs(age)).unwrap();
+    }
+    
+    // When
+    let ordered = prioritize_untracked(repo_path, files.iter().map(|(file, _)| PathBuf::from(file)).collect());
+    
+    // Then
+    let expected: Vec<PathBuf> = ["new.rs", "old.rs", "dump.json", ".new.rs.swp"].iter().map(PathBuf::from).collect();
+    assert_eq!(ordered, expected);
+}
+
+#[tokio::test]
+async fn test_scratch_files_are_left_out_of_the_prompt() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo_path = temp_dir.path();
+    git(repo_path, &["init"]);
+    write(repo_path, "README.md");
+    git(repo_path, &["add", "."]);
+    git(repo_path, &["commit", "-m", "init"]);
+    
+    write(repo_path, "src/export.rs");
+    write(repo_path, ".export.rs.swp");
+    write(repo_path, ".DS_Store");
+    
+    // When
+    let git_info = GitCollector::new(repo_path.to_path_buf()).collect_all().await.unwrap();
+    let prompt = PromptBuilder::new(10, 50).build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("Untracked files (1 files; 1 new directories): src/export.rs (2 editor/OS scratch files omitted)"),
+        "Unexpected prompt: {}", prompt);
+    assert!(!prompt.contains(".DS_Store"));
+}
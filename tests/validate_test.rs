@@ -0,0 +1,173 @@
+use git_ai_commit::config::{BannedWordAction, ValidationConfig};
+use git_ai_commit::formatting::validate::{enforce_banned_words, validate, validate_for_changes, Violation};
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::files::ChangeType;
+use git_ai_commit::git::{DiffInfo, FileChange, GitInfo, GitStatus};
+use std::path::PathBuf;
+
+#[test]
+fn test_valid_message() {
+    let config = ValidationConfig::default();
+    assert!(validate("feat(auth): add session refresh endpoint", &config).is_empty());
+    assert!(validate("Speed up diff parsing\n\nAvoids reallocating per line.", &config).is_empty());
+}
+
+#[test]
+fn test_empty_message() {
+    assert_eq!(validate("  \n", &ValidationConfig::default()), vec![Violation::Empty]);
+}
+
+#[test]
+fn test_punctuation_only_message_is_empty() {
+    assert_eq!(validate("```\n```", &ValidationConfig::default()), vec![Violation::Empty]);
+    assert_eq!(validate("...", &ValidationConfig::default()), vec![Violation::Empty]);
+}
+
+#[test]
+fn test_refusal() {
+    let config = ValidationConfig::default();
+    for answer in [
+        "I'm sorry, but I can't help with that.",
+        "I cannot generate a commit message without a diff.",
+        "As an AI language model, I don't have access to your repository.",
+        "Unfortunately the diff is empty.",
+    ] {
+        assert_eq!(validate(answer, &config), vec![Violation::Refusal], "{}", answer);
+    }
+    
+    assert!(Violation::Refusal.is_unusable());
+    assert!(Violation::Empty.is_unusable());
+    assert!(!Violation::Preamble.is_unusable());
+    // Subjects that merely start with similar words are fine
+    assert!(validate("Handle sorry-state in retry loop", &config).is_empty());
+    assert!(validate("Don't retry unusable answers", &config).is_empty());
+}
+
+#[test]
+fn test_subject_too_long() {
+    let config = ValidationConfig { max_subject_length: 20, ..ValidationConfig::default() };
+    
+    let violations = validate("fix: handle expired session tokens", &config);
+    
+    assert_eq!(violations, vec![Violation::SubjectTooLong { length: 34, max: 20 }]);
+    assert_eq!(violations[0].describe(), "subject was 34 chars (max 20)");
+}
+
+#[test]
+fn test_past_tense_and_period() {
+    let violations = validate("fix: added retry to the client.", &ValidationConfig::default());
+    
+    assert_eq!(violations, vec![Violation::PastTense("added".to_string()), Violation::TrailingPeriod]);
+    assert_eq!(violations[0].describe(), "'added' is past tense; use the imperative mood");
+}
+
+#[test]
+fn test_preamble() {
+    let violations = validate("Here is your commit message: fix typo", &ValidationConfig::default());
+    assert_eq!(violations, vec![Violation::Preamble]);
+}
+
+#[test]
+fn test_retry_prompt_lists_violations() {
+    let violations = vec![
+        Violation::SubjectTooLong { length: 94, max: 72 },
+        Violation::PastTense("added".to_string()),
+    ];
+    
+    let prompt = PromptBuilder::build_retry("Original prompt", "added a very long subject", &violations);
+    
+    assert!(prompt.starts_with("Original prompt"));
+    assert!(prompt.contains("added a very long subject"));
+    assert!(prompt.contains("- subject was 94 chars (max 72)"));
+    assert!(prompt.contains("- 'added' is past tense; use the imperative mood"));
+}
+
+fn banned(action: BannedWordAction) -> ValidationConfig {
+    ValidationConfig {
+        banned_words: vec!["Project Falcon".to_string(), "acme".to_string()],
+        banned_word_action: action,
+        ..ValidationConfig::default()
+    }
+}
+
+#[test]
+fn test_banned_words_rejected() {
+    let config = banned(BannedWordAction::Reject);
+    
+    let violations = validate("feat: add ACME export for project falcon", &config);
+    assert_eq!(
+        violations,
+        vec![
+            Violation::BannedWord("Project Falcon".to_string()),
+            Violation::BannedWord("acme".to_string()),
+        ]
+    );
+    
+    let err = enforce_banned_words("feat: add ACME export", &config).unwrap_err();
+    assert!(err.to_string().contains("banned words: acme"), "Unexpected error: {}", err);
+    
+    // Only whole words count
+    assert!(validate("feat: add acmexport tool", &config).is_empty());
+    assert_eq!(enforce_banned_words("fix: typo", &config).unwrap(), "fix: typo");
+}
+
+#[test]
+fn test_banned_words_masked() {
+    let config = banned(BannedWordAction::Mask);
+    
+    // Masked words are not violations, so they don't trigger retries
+    assert!(validate("feat: add Acme export", &config).is_empty());
+    assert_eq!(
+        enforce_banned_words("feat: add Acme export for Project Falcon", &config).unwrap(),
+        "feat: add *** export for ***"
+    );
+}
+
+fn mode_only_change() -> GitInfo {
+    GitInfo {
+        status: GitStatus {
+            staged_files: vec![PathBuf::from("scripts/release.sh")],
+            modified_files: vec![],
+            untracked_files: vec![],
+            deleted_files: vec![],
+        },
+        diff_stat: DiffInfo {
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+            file_stats: vec![],
+        },
+        file_changes: vec![FileChange {
+            change_type: ChangeType::ModeChanged {
+                old_mode: "100644".to_string(),
+                new_mode: "100755".to_string(),
+            },
+            file_path: PathBuf::from("scripts/release.sh"),
+            old_path: None,
+            ..Default::default()
+        }],
+        untracked_files: vec![],
+        branch_name: "main".to_string(),
+        last_commit: None,
+    }
+}
+
+#[test]
+fn test_mode_only_change_requires_chore_or_build() {
+    // Given
+    let git_info = mode_only_change();
+    let config = ValidationConfig::default();
+    
+    // When
+    let accepted = validate_for_changes("chore: make release.sh executable", &config, &git_info);
+    let rejected = validate_for_changes("feat: implement release automation", &config, &git_info);
+    
+    // Then
+    assert!(git_info.is_mode_only());
+    assert!(accepted.is_empty());
+    assert_eq!(rejected, vec![
+        Violation::ModeChangeType("feat".to_string()),
+        Violation::InventedFunctionality("implement".to_string()),
+    ]);
+    assert!(PromptBuilder::new(10, 50).build(&git_info).contains("Only file permissions changed"));
+}
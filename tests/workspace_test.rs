@@ -0,0 +1,151 @@
+use git_ai_commit::analysis::language::ProjectLayout;
+use git_ai_commit::analysis::workspace::{Workspace, WorkspaceKind};
+use git_ai_commit::config::{ContextBlocks, ValidationConfig};
+use git_ai_commit::formatting::candidate::Candidate;
+use git_ai_commit::formatting::validate::{check_package_scope, Violation};
+use git_ai_commit::formatting::PromptBuilder;
+use git_ai_commit::git::exclude::PathFilter;
+use git_ai_commit::git::GitInfo;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+/// Write `files` under `root`, creating their directories
+fn write_files(root: &Path, files: &[(&str, &str)]) -> Vec<PathBuf> {
+    for (path, content) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    files.iter().map(|(path, _)| PathBuf::from(path)).collect()
+}
+
+fn member_names(workspace: &Workspace) -> Vec<&str> {
+    let mut names: Vec<&str> = workspace.members.iter().map(|member| member.name.as_str()).collect();
+    names.sort();
+    names
+}
+
+const PATCH: &str = "\
+diff --git a/crates/api/src/routes.rs b/crates/api/src/routes.rs
+index 1111111..2222222 100644
+--- a/crates/api/src/routes.rs
++++ b/crates/api/src/routes.rs
+@@ -1 +1 @@
+-fn routes() {}
++fn routes() { login(); }
+";
+
+#[test]
+fn test_cargo_workspace_members_are_detected() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let manifests = write_files(temp_dir.path(), &[
+        ("Cargo.toml", "[package]\nname = \"acme\"\n\n[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/legacy\"]\n"),
+        ("crates/api/Cargo.toml", "[package]\nname = \"acme-api\"\n"),
+        ("crates/core/Cargo.toml", "[package]\nname = \"acme-core\"\n"),
+        ("crates/legacy/Cargo.toml", "[package]\nname = \"acme-legacy\"\n"),
+    ]);
+    
+    // When
+    let workspaces = Workspace::detect(temp_dir.path(), &manifests);
+    let layout = ProjectLayout::detect(temp_dir.path(), &manifests);
+    
+    // Then
+    assert_eq!(workspaces.len(), 1);
+    assert_eq!(workspaces[0].kind, WorkspaceKind::Cargo);
+    assert_eq!(member_names(&workspaces[0]), vec!["acme", "acme-api", "acme-core"]);
+    let affected = layout.affected_packages(["crates/api/src/lib.rs", "crates/core/src/db.rs", "crates/api/Cargo.toml"].map(Path::new));
+    assert_eq!(affected, vec!["acme-api", "acme-core"]);
+    assert_eq!(layout.affected_packages(["src/main.rs", "crates/legacy/src/lib.rs"].map(Path::new)), vec!["acme"]);
+}
+
+#[test]
+fn test_js_workspace_members_are_detected() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let manifests = write_files(temp_dir.path(), &[
+        ("pnpm-workspace.yaml", "packages:\n  # all packages\n  - 'packages/*'\n  - \"!packages/scratch\"\n\nonlyBuiltDependencies:\n  - esbuild\n"),
+        ("packages/ui/package.json", "{\"name\": \"@acme/ui\"}"),
+        ("packages/scratch/package.json", "{\"name\": \"scratch\"}"),
+        ("packages/ui/node_modules/esbuild/package.json", "{\"name\": \"esbuild\"}"),
+        ("site/package.json", "{\"private\": true, \"workspaces\": {\"packages\": [\"docs/**\"]}}"),
+        ("site/docs/guide/package.json", "{\"private\": true}"),
+        ("nx.json", "{}"),
+        ("apps/web/project.json", "{\"name\": \"web\", \"sourceRoot\": \"apps/web/src\"}"),
+        ("libs/shared/project.json", "{\"sourceRoot\": \"libs/shared/src\"}"),
+    ]);
+    
+    // When
+    let workspaces = Workspace::detect(temp_dir.path(), &manifests);
+    
+    // Then
+    let kinds: Vec<WorkspaceKind> = workspaces.iter().map(|workspace| workspace.kind).collect();
+    assert_eq!(kinds, vec![WorkspaceKind::Pnpm, WorkspaceKind::Npm, WorkspaceKind::Nx]);
+    assert_eq!(member_names(&workspaces[0]), vec!["@acme/ui"]);
+    assert_eq!(member_names(&workspaces[1]), vec!["guide"]);
+    assert_eq!(workspaces[1].members[0].root, PathBuf::from("site/docs/guide"));
+    assert_eq!(member_names(&workspaces[2]), vec!["shared", "web"]);
+    
+    let layout = ProjectLayout::detect(temp_dir.path(), &manifests);
+    let affected = layout.affected_packages(["packages/ui/src/Button.tsx", "apps/web/src/main.ts", "README.md"].map(Path::new));
+    assert_eq!(affected, vec!["ui", "web"]);
+}
+
+#[test]
+fn test_prompt_lists_affected_packages() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let manifests = write_files(temp_dir.path(), &[
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/api\"]\n"),
+        ("crates/api/Cargo.toml", "[package]\nname = \"api\"\n"),
+    ]);
+    let layout = ProjectLayout::detect(temp_dir.path(), &manifests);
+    let git_info = GitInfo::from_patch(PATCH).unwrap();
+    let prompt_builder = PromptBuilder::new(10, 50).with_layout(layout);
+    
+    // When
+    let prompt = prompt_builder.build(&git_info);
+    
+    // Then
+    assert!(prompt.contains("\nAffected packages: api\n"), "{}", prompt);
+    assert_eq!(prompt_builder.affected_packages(&git_info), vec!["api"]);
+    
+    let excluding = prompt_builder.clone().with_excluded_paths(PathFilter::new(&["crates/api/".to_string()]));
+    assert!(excluding.affected_packages(&git_info).is_empty());
+    
+    let prompt = prompt_builder
+        .with_context_blocks(ContextBlocks { packages: false, ..ContextBlocks::default() })
+        .build(&git_info);
+    assert!(!prompt.contains("Affected packages"), "{}", prompt);
+}
+
+#[test]
+fn test_package_scope_is_enforced_for_a_single_package() {
+    // Given
+    let packages = vec!["api".to_string()];
+    let rules = ValidationConfig { package_scope: true, ..ValidationConfig::default() };
+    let git_info = GitInfo::from_patch(PATCH).unwrap();
+    
+    // When
+    let scoped = Candidate::for_changes("m", "feat(api): log in on every route", &rules, &git_info)
+        .with_affected_packages(&packages, &rules);
+    let misscoped = Candidate::for_changes("m", "feat(routes): log in on every route", &rules, &git_info)
+        .with_affected_packages(&packages, &rules);
+    let unenforced = Candidate::for_changes("m", "feat: log in on every route", &ValidationConfig::default(), &git_info)
+        .with_affected_packages(&packages, &ValidationConfig::default());
+    
+    // Then
+    assert!(scoped.violations.is_empty(), "{:?}", scoped.violations);
+    assert_eq!(misscoped.violations, vec![Violation::PackageScope {
+        expected: "api".to_string(),
+        found: Some("routes".to_string()),
+    }]);
+    assert_eq!(misscoped.violations[0].describe(), "only package 'api' changed; use it as the scope instead of 'routes'");
+    assert!(unenforced.violations.is_empty());
+    assert_eq!(
+        check_package_scope("Log in on every route", &packages).map(|violation| violation.describe()),
+        Some("only package 'api' changed; use it as the scope, as in 'feat(api): ...'".to_string())
+    );
+    assert_eq!(check_package_scope("feat: log in", &["api".to_string(), "ui".to_string()]), None);
+    assert_eq!(check_package_scope("feat: log in", &[]), None);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::git;
+use clap::Parser;
+use git_ai_commit::cli::Args;
+use git_ai_commit::config::repo_config_path;
+use git_ai_commit::git::{is_git_repository, GitCollector};
+use std::path::Path;
+use tempfile::tempdir;
+
+/// A repository at `main/` with a linked worktree on branch `hotfix` at `hotfix/`
+fn with_worktree(root: &Path) {
+    std::fs::create_dir(root.join("main")).unwrap();
+    git(&root.join("main"), &["init"]);
+    std::fs::write(root.join("main/lib.rs"), "pub fn run() {}\n").unwrap();
+    git(&root.join("main"), &["add", "."]);
+    git(&root.join("main"), &["commit", "-m", "init"]);
+    git(&root.join("main"), &["worktree", "add", "-b", "hotfix", "../hotfix"]);
+}
+
+#[tokio::test]
+async fn test_git_files_count_as_repositories() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    with_worktree(root);
+    // Submodules point at their git directory the same way
+    std::fs::create_dir(root.join("module")).unwrap();
+    git(&root.join("module"), &["init", "--separate-git-dir", "../module.git"]);
+    std::fs::create_dir(root.join("plain")).unwrap();
+    
+    // Then
+    assert!(root.join("hotfix/.git").is_file());
+    assert!(root.join("module/.git").is_file());
+    assert!(is_git_repository(&root.join("hotfix")).await.unwrap());
+    assert!(is_git_repository(&root.join("module")).await.unwrap());
+    assert!(is_git_repository(&root.join("main")).await.unwrap());
+    
+    // No work tree to commit to
+    assert!(!is_git_repository(&root.join("main/.git")).await.unwrap());
+    assert!(!is_git_repository(&root.join("module.git")).await.unwrap());
+    assert!(!is_git_repository(&root.join("plain")).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_worktree_changes_are_collected_from_the_worktree() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    with_worktree(root);
+    std::fs::write(root.join("hotfix/lib.rs"), "pub fn run() { fix(); }\n").unwrap();
+    git(&root.join("hotfix"), &["add", "lib.rs"]);
+    let git_collector = GitCollector::new(root.join("hotfix"));
+    
+    // When
+    let git_info = git_collector.collect_all().await.unwrap();
+    let git_dir = git_collector.git_dir().await.unwrap();
+    
+    // Then
+    assert_eq!(git_info.branch_name, "hotfix");
+    assert_eq!(git_info.status.staged_files.len(), 1);
+    assert!(git_dir.ends_with(".git/worktrees/hotfix"), "{}", git_dir.display());
+    
+    let main_info = GitCollector::new(root.join("main")).collect_all().await.unwrap();
+    assert!(main_info.status.staged_files.is_empty());
+}
+
+#[test]
+fn test_repo_config_is_read_from_the_worktree_root() {
+    // Given
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    with_worktree(root);
+    std::fs::create_dir(root.join("hotfix/src")).unwrap();
+    std::fs::write(root.join("hotfix/.git-ai-commit.toml"), "max_files = 3\n").unwrap();
+    
+    // When
+    let path = repo_config_path(&root.join("hotfix/src"));
+    
+    // Then
+    assert_eq!(path, Some(root.join("hotfix/.git-ai-commit.toml")));
+    assert_eq!(repo_config_path(&root.join("main")), None);
+}
+
+#[test]
+fn test_repo_flag_is_resolved_like_git_c() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let repo = temp_dir.path().to_str().unwrap();
+    
+    let args = Args::try_parse_from(["git-ai-commit", "-C", repo, "--dry-run"]).unwrap();
+    assert_eq!(args.repo_dir().unwrap(), temp_dir.path());
+    let args = Args::try_parse_from(["git-ai-commit", "doctor", "--repo", repo]).unwrap();
+    assert_eq!(args.repo_dir().unwrap(), temp_dir.path());
+    
+    let args = Args::try_parse_from(["git-ai-commit", "-C", "."]).unwrap();
+    assert_eq!(args.repo_dir().unwrap(), std::env::current_dir().unwrap().join("."));
+    let args = Args::try_parse_from(["git-ai-commit"]).unwrap();
+    assert_eq!(args.repo_dir().unwrap(), std::env::current_dir().unwrap());
+    
+    let missing = temp_dir.path().join("missing");
+    let args = Args::try_parse_from(["git-ai-commit", "-C", missing.to_str().unwrap()]).unwrap();
+    assert!(args.repo_dir().unwrap_err().to_string().contains("no such directory"));
+}